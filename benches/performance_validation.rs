@@ -1,7 +1,9 @@
 use criterion::{
     criterion_group, criterion_main, AxisScale, BenchmarkId, Criterion, PlotConfiguration,
 };
+use embeddenator_testkit::{random_sparse_vec, VecPool};
 use embeddenator_vsa::{ReversibleVSAConfig, SparseVec};
+use rand::SeedableRng;
 use std::hint::black_box;
 
 /// Comprehensive performance validation benchmark
@@ -134,10 +136,45 @@ fn bench_scalability(c: &mut Criterion) {
     group.finish();
 }
 
+/// Generator allocation overhead
+///
+/// Compares fresh allocations against buffers recycled through `VecPool`;
+/// both paths produce identical vectors for the same seed.
+fn bench_generator_pooling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generator_pooling");
+
+    for sparsity in [200, 2_000] {
+        group.bench_with_input(
+            BenchmarkId::new("allocating", sparsity),
+            &sparsity,
+            |bencher, &sparsity| {
+                let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+                bencher.iter(|| black_box(random_sparse_vec(&mut rng, 10_000, sparsity)))
+            },
+        );
+
+        let pool = VecPool::new();
+        group.bench_with_input(
+            BenchmarkId::new("pooled", sparsity),
+            &sparsity,
+            |bencher, &sparsity| {
+                let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+                bencher.iter(|| {
+                    let v = pool.random_sparse_vec(&mut rng, 10_000, sparsity);
+                    black_box(v.pos.len() + v.neg.len())
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_vsa_operations_optimized,
     bench_memory_efficiency,
-    bench_scalability
+    bench_scalability,
+    bench_generator_pooling
 );
 criterion_main!(benches);
@@ -0,0 +1,20 @@
+//! Records the compiler version and target triple for `EnvironmentInfo`
+
+use std::process::Command;
+
+fn main() {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    if let Ok(output) = Command::new(rustc).arg("--version").output() {
+        if output.status.success() {
+            println!(
+                "cargo:rustc-env=TESTKIT_RUSTC_VERSION={}",
+                String::from_utf8_lossy(&output.stdout).trim()
+            );
+        }
+    }
+    println!(
+        "cargo:rustc-env=TESTKIT_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,76 @@
+//! Cooperative cancellation for long-running generators and runners
+//!
+//! A [`CancellationToken`] is checked at file, chunk or phase boundaries, so
+//! cancelling never leaves a half-written file behind. Operations that see
+//! the token cancelled stop early and return what they finished so far,
+//! marked as cancelled, instead of an error.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Shared flag asking an operation to stop at its next safe point
+///
+/// Clones share the flag, so a token handed to a runner can be cancelled
+/// from another thread or a signal handler.
+///
+/// # Example
+/// ```rust,ignore
+/// let token = CancellationToken::new();
+/// token.cancel_on_ctrl_c()?;
+/// let options = DatasetOptions::new().with_cancellation(token);
+/// let dataset = harness.try_create_dataset_seeded_with(2048, 7, &options)?;
+/// if dataset.outcome.is_cancelled() {
+///     eprintln!("cancelled after {} files", dataset.files);
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    flag: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every holder of this token to stop
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called on any clone
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+
+    /// The underlying flag, for APIs that predate the token
+    pub(crate) fn flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.flag)
+    }
+
+    /// Cancel this token when the process receives ctrl-c (or SIGTERM)
+    ///
+    /// The `ctrlc` crate allows one handler per process, so this fails if a
+    /// handler is already installed.
+    #[cfg(feature = "ctrlc")]
+    pub fn cancel_on_ctrl_c(&self) -> std::result::Result<(), ctrlc::Error> {
+        let token = self.clone();
+        ctrlc::set_handler(move || token.cancel())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        std::thread::spawn(move || token.cancel()).join().unwrap();
+        assert!(clone.is_cancelled());
+        assert!(clone.flag().load(Ordering::SeqCst));
+    }
+}
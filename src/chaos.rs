@@ -2,16 +2,48 @@
 //!
 //! Provides tools for testing system resilience:
 //! - Random bitflip injection on byte data
-//! - Packet loss simulation
-//! - Corruption simulation
+//! - Packet loss, duplication and reordering simulation
+//! - Corruption simulation (bitflips, truncation, garbage, splicing)
 //! - Noise tolerance testing
+//! - Memory pressure simulation
+//! - Background CPU and I/O load
+//! - Seeded latency and jitter injection
+//! - Dependencies that fail on a reproducible schedule ([`Flaky`])
+
+use crate::error::{IoResultExt, Result};
+use crate::events;
+use crate::integrity::MismatchKind;
+use crate::metrics::MemoryProbe;
+use embeddenator_vsa::{ReversibleVSAConfig, SparseVec};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 /// Chaos injection utilities for resilience testing
+///
+/// Injection decisions ([`should_inject`](Self::should_inject),
+/// [`maybe`](Self::maybe)) draw from a seeded stream held in an atomic, so
+/// they take `&self` and can be mixed freely with the injection methods.
+/// The stream advances once per decision and is independent of the
+/// injections themselves, so two injectors with the same seed fire on the
+/// same decisions. The injector can be shared across threads; every
+/// decision then still consumes exactly one step of the stream, but which
+/// thread gets which step depends on scheduling.
 pub struct ChaosInjector {
     /// Random seed for reproducibility
     seed: u64,
     /// Injection probability (0.0 - 1.0)
     probability: f64,
+    /// LCG state for injection decisions
+    decisions: AtomicU64,
 }
 
 impl ChaosInjector {
@@ -20,6 +52,7 @@ impl ChaosInjector {
         Self {
             seed,
             probability: 0.01, // 1% default
+            decisions: AtomicU64::new(seed ^ 0x9E37_79B9_7F4A_7C15),
         }
     }
 
@@ -29,6 +62,44 @@ impl ChaosInjector {
         self
     }
 
+    /// Configured injection probability
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    /// Decide whether to inject on this operation
+    ///
+    /// Returns `true` with the configured probability and advances the
+    /// decision stream by one step.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let chaos = ChaosInjector::new(7).with_probability(0.05);
+    /// for chunk in chunks.iter_mut() {
+    ///     if chaos.should_inject() {
+    ///         chaos.corrupt_bytes(chunk, 0.01);
+    ///     }
+    /// }
+    /// ```
+    pub fn should_inject(&self) -> bool {
+        let step = |s: u64| s.wrapping_mul(6364136223846793005).wrapping_add(1);
+        let previous = self
+            .decisions
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |s| Some(step(s)))
+            .unwrap_or_else(|s| s);
+        let state = step(previous);
+        // Top 53 bits as a uniform value in [0, 1)
+        let draw = (state >> 11) as f64 / (1u64 << 53) as f64;
+        draw < self.probability
+    }
+
+    /// Run `f` with the configured probability
+    ///
+    /// Returns `Some` with the closure's result when the decision fires.
+    pub fn maybe<T>(&self, f: impl FnOnce() -> T) -> Option<T> {
+        self.should_inject().then(f)
+    }
+
     /// Inject random noise into byte data
     ///
     /// # Arguments
@@ -44,6 +115,15 @@ impl ChaosInjector {
             let bit = (state >> 8) % 8;
             data[pos] ^= 1u8 << bit;
         }
+
+        events::emit("chaos", "corrupt_bytes", || {
+            serde_json::json!({
+                "seed": self.seed,
+                "len": data.len(),
+                "error_rate": error_rate,
+                "flips": num_errors,
+            })
+        });
     }
 
     /// Create corrupted copy of byte data
@@ -55,30 +135,93 @@ impl ChaosInjector {
 
     /// Simulate packet loss by erasing random chunks
     ///
+    /// Same as [`simulate_packet_loss_into`](Self::simulate_packet_loss_into)
+    /// with [`LossMode::ZeroFill`], in place.
+    ///
     /// # Arguments
     /// * `data` - Data to corrupt (modified in place)
     /// * `loss_rate` - Fraction of packets to drop (0.0-1.0)
     /// * `packet_size` - Size of each packet in bytes
     pub fn simulate_packet_loss(&self, data: &mut [u8], loss_rate: f64, packet_size: usize) {
-        use std::collections::HashSet;
+        let dropped = self.dropped_packets(data.len(), loss_rate, packet_size, LossMode::ZeroFill);
+        for &packet_idx in &dropped {
+            let start = packet_idx * packet_size;
+            let end = (start + packet_size).min(data.len());
+            data[start..end].fill(0);
+        }
+    }
+
+    /// Copy of `data` with random packets lost according to `mode`
+    ///
+    /// Returns the transformed data and the sorted indices of the dropped
+    /// packets, counted in `packet_size` units of the input. The last
+    /// packet may be shorter than `packet_size`; with [`LossMode::Remove`]
+    /// dropping it removes only the bytes it has. The same seed drops the
+    /// same packets in every mode.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let (received, dropped) =
+    ///     chaos.simulate_packet_loss_into(&stream, 0.1, 1500, LossMode::Remove);
+    /// for packet in dropped {
+    ///     assert!(decoder.recover(packet).is_ok());
+    /// }
+    /// ```
+    pub fn simulate_packet_loss_into(
+        &self,
+        data: &[u8],
+        loss_rate: f64,
+        packet_size: usize,
+        mode: LossMode,
+    ) -> (Vec<u8>, Vec<usize>) {
+        let dropped = self.dropped_packets(data.len(), loss_rate, packet_size, mode);
+        let mut lost = dropped.iter().peekable();
+        let mut out = Vec::with_capacity(data.len());
+        for (packet_idx, packet) in data.chunks(packet_size).enumerate() {
+            if lost.next_if_eq(&&packet_idx).is_none() {
+                out.extend_from_slice(packet);
+                continue;
+            }
+            match mode {
+                LossMode::ZeroFill => out.resize(out.len() + packet.len(), 0),
+                LossMode::MarkerFill(marker) => out.resize(out.len() + packet.len(), marker),
+                LossMode::Remove => {}
+            }
+        }
+        (out, dropped)
+    }
 
-        let num_packets = data.len().div_ceil(packet_size);
+    /// Sorted indices of the packets lost from `len` bytes
+    fn dropped_packets(
+        &self,
+        len: usize,
+        loss_rate: f64,
+        packet_size: usize,
+        mode: LossMode,
+    ) -> Vec<usize> {
+        let num_packets = len.div_ceil(packet_size);
         let packets_to_drop = ((num_packets as f64) * loss_rate) as usize;
 
         let mut state = self.seed;
-        let mut dropped = HashSet::new();
-
+        let mut dropped = Vec::with_capacity(packets_to_drop);
         for _ in 0..packets_to_drop {
             state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
-            let packet_idx = (state as usize) % num_packets;
-            dropped.insert(packet_idx);
+            dropped.push((state as usize) % num_packets);
         }
+        dropped.sort_unstable();
+        dropped.dedup();
 
-        for packet_idx in dropped {
-            let start = packet_idx * packet_size;
-            let end = (start + packet_size).min(data.len());
-            data[start..end].fill(0);
-        }
+        events::emit("chaos", "packet_loss", || {
+            serde_json::json!({
+                "seed": self.seed,
+                "len": len,
+                "loss_rate": loss_rate,
+                "packet_size": packet_size,
+                "mode": format!("{:?}", mode),
+                "dropped": dropped.len(),
+            })
+        });
+        dropped
     }
 
     /// Inject random erasures (zero out bytes)
@@ -96,8 +239,458 @@ impl ChaosInjector {
             }
         }
 
+        events::emit("chaos", "erasures", || {
+            serde_json::json!({
+                "seed": self.seed,
+                "requested": count,
+                "erased": erased.len(),
+            })
+        });
         erased
     }
+
+    /// Duplicate random chunks, as a network might redeliver packets
+    ///
+    /// Each selected chunk is emitted twice in a row, so the output grows by
+    /// the length of every duplicated chunk (the final chunk may be partial).
+    /// Returns the new data and the sorted indices of duplicated chunks.
+    ///
+    /// # Arguments
+    /// * `data` - Original data (left untouched)
+    /// * `dup_rate` - Fraction of chunks to duplicate (0.0-1.0)
+    /// * `chunk_size` - Size of each chunk in bytes
+    pub fn duplicate_chunks(
+        &self,
+        data: &[u8],
+        dup_rate: f64,
+        chunk_size: usize,
+    ) -> (Vec<u8>, Vec<usize>) {
+        use std::collections::BTreeSet;
+
+        if data.is_empty() || chunk_size == 0 {
+            return (data.to_vec(), Vec::new());
+        }
+
+        let num_chunks = data.len().div_ceil(chunk_size);
+        let to_duplicate = ((num_chunks as f64) * dup_rate.clamp(0.0, 1.0)) as usize;
+
+        let mut state = self.seed.wrapping_add(23456);
+        let mut duplicated = BTreeSet::new();
+        for _ in 0..to_duplicate {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            duplicated.insert((state >> 33) as usize % num_chunks);
+        }
+
+        let mut out = Vec::with_capacity(data.len() + duplicated.len() * chunk_size);
+        for (index, chunk) in data.chunks(chunk_size).enumerate() {
+            out.extend_from_slice(chunk);
+            if duplicated.contains(&index) {
+                out.extend_from_slice(chunk);
+            }
+        }
+
+        let duplicated: Vec<usize> = duplicated.into_iter().collect();
+        events::emit("chaos", "duplicate_chunks", || {
+            serde_json::json!({
+                "seed": self.seed,
+                "len": data.len(),
+                "chunk_size": chunk_size,
+                "duplicated": duplicated.len(),
+                "out_len": out.len(),
+            })
+        });
+        (out, duplicated)
+    }
+
+    /// Reorder data by swapping the contents of random chunk pairs in place
+    ///
+    /// Only full chunks take part; a partial chunk at the tail never moves.
+    /// The swapped pairs are disjoint, so replaying the returned list with
+    /// [`swap_chunks`](Self::swap_chunks) restores the original data. At
+    /// most `len / chunk_size / 2` swaps are made.
+    pub fn reorder_chunks(
+        &self,
+        data: &mut [u8],
+        swap_count: usize,
+        chunk_size: usize,
+    ) -> Vec<(usize, usize)> {
+        let full_chunks = data.len().checked_div(chunk_size).unwrap_or(0);
+        let swaps = swap_count.min(full_chunks / 2);
+
+        // Partial Fisher-Yates: the first 2 * swaps slots become the pairs
+        let mut order: Vec<usize> = (0..full_chunks).collect();
+        let mut state = self.seed.wrapping_add(34567);
+        for i in 0..swaps * 2 {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let j = i + (state >> 33) as usize % (full_chunks - i);
+            order.swap(i, j);
+        }
+        let pairs: Vec<(usize, usize)> = order[..swaps * 2]
+            .chunks(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+
+        Self::swap_chunks(data, &pairs, chunk_size);
+
+        events::emit("chaos", "reorder_chunks", || {
+            serde_json::json!({
+                "seed": self.seed,
+                "len": data.len(),
+                "chunk_size": chunk_size,
+                "requested": swap_count,
+                "swapped": pairs.len(),
+            })
+        });
+        pairs
+    }
+
+    /// Swap the contents of each `(a, b)` chunk pair in `data`, in order
+    ///
+    /// Pairs that fall outside the full chunks of `data` are ignored.
+    pub fn swap_chunks(data: &mut [u8], swaps: &[(usize, usize)], chunk_size: usize) {
+        let full_chunks = data.len().checked_div(chunk_size).unwrap_or(0);
+        for &(a, b) in swaps {
+            if a == b || a >= full_chunks || b >= full_chunks {
+                continue;
+            }
+            let (lo, hi) = (a.min(b), a.max(b));
+            let (left, right) = data.split_at_mut(hi * chunk_size);
+            left[lo * chunk_size..(lo + 1) * chunk_size].swap_with_slice(&mut right[..chunk_size]);
+        }
+    }
+
+    /// Flip bits in a seeded subset of the files under `dir`
+    ///
+    /// Panicking wrapper around [`try_corrupt_directory`](Self::try_corrupt_directory).
+    pub fn corrupt_directory(
+        &self,
+        dir: &Path,
+        file_fraction: f64,
+        per_file_error_rate: f64,
+    ) -> CorruptionManifest {
+        self.try_corrupt_directory(dir, file_fraction, per_file_error_rate)
+            .unwrap_or_else(|e| panic!("Failed to corrupt directory: {}", e))
+    }
+
+    /// Flip bits in a seeded subset of the files under `dir`
+    ///
+    /// Regular non-empty files are walked in sorted order and
+    /// `file_fraction` of them (rounded) are picked with the injector's seed.
+    /// Each picked file gets `len * per_file_error_rate` distinct bit flips
+    /// (at least one). Flips are applied one 64 KiB block at a time: each
+    /// block holding a flip is read once and written back once, so large
+    /// files are never read whole. Sizes are unchanged, so every corrupted file shows
+    /// up as a [`MismatchKind::Content`] mismatch against a prior manifest.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`](crate::Error::Io) for the file or directory that
+    /// could not be read or written; files corrupted before the failure stay
+    /// corrupted.
+    pub fn try_corrupt_directory(
+        &self,
+        dir: &Path,
+        file_fraction: f64,
+        per_file_error_rate: f64,
+    ) -> Result<CorruptionManifest> {
+        let mut candidates = Vec::new();
+        for (rel, path) in crate::integrity::collect_files(dir).at_path(dir)? {
+            let len = fs::metadata(&path).at_path(&path)?.len();
+            if len > 0 {
+                candidates.push((rel, path, len));
+            }
+        }
+
+        let count = ((candidates.len() as f64) * file_fraction.clamp(0.0, 1.0)).round() as usize;
+        let mut state = self.seed.wrapping_add(67890);
+        for i in 0..count {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let j = i + (state >> 33) as usize % (candidates.len() - i);
+            candidates.swap(i, j);
+        }
+        candidates.truncate(count);
+        candidates.sort();
+
+        let mut files = Vec::with_capacity(count);
+        for (rel, path, len) in candidates {
+            let bits = len.saturating_mul(8);
+            let wanted = (((len as f64) * per_file_error_rate) as u64).clamp(1, bits);
+
+            let mut chosen = std::collections::BTreeSet::new();
+            while (chosen.len() as u64) < wanted {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                chosen.insert((state >> 16) % bits);
+            }
+            let flips: Vec<BitFlip> = chosen
+                .into_iter()
+                .map(|bit| BitFlip {
+                    offset: bit / 8,
+                    bit: (bit % 8) as u8,
+                })
+                .collect();
+
+            let mut file = fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .at_path(&path)?;
+            let mut block = Vec::new();
+            for group in flips.chunk_by(|a, b| a.offset / CORRUPT_BLOCK == b.offset / CORRUPT_BLOCK)
+            {
+                let start = group[0].offset / CORRUPT_BLOCK * CORRUPT_BLOCK;
+                block.resize((len - start).min(CORRUPT_BLOCK) as usize, 0);
+                file.seek(SeekFrom::Start(start)).at_path(&path)?;
+                file.read_exact(&mut block).at_path(&path)?;
+                for flip in group {
+                    block[(flip.offset - start) as usize] ^= 1u8 << flip.bit;
+                }
+                file.seek(SeekFrom::Start(start)).at_path(&path)?;
+                file.write_all(&block).at_path(&path)?;
+            }
+            file.flush().at_path(&path)?;
+
+            files.push(CorruptedFile { path: rel, flips });
+        }
+
+        events::emit("chaos", "corrupt_directory", || {
+            serde_json::json!({
+                "seed": self.seed,
+                "path": dir.display().to_string(),
+                "files": files.len(),
+                "flips": files.iter().map(|f| f.flips.len()).sum::<usize>(),
+            })
+        });
+        Ok(CorruptionManifest {
+            seed: self.seed,
+            files,
+        })
+    }
+
+    /// Copy of `data` with its tail cut off, simulating a short read
+    ///
+    /// `amount` is how much to remove: a byte count (`usize`) or a fraction
+    /// of the length (`f64`, rounded down). Removing more than `data` holds
+    /// yields an empty buffer.
+    pub fn truncate(&self, data: &[u8], amount: impl Into<TruncateAmount>) -> Vec<u8> {
+        let removed = amount.into().resolve(data.len());
+        let kept = data.len() - removed;
+        events::emit(
+            "chaos",
+            "truncate",
+            || serde_json::json!({ "len": data.len(), "removed": removed }),
+        );
+        data[..kept].to_vec()
+    }
+
+    /// Copy of `data` followed by `extra_bytes` of seeded garbage
+    pub fn extend_with_garbage(&self, data: &[u8], extra_bytes: usize) -> Vec<u8> {
+        let mut out = Vec::with_capacity(data.len() + extra_bytes);
+        out.extend_from_slice(data);
+        let mut state = self.seed.wrapping_add(45678);
+        out.extend((0..extra_bytes).map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (state >> 33) as u8
+        }));
+        events::emit(
+            "chaos",
+            "extend_with_garbage",
+            || serde_json::json!({ "seed": self.seed, "len": data.len(), "extra": extra_bytes }),
+        );
+        out
+    }
+
+    /// Interleave segments of `a` and `b` at seeded cut positions
+    ///
+    /// Up to `cut_points` distinct offsets are drawn below the shorter
+    /// buffer's length. The output takes `a` up to the first cut, `b` up to
+    /// the next, and so on, with the last segment running to the end of its
+    /// source. Returns the output and the segments it was assembled from.
+    pub fn splice(&self, a: &[u8], b: &[u8], cut_points: usize) -> (Vec<u8>, Vec<SpliceSegment>) {
+        use std::collections::BTreeSet;
+
+        let shared = a.len().min(b.len());
+        let wanted = cut_points.min(shared.saturating_sub(1));
+        let mut state = self.seed.wrapping_add(56789);
+        let mut cuts = BTreeSet::new();
+        while cuts.len() < wanted {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            cuts.insert(1 + (state >> 33) as usize % (shared - 1));
+        }
+
+        let mut segments = Vec::with_capacity(cuts.len() + 1);
+        let mut start = 0;
+        for (i, cut) in cuts.iter().copied().chain([usize::MAX]).enumerate() {
+            let source = if i % 2 == 0 {
+                SpliceSource::A
+            } else {
+                SpliceSource::B
+            };
+            let len = match source {
+                SpliceSource::A => a.len(),
+                SpliceSource::B => b.len(),
+            };
+            segments.push(SpliceSegment {
+                source,
+                start,
+                end: cut.min(len),
+            });
+            start = cut;
+        }
+
+        let mut out = Vec::new();
+        for segment in &segments {
+            let source = match segment.source {
+                SpliceSource::A => a,
+                SpliceSource::B => b,
+            };
+            out.extend_from_slice(&source[segment.start..segment.end]);
+        }
+
+        events::emit("chaos", "splice", || {
+            serde_json::json!({
+                "seed": self.seed,
+                "a_len": a.len(),
+                "b_len": b.len(),
+                "cuts": cuts.len(),
+                "out_len": out.len(),
+            })
+        });
+        (out, segments)
+    }
+}
+
+/// Block size [`ChaosInjector::try_corrupt_directory`] reads and rewrites
+const CORRUPT_BLOCK: u64 = 64 * 1024;
+
+/// A single bit flipped by [`ChaosInjector::corrupt_directory`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BitFlip {
+    /// Byte offset in the file
+    pub offset: u64,
+    /// Bit index within the byte (0 = least significant)
+    pub bit: u8,
+}
+
+/// One file corrupted by [`ChaosInjector::corrupt_directory`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorruptedFile {
+    /// Path relative to the corrupted directory, `/`-separated
+    pub path: String,
+    /// Flipped bits, sorted by offset; no bit is flipped twice
+    pub flips: Vec<BitFlip>,
+}
+
+/// Record of the files [`ChaosInjector::corrupt_directory`] changed
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorruptionManifest {
+    /// Seed of the injector that made the changes
+    pub seed: u64,
+    /// Corrupted files, sorted by path
+    pub files: Vec<CorruptedFile>,
+}
+
+impl CorruptionManifest {
+    /// Relative paths of the corrupted files
+    pub fn paths(&self) -> Vec<&str> {
+        self.files.iter().map(|f| f.path.as_str()).collect()
+    }
+
+    /// Total number of flipped bits
+    pub fn total_flips(&self) -> usize {
+        self.files.iter().map(|f| f.flips.len()).sum()
+    }
+
+    /// Mismatches a before/after [`ChecksumManifest`](crate::ChecksumManifest)
+    /// diff should report, sorted by path
+    pub fn expected_mismatches(&self) -> Vec<(String, MismatchKind)> {
+        self.files
+            .iter()
+            .map(|f| (f.path.clone(), MismatchKind::Content))
+            .collect()
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parse from JSON produced by [`to_json`](Self::to_json)
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// What [`ChaosInjector::simulate_packet_loss_into`] does with a lost packet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LossMode {
+    /// Overwrite it with zeros, keeping the length (storage corruption)
+    #[default]
+    ZeroFill,
+    /// Cut it out, shrinking the output (stream truncation)
+    Remove,
+    /// Overwrite it with the given byte, keeping the length
+    MarkerFill(u8),
+}
+
+/// How much [`ChaosInjector::truncate`] removes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TruncateAmount {
+    /// Exact number of bytes
+    Bytes(usize),
+    /// Fraction of the input length (0.0-1.0, rounded down)
+    Fraction(f64),
+}
+
+impl TruncateAmount {
+    fn resolve(self, len: usize) -> usize {
+        match self {
+            TruncateAmount::Bytes(n) => n.min(len),
+            TruncateAmount::Fraction(f) => (f.clamp(0.0, 1.0) * len as f64) as usize,
+        }
+    }
+}
+
+impl From<usize> for TruncateAmount {
+    fn from(n: usize) -> Self {
+        TruncateAmount::Bytes(n)
+    }
+}
+
+impl From<f64> for TruncateAmount {
+    fn from(fraction: f64) -> Self {
+        TruncateAmount::Fraction(fraction)
+    }
+}
+
+/// Which input of [`ChaosInjector::splice`] a segment came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpliceSource {
+    /// The first buffer
+    A,
+    /// The second buffer
+    B,
+}
+
+/// One segment of a splice: `source[start..end]`, in output order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpliceSegment {
+    /// Buffer the bytes were taken from
+    pub source: SpliceSource,
+    /// Start offset in the source buffer
+    pub start: usize,
+    /// End offset (exclusive) in the source buffer
+    pub end: usize,
+}
+
+impl SpliceSegment {
+    /// Number of bytes in the segment
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the segment is empty
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
 }
 
 impl Default for ChaosInjector {
@@ -106,6 +699,766 @@ impl Default for ChaosInjector {
     }
 }
 
+/// Delay distribution sampled by [`LatencyInjector`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DelayDistribution {
+    /// Always the same delay
+    Fixed(Duration),
+    /// Uniform over `min..=max`
+    Uniform { min: Duration, max: Duration },
+    /// Exponential with the given mean (memoryless, long-tailed)
+    Exponential { mean: Duration },
+    /// `base` on most calls, `spike_duration` on every `spike_every_n`th call
+    Spike {
+        base: Duration,
+        spike_every_n: u64,
+        spike_duration: Duration,
+    },
+}
+
+/// Reproducible artificial latency for timeout and backpressure tests
+///
+/// Delays are drawn from a seeded stream, so the same seed and distribution
+/// give the same sequence. In [virtual mode](Self::virtual_mode) nothing
+/// sleeps and the delays are only recorded, which keeps unit tests fast.
+///
+/// # Example
+/// ```rust,ignore
+/// let mut latency = LatencyInjector::new(
+///     3,
+///     DelayDistribution::Exponential { mean: Duration::from_millis(5) },
+/// );
+/// let reply = latency.wrap(|| client.fetch(key));
+/// ```
+pub struct LatencyInjector {
+    distribution: DelayDistribution,
+    rng: StdRng,
+    calls: u64,
+    virtual_mode: bool,
+    injected: Vec<Duration>,
+}
+
+impl LatencyInjector {
+    /// Create an injector sampling `distribution` with `seed`
+    pub fn new(seed: u64, distribution: DelayDistribution) -> Self {
+        Self {
+            distribution,
+            rng: StdRng::seed_from_u64(seed),
+            calls: 0,
+            virtual_mode: false,
+            injected: Vec::new(),
+        }
+    }
+
+    /// Record delays without sleeping
+    pub fn virtual_mode(mut self) -> Self {
+        self.virtual_mode = true;
+        self
+    }
+
+    /// Whether delays are only recorded
+    pub fn is_virtual(&self) -> bool {
+        self.virtual_mode
+    }
+
+    /// Draw the next delay without sleeping or recording it
+    pub fn sample(&mut self) -> Duration {
+        self.calls += 1;
+        match self.distribution {
+            DelayDistribution::Fixed(delay) => delay,
+            DelayDistribution::Uniform { min, max } => {
+                let (lo, hi) = (min.as_nanos() as u64, max.as_nanos() as u64);
+                Duration::from_nanos(self.rng.random_range(lo.min(hi)..=hi.max(lo)))
+            }
+            DelayDistribution::Exponential { mean } => {
+                let u: f64 = 1.0 - self.rng.random::<f64>();
+                mean.mul_f64(-u.ln())
+            }
+            DelayDistribution::Spike {
+                base,
+                spike_every_n,
+                spike_duration,
+            } => {
+                if spike_every_n > 0 && self.calls.is_multiple_of(spike_every_n) {
+                    spike_duration
+                } else {
+                    base
+                }
+            }
+        }
+    }
+
+    /// Sleep for the next sampled delay (record only in virtual mode)
+    ///
+    /// Returns the delay that was injected.
+    pub fn delay(&mut self) -> Duration {
+        let delay = self.sample();
+        self.injected.push(delay);
+        if !self.virtual_mode && !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+        delay
+    }
+
+    /// Inject a delay, then call `f`
+    pub fn wrap<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.delay();
+        f()
+    }
+
+    /// Delays injected so far, in order
+    pub fn injected(&self) -> &[Duration] {
+        &self.injected
+    }
+
+    /// Sum of all injected delays
+    pub fn total_injected(&self) -> Duration {
+        self.injected.iter().sum()
+    }
+}
+
+/// When a [`Flaky`] dependency fails, by zero-based invocation index
+#[derive(Clone, Debug, PartialEq)]
+pub enum FailurePattern {
+    /// Each call fails independently with this probability (seeded)
+    Probability(f64),
+    /// Every `n`th call fails (invocations `n-1`, `2n-1`, ...)
+    EveryNth(u64),
+    /// Cycles of `after` successes followed by `burst` failures
+    Burst { after: u64, burst: u64 },
+    /// `true` entries fail; calls past the end succeed
+    Schedule(Vec<bool>),
+}
+
+/// Failure returned by [`Flaky::call`] instead of running the closure
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("injected fault on invocation {invocation}")]
+pub struct InjectedFault {
+    /// Zero-based index of the failed call
+    pub invocation: u64,
+}
+
+/// A dependency that fails on a reproducible schedule
+///
+/// Wraps a value (a client, a closure, a plain counter) and hands it to the
+/// closure given to [`call`](Self::call) unless the [`FailurePattern`] says
+/// this invocation fails. Every decision is logged, so retry logic under test
+/// can be checked call by call.
+///
+/// # Example
+/// ```rust,ignore
+/// let mut store = Flaky::new(client, 7, FailurePattern::EveryNth(3));
+/// let value = retry(3, || store.call(|c| c.get(key)))?;
+/// assert_eq!(store.failed_invocations(), vec![2]);
+/// ```
+pub struct Flaky<T> {
+    inner: T,
+    pattern: FailurePattern,
+    rng: StdRng,
+    log: Vec<bool>,
+}
+
+impl<T> Flaky<T> {
+    /// Wrap `inner`, failing calls according to `pattern`
+    ///
+    /// `seed` only matters for [`FailurePattern::Probability`].
+    pub fn new(inner: T, seed: u64, pattern: FailurePattern) -> Self {
+        Self {
+            inner,
+            pattern,
+            rng: StdRng::seed_from_u64(seed),
+            log: Vec::new(),
+        }
+    }
+
+    /// Run `f` on the wrapped value, or fail this invocation
+    ///
+    /// A failed invocation does not run `f`.
+    pub fn call<F, R>(&mut self, f: F) -> std::result::Result<R, InjectedFault>
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        let invocation = self.log.len() as u64;
+        let fail = self.should_fail(invocation);
+        self.log.push(fail);
+        if fail {
+            Err(InjectedFault { invocation })
+        } else {
+            Ok(f(&mut self.inner))
+        }
+    }
+
+    fn should_fail(&mut self, invocation: u64) -> bool {
+        match &self.pattern {
+            FailurePattern::Probability(p) => self.rng.random::<f64>() < *p,
+            FailurePattern::EveryNth(n) => *n > 0 && (invocation + 1).is_multiple_of(*n),
+            FailurePattern::Burst { after, burst } => {
+                let cycle = after + burst;
+                cycle > 0 && invocation % cycle >= *after
+            }
+            FailurePattern::Schedule(schedule) => usize::try_from(invocation)
+                .ok()
+                .and_then(|i| schedule.get(i))
+                .copied()
+                .unwrap_or(false),
+        }
+    }
+
+    /// Outcome of every call so far, `true` where it was failed
+    pub fn log(&self) -> &[bool] {
+        &self.log
+    }
+
+    /// Indices of the failed calls, in order
+    pub fn failed_invocations(&self) -> Vec<u64> {
+        (0..)
+            .zip(&self.log)
+            .filter_map(|(i, &failed)| failed.then_some(i))
+            .collect()
+    }
+
+    /// Number of calls so far
+    pub fn calls(&self) -> u64 {
+        self.log.len() as u64
+    }
+
+    /// The wrapped value
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwrap the value
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Result of one error rate in a [`NoiseSweepReport`]
+///
+/// `corrupted_bytes` and `similarity` measure noise in the input bytes;
+/// the `flipped_signs` and `decode_*` fields measure noise in the vector
+/// itself. The encoder is reversible, so decoding a re-encoded corrupted
+/// input only returns the corrupted bytes and says nothing about noise
+/// tolerance.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NoisePoint {
+    /// Error rate passed to [`ChaosInjector::corrupt_copy`] and used as the
+    /// sign-flip probability
+    pub error_rate: f64,
+    /// Bytes that differ between the clean and corrupted input
+    pub corrupted_bytes: usize,
+    /// Cosine between the clean encoding and the encoding of the corrupted input
+    pub similarity: f64,
+    /// Non-zeros of the clean encoding whose sign was flipped before decoding
+    pub flipped_signs: usize,
+    /// Whether decoding the sign-flipped encoding reproduced the clean input
+    pub decode_ok: bool,
+    /// Fraction of bytes decoded from the sign-flipped encoding that match
+    /// the clean input
+    pub decode_accuracy: f64,
+}
+
+/// Outcome of [`noise_tolerance_sweep`]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoiseSweepReport {
+    /// Input length in bytes
+    pub data_len: usize,
+    /// One entry per requested error rate, in input order
+    pub points: Vec<NoisePoint>,
+}
+
+impl NoiseSweepReport {
+    /// Highest error rate whose sign-flipped encoding still decoded to the clean input
+    pub fn max_decodable_rate(&self) -> Option<f64> {
+        self.points
+            .iter()
+            .filter(|p| p.decode_ok)
+            .map(|p| p.error_rate)
+            .reduce(f64::max)
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// CSV with one row per error rate
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "error_rate,corrupted_bytes,similarity,flipped_signs,decode_ok,decode_accuracy\n",
+        );
+        for p in &self.points {
+            csv.push_str(&format!(
+                "{},{},{:.6},{},{},{:.6}\n",
+                p.error_rate,
+                p.corrupted_bytes,
+                p.similarity,
+                p.flipped_signs,
+                p.decode_ok,
+                p.decode_accuracy
+            ));
+        }
+        csv
+    }
+}
+
+/// Measure how encoding similarity degrades with input noise and decoding
+/// with vector noise
+///
+/// For each rate, corrupts a copy of `data` with `injector`, re-encodes it
+/// and compares the encoding against the clean one by cosine. Separately,
+/// flips the sign of each non-zero of the clean encoding with probability
+/// `rate` (seeded from the injector) and decodes the result to check how
+/// much of the clean input is still recovered.
+///
+/// # Example
+/// ```rust,ignore
+/// let config = ReversibleVSAConfig::default();
+/// let report = noise_tolerance_sweep(&data, &config, &[0.0, 0.001, 0.01, 0.1], &ChaosInjector::new(7));
+/// println!("{}", report.to_csv());
+/// ```
+pub fn noise_tolerance_sweep(
+    data: &[u8],
+    config: &ReversibleVSAConfig,
+    error_rates: &[f64],
+    injector: &ChaosInjector,
+) -> NoiseSweepReport {
+    let clean = SparseVec::encode_data(data, config, None);
+
+    let points = error_rates
+        .iter()
+        .enumerate()
+        .map(|(i, &error_rate)| {
+            let corrupted = injector.corrupt_copy(data, error_rate);
+            let encoded = SparseVec::encode_data(&corrupted, config, None);
+
+            let mut rng = StdRng::seed_from_u64(injector.seed.wrapping_add(i as u64));
+            let (perturbed, flipped_signs) = flip_signs(&clean, error_rate, &mut rng);
+            let decoded = perturbed.decode_data(config, None, data.len());
+            let matching = decoded
+                .iter()
+                .zip(data.iter())
+                .filter(|(a, b)| a == b)
+                .count();
+
+            NoisePoint {
+                error_rate,
+                corrupted_bytes: corrupted.iter().zip(data).filter(|(a, b)| a != b).count(),
+                similarity: clean.cosine(&encoded),
+                flipped_signs,
+                decode_ok: decoded.len() == data.len() && matching == data.len(),
+                decode_accuracy: if data.is_empty() {
+                    1.0
+                } else {
+                    matching as f64 / data.len() as f64
+                },
+            }
+        })
+        .collect();
+
+    NoiseSweepReport {
+        data_len: data.len(),
+        points,
+    }
+}
+
+/// Copy of `v` with each non-zero's sign flipped with probability `rate`
+///
+/// Returns the copy and the number of flipped entries.
+fn flip_signs(v: &SparseVec, rate: f64, rng: &mut impl Rng) -> (SparseVec, usize) {
+    let rate = rate.clamp(0.0, 1.0);
+    let mut out = SparseVec {
+        pos: Vec::with_capacity(v.pos.len()),
+        neg: Vec::with_capacity(v.neg.len()),
+    };
+    let mut flipped = 0;
+    for &i in &v.pos {
+        if rng.random_bool(rate) {
+            out.neg.push(i);
+            flipped += 1;
+        } else {
+            out.pos.push(i);
+        }
+    }
+    for &i in &v.neg {
+        if rng.random_bool(rate) {
+            out.pos.push(i);
+            flipped += 1;
+        } else {
+            out.neg.push(i);
+        }
+    }
+    out.pos.sort_unstable();
+    out.neg.sort_unstable();
+    (out, flipped)
+}
+
+/// Chunk size used when allocating towards a free-memory target
+const PRESSURE_CHUNK: usize = 64 * 1024 * 1024;
+
+/// Page stride used to fault in freshly allocated memory
+const PAGE_SIZE: usize = 4096;
+
+/// How a [`MemoryPressure`] decides how much to allocate
+enum PressurePlan {
+    /// A fixed number of bytes split into steps with a dwell after each
+    Fixed {
+        bytes: usize,
+        steps: usize,
+        dwell: Duration,
+    },
+    /// Allocate until available memory drops below a fraction of the total
+    UntilFractionFree { fraction: f64, ceiling: usize },
+}
+
+/// Reproducible memory pressure held by a background thread
+///
+/// Memory is allocated and touched page by page so it is really resident,
+/// and is released when the guard is dropped.
+///
+/// # Example
+/// ```rust,ignore
+/// let pressure = MemoryPressure::new(512 * 1024 * 1024);
+/// pressure.wait_until_allocated();
+/// // ... run the workload under pressure ...
+/// drop(pressure); // memory released
+/// ```
+pub struct MemoryPressure {
+    allocated: Arc<AtomicUsize>,
+    settled: Arc<AtomicBool>,
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MemoryPressure {
+    /// Allocate and hold `bytes` in a single step
+    pub fn new(bytes: usize) -> Self {
+        Self::stepped(bytes, 1, Duration::ZERO)
+    }
+
+    /// Allocate `bytes` in `steps` equal increments, pausing `dwell` after each
+    pub fn stepped(bytes: usize, steps: usize, dwell: Duration) -> Self {
+        Self::spawn(PressurePlan::Fixed {
+            bytes,
+            steps: steps.max(1),
+            dwell,
+        })
+    }
+
+    /// Allocate until free memory drops below `fraction` of total memory
+    ///
+    /// Never allocates more than `ceiling` bytes, so a misjudged target
+    /// cannot invoke the OOM killer. Returns `None` when system memory cannot
+    /// be queried on this platform.
+    pub fn until_fraction_free(fraction: f64, ceiling: usize) -> Option<Self> {
+        MemoryProbe::system_memory()?;
+        Some(Self::spawn(PressurePlan::UntilFractionFree {
+            fraction: fraction.clamp(0.0, 1.0),
+            ceiling,
+        }))
+    }
+
+    /// Bytes currently held
+    pub fn allocated(&self) -> usize {
+        self.allocated.load(Ordering::SeqCst)
+    }
+
+    /// True once the allocation plan has finished
+    pub fn is_settled(&self) -> bool {
+        self.settled.load(Ordering::SeqCst)
+    }
+
+    /// Block until the allocation plan has finished
+    pub fn wait_until_allocated(&self) {
+        while !self.is_settled() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn spawn(plan: PressurePlan) -> Self {
+        let allocated = Arc::new(AtomicUsize::new(0));
+        let settled = Arc::new(AtomicBool::new(false));
+        let (stop, stop_rx) = mpsc::channel::<()>();
+
+        let thread_allocated = allocated.clone();
+        let thread_settled = settled.clone();
+        let handle = std::thread::spawn(move || {
+            let mut held: Vec<Vec<u8>> = Vec::new();
+            let mut hold = |bytes: usize| {
+                held.push(touched_buffer(bytes));
+                thread_allocated.fetch_add(bytes, Ordering::SeqCst);
+            };
+
+            match plan {
+                PressurePlan::Fixed {
+                    bytes,
+                    steps,
+                    dwell,
+                } => {
+                    let step = bytes / steps;
+                    for i in 0..steps {
+                        // Last step absorbs the remainder
+                        let size = if i + 1 == steps {
+                            bytes - step * (steps - 1)
+                        } else {
+                            step
+                        };
+                        hold(size);
+                        if !dwell.is_zero() && i + 1 < steps {
+                            if let Ok(()) | Err(RecvTimeoutError::Disconnected) =
+                                stop_rx.recv_timeout(dwell)
+                            {
+                                return;
+                            }
+                        }
+                    }
+                }
+                PressurePlan::UntilFractionFree { fraction, ceiling } => {
+                    let mut total = 0;
+                    while total < ceiling {
+                        let Some(mem) = MemoryProbe::system_memory() else {
+                            break;
+                        };
+                        if mem.available_fraction() <= fraction {
+                            break;
+                        }
+                        let size = PRESSURE_CHUNK.min(ceiling - total);
+                        hold(size);
+                        total += size;
+                    }
+                }
+            }
+
+            thread_settled.store(true, Ordering::SeqCst);
+            // Hold until the guard is dropped
+            let _ = stop_rx.recv();
+        });
+
+        Self {
+            allocated,
+            settled,
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for MemoryPressure {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Allocate `bytes` and write one byte per page so the memory is resident
+fn touched_buffer(bytes: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; bytes];
+    for i in (0..bytes).step_by(PAGE_SIZE) {
+        buf[i] = 1;
+    }
+    std::hint::black_box(buf)
+}
+
+/// Split a duty-cycle period into (busy, idle) durations
+fn duty_split(period: Duration, duty: f64) -> (Duration, Duration) {
+    let busy =
+        Duration::from_nanos((period.as_nanos() as f64 * duty.clamp(0.0, 1.0)).round() as u64);
+    (busy, period.saturating_sub(busy))
+}
+
+/// Busy/idle time accumulated by [`LoadGenerator`] threads
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LoadStats {
+    /// Total time spent spinning
+    pub busy: Duration,
+    /// Total time spent sleeping
+    pub idle: Duration,
+}
+
+impl LoadStats {
+    /// Observed busy fraction (0.0 - 1.0)
+    pub fn duty(&self) -> f64 {
+        let total = (self.busy + self.idle).as_secs_f64();
+        if total == 0.0 {
+            0.0
+        } else {
+            self.busy.as_secs_f64() / total
+        }
+    }
+}
+
+/// Background CPU load with a fixed duty cycle
+///
+/// Each thread spins for `duty * period` then sleeps for the rest of the
+/// period. Threads stop when the guard is dropped.
+///
+/// # Example
+/// ```rust,ignore
+/// let baseline = measure_ingest();
+/// let loaded = {
+///     let _load = LoadGenerator::start(4, 0.7, Duration::from_millis(10));
+///     measure_ingest()
+/// };
+/// metrics.record_metric("throughput_delta", baseline - loaded);
+/// ```
+pub struct LoadGenerator {
+    stop: Arc<AtomicBool>,
+    busy_ns: Arc<AtomicU64>,
+    idle_ns: Arc<AtomicU64>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl LoadGenerator {
+    /// Start `threads` busy-loop threads at `duty` (0.0 - 1.0) per `period`
+    pub fn start(threads: usize, duty: f64, period: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let busy_ns = Arc::new(AtomicU64::new(0));
+        let idle_ns = Arc::new(AtomicU64::new(0));
+        let (busy, idle) = duty_split(period, duty);
+
+        let handles = (0..threads)
+            .map(|_| {
+                let stop = stop.clone();
+                let busy_ns = busy_ns.clone();
+                let idle_ns = idle_ns.clone();
+                std::thread::spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        let spin_start = Instant::now();
+                        while spin_start.elapsed() < busy {
+                            std::hint::spin_loop();
+                        }
+                        busy_ns
+                            .fetch_add(spin_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+
+                        if !idle.is_zero() {
+                            let sleep_start = Instant::now();
+                            std::thread::sleep(idle);
+                            idle_ns.fetch_add(
+                                sleep_start.elapsed().as_nanos() as u64,
+                                Ordering::Relaxed,
+                            );
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            stop,
+            busy_ns,
+            idle_ns,
+            handles,
+        }
+    }
+
+    /// Busy/idle time accumulated so far across all threads
+    pub fn stats(&self) -> LoadStats {
+        LoadStats {
+            busy: Duration::from_nanos(self.busy_ns.load(Ordering::Relaxed)),
+            idle: Duration::from_nanos(self.idle_ns.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Drop for LoadGenerator {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Background sequential writes into a throwaway temp file
+///
+/// Writes are paced to roughly `mb_per_sec` and the file is rewound once it
+/// reaches 64MB so disk usage stays bounded. The file is deleted when the
+/// guard is dropped.
+pub struct IoNoise {
+    stop: Option<Sender<()>>,
+    bytes_written: Arc<AtomicU64>,
+    handle: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl IoNoise {
+    /// Write chunk size
+    const CHUNK: usize = 64 * 1024;
+    /// File size at which writing wraps back to the start
+    const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+    /// Start writing `mb_per_sec` into a temp file under `dir`
+    pub fn start(dir: &Path, mb_per_sec: f64) -> io::Result<Self> {
+        use std::io::Seek;
+
+        let mut file = tempfile::tempfile_in(dir)?;
+        let (stop, stop_rx) = mpsc::channel::<()>();
+        let bytes_written = Arc::new(AtomicU64::new(0));
+        let counter = bytes_written.clone();
+        let chunk_interval =
+            Duration::from_secs_f64(Self::CHUNK as f64 / (mb_per_sec.max(0.001) * 1024.0 * 1024.0));
+
+        let handle = std::thread::spawn(move || {
+            let chunk = vec![0xA5u8; Self::CHUNK];
+            let mut position = 0u64;
+            loop {
+                let started = Instant::now();
+                file.write_all(&chunk)?;
+                file.flush()?;
+                counter.fetch_add(Self::CHUNK as u64, Ordering::Relaxed);
+
+                position += Self::CHUNK as u64;
+                if position >= Self::MAX_FILE_SIZE {
+                    file.rewind()?;
+                    position = 0;
+                }
+
+                let wait = chunk_interval.saturating_sub(started.elapsed());
+                match stop_rx.recv_timeout(wait) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    _ => return Ok(()),
+                }
+            }
+        });
+
+        Ok(Self {
+            stop: Some(stop),
+            bytes_written,
+            handle: Some(handle),
+        })
+    }
+
+    /// Bytes written so far
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written.load(Ordering::Relaxed)
+    }
+
+    /// Stop writing and return any I/O error hit by the background thread
+    pub fn stop(mut self) -> io::Result<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        drop(self.stop.take());
+        match self.handle.take() {
+            Some(handle) => handle
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("I/O noise thread panicked"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for IoNoise {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,6 +1474,63 @@ mod tests {
         assert!(corrupted_count > 0);
     }
 
+    #[test]
+    fn test_injection_decisions_follow_probability() {
+        let decide = |seed| {
+            let chaos = ChaosInjector::new(seed).with_probability(0.3);
+            (0..10_000)
+                .map(|_| chaos.should_inject())
+                .collect::<Vec<_>>()
+        };
+        let a = decide(11);
+        let rate = a.iter().filter(|&&fired| fired).count() as f64 / a.len() as f64;
+        assert!((rate - 0.3).abs() < 0.02, "rate {}", rate);
+        assert_eq!(a, decide(11));
+        assert_ne!(a, decide(12));
+
+        let never = ChaosInjector::new(1).with_probability(0.0);
+        let always = ChaosInjector::new(1).with_probability(1.0);
+        assert!((0..1000).all(|_| !never.should_inject()));
+        assert!((0..1000).all(|_| always.should_inject()));
+    }
+
+    #[test]
+    fn test_shared_injector_consumes_one_step_per_decision() {
+        fn assert_sync<T: Sync>(_: &T) {}
+        let shared = ChaosInjector::new(11).with_probability(0.3);
+        assert_sync(&shared);
+
+        let fired = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..2_500 {
+                        if shared.should_inject() {
+                            fired.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+
+        // The threads drew the same 10,000 steps a single thread would
+        let sequential = ChaosInjector::new(11).with_probability(0.3);
+        let expected = (0..10_000).filter(|_| sequential.should_inject()).count();
+        assert_eq!(fired.into_inner(), expected);
+    }
+
+    #[test]
+    fn test_maybe_runs_injection_on_decision() {
+        let chaos = ChaosInjector::new(5).with_probability(0.5);
+        let data = vec![0u8; 64];
+        // Each injection flips the same bits, so corrupt fresh copies
+        let corrupted: Vec<Vec<u8>> = (0..100)
+            .filter_map(|_| chaos.maybe(|| chaos.corrupt_copy(&data, 0.1)))
+            .collect();
+        assert!(!corrupted.is_empty() && corrupted.len() < 100);
+        assert!(corrupted.iter().all(|c| c != &data));
+    }
+
     #[test]
     fn test_corrupt_copy() {
         let data = vec![0xFF; 100];
@@ -146,6 +1556,62 @@ mod tests {
         assert!(zero_count > 0);
     }
 
+    #[test]
+    fn test_packet_loss_modes() {
+        // 10 packets of 10 bytes and a final partial packet of 5
+        let data: Vec<u8> = (0..105).map(|i| (i % 200) as u8 + 1).collect();
+        let injector = ChaosInjector::new(42);
+
+        let (zeroed, dropped) =
+            injector.simulate_packet_loss_into(&data, 0.5, 10, LossMode::ZeroFill);
+        assert!(!dropped.is_empty());
+        assert!(dropped.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(zeroed.len(), data.len());
+        let mut in_place = data.clone();
+        injector.simulate_packet_loss(&mut in_place, 0.5, 10);
+        assert_eq!(in_place, zeroed);
+
+        let (marked, again) =
+            injector.simulate_packet_loss_into(&data, 0.5, 10, LossMode::MarkerFill(0xEE));
+        assert_eq!(again, dropped);
+        assert_eq!(marked.len(), data.len());
+        for (packet_idx, packet) in marked.chunks(10).enumerate() {
+            let lost = dropped.contains(&packet_idx);
+            assert_eq!(
+                packet.iter().all(|&b| b == 0xEE),
+                lost,
+                "packet {}",
+                packet_idx
+            );
+        }
+
+        let (removed, again) = injector.simulate_packet_loss_into(&data, 0.5, 10, LossMode::Remove);
+        assert_eq!(again, dropped);
+        let lost_bytes: usize = dropped.iter().map(|&p| (data.len() - p * 10).min(10)).sum();
+        assert_eq!(removed.len(), data.len() - lost_bytes);
+        let kept: Vec<u8> = data
+            .chunks(10)
+            .enumerate()
+            .filter(|(p, _)| !dropped.contains(p))
+            .flat_map(|(_, packet)| packet.to_vec())
+            .collect();
+        assert_eq!(removed, kept);
+
+        // Removing the short final packet drops only its 5 bytes
+        let tail = &data[..15];
+        let partial = (0..64)
+            .map(|seed| {
+                ChaosInjector::new(seed).simulate_packet_loss_into(tail, 0.5, 10, LossMode::Remove)
+            })
+            .find(|(_, dropped)| dropped == &[1])
+            .expect("some seed drops the final packet");
+        assert_eq!(partial.0, &tail[..10]);
+
+        let other =
+            ChaosInjector::new(43).simulate_packet_loss_into(&data, 0.5, 10, LossMode::Remove);
+        assert_ne!(other.1, dropped);
+    }
+
     #[test]
     fn test_inject_erasures() {
         let mut data = vec![0xFF; 100];
@@ -161,6 +1627,440 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_duplicate_chunks_length() {
+        let data: Vec<u8> = (0..=255).cycle().take(1005).collect();
+        let injector = ChaosInjector::new(42);
+
+        let (out, duplicated) = injector.duplicate_chunks(&data, 0.3, 100);
+        assert!(!duplicated.is_empty());
+        let added: usize = duplicated
+            .iter()
+            .map(|&i| data.chunks(100).nth(i).unwrap().len())
+            .sum();
+        assert_eq!(out.len(), data.len() + added);
+
+        // Dropping the second copy of each duplicated chunk gives back the input
+        let mut rebuilt = Vec::new();
+        let mut pos = 0;
+        for (index, chunk) in data.chunks(100).enumerate() {
+            assert_eq!(&out[pos..pos + chunk.len()], chunk);
+            rebuilt.extend_from_slice(chunk);
+            pos += chunk.len();
+            if duplicated.contains(&index) {
+                assert_eq!(&out[pos..pos + chunk.len()], chunk);
+                pos += chunk.len();
+            }
+        }
+        assert_eq!(rebuilt, data);
+
+        assert_eq!(injector.duplicate_chunks(&data, 0.3, 100).0, out);
+        assert_eq!(injector.duplicate_chunks(&[], 0.5, 10).0, Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_corrupt_directory_matches_checksum_diff() {
+        use crate::integrity::ChecksumManifest;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join("tree");
+        for i in 0..20 {
+            let sub = dir.join(format!("d{}", i % 3));
+            fs::create_dir_all(&sub).unwrap();
+            let content: Vec<u8> = (0..(500 + i * 97)).map(|b| (b * 7 + i) as u8).collect();
+            fs::write(sub.join(format!("f{:02}.bin", i)), content).unwrap();
+        }
+        let before = ChecksumManifest::capture(&dir).unwrap();
+
+        let corruption = ChaosInjector::new(77).corrupt_directory(&dir, 0.3, 0.01);
+        assert_eq!(corruption.files.len(), 6);
+        assert!(corruption.files.iter().all(|f| !f.flips.is_empty()));
+
+        let mut found: Vec<_> = before
+            .verify(&dir)
+            .unwrap()
+            .file_mismatches
+            .into_iter()
+            .map(|m| (m.path, m.kind))
+            .collect();
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(found, corruption.expected_mismatches());
+
+        let json = corruption.to_json().unwrap();
+        assert_eq!(CorruptionManifest::from_json(&json).unwrap(), corruption);
+
+        // Same seed picks the same bits, so replaying on a copy undoes the damage
+        let other = temp.path().join("other");
+        for rel in before.entries.keys() {
+            let original = dir.join(rel);
+            let target = other.join(rel);
+            fs::create_dir_all(target.parent().unwrap()).unwrap();
+            fs::copy(&original, &target).unwrap();
+        }
+        let replay = ChaosInjector::new(77).corrupt_directory(&other, 0.3, 0.01);
+        assert_eq!(replay, corruption);
+        assert!(before.verify(&other).unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_corrupt_directory_flips_across_blocks() {
+        let temp = tempfile::TempDir::new().unwrap();
+        // Spans three blocks, the last one partial
+        let original: Vec<u8> = (0..(2 * CORRUPT_BLOCK as usize + 1000))
+            .map(|b| (b * 31) as u8)
+            .collect();
+        fs::write(temp.path().join("big.bin"), &original).unwrap();
+
+        let corruption = ChaosInjector::new(5).corrupt_directory(temp.path(), 1.0, 0.001);
+        let flips = &corruption.files[0].flips;
+        assert!(flips.len() > 100);
+        let last_block = original.len() as u64 / CORRUPT_BLOCK;
+        assert!(flips.iter().any(|f| f.offset / CORRUPT_BLOCK == last_block));
+
+        let mut expected = original;
+        for flip in flips {
+            expected[flip.offset as usize] ^= 1u8 << flip.bit;
+        }
+        assert_eq!(fs::read(temp.path().join("big.bin")).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_latency_injector_virtual_distributions() {
+        let ms = Duration::from_millis;
+        let run = |seed, distribution| {
+            let mut latency = LatencyInjector::new(seed, distribution).virtual_mode();
+            for _ in 0..10_000 {
+                latency.delay();
+            }
+            latency.injected().to_vec()
+        };
+        let mean_ms = |delays: &[Duration]| {
+            delays.iter().map(|d| d.as_secs_f64() * 1e3).sum::<f64>() / delays.len() as f64
+        };
+
+        let fixed = run(1, DelayDistribution::Fixed(ms(4)));
+        assert!(fixed.iter().all(|&d| d == ms(4)));
+
+        let uniform = DelayDistribution::Uniform {
+            min: ms(2),
+            max: ms(10),
+        };
+        let samples = run(1, uniform);
+        assert!(samples.iter().all(|&d| d >= ms(2) && d <= ms(10)));
+        assert!((mean_ms(&samples) - 6.0).abs() < 0.1);
+        assert_eq!(samples, run(1, uniform));
+        assert_ne!(samples, run(2, uniform));
+
+        let exponential = run(7, DelayDistribution::Exponential { mean: ms(5) });
+        assert!((mean_ms(&exponential) - 5.0).abs() < 0.25);
+        let median = {
+            let mut sorted = exponential.clone();
+            sorted.sort();
+            sorted[sorted.len() / 2].as_secs_f64() * 1e3
+        };
+        assert!((median - 5.0 * std::f64::consts::LN_2).abs() < 0.25);
+
+        let spikes = run(
+            1,
+            DelayDistribution::Spike {
+                base: ms(1),
+                spike_every_n: 100,
+                spike_duration: ms(50),
+            },
+        );
+        for (i, &d) in spikes.iter().enumerate() {
+            assert_eq!(d, if (i + 1) % 100 == 0 { ms(50) } else { ms(1) });
+        }
+    }
+
+    #[test]
+    fn test_latency_injector_wrap_sleeps() {
+        let mut latency =
+            LatencyInjector::new(0, DelayDistribution::Fixed(Duration::from_millis(5)));
+        let start = Instant::now();
+        assert_eq!(latency.wrap(|| 42), 42);
+        assert!(start.elapsed() >= Duration::from_millis(5));
+        assert_eq!(latency.total_injected(), Duration::from_millis(5));
+        assert!(!latency.is_virtual());
+    }
+
+    #[test]
+    fn test_flaky_follows_explicit_schedule() {
+        let schedule = vec![false, false, true, false, true];
+        let mut counter = Flaky::new(0u32, 0, FailurePattern::Schedule(schedule.clone()));
+
+        let outcomes: Vec<_> = (0..5)
+            .map(|_| {
+                counter.call(|n| {
+                    *n += 1;
+                    *n
+                })
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                Ok(1),
+                Ok(2),
+                Err(InjectedFault { invocation: 2 }),
+                Ok(3),
+                Err(InjectedFault { invocation: 4 }),
+            ]
+        );
+        assert_eq!(counter.log(), schedule.as_slice());
+        assert_eq!(counter.failed_invocations(), vec![2, 4]);
+        assert_eq!(*counter.inner(), 3);
+
+        // Past the end of the schedule every call succeeds
+        assert_eq!(counter.call(|n| *n), Ok(3));
+        assert_eq!(counter.calls(), 6);
+    }
+
+    #[test]
+    fn test_flaky_patterns() {
+        let failed = |pattern: FailurePattern, seed: u64| {
+            let mut flaky = Flaky::new((), seed, pattern);
+            for _ in 0..12 {
+                let _ = flaky.call(|_| ());
+            }
+            flaky.failed_invocations()
+        };
+        assert_eq!(failed(FailurePattern::EveryNth(4), 0), vec![3, 7, 11]);
+        assert_eq!(
+            failed(FailurePattern::Burst { after: 3, burst: 2 }, 0),
+            vec![3, 4, 8, 9]
+        );
+        assert!(failed(FailurePattern::Probability(0.0), 1).is_empty());
+        assert_eq!(failed(FailurePattern::Probability(1.0), 1).len(), 12);
+        assert_eq!(
+            failed(FailurePattern::Probability(0.5), 9),
+            failed(FailurePattern::Probability(0.5), 9)
+        );
+    }
+
+    #[test]
+    fn test_truncate_and_extend_lengths() {
+        let data: Vec<u8> = (0..200).map(|i| i as u8).collect();
+        let injector = ChaosInjector::new(3);
+
+        assert_eq!(injector.truncate(&data, 50usize), data[..150]);
+        assert_eq!(injector.truncate(&data, 0.25).len(), 150);
+        assert_eq!(injector.truncate(&data, 1000usize).len(), 0);
+        assert_eq!(injector.truncate(&data, 0.0), data);
+
+        let extended = injector.extend_with_garbage(&data, 37);
+        assert_eq!(extended.len(), 237);
+        assert_eq!(extended[..200], data[..]);
+        assert_eq!(
+            ChaosInjector::new(3).extend_with_garbage(&data, 37),
+            extended
+        );
+        assert_ne!(
+            ChaosInjector::new(4).extend_with_garbage(&data, 37),
+            extended
+        );
+    }
+
+    #[test]
+    fn test_splice_segments_cover_output() {
+        let a = vec![0xAAu8; 300];
+        let b = vec![0xBBu8; 120];
+        let injector = ChaosInjector::new(21);
+
+        let (out, segments) = injector.splice(&a, &b, 5);
+        assert_eq!(segments.len(), 6);
+        assert_eq!(out.len(), segments.iter().map(|s| s.len()).sum::<usize>());
+
+        // Segments alternate sources and stay in order
+        let mut pos = 0;
+        for (i, segment) in segments.iter().enumerate() {
+            let expected = if i % 2 == 0 { 0xAA } else { 0xBB };
+            assert!(out[pos..pos + segment.len()].iter().all(|&x| x == expected));
+            pos += segment.len();
+        }
+        for pair in segments.windows(2) {
+            assert!(pair[0].start < pair[1].start);
+        }
+        // Last segment (from b) runs to the end of b
+        assert_eq!(segments.last().unwrap().end, b.len());
+
+        assert_eq!(ChaosInjector::new(21).splice(&a, &b, 5), (out, segments));
+        assert_eq!(injector.splice(&a, &[], 3).0, a);
+    }
+
+    #[test]
+    fn test_reorder_chunks_replay_restores() {
+        let original: Vec<u8> = (0..1037).map(|i| (i % 251) as u8).collect();
+        let mut data = original.clone();
+        let injector = ChaosInjector::new(9);
+
+        let swaps = injector.reorder_chunks(&mut data, 4, 64);
+        assert_eq!(swaps.len(), 4);
+        assert_ne!(data, original);
+        assert_eq!(data[1024..], original[1024..]); // partial tail untouched
+
+        let mut again = original.clone();
+        assert_eq!(
+            ChaosInjector::new(9).reorder_chunks(&mut again, 4, 64),
+            swaps
+        );
+        assert_eq!(again, data);
+
+        ChaosInjector::swap_chunks(&mut data, &swaps, 64);
+        assert_eq!(data, original);
+
+        // Swap count is capped by the number of full chunk pairs
+        let mut small = vec![1u8, 2, 3];
+        assert_eq!(injector.reorder_chunks(&mut small, 5, 1).len(), 1);
+        assert!(injector.reorder_chunks(&mut small, 5, 0).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "measures process RSS, which concurrently running tests disturb"]
+    fn test_memory_pressure_grows_and_releases() {
+        let size = 100 * 1024 * 1024;
+        let before = MemoryProbe::current_rss().unwrap();
+
+        let pressure = MemoryPressure::new(size);
+        pressure.wait_until_allocated();
+        assert_eq!(pressure.allocated(), size);
+        let during = MemoryProbe::current_rss().unwrap();
+        assert!(
+            during >= before + size * 9 / 10,
+            "RSS did not grow: before={} during={}",
+            before,
+            during
+        );
+
+        drop(pressure);
+        let after = MemoryProbe::current_rss().unwrap();
+        assert!(
+            after + size / 2 < during,
+            "RSS not released: during={} after={}",
+            during,
+            after
+        );
+    }
+
+    #[test]
+    fn test_memory_pressure_steps_and_ceiling() {
+        let pressure = MemoryPressure::stepped(3 * 1024 * 1024 + 1, 3, Duration::from_millis(1));
+        pressure.wait_until_allocated();
+        assert_eq!(pressure.allocated(), 3 * 1024 * 1024 + 1);
+
+        // Asking for 0% free would exhaust memory; the ceiling bounds it
+        if let Some(pressure) = MemoryPressure::until_fraction_free(0.0, 8 * 1024 * 1024) {
+            pressure.wait_until_allocated();
+            assert!(pressure.allocated() <= 8 * 1024 * 1024);
+        }
+    }
+
+    #[test]
+    fn test_noise_tolerance_sweep() {
+        let data = b"The quick brown fox jumps over the lazy dog.".repeat(4);
+        let config = ReversibleVSAConfig::default();
+        let rates = [0.0, 0.01, 0.1, 0.5];
+
+        let report = noise_tolerance_sweep(&data, &config, &rates, &ChaosInjector::new(42));
+
+        assert_eq!(report.points.len(), rates.len());
+        let clean = &report.points[0];
+        assert_eq!(clean.corrupted_bytes, 0);
+        assert!((clean.similarity - 1.0).abs() < 1e-9);
+        assert!(clean.decode_ok);
+        assert_eq!(clean.flipped_signs, 0);
+        assert!(report.max_decodable_rate().is_some());
+        assert_eq!(report.to_csv().lines().count(), rates.len() + 1);
+        let json = report.to_json().unwrap();
+        let parsed: NoiseSweepReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.points.len(), rates.len());
+    }
+
+    #[test]
+    fn test_decode_accuracy_tracks_vector_noise() {
+        let data = b"The quick brown fox jumps over the lazy dog.".repeat(4);
+        let config = ReversibleVSAConfig::default();
+        let rates = [0.0, 0.05, 0.3, 1.0];
+        let clean = SparseVec::encode_data(&data, &config, None);
+
+        // Decoding starts from the clean encoding, so only the sign flips
+        // can cost accuracy, however the bytes were corrupted
+        let report = noise_tolerance_sweep(&data, &config, &rates, &ChaosInjector::new(42)).points;
+        assert_eq!(report[0].decode_accuracy, 1.0);
+        assert_eq!(report[3].flipped_signs, clean.pos.len() + clean.neg.len());
+        assert!(report[1].flipped_signs < report[2].flipped_signs);
+        for pair in report.windows(2) {
+            assert!(
+                pair[1].decode_accuracy < pair[0].decode_accuracy,
+                "{:?}",
+                report
+            );
+        }
+        assert!(report[1..].iter().all(|p| !p.decode_ok));
+
+        let again = noise_tolerance_sweep(&data, &config, &rates, &ChaosInjector::new(42));
+        assert_eq!(again.points, report);
+    }
+
+    #[test]
+    fn test_duty_split() {
+        let (busy, idle) = duty_split(Duration::from_millis(10), 0.7);
+        assert_eq!(busy, Duration::from_millis(7));
+        assert_eq!(idle, Duration::from_millis(3));
+
+        let (busy, idle) = duty_split(Duration::from_millis(10), 1.5);
+        assert_eq!(busy, Duration::from_millis(10));
+        assert!(idle.is_zero());
+    }
+
+    #[test]
+    fn test_load_generator_spins_sleeps_and_stops() {
+        let load = LoadGenerator::start(2, 0.5, Duration::from_millis(10));
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            let stats = load.stats();
+            if !stats.busy.is_zero() && !stats.idle.is_zero() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let stats = load.stats();
+        assert!(!stats.busy.is_zero(), "threads never spun");
+        assert!(!stats.idle.is_zero(), "threads never slept");
+        drop(load);
+    }
+
+    #[test]
+    #[ignore = "asserts on wall-clock duty cycle and stop latency"]
+    fn test_load_generator_duty_cycle_timing() {
+        let load = LoadGenerator::start(2, 0.5, Duration::from_millis(10));
+        std::thread::sleep(Duration::from_millis(200));
+        let stats = load.stats();
+        assert!(
+            (0.2..0.8).contains(&stats.duty()),
+            "duty {} out of range",
+            stats.duty()
+        );
+
+        let started = Instant::now();
+        drop(load);
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_io_noise_writes_and_stops() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let noise = IoNoise::start(dir.path(), 10.0).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while noise.bytes_written() == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(noise.bytes_written() > 0);
+        noise.stop().unwrap();
+    }
+
     #[test]
     fn test_determinism() {
         let data = vec![0xFF; 100];
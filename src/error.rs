@@ -0,0 +1,166 @@
+//! Structured error type for testkit operations
+//!
+//! Every variant carries the path that caused the failure so CI logs point
+//! straight at the offending file instead of a bare `expect` message.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Errors produced by the fallible (`try_*`) testkit APIs
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Underlying I/O failure
+    #[error("I/O error at {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// The filesystem ran out of space while writing
+    #[error("insufficient disk space writing {} ({required} bytes requested)", path.display())]
+    DiskSpace { path: PathBuf, required: u64 },
+    /// The requested dataset/file specification is invalid
+    #[error("invalid spec for {}: {reason}", path.display())]
+    InvalidSpec { path: PathBuf, reason: String },
+    /// File content does not match the expected pattern
+    #[error(
+        "pattern mismatch in {} at offset {offset}: expected {expected:#04x}, got {actual:#04x}",
+        path.display()
+    )]
+    PatternMismatch {
+        path: PathBuf,
+        offset: u64,
+        expected: u8,
+        actual: u8,
+    },
+    /// A binary testkit file failed validation
+    #[error("corrupt file {}: {kind}", path.display())]
+    Corrupt { path: PathBuf, kind: CorruptionKind },
+    /// The operation is not available on this platform or filesystem
+    #[error("{operation} is not supported for {}", path.display())]
+    Unsupported {
+        path: PathBuf,
+        operation: &'static str,
+    },
+    /// An external corpus could not be downloaded or unpacked into `path`
+    #[error("cannot fetch {url} into {}: {reason}", path.display())]
+    Fetch {
+        path: PathBuf,
+        url: String,
+        reason: String,
+    },
+}
+
+/// Why a binary testkit file was rejected
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum CorruptionKind {
+    /// The file does not start with the expected magic bytes
+    #[error("bad magic bytes")]
+    BadMagic,
+    /// Written by a newer, unknown format version
+    #[error("unsupported format version {0}")]
+    UnsupportedVersion(u16),
+    /// Shorter than its header says
+    #[error("truncated: expected {expected} bytes, found {actual}")]
+    Truncated { expected: u64, actual: u64 },
+    /// Longer than its header says
+    #[error("{extra} unexpected trailing bytes")]
+    TrailingData { extra: u64 },
+    /// Payload does not hash to the recorded checksum
+    #[error("checksum mismatch: expected {expected:#018x}, got {actual:#018x}")]
+    ChecksumMismatch { expected: u64, actual: u64 },
+    /// A record could not be decoded
+    #[error("malformed record {record}: {reason}")]
+    Malformed { record: u64, reason: String },
+}
+
+/// Result alias for testkit operations
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Path associated with this error
+    pub fn path(&self) -> &Path {
+        match self {
+            Error::Io { path, .. }
+            | Error::DiskSpace { path, .. }
+            | Error::InvalidSpec { path, .. }
+            | Error::PatternMismatch { path, .. }
+            | Error::Corrupt { path, .. }
+            | Error::Unsupported { path, .. }
+            | Error::Fetch { path, .. } => path,
+        }
+    }
+
+    /// Wrap an I/O error with the path it occurred on
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Wrap an I/O error raised while writing `required` bytes
+    ///
+    /// Out-of-space failures become [`Error::DiskSpace`]; everything else is
+    /// reported as [`Error::Io`].
+    pub fn write(path: impl Into<PathBuf>, required: u64, source: io::Error) -> Self {
+        let path = path.into();
+        if source.kind() == io::ErrorKind::StorageFull {
+            Error::DiskSpace { path, required }
+        } else {
+            Error::Io { path, source }
+        }
+    }
+
+    /// Build an [`Error::Corrupt`]
+    pub fn corrupt(path: impl Into<PathBuf>, kind: CorruptionKind) -> Self {
+        Error::Corrupt {
+            path: path.into(),
+            kind,
+        }
+    }
+
+    /// Build an [`Error::InvalidSpec`]
+    pub fn invalid_spec(path: impl Into<PathBuf>, reason: impl Into<String>) -> Self {
+        Error::InvalidSpec {
+            path: path.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Attach a path to `std::io::Result` values
+pub(crate) trait IoResultExt<T> {
+    fn at_path(self, path: &Path) -> Result<T>;
+}
+
+impl<T> IoResultExt<T> for io::Result<T> {
+    fn at_path(self, path: &Path) -> Result<T> {
+        self.map_err(|e| Error::io(path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_carries_path() {
+        let err = Error::io(
+            "/tmp/missing.bin",
+            io::Error::new(io::ErrorKind::NotFound, "gone"),
+        );
+        assert_eq!(err.path(), Path::new("/tmp/missing.bin"));
+        assert!(err.to_string().contains("/tmp/missing.bin"));
+    }
+
+    #[test]
+    fn test_storage_full_maps_to_disk_space() {
+        let err = Error::write(
+            "/data/out.bin",
+            4096,
+            io::Error::from(io::ErrorKind::StorageFull),
+        );
+        assert!(matches!(err, Error::DiskSpace { required: 4096, .. }));
+    }
+}
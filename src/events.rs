@@ -0,0 +1,163 @@
+//! Optional JSONL event log of testkit operations
+//!
+//! After [`init`], dataset creation, chaos injections, integrity check
+//! results and metric summaries each append one JSON record per line to the
+//! log file. Until then emission costs a single static check, so the log can
+//! stay wired in everywhere.
+//!
+//! # Example
+//! ```rust,ignore
+//! events::init(Path::new("target/testkit-events.jsonl"))?;
+//! run_scenario();
+//! for event in events::read(Path::new("target/testkit-events.jsonl"))? {
+//!     println!("{} {} {}", event.module, event.event, event.payload);
+//! }
+//! ```
+
+use crate::error::{Error, IoResultExt, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Active log writer; `None` inside the cell after [`shutdown`]
+static LOG: OnceLock<Mutex<Option<LineWriter<File>>>> = OnceLock::new();
+
+/// One record of the event log
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Emitting module, e.g. `harness`
+    pub module: String,
+    /// Event type, e.g. `dataset_created`
+    pub event: String,
+    /// Event-specific details
+    pub payload: serde_json::Value,
+}
+
+/// Start appending events to `path` (created if missing)
+///
+/// Calling `init` again redirects the log to the new path.
+pub fn init(path: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .at_path(path)?;
+    let log = LOG.get_or_init(|| Mutex::new(None));
+    *log.lock().unwrap() = Some(LineWriter::new(file));
+    Ok(())
+}
+
+/// Stop logging; later events are dropped
+pub fn shutdown() {
+    if let Some(log) = LOG.get() {
+        *log.lock().unwrap() = None;
+    }
+}
+
+/// Whether an event log is currently active
+pub fn is_active() -> bool {
+    LOG.get().is_some_and(|log| log.lock().unwrap().is_some())
+}
+
+/// Read every event from a log file, in the order written
+pub fn read(path: &Path) -> Result<Vec<Event>> {
+    let content = fs::read_to_string(path).at_path(path)?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| Error::io(path, std::io::Error::from(e)))
+        })
+        .collect()
+}
+
+/// Append an event if logging is active
+///
+/// `payload` is only evaluated when a log is active. Write failures are
+/// ignored so tracing never changes the outcome of a test.
+pub(crate) fn emit(
+    module: &'static str,
+    event: &'static str,
+    payload: impl FnOnce() -> serde_json::Value,
+) {
+    let Some(log) = LOG.get() else {
+        return;
+    };
+    let mut guard = log.lock().unwrap();
+    let Some(writer) = guard.as_mut() else {
+        return;
+    };
+
+    let record = Event {
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64),
+        module: module.to_string(),
+        event: event.to_string(),
+        payload: payload(),
+    };
+    if let Ok(line) = serde_json::to_string(&record) {
+        let _ = writeln!(writer, "{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChaosInjector, ChecksumManifest, TestHarness, TestMetrics};
+
+    #[test]
+    fn test_event_log_records_scenario_in_order() {
+        let harness = TestHarness::new();
+        let log_path = harness.temp_dir().join("events.jsonl");
+        init(&log_path).unwrap();
+        assert!(is_active());
+
+        let dataset = harness.create_dataset(1);
+        let manifest = ChecksumManifest::capture(&dataset).unwrap();
+        let mut data = vec![0u8; 1024];
+        ChaosInjector::new(3).corrupt_bytes(&mut data, 0.01);
+        manifest.verify(&dataset).unwrap();
+        let _ = TestMetrics::new("events_scenario").summary();
+
+        shutdown();
+        assert!(!is_active());
+        emit("test", "after_shutdown", || serde_json::json!({}));
+
+        // Other tests may emit concurrently, so check for an ordered subsequence
+        let events = read(&log_path).unwrap();
+        let expected = [
+            ("harness", "dataset_created"),
+            ("chaos", "corrupt_bytes"),
+            ("integrity", "manifest_diff"),
+            ("metrics", "summary"),
+        ];
+        let mut remaining = expected.iter().peekable();
+        for event in &events {
+            if let Some((module, name)) = remaining.peek() {
+                if event.module == *module && event.event == *name {
+                    remaining.next();
+                }
+            }
+        }
+        assert!(
+            remaining.peek().is_none(),
+            "events out of order: {:?}",
+            events
+        );
+
+        let created = events
+            .iter()
+            .find(|e| {
+                e.event == "dataset_created" && e.payload["path"] == dataset.display().to_string()
+            })
+            .unwrap();
+        assert_eq!(created.payload["size_mb"], 1);
+        assert!(events.iter().all(|e| e.event != "after_shutdown"));
+    }
+}
@@ -0,0 +1,341 @@
+//! Frozen corpus of canonical vectors with expected operation results
+//!
+//! Fifty vectors of varied sparsity, from the empty vector to one that is
+//! non-zero in every dimension, plus a table of expected `sparse_dot`,
+//! bundle and bind results for selected pairs. The table was computed once
+//! and is checked in as constants, so running it against a new VSA release
+//! (or another implementation) catches silent semantic drift.
+//!
+//! The vectors themselves are rebuilt from the pinned generators on each
+//! call; [`CORPUS_FINGERPRINT`] guards them against drift as well.
+//!
+//! # Example
+//! ```rust,ignore
+//! struct Candidate;
+//! impl VsaOps for Candidate {
+//!     fn bundle(&self, a: &SparseVec, b: &SparseVec) -> SparseVec { new_vsa::bundle(a, b) }
+//!     fn bind(&self, a: &SparseVec, b: &SparseVec) -> SparseVec { new_vsa::bind(a, b) }
+//! }
+//! let report = corpus::verify_against(&Candidate);
+//! assert!(report.is_ok(), "{}", report.failures.join("\n"));
+//! ```
+
+use crate::generators::special_vectors::{self, Sign};
+use crate::generators::{
+    bytes_fingerprint, deterministic_sparse_vec, reference_bundle_many, sparse_dot,
+    sparse_vec_fingerprint,
+};
+use crate::integrity::IntegrityReport;
+use embeddenator_vsa::SparseVec;
+use std::time::Instant;
+
+/// Dimension of every corpus vector
+pub const CORPUS_DIMS: usize = 10_000;
+
+/// Number of corpus vectors
+pub const CORPUS_LEN: usize = 50;
+
+/// Stable hash of the whole corpus, in order
+pub const CORPUS_FINGERPRINT: u64 = 0xe0fd_f6e1_bb56_2472;
+
+/// Validator name recorded for corpus checks
+const VALIDATOR: &str = "corpus";
+
+/// Non-zeros of the generated corpus vectors, cycled
+const SPARSITIES: [usize; 8] = [1, 2, 10, 50, 100, 200, 500, 1000];
+
+/// First corpus index holding a bundle of two earlier vectors
+const FIRST_DERIVED: usize = 44;
+
+/// VSA operations under test
+///
+/// Implement by delegating to the version or implementation being checked.
+/// `dot` defaults to the testkit's reference [`sparse_dot`].
+pub trait VsaOps {
+    /// Bundle (superpose) two vectors
+    fn bundle(&self, a: &SparseVec, b: &SparseVec) -> SparseVec;
+
+    /// Bind two vectors
+    fn bind(&self, a: &SparseVec, b: &SparseVec) -> SparseVec;
+
+    /// Ternary dot product
+    fn dot(&self, a: &SparseVec, b: &SparseVec) -> i32 {
+        sparse_dot(a, b)
+    }
+}
+
+/// [`VsaOps`] backed by the linked `embeddenator-vsa` crate
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CurrentVsa;
+
+impl VsaOps for CurrentVsa {
+    fn bundle(&self, a: &SparseVec, b: &SparseVec) -> SparseVec {
+        a.bundle(b)
+    }
+
+    fn bind(&self, a: &SparseVec, b: &SparseVec) -> SparseVec {
+        a.bind(b)
+    }
+}
+
+/// Expected results for one corpus pair
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CorpusCase {
+    /// Corpus index of the left operand
+    pub a: usize,
+    /// Corpus index of the right operand
+    pub b: usize,
+    /// Expected `dot(a, b)`
+    pub dot: i32,
+    /// Expected [`sparse_vec_fingerprint`] of `bundle(a, b)`
+    pub bundle: u64,
+    /// Expected [`sparse_vec_fingerprint`] of `bind(a, b)`
+    pub bind: u64,
+}
+
+/// Expected results, computed once and frozen
+pub const CASES: &[CorpusCase] = &[
+    case(0, 1, 0, 0x5b2a_969b_42d2_38a4, 0x8820_1fb9_60ff_6465),
+    case(1, 2, 0, 0x6c21_7b89_20b8_c663, 0x8820_1fb9_60ff_6465),
+    case(2, 3, 0, 0x5cb3_a7e8_d5ce_5452, 0x8820_1fb9_60ff_6465),
+    case(3, 4, 0, 0xf8c7_126d_3b44_6a61, 0x40af_5d5e_1a64_6c25),
+    case(4, 5, 0, 0x44b8_6728_274d_09ad, 0x513c_84ad_6fd4_0f45),
+    case(5, 6, -4, 0xae03_736c_0490_babf, 0x77cf_b8a7_055f_2a44),
+    case(6, 7, -10, 0x7687_d2d2_e4d3_2265, 0xb1f5_2592_efbe_edab),
+    case(7, 8, 0, 0x94c1_451c_f3d6_da54, 0x8820_1fb9_60ff_6465),
+    case(8, 9, 0, 0x30f8_19cd_9db2_9f5e, 0x8820_1fb9_60ff_6465),
+    case(9, 10, 0, 0x970a_240b_0c2a_3539, 0x8820_1fb9_60ff_6465),
+    case(10, 11, 0, 0xc411_94ac_fc08_df9f, 0x8820_1fb9_60ff_6465),
+    case(11, 12, 0, 0xe722_b111_ca1c_3f12, 0x8820_1fb9_60ff_6465),
+    case(12, 13, -1, 0x0e20_c2e0_3f98_ac26, 0xa95b_17e6_5bda_6029),
+    case(13, 14, 0, 0xaa4b_5f74_f5ce_20d3, 0x371d_5057_348e_19fc),
+    case(14, 15, -4, 0x5ffa_4b78_e7b8_b971, 0x475e_7d7f_0281_bee7),
+    case(15, 16, 0, 0xc8c7_4d09_c16d_ce96, 0x8820_1fb9_60ff_6465),
+    case(16, 17, 0, 0x53c5_4440_112b_7166, 0x8820_1fb9_60ff_6465),
+    case(17, 18, 0, 0x40fa_c020_2767_6507, 0x8820_1fb9_60ff_6465),
+    case(18, 19, 0, 0x79b4_d2ab_5028_2942, 0x8820_1fb9_60ff_6465),
+    case(19, 20, 0, 0x55ee_43cc_3560_d625, 0x8820_1fb9_60ff_6465),
+    case(20, 21, 2, 0xd406_a2e1_d273_c4af, 0x2128_f317_7e20_711f),
+    case(21, 22, -5, 0xecb4_650d_f97f_dd7c, 0x2761_c18e_3885_71d8),
+    case(22, 23, -2, 0xd47a_9843_2162_60bb, 0x676a_5163_c380_31f2),
+    case(23, 24, 0, 0x3739_330f_caa1_0ed9, 0x8820_1fb9_60ff_6465),
+    case(24, 25, 0, 0xbbfb_1441_20a9_ba5f, 0x8820_1fb9_60ff_6465),
+    case(25, 26, 0, 0x941a_e796_4ce5_8a50, 0x8820_1fb9_60ff_6465),
+    case(26, 27, 0, 0x34a7_69ce_e0a4_5052, 0x8820_1fb9_60ff_6465),
+    case(27, 28, 0, 0x1cb4_f5f1_5c9a_9477, 0x983c_cfcf_9270_74c5),
+    case(28, 29, 1, 0xbeac_f032_8553_cd10, 0x2bcb_3255_9678_dd52),
+    case(29, 30, 2, 0x16d0_ac1e_bddb_d821, 0x6186_ab88_e77c_32a4),
+    case(30, 31, 1, 0xa019_4ef3_b15d_c907, 0x9fec_6d26_3d7b_6880),
+    case(31, 32, 0, 0x94b0_c8bb_4641_fda4, 0x8820_1fb9_60ff_6465),
+    case(32, 33, 0, 0x1bdb_f627_fb4b_05c3, 0x8820_1fb9_60ff_6465),
+    case(33, 34, 0, 0xfeab_0bcc_19a2_e4ed, 0x8820_1fb9_60ff_6465),
+    case(34, 35, 0, 0xfe06_1a3b_21a7_45f0, 0x8820_1fb9_60ff_6465),
+    case(35, 36, 0, 0xf447_f8cc_1467_b9f5, 0x8820_1fb9_60ff_6465),
+    case(36, 37, -1, 0x4a57_2909_3f69_1943, 0xc430_85b5_f103_3f2d),
+    case(37, 38, 1, 0xb17f_48d7_c17f_d879, 0x911a_3cc4_3971_aa88),
+    case(38, 39, -2, 0xdfc6_f3d7_f13f_f771, 0xd67e_7f81_fb36_c4e9),
+    case(39, 40, 0, 0x3e1c_d9cf_1f3e_25cb, 0x8820_1fb9_60ff_6465),
+    case(40, 41, 0, 0x7f7b_68aa_dc5b_3098, 0x8820_1fb9_60ff_6465),
+    case(41, 42, 0, 0x705f_5296_c022_14c9, 0x8820_1fb9_60ff_6465),
+    case(42, 43, 0, 0xa4b0_455e_a27c_af66, 0x8820_1fb9_60ff_6465),
+    case(43, 44, 0, 0x986d_f2c2_35f8_652a, 0x8820_1fb9_60ff_6465),
+    case(44, 45, 0, 0x7d36_6b87_d352_1ae7, 0x8820_1fb9_60ff_6465),
+    case(45, 46, 1, 0x6879_5225_6951_ce1e, 0x76d8_cf40_55c3_70dc),
+    case(46, 47, 1, 0xbc97_264d_f19d_561f, 0x9407_0ed0_b01f_7f49),
+    case(47, 48, 3, 0x9950_47e1_3db1_8d2b, 0x719c_0529_5a51_700e),
+    case(48, 49, 2, 0xa535_c226_256a_21d3, 0xbb77_dffa_608b_805e),
+    case(0, 0, 0, 0x8820_1fb9_60ff_6465, 0x8820_1fb9_60ff_6465),
+    case(3, 3, 64, 0x40af_5d5e_1a64_6c25, 0x41e2_ed13_7e84_86a5),
+    case(5, 5, 10000, 0x0144_75ea_58cf_41c5, 0x53e6_656b_4185_8638),
+    case(12, 12, 100, 0x0bcf_48e1_c469_3cc9, 0x4635_a206_1dca_458d),
+    case(30, 30, 500, 0xfeea_f9c7_2606_b6ba, 0x7e03_b5e8_83d1_9f11),
+    case(44, 10, 10, 0x2a64_3e7d_4198_2660, 0xa307_c699_df9e_65fe),
+    case(44, 18, 10, 0x2a64_3e7d_4198_2660, 0x99c5_becf_45d7_8783),
+    case(45, 11, 49, 0xba09_d088_1c55_27d5, 0x3e28_8801_8314_1309),
+    case(45, 19, 49, 0x6617_6908_fd38_8e3d, 0x5096_01fe_b774_e305),
+    case(46, 12, 98, 0xf04e_a47b_0eb9_de37, 0x9db3_172a_d65d_f7ee),
+    case(46, 20, 98, 0x86d4_3afc_8521_e47b, 0xafea_588d_9898_1a75),
+    case(47, 13, 197, 0x9837_ae9f_41f8_4034, 0x9991_de31_9f0f_0f06),
+    case(47, 21, 197, 0x5ca9_9470_e594_1594, 0xa1d5_1711_34b6_9ff0),
+    case(48, 14, 484, 0x5a55_416a_41a2_7618, 0x8310_c761_12ca_fbe0),
+    case(48, 22, 484, 0x7cf8_e72b_a7d7_22f8, 0x54c8_35c1_6879_f6ba),
+    case(49, 15, 941, 0x2d14_eb43_1f67_f5e0, 0xb73e_88c4_c868_f860),
+    case(49, 23, 941, 0xec2b_b58e_6e93_fcfc, 0x0bf1_ef8d_7d84_6e96),
+    case(5, 1, 1, 0x0144_75ea_58cf_41c5, 0x5b2a_969b_42d2_38a4),
+    case(5, 2, 1, 0x0144_75ea_58cf_41c5, 0x6a3e_979b_adc5_d6f2),
+    case(5, 3, 64, 0x0144_75ea_58cf_41c5, 0x41e2_ed13_7e84_86a5),
+    case(5, 4, 0, 0x44b8_6728_274d_09ad, 0x513c_84ad_6fd4_0f45),
+    case(5, 20, 0, 0xceb5_8bea_046d_2025, 0x9b3a_0cb3_5e15_9f8e),
+    case(5, 30, -2, 0x7d05_5c3e_e97b_4ccc, 0xd35a_4cbd_86ac_e184),
+];
+
+/// Shorthand for the [`CASES`] table
+const fn case(a: usize, b: usize, dot: i32, bundle: u64, bind: u64) -> CorpusCase {
+    CorpusCase {
+        a,
+        b,
+        dot,
+        bundle,
+        bind,
+    }
+}
+
+/// The corpus vectors, in order
+///
+/// - 0: empty
+/// - 1, 2: single non-zeros at the first and last dimension
+/// - 3: alternating signs over `0..64`
+/// - 4: all positive over `0..100`
+/// - 5: non-zero in every dimension
+/// - 6..44: [`deterministic_sparse_vec`] with cycling sparsity
+/// - 44..50: bundles of earlier pairs, so some cases overlap
+pub fn vectors() -> Vec<SparseVec> {
+    let mut corpus = vec![
+        special_vectors::empty(),
+        special_vectors::single(0, Sign::Positive),
+        special_vectors::single(CORPUS_DIMS - 1, Sign::Negative),
+        special_vectors::alternating(64),
+        special_vectors::all_positive_upto(100),
+        special_vectors::max_density(CORPUS_DIMS),
+    ];
+    for i in corpus.len()..FIRST_DERIVED {
+        let nnz = SPARSITIES[i % SPARSITIES.len()];
+        corpus.push(deterministic_sparse_vec(CORPUS_DIMS, nnz, 1000 + i as u64));
+    }
+    for i in FIRST_DERIVED..CORPUS_LEN {
+        let k = i - FIRST_DERIVED;
+        let pair = [corpus[10 + k].clone(), corpus[18 + k].clone()];
+        corpus.push(reference_bundle_many(&pair));
+    }
+    corpus
+}
+
+/// Stable hash of `vectors` in order
+pub fn corpus_fingerprint(vectors: &[SparseVec]) -> u64 {
+    let fingerprints: Vec<u8> = vectors
+        .iter()
+        .flat_map(|v| sparse_vec_fingerprint(v).to_le_bytes())
+        .collect();
+    bytes_fingerprint(&fingerprints)
+}
+
+/// Run every [`CASES`] entry through `ops` and compare with the frozen results
+///
+/// Records one check per operation and case (named e.g. `bundle[10,11]`)
+/// plus `corpus.fingerprint`, which fails if the corpus itself drifted.
+pub fn verify_against(ops: &impl VsaOps) -> IntegrityReport {
+    let mut report = IntegrityReport::new();
+    let corpus = vectors();
+
+    let started = Instant::now();
+    let actual = corpus_fingerprint(&corpus);
+    report.record_check(
+        VALIDATOR,
+        "corpus.fingerprint",
+        started.elapsed(),
+        (actual != CORPUS_FINGERPRINT).then(|| {
+            format!(
+                "corpus drifted: fingerprint {:#018x}, expected {:#018x}",
+                actual, CORPUS_FINGERPRINT
+            )
+        }),
+    );
+
+    for case in CASES {
+        let (a, b) = (&corpus[case.a], &corpus[case.b]);
+        let pair = format!("[{},{}]", case.a, case.b);
+
+        let started = Instant::now();
+        let dot = ops.dot(a, b);
+        report.record_check(
+            VALIDATOR,
+            format!("dot{}", pair),
+            started.elapsed(),
+            (dot != case.dot).then(|| format!("dot{}: got {}, expected {}", pair, dot, case.dot)),
+        );
+
+        check_op(&mut report, "bundle", &pair, case.bundle, || {
+            ops.bundle(a, b)
+        });
+        check_op(&mut report, "bind", &pair, case.bind, || ops.bind(a, b));
+    }
+    report
+}
+
+/// Record whether `run` produced a vector with the `expected` fingerprint
+fn check_op(
+    report: &mut IntegrityReport,
+    op: &str,
+    pair: &str,
+    expected: u64,
+    run: impl FnOnce() -> SparseVec,
+) {
+    let started = Instant::now();
+    let result = run();
+    let actual = sparse_vec_fingerprint(&result);
+    report.record_check(
+        VALIDATOR,
+        format!("{}{}", op, pair),
+        started.elapsed(),
+        (actual != expected).then(|| {
+            format!(
+                "{}{}: fingerprint {:#018x} (nnz=+{}/-{}), expected {:#018x}",
+                op,
+                pair,
+                actual,
+                result.pos.len(),
+                result.neg.len(),
+                expected
+            )
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Drops the first positive index of every bundle
+    struct LossyBundle;
+
+    impl VsaOps for LossyBundle {
+        fn bundle(&self, a: &SparseVec, b: &SparseVec) -> SparseVec {
+            let mut v = CurrentVsa.bundle(a, b);
+            if !v.pos.is_empty() {
+                v.pos.remove(0);
+            }
+            v
+        }
+
+        fn bind(&self, a: &SparseVec, b: &SparseVec) -> SparseVec {
+            CurrentVsa.bind(a, b)
+        }
+    }
+
+    #[test]
+    fn test_corpus_shape() {
+        let corpus = vectors();
+        assert_eq!(corpus.len(), CORPUS_LEN);
+        assert_eq!(corpus_fingerprint(&corpus), CORPUS_FINGERPRINT);
+        let nnz: Vec<usize> = corpus.iter().map(|v| v.pos.len() + v.neg.len()).collect();
+        assert_eq!(nnz[0], 0);
+        assert_eq!(nnz[5], CORPUS_DIMS);
+        assert!(CASES.iter().all(|c| c.a < CORPUS_LEN && c.b < CORPUS_LEN));
+        assert!(CASES.iter().filter(|c| c.dot != 0).count() >= 20);
+    }
+
+    #[test]
+    fn test_current_vsa_matches_corpus() {
+        let report = verify_against(&CurrentVsa);
+        assert!(report.is_ok(), "{}", report.failures.join("\n"));
+        assert_eq!(report.checks_total, 1 + 3 * CASES.len() as u64);
+    }
+
+    #[test]
+    fn test_altered_delegate_fails() {
+        let report = verify_against(&LossyBundle);
+        assert!(!report.is_ok());
+        assert!(report.failures.iter().all(|f| f.starts_with("bundle[")));
+        assert!(report.failures_for("corpus").len() > 10);
+    }
+}
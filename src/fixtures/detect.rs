@@ -0,0 +1,303 @@
+//! Classify bytes back to the [`TestDataPattern`] that produced them
+
+use super::{pattern_byte, TestDataPattern};
+use std::fmt;
+
+/// Patterns tried by [`detect_pattern`]; seeded patterns need their seed
+const KNOWN_PATTERNS: [TestDataPattern; 6] = [
+    TestDataPattern::Zeros,
+    TestDataPattern::Ones,
+    TestDataPattern::Sequential,
+    TestDataPattern::Random,
+    TestDataPattern::Compressible,
+    TestDataPattern::Text,
+];
+
+/// Bytes compared per candidate; longer buffers are judged on a prefix
+const SAMPLE_LEN: usize = 4096;
+
+/// Offsets tried for patterns that never repeat
+const APERIODIC_OFFSETS: usize = 4096;
+
+/// Match fraction at which a variant counts as recognized
+const MATCH_THRESHOLD: f64 = 0.95;
+
+/// Shortest matching prefix reported as truncation
+const MIN_TRUNCATED_PREFIX: usize = 16;
+
+/// Offsets after which `pattern` repeats itself
+fn period(pattern: TestDataPattern) -> usize {
+    match pattern {
+        TestDataPattern::Zeros | TestDataPattern::Ones => 1,
+        TestDataPattern::Sequential | TestDataPattern::Random => 256,
+        TestDataPattern::Compressible => 45,
+        TestDataPattern::Text => 64,
+        TestDataPattern::Seeded(_) => APERIODIC_OFFSETS,
+    }
+}
+
+/// Fraction of `data` equal to `pattern` read through `index`
+fn match_fraction(data: &[u8], pattern: TestDataPattern, index: impl Fn(usize) -> usize) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let hits = data
+        .iter()
+        .enumerate()
+        .filter(|&(i, &b)| b == pattern_byte(pattern, index(i)))
+        .count();
+    hits as f64 / data.len() as f64
+}
+
+/// Position `i` with its `width`-byte word reversed (tail bytes unchanged)
+fn swapped_index(i: usize, width: usize, len: usize) -> usize {
+    let word = i / width * width;
+    if word + width > len {
+        i
+    } else {
+        word + width - 1 - i % width
+    }
+}
+
+/// Best match of a buffer against the known patterns
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PatternDetection {
+    /// Pattern that matched best
+    pub pattern: TestDataPattern,
+    /// Pattern position of the buffer's first byte
+    ///
+    /// Data that lost its first 7 bytes has offset 7.
+    pub offset: usize,
+    /// Fraction of sampled bytes agreeing with the pattern at `offset`
+    pub match_fraction: f64,
+}
+
+impl PatternDetection {
+    /// Whether the match is strong enough to trust
+    pub fn is_confident(&self) -> bool {
+        self.match_fraction >= MATCH_THRESHOLD
+    }
+}
+
+impl fmt::Display for PatternDetection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} at offset {} ({:.1}% match)",
+            self.pattern,
+            self.offset,
+            self.match_fraction * 100.0
+        )
+    }
+}
+
+/// Guess which [`TestDataPattern`] produced `data`, and at what offset
+///
+/// Tries every pattern except `Seeded` (whose seed cannot be guessed) at
+/// every offset within its period, comparing up to the first 4096 bytes.
+/// Ties go to the earlier pattern in declaration order and the lower
+/// offset. Use [`detect_pattern_among`] to include seeded patterns.
+///
+/// # Example
+/// ```rust,ignore
+/// let detection = detect_pattern(&extracted);
+/// eprintln!("extracted data looks like {}", detection);
+/// ```
+pub fn detect_pattern(data: &[u8]) -> PatternDetection {
+    detect_pattern_among(data, &KNOWN_PATTERNS)
+}
+
+/// [`detect_pattern`] over an explicit candidate list
+///
+/// Seeded candidates are tried at offsets `0..4096`.
+///
+/// # Panics
+/// Panics if `candidates` is empty.
+pub fn detect_pattern_among(data: &[u8], candidates: &[TestDataPattern]) -> PatternDetection {
+    assert!(!candidates.is_empty(), "no candidate patterns");
+    let sample = &data[..data.len().min(SAMPLE_LEN)];
+    let mut best: Option<PatternDetection> = None;
+
+    for &pattern in candidates {
+        for offset in 0..period(pattern) {
+            let fraction = match_fraction(sample, pattern, |i| i + offset);
+            if best.is_none_or(|b| fraction > b.match_fraction) {
+                best = Some(PatternDetection {
+                    pattern,
+                    offset,
+                    match_fraction: fraction,
+                });
+            }
+        }
+    }
+    best.expect("at least one candidate")
+}
+
+/// How a buffer relates to the pattern it was expected to contain
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PatternMismatch {
+    /// The data is the expected pattern, unshifted
+    Matches,
+    /// The expected pattern starting `offset` bytes in
+    Shifted { offset: usize, match_fraction: f64 },
+    /// The expected pattern with every `width`-byte word reversed
+    ByteSwapped { width: usize, match_fraction: f64 },
+    /// The expected pattern for `valid_len` bytes, then something else
+    Truncated { valid_len: usize },
+    /// Not a recognizable variant; `closest` is the best known pattern
+    Unrelated { closest: PatternDetection },
+}
+
+impl fmt::Display for PatternMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternMismatch::Matches => write!(f, "matches the expected pattern"),
+            PatternMismatch::Shifted {
+                offset,
+                match_fraction,
+            } => write!(
+                f,
+                "expected pattern shifted by {} bytes ({:.1}% match)",
+                offset,
+                match_fraction * 100.0
+            ),
+            PatternMismatch::ByteSwapped {
+                width,
+                match_fraction,
+            } => write!(
+                f,
+                "expected pattern with {}-byte words byte-swapped ({:.1}% match)",
+                width,
+                match_fraction * 100.0
+            ),
+            PatternMismatch::Truncated { valid_len } => write!(
+                f,
+                "expected pattern for the first {} bytes, then diverges",
+                valid_len
+            ),
+            PatternMismatch::Unrelated { closest } => {
+                write!(
+                    f,
+                    "not a variant of the expected pattern; closest is {}",
+                    closest
+                )
+            }
+        }
+    }
+}
+
+/// Explain how `data` differs from `expected`
+///
+/// Checks, in order: an exact match, a shift, a 2/4/8-byte byte swap, and
+/// a valid prefix followed by other content. Shifts and swaps count when
+/// at least 95% of the compared bytes agree.
+///
+/// # Example
+/// ```rust,ignore
+/// if extracted != original {
+///     panic!("extraction wrong: {}", explain_mismatch(TestDataPattern::Text, &extracted));
+/// }
+/// ```
+pub fn explain_mismatch(expected: TestDataPattern, data: &[u8]) -> PatternMismatch {
+    let sample = &data[..data.len().min(SAMPLE_LEN)];
+    let valid_len = data
+        .iter()
+        .enumerate()
+        .take_while(|&(i, &b)| b == pattern_byte(expected, i))
+        .count();
+    if valid_len == data.len() {
+        return PatternMismatch::Matches;
+    }
+
+    let shifted = detect_pattern_among(data, &[expected]);
+    if shifted.offset != 0 && shifted.is_confident() {
+        return PatternMismatch::Shifted {
+            offset: shifted.offset,
+            match_fraction: shifted.match_fraction,
+        };
+    }
+
+    for width in [2, 4, 8] {
+        let fraction = match_fraction(sample, expected, |i| swapped_index(i, width, sample.len()));
+        if fraction >= MATCH_THRESHOLD {
+            return PatternMismatch::ByteSwapped {
+                width,
+                match_fraction: fraction,
+            };
+        }
+    }
+
+    if valid_len >= MIN_TRUNCATED_PREFIX {
+        return PatternMismatch::Truncated { valid_len };
+    }
+    PatternMismatch::Unrelated {
+        closest: detect_pattern(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern_from(pattern: TestDataPattern, offset: usize, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| pattern_byte(pattern, i + offset))
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_shifted_sequential() {
+        let data = pattern_from(TestDataPattern::Sequential, 7, 10_000);
+        let detection = detect_pattern(&data);
+        assert_eq!(detection.pattern, TestDataPattern::Sequential);
+        assert_eq!(detection.offset, 7);
+        assert!(detection.is_confident());
+        assert_eq!(detection.match_fraction, 1.0);
+
+        assert_eq!(
+            explain_mismatch(TestDataPattern::Sequential, &data),
+            PatternMismatch::Shifted {
+                offset: 7,
+                match_fraction: 1.0
+            }
+        );
+        let text = pattern_from(TestDataPattern::Text, 3, 500);
+        assert_eq!(detect_pattern(&text).pattern, TestDataPattern::Text);
+        assert_eq!(detect_pattern(&text).offset, 3);
+    }
+
+    #[test]
+    fn test_explain_mismatch_variants() {
+        let expected = TestDataPattern::Compressible;
+        let clean = pattern_from(expected, 0, 1000);
+        assert_eq!(explain_mismatch(expected, &clean), PatternMismatch::Matches);
+
+        let swapped: Vec<u8> = clean
+            .chunks(4)
+            .flat_map(|w| w.iter().rev().copied())
+            .collect();
+        assert!(matches!(
+            explain_mismatch(expected, &swapped),
+            PatternMismatch::ByteSwapped { width: 4, .. }
+        ));
+
+        let mut truncated = clean.clone();
+        truncated[600..].fill(0);
+        assert_eq!(
+            explain_mismatch(expected, &truncated),
+            PatternMismatch::Truncated { valid_len: 600 }
+        );
+
+        let other = pattern_from(TestDataPattern::Sequential, 0, 1000);
+        match explain_mismatch(expected, &other) {
+            PatternMismatch::Unrelated { closest } => {
+                assert_eq!(closest.pattern, TestDataPattern::Sequential)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let seeded = pattern_from(TestDataPattern::Seeded(9), 12, 2000);
+        let detection = detect_pattern_among(&seeded, &[TestDataPattern::Seeded(9)]);
+        assert_eq!(detection.offset, 12);
+    }
+}
@@ -0,0 +1,220 @@
+//! On-disk footprint of generated datasets
+//!
+//! Filesystems allocate whole blocks and spend an inode per file, so a
+//! dataset of many tiny files occupies far more disk than its logical size.
+
+use super::{DatasetOptions, SELF_DESCRIBING_HEADER_LEN};
+use crate::error::{Error, IoResultExt, Result};
+use std::fs;
+use std::path::Path;
+
+/// Disk needed for a dataset, from [`estimate_disk_usage`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiskEstimate {
+    /// Sum of file lengths
+    pub logical: u64,
+    /// Logical size rounded up to whole blocks, plus per-file overhead
+    pub estimated_on_disk: u64,
+    /// Inodes used: one per file plus the dataset directory
+    pub inode_count: u64,
+}
+
+impl DiskEstimate {
+    /// `estimated_on_disk` over `logical` (1.0 for an empty dataset)
+    pub fn overhead_ratio(&self) -> f64 {
+        if self.logical == 0 {
+            1.0
+        } else {
+            self.estimated_on_disk as f64 / self.logical as f64
+        }
+    }
+}
+
+/// Filesystem allocation model used for disk estimates
+///
+/// Defaults to 4096-byte blocks and 256 bytes of inode and directory entry
+/// overhead per file, typical of ext4 and XFS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FsOverhead {
+    /// Allocation unit; every non-empty file occupies whole blocks
+    pub block_size: u64,
+    /// Metadata bytes charged per file and directory
+    pub per_file_bytes: u64,
+}
+
+impl Default for FsOverhead {
+    fn default() -> Self {
+        Self {
+            block_size: 4096,
+            per_file_bytes: 256,
+        }
+    }
+}
+
+impl FsOverhead {
+    /// Default allocation model
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate in `block_size`-byte blocks (at least 1)
+    pub fn with_block_size(mut self, block_size: u64) -> Self {
+        self.block_size = block_size.max(1);
+        self
+    }
+
+    /// Charge `bytes` of metadata per file
+    pub fn with_per_file_bytes(mut self, bytes: u64) -> Self {
+        self.per_file_bytes = bytes;
+        self
+    }
+
+    /// Disk needed for a `size_mb` dataset generated with `options`
+    pub fn estimate(&self, size_mb: usize, options: &DatasetOptions) -> DiskEstimate {
+        let mut logical = 0;
+        // The dataset directory itself takes one block
+        let mut on_disk = self.block_size + self.per_file_bytes;
+        let plan = options.file_plan(size_mb);
+        for (_, size) in &plan {
+            let size = if options.self_describing {
+                (*size).max(SELF_DESCRIBING_HEADER_LEN)
+            } else {
+                *size
+            } as u64;
+            logical += size;
+            on_disk += size.div_ceil(self.block_size) * self.block_size + self.per_file_bytes;
+        }
+        DiskEstimate {
+            logical,
+            estimated_on_disk: on_disk,
+            inode_count: plan.len() as u64 + 1,
+        }
+    }
+}
+
+/// Disk needed for a `size_mb` dataset, with the default [`FsOverhead`]
+///
+/// # Example
+/// ```rust,ignore
+/// let options = DatasetOptions::new().with_size_distribution(FileSizeDistribution::Fixed(1024));
+/// let estimate = estimate_disk_usage(1024, &options);
+/// println!("1 GB of 1KB files needs {} bytes on disk", estimate.estimated_on_disk);
+/// ```
+pub fn estimate_disk_usage(size_mb: usize, options: &DatasetOptions) -> DiskEstimate {
+    FsOverhead::default().estimate(size_mb, options)
+}
+
+/// Fail early if `dir` lacks room for a `size_mb` dataset
+///
+/// Compares the on-disk estimate, not the logical size, with the space
+/// available to unprivileged users on the filesystem holding `dir` (or its
+/// nearest existing ancestor). Passes when the free space cannot be
+/// determined.
+///
+/// # Errors
+/// Returns [`Error::DiskSpace`] with the estimated requirement.
+pub fn check_disk_space(
+    dir: &Path,
+    size_mb: usize,
+    options: &DatasetOptions,
+) -> Result<DiskEstimate> {
+    let estimate = estimate_disk_usage(size_mb, options);
+    ensure_disk_space(dir, estimate.estimated_on_disk)?;
+    Ok(estimate)
+}
+
+/// Fail with [`Error::DiskSpace`] if `dir` has less than `required` bytes free
+///
+/// Passes when the free space cannot be determined.
+pub(crate) fn ensure_disk_space(dir: &Path, required: u64) -> Result<()> {
+    match available_disk_space(dir) {
+        Some(available) if available < required => Err(Error::DiskSpace {
+            path: dir.to_path_buf(),
+            required,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Bytes actually allocated under `dir`, directories included
+///
+/// Sums allocated blocks (`st_blocks * 512`) on Unix and falls back to
+/// logical lengths elsewhere. Symlinks are not followed.
+pub fn measure_actual_disk_usage(dir: &Path) -> Result<u64> {
+    let mut total = 0;
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(path) = stack.pop() {
+        let meta = fs::symlink_metadata(&path).at_path(&path)?;
+        total += allocated_bytes(&meta);
+        if meta.is_dir() {
+            for entry in fs::read_dir(&path).at_path(&path)? {
+                stack.push(entry.at_path(&path)?.path());
+            }
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(unix)]
+fn allocated_bytes(meta: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_bytes(meta: &fs::Metadata) -> u64 {
+    meta.len()
+}
+
+/// Bytes available to unprivileged users on the filesystem holding `path`
+fn available_disk_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    fs4::available_space(existing).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{try_create_test_dataset_with, FileSizeDistribution, TestDataPattern};
+
+    #[test]
+    fn test_tiny_files_estimate_exceeds_logical() {
+        let options =
+            DatasetOptions::new().with_size_distribution(FileSizeDistribution::Fixed(1024));
+        let estimate = estimate_disk_usage(1, &options);
+        assert_eq!(estimate.logical, 1024 * 1024);
+        assert_eq!(estimate.inode_count, 1025);
+        // 1024 files of one 4KB block plus 256 bytes each, plus the directory
+        assert_eq!(estimate.estimated_on_disk, 1024 * (4096 + 256) + 4096 + 256);
+        assert!(estimate.overhead_ratio() > 4.0);
+
+        let large = estimate_disk_usage(1, &DatasetOptions::new());
+        assert!(large.overhead_ratio() < 1.05);
+
+        let coarse = FsOverhead::new()
+            .with_block_size(64 * 1024)
+            .with_per_file_bytes(0)
+            .estimate(1, &options);
+        assert_eq!(coarse.estimated_on_disk, 1025 * 64 * 1024);
+    }
+
+    #[test]
+    fn test_measure_and_check_disk_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let options =
+            DatasetOptions::new().with_size_distribution(FileSizeDistribution::Fixed(16 * 1024));
+        let estimate = check_disk_space(dir.path(), 1, &options).unwrap();
+        try_create_test_dataset_with(dir.path(), 1, TestDataPattern::Random, &options).unwrap();
+
+        let actual = measure_actual_disk_usage(dir.path()).unwrap();
+        assert!(actual > 0);
+        assert!(actual < estimate.estimated_on_disk * 2);
+
+        if available_disk_space(dir.path()).is_some() {
+            let huge =
+                DatasetOptions::new().with_size_distribution(FileSizeDistribution::Fixed(1 << 30));
+            let err = check_disk_space(&dir.path().join("missing"), 1 << 24, &huge).unwrap_err();
+            assert!(matches!(err, Error::DiskSpace { .. }));
+        }
+    }
+}
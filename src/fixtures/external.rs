@@ -0,0 +1,401 @@
+//! Standard external corpora fetched into a local cache
+//!
+//! Synthetic data never has the quirks of real files. [`fetch`] downloads a
+//! small standard corpus once, checks it against a pinned SHA-256, unpacks
+//! it and writes a [`ChecksumManifest`] next to the tree, so integrity
+//! tooling applies to it like to any generated dataset. Later calls are
+//! served from the cache without touching the network.
+//!
+//! ```rust,ignore
+//! let tree = external::fetch(ExternalCorpus::Canterbury, &cache_dir);
+//! ingest_and_verify(&tree);
+//! ```
+//!
+//! Set `TESTKIT_OFFLINE` (or use [`FetchOptions::with_offline`]) to forbid
+//! downloads; a corpus that is not cached then fails with
+//! [`Error::Fetch`] instead of hanging on the network.
+
+use crate::error::{Error, IoResultExt, Result};
+use crate::integrity::ChecksumManifest;
+use crate::metrics::format;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Environment variable that turns on offline mode in [`FetchOptions::new`]
+pub const OFFLINE_ENV: &str = "TESTKIT_OFFLINE";
+
+/// Name of the manifest written next to each unpacked corpus
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// A standard corpus [`fetch`] knows how to download
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExternalCorpus {
+    /// The Canterbury corpus: 11 small text and binary files (~3 MB)
+    Canterbury,
+    /// The large Canterbury corpus: E. coli, the King James bible, the CIA
+    /// world fact book (~11 MB)
+    CanterburyLarge,
+    /// Linux 6.6 source tree (~1.4 GB unpacked)
+    LinuxKernel,
+}
+
+impl ExternalCorpus {
+    /// Every corpus, smallest first
+    pub const ALL: [ExternalCorpus; 3] = [
+        ExternalCorpus::Canterbury,
+        ExternalCorpus::CanterburyLarge,
+        ExternalCorpus::LinuxKernel,
+    ];
+
+    /// Directory name under the cache
+    pub fn name(self) -> &'static str {
+        match self {
+            ExternalCorpus::Canterbury => "canterbury",
+            ExternalCorpus::CanterburyLarge => "canterbury-large",
+            ExternalCorpus::LinuxKernel => "linux-6.6",
+        }
+    }
+
+    /// Source of the `.tar.gz` archive
+    pub fn url(self) -> &'static str {
+        match self {
+            ExternalCorpus::Canterbury => {
+                "https://corpus.canterbury.ac.nz/resources/cantrbry.tar.gz"
+            }
+            ExternalCorpus::CanterburyLarge => {
+                "https://corpus.canterbury.ac.nz/resources/large.tar.gz"
+            }
+            ExternalCorpus::LinuxKernel => {
+                "https://cdn.kernel.org/pub/linux/kernel/v6.x/linux-6.6.tar.gz"
+            }
+        }
+    }
+
+    /// SHA-256 of the archive at [`url`](Self::url), as lowercase hex
+    ///
+    /// `None` until a digest has been verified against the published
+    /// archive; such a corpus needs [`FetchOptions::with_sha256`] or a pin
+    /// file (see [`try_fetch_with`]).
+    pub fn sha256(self) -> Option<&'static str> {
+        match self {
+            ExternalCorpus::Canterbury => None,
+            ExternalCorpus::CanterburyLarge => None,
+            ExternalCorpus::LinuxKernel => None,
+        }
+    }
+
+    /// Default cap on both the download and the unpacked tree
+    pub fn max_bytes(self) -> u64 {
+        match self {
+            ExternalCorpus::Canterbury => 16 << 20,
+            ExternalCorpus::CanterburyLarge => 64 << 20,
+            ExternalCorpus::LinuxKernel => 2 << 30,
+        }
+    }
+}
+
+/// How [`try_fetch_with`] may reach the network
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FetchOptions {
+    /// Never download; fail unless the corpus is already cached
+    pub offline: bool,
+    /// Cap on download and unpacked size; `None` uses the corpus default
+    pub max_bytes: Option<u64>,
+    /// Expected SHA-256 of the archive, as lowercase hex; overrides the
+    /// corpus pin
+    pub sha256: Option<String>,
+}
+
+impl FetchOptions {
+    /// Online unless [`OFFLINE_ENV`] is set
+    pub fn new() -> Self {
+        Self {
+            offline: std::env::var_os(OFFLINE_ENV).is_some(),
+            ..Self::default()
+        }
+    }
+
+    /// Forbid or allow downloads
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Override the corpus size cap
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Require the archive to hash to `sha256` instead of the corpus pin
+    pub fn with_sha256(mut self, sha256: impl Into<String>) -> Self {
+        self.sha256 = Some(sha256.into().to_ascii_lowercase());
+        self
+    }
+}
+
+/// Fetch `corpus` into `cache_dir` and return the unpacked tree
+///
+/// # Panics
+/// Panics if the corpus can be neither served from the cache nor fetched.
+pub fn fetch(corpus: ExternalCorpus, cache_dir: &Path) -> PathBuf {
+    try_fetch(corpus, cache_dir).unwrap_or_else(|e| panic!("Failed to fetch corpus: {}", e))
+}
+
+/// Fallible variant of [`fetch`]
+pub fn try_fetch(corpus: ExternalCorpus, cache_dir: &Path) -> Result<PathBuf> {
+    try_fetch_with(corpus, cache_dir, &FetchOptions::new())
+}
+
+/// Fetch `corpus` into `cache_dir/<name>/data` under `options`
+///
+/// A corpus counts as cached once its [`MANIFEST_FILE`] exists; that is
+/// written last, so an interrupted fetch is redone from scratch.
+///
+/// Every download must match a known SHA-256, or nothing is unpacked. The
+/// digest comes from [`FetchOptions::with_sha256`], else
+/// [`ExternalCorpus::sha256`], else the pin file `cache_dir/<name>.sha256`
+/// (see [`pin_path`]). Pin files are only read, never written, so a first
+/// download cannot pin whatever the server happened to return.
+pub fn try_fetch_with(
+    corpus: ExternalCorpus,
+    cache_dir: &Path,
+    options: &FetchOptions,
+) -> Result<PathBuf> {
+    let root = cache_dir.join(corpus.name());
+    let data = root.join("data");
+    if root.join(MANIFEST_FILE).is_file() && data.is_dir() {
+        return Ok(data);
+    }
+
+    let url = corpus.url();
+    let fail = |reason: String| Error::Fetch {
+        path: root.clone(),
+        url: url.to_string(),
+        reason,
+    };
+    if options.offline {
+        return Err(fail(format!(
+            "offline and not cached; fetch once with network access or unset {}",
+            OFFLINE_ENV
+        )));
+    }
+
+    // Resolve the digest first so an unpinned corpus fails before downloading
+    let expected = expected_sha256(corpus, cache_dir, options).map_err(&fail)?;
+    let max_bytes = options.max_bytes.unwrap_or(corpus.max_bytes());
+    fs::create_dir_all(&root).at_path(&root)?;
+    let archive = root.join("archive.tar.gz");
+    let result = download(url, &archive, max_bytes)
+        .and_then(|actual| check_digest(&expected, &actual))
+        .and_then(|()| unpack(&archive, &data, max_bytes));
+    fs::remove_file(&archive).ok();
+    result.map_err(fail)?;
+
+    let manifest = root.join(MANIFEST_FILE);
+    ChecksumManifest::capture(&data)
+        .and_then(|m| m.save(&manifest))
+        .at_path(&manifest)?;
+    Ok(data)
+}
+
+/// Path of the optional pin file for `corpus` in `cache_dir`
+///
+/// Holds the archive's SHA-256 as hex; write it by hand, e.g. from the
+/// publisher's checksum list.
+pub fn pin_path(corpus: ExternalCorpus, cache_dir: &Path) -> PathBuf {
+    cache_dir.join(format!("{}.sha256", corpus.name()))
+}
+
+/// Stream `url` into `dest`, returning the SHA-256 of what was written
+fn download(url: &str, dest: &Path, max_bytes: u64) -> std::result::Result<String, String> {
+    let response = reqwest::blocking::get(url)
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    if let Some(len) = response.content_length().filter(|&len| len > max_bytes) {
+        return Err(too_large("download", len, max_bytes));
+    }
+
+    let mut reader = HashingReader {
+        inner: response.take(max_bytes + 1),
+        hasher: Sha256::new(),
+    };
+    let mut file = fs::File::create(dest).map_err(|e| e.to_string())?;
+    let written = io::copy(&mut reader, &mut file).map_err(|e| e.to_string())?;
+    if written > max_bytes {
+        return Err(too_large("download", written, max_bytes));
+    }
+    Ok(hex::encode(reader.hasher.finalize()))
+}
+
+/// The SHA-256 a download of `corpus` must match, by the precedence
+/// documented on [`try_fetch_with`]
+fn expected_sha256(
+    corpus: ExternalCorpus,
+    cache_dir: &Path,
+    options: &FetchOptions,
+) -> std::result::Result<String, String> {
+    let pin = pin_path(corpus, cache_dir);
+    options
+        .sha256
+        .clone()
+        .or_else(|| corpus.sha256().map(str::to_string))
+        .or_else(|| {
+            fs::read_to_string(&pin)
+                .ok()
+                .map(|s| s.trim().to_ascii_lowercase())
+        })
+        .ok_or_else(|| {
+            format!(
+                "no sha256 pinned for {}; pass FetchOptions::with_sha256 or write {}",
+                corpus.name(),
+                pin.display()
+            )
+        })
+}
+
+fn check_digest(expected: &str, actual: &str) -> std::result::Result<(), String> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!(
+            "sha256 mismatch: expected {}, got {}",
+            expected, actual
+        ))
+    }
+}
+
+/// Unpack a `.tar.gz` into a fresh `dest`, refusing trees over `max_bytes`
+fn unpack(archive: &Path, dest: &Path, max_bytes: u64) -> std::result::Result<(), String> {
+    if dest.exists() {
+        fs::remove_dir_all(dest).map_err(|e| e.to_string())?;
+    }
+    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+
+    let file = fs::File::open(archive).map_err(|e| e.to_string())?;
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    let mut total = 0u64;
+    for entry in tar.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        total += entry.header().size().map_err(|e| e.to_string())?;
+        if total > max_bytes {
+            return Err(too_large("unpacked tree", total, max_bytes));
+        }
+        // unpack_in refuses entries that would land outside `dest`
+        entry.unpack_in(dest).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn too_large(what: &str, len: u64, max_bytes: u64) -> String {
+    format!(
+        "{} exceeds the {} cap ({} so far)",
+        what,
+        format::bytes(max_bytes),
+        format::bytes(len)
+    )
+}
+
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_offline_cold_cache_errors_clearly() {
+        let cache = TempDir::new().unwrap();
+        let offline = FetchOptions::new().with_offline(true);
+        let err = try_fetch_with(ExternalCorpus::Canterbury, cache.path(), &offline).unwrap_err();
+        assert!(matches!(err, Error::Fetch { .. }));
+        assert_eq!(err.path(), cache.path().join("canterbury"));
+        assert!(
+            err.to_string().contains("offline and not cached"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_expected_sha256_requires_a_known_digest() {
+        let cache = TempDir::new().unwrap();
+        let corpus = ExternalCorpus::Canterbury;
+        let expected = |options: &FetchOptions| expected_sha256(corpus, cache.path(), options);
+
+        if corpus.sha256().is_none() {
+            let err = expected(&FetchOptions::default()).unwrap_err();
+            assert!(err.contains("no sha256 pinned"), "{}", err);
+        }
+        let given = FetchOptions::default().with_sha256("AB".repeat(32));
+        assert_eq!(expected(&given), Ok("ab".repeat(32)));
+
+        // A hand-written pin file is honored after the built-in digest
+        let pin = pin_path(corpus, cache.path());
+        fs::write(&pin, format!("{}\n", "CD".repeat(32))).unwrap();
+        let pinned = corpus.sha256().map_or("cd".repeat(32), str::to_string);
+        assert_eq!(expected(&FetchOptions::default()), Ok(pinned));
+        assert_eq!(expected(&given), Ok("ab".repeat(32)));
+
+        assert_eq!(check_digest("ab", "ab"), Ok(()));
+        assert!(check_digest("ab", "cd")
+            .unwrap_err()
+            .contains("sha256 mismatch"));
+    }
+
+    #[test]
+    fn test_unpinned_corpus_fails_before_downloading() {
+        let cache = TempDir::new().unwrap();
+        for corpus in ExternalCorpus::ALL
+            .into_iter()
+            .filter(|c| c.sha256().is_none())
+        {
+            let err = try_fetch_with(corpus, cache.path(), &FetchOptions::default()).unwrap_err();
+            assert!(err.to_string().contains("no sha256 pinned"), "{}", err);
+            assert!(!cache.path().join(corpus.name()).exists());
+        }
+    }
+
+    #[test]
+    #[ignore = "downloads the Canterbury corpus"]
+    fn test_fetch_canterbury_checks_digest_and_serves_from_cache() {
+        let cache = TempDir::new().unwrap();
+        let corpus = ExternalCorpus::Canterbury;
+        let pinned = corpus.sha256().expect("Canterbury ships a built-in digest");
+
+        // A wrong digest unpacks nothing
+        let wrong = FetchOptions::default().with_sha256("0".repeat(64));
+        let err = try_fetch_with(corpus, cache.path(), &wrong).unwrap_err();
+        assert!(
+            err.to_string().contains(&format!("got {}", pinned)),
+            "{}",
+            err
+        );
+        assert!(!cache.path().join(corpus.name()).join("data").exists());
+        assert!(!pin_path(corpus, cache.path()).exists());
+
+        let data = try_fetch_with(corpus, cache.path(), &FetchOptions::default()).unwrap();
+        let manifest = ChecksumManifest::load(&data.with_file_name(MANIFEST_FILE)).unwrap();
+        assert!(!manifest.is_empty());
+        assert!(manifest.verify(&data).unwrap().is_ok());
+
+        let offline = FetchOptions::new().with_offline(true);
+        assert_eq!(
+            try_fetch_with(corpus, cache.path(), &offline).unwrap(),
+            data
+        );
+    }
+}
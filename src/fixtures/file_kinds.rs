@@ -0,0 +1,490 @@
+//! Typed file content for extension/content sniffing tests
+//!
+//! Each [`FileKind`] has a generator producing content of an exact size and
+//! a validator recognizing it again. Binary kinds are structurally shaped
+//! (magic numbers, headers, trailers) but not complete files: PNG CRCs are
+//! zero and ZIP entries are not indexed by a central directory.
+
+use super::{seeded_content, DatasetManifest, DatasetOptions, ManifestEntry, SeededFileKind};
+use crate::error::{Error, Result};
+use crate::generators::generate_noise_pattern;
+use crate::integrity::{IntegrityReport, MismatchKind};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+const PNG_IEND: &[u8; 12] = b"\0\0\0\0IEND\xae\x42\x60\x82";
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+const ZIP_LOCAL_HEADER: &[u8; 4] = b"PK\x03\x04";
+const ZIP_END_OF_DIRECTORY: &[u8; 4] = b"PK\x05\x06";
+const ZIP_END_LEN: usize = 22;
+
+/// Smallest file [`typed_dataset_entries`] writes: the largest
+/// [`FileKind::min_len`]
+const MIN_TYPED_FILE_LEN: usize = 64;
+
+/// Content type of a generated file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    /// Prose-like ASCII text
+    Text,
+    /// JSON lines records
+    Json,
+    /// Comma-separated rows under a header
+    Csv,
+    /// Timestamped log lines
+    Log,
+    /// PNG signature, IHDR, one IDAT chunk of noise and IEND
+    Png,
+    /// ELF64 header followed by noise
+    Elf,
+    /// One stored ZIP entry and an end-of-central-directory record
+    Zip,
+    /// Pseudo-random bytes
+    Random,
+}
+
+impl FileKind {
+    /// Every kind, in declaration order
+    pub const ALL: [FileKind; 8] = [
+        FileKind::Text,
+        FileKind::Json,
+        FileKind::Csv,
+        FileKind::Log,
+        FileKind::Png,
+        FileKind::Elf,
+        FileKind::Zip,
+        FileKind::Random,
+    ];
+
+    /// File extension used for this kind
+    pub fn extension(self) -> &'static str {
+        match self {
+            FileKind::Text => "txt",
+            FileKind::Json => "jsonl",
+            FileKind::Csv => "csv",
+            FileKind::Log => "log",
+            FileKind::Png => "png",
+            FileKind::Elf => "elf",
+            FileKind::Zip => "zip",
+            FileKind::Random => "bin",
+        }
+    }
+
+    /// Kind whose [`extension`](Self::extension) is `ext`
+    pub fn from_extension(ext: &str) -> Option<FileKind> {
+        Self::ALL.into_iter().find(|kind| kind.extension() == ext)
+    }
+
+    /// Closest [`SeededFileKind`], recorded in [`ManifestEntry::kind`]
+    pub fn seeded_kind(self) -> SeededFileKind {
+        match self {
+            FileKind::Text | FileKind::Csv => SeededFileKind::Text,
+            FileKind::Json => SeededFileKind::Json,
+            FileKind::Log => SeededFileKind::Log,
+            FileKind::Png | FileKind::Elf | FileKind::Zip | FileKind::Random => {
+                SeededFileKind::Binary
+            }
+        }
+    }
+
+    /// Smallest file this kind produces, and still [`sniff`](Self::sniff)s as it
+    pub fn min_len(self) -> usize {
+        match self {
+            FileKind::Text | FileKind::Log => 1,
+            // One complete record, the header row
+            FileKind::Json => 64,
+            FileKind::Csv => 15,
+            FileKind::Png => PNG_SIGNATURE.len() + 25 + 12 + PNG_IEND.len(),
+            FileKind::Elf => 64,
+            FileKind::Zip => 30 + 8 + ZIP_END_LEN,
+            // Short noise can happen to be all ASCII
+            FileKind::Random => 64,
+        }
+    }
+
+    /// Deterministic content of `size` bytes (at least [`min_len`](Self::min_len))
+    pub fn generate(self, size: usize, seed: u64) -> Vec<u8> {
+        let size = size.max(self.min_len());
+        match self {
+            FileKind::Text | FileKind::Json | FileKind::Log => {
+                seeded_content(self.seeded_kind(), size, seed)
+            }
+            FileKind::Csv => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut out = b"id,value,score\n".to_vec();
+                let mut row = 0u64;
+                while out.len() < size {
+                    let line = format!(
+                        "{},{},{:.3}\n",
+                        row,
+                        rng.random_range(0..100_000),
+                        rng.random::<f64>()
+                    );
+                    out.extend_from_slice(line.as_bytes());
+                    row += 1;
+                }
+                out.truncate(size);
+                out
+            }
+            FileKind::Png => {
+                let payload = size - self.min_len();
+                let mut out = PNG_SIGNATURE.to_vec();
+                out.extend_from_slice(&13u32.to_be_bytes());
+                out.extend_from_slice(b"IHDR");
+                out.extend_from_slice(&64u32.to_be_bytes());
+                out.extend_from_slice(&64u32.to_be_bytes());
+                out.extend_from_slice(&[8, 2, 0, 0, 0]);
+                out.extend_from_slice(&[0; 4]);
+                out.extend_from_slice(&(payload as u32).to_be_bytes());
+                out.extend_from_slice(b"IDAT");
+                out.extend(generate_noise_pattern(payload, seed));
+                out.extend_from_slice(&[0; 4]);
+                out.extend_from_slice(PNG_IEND);
+                out
+            }
+            FileKind::Elf => {
+                let mut out = vec![0u8; 64];
+                out[0..4].copy_from_slice(ELF_MAGIC);
+                out[4] = 2; // ELFCLASS64
+                out[5] = 1; // little-endian
+                out[6] = 1; // EV_CURRENT
+                out[16..18].copy_from_slice(&2u16.to_le_bytes()); // ET_EXEC
+                out[18..20].copy_from_slice(&0x3Eu16.to_le_bytes()); // x86-64
+                out[20..24].copy_from_slice(&1u32.to_le_bytes());
+                out[52..54].copy_from_slice(&64u16.to_le_bytes());
+                out.extend(generate_noise_pattern(size - 64, seed));
+                out
+            }
+            FileKind::Zip => {
+                let payload = size - self.min_len();
+                let mut out = ZIP_LOCAL_HEADER.to_vec();
+                out.extend_from_slice(&[20, 0, 0, 0, 0, 0]); // version, flags, stored
+                out.extend_from_slice(&[0; 8]); // time, date, crc
+                out.extend_from_slice(&(payload as u32).to_le_bytes());
+                out.extend_from_slice(&(payload as u32).to_le_bytes());
+                out.extend_from_slice(&8u16.to_le_bytes());
+                out.extend_from_slice(&0u16.to_le_bytes());
+                out.extend_from_slice(b"data.bin");
+                out.extend(generate_noise_pattern(payload, seed));
+                out.extend_from_slice(ZIP_END_OF_DIRECTORY);
+                out.extend_from_slice(&[0; ZIP_END_LEN - 4]);
+                out
+            }
+            FileKind::Random => generate_noise_pattern(size, seed),
+        }
+    }
+
+    /// Kind that `data` looks like
+    ///
+    /// Binary kinds are recognized by their magic numbers and trailers.
+    /// ASCII content is classified by its complete lines (a trailing
+    /// partial line is ignored): JSON objects, then rows with a constant
+    /// number of commas, then `[`-prefixed log lines, then plain text.
+    /// Anything else is [`FileKind::Random`].
+    pub fn sniff(data: &[u8]) -> FileKind {
+        if data.starts_with(PNG_SIGNATURE) && data.get(12..16) == Some(b"IHDR") {
+            return FileKind::Png;
+        }
+        if data.starts_with(ELF_MAGIC)
+            && data.len() >= 64
+            && matches!(data[4], 1 | 2)
+            && matches!(data[5], 1 | 2)
+            && data[6] == 1
+        {
+            return FileKind::Elf;
+        }
+        if data.starts_with(ZIP_LOCAL_HEADER)
+            && data.len() >= 30 + ZIP_END_LEN
+            && data[data.len() - ZIP_END_LEN..].starts_with(ZIP_END_OF_DIRECTORY)
+        {
+            return FileKind::Zip;
+        }
+        let is_text = |b: &u8| b.is_ascii_graphic() || matches!(b, b' ' | b'\n' | b'\t');
+        if data.is_empty() || !data.iter().all(is_text) {
+            return FileKind::Random;
+        }
+
+        let mut lines: Vec<&[u8]> = data.split(|&b| b == b'\n').collect();
+        if lines.len() > 1 {
+            // The last element is empty or a partial line
+            lines.pop();
+        }
+        let commas = |line: &[u8]| line.iter().filter(|&&b| b == b',').count();
+        if lines.iter().all(|line| {
+            serde_json::from_slice::<serde_json::Value>(line).is_ok_and(|v| v.is_object())
+        }) {
+            FileKind::Json
+        } else if commas(lines[0]) > 0 && lines.iter().all(|line| commas(line) == commas(lines[0]))
+        {
+            FileKind::Csv
+        } else if lines.iter().all(|line| line.starts_with(b"[")) {
+            FileKind::Log
+        } else {
+            FileKind::Text
+        }
+    }
+
+    /// Whether `data` is recognized as this kind
+    pub fn validate(self, data: &[u8]) -> bool {
+        Self::sniff(data) == self
+    }
+}
+
+/// Flat dataset with kinds drawn from [`DatasetOptions::type_mix`]
+///
+/// Sizes follow the seeded generator (256B-256KB up to `size_mb` MB); a
+/// remainder under [`MIN_TYPED_FILE_LEN`] is folded into the last file, so
+/// every file is large enough to be recognized as its kind.
+/// Files are marked for a wrong extension at evenly spaced indices so that
+/// the first `n` files always contain `floor(n * mismatch_fraction)` of them.
+pub(crate) fn typed_dataset_entries(
+    root: &Path,
+    size_mb: usize,
+    seed: u64,
+    options: &DatasetOptions,
+) -> Result<impl Iterator<Item = (ManifestEntry, Vec<u8>)>> {
+    let total_weight: f64 = options.type_mix.iter().map(|&(_, w)| w).sum();
+    if options
+        .type_mix
+        .iter()
+        .any(|&(_, w)| !w.is_finite() || w < 0.0)
+        || total_weight <= 0.0
+    {
+        return Err(Error::invalid_spec(
+            root,
+            "type_mix weights must be finite, non-negative and not all zero",
+        ));
+    }
+    let mismatch_fraction = options.mismatch_fraction;
+    if !(0.0..=1.0).contains(&mismatch_fraction) {
+        return Err(Error::invalid_spec(
+            root,
+            format!(
+                "mismatch_fraction must be within [0, 1], got {}",
+                mismatch_fraction
+            ),
+        ));
+    }
+
+    let mix = options.type_mix.clone();
+    let target = size_mb * 1024 * 1024;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut written = 0;
+    let mut index = 0usize;
+
+    Ok(std::iter::from_fn(move || {
+        if written >= target {
+            return None;
+        }
+        let mut pick = rng.random::<f64>() * total_weight;
+        let kind = mix
+            .iter()
+            .find(|&&(_, w)| {
+                pick -= w;
+                pick < 0.0
+            })
+            .or_else(|| mix.iter().rev().find(|&&(_, w)| w > 0.0))
+            .map(|&(kind, _)| kind)
+            .expect("total weight is positive");
+        // A remainder too small for every kind joins this file instead
+        let remaining = target - written;
+        let mut size = (256 + rng.random_range(0..256 * 1024)).min(remaining);
+        if remaining - size < MIN_TYPED_FILE_LEN {
+            size = remaining;
+        }
+        let content = kind.generate(size, rng.random());
+
+        let mismatched = (((index + 1) as f64 * mismatch_fraction).floor() as usize)
+            > ((index as f64 * mismatch_fraction).floor() as usize);
+        let extension = if mismatched {
+            let others: Vec<FileKind> = FileKind::ALL.into_iter().filter(|&k| k != kind).collect();
+            others[rng.random_range(0..others.len())].extension()
+        } else {
+            kind.extension()
+        };
+
+        let entry = ManifestEntry {
+            path: format!("file_{:05}.{}", index, extension),
+            size: content.len() as u64,
+            kind: kind.seeded_kind(),
+            file_kind: Some(kind),
+            extension_mismatch: mismatched,
+            pattern: None,
+        };
+        written += content.len();
+        index += 1;
+        Some((entry, content))
+    }))
+}
+
+/// Outcome of [`verify_dataset_against_manifest`]
+#[derive(Clone, Debug, Default)]
+pub struct DatasetTypeReport {
+    /// Presence and size checks plus one content and one extension check
+    /// per typed file
+    pub report: IntegrityReport,
+    /// Typed files whose extension names their content's kind
+    pub consistent: Vec<String>,
+    /// Typed files whose extension names a different kind
+    pub mismatched: Vec<String>,
+}
+
+/// Check `dir` against `manifest`, including the content of typed files
+///
+/// Runs [`DatasetManifest::verify`], then for every entry with a
+/// [`file_kind`](ManifestEntry::file_kind) checks that the content is
+/// recognized as that kind by [`FileKind::validate`], and that the
+/// extension disagrees with it exactly when the entry is flagged
+/// [`extension_mismatch`](ManifestEntry::extension_mismatch). Files are
+/// sorted into the two populations by what is on disk, not by the flag.
+///
+/// # Example
+/// ```rust,ignore
+/// let manifest = DatasetManifest::load(&DatasetManifest::path_for(&dir))?;
+/// let types = verify_dataset_against_manifest(&dir, &manifest)?;
+/// assert!(types.report.is_ok());
+/// for path in &types.mismatched {
+///     assert!(my_sniffer(&dir.join(path)).ignores_extension());
+/// }
+/// ```
+pub fn verify_dataset_against_manifest(
+    dir: &Path,
+    manifest: &DatasetManifest,
+) -> Result<DatasetTypeReport> {
+    let mut out = DatasetTypeReport {
+        report: manifest.verify(dir)?,
+        ..DatasetTypeReport::default()
+    };
+
+    for entry in &manifest.files {
+        let Some(kind) = entry.file_kind else {
+            continue;
+        };
+        let path = dir.join(&entry.path);
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            // Already reported as missing
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(Error::io(&path, e)),
+        };
+
+        let found = FileKind::sniff(&data);
+        if found == kind {
+            out.report.pass();
+        } else {
+            out.report
+                .record_file_mismatch(entry.path.as_str(), MismatchKind::Content);
+        }
+
+        let named = Path::new(&entry.path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(FileKind::from_extension);
+        let extension_mismatch = named != Some(found);
+        if extension_mismatch == entry.extension_mismatch {
+            out.report.pass();
+        } else {
+            out.report.fail(format!(
+                "{}: extension mismatch is {}, manifest says {}",
+                entry.path, extension_mismatch, entry.extension_mismatch
+            ));
+        }
+        if extension_mismatch {
+            out.mismatched.push(entry.path.clone());
+        } else {
+            out.consistent.push(entry.path.clone());
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_kinds_are_recognized() {
+        for kind in FileKind::ALL {
+            for size in [0, 100, 4097] {
+                let data = kind.generate(size, 5);
+                assert_eq!(data.len(), size.max(kind.min_len()), "{:?}", kind);
+                assert_eq!(FileKind::sniff(&data), kind, "{:?} at {} bytes", kind, size);
+                assert_eq!(data, kind.generate(size, 5));
+            }
+            assert_eq!(FileKind::from_extension(kind.extension()), Some(kind));
+        }
+        let tiny = FileKind::Zip.generate(0, 1);
+        assert_eq!(tiny.len(), FileKind::Zip.min_len());
+        assert!(FileKind::Zip.validate(&tiny));
+        assert_eq!(
+            FileKind::ALL.map(FileKind::min_len).into_iter().max(),
+            Some(MIN_TYPED_FILE_LEN)
+        );
+    }
+
+    #[test]
+    fn test_last_typed_file_is_never_too_small() {
+        let root = Path::new("unused");
+        let options = DatasetOptions::new().with_type_mix(vec![(FileKind::Json, 1.0)]);
+        for seed in 0..20 {
+            let entries: Vec<_> = typed_dataset_entries(root, 1, seed, &options)
+                .unwrap()
+                .collect();
+            let total: usize = entries.iter().map(|(_, content)| content.len()).sum();
+            assert_eq!(total, 1024 * 1024);
+            for (entry, content) in &entries {
+                assert!(content.len() >= MIN_TYPED_FILE_LEN, "{}", entry.path);
+                assert_eq!(FileKind::sniff(content), FileKind::Json, "{}", entry.path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed_dataset_with_wrong_extensions() {
+        let harness = crate::TestHarness::new();
+        let options = DatasetOptions::new()
+            .with_type_mix(FileKind::ALL.map(|kind| (kind, 1.0)).to_vec())
+            .with_mismatch_fraction(0.2)
+            .max_files(30);
+        let dataset = harness
+            .try_create_dataset_seeded_with(8, 21, &options)
+            .unwrap();
+        assert_eq!(dataset.files, 30);
+
+        let manifest = DatasetManifest::load(&DatasetManifest::path_for(&dataset.path)).unwrap();
+        let flagged: Vec<&str> = manifest
+            .files
+            .iter()
+            .filter(|f| f.extension_mismatch)
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(flagged.len(), 6);
+        let kinds: std::collections::HashSet<_> =
+            manifest.files.iter().filter_map(|f| f.file_kind).collect();
+        assert!(kinds.len() >= 6, "{:?}", kinds);
+
+        let types = verify_dataset_against_manifest(&dataset.path, &manifest).unwrap();
+        assert!(types.report.is_ok(), "{:?}", types.report.failures);
+        assert_eq!(types.mismatched, flagged);
+        assert_eq!(types.consistent.len(), 24);
+
+        // Swap the contents of a consistent file for another kind
+        let victim = &types.consistent[0];
+        let entry = manifest.files.iter().find(|f| &f.path == victim).unwrap();
+        let other = FileKind::ALL
+            .into_iter()
+            .find(|&k| Some(k) != entry.file_kind)
+            .unwrap();
+        let path = dataset.path.join(victim);
+        fs::write(&path, other.generate(entry.size as usize, 1)).unwrap();
+        assert_eq!(FileKind::sniff(&fs::read(&path).unwrap()), other);
+        let types = verify_dataset_against_manifest(&dataset.path, &manifest).unwrap();
+        assert!(!types.report.is_ok());
+        assert!(types.mismatched.contains(victim));
+    }
+}
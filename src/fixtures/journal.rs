@@ -0,0 +1,121 @@
+//! Progress journal for resumable dataset generation
+//!
+//! With [`DatasetOptions::resumable`] set,
+//! [`try_create_test_dataset_with`](super::try_create_test_dataset_with)
+//! records how many planned files are complete, saving at most every 64
+//! files or every second and when it stops early. A later run with the same
+//! inputs reads the journal, keeps the files whose contents still match the
+//! plan and continues from the first missing one. The journal is removed
+//! once generation completes.
+
+use super::{DatasetOptions, TestDataPattern};
+use crate::error::{Error, IoResultExt, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether a generation run started from scratch or picked up a partial one
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GenerationStart {
+    /// No usable journal; every file was written by this run
+    #[default]
+    Fresh,
+    /// A journal from an interrupted run with the same inputs was found
+    Resumed {
+        /// Files kept from the earlier run
+        skipped_files: usize,
+        /// Journaled files whose contents no longer matched and were rewritten
+        regenerated_files: usize,
+    },
+}
+
+impl GenerationStart {
+    /// Whether an earlier partial run was continued
+    pub fn is_resumed(&self) -> bool {
+        matches!(self, GenerationStart::Resumed { .. })
+    }
+
+    /// Files kept from the earlier run (0 when fresh)
+    pub fn skipped_files(&self) -> usize {
+        match *self {
+            GenerationStart::Fresh => 0,
+            GenerationStart::Resumed { skipped_files, .. } => skipped_files,
+        }
+    }
+}
+
+/// Partial generation state, written next to the dataset directory
+///
+/// Lives outside the dataset tree (as `<dir>.journal.json`) so an
+/// interrupted dataset can still be ingested without picking it up.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerationJournal {
+    /// Requested dataset size
+    pub size_mb: usize,
+    /// Content pattern (`Debug` form)
+    pub pattern: String,
+    /// Size sampling and header seed
+    pub seed: u64,
+    /// File size distribution (`Debug` form)
+    pub size_distribution: String,
+    /// Whether files carry self-describing headers
+    pub self_describing: bool,
+    /// Leading files of the plan that were completely written
+    pub completed_files: usize,
+}
+
+impl GenerationJournal {
+    /// Journal location for a dataset directory
+    ///
+    /// An existing directory is canonicalized first, so a relative path
+    /// like `.` still gets a journal beside the directory, not inside it.
+    pub fn path_for(dataset_dir: &Path) -> PathBuf {
+        let dir = fs::canonicalize(dataset_dir).unwrap_or_else(|_| dataset_dir.to_path_buf());
+        let mut name = dir.file_name().unwrap_or_default().to_os_string();
+        name.push(".journal.json");
+        dir.with_file_name(name)
+    }
+
+    /// Empty journal for the inputs that determine file contents
+    ///
+    /// Progress, time budget and file cap do not affect contents, so runs
+    /// differing only in those can resume each other.
+    pub fn new(size_mb: usize, pattern: TestDataPattern, options: &DatasetOptions) -> Self {
+        Self {
+            size_mb,
+            pattern: format!("{:?}", pattern),
+            seed: options.seed,
+            size_distribution: format!("{:?}", options.size_distribution),
+            self_describing: options.self_describing,
+            completed_files: 0,
+        }
+    }
+
+    /// Whether `other` describes the same dataset, ignoring progress
+    pub fn same_inputs(&self, other: &Self) -> bool {
+        Self {
+            completed_files: 0,
+            ..self.clone()
+        } == Self {
+            completed_files: 0,
+            ..other.clone()
+        }
+    }
+
+    /// Save as JSON, replacing any previous journal atomically
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content =
+            serde_json::to_string(self).map_err(|e| Error::io(path, std::io::Error::from(e)))?;
+        let mut tmp = path.as_os_str().to_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        fs::write(&tmp, content).at_path(&tmp)?;
+        fs::rename(&tmp, path).at_path(path)
+    }
+
+    /// Load from JSON
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).at_path(path)?;
+        serde_json::from_str(&content).map_err(|e| Error::io(path, std::io::Error::from(e)))
+    }
+}
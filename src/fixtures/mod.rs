@@ -0,0 +1,2644 @@
+//! Test data fixtures and dataset generation
+//!
+//! Provides utilities for creating test datasets:
+//! - Various data patterns (zeros, sequential, random, text, etc.)
+//! - File generation with controlled sizes
+//! - Realistic test data scenarios
+//! - Seeded, reproducible dataset layouts with manifests
+//! - A frozen corpus of vectors with expected operation results ([`corpus`])
+//! - Detection of which pattern produced a buffer ([`detect_pattern`])
+//! - On-disk size estimates and free-space preflight ([`estimate_disk_usage`])
+//! - Resuming interrupted generation from a progress journal ([`GenerationJournal`])
+//! - Compact binary files of sparse vectors ([`VectorCorpusFile`])
+//! - Typed file content and extension/content mismatches ([`FileKind`])
+//! - Streaming pattern output in fixed-size blocks ([`write_pattern_to`])
+//! - Clean/corrupted payload pairs for decoder robustness ([`robustness_suite`])
+//! - Pinned downloads of standard external corpora (`external`, `net` feature)
+
+pub mod corpus;
+mod detect;
+mod disk;
+#[cfg(feature = "net")]
+pub mod external;
+mod file_kinds;
+mod journal;
+mod robustness;
+mod vector_file;
+
+pub use detect::{
+    detect_pattern, detect_pattern_among, explain_mismatch, PatternDetection, PatternMismatch,
+};
+#[cfg(feature = "large-scale")]
+pub(crate) use disk::ensure_disk_space;
+pub use disk::{
+    check_disk_space, estimate_disk_usage, measure_actual_disk_usage, DiskEstimate, FsOverhead,
+};
+pub(crate) use file_kinds::typed_dataset_entries;
+pub use file_kinds::{verify_dataset_against_manifest, DatasetTypeReport, FileKind};
+pub use journal::{GenerationJournal, GenerationStart};
+pub use robustness::{
+    robustness_suite, run_robustness_cases, run_robustness_suite, CorruptionRecipe,
+    ExpectedOutcome, RobustnessCase, DEFAULT_ROBUSTNESS_SEED,
+};
+pub use vector_file::{VectorCorpusFile, VectorCorpusReader};
+
+use crate::cancel::CancellationToken;
+use crate::error::{Error, IoResultExt, Result};
+use crate::generators::generate_noise_pattern;
+use crate::integrity::{IntegrityReport, MismatchKind};
+use crate::metrics::TestMetrics;
+use crate::progress::{ProgressOptions, ProgressSink};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Test data patterns for file generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestDataPattern {
+    /// All zeros
+    Zeros,
+    /// All ones (0xFF)
+    Ones,
+    /// Sequential bytes (0, 1, 2, ..., 255, 0, 1, ...)
+    Sequential,
+    /// Pseudo-random pattern (deterministic, identical in every test)
+    Random,
+    /// Pseudo-random pattern keyed by a seed: byte `i` is a hash of `(seed, i)`
+    ///
+    /// Different seeds give unrelated content, so datasets from different
+    /// tests do not accidentally deduplicate against each other.
+    Seeded(u64),
+    /// Compressible repeating text
+    Compressible,
+    /// ASCII text pattern
+    Text,
+}
+
+/// Create test data with specified pattern
+///
+/// # Arguments
+/// * `size_mb` - Size in megabytes
+/// * `pattern` - Data pattern to generate
+///
+/// # Returns
+/// Vector of bytes with the specified pattern
+pub fn create_test_data(size_mb: usize, pattern: TestDataPattern) -> Vec<u8> {
+    create_test_data_bytes(size_mb * 1024 * 1024, pattern)
+}
+
+/// Verify data matches expected pattern (with sampling for large data)
+///
+/// # Arguments
+/// * `data` - Data to verify
+/// * `expected_pattern` - Expected pattern
+/// * `sample_points` - Number of points to sample
+pub fn verify_data_sampled(data: &[u8], expected_pattern: TestDataPattern, sample_points: usize) {
+    let len = data.len();
+    let stride = len / sample_points;
+
+    for i in 0..sample_points {
+        let pos = i * stride;
+        if pos >= len {
+            break;
+        }
+        let expected = pattern_byte(expected_pattern, pos);
+        assert_eq!(
+            data[pos], expected,
+            "Mismatch at position {} (sample {}): expected {}, got {}",
+            pos, i, expected, data[pos]
+        );
+    }
+}
+
+/// Create a test dataset directory with multiple files
+///
+/// Panicking wrapper around [`try_create_test_dataset`].
+///
+/// # Arguments
+/// * `base_path` - Base directory for dataset
+/// * `size_mb` - Total size in megabytes
+/// * `pattern` - Data pattern to use
+///
+/// # Returns
+/// Number of files created
+pub fn create_test_dataset(base_path: &Path, size_mb: usize, pattern: TestDataPattern) -> usize {
+    try_create_test_dataset(base_path, size_mb, pattern)
+        .unwrap_or_else(|e| panic!("Failed to create test dataset: {}", e))
+}
+
+/// Create a test dataset directory with multiple files
+///
+/// # Errors
+/// Returns [`Error::Io`] or [`Error::DiskSpace`] carrying the directory or
+/// file that could not be written.
+pub fn try_create_test_dataset(
+    base_path: &Path,
+    size_mb: usize,
+    pattern: TestDataPattern,
+) -> Result<usize> {
+    try_create_test_dataset_with(base_path, size_mb, pattern, &DatasetOptions::default())
+        .map(|dataset| dataset.files)
+}
+
+/// Step sizes cycled by [`FileSizeDistribution::Steps`]
+const STEP_SIZES: [usize; 5] = [1024, 10 * 1024, 100 * 1024, 500 * 1024, 1024 * 1024];
+
+/// Samples larger than this multiple of the median are clipped
+const OUTLIER_CLIP_FACTOR: f64 = 20.0;
+
+/// Distribution of file sizes for generated datasets
+///
+/// Real filesystems are heavy-tailed; `LogNormal` and `Pareto` model that,
+/// while `Steps` reproduces the original fixed 1KB-1MB cycle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FileSizeDistribution {
+    /// Every file has the same size
+    Fixed(usize),
+    /// Uniform in `[min, max]`
+    UniformRange { min: usize, max: usize },
+    /// Cycle through 1KB, 10KB, 100KB, 500KB, 1MB
+    #[default]
+    Steps,
+    /// `exp(N(mu, sigma))` bytes
+    LogNormal { mu: f64, sigma: f64 },
+    /// Pareto with shape `alpha` and scale `min` bytes
+    Pareto { alpha: f64, min: usize },
+}
+
+impl FileSizeDistribution {
+    /// Draw one raw file size (at least 1 byte)
+    ///
+    /// `Steps` has no randomness and always returns the first step; use
+    /// [`sample_sizes`](Self::sample_sizes) to cycle through it.
+    pub fn sample(&self, rng: &mut impl Rng) -> usize {
+        let size = match *self {
+            FileSizeDistribution::Fixed(size) => size as f64,
+            FileSizeDistribution::UniformRange { min, max } => {
+                rng.random_range(min.min(max)..=max.max(min)) as f64
+            }
+            FileSizeDistribution::Steps => STEP_SIZES[0] as f64,
+            FileSizeDistribution::LogNormal { mu, sigma } => {
+                (mu + sigma * standard_normal(rng)).exp()
+            }
+            FileSizeDistribution::Pareto { alpha, min } => {
+                // Inverse CDF; 1 - u avoids u == 0
+                let u: f64 = 1.0 - rng.random::<f64>();
+                min as f64 / u.powf(1.0 / alpha.max(f64::EPSILON))
+            }
+        };
+        (size.round() as usize).max(1)
+    }
+
+    /// Approximate mean file size, used to pick a file count for a budget
+    pub fn mean_size(&self) -> f64 {
+        match *self {
+            FileSizeDistribution::Fixed(size) => size as f64,
+            FileSizeDistribution::UniformRange { min, max } => (min + max) as f64 / 2.0,
+            FileSizeDistribution::Steps => {
+                STEP_SIZES.iter().sum::<usize>() as f64 / STEP_SIZES.len() as f64
+            }
+            FileSizeDistribution::LogNormal { mu, sigma } => (mu + sigma * sigma / 2.0).exp(),
+            FileSizeDistribution::Pareto { alpha, min } if alpha > 1.0 => {
+                alpha * min as f64 / (alpha - 1.0)
+            }
+            // Infinite mean; fall back to a generous multiple of the scale
+            FileSizeDistribution::Pareto { min, .. } => min as f64 * 10.0,
+        }
+    }
+
+    /// Draw `count` sizes scaled to sum to exactly `total_budget` bytes
+    ///
+    /// Raw samples above 20x the sample median are clipped before scaling,
+    /// so a single outlier cannot swallow the whole budget.
+    pub fn sample_sizes(
+        &self,
+        rng: &mut impl Rng,
+        count: usize,
+        total_budget: usize,
+    ) -> Vec<usize> {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let mut raw: Vec<f64> = match self {
+            FileSizeDistribution::Steps => (0..count)
+                .map(|i| STEP_SIZES[i % STEP_SIZES.len()] as f64)
+                .collect(),
+            _ => (0..count).map(|_| self.sample(rng) as f64).collect(),
+        };
+
+        let mut sorted = raw.clone();
+        sorted.sort_by(f64::total_cmp);
+        let clip = sorted[count / 2] * OUTLIER_CLIP_FACTOR;
+        for value in &mut raw {
+            *value = value.min(clip);
+        }
+
+        let scale = total_budget as f64 / raw.iter().sum::<f64>();
+        let mut sizes: Vec<usize> = raw.iter().map(|v| (v * scale).floor() as usize).collect();
+
+        // Hand the rounding remainder out one byte at a time
+        let remainder = total_budget - sizes.iter().sum::<usize>();
+        for i in 0..remainder {
+            sizes[i % count] += 1;
+        }
+        sizes
+    }
+}
+
+/// Standard normal sample via Box-Muller
+fn standard_normal(rng: &mut impl Rng) -> f64 {
+    let u1: f64 = 1.0 - rng.random::<f64>();
+    let u2: f64 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+/// Options for dataset generation
+///
+/// # Example
+/// ```rust,ignore
+/// let options = DatasetOptions::new()
+///     .with_size_distribution(FileSizeDistribution::LogNormal { mu: 9.0, sigma: 1.5 })
+///     .with_seed(7)
+///     .with_progress(IndicatifSink::new());
+/// try_create_test_dataset_with(&path, 2048, TestDataPattern::Random, &options)?;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DatasetOptions {
+    /// Progress reporting (bytes written, phase `"generate"`)
+    pub progress: ProgressOptions,
+    /// File size distribution (defaults to the 1KB-1MB step cycle)
+    pub size_distribution: FileSizeDistribution,
+    /// Seed for sampling file sizes (also recorded in self-describing headers)
+    pub seed: u64,
+    /// Prefix every file with a [`SELF_DESCRIBING_HEADER_LEN`]-byte header
+    pub self_describing: bool,
+    /// Stop generating at the next file boundary once this much time has passed
+    pub time_budget: Option<Duration>,
+    /// Stop generating after this many files
+    pub max_files: Option<usize>,
+    /// Weighted file kinds for seeded datasets; empty keeps the default mix
+    pub type_mix: Vec<(FileKind, f64)>,
+    /// Fraction of typed files given another kind's extension on purpose
+    pub mismatch_fraction: f64,
+    /// Stop generating at the next file boundary once this is cancelled
+    pub cancellation: Option<CancellationToken>,
+    /// Journal progress so an interrupted run can be resumed
+    pub resumable: bool,
+}
+
+impl DatasetOptions {
+    /// Default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `distribution` for file sizes
+    pub fn with_size_distribution(mut self, distribution: FileSizeDistribution) -> Self {
+        self.size_distribution = distribution;
+        self
+    }
+
+    /// Seed the size sampler
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Start every file with a self-describing header
+    ///
+    /// The header records the dataset seed, file index and pattern so
+    /// [`identify_extracted_file`](crate::integrity::identify_extracted_file)
+    /// can tell which source file extracted bytes came from. The header
+    /// counts toward the file size; files are never shorter than the header.
+    pub fn self_describing(mut self, enabled: bool) -> Self {
+        self.self_describing = enabled;
+        self
+    }
+
+    /// Stop generation once `budget` has elapsed
+    ///
+    /// The budget is checked before each file, so generation always stops at
+    /// a file boundary and every written file is complete. The result then
+    /// reports [`GenerationOutcome::Truncated`] with the bytes actually
+    /// written.
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Journal progress next to the dataset directory so a later run resumes
+    ///
+    /// Used by [`try_create_test_dataset_with`]; see [`GenerationJournal`].
+    /// Off by default, since the journal is written to the directory's
+    /// parent, which must then be writable.
+    pub fn resumable(mut self, enabled: bool) -> Self {
+        self.resumable = enabled;
+        self
+    }
+
+    /// Stop generation after `max_files` files
+    ///
+    /// Like [`time_budget`](Self::time_budget) this reports
+    /// [`GenerationOutcome::Truncated`]; it is mainly useful for simulating
+    /// an interrupted run deterministically.
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// Draw each seeded file's kind from `mix` with the given relative weights
+    ///
+    /// Applies to [`TestHarness::try_create_dataset_seeded_with`](crate::TestHarness::try_create_dataset_seeded_with);
+    /// the manifest then records every file's [`FileKind`] for
+    /// [`verify_dataset_against_manifest`].
+    pub fn with_type_mix(mut self, mix: Vec<(FileKind, f64)>) -> Self {
+        self.type_mix = mix;
+        self
+    }
+
+    /// Give `fraction` of typed files the extension of a different kind
+    ///
+    /// Mismatched files are spread evenly over the dataset and flagged in
+    /// the manifest. Only used together with [`with_type_mix`](Self::with_type_mix).
+    pub fn with_mismatch_fraction(mut self, fraction: f64) -> Self {
+        self.mismatch_fraction = fraction;
+        self
+    }
+
+    /// Stop generation at the next file boundary once `token` is cancelled
+    ///
+    /// The result then reports [`GenerationOutcome::Cancelled`]; seeded
+    /// datasets still save a manifest listing the files that were written.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Why generation should stop before the next file, if it should
+    ///
+    /// Cancellation wins over the time budget and file cap.
+    pub(crate) fn stop_outcome(
+        &self,
+        started: Instant,
+        files: usize,
+        written_bytes: u64,
+    ) -> Option<GenerationOutcome> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            Some(GenerationOutcome::Cancelled { written_bytes })
+        } else if self.budget_expired(started) || self.file_cap_reached(files) {
+            Some(GenerationOutcome::Truncated { written_bytes })
+        } else {
+            None
+        }
+    }
+
+    /// Whether the file cap, if any, is reached after `files` files
+    pub(crate) fn file_cap_reached(&self, files: usize) -> bool {
+        self.max_files.is_some_and(|cap| files >= cap)
+    }
+
+    /// Whether the time budget, if any, has run out since `started`
+    pub(crate) fn budget_expired(&self, started: Instant) -> bool {
+        self.time_budget
+            .is_some_and(|budget| started.elapsed() >= budget)
+    }
+
+    /// File names and sizes for a `size_mb` dataset under these options
+    pub(crate) fn file_plan(&self, size_mb: usize) -> Vec<(String, usize)> {
+        if self.size_distribution == FileSizeDistribution::Steps {
+            return dataset_file_plan(size_mb);
+        }
+
+        let budget = size_mb * 1024 * 1024;
+        let count = ((budget as f64 / self.size_distribution.mean_size()).round() as usize)
+            .clamp(1, budget.max(1));
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        self.size_distribution
+            .sample_sizes(&mut rng, count, budget)
+            .into_iter()
+            .enumerate()
+            .map(|(i, size)| (format!("file_{:04}.bin", i), size))
+            .collect()
+    }
+
+    /// Report progress to `sink`
+    pub fn with_progress(mut self, sink: impl ProgressSink + 'static) -> Self {
+        self.progress = self.progress.with_sink(sink);
+        self
+    }
+
+    /// Replace the progress configuration
+    pub fn with_progress_options(mut self, progress: ProgressOptions) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+/// Whether dataset generation produced everything that was requested
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum GenerationOutcome {
+    /// Every planned file was written
+    #[default]
+    Completed,
+    /// The time budget or file cap ran out; only the first files were written
+    Truncated {
+        /// Bytes actually written across all complete files
+        written_bytes: u64,
+    },
+    /// A [`CancellationToken`] was cancelled; only the first files were written
+    Cancelled {
+        /// Bytes actually written across all complete files
+        written_bytes: u64,
+    },
+}
+
+impl GenerationOutcome {
+    /// Whether generation stopped early, truncated or cancelled
+    pub fn is_truncated(&self) -> bool {
+        !matches!(self, GenerationOutcome::Completed)
+    }
+
+    /// Whether generation stopped because it was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, GenerationOutcome::Cancelled { .. })
+    }
+}
+
+/// Result of generating a dataset with [`DatasetOptions`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedDataset {
+    /// Dataset root directory
+    pub path: PathBuf,
+    /// Number of files written
+    pub files: usize,
+    /// Whether all files were written
+    pub outcome: GenerationOutcome,
+    /// Whether an interrupted earlier run was resumed
+    pub start: GenerationStart,
+}
+
+impl GeneratedDataset {
+    /// Whether generation stopped early
+    pub fn is_truncated(&self) -> bool {
+        self.outcome.is_truncated()
+    }
+}
+
+/// Create a test dataset directory with multiple files using `options`
+///
+/// With [`DatasetOptions::time_budget`] or [`DatasetOptions::max_files`]
+/// set, generation may stop early at a file boundary; check
+/// [`GeneratedDataset::outcome`].
+///
+/// With [`DatasetOptions::resumable`], progress is journaled next to the
+/// directory (see [`GenerationJournal`]), so calling this again with the
+/// same size, pattern and options after an interruption resumes instead of
+/// starting over. Journaled files are only kept if their contents still
+/// match; anything else is regenerated. [`GeneratedDataset::start`] tells
+/// whether the run resumed.
+pub fn try_create_test_dataset_with(
+    base_path: &Path,
+    size_mb: usize,
+    pattern: TestDataPattern,
+    options: &DatasetOptions,
+) -> Result<GeneratedDataset> {
+    fs::create_dir_all(base_path).at_path(base_path)?;
+
+    let started = Instant::now();
+    let plan = options.file_plan(size_mb);
+    let tracker = options
+        .progress
+        .tracker(Some((size_mb * 1024 * 1024) as u64), "generate");
+    let journal_path = GenerationJournal::path_for(base_path);
+    let mut journal = GenerationJournal::new(size_mb, pattern, options);
+    let resume_from = match GenerationJournal::load(&journal_path) {
+        Ok(previous) if options.resumable && previous.same_inputs(&journal) => {
+            Some(previous.completed_files)
+        }
+        _ => None,
+    };
+    let mut saved_files = resume_from.unwrap_or(0);
+    let mut last_save = Instant::now();
+
+    let mut files = plan.len();
+    let mut written_bytes = 0u64;
+    let mut skipped_files = 0;
+    let mut regenerated_files = 0;
+    let mut outcome = GenerationOutcome::Completed;
+    for (index, (filename, size)) in plan.iter().enumerate() {
+        let filepath = base_path.join(filename);
+        let (prefix, payload_len) = if options.self_describing {
+            let payload_len = size.saturating_sub(SELF_DESCRIBING_HEADER_LEN);
+            let header = DatasetHeader {
+                dataset_seed: options.seed,
+                file_index: index as u64,
+                pattern,
+                payload_len: payload_len as u64,
+            };
+            (header.encode().to_vec(), payload_len)
+        } else {
+            (Vec::new(), *size)
+        };
+        let expected_len = (prefix.len() + payload_len) as u64;
+
+        let journaled = resume_from.is_some_and(|completed| index < completed);
+        if journaled {
+            if pattern_file_matches(&filepath, &prefix, payload_len, pattern) {
+                skipped_files += 1;
+                written_bytes += expected_len;
+                tracker.advance(*size as u64);
+                continue;
+            }
+            regenerated_files += 1;
+        } else if let Some(stop) = options.stop_outcome(started, index, written_bytes) {
+            files = index;
+            outcome = stop;
+            break;
+        }
+
+        write_pattern_file(&filepath, &prefix, payload_len, pattern)?;
+        written_bytes += expected_len;
+        tracker.advance(*size as u64);
+
+        if options.resumable && !journaled {
+            journal.completed_files = index + 1;
+            if journal.completed_files - saved_files >= JOURNAL_BATCH_FILES
+                || last_save.elapsed() >= JOURNAL_BATCH_INTERVAL
+            {
+                journal.save(&journal_path)?;
+                saved_files = journal.completed_files;
+                last_save = Instant::now();
+            }
+        }
+    }
+    tracker.finish();
+
+    if options.resumable {
+        if outcome.is_truncated() {
+            if journal.completed_files > saved_files {
+                journal.save(&journal_path)?;
+            }
+        } else if journal_path.exists() {
+            fs::remove_file(&journal_path).at_path(&journal_path)?;
+        }
+    }
+    Ok(GeneratedDataset {
+        path: base_path.to_path_buf(),
+        files,
+        outcome,
+        start: match resume_from {
+            Some(_) => GenerationStart::Resumed {
+                skipped_files,
+                regenerated_files,
+            },
+            None => GenerationStart::Fresh,
+        },
+    })
+}
+
+/// Verify a whole file against `pattern` using 256MB memory-mapped windows
+#[cfg(feature = "mmap")]
+pub fn verify_file_pattern_mmap(path: &Path, pattern: TestDataPattern) -> Result<()> {
+    verify_file_pattern_mmap_with(path, pattern, &crate::mmap::MmapConfig::default())
+}
+
+/// Verify every byte of a file against `pattern` using windowed mmap
+///
+/// # Errors
+/// * [`Error::Io`] if the file cannot be opened or read
+/// * [`Error::PatternMismatch`] at the first byte that differs
+#[cfg(feature = "mmap")]
+pub fn verify_file_pattern_mmap_with(
+    path: &Path,
+    pattern: TestDataPattern,
+    config: &crate::mmap::MmapConfig,
+) -> Result<()> {
+    use crate::mmap::{load_window, windows};
+
+    let mut file = fs::File::open(path).at_path(path)?;
+    let total = file.metadata().at_path(path)?.len();
+
+    for (offset, len) in windows(total, config.window_size) {
+        let window = load_window(&mut file, offset, len, config).at_path(path)?;
+        for (i, &actual) in window.iter().enumerate() {
+            let pos = offset + i as u64;
+            let expected = pattern_byte(pattern, pos as usize);
+            if actual != expected {
+                return Err(Error::PatternMismatch {
+                    path: path.to_path_buf(),
+                    offset: pos,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        config.report(offset + len as u64, total);
+    }
+
+    Ok(())
+}
+
+/// File names and sizes making up a `size_mb` dataset
+///
+/// Files cycle through 1KB, 10KB, 100KB, 500KB and 1MB, with the last file
+/// truncated so the total matches exactly.
+fn dataset_file_plan(size_mb: usize) -> Vec<(String, usize)> {
+    let target_bytes = size_mb * 1024 * 1024;
+    let mut plan = Vec::new();
+    let mut written = 0;
+
+    while written < target_bytes {
+        let file_size = match plan.len() % 5 {
+            0 => 1024,        // 1KB
+            1 => 10 * 1024,   // 10KB
+            2 => 100 * 1024,  // 100KB
+            3 => 500 * 1024,  // 500KB
+            _ => 1024 * 1024, // 1MB
+        };
+
+        let actual_size = file_size.min(target_bytes - written);
+        plan.push((format!("file_{:04}.bin", plan.len()), actual_size));
+        written += actual_size;
+    }
+
+    plan
+}
+
+/// Default bound on concurrent file writes for the async generators
+#[cfg(feature = "async")]
+pub const DEFAULT_MAX_CONCURRENT_WRITES: usize = 16;
+
+/// Async variant of [`try_create_test_dataset`]
+///
+/// Produces byte-identical files to the sync version. At most
+/// `max_concurrent` files are generated and written at once. Each file is
+/// written to a `.partial` sibling, flushed, then renamed into place, so a
+/// dropped future never leaves a truncated file under its final name. The
+/// file count is only returned once every write has completed.
+#[cfg(feature = "async")]
+pub async fn create_test_dataset_async(
+    base_path: &Path,
+    size_mb: usize,
+    pattern: TestDataPattern,
+    max_concurrent: usize,
+) -> Result<usize> {
+    tokio::fs::create_dir_all(base_path)
+        .await
+        .at_path(base_path)?;
+
+    let entries = dataset_file_plan(size_mb)
+        .into_iter()
+        .map(|(filename, size)| {
+            let write = move |mut out: &mut dyn Write| write_pattern_to(&mut out, size, pattern);
+            (base_path.join(filename), size as u64, write)
+        });
+
+    write_files_async(entries, max_concurrent).await
+}
+
+/// Write lazily described files with bounded concurrency
+///
+/// Each entry is a path, its expected size and a function streaming the
+/// content into a writer. The iterator is only advanced after a write slot
+/// is free, and each write runs on the blocking pool through a buffered
+/// writer, so no file is ever held in memory whole.
+#[cfg(feature = "async")]
+pub(crate) async fn write_files_async<I, F>(entries: I, max_concurrent: usize) -> Result<usize>
+where
+    I: Iterator<Item = (PathBuf, u64, F)>,
+    F: FnOnce(&mut dyn Write) -> std::io::Result<()> + Send + 'static,
+{
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+    use tokio::task::JoinSet;
+
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut tasks = JoinSet::new();
+    let mut entries = entries;
+
+    loop {
+        let permit = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("semaphore is never closed");
+        let Some((path, size, write)) = entries.next() else {
+            break;
+        };
+        tasks.spawn(async move {
+            let _permit = permit;
+            tokio::task::spawn_blocking(move || write_file_atomic(&path, size, write))
+                .await
+                .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+        });
+    }
+
+    let mut count = 0;
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => result?,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Stream into a synced `.partial` temp file and rename it into place
+#[cfg(feature = "async")]
+fn write_file_atomic(
+    path: &Path,
+    size: u64,
+    write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>,
+) -> Result<()> {
+    let mut partial_name = path.file_name().unwrap_or_default().to_os_string();
+    partial_name.push(".partial");
+    let partial = path.with_file_name(partial_name);
+
+    let file = fs::File::create(&partial).at_path(&partial)?;
+    let mut out = std::io::BufWriter::new(file);
+    write(&mut out).map_err(|e| Error::write(&partial, size, e))?;
+    let file = out
+        .into_inner()
+        .map_err(|e| Error::write(&partial, size, e.into_error()))?;
+    file.sync_all().at_path(&partial)?;
+    drop(file);
+
+    fs::rename(&partial, path).at_path(path)
+}
+
+/// Verify a file on disk matches `expected_pattern` at sampled positions
+///
+/// # Errors
+/// * [`Error::InvalidSpec`] if `sample_points` is zero
+/// * [`Error::Io`] if the file cannot be read
+/// * [`Error::PatternMismatch`] at the first sampled byte that differs
+pub fn try_verify_file_sampled(
+    path: &Path,
+    expected_pattern: TestDataPattern,
+    sample_points: usize,
+) -> Result<()> {
+    if sample_points == 0 {
+        return Err(Error::invalid_spec(path, "sample_points must be non-zero"));
+    }
+
+    let data = fs::read(path).at_path(path)?;
+    let stride = (data.len() / sample_points).max(1);
+
+    for pos in (0..data.len()).step_by(stride).take(sample_points) {
+        let expected = pattern_byte(expected_pattern, pos);
+        if data[pos] != expected {
+            return Err(Error::PatternMismatch {
+                path: path.to_path_buf(),
+                offset: pos as u64,
+                expected,
+                actual: data[pos],
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Expected byte at `pos` for a pattern (helper)
+pub(crate) fn pattern_byte(pattern: TestDataPattern, pos: usize) -> u8 {
+    match pattern {
+        TestDataPattern::Zeros => 0u8,
+        TestDataPattern::Ones => 0xFF,
+        TestDataPattern::Sequential => (pos % 256) as u8,
+        TestDataPattern::Random => ((pos.wrapping_mul(2654435761)) % 256) as u8,
+        TestDataPattern::Seeded(seed) => seeded_byte(seed, pos),
+        TestDataPattern::Compressible => {
+            let pattern = b"The quick brown fox jumps over the lazy dog. ";
+            pattern[pos % pattern.len()]
+        }
+        TestDataPattern::Text => {
+            let chars = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789 \n";
+            chars[pos % chars.len()]
+        }
+    }
+}
+
+/// Length of the header written by [`DatasetOptions::self_describing`]
+pub const SELF_DESCRIBING_HEADER_LEN: usize = 64;
+
+/// Magic bytes opening a self-describing dataset file
+const HEADER_MAGIC: &[u8; 8] = b"EMBTKHD1";
+
+/// Self-describing file header
+///
+/// Layout (little-endian): magic `[0..8]`, dataset seed `[8..16]`, file
+/// index `[16..24]`, pattern tag `[24]`, pattern seed `[32..40]`, payload
+/// length `[40..48]`, BLAKE3 checksum of bytes `[0..48]` truncated to 8
+/// bytes `[48..56]`. Remaining bytes are zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DatasetHeader {
+    pub dataset_seed: u64,
+    pub file_index: u64,
+    pub pattern: TestDataPattern,
+    pub payload_len: u64,
+}
+
+impl DatasetHeader {
+    pub(crate) fn encode(&self) -> [u8; SELF_DESCRIBING_HEADER_LEN] {
+        let (tag, pattern_seed) = match self.pattern {
+            TestDataPattern::Zeros => (0u8, 0),
+            TestDataPattern::Ones => (1, 0),
+            TestDataPattern::Sequential => (2, 0),
+            TestDataPattern::Random => (3, 0),
+            TestDataPattern::Compressible => (4, 0),
+            TestDataPattern::Text => (5, 0),
+            TestDataPattern::Seeded(seed) => (6, seed),
+        };
+
+        let mut header = [0u8; SELF_DESCRIBING_HEADER_LEN];
+        header[0..8].copy_from_slice(HEADER_MAGIC);
+        header[8..16].copy_from_slice(&self.dataset_seed.to_le_bytes());
+        header[16..24].copy_from_slice(&self.file_index.to_le_bytes());
+        header[24] = tag;
+        header[32..40].copy_from_slice(&pattern_seed.to_le_bytes());
+        header[40..48].copy_from_slice(&self.payload_len.to_le_bytes());
+        let checksum = blake3::hash(&header[0..48]);
+        header[48..56].copy_from_slice(&checksum.as_bytes()[0..8]);
+        header
+    }
+
+    /// Parse a header, rejecting bad magic, checksum or pattern tag
+    pub(crate) fn decode(header: &[u8]) -> Option<Self> {
+        let header = header.get(0..SELF_DESCRIBING_HEADER_LEN)?;
+        if &header[0..8] != HEADER_MAGIC
+            || header[48..56] != blake3::hash(&header[0..48]).as_bytes()[0..8]
+        {
+            return None;
+        }
+
+        let u64_at = |at: usize| u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+        let pattern = match header[24] {
+            0 => TestDataPattern::Zeros,
+            1 => TestDataPattern::Ones,
+            2 => TestDataPattern::Sequential,
+            3 => TestDataPattern::Random,
+            4 => TestDataPattern::Compressible,
+            5 => TestDataPattern::Text,
+            6 => TestDataPattern::Seeded(u64_at(32)),
+            _ => return None,
+        };
+        Some(Self {
+            dataset_seed: u64_at(8),
+            file_index: u64_at(16),
+            pattern,
+            payload_len: u64_at(40),
+        })
+    }
+}
+
+/// Byte `pos` of [`TestDataPattern::Seeded`]: SplitMix64 finalizer over `(seed, pos)`
+///
+/// Depends only on the seed and position, so any byte can be verified
+/// without generating its predecessors.
+fn seeded_byte(seed: u64, pos: usize) -> u8 {
+    let mut z = seed ^ (pos as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) as u8
+}
+
+/// Create test data with exact byte count (helper)
+fn create_test_data_bytes(size_bytes: usize, pattern: TestDataPattern) -> Vec<u8> {
+    let mut data = vec![0u8; size_bytes];
+    fill_pattern(&mut data, pattern, 0);
+    data
+}
+
+/// Fill `buf` with the bytes of `pattern` starting at stream position `offset`
+///
+/// Every pattern is a function of position only, so consecutive blocks
+/// filled with increasing offsets concatenate to the unblocked data.
+pub(crate) fn fill_pattern(buf: &mut [u8], pattern: TestDataPattern, offset: usize) {
+    let positions = buf.iter_mut().zip(offset..);
+    match pattern {
+        TestDataPattern::Zeros => buf.fill(0),
+        TestDataPattern::Ones => buf.fill(0xFF),
+        TestDataPattern::Sequential => positions.for_each(|(b, i)| *b = (i % 256) as u8),
+        TestDataPattern::Random => {
+            // Simple deterministic "random" pattern using LCG
+            positions.for_each(|(b, i)| *b = ((i.wrapping_mul(2654435761)) % 256) as u8)
+        }
+        TestDataPattern::Seeded(seed) => positions.for_each(|(b, i)| *b = seeded_byte(seed, i)),
+        TestDataPattern::Compressible | TestDataPattern::Text => {
+            positions.for_each(|(b, i)| *b = pattern_byte(pattern, i))
+        }
+    }
+}
+
+/// Block size used by [`write_pattern_to`]
+pub const PATTERN_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Files a resumable generation writes between journal saves, at most
+const JOURNAL_BATCH_FILES: usize = 64;
+
+/// Time a resumable generation lets pass between journal saves, at most
+const JOURNAL_BATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Stream `size_bytes` of `pattern` into `writer` without materializing it
+///
+/// Writes the same bytes as [`create_test_data`] would produce, one
+/// [`PATTERN_BLOCK_SIZE`] block at a time from a single reused buffer, so
+/// memory stays flat for multi-GB files.
+///
+/// # Example
+/// ```rust,ignore
+/// let mut file = File::create(&path)?;
+/// write_pattern_to(&mut file, 20 << 30, TestDataPattern::Seeded(7))?;
+/// ```
+pub fn write_pattern_to(
+    writer: &mut impl Write,
+    size_bytes: usize,
+    pattern: TestDataPattern,
+) -> std::io::Result<()> {
+    write_pattern_to_with(writer, size_bytes, pattern, PATTERN_BLOCK_SIZE, None)
+}
+
+/// [`write_pattern_to`] with an explicit block size and optional metrics
+///
+/// The buffer is `min(block_size, size_bytes)` bytes and every `write_all`
+/// call passes at most that many. With `metrics`, the whole write is
+/// recorded as one timing sample and its throughput as the
+/// `"write_mbps"` custom metric.
+pub fn write_pattern_to_with(
+    writer: &mut impl Write,
+    size_bytes: usize,
+    pattern: TestDataPattern,
+    block_size: usize,
+    metrics: Option<&mut TestMetrics>,
+) -> std::io::Result<()> {
+    let started = Instant::now();
+    let mut block = vec![0u8; block_size.max(1).min(size_bytes)];
+    let mut offset = 0;
+    while offset < size_bytes {
+        let len = block.len().min(size_bytes - offset);
+        fill_pattern(&mut block[..len], pattern, offset);
+        writer.write_all(&block[..len])?;
+        offset += len;
+    }
+
+    if let Some(metrics) = metrics {
+        let elapsed = started.elapsed();
+        metrics.timings_ns.push(elapsed.as_nanos() as u64);
+        let secs = elapsed.as_secs_f64();
+        if secs > 0.0 {
+            metrics.record_metric("write_mbps", size_bytes as f64 / (1024.0 * 1024.0) / secs);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` holds exactly what [`write_pattern_file`] would write
+///
+/// Compares block by block, stopping at the first difference.
+fn pattern_file_matches(
+    path: &Path,
+    prefix: &[u8],
+    size_bytes: usize,
+    pattern: TestDataPattern,
+) -> bool {
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    let total = (prefix.len() + size_bytes) as u64;
+    if !file.metadata().is_ok_and(|meta| meta.len() == total) {
+        return false;
+    }
+    let mut reader = std::io::BufReader::new(file);
+    let mut actual = vec![0u8; prefix.len()];
+    if reader.read_exact(&mut actual).is_err() || actual != prefix {
+        return false;
+    }
+
+    let mut expected = vec![0u8; PATTERN_BLOCK_SIZE.min(size_bytes)];
+    actual.resize(expected.len(), 0);
+    let mut offset = 0;
+    while offset < size_bytes {
+        let len = expected.len().min(size_bytes - offset);
+        fill_pattern(&mut expected[..len], pattern, offset);
+        if reader.read_exact(&mut actual[..len]).is_err() || actual[..len] != expected[..len] {
+            return false;
+        }
+        offset += len;
+    }
+    true
+}
+
+/// Create `path` holding `prefix` followed by `size_bytes` of `pattern`
+///
+/// Streams through [`write_pattern_to`]; errors are classified like
+/// [`Error::write`], so a full disk becomes [`Error::DiskSpace`].
+pub(crate) fn write_pattern_file(
+    path: &Path,
+    prefix: &[u8],
+    size_bytes: usize,
+    pattern: TestDataPattern,
+) -> Result<()> {
+    let total = (prefix.len() + size_bytes) as u64;
+    let mut file = fs::File::create(path).map_err(|e| Error::write(path, total, e))?;
+    file.write_all(prefix)
+        .and_then(|()| write_pattern_to(&mut file, size_bytes, pattern))
+        .map_err(|e| Error::write(path, total, e))
+}
+
+/// Kind of file produced by the seeded dataset generators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeededFileKind {
+    /// Prose-like ASCII text
+    Text,
+    /// JSON lines records
+    Json,
+    /// Timestamped log lines
+    Log,
+    /// Pseudo-random binary
+    Binary,
+}
+
+impl SeededFileKind {
+    const ALL: [SeededFileKind; 4] = [
+        SeededFileKind::Text,
+        SeededFileKind::Json,
+        SeededFileKind::Log,
+        SeededFileKind::Binary,
+    ];
+
+    /// File extension used for this kind
+    pub fn extension(self) -> &'static str {
+        match self {
+            SeededFileKind::Text => "txt",
+            SeededFileKind::Json => "jsonl",
+            SeededFileKind::Log => "log",
+            SeededFileKind::Binary => "bin",
+        }
+    }
+}
+
+/// One file recorded in a [`DatasetManifest`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path relative to the dataset root, `/`-separated
+    pub path: String,
+    /// File size in bytes
+    pub size: u64,
+    /// Content kind
+    pub kind: SeededFileKind,
+    /// Exact content kind, for datasets generated with a type mix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_kind: Option<FileKind>,
+    /// Whether the extension deliberately names a different kind
+    #[serde(default)]
+    pub extension_mismatch: bool,
+    /// Pattern the content was written with, for pattern-filled files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<TestDataPattern>,
+}
+
+/// Description of a seeded dataset, written next to the dataset directory
+///
+/// The manifest lives outside the dataset tree (as `<dir>.manifest.json`) so
+/// it is never picked up by ingestion of the dataset itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    /// Seed the dataset was generated from
+    pub seed: u64,
+    /// Files in generation order
+    pub files: Vec<ManifestEntry>,
+    /// Whether generation finished or was cut short by a time budget
+    #[serde(default)]
+    pub outcome: GenerationOutcome,
+}
+
+impl DatasetManifest {
+    /// Manifest location for a dataset directory
+    pub fn path_for(dataset_dir: &Path) -> PathBuf {
+        let mut name = dataset_dir.file_name().unwrap_or_default().to_os_string();
+        name.push(".manifest.json");
+        dataset_dir.with_file_name(name)
+    }
+
+    /// Save as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::io(path, std::io::Error::from(e)))?;
+        fs::write(path, content).at_path(path)
+    }
+
+    /// Load from JSON
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).at_path(path)?;
+        serde_json::from_str(&content).map_err(|e| Error::io(path, std::io::Error::from(e)))
+    }
+
+    /// Total bytes across all files
+    pub fn total_bytes(&self) -> u64 {
+        self.files.iter().map(|f| f.size).sum()
+    }
+
+    /// Check that `dir` holds exactly the files listed, at the listed sizes
+    ///
+    /// Truncated manifests list only the files that were written, so a
+    /// partial dataset verifies cleanly against its own manifest.
+    pub fn verify(&self, dir: &Path) -> Result<IntegrityReport> {
+        let mut report = IntegrityReport::new();
+        let mut listed = std::collections::HashSet::new();
+
+        for entry in &self.files {
+            listed.insert(entry.path.as_str());
+            let path = dir.join(&entry.path);
+            match fs::metadata(&path) {
+                Ok(meta) if meta.len() == entry.size => report.pass(),
+                Ok(_) => report.record_file_mismatch(entry.path.as_str(), MismatchKind::Size),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    report.record_file_mismatch(entry.path.as_str(), MismatchKind::Missing)
+                }
+                Err(e) => return Err(Error::io(&path, e)),
+            }
+        }
+
+        for (rel, _) in crate::integrity::collect_files(dir).at_path(dir)? {
+            if !listed.contains(rel.as_str()) {
+                report.record_file_mismatch(rel.as_str(), MismatchKind::Extra);
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Advance the LCG state used by the seeded generators
+fn lcg(state: &mut u64) -> u64 {
+    *state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+    *state >> 16
+}
+
+/// Deterministic content of `size` bytes for a seeded file
+pub(crate) fn seeded_content(kind: SeededFileKind, size: usize, seed: u64) -> Vec<u8> {
+    const WORDS: &[&str] = &[
+        "vector",
+        "bundle",
+        "bind",
+        "sparse",
+        "ternary",
+        "engram",
+        "chunk",
+        "codebook",
+        "similarity",
+        "index",
+        "the",
+        "a",
+        "of",
+        "and",
+        "with",
+        "over",
+    ];
+
+    let mut state = seed;
+    let mut out = Vec::with_capacity(size + 64);
+    let mut line = 0u64;
+
+    while out.len() < size {
+        match kind {
+            SeededFileKind::Binary => return generate_noise_pattern(size, seed),
+            SeededFileKind::Text => {
+                let word = WORDS[(lcg(&mut state) % WORDS.len() as u64) as usize];
+                out.extend_from_slice(word.as_bytes());
+                out.push(if lcg(&mut state).is_multiple_of(12) {
+                    b'\n'
+                } else {
+                    b' '
+                });
+            }
+            SeededFileKind::Json => {
+                let record = format!(
+                    "{{\"id\": {}, \"value\": {}, \"tag\": \"{}\"}}\n",
+                    line,
+                    lcg(&mut state) % 100_000,
+                    WORDS[(lcg(&mut state) % WORDS.len() as u64) as usize]
+                );
+                out.extend_from_slice(record.as_bytes());
+            }
+            SeededFileKind::Log => {
+                let level = ["INFO", "WARN", "DEBUG", "ERROR"][(lcg(&mut state) % 4) as usize];
+                let record = format!(
+                    "[{:010}] {} op={} latency_us={}\n",
+                    line * 1000 + lcg(&mut state) % 1000,
+                    level,
+                    WORDS[(lcg(&mut state) % WORDS.len() as u64) as usize],
+                    lcg(&mut state) % 10_000
+                );
+                out.extend_from_slice(record.as_bytes());
+            }
+        }
+        line += 1;
+    }
+
+    out.truncate(size);
+    out
+}
+
+/// Lazily generate a flat seeded dataset of exactly `size_mb` megabytes
+///
+/// Yields `(relative path, kind, content)`; file kinds and sizes (256B-256KB)
+/// are derived from `seed`, and each file's content from a per-file seed.
+pub(crate) fn seeded_dataset_entries(
+    size_mb: usize,
+    seed: u64,
+) -> impl Iterator<Item = (String, SeededFileKind, Vec<u8>)> {
+    let target = size_mb * 1024 * 1024;
+    let mut state = seed;
+    let mut written = 0;
+    let mut index = 0;
+
+    std::iter::from_fn(move || {
+        if written >= target {
+            return None;
+        }
+        let kind = SeededFileKind::ALL[(lcg(&mut state) % 4) as usize];
+        let size = (256 + (lcg(&mut state) % (256 * 1024)) as usize).min(target - written);
+        let file_seed = lcg(&mut state);
+
+        let path = format!("file_{:05}.{}", index, kind.extension());
+        written += size;
+        index += 1;
+        Some((path, kind, seeded_content(kind, size, file_seed)))
+    })
+}
+
+/// Seeded nested directory layout: 6-15 files (16B-8KB) under up to three levels
+pub(crate) fn seeded_tree_entries(seed: u64) -> Vec<(String, SeededFileKind, Vec<u8>)> {
+    const DIRS: &[&str] = &[
+        "",
+        "docs",
+        "data",
+        "logs",
+        "data/raw",
+        "data/raw/2024",
+        "src",
+    ];
+
+    let mut state = seed;
+    let count = 6 + (lcg(&mut state) % 10) as usize;
+    (0..count)
+        .map(|i| {
+            let dir = DIRS[(lcg(&mut state) % DIRS.len() as u64) as usize];
+            let kind = SeededFileKind::ALL[(lcg(&mut state) % 4) as usize];
+            let size = 16 + (lcg(&mut state) % 8192) as usize;
+            let name = format!("item_{:03}.{}", i, kind.extension());
+            let path = if dir.is_empty() {
+                name
+            } else {
+                format!("{}/{}", dir, name)
+            };
+            (path, kind, seeded_content(kind, size, lcg(&mut state)))
+        })
+        .collect()
+}
+
+/// How many files a [`DatasetMutator`] operation touches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MutationAmount {
+    /// Exact number of files
+    Count(usize),
+    /// Fraction of the files present before mutation (rounded)
+    Ratio(f64),
+}
+
+impl MutationAmount {
+    fn resolve(self, files: usize) -> usize {
+        match self {
+            MutationAmount::Count(n) => n,
+            MutationAmount::Ratio(r) => (r.clamp(0.0, 1.0) * files as f64).round() as usize,
+        }
+    }
+}
+
+impl From<usize> for MutationAmount {
+    fn from(n: usize) -> Self {
+        MutationAmount::Count(n)
+    }
+}
+
+impl From<f64> for MutationAmount {
+    fn from(ratio: f64) -> Self {
+        MutationAmount::Ratio(ratio)
+    }
+}
+
+/// A single change applied by [`DatasetMutator`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum MutationKind {
+    /// Bytes `start..end` were appended
+    Append { start: u64, end: u64 },
+    /// Bytes `start..end` were overwritten in place (size unchanged)
+    Overwrite { start: u64, end: u64 },
+    /// A new file of `size` bytes was created
+    Create { size: u64 },
+    /// The file was deleted
+    Delete,
+    /// The file was renamed to `to`
+    Rename { to: String },
+}
+
+/// Logged mutation: which file changed and how
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mutation {
+    /// Path relative to the dataset root, `/`-separated
+    pub path: String,
+    /// What was done to it
+    #[serde(flatten)]
+    pub kind: MutationKind,
+}
+
+/// Ordered record of everything a [`DatasetMutator`] changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MutationLog {
+    /// Seed the mutations were derived from
+    pub seed: u64,
+    /// Mutations in application order
+    pub mutations: Vec<Mutation>,
+}
+
+impl MutationLog {
+    /// Number of logged mutations
+    pub fn len(&self) -> usize {
+        self.mutations.len()
+    }
+
+    /// Whether nothing was mutated
+    pub fn is_empty(&self) -> bool {
+        self.mutations.is_empty()
+    }
+
+    /// Mismatches a before/after [`ChecksumManifest`](crate::ChecksumManifest)
+    /// diff should report, sorted by path
+    ///
+    /// Only exact when no file was mutated twice.
+    pub fn expected_mismatches(&self) -> Vec<(String, MismatchKind)> {
+        let mut expected = Vec::new();
+        for m in &self.mutations {
+            match &m.kind {
+                MutationKind::Append { .. } => expected.push((m.path.clone(), MismatchKind::Size)),
+                MutationKind::Overwrite { .. } => {
+                    expected.push((m.path.clone(), MismatchKind::Content))
+                }
+                MutationKind::Create { .. } => expected.push((m.path.clone(), MismatchKind::Extra)),
+                MutationKind::Delete => expected.push((m.path.clone(), MismatchKind::Missing)),
+                MutationKind::Rename { to } => {
+                    expected.push((m.path.clone(), MismatchKind::Missing));
+                    expected.push((to.clone(), MismatchKind::Extra));
+                }
+            }
+        }
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        expected
+    }
+}
+
+/// Seeded generator of controlled changes to an existing dataset
+///
+/// Used to exercise delta/incremental ingestion: apply a mutation set, then
+/// assert the system under test detected exactly what the returned
+/// [`MutationLog`] describes. Unless [`allow_repeats`](Self::allow_repeats)
+/// is set, every existing file is touched at most once per run.
+///
+/// # Example
+/// ```rust,ignore
+/// let log = DatasetMutator::new(7)
+///     .with_appends(3)
+///     .with_deletes(0.1)
+///     .apply(&dataset);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DatasetMutator {
+    seed: u64,
+    appends: MutationAmount,
+    overwrites: MutationAmount,
+    creates: MutationAmount,
+    deletes: MutationAmount,
+    renames: MutationAmount,
+    allow_repeats: bool,
+}
+
+/// Largest append, overwrite or created file produced by [`DatasetMutator`]
+const MAX_MUTATION_BYTES: u64 = 4096;
+
+impl DatasetMutator {
+    /// Mutator that changes nothing until counts are configured
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            appends: MutationAmount::Count(0),
+            overwrites: MutationAmount::Count(0),
+            creates: MutationAmount::Count(0),
+            deletes: MutationAmount::Count(0),
+            renames: MutationAmount::Count(0),
+            allow_repeats: false,
+        }
+    }
+
+    /// Append random bytes to files
+    pub fn with_appends(mut self, amount: impl Into<MutationAmount>) -> Self {
+        self.appends = amount.into();
+        self
+    }
+
+    /// Overwrite a byte range inside files
+    pub fn with_overwrites(mut self, amount: impl Into<MutationAmount>) -> Self {
+        self.overwrites = amount.into();
+        self
+    }
+
+    /// Create new files (a ratio is relative to the existing file count)
+    pub fn with_creates(mut self, amount: impl Into<MutationAmount>) -> Self {
+        self.creates = amount.into();
+        self
+    }
+
+    /// Delete files
+    pub fn with_deletes(mut self, amount: impl Into<MutationAmount>) -> Self {
+        self.deletes = amount.into();
+        self
+    }
+
+    /// Rename files within their directory
+    pub fn with_renames(mut self, amount: impl Into<MutationAmount>) -> Self {
+        self.renames = amount.into();
+        self
+    }
+
+    /// Allow the same file to be mutated more than once
+    pub fn allow_repeats(mut self) -> Self {
+        self.allow_repeats = true;
+        self
+    }
+
+    /// Apply the mutations to `dir`
+    ///
+    /// # Panics
+    /// Panics on I/O failure or if there are not enough files; see
+    /// [`try_apply`](Self::try_apply).
+    pub fn apply(&self, dir: &Path) -> MutationLog {
+        self.try_apply(dir)
+            .unwrap_or_else(|e| panic!("Failed to mutate dataset: {e}"))
+    }
+
+    /// Apply the mutations to `dir`, returning a log of every change
+    ///
+    /// Operations run in a fixed order (appends, overwrites, renames,
+    /// deletes, creates) over a seeded shuffle of the files, so the same
+    /// seed on the same tree always yields the same log.
+    pub fn try_apply(&self, dir: &Path) -> Result<MutationLog> {
+        let mut live: Vec<String> = crate::integrity::collect_files(dir)
+            .at_path(dir)?
+            .into_iter()
+            .map(|(rel, _)| rel)
+            .collect();
+        let initial = live.len();
+        let appends = self.appends.resolve(initial);
+        let overwrites = self.overwrites.resolve(initial);
+        let renames = self.renames.resolve(initial);
+        let deletes = self.deletes.resolve(initial);
+        let creates = self.creates.resolve(initial);
+
+        let targeted = appends + overwrites + renames + deletes;
+        if !self.allow_repeats && targeted > initial {
+            return Err(Error::invalid_spec(
+                dir,
+                format!(
+                    "{} mutations requested but only {} files exist",
+                    targeted, initial
+                ),
+            ));
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut pool = live.clone();
+        pool.shuffle(&mut rng);
+        let mut log = MutationLog {
+            seed: self.seed,
+            mutations: Vec::new(),
+        };
+
+        let plan = [
+            (appends, MutationStep::Append),
+            (overwrites, MutationStep::Overwrite),
+            (renames, MutationStep::Rename),
+            (deletes, MutationStep::Delete),
+        ];
+        for (count, step) in plan {
+            for _ in 0..count {
+                let rel = if self.allow_repeats {
+                    if live.is_empty() {
+                        return Err(Error::invalid_spec(dir, "no files left to mutate"));
+                    }
+                    live[rng.random_range(0..live.len())].clone()
+                } else {
+                    pool.pop().expect("pool size checked above")
+                };
+                let kind = apply_step(dir, &rel, step, &mut rng)?;
+                match &kind {
+                    MutationKind::Delete => live.retain(|p| *p != rel),
+                    MutationKind::Rename { to } => {
+                        for p in live.iter_mut().filter(|p| **p == rel) {
+                            *p = to.clone();
+                        }
+                    }
+                    _ => {}
+                }
+                log.mutations.push(Mutation { path: rel, kind });
+            }
+        }
+
+        for i in 0..creates {
+            let parent = live
+                .get(rng.random_range(0..live.len().max(1)))
+                .and_then(|p| p.rsplit_once('/'))
+                .map(|(parent, _)| format!("{parent}/"))
+                .unwrap_or_default();
+            let rel = format!("{}mutation_{:x}_{:04}.bin", parent, self.seed, i);
+            let size = rng.random_range(1..=MAX_MUTATION_BYTES);
+            let path = dir.join(&rel);
+            if path.exists() {
+                return Err(Error::invalid_spec(path, "mutation target already exists"));
+            }
+            let data = seeded_content(SeededFileKind::Binary, size as usize, rng.random());
+            fs::write(&path, &data).map_err(|e| Error::write(&path, size, e))?;
+            log.mutations.push(Mutation {
+                path: rel,
+                kind: MutationKind::Create { size },
+            });
+        }
+
+        Ok(log)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MutationStep {
+    Append,
+    Overwrite,
+    Rename,
+    Delete,
+}
+
+/// Perform one mutation on `rel` and describe what changed
+fn apply_step(dir: &Path, rel: &str, step: MutationStep, rng: &mut StdRng) -> Result<MutationKind> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let path = dir.join(rel);
+    match step {
+        MutationStep::Append => {
+            let start = fs::metadata(&path).at_path(&path)?.len();
+            let len = rng.random_range(1..=MAX_MUTATION_BYTES);
+            let data = seeded_content(SeededFileKind::Binary, len as usize, rng.random());
+            let mut file = fs::OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .at_path(&path)?;
+            file.write_all(&data)
+                .map_err(|e| Error::write(&path, len, e))?;
+            Ok(MutationKind::Append {
+                start,
+                end: start + len,
+            })
+        }
+        MutationStep::Overwrite => {
+            let size = fs::metadata(&path).at_path(&path)?.len();
+            if size == 0 {
+                // Nothing to overwrite in place; grow by one byte instead
+                fs::write(&path, [0xFFu8]).map_err(|e| Error::write(&path, 1, e))?;
+                return Ok(MutationKind::Append { start: 0, end: 1 });
+            }
+            let start = rng.random_range(0..size);
+            let end = start + rng.random_range(1..=MAX_MUTATION_BYTES.min(size - start));
+            let mut file = fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&path)
+                .at_path(&path)?;
+            let mut range = vec![0u8; (end - start) as usize];
+            file.seek(SeekFrom::Start(start)).at_path(&path)?;
+            file.read_exact(&mut range).at_path(&path)?;
+            // Inverting guarantees every byte in the range actually changes
+            for b in &mut range {
+                *b = !*b;
+            }
+            file.seek(SeekFrom::Start(start)).at_path(&path)?;
+            file.write_all(&range)
+                .map_err(|e| Error::write(&path, end - start, e))?;
+            Ok(MutationKind::Overwrite { start, end })
+        }
+        MutationStep::Rename => {
+            let to = format!("{rel}.renamed");
+            let target = dir.join(&to);
+            if target.exists() {
+                return Err(Error::invalid_spec(target, "rename target already exists"));
+            }
+            fs::rename(&path, &target).at_path(&path)?;
+            Ok(MutationKind::Rename { to })
+        }
+        MutationStep::Delete => {
+            fs::remove_file(&path).at_path(&path)?;
+            Ok(MutationKind::Delete)
+        }
+    }
+}
+
+/// Metadata to apply to (or expect on) a fixture file
+///
+/// Unset fields are left alone when creating and skipped when comparing.
+/// Extended attributes are only applied on Linux with the `xattrs` feature.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileMetaSpec {
+    /// Permission bits (e.g. `0o640`); only read-only-ness maps off Unix
+    pub mode: Option<u32>,
+    /// Modification time
+    pub mtime: Option<SystemTime>,
+    /// Access time
+    pub atime: Option<SystemTime>,
+    /// Extended attributes as (name, value)
+    pub xattrs: Vec<(String, Vec<u8>)>,
+}
+
+impl FileMetaSpec {
+    /// Empty spec
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture the mode and timestamps of an existing file
+    ///
+    /// Extended attributes are captured when the `xattrs` feature is enabled
+    /// on Linux.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let metadata = fs::metadata(path).at_path(path)?;
+        Ok(Self {
+            mode: Some(metadata_mode(&metadata)),
+            mtime: metadata.modified().ok(),
+            atime: metadata.accessed().ok(),
+            xattrs: read_xattrs(path)?,
+        })
+    }
+
+    /// Set permission bits
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Set modification time
+    pub fn with_mtime(mut self, mtime: SystemTime) -> Self {
+        self.mtime = Some(mtime);
+        self
+    }
+
+    /// Set access time
+    pub fn with_atime(mut self, atime: SystemTime) -> Self {
+        self.atime = Some(atime);
+        self
+    }
+
+    /// Add an extended attribute
+    pub fn with_xattr(mut self, name: impl Into<String>, value: impl Into<Vec<u8>>) -> Self {
+        self.xattrs.push((name.into(), value.into()));
+        self
+    }
+
+    /// Apply this spec to an existing file
+    ///
+    /// Timestamps and xattrs are set before the mode so read-only modes do
+    /// not block them.
+    pub(crate) fn apply(&self, path: &Path) -> Result<()> {
+        if self.mtime.is_some() || self.atime.is_some() {
+            let mut times = fs::FileTimes::new();
+            if let Some(mtime) = self.mtime {
+                times = times.set_modified(mtime);
+            }
+            if let Some(atime) = self.atime {
+                times = times.set_accessed(atime);
+            }
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .and_then(|f| f.set_times(times))
+                .at_path(path)?;
+        }
+
+        #[cfg(all(feature = "xattrs", target_os = "linux"))]
+        for (name, value) in &self.xattrs {
+            xattr::set(path, name, value).at_path(path)?;
+        }
+
+        if let Some(mode) = self.mode {
+            fs::set_permissions(path, permissions_for_mode(path, mode)?).at_path(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Permission bits of `metadata` (Unix mode, or a read-only approximation)
+pub(crate) fn metadata_mode(metadata: &fs::Metadata) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o7777
+    }
+    #[cfg(not(unix))]
+    {
+        if metadata.permissions().readonly() {
+            0o444
+        } else {
+            0o644
+        }
+    }
+}
+
+/// Permissions for Unix `mode` (only the write bits count elsewhere)
+#[cfg(unix)]
+pub(crate) fn permissions_for_mode(_path: &Path, mode: u32) -> Result<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn permissions_for_mode(path: &Path, mode: u32) -> Result<fs::Permissions> {
+    let mut permissions = fs::metadata(path).at_path(path)?.permissions();
+    permissions.set_readonly(mode & 0o222 == 0);
+    Ok(permissions)
+}
+
+/// Extended attributes of `path`, sorted by name (empty when unsupported)
+pub(crate) fn read_xattrs(path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    #[cfg(all(feature = "xattrs", target_os = "linux"))]
+    {
+        let mut attrs = Vec::new();
+        for name in xattr::list(path).at_path(path)? {
+            if let Some(value) = xattr::get(path, &name).at_path(path)? {
+                attrs.push((name.to_string_lossy().into_owned(), value));
+            }
+        }
+        attrs.sort();
+        Ok(attrs)
+    }
+    #[cfg(not(all(feature = "xattrs", target_os = "linux")))]
+    {
+        let _ = path;
+        Ok(Vec::new())
+    }
+}
+
+/// Write a file of specified size with pattern
+///
+/// Streams through [`write_pattern_to`], so the content is never held in
+/// memory as a whole.
+pub fn write_file_of_size(
+    path: &Path,
+    size_bytes: usize,
+    pattern: TestDataPattern,
+) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    write_pattern_to(&mut file, size_bytes, pattern)
+}
+
+/// File sizes that straddle multiples of `chunk_size`, ascending and distinct
+///
+/// 0, 1, chunk-1, chunk, chunk+1, 2*chunk-1, 2*chunk, 4*chunk+3 and the
+/// smallest prime above 8*chunk. Coinciding sizes (for tiny chunks) appear
+/// once.
+pub fn boundary_sizes(chunk_size: usize) -> Vec<usize> {
+    let c = chunk_size;
+    let mut sizes = vec![
+        0,
+        1,
+        c.saturating_sub(1),
+        c,
+        c + 1,
+        (2 * c).saturating_sub(1),
+        2 * c,
+        4 * c + 3,
+        next_prime(8 * c + 1),
+    ];
+    sizes.sort_unstable();
+    sizes.dedup();
+    sizes
+}
+
+/// Smallest prime `>= n`
+fn next_prime(n: usize) -> usize {
+    let is_prime = |k: usize| {
+        k >= 2
+            && (2..)
+                .take_while(|d| d * d <= k)
+                .all(|d| !k.is_multiple_of(d))
+    };
+    (n..).find(|&k| is_prime(k)).expect("primes are unbounded")
+}
+
+/// Create one file per [`boundary_sizes`] entry under `dir`
+///
+/// The canonical fixture for chunking roundtrips: bugs cluster at sizes
+/// one byte either side of a chunk boundary. Files are named
+/// `boundary_<size>.bin` and a [`DatasetManifest`] is written next to
+/// `dir`. Returns each path with its size, smallest first.
+pub fn create_boundary_size_files(
+    dir: &Path,
+    chunk_size: usize,
+    pattern: TestDataPattern,
+) -> Vec<(PathBuf, usize)> {
+    try_create_boundary_size_files(dir, chunk_size, pattern)
+        .unwrap_or_else(|e| panic!("Failed to create boundary size files: {}", e))
+}
+
+/// Fallible variant of [`create_boundary_size_files`]
+///
+/// # Errors
+/// Returns [`Error::InvalidSpec`] for a zero `chunk_size`, or the I/O error
+/// for the file that could not be written.
+pub fn try_create_boundary_size_files(
+    dir: &Path,
+    chunk_size: usize,
+    pattern: TestDataPattern,
+) -> Result<Vec<(PathBuf, usize)>> {
+    if chunk_size == 0 {
+        return Err(Error::invalid_spec(dir, "chunk_size must be positive"));
+    }
+    fs::create_dir_all(dir).at_path(dir)?;
+
+    let kind = match pattern {
+        TestDataPattern::Text | TestDataPattern::Compressible => SeededFileKind::Text,
+        _ => SeededFileKind::Binary,
+    };
+    let mut created = Vec::new();
+    let mut entries = Vec::new();
+    for size in boundary_sizes(chunk_size) {
+        let name = format!("boundary_{}.bin", size);
+        let path = dir.join(&name);
+        write_pattern_file(&path, &[], size, pattern)?;
+        entries.push(ManifestEntry {
+            path: name,
+            size: size as u64,
+            kind,
+            file_kind: None,
+            extension_mismatch: false,
+            pattern: Some(pattern),
+        });
+        created.push((path, size));
+    }
+
+    let seed = match pattern {
+        TestDataPattern::Seeded(seed) => seed,
+        _ => 0,
+    };
+    DatasetManifest {
+        seed,
+        files: entries,
+        outcome: GenerationOutcome::Completed,
+    }
+    .save(&DatasetManifest::path_for(dir))?;
+    Ok(created)
+}
+
+/// Order in which [`walk_deterministic`] lists files
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WalkOrder {
+    /// Ascending by relative path
+    Sorted,
+    /// Descending by relative path
+    ReverseSorted,
+    /// Fisher-Yates shuffle of the sorted list, driven by this seed
+    SeededShuffle(u64),
+}
+
+/// Regular files under `dir` in a reproducible order
+///
+/// Unlike `read_dir`, the result never depends on the filesystem: the
+/// files are sorted by relative path first, and a shuffle is a function of
+/// the seed and that sorted list only. Symlinks and special files are
+/// skipped.
+pub fn walk_deterministic(dir: &Path, order: WalkOrder) -> Vec<PathBuf> {
+    try_walk_deterministic(dir, order).unwrap_or_else(|e| panic!("Failed to walk directory: {}", e))
+}
+
+/// Fallible variant of [`walk_deterministic`]
+pub fn try_walk_deterministic(dir: &Path, order: WalkOrder) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = crate::integrity::collect_files(dir)
+        .at_path(dir)?
+        .into_iter()
+        .map(|(_, path)| path)
+        .collect();
+    match order {
+        WalkOrder::Sorted => {}
+        WalkOrder::ReverseSorted => paths.reverse(),
+        WalkOrder::SeededShuffle(seed) => {
+            // Own LCG rather than `rand` so orders stay stable across versions
+            let mut state = seed;
+            for i in (1..paths.len()).rev() {
+                let j = (lcg(&mut state) % (i as u64 + 1)) as usize;
+                paths.swap(i, j);
+            }
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_create_test_data() {
+        let data = create_test_data(1, TestDataPattern::Zeros);
+        assert_eq!(data.len(), 1024 * 1024);
+        assert!(data.iter().all(|&b| b == 0));
+
+        let data = create_test_data(1, TestDataPattern::Ones);
+        assert!(data.iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn test_sequential_pattern() {
+        let data = create_test_data_bytes(512, TestDataPattern::Sequential);
+        assert_eq!(data.len(), 512);
+        for (i, &byte) in data.iter().enumerate().take(256) {
+            assert_eq!(byte, i as u8);
+        }
+        // Should wrap around
+        for (i, &byte) in data.iter().enumerate().take(512).skip(256) {
+            assert_eq!(byte, (i % 256) as u8);
+        }
+    }
+
+    #[test]
+    fn test_compressible_pattern() {
+        let data = create_test_data_bytes(100, TestDataPattern::Compressible);
+        let pattern = b"The quick brown fox jumps over the lazy dog. ";
+
+        // Check first occurrence
+        assert_eq!(&data[0..pattern.len()], pattern);
+    }
+
+    #[test]
+    fn test_verify_data_sampled() {
+        let data = create_test_data_bytes(10000, TestDataPattern::Sequential);
+        // Should not panic
+        verify_data_sampled(&data, TestDataPattern::Sequential, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Mismatch at position")]
+    fn test_verify_data_sampled_mismatch() {
+        let mut data = create_test_data_bytes(1000, TestDataPattern::Sequential);
+        data[500] = 0xFF; // Corrupt data
+        verify_data_sampled(&data, TestDataPattern::Sequential, 100);
+    }
+
+    #[test]
+    fn test_create_test_dataset() {
+        let temp_dir = TempDir::new().unwrap();
+        let dataset_path = temp_dir.path().join("dataset");
+
+        let file_count = create_test_dataset(&dataset_path, 5, TestDataPattern::Random);
+
+        assert!(file_count > 0);
+        assert!(dataset_path.exists());
+
+        // Verify total size is approximately correct
+        let mut total_size = 0;
+        for entry in fs::read_dir(&dataset_path).unwrap() {
+            let entry = entry.unwrap();
+            let metadata = entry.metadata().unwrap();
+            total_size += metadata.len();
+        }
+
+        let expected_size = 5 * 1024 * 1024;
+        assert!(total_size >= expected_size - 1024 * 1024); // Within 1MB
+        assert!(total_size <= expected_size + 1024 * 1024);
+    }
+
+    #[test]
+    fn test_try_verify_file_sampled() {
+        let temp_dir = TempDir::new().unwrap();
+        let filepath = temp_dir.path().join("seq.bin");
+        write_file_of_size(&filepath, 4096, TestDataPattern::Sequential).unwrap();
+        try_verify_file_sampled(&filepath, TestDataPattern::Sequential, 64).unwrap();
+
+        let err = try_verify_file_sampled(&filepath, TestDataPattern::Zeros, 64).unwrap_err();
+        assert!(matches!(err, Error::PatternMismatch { offset: 64, .. }));
+        assert_eq!(err.path(), filepath);
+
+        let err = try_verify_file_sampled(&filepath, TestDataPattern::Sequential, 0).unwrap_err();
+        assert!(matches!(err, Error::InvalidSpec { .. }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_try_create_test_dataset_read_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dataset_path = temp_dir.path().join("readonly");
+        fs::create_dir(&dataset_path).unwrap();
+        fs::set_permissions(&dataset_path, fs::Permissions::from_mode(0o555)).unwrap();
+
+        // Privileged users (e.g. root in containers) bypass permission bits
+        let probe = dataset_path.join(".probe");
+        if fs::write(&probe, b"").is_ok() {
+            fs::remove_file(&probe).unwrap();
+            eprintln!(
+                "skipping test_try_create_test_dataset_read_only: permission bits are not \
+                 enforced for this user (running as root?)"
+            );
+            return;
+        }
+
+        let err = try_create_test_dataset(&dataset_path, 1, TestDataPattern::Zeros).unwrap_err();
+        fs::set_permissions(&dataset_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        match err {
+            Error::Io { path, source } => {
+                assert_eq!(path, dataset_path.join("file_0000.bin"));
+                assert_eq!(source.kind(), std::io::ErrorKind::PermissionDenied);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_create_test_dataset_async_matches_sync() {
+        let temp_dir = TempDir::new().unwrap();
+        let sync_path = temp_dir.path().join("sync");
+        let async_path = temp_dir.path().join("async");
+
+        let sync_count = try_create_test_dataset(&sync_path, 10, TestDataPattern::Random).unwrap();
+        let async_count = create_test_dataset_async(&async_path, 10, TestDataPattern::Random, 4)
+            .await
+            .unwrap();
+        assert_eq!(sync_count, async_count);
+
+        let report = crate::integrity::compare_directories_async(&sync_path, &async_path, 4)
+            .await
+            .unwrap();
+        assert!(report.is_ok(), "{}", report.summary());
+        assert_eq!(report.checks_passed as usize, sync_count);
+
+        // No temp files left behind
+        assert!(fs::read_dir(&async_path).unwrap().all(|e| !e
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .ends_with(".partial")));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_verify_file_pattern_mmap() {
+        use crate::mmap::MmapConfig;
+
+        let temp_dir = TempDir::new().unwrap();
+        let filepath = temp_dir.path().join("seq.bin");
+        write_file_of_size(&filepath, 2 * 1024 * 1024 + 7, TestDataPattern::Sequential).unwrap();
+
+        let config = MmapConfig::new().with_window_size(64 * 1024);
+        verify_file_pattern_mmap_with(&filepath, TestDataPattern::Sequential, &config).unwrap();
+
+        // Corrupt the first byte of the third window
+        let mut data = fs::read(&filepath).unwrap();
+        data[128 * 1024] ^= 0x01;
+        fs::write(&filepath, data).unwrap();
+
+        for config in [
+            config,
+            MmapConfig::new().with_window_size(64 * 1024).buffered(),
+        ] {
+            let err =
+                verify_file_pattern_mmap_with(&filepath, TestDataPattern::Sequential, &config)
+                    .unwrap_err();
+            assert!(matches!(err, Error::PatternMismatch { offset: 131072, .. }));
+        }
+    }
+
+    #[test]
+    fn test_dataset_progress_is_monotonic() {
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        let temp_dir = TempDir::new().unwrap();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink_reports = reports.clone();
+        let options = DatasetOptions::new().with_progress_options(
+            ProgressOptions::new()
+                .with_sink(move |done, total: Option<u64>, _phase: &str| {
+                    sink_reports.lock().unwrap().push((done, total))
+                })
+                .with_min_interval(Duration::ZERO),
+        );
+
+        let count = try_create_test_dataset_with(
+            &temp_dir.path().join("dataset"),
+            3,
+            TestDataPattern::Sequential,
+            &options,
+        )
+        .unwrap()
+        .files;
+
+        let reports = reports.lock().unwrap();
+        let total = 3 * 1024 * 1024;
+        assert_eq!(reports.len(), count);
+        assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(*reports.last().unwrap(), (total, Some(total)));
+    }
+
+    #[test]
+    fn test_seeded_content_is_deterministic() {
+        for kind in SeededFileKind::ALL {
+            let a = seeded_content(kind, 1000, 7);
+            assert_eq!(a.len(), 1000);
+            assert_eq!(a, seeded_content(kind, 1000, 7));
+            assert_ne!(a, seeded_content(kind, 1000, 8));
+        }
+
+        let total: usize = seeded_dataset_entries(2, 3).map(|(_, _, c)| c.len()).sum();
+        assert_eq!(total, 2 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_sample_sizes_hit_budget() {
+        let budget = 50 * 1024 * 1024;
+        let distributions = [
+            FileSizeDistribution::Fixed(4096),
+            FileSizeDistribution::UniformRange {
+                min: 1024,
+                max: 64 * 1024,
+            },
+            FileSizeDistribution::Steps,
+            FileSizeDistribution::LogNormal {
+                mu: 9.0,
+                sigma: 1.5,
+            },
+            FileSizeDistribution::Pareto {
+                alpha: 1.2,
+                min: 1024,
+            },
+        ];
+
+        for dist in distributions {
+            let mut rng = StdRng::seed_from_u64(1);
+            let sizes = dist.sample_sizes(&mut rng, 500, budget);
+            let total: usize = sizes.iter().sum();
+            assert!(
+                total.abs_diff(budget) <= budget / 50,
+                "{:?}: total {} vs budget {}",
+                dist,
+                total,
+                budget
+            );
+            let largest = *sizes.iter().max().unwrap();
+            assert!(
+                largest < budget / 4,
+                "{:?}: outlier of {} bytes",
+                dist,
+                largest
+            );
+        }
+    }
+
+    #[test]
+    fn test_lognormal_spread() {
+        let dist = FileSizeDistribution::LogNormal {
+            mu: 9.0,
+            sigma: 1.0,
+        };
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut samples: Vec<usize> = (0..10_000).map(|_| dist.sample(&mut rng)).collect();
+        samples.sort_unstable();
+
+        let median = samples[samples.len() / 2] as f64;
+        let mean = samples.iter().sum::<usize>() as f64 / samples.len() as f64;
+        // median ~ e^9 ~ 8103, mean ~ e^9.5 ~ 13360
+        assert!((7000.0..9500.0).contains(&median), "median {}", median);
+        assert!(mean > median * 1.3, "mean {} median {}", mean, median);
+    }
+
+    #[test]
+    fn test_dataset_with_size_distribution() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = DatasetOptions::new()
+            .with_size_distribution(FileSizeDistribution::Pareto {
+                alpha: 1.5,
+                min: 2048,
+            })
+            .with_seed(9);
+        let dir = temp_dir.path().join("pareto");
+        let count = try_create_test_dataset_with(&dir, 4, TestDataPattern::Random, &options)
+            .unwrap()
+            .files;
+
+        let total: u64 = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().metadata().unwrap().len())
+            .sum();
+        assert_eq!(count, fs::read_dir(&dir).unwrap().count());
+        assert_eq!(total, 4 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_expired_budget_and_file_cap_stop_at_file_boundary() {
+        use crate::integrity::ChecksumManifest;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("expired");
+        let options = DatasetOptions::new().time_budget(Duration::ZERO);
+        let dataset =
+            try_create_test_dataset_with(&dir, 4, TestDataPattern::Sequential, &options).unwrap();
+        assert_eq!(
+            dataset.outcome,
+            GenerationOutcome::Truncated { written_bytes: 0 }
+        );
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        let dir = temp_dir.path().join("capped");
+        let options = DatasetOptions::new().max_files(3);
+        let dataset =
+            try_create_test_dataset_with(&dir, 4, TestDataPattern::Sequential, &options).unwrap();
+        let plan = options.file_plan(4);
+        assert_eq!(dataset.files, 3);
+        assert_eq!(
+            dataset.outcome,
+            GenerationOutcome::Truncated {
+                written_bytes: plan[..3].iter().map(|(_, size)| *size as u64).sum()
+            }
+        );
+
+        // Sizes and hashes computed from the pattern, not from the files
+        let manifest = ChecksumManifest::capture(&dir).unwrap();
+        assert_eq!(manifest.entries.len(), 3);
+        for (name, size) in &plan[..3] {
+            let entry = &manifest.entries[name];
+            let expected = create_test_data_bytes(*size, TestDataPattern::Sequential);
+            assert_eq!(entry.size, *size as u64);
+            assert_eq!(entry.hash, blake3::hash(&expected).to_hex().to_string());
+        }
+    }
+
+    #[test]
+    #[ignore = "writes up to 512 MB against a 50 ms wall-clock budget"]
+    fn test_time_budget_truncates_at_file_boundary() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("bounded");
+        let options = DatasetOptions::new().time_budget(Duration::from_millis(50));
+        let dataset =
+            try_create_test_dataset_with(&dir, 512, TestDataPattern::Sequential, &options).unwrap();
+
+        let GenerationOutcome::Truncated { written_bytes } = dataset.outcome else {
+            panic!("512MB in 50ms should not complete");
+        };
+        let plan = options.file_plan(512);
+        assert!(dataset.files < plan.len());
+        assert_eq!(dataset.files, fs::read_dir(&dir).unwrap().count());
+
+        // Every written file is complete and matches the plan
+        let mut total = 0;
+        for (name, size) in &plan[..dataset.files] {
+            let data = fs::read(dir.join(name)).unwrap();
+            assert_eq!(data.len(), *size);
+            assert_eq!(
+                data,
+                create_test_data_bytes(*size, TestDataPattern::Sequential)
+            );
+            total += *size as u64;
+        }
+        assert_eq!(written_bytes, total);
+    }
+
+    #[test]
+    fn test_interrupted_generation_resumes() {
+        use crate::integrity::ChecksumManifest;
+
+        let temp_dir = TempDir::new().unwrap();
+        let options = DatasetOptions::new()
+            .with_size_distribution(FileSizeDistribution::UniformRange {
+                min: 1024,
+                max: 64 * 1024,
+            })
+            .with_seed(11)
+            .self_describing(true)
+            .resumable(true);
+        let pattern = TestDataPattern::Seeded(5);
+
+        let reference = temp_dir.path().join("reference");
+        let full = try_create_test_dataset_with(&reference, 2, pattern, &options).unwrap();
+        assert_eq!(full.start, GenerationStart::Fresh);
+        assert!(!GenerationJournal::path_for(&reference).exists());
+
+        // Interrupt after 10 files, leaving a journal behind
+        let dir = temp_dir.path().join("resumed");
+        let capped = options.clone().max_files(10);
+        let partial = try_create_test_dataset_with(&dir, 2, pattern, &capped).unwrap();
+        assert!(partial.is_truncated());
+        assert_eq!(partial.files, 10);
+        let journal_path = GenerationJournal::path_for(&dir);
+        assert_eq!(
+            GenerationJournal::load(&journal_path)
+                .unwrap()
+                .completed_files,
+            10
+        );
+
+        // A relative path to the same directory finds the same journal
+        assert_eq!(GenerationJournal::path_for(&dir.join(".")), journal_path);
+
+        // Damage two journaled files, one keeping its size; both must be
+        // regenerated on resume
+        let plan = options.file_plan(2);
+        fs::write(dir.join(&plan[3].0), b"short").unwrap();
+        let flipped = dir.join(&plan[5].0);
+        let mut data = fs::read(&flipped).unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        fs::write(&flipped, data).unwrap();
+
+        let resumed = try_create_test_dataset_with(&dir, 2, pattern, &options).unwrap();
+        assert_eq!(resumed.outcome, GenerationOutcome::Completed);
+        assert_eq!(resumed.files, plan.len());
+        assert_eq!(
+            resumed.start,
+            GenerationStart::Resumed {
+                skipped_files: 8,
+                regenerated_files: 2
+            }
+        );
+        assert!(!journal_path.exists());
+
+        let expected = ChecksumManifest::capture(&reference).unwrap();
+        assert!(expected.verify(&dir).unwrap().is_ok());
+
+        // Different inputs ignore a stale journal
+        let other = temp_dir.path().join("other");
+        try_create_test_dataset_with(&other, 2, pattern, &capped).unwrap();
+        let fresh =
+            try_create_test_dataset_with(&other, 2, TestDataPattern::Zeros, &options).unwrap();
+        assert_eq!(fresh.start.skipped_files(), 0);
+        assert!(!fresh.start.is_resumed());
+
+        // Without resumable, no journal is read or written
+        let plain = temp_dir.path().join("plain");
+        let plain_capped = capped.clone().resumable(false);
+        try_create_test_dataset_with(&plain, 2, pattern, &plain_capped).unwrap();
+        assert!(!GenerationJournal::path_for(&plain).exists());
+    }
+
+    #[test]
+    fn test_write_file_of_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let filepath = temp_dir.path().join("test.bin");
+
+        write_file_of_size(&filepath, 4096, TestDataPattern::Random).unwrap();
+
+        assert!(filepath.exists());
+        let metadata = fs::metadata(&filepath).unwrap();
+        assert_eq!(metadata.len(), 4096);
+    }
+
+    /// Hashes what it is given and remembers the largest single write
+    #[derive(Default)]
+    struct CountingWriter {
+        hasher: blake3::Hasher,
+        bytes: usize,
+        writes: usize,
+        largest_write: usize,
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.hasher.update(buf);
+            self.bytes += buf.len();
+            self.writes += 1;
+            self.largest_write = self.largest_write.max(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_pattern_to_matches_create_test_data() {
+        let patterns = [
+            TestDataPattern::Zeros,
+            TestDataPattern::Ones,
+            TestDataPattern::Sequential,
+            TestDataPattern::Random,
+            TestDataPattern::Seeded(9),
+            TestDataPattern::Compressible,
+            TestDataPattern::Text,
+        ];
+        let block = 64 * 1024;
+        let size = 3 * 1024 * 1024 + 5;
+        for pattern in patterns {
+            let mut expected = create_test_data(3, pattern);
+            expected.extend((0..5).map(|i| pattern_byte(pattern, 3 * 1024 * 1024 + i)));
+
+            let mut writer = CountingWriter::default();
+            let mut metrics = TestMetrics::new("write");
+            write_pattern_to_with(&mut writer, size, pattern, block, Some(&mut metrics)).unwrap();
+            assert_eq!(writer.bytes, size);
+            assert_eq!(
+                writer.hasher.finalize(),
+                blake3::hash(&expected),
+                "{:?}",
+                pattern
+            );
+            assert_eq!(writer.largest_write, block);
+            assert_eq!(writer.writes, size.div_ceil(block));
+            assert_eq!(metrics.timings_ns.len(), 1);
+        }
+
+        // The file variants stream the same bytes
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("streamed.bin");
+        write_file_of_size(&path, 2 * 1024 * 1024, TestDataPattern::Seeded(3)).unwrap();
+        assert_eq!(
+            fs::read(&path).unwrap(),
+            create_test_data(2, TestDataPattern::Seeded(3))
+        );
+
+        let mut small = CountingWriter::default();
+        write_pattern_to(&mut small, 10, TestDataPattern::Text).unwrap();
+        assert_eq!(small.largest_write, 10);
+    }
+
+    fn write_seeded_dataset(dir: &Path, seed: u64) {
+        for (rel, _, content) in seeded_tree_entries(seed) {
+            let path = dir.join(&rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_dataset_mutator_log_matches_manifest_diff() {
+        use crate::integrity::ChecksumManifest;
+
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("data");
+        write_seeded_dataset(&dir, 11);
+        let before = ChecksumManifest::capture(&dir).unwrap();
+
+        let log = DatasetMutator::new(5)
+            .with_appends(1)
+            .with_overwrites(1)
+            .with_renames(1)
+            .with_deletes(1)
+            .with_creates(2)
+            .apply(&dir);
+        assert_eq!(log.len(), 6);
+
+        // No existing file is touched twice
+        let mut touched: Vec<_> = log.mutations.iter().map(|m| &m.path).collect();
+        touched.sort();
+        touched.dedup();
+        assert_eq!(touched.len(), log.len());
+
+        let report = before.diff(&ChecksumManifest::capture(&dir).unwrap());
+        let mut actual: Vec<_> = report
+            .file_mismatches
+            .iter()
+            .map(|m| (m.path.clone(), m.kind))
+            .collect();
+        actual.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(actual, log.expected_mismatches());
+
+        for m in &log.mutations {
+            if let MutationKind::Append { start, end } = m.kind {
+                assert_eq!(fs::metadata(dir.join(&m.path)).unwrap().len(), end);
+                assert!(end > start);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dataset_mutator_is_deterministic_and_bounded() {
+        let temp = TempDir::new().unwrap();
+        let (a, b) = (temp.path().join("a"), temp.path().join("b"));
+        write_seeded_dataset(&a, 3);
+        write_seeded_dataset(&b, 3);
+
+        let mutator = DatasetMutator::new(9).with_overwrites(0.5).with_deletes(1);
+        assert_eq!(mutator.apply(&a), mutator.apply(&b));
+
+        let err = DatasetMutator::new(9).with_deletes(1000).try_apply(&a);
+        assert!(matches!(err, Err(Error::InvalidSpec { .. })));
+    }
+
+    #[test]
+    fn test_seeded_pattern() {
+        let a = create_test_data(1, TestDataPattern::Seeded(1));
+        let b = create_test_data(1, TestDataPattern::Seeded(2));
+        assert_eq!(a, create_test_data(1, TestDataPattern::Seeded(1)));
+        let same = a.iter().zip(&b).filter(|(x, y)| x == y).count();
+        // Unrelated streams agree on roughly 1/256 of bytes
+        assert!(same < a.len() / 64, "{} matching bytes", same);
+        assert_ne!(a, create_test_data(1, TestDataPattern::Random));
+
+        // Verification only needs (seed, position)
+        verify_data_sampled(&a, TestDataPattern::Seeded(1), 1000);
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("seeded.bin");
+        write_file_of_size(&path, 10_000, TestDataPattern::Seeded(7)).unwrap();
+        let tail = &fs::read(&path).unwrap()[5_000..];
+        assert!(tail
+            .iter()
+            .enumerate()
+            .all(|(i, &b)| b == pattern_byte(TestDataPattern::Seeded(7), 5_000 + i)));
+
+        let result =
+            std::panic::catch_unwind(|| verify_data_sampled(&b, TestDataPattern::Seeded(1), 100));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_boundary_size_files() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("boundary");
+        let files = create_boundary_size_files(&dir, 4096, TestDataPattern::Sequential);
+        let sizes: Vec<usize> = files.iter().map(|(_, size)| *size).collect();
+        assert_eq!(
+            sizes,
+            vec![0, 1, 4095, 4096, 4097, 8191, 8192, 16_387, 32_771]
+        );
+        for (path, size) in &files {
+            assert_eq!(fs::metadata(path).unwrap().len(), *size as u64);
+            let data = fs::read(path).unwrap();
+            assert!(data
+                .iter()
+                .enumerate()
+                .all(|(i, &b)| b == pattern_byte(TestDataPattern::Sequential, i)));
+        }
+        let manifest = DatasetManifest::load(&DatasetManifest::path_for(&dir)).unwrap();
+        assert_eq!(manifest.files.len(), files.len());
+        assert!(manifest.verify(&dir).unwrap().is_ok());
+        assert!(manifest
+            .files
+            .iter()
+            .all(|f| f.kind == SeededFileKind::Binary
+                && f.pattern == Some(TestDataPattern::Sequential)));
+
+        let text_dir = temp.path().join("boundary_text");
+        create_boundary_size_files(&text_dir, 64, TestDataPattern::Text);
+        let manifest = DatasetManifest::load(&DatasetManifest::path_for(&text_dir)).unwrap();
+        assert!(manifest
+            .files
+            .iter()
+            .all(|f| f.kind == SeededFileKind::Text && f.pattern == Some(TestDataPattern::Text)));
+
+        // Tiny chunks collapse coinciding sizes instead of duplicating files
+        assert_eq!(boundary_sizes(1), vec![0, 1, 2, 7, 11]);
+        let err = try_create_boundary_size_files(&dir, 0, TestDataPattern::Zeros);
+        assert!(matches!(err, Err(Error::InvalidSpec { .. })));
+    }
+
+    #[test]
+    fn test_walk_deterministic_orders() {
+        let temp = TempDir::new().unwrap();
+        write_seeded_dataset(temp.path(), 11);
+        let sorted = walk_deterministic(temp.path(), WalkOrder::Sorted);
+        assert!(sorted.len() > 3);
+
+        let mut reversed = walk_deterministic(temp.path(), WalkOrder::ReverseSorted);
+        reversed.reverse();
+        assert_eq!(reversed, sorted);
+
+        let a = walk_deterministic(temp.path(), WalkOrder::SeededShuffle(7));
+        let b = walk_deterministic(temp.path(), WalkOrder::SeededShuffle(7));
+        assert_eq!(a, b);
+        assert_ne!(a, sorted);
+        assert_ne!(
+            a,
+            walk_deterministic(temp.path(), WalkOrder::SeededShuffle(8))
+        );
+        let shuffled: std::collections::HashSet<_> = a.iter().collect();
+        assert_eq!(shuffled, sorted.iter().collect());
+    }
+}
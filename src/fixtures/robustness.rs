@@ -0,0 +1,327 @@
+//! Clean/corrupted payload pairs with the outcome a decoder must reach
+//!
+//! [`robustness_suite`] builds the cases once from a seed; every decoder
+//! robustness test can then run them through [`run_robustness_cases`]
+//! instead of hand-rolling its own corruptions and expectations.
+
+use super::{create_test_data_bytes, TestDataPattern};
+use crate::chaos::{ChaosInjector, TruncateAmount};
+use crate::integrity::IntegrityReport;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+use std::time::Instant;
+
+/// Seed used by [`run_robustness_suite`]
+pub const DEFAULT_ROBUSTNESS_SEED: u64 = 0x0B57_AC1E;
+
+/// Validator name on the checks recorded by [`run_robustness_cases`]
+const VALIDATOR: &str = "robustness_suite";
+
+/// Payload sizes in every suite, including the degenerate ones
+const SIZES: [usize; 5] = [0, 1, 7, 256, 4096];
+
+/// Longest run of bytes inverted by [`CorruptionRecipe::Burst`]
+const MAX_BURST: usize = 16;
+
+/// How a corrupted payload was derived from its clean one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CorruptionRecipe {
+    /// One or two single-bit flips ([`ChaosInjector::corrupt_copy`])
+    BitFlip,
+    /// A contiguous run of inverted bytes
+    Burst,
+    /// Tail cut off ([`ChaosInjector::truncate`])
+    Truncation,
+    /// Whole packets zeroed ([`ChaosInjector::simulate_packet_loss`])
+    ZeroFill,
+    /// Chunks delivered twice ([`ChaosInjector::duplicate_chunks`])
+    Duplication,
+}
+
+impl CorruptionRecipe {
+    /// Every recipe, in suite order
+    pub const ALL: [CorruptionRecipe; 5] = [
+        CorruptionRecipe::BitFlip,
+        CorruptionRecipe::Burst,
+        CorruptionRecipe::Truncation,
+        CorruptionRecipe::ZeroFill,
+        CorruptionRecipe::Duplication,
+    ];
+
+    /// Short lowercase name
+    pub fn name(self) -> &'static str {
+        match self {
+            CorruptionRecipe::BitFlip => "bitflip",
+            CorruptionRecipe::Burst => "burst",
+            CorruptionRecipe::Truncation => "truncation",
+            CorruptionRecipe::ZeroFill => "zero_fill",
+            CorruptionRecipe::Duplication => "duplication",
+        }
+    }
+}
+
+/// What a decoder fed the corrupted payload must do
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExpectedOutcome {
+    /// Return an error; any returned data counts as silent corruption
+    MustDetect,
+    /// Either return an error or return exactly the clean payload
+    MayRecover,
+    /// Return exactly the clean payload (the corruption changed nothing)
+    MustRecover,
+}
+
+impl fmt::Display for ExpectedOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// One clean/corrupted pair from [`robustness_suite`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RobustnessCase {
+    /// Unique name, e.g. `burst_256b_text`
+    pub name: String,
+    /// Payload as written
+    pub clean: Vec<u8>,
+    /// Payload after `recipe`
+    pub corrupted: Vec<u8>,
+    /// How `corrupted` was produced
+    pub recipe: CorruptionRecipe,
+    /// What the recipe did, e.g. `16 bytes inverted at offset 40`
+    pub description: String,
+    /// Outcome the decoder must reach on `corrupted`
+    pub expected: ExpectedOutcome,
+}
+
+/// Every recipe applied to payloads of 0, 1, 7, 256 and 4096 bytes
+///
+/// Patterns rotate across cases so each recipe meets several of them. The
+/// expectation follows severity: a few flipped bits [`MayRecover`], every
+/// other recipe [`MustDetect`], and a corruption that happened to leave the
+/// payload unchanged (e.g. zero-filling zeros) [`MustRecover`]. The same
+/// seed always gives the same cases.
+///
+/// [`MayRecover`]: ExpectedOutcome::MayRecover
+/// [`MustDetect`]: ExpectedOutcome::MustDetect
+/// [`MustRecover`]: ExpectedOutcome::MustRecover
+pub fn robustness_suite(seed: u64) -> Vec<RobustnessCase> {
+    let patterns = [
+        TestDataPattern::Sequential,
+        TestDataPattern::Seeded(seed),
+        TestDataPattern::Text,
+        TestDataPattern::Compressible,
+        TestDataPattern::Zeros,
+    ];
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut cases = Vec::with_capacity(SIZES.len() * CorruptionRecipe::ALL.len());
+
+    for (i, &size) in SIZES.iter().enumerate() {
+        for (j, recipe) in CorruptionRecipe::ALL.into_iter().enumerate() {
+            let pattern = patterns[(i + j) % patterns.len()];
+            let clean = create_test_data_bytes(size, pattern);
+            let injector = ChaosInjector::new(rng.random());
+            let (corrupted, description) = apply(&injector, &mut rng, recipe, &clean);
+            let expected = if corrupted == clean {
+                ExpectedOutcome::MustRecover
+            } else if recipe == CorruptionRecipe::BitFlip {
+                ExpectedOutcome::MayRecover
+            } else {
+                ExpectedOutcome::MustDetect
+            };
+            cases.push(RobustnessCase {
+                name: format!("{}_{}b_{}", recipe.name(), size, pattern_name(pattern)),
+                clean,
+                corrupted,
+                recipe,
+                description,
+                expected,
+            });
+        }
+    }
+    cases
+}
+
+/// [`run_robustness_cases`] over `robustness_suite(DEFAULT_ROBUSTNESS_SEED)`
+pub fn run_robustness_suite<E, F>(decode: F) -> IntegrityReport
+where
+    E: fmt::Display,
+    F: Fn(&[u8]) -> Result<Vec<u8>, E>,
+{
+    run_robustness_cases(&robustness_suite(DEFAULT_ROBUSTNESS_SEED), decode)
+}
+
+/// Check `decode` against each case's expectation
+///
+/// Records two checks per case: `<name>.clean`, that the clean payload
+/// decodes to itself, and `<name>`, that the corrupted payload meets
+/// [`RobustnessCase::expected`]. Failures name the recipe description.
+pub fn run_robustness_cases<E, F>(cases: &[RobustnessCase], decode: F) -> IntegrityReport
+where
+    E: fmt::Display,
+    F: Fn(&[u8]) -> Result<Vec<u8>, E>,
+{
+    let mut report = IntegrityReport::new();
+    for case in cases {
+        let started = Instant::now();
+        let failure = match decode(&case.clean) {
+            Ok(out) if out == case.clean => None,
+            Ok(_) => Some(format!(
+                "{}: clean payload decoded to different bytes",
+                case.name
+            )),
+            Err(e) => Some(format!("{}: clean payload rejected: {}", case.name, e)),
+        };
+        report.record_check(
+            VALIDATOR,
+            format!("{}.clean", case.name),
+            started.elapsed(),
+            failure,
+        );
+
+        let started = Instant::now();
+        let outcome = decode(&case.corrupted);
+        let failure = match (case.expected, outcome) {
+            (ExpectedOutcome::MustRecover, Ok(out)) | (ExpectedOutcome::MayRecover, Ok(out))
+                if out == case.clean =>
+            {
+                None
+            }
+            (ExpectedOutcome::MustDetect | ExpectedOutcome::MayRecover, Err(_)) => None,
+            (ExpectedOutcome::MustRecover, Err(e)) => Some(format!("rejected: {}", e)),
+            (_, Ok(_)) => Some("returned corrupted data without an error".to_string()),
+        };
+        let failure = failure.map(|why| {
+            format!(
+                "{} ({}; expected {}): {}",
+                case.name, case.description, case.expected, why
+            )
+        });
+        report.record_check(VALIDATOR, case.name.as_str(), started.elapsed(), failure);
+    }
+    report
+}
+
+/// Corrupted copy of `clean` and a description of what changed
+fn apply(
+    injector: &ChaosInjector,
+    rng: &mut StdRng,
+    recipe: CorruptionRecipe,
+    clean: &[u8],
+) -> (Vec<u8>, String) {
+    let len = clean.len();
+    let unit = (len / 8).max(1);
+    match recipe {
+        CorruptionRecipe::BitFlip => {
+            let rate = if len == 0 { 0.0 } else { 1.5 / len as f64 };
+            let flips = (len as f64 * rate) as usize;
+            (
+                injector.corrupt_copy(clean, rate),
+                format!("{} bit flips", flips),
+            )
+        }
+        CorruptionRecipe::Burst => {
+            let mut out = clean.to_vec();
+            if len == 0 {
+                return (out, "empty burst".to_string());
+            }
+            let start = rng.random_range(0..len);
+            let end = (start + MAX_BURST).min(len);
+            out[start..end].iter_mut().for_each(|b| *b ^= 0xFF);
+            (
+                out,
+                format!("{} bytes inverted at offset {}", end - start, start),
+            )
+        }
+        CorruptionRecipe::Truncation => {
+            let removed = (len / 4).max(1).min(len);
+            (
+                injector.truncate(clean, TruncateAmount::Bytes(removed)),
+                format!("last {} bytes cut", removed),
+            )
+        }
+        CorruptionRecipe::ZeroFill => {
+            let mut out = clean.to_vec();
+            if len > 0 {
+                injector.simulate_packet_loss(&mut out, 0.5, unit);
+            }
+            (out, format!("half of the {}-byte packets zeroed", unit))
+        }
+        CorruptionRecipe::Duplication => {
+            let (out, duplicated) = injector.duplicate_chunks(clean, 0.5, unit);
+            (
+                out,
+                format!("{} of the {}-byte chunks repeated", duplicated.len(), unit),
+            )
+        }
+    }
+}
+
+fn pattern_name(pattern: TestDataPattern) -> &'static str {
+    match pattern {
+        TestDataPattern::Zeros => "zeros",
+        TestDataPattern::Ones => "ones",
+        TestDataPattern::Sequential => "sequential",
+        TestDataPattern::Random => "random",
+        TestDataPattern::Seeded(_) => "seeded",
+        TestDataPattern::Compressible => "compressible",
+        TestDataPattern::Text => "text",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrity::CheckOutcome;
+
+    #[test]
+    fn test_identity_decoder_misses_must_detect_cases() {
+        let cases = robustness_suite(DEFAULT_ROBUSTNESS_SEED);
+        assert_eq!(cases.len(), 25);
+        assert_eq!(cases, robustness_suite(DEFAULT_ROBUSTNESS_SEED));
+        assert!(cases.iter().any(|c| c.clean.is_empty()));
+        assert!(cases.iter().any(|c| c.clean.len() == 1));
+        for expected in [
+            ExpectedOutcome::MustDetect,
+            ExpectedOutcome::MayRecover,
+            ExpectedOutcome::MustRecover,
+        ] {
+            assert!(cases.iter().any(|c| c.expected == expected), "{}", expected);
+        }
+
+        let identity = |data: &[u8]| Ok::<_, String>(data.to_vec());
+        let report = run_robustness_suite(identity);
+        assert_eq!(report.checks_total, 50);
+        for case in &cases {
+            let record = report
+                .records()
+                .iter()
+                .find(|r| r.check == case.name)
+                .unwrap();
+            let should_pass = case.expected == ExpectedOutcome::MustRecover;
+            assert_eq!(
+                record.outcome == CheckOutcome::Passed,
+                should_pass,
+                "{}",
+                case.name
+            );
+        }
+        let failed = report.failures_for(VALIDATOR).len();
+        let changed = cases.iter().filter(|c| c.corrupted != c.clean).count();
+        assert_eq!(failed, changed);
+
+        // A decoder that rejects anything but its own clean payload passes
+        for case in &cases {
+            let verifying = |data: &[u8]| {
+                if data == case.clean.as_slice() {
+                    Ok(data.to_vec())
+                } else {
+                    Err("checksum mismatch")
+                }
+            };
+            let report = run_robustness_cases(std::slice::from_ref(case), verifying);
+            assert!(report.is_ok(), "{:?}", report.failures);
+        }
+    }
+}
@@ -0,0 +1,530 @@
+//! Compact binary files of sparse vectors
+//!
+//! Layout (all integers little-endian):
+//!
+//! | bytes | field                                            |
+//! |-------|--------------------------------------------------|
+//! | 4     | magic `EVCF`                                     |
+//! | 2     | format version (1)                               |
+//! | 2     | reserved, zero                                   |
+//! | 8     | vector count                                     |
+//! | 8     | dimensions (largest index + 1)                   |
+//! | 8     | payload length                                   |
+//! | 8     | checksum: first 8 bytes of a BLAKE3 (see below)  |
+//!
+//! The payload holds each vector as its positive then negative indices,
+//! each list written as a LEB128 length followed by LEB128 deltas between
+//! consecutive sorted indices (the first delta is from zero).
+//!
+//! The checksum hashes the vector count and dimensions, as little-endian
+//! `u64`s, followed by the payload, so a damaged header is caught too.
+
+use crate::error::{CorruptionKind, Error, IoResultExt, Result};
+use embeddenator_vsa::SparseVec;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"EVCF";
+const VERSION: u16 = 1;
+const HEADER_LEN: u64 = 40;
+
+/// Header of a vector corpus file
+///
+/// # Example
+/// ```rust,ignore
+/// let vecs: Vec<SparseVec> = (0..10_000).map(|_| random_sparse_vec(&mut rng, 10_000, 200)).collect();
+/// VectorCorpusFile::write(&path, &vecs)?;
+/// for v in VectorCorpusFile::read_lazy(&path)? {
+///     check(&v?);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VectorCorpusFile {
+    /// Number of vectors
+    pub count: u64,
+    /// One past the largest index in any vector (0 if all are empty)
+    pub dims: u64,
+    /// Encoded payload size in bytes
+    pub payload_len: u64,
+    /// Truncated BLAKE3 of the count, dimensions and payload
+    pub checksum: u64,
+}
+
+impl VectorCorpusFile {
+    /// Write `vecs` to `path`, returning the header
+    ///
+    /// Indices are stored sorted, so vectors read back have sorted `pos` and
+    /// `neg` lists.
+    pub fn write(path: &Path, vecs: &[SparseVec]) -> Result<Self> {
+        let mut payload = Vec::new();
+        let mut dims = 0u64;
+        for v in vecs {
+            for indices in [&v.pos, &v.neg] {
+                let mut sorted = indices.clone();
+                sorted.sort_unstable();
+                if let Some(&last) = sorted.last() {
+                    dims = dims.max(last as u64 + 1);
+                }
+                write_varint(&mut payload, sorted.len() as u64);
+                let mut prev = 0;
+                for index in sorted {
+                    write_varint(&mut payload, (index - prev) as u64);
+                    prev = index;
+                }
+            }
+        }
+
+        let count = vecs.len() as u64;
+        let header = Self {
+            count,
+            dims,
+            payload_len: payload.len() as u64,
+            checksum: finish(checksum_hasher(count, dims).update(&payload)),
+        };
+        let required = HEADER_LEN + header.payload_len;
+        let file = File::create(path).map_err(|e| Error::write(path, required, e))?;
+        let mut out = BufWriter::new(file);
+        out.write_all(&header.encode())
+            .and_then(|_| out.write_all(&payload))
+            .and_then(|_| out.flush())
+            .map_err(|e| Error::write(path, required, e))?;
+        Ok(header)
+    }
+
+    /// Read every vector in `path` into memory
+    ///
+    /// # Errors
+    /// [`Error::Corrupt`] if the header, length or checksum is wrong or a
+    /// vector cannot be decoded; [`Error::Io`] if the file cannot be read.
+    pub fn read(path: &Path) -> Result<Vec<SparseVec>> {
+        let data = fs::read(path).at_path(path)?;
+        let header = Self::decode(path, &data, data.len() as u64)?;
+        let payload = &data[HEADER_LEN as usize..];
+        let actual = finish(checksum_hasher(header.count, header.dims).update(payload));
+        if actual != header.checksum {
+            return Err(checksum_mismatch(path, header.checksum, actual));
+        }
+
+        let mut reader = payload;
+        let vecs = (0..header.count)
+            .map(|record| decode_vector(&mut reader, path, record, header.dims))
+            .collect::<Result<Vec<_>>>()?;
+        if !reader.is_empty() {
+            return Err(undecoded_bytes(path, header.count, reader.len() as u64));
+        }
+        Ok(vecs)
+    }
+
+    /// Open `path` for decoding one vector at a time
+    ///
+    /// The header, length and checksum are validated up front with a
+    /// streaming pass, so corruption is reported before any vector is
+    /// returned and memory use stays constant however large the corpus is.
+    /// Payload bytes left after the last vector turn that vector into an
+    /// [`Error::Corrupt`].
+    pub fn read_lazy(path: &Path) -> Result<VectorCorpusReader> {
+        let mut file = File::open(path).at_path(path)?;
+        let file_len = file.metadata().at_path(path)?.len();
+        let mut head = [0u8; HEADER_LEN as usize];
+        let read = read_up_to(&mut file, &mut head).at_path(path)?;
+        let header = Self::decode(path, &head[..read], file_len)?;
+
+        let mut hasher = checksum_hasher(header.count, header.dims);
+        io::copy(&mut BufReader::new(&mut file), &mut hasher).at_path(path)?;
+        let actual = finish(&hasher);
+        if actual != header.checksum {
+            return Err(checksum_mismatch(path, header.checksum, actual));
+        }
+
+        if header.count == 0 && header.payload_len > 0 {
+            return Err(undecoded_bytes(path, 0, header.payload_len));
+        }
+
+        file.seek(SeekFrom::Start(HEADER_LEN)).at_path(path)?;
+        Ok(VectorCorpusReader {
+            path: path.to_path_buf(),
+            reader: BufReader::new(file),
+            header,
+            next: 0,
+        })
+    }
+
+    fn encode(&self) -> [u8; HEADER_LEN as usize] {
+        let mut out = [0u8; HEADER_LEN as usize];
+        out[0..4].copy_from_slice(MAGIC);
+        out[4..6].copy_from_slice(&VERSION.to_le_bytes());
+        out[8..16].copy_from_slice(&self.count.to_le_bytes());
+        out[16..24].copy_from_slice(&self.dims.to_le_bytes());
+        out[24..32].copy_from_slice(&self.payload_len.to_le_bytes());
+        out[32..40].copy_from_slice(&self.checksum.to_le_bytes());
+        out
+    }
+
+    /// Parse and validate the header at the start of a `file_len`-byte file
+    fn decode(path: &Path, head: &[u8], file_len: u64) -> Result<Self> {
+        if head.len() < 4 || &head[0..4] != MAGIC {
+            return Err(Error::corrupt(path, CorruptionKind::BadMagic));
+        }
+        if head.len() < HEADER_LEN as usize {
+            return Err(Error::corrupt(
+                path,
+                CorruptionKind::Truncated {
+                    expected: HEADER_LEN,
+                    actual: file_len,
+                },
+            ));
+        }
+        let version = u16::from_le_bytes([head[4], head[5]]);
+        if version != VERSION {
+            return Err(Error::corrupt(
+                path,
+                CorruptionKind::UnsupportedVersion(version),
+            ));
+        }
+        let u64_at = |at: usize| u64::from_le_bytes(head[at..at + 8].try_into().unwrap());
+        let header = Self {
+            count: u64_at(8),
+            dims: u64_at(16),
+            payload_len: u64_at(24),
+            checksum: u64_at(32),
+        };
+
+        let expected = HEADER_LEN.saturating_add(header.payload_len);
+        let kind = match file_len.cmp(&expected) {
+            std::cmp::Ordering::Equal => return Ok(header),
+            std::cmp::Ordering::Less => CorruptionKind::Truncated {
+                expected,
+                actual: file_len,
+            },
+            std::cmp::Ordering::Greater => CorruptionKind::TrailingData {
+                extra: file_len - expected,
+            },
+        };
+        Err(Error::corrupt(path, kind))
+    }
+}
+
+/// Streaming decoder returned by [`VectorCorpusFile::read_lazy`]
+pub struct VectorCorpusReader {
+    path: PathBuf,
+    reader: BufReader<File>,
+    header: VectorCorpusFile,
+    next: u64,
+}
+
+impl VectorCorpusReader {
+    /// Header of the file being read
+    pub fn header(&self) -> &VectorCorpusFile {
+        &self.header
+    }
+
+    /// [`Error::Corrupt`] if payload bytes remain after the last record
+    fn leftover_bytes(&mut self) -> Option<Error> {
+        let end = HEADER_LEN + self.header.payload_len;
+        let extra = match self.reader.stream_position() {
+            Ok(pos) => end.saturating_sub(pos),
+            Err(e) => return Some(Error::io(&self.path, e)),
+        };
+        (extra > 0).then(|| undecoded_bytes(&self.path, self.header.count, extra))
+    }
+}
+
+impl Iterator for VectorCorpusReader {
+    type Item = Result<SparseVec>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.header.count {
+            return None;
+        }
+        let record = self.next;
+        self.next += 1;
+        let decoded = decode_vector(&mut self.reader, &self.path, record, self.header.dims);
+        // The last record must end the payload exactly
+        if decoded.is_ok() && self.next == self.header.count {
+            if let Some(err) = self.leftover_bytes() {
+                return Some(Err(err));
+            }
+        }
+        Some(decoded)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.header.count - self.next) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for VectorCorpusReader {}
+
+/// Hasher primed with the header fields the checksum covers
+fn checksum_hasher(count: u64, dims: u64) -> blake3::Hasher {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&count.to_le_bytes());
+    hasher.update(&dims.to_le_bytes());
+    hasher
+}
+
+fn finish(hasher: &blake3::Hasher) -> u64 {
+    u64::from_le_bytes(hasher.finalize().as_bytes()[..8].try_into().unwrap())
+}
+
+fn undecoded_bytes(path: &Path, count: u64, extra: u64) -> Error {
+    Error::corrupt(
+        path,
+        CorruptionKind::Malformed {
+            record: count,
+            reason: format!("{} undecoded payload bytes", extra),
+        },
+    )
+}
+
+fn checksum_mismatch(path: &Path, expected: u64, actual: u64) -> Error {
+    Error::corrupt(path, CorruptionKind::ChecksumMismatch { expected, actual })
+}
+
+/// Fill as much of `buf` as the reader holds
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(reader: &mut impl Read) -> std::result::Result<u64, String> {
+    let mut value = 0u64;
+    let mut byte = [0u8; 1];
+    for shift in (0..64).step_by(7) {
+        reader
+            .read_exact(&mut byte)
+            .map_err(|_| "payload ends inside a varint".to_string())?;
+        value |= u64::from(byte[0] & 0x7F) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("varint longer than 64 bits".to_string())
+}
+
+fn decode_vector(reader: &mut impl Read, path: &Path, record: u64, dims: u64) -> Result<SparseVec> {
+    let mut decode_list = || -> std::result::Result<Vec<usize>, String> {
+        let len = read_varint(reader)?;
+        if len > dims {
+            return Err(format!("{} indices exceed dims {}", len, dims));
+        }
+        let mut indices = Vec::with_capacity(len as usize);
+        let mut index = 0u64;
+        for _ in 0..len {
+            index = index
+                .checked_add(read_varint(reader)?)
+                .filter(|&i| i < dims)
+                .ok_or_else(|| format!("index out of range for dims {}", dims))?;
+            indices.push(index as usize);
+        }
+        Ok(indices)
+    };
+    let decoded = decode_list().and_then(|pos| Ok((pos, decode_list()?)));
+    match decoded {
+        Ok((pos, neg)) => Ok(SparseVec { pos, neg }),
+        Err(reason) => Err(Error::corrupt(
+            path,
+            CorruptionKind::Malformed { record, reason },
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chaos::ChaosInjector;
+    use crate::generators::random_sparse_vec;
+    use crate::generators::special_vectors::{empty, max_density, single, Sign};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use tempfile::TempDir;
+
+    fn sample_vectors() -> Vec<SparseVec> {
+        let mut rng = StdRng::seed_from_u64(21);
+        let mut vecs: Vec<SparseVec> = (0..200)
+            .map(|i| random_sparse_vec(&mut rng, 10_000, 1 + i % 300))
+            .collect();
+        vecs.extend([
+            empty(),
+            max_density(512),
+            single(0, Sign::Negative),
+            single(1 << 40, Sign::Positive),
+        ]);
+        for v in &mut vecs {
+            v.pos.sort_unstable();
+            v.neg.sort_unstable();
+        }
+        vecs
+    }
+
+    fn parts(vecs: &[SparseVec]) -> Vec<(Vec<usize>, Vec<usize>)> {
+        vecs.iter()
+            .map(|v| (v.pos.clone(), v.neg.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip_eager_and_lazy() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("corpus.evcf");
+        let vecs = sample_vectors();
+
+        let header = VectorCorpusFile::write(&path, &vecs).unwrap();
+        assert_eq!(header.count, vecs.len() as u64);
+        assert_eq!(header.dims, (1 << 40) + 1);
+        assert_eq!(parts(&VectorCorpusFile::read(&path).unwrap()), parts(&vecs));
+
+        let reader = VectorCorpusFile::read_lazy(&path).unwrap();
+        assert_eq!(reader.header(), &header);
+        assert_eq!(reader.len(), vecs.len());
+        let lazy: Vec<SparseVec> = reader.map(|v| v.unwrap()).collect();
+        assert_eq!(parts(&lazy), parts(&vecs));
+
+        // Far smaller than the JSON equivalent
+        let json =
+            serde_json::to_vec(&vecs.iter().map(|v| (&v.pos, &v.neg)).collect::<Vec<_>>()).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() * 2 < json.len() as u64);
+
+        VectorCorpusFile::write(&path, &[]).unwrap();
+        assert!(VectorCorpusFile::read(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_chaos_corruption_is_detected() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("corpus.evcf");
+        VectorCorpusFile::write(&path, &sample_vectors()).unwrap();
+        let original = fs::read(&path).unwrap();
+        let chaos = ChaosInjector::new(9);
+
+        let corrupt_kind = |data: &[u8]| {
+            fs::write(&path, data).unwrap();
+            let eager = VectorCorpusFile::read(&path).unwrap_err();
+            let lazy = VectorCorpusFile::read_lazy(&path).err().unwrap();
+            assert_eq!(eager.to_string(), lazy.to_string());
+            match eager {
+                Error::Corrupt { kind, .. } => kind,
+                other => panic!("expected corruption, got {}", other),
+            }
+        };
+
+        // Flip a payload bit
+        let mut flipped = original.clone();
+        flipped[HEADER_LEN as usize + 100] ^= 0x10;
+        assert!(matches!(
+            corrupt_kind(&flipped),
+            CorruptionKind::ChecksumMismatch { .. }
+        ));
+
+        // Random bit flips anywhere
+        let noisy = chaos.corrupt_copy(&original, 0.001);
+        assert_ne!(noisy, original);
+        corrupt_kind(&noisy);
+
+        let short = chaos.truncate(&original, 0.25);
+        assert!(matches!(
+            corrupt_kind(&short),
+            CorruptionKind::Truncated { .. }
+        ));
+        assert!(matches!(
+            corrupt_kind(&original[..20]),
+            CorruptionKind::Truncated {
+                expected: 40,
+                actual: 20
+            }
+        ));
+        assert_eq!(
+            corrupt_kind(&chaos.extend_with_garbage(&original, 7)),
+            CorruptionKind::TrailingData { extra: 7 }
+        );
+        assert_eq!(corrupt_kind(b"nope"), CorruptionKind::BadMagic);
+
+        let mut future = original.clone();
+        future[4] = 9;
+        assert_eq!(corrupt_kind(&future), CorruptionKind::UnsupportedVersion(9));
+    }
+
+    #[test]
+    fn test_checksum_covers_count_and_dims() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("corpus.evcf");
+        VectorCorpusFile::write(&path, &sample_vectors()).unwrap();
+        let original = fs::read(&path).unwrap();
+
+        for field in [8, 16] {
+            let mut tampered = original.clone();
+            tampered[field] ^= 1;
+            fs::write(&path, &tampered).unwrap();
+            for err in [
+                VectorCorpusFile::read(&path).unwrap_err(),
+                VectorCorpusFile::read_lazy(&path).err().unwrap(),
+            ] {
+                assert!(
+                    matches!(
+                        err,
+                        Error::Corrupt {
+                            kind: CorruptionKind::ChecksumMismatch { .. },
+                            ..
+                        }
+                    ),
+                    "{}",
+                    err
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_reader_rejects_leftover_payload() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("corpus.evcf");
+        let vecs = sample_vectors();
+        let written = VectorCorpusFile::write(&path, &vecs).unwrap();
+        let payload = fs::read(&path).unwrap()[HEADER_LEN as usize..].to_vec();
+
+        // A consistent header that claims one vector fewer than encoded
+        let write_claiming = |count: u64| {
+            let header = VectorCorpusFile {
+                count,
+                checksum: finish(checksum_hasher(count, written.dims).update(&payload)),
+                ..written
+            };
+            let mut data = header.encode().to_vec();
+            data.extend_from_slice(&payload);
+            fs::write(&path, data).unwrap();
+        };
+
+        write_claiming(vecs.len() as u64 - 1);
+        let eager = VectorCorpusFile::read(&path).unwrap_err();
+        let results: Vec<Result<SparseVec>> = VectorCorpusFile::read_lazy(&path).unwrap().collect();
+        assert_eq!(results.len(), vecs.len() - 1);
+        let lazy = results.last().unwrap().as_ref().unwrap_err();
+        assert_eq!(lazy.to_string(), eager.to_string());
+        assert!(
+            lazy.to_string().contains("undecoded payload bytes"),
+            "{}",
+            lazy
+        );
+
+        write_claiming(0);
+        let eager = VectorCorpusFile::read(&path).unwrap_err();
+        let lazy = VectorCorpusFile::read_lazy(&path).err().unwrap();
+        assert_eq!(lazy.to_string(), eager.to_string());
+    }
+}
@@ -0,0 +1,106 @@
+//! Independent RNG streams for parallel workloads
+//!
+//! Child seeds are derived from a base seed and a work-item index with
+//! SplitMix64, never from the thread a task happens to run on, so results
+//! do not depend on thread count or work-stealing order.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Seed of child stream `index` under `base_seed`
+///
+/// Nearby base seeds and indices give unrelated children.
+pub fn fork_seed(base_seed: u64, index: u64) -> u64 {
+    let mut z = base_seed.wrapping_add(index.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// `n` independent RNGs forked from `base_seed`
+///
+/// # Example
+/// ```rust,ignore
+/// let rngs = forked_rngs(42, 8);
+/// std::thread::scope(|s| {
+///     for mut rng in rngs {
+///         s.spawn(move || random_sparse_vec(&mut rng, 10_000, 200));
+///     }
+/// });
+/// ```
+pub fn forked_rngs(base_seed: u64, n: usize) -> Vec<StdRng> {
+    (0..n as u64)
+        .map(|i| StdRng::seed_from_u64(fork_seed(base_seed, i)))
+        .collect()
+}
+
+/// Map `f` over `items` in parallel, giving each item its own RNG
+///
+/// Item `i` always sees the RNG seeded with `fork_seed(base_seed, i)`, so
+/// the output is identical for any rayon pool size.
+///
+/// # Example
+/// ```rust,ignore
+/// let vectors = par_map_seeded(&dims, 7, |&d, rng| random_sparse_vec(rng, d, d / 50));
+/// ```
+pub fn par_map_seeded<T, R, F>(items: &[T], base_seed: u64, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T, &mut StdRng) -> R + Sync,
+{
+    items
+        .par_iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let mut rng = StdRng::seed_from_u64(fork_seed(base_seed, i as u64));
+            f(item, &mut rng)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::random_sparse_vec;
+    use rand::Rng;
+
+    fn in_pool<R: Send>(threads: usize, f: impl FnOnce() -> R + Send) -> R {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(f)
+    }
+
+    #[test]
+    fn test_par_map_seeded_independent_of_thread_count() {
+        let items: Vec<usize> = (0..200).collect();
+        let work = |&i: &usize, rng: &mut StdRng| {
+            let v = random_sparse_vec(rng, 1000, 10 + i % 20);
+            (v.pos, v.neg, rng.random::<u64>())
+        };
+
+        let single = in_pool(1, || par_map_seeded(&items, 99, work));
+        let eight = in_pool(8, || par_map_seeded(&items, 99, work));
+        assert_eq!(single, eight);
+        assert_ne!(single, in_pool(8, || par_map_seeded(&items, 100, work)));
+    }
+
+    #[test]
+    fn test_forked_rngs_are_distinct() {
+        let draws: Vec<u64> = forked_rngs(5, 16)
+            .iter_mut()
+            .map(|rng| rng.random())
+            .collect();
+        let unique: std::collections::HashSet<_> = draws.iter().collect();
+        assert_eq!(unique.len(), 16);
+
+        let mut again = forked_rngs(5, 16);
+        assert_eq!(again[3].random::<u64>(), draws[3]);
+        assert_ne!(fork_seed(5, 0), fork_seed(6, 0));
+    }
+}
@@ -0,0 +1,198 @@
+//! Trees of vectors where every parent is the bundle of its children
+//!
+//! Mirrors hierarchical encoding: leaves are random sparse vectors and each
+//! internal node is the [`reference_bundle_many`] of its children, so
+//! hierarchical retrieval can be tested against a known structure.
+//!
+//! # Example
+//! ```rust,ignore
+//! let mut rng = StdRng::seed_from_u64(7);
+//! let tree = hierarchy::build(&mut rng, &[4, 3, 2], 100);
+//! assert_eq!(tree.leaves().len(), 24);
+//! let branch = tree.node(&[2, 1]).unwrap();
+//! let report = IntegrityValidator::new().validate_hierarchy(&tree, 0.2);
+//! ```
+
+use super::{random_sparse_vec, reference_bundle_many};
+use embeddenator_vsa::{SparseVec, DIM};
+use rand::Rng;
+
+/// One node and its subtree
+#[derive(Clone, Debug)]
+pub struct HierarchyNode {
+    /// Leaf vector, or bundle of the children's vectors
+    pub vector: SparseVec,
+    /// Children, empty for leaves
+    pub children: Vec<HierarchyNode>,
+}
+
+impl HierarchyNode {
+    /// Whether this node has no children
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// A bundle-of-bundles tree from [`build`]
+#[derive(Clone, Debug)]
+pub struct VsaHierarchy {
+    dims: usize,
+    root: HierarchyNode,
+}
+
+/// Build a hierarchy in [`DIM`] dimensions
+///
+/// `branching[d]` is the number of children of every node at depth `d`
+/// (zero is treated as one), so `&[3, 2]` gives a root with 3 children of
+/// 2 leaves each. Leaves have `sparsity` non-zeros; an empty `branching`
+/// gives a single leaf root.
+pub fn build(rng: &mut impl Rng, branching: &[usize], sparsity: usize) -> VsaHierarchy {
+    build_with_dims(rng, DIM, branching, sparsity)
+}
+
+/// [`build`] in `dims` dimensions
+pub fn build_with_dims(
+    rng: &mut impl Rng,
+    dims: usize,
+    branching: &[usize],
+    sparsity: usize,
+) -> VsaHierarchy {
+    VsaHierarchy {
+        dims,
+        root: build_node(rng, dims, branching, sparsity),
+    }
+}
+
+fn build_node(
+    rng: &mut impl Rng,
+    dims: usize,
+    branching: &[usize],
+    sparsity: usize,
+) -> HierarchyNode {
+    let Some((&fanout, rest)) = branching.split_first() else {
+        return HierarchyNode {
+            vector: random_sparse_vec(rng, dims, sparsity),
+            children: Vec::new(),
+        };
+    };
+    let children: Vec<HierarchyNode> = (0..fanout.max(1))
+        .map(|_| build_node(rng, dims, rest, sparsity))
+        .collect();
+    let vectors: Vec<SparseVec> = children.iter().map(|c| c.vector.clone()).collect();
+    HierarchyNode {
+        vector: reference_bundle_many(&vectors),
+        children,
+    }
+}
+
+impl VsaHierarchy {
+    /// Vector dimension
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// The root node's vector
+    pub fn root(&self) -> &SparseVec {
+        &self.root.vector
+    }
+
+    /// The whole tree
+    pub fn tree(&self) -> &HierarchyNode {
+        &self.root
+    }
+
+    /// Vector at `path`, a child index per level from the root
+    ///
+    /// The empty path is the root.
+    pub fn node(&self, path: &[usize]) -> Option<&SparseVec> {
+        self.subtree(path).map(|node| &node.vector)
+    }
+
+    /// Node at `path` with its subtree
+    pub fn subtree(&self, path: &[usize]) -> Option<&HierarchyNode> {
+        path.iter()
+            .try_fold(&self.root, |node, &i| node.children.get(i))
+    }
+
+    /// Leaf vectors in depth-first order
+    pub fn leaves(&self) -> Vec<&SparseVec> {
+        self.nodes()
+            .into_iter()
+            .filter(|(_, node)| node.is_leaf())
+            .map(|(_, node)| &node.vector)
+            .collect()
+    }
+
+    /// Every node with its path, depth-first with parents before children
+    pub fn nodes(&self) -> Vec<(Vec<usize>, &HierarchyNode)> {
+        let mut out = Vec::new();
+        let mut stack = vec![(Vec::new(), &self.root)];
+        while let Some((path, node)) = stack.pop() {
+            for (i, child) in node.children.iter().enumerate().rev() {
+                let mut child_path = path.clone();
+                child_path.push(i);
+                stack.push((child_path, child));
+            }
+            out.push((path, node));
+        }
+        out
+    }
+
+    /// Levels below the root (0 for a single leaf)
+    pub fn depth(&self) -> usize {
+        self.nodes()
+            .iter()
+            .map(|(path, _)| path.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Exchange the vectors at `a` and `b`, leaving parents untouched
+    ///
+    /// Produces a deliberately inconsistent hierarchy for negative tests.
+    /// Returns `false` if either path does not exist.
+    pub fn swap_vectors(&mut self, a: &[usize], b: &[usize]) -> bool {
+        let (Some(va), Some(vb)) = (self.node(a).cloned(), self.node(b).cloned()) else {
+            return false;
+        };
+        self.node_mut(a).unwrap().vector = vb;
+        self.node_mut(b).unwrap().vector = va;
+        true
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> Option<&mut HierarchyNode> {
+        path.iter()
+            .try_fold(&mut self.root, |node, &i| node.children.get_mut(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_shape_and_parent_bundles() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let tree = build(&mut rng, &[3, 1, 2, 2], 50);
+        assert_eq!(tree.depth(), 4);
+        assert_eq!(tree.leaves().len(), 12);
+        assert!(tree.node(&[2, 0, 1, 1]).is_some());
+        assert!(tree.node(&[0, 1]).is_none());
+
+        // A single child is its parent's bundle of one: the same vector
+        let single = tree.subtree(&[1]).unwrap();
+        assert_eq!(single.vector.pos, single.children[0].vector.pos);
+
+        let children: Vec<SparseVec> = tree
+            .subtree(&[])
+            .unwrap()
+            .children
+            .iter()
+            .map(|c| c.vector.clone())
+            .collect();
+        assert_eq!(tree.root().pos, reference_bundle_many(&children).pos);
+        assert_eq!(build(&mut rng, &[], 50).leaves().len(), 1);
+    }
+}
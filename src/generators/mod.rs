@@ -0,0 +1,1762 @@
+//! Test data generators for VSA vectors and test datasets
+//!
+//! Provides utilities to generate:
+//! - Random sparse vectors with controlled sparsity
+//! - Deterministic vectors for reproducible testing
+//! - Noise patterns and synthetic data
+//! - Test helper functions for VSA operations
+//! - Pairwise similarity matrices for vector sets
+//! - Index permutations for sequence encoding
+//! - Reference (oracle) bundling implementations
+//! - Discrete overlap and distance measures
+//! - Named degenerate vectors for edge-case tests (`special_vectors`)
+//! - Zipf-distributed text corpora with exact token counts (`text`)
+//! - Per-item RNG forking for rayon workloads ([`par_map_seeded`])
+//! - Bundle-of-bundles trees mirroring hierarchical encoding (`hierarchy`)
+//! - Query probe streams with a known hit ratio ([`query_workload`])
+//! - Vectors confined to shard index ranges ([`shard_confined_vec`])
+//! - Encoder-independent vectors for fixture patterns ([`vec_from_pattern`])
+
+mod fork;
+pub mod hierarchy;
+mod pattern;
+mod shard;
+pub mod special_vectors;
+pub mod text;
+mod workload;
+
+pub use fork::{fork_seed, forked_rngs, par_map_seeded};
+pub use pattern::vec_from_pattern;
+pub use shard::{shard_confined_vec, shard_spanning_vec};
+pub use workload::{query_workload, QueryProbe};
+
+use embeddenator_vsa::SparseVec;
+use rand::Rng;
+use rayon::prelude::*;
+use std::collections::{BinaryHeap, HashSet};
+use std::sync::Mutex;
+
+/// Vector sets at least this large compute similarities in parallel
+const PARALLEL_SIMILARITY_THRESHOLD: usize = 64;
+
+/// Generator parameters that cannot produce a valid vector
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum GenerationError {
+    /// Vectors need at least one dimension
+    #[error("dims must be positive")]
+    ZeroDims,
+    /// More non-zeros requested than there are dimensions
+    #[error("sparsity {sparsity} exceeds dims {dims}")]
+    SparsityExceedsDims { sparsity: usize, dims: usize },
+    /// A fraction parameter outside `[0, 1]`
+    #[error("{name} must be within [0, 1], got {value}")]
+    InvalidFraction { name: &'static str, value: f64 },
+    /// An input vector has an index outside `0..dims`
+    #[error("index {index} out of range for dims {dims}")]
+    IndexOutOfRange { index: usize, dims: usize },
+    /// A shard range holds fewer indices than the requested sparsity
+    #[error("shard {start}..{end} cannot hold {sparsity} non-zeros")]
+    ShardTooSmall {
+        start: usize,
+        end: usize,
+        sparsity: usize,
+    },
+    /// Two shard ranges share indices
+    #[error("shards {first} and {second} overlap")]
+    OverlappingShards { first: usize, second: usize },
+}
+
+/// Check that `sparsity` non-zeros fit in `dims` dimensions
+fn validate_sparsity(dims: usize, sparsity: usize) -> Result<(), GenerationError> {
+    if dims == 0 {
+        Err(GenerationError::ZeroDims)
+    } else if sparsity > dims {
+        Err(GenerationError::SparsityExceedsDims { sparsity, dims })
+    } else {
+        Ok(())
+    }
+}
+
+/// Panic with the validation error, for the infallible free functions
+#[track_caller]
+fn assert_valid_sparsity(dims: usize, sparsity: usize) {
+    if let Err(e) = validate_sparsity(dims, sparsity) {
+        panic!("invalid generator parameters: {}", e);
+    }
+}
+
+/// Like [`assert_valid_sparsity`], but an empty vector fits in zero dimensions
+#[track_caller]
+fn assert_sparsity_fits(dims: usize, sparsity: usize) {
+    if sparsity > 0 {
+        assert_valid_sparsity(dims, sparsity);
+    }
+}
+
+/// Check that `value` is a finite fraction in `[0, 1]`
+fn validate_fraction(name: &'static str, value: f64) -> Result<(), GenerationError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(GenerationError::InvalidFraction { name, value })
+    }
+}
+
+/// Generators with a fixed, validated dimension
+///
+/// Every method checks its parameters against the dimension and returns a
+/// [`GenerationError`] instead of looping forever or panicking deep inside
+/// the VSA crate. Obtain one with [`for_dim`] or
+/// [`DimGenerators::standard`].
+///
+/// # Example
+/// ```rust,ignore
+/// let gen = DimGenerators::standard();
+/// let a = gen.random(&mut rng, 200)?;
+/// let b = gen.correlated(&mut rng, &a, 0.8)?;
+/// assert!(a.cosine(&b) > 0.5);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DimGenerators {
+    dims: usize,
+}
+
+/// Generators for vectors of `dims` dimensions
+///
+/// A zero `dims` is reported by each generator as
+/// [`GenerationError::ZeroDims`].
+pub fn for_dim(dims: usize) -> DimGenerators {
+    DimGenerators { dims }
+}
+
+impl DimGenerators {
+    /// Generators pinned to the VSA crate's [`DIM`](crate::DIM)
+    pub fn standard() -> Self {
+        for_dim(crate::DIM)
+    }
+
+    /// Dimension of generated vectors
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// [`random_sparse_vec`] with `sparsity` validated against the dimension
+    pub fn random(
+        &self,
+        rng: &mut impl Rng,
+        sparsity: usize,
+    ) -> Result<SparseVec, GenerationError> {
+        validate_sparsity(self.dims, sparsity)?;
+        Ok(random_sparse_vec(rng, self.dims, sparsity))
+    }
+
+    /// [`deterministic_sparse_vec`] with `nnz` validated against the dimension
+    pub fn deterministic(&self, nnz: usize, seed: u64) -> Result<SparseVec, GenerationError> {
+        validate_sparsity(self.dims, nnz)?;
+        Ok(deterministic_sparse_vec(self.dims, nnz, seed))
+    }
+
+    /// Random vector with `density * dims` non-zeros (rounded)
+    pub fn dense(&self, rng: &mut impl Rng, density: f64) -> Result<SparseVec, GenerationError> {
+        validate_fraction("density", density)?;
+        self.random(rng, (density * self.dims as f64).round() as usize)
+    }
+
+    /// Random vector sharing about `overlap` of `base`'s non-zeros
+    ///
+    /// Keeps `round(overlap * nnz)` randomly chosen entries of `base` with
+    /// their signs and replaces the rest with fresh indices outside `base`'s
+    /// support, so the result has the same number of non-zeros.
+    pub fn correlated(
+        &self,
+        rng: &mut impl Rng,
+        base: &SparseVec,
+        overlap: f64,
+    ) -> Result<SparseVec, GenerationError> {
+        use rand::seq::SliceRandom;
+
+        validate_fraction("overlap", overlap)?;
+        let nnz = base.pos.len() + base.neg.len();
+        validate_sparsity(self.dims, nnz)?;
+        if let Some(&index) = base.pos.iter().chain(&base.neg).find(|&&i| i >= self.dims) {
+            return Err(GenerationError::IndexOutOfRange {
+                index,
+                dims: self.dims,
+            });
+        }
+
+        let keep = (overlap * nnz as f64).round() as usize;
+        let fresh = nnz - keep;
+        // Fresh indices must avoid the whole base support
+        validate_sparsity(self.dims, nnz + fresh)?;
+
+        let mut entries: Vec<(usize, bool)> = base
+            .pos
+            .iter()
+            .map(|&i| (i, true))
+            .chain(base.neg.iter().map(|&i| (i, false)))
+            .collect();
+        entries.shuffle(rng);
+        let mut used: HashSet<usize> = entries.iter().map(|&(i, _)| i).collect();
+        entries.truncate(keep);
+        while entries.len() < nnz {
+            let idx = rng.random_range(0..self.dims);
+            if used.insert(idx) {
+                entries.push((idx, rng.random()));
+            }
+        }
+
+        let mut out = SparseVec {
+            pos: Vec::with_capacity(nnz),
+            neg: Vec::with_capacity(nnz),
+        };
+        for (idx, positive) in entries {
+            if positive {
+                out.pos.push(idx);
+            } else {
+                out.neg.push(idx);
+            }
+        }
+        out.pos.sort_unstable();
+        out.neg.sort_unstable();
+        Ok(out)
+    }
+}
+
+/// Generate a random sparse vector with specified dimensions and sparsity
+///
+/// # Arguments
+/// * `rng` - Random number generator
+/// * `dims` - Total dimensions of the vector
+/// * `sparsity` - Number of non-zero elements (split roughly evenly between pos/neg)
+///
+/// # Example
+/// ```rust,ignore
+/// use rand::thread_rng;
+/// let mut rng = thread_rng();
+/// let vec = random_sparse_vec(&mut rng, 10000, 200);
+/// assert_eq!(vec.pos.len() + vec.neg.len(), 200);
+/// ```
+///
+/// # Panics
+/// Panics if `sparsity > dims`; use [`for_dim`] to get a
+/// [`GenerationError`] instead.
+#[track_caller]
+pub fn random_sparse_vec(rng: &mut impl Rng, dims: usize, sparsity: usize) -> SparseVec {
+    let mut out = SparseVec {
+        pos: Vec::with_capacity(sparsity / 2),
+        neg: Vec::with_capacity(sparsity / 2),
+    };
+    random_sparse_vec_into(rng, dims, sparsity, &mut out);
+    out
+}
+
+/// Fill `out` with a random sparse vector, reusing its buffers
+///
+/// Produces the same vector as [`random_sparse_vec`] for the same RNG state.
+///
+/// # Panics
+/// Panics if `sparsity > dims`.
+#[track_caller]
+pub fn random_sparse_vec_into(
+    rng: &mut impl Rng,
+    dims: usize,
+    sparsity: usize,
+    out: &mut SparseVec,
+) {
+    assert_sparsity_fits(dims, sparsity);
+    let mut used = HashSet::with_capacity(sparsity.saturating_mul(2));
+    fill_random_sparse(rng, dims, sparsity, out, &mut used);
+}
+
+/// Body of [`random_sparse_vec_into`], drawing into the empty set `used`
+fn fill_random_sparse(
+    rng: &mut impl Rng,
+    dims: usize,
+    sparsity: usize,
+    out: &mut SparseVec,
+    used: &mut HashSet<usize>,
+) {
+    let (pos, neg) = (&mut out.pos, &mut out.neg);
+    pos.clear();
+    neg.clear();
+
+    // Roughly half pos/half neg.
+    let target_each = sparsity / 2;
+    while pos.len() < target_each {
+        let idx = rng.random_range(0..dims);
+        if used.insert(idx) {
+            pos.push(idx);
+        }
+    }
+    while neg.len() < target_each {
+        let idx = rng.random_range(0..dims);
+        if used.insert(idx) {
+            neg.push(idx);
+        }
+    }
+
+    pos.sort_unstable();
+    neg.sort_unstable();
+}
+
+/// Alias for `random_sparse_vec` for backwards compatibility
+pub fn mk_random_sparsevec(rng: &mut impl Rng, dims: usize, sparsity: usize) -> SparseVec {
+    random_sparse_vec(rng, dims, sparsity)
+}
+
+/// Generate a deterministic sparse vector using LCG for reproducibility
+///
+/// # Arguments
+/// * `dim` - Total dimensions of the vector
+/// * `nnz` - Number of non-zero elements
+/// * `seed` - Random seed for reproducibility
+///
+/// # Example
+/// ```rust,ignore
+/// let vec1 = deterministic_sparse_vec(10000, 200, 42);
+/// let vec2 = deterministic_sparse_vec(10000, 200, 42);
+/// assert_eq!(vec1.pos, vec2.pos);
+/// assert_eq!(vec1.neg, vec2.neg);
+/// ```
+///
+/// # Panics
+/// Panics if `nnz > dim`; use [`for_dim`] to get a
+/// [`GenerationError`] instead.
+#[track_caller]
+pub fn deterministic_sparse_vec(dim: usize, nnz: usize, seed: u64) -> SparseVec {
+    let mut out = SparseVec {
+        pos: Vec::with_capacity(nnz / 2),
+        neg: Vec::with_capacity(nnz - nnz / 2),
+    };
+    deterministic_sparse_vec_into(dim, nnz, seed, &mut out);
+    out
+}
+
+/// Fill `out` with [`deterministic_sparse_vec`]`(dim, nnz, seed)`, reusing its buffers
+///
+/// # Panics
+/// Panics if `nnz > dim`.
+#[track_caller]
+pub fn deterministic_sparse_vec_into(dim: usize, nnz: usize, seed: u64, out: &mut SparseVec) {
+    assert_sparsity_fits(dim, nnz);
+    // Split nnz roughly evenly between pos and neg
+    let pos_count = nnz / 2;
+    let neg_count = nnz - pos_count;
+
+    let mut state = seed;
+    let lcg = |s: &mut u64| -> u64 {
+        *s = s.wrapping_mul(6364136223846793005).wrapping_add(1);
+        *s
+    };
+
+    let (pos, neg) = (&mut out.pos, &mut out.neg);
+    pos.clear();
+    neg.clear();
+    let mut used = HashSet::new();
+
+    for _ in 0..pos_count {
+        loop {
+            let idx = (lcg(&mut state) as usize) % dim;
+            if used.insert(idx) {
+                pos.push(idx);
+                break;
+            }
+        }
+    }
+
+    for _ in 0..neg_count {
+        loop {
+            let idx = (lcg(&mut state) as usize) % dim;
+            if used.insert(idx) {
+                neg.push(idx);
+                break;
+            }
+        }
+    }
+
+    pos.sort_unstable();
+    neg.sort_unstable();
+}
+
+/// Default number of idle buffers a [`VecPool`] keeps
+const DEFAULT_POOL_BUFFERS: usize = 1024;
+
+/// Counters describing how well a [`VecPool`] is recycling
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Buffers handed out from the pool
+    pub hits: u64,
+    /// Buffers freshly allocated because the pool was empty
+    pub misses: u64,
+    /// Returned buffers freed because the pool was full
+    pub discarded: u64,
+    /// Most idle buffers held at once
+    pub peak_buffers: usize,
+}
+
+impl PoolStats {
+    /// Fraction of buffer requests served from the pool (0.0 - 1.0)
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+/// Recycles the `pos`/`neg` buffers of short-lived [`SparseVec`]s
+///
+/// Vectors handed out by the pool return their buffers on drop, cleared but
+/// with their capacity, so generator-heavy loops stop hitting the
+/// allocator after warm-up. At most `max_buffers` idle buffers are kept;
+/// extra returns are freed. [`random_sparse_vec`](Self::random_sparse_vec)
+/// also reuses the pool's scratch sets for duplicate detection. The pool
+/// is `Sync` and can be shared by rayon workers.
+///
+/// # Example
+/// ```rust,ignore
+/// let pool = VecPool::new();
+/// for _ in 0..1_000_000 {
+///     let v = pool.random_sparse_vec(&mut rng, DIM, 200);
+///     probe.cosine(&v);
+/// } // buffers go back to the pool here
+/// assert!(pool.stats().hit_rate() > 0.99);
+/// ```
+#[derive(Debug)]
+pub struct VecPool {
+    max_buffers: usize,
+    state: Mutex<PoolState>,
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    free: Vec<Vec<usize>>,
+    /// Idle duplicate-detection sets for `random_sparse_vec`, one per
+    /// concurrent caller at most
+    scratch: Vec<HashSet<usize>>,
+    stats: PoolStats,
+}
+
+impl VecPool {
+    /// Pool keeping up to 1024 idle buffers
+    pub fn new() -> Self {
+        Self::with_max_buffers(DEFAULT_POOL_BUFFERS)
+    }
+
+    /// Pool keeping up to `max_buffers` idle buffers
+    pub fn with_max_buffers(max_buffers: usize) -> Self {
+        Self {
+            max_buffers,
+            state: Mutex::new(PoolState::default()),
+        }
+    }
+
+    /// Empty vector backed by recycled buffers
+    pub fn get(&self) -> PooledSparseVec<'_> {
+        PooledSparseVec {
+            vec: SparseVec {
+                pos: self.take(),
+                neg: self.take(),
+            },
+            pool: self,
+        }
+    }
+
+    /// Pooled equivalent of [`random_sparse_vec`]
+    pub fn random_sparse_vec(
+        &self,
+        rng: &mut impl Rng,
+        dims: usize,
+        sparsity: usize,
+    ) -> PooledSparseVec<'_> {
+        assert_sparsity_fits(dims, sparsity);
+        let mut v = self.get();
+        let mut used = self.state.lock().unwrap().scratch.pop().unwrap_or_default();
+        fill_random_sparse(rng, dims, sparsity, &mut v, &mut used);
+        used.clear();
+        let mut state = self.state.lock().unwrap();
+        if state.scratch.len() < self.max_buffers {
+            state.scratch.push(used);
+        }
+        v
+    }
+
+    /// Pooled equivalent of [`deterministic_sparse_vec`]
+    pub fn deterministic_sparse_vec(
+        &self,
+        dim: usize,
+        nnz: usize,
+        seed: u64,
+    ) -> PooledSparseVec<'_> {
+        let mut v = self.get();
+        deterministic_sparse_vec_into(dim, nnz, seed, &mut v);
+        v
+    }
+
+    /// Recycling counters so far
+    pub fn stats(&self) -> PoolStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// Idle buffers currently held
+    pub fn idle_buffers(&self) -> usize {
+        self.state.lock().unwrap().free.len()
+    }
+
+    fn take(&self) -> Vec<usize> {
+        let mut state = self.state.lock().unwrap();
+        match state.free.pop() {
+            Some(buf) => {
+                state.stats.hits += 1;
+                buf
+            }
+            None => {
+                state.stats.misses += 1;
+                Vec::new()
+            }
+        }
+    }
+
+    fn put(&self, mut buf: Vec<usize>) {
+        // Never-grown buffers are not worth keeping
+        if buf.capacity() == 0 {
+            return;
+        }
+        buf.clear();
+        let mut state = self.state.lock().unwrap();
+        if state.free.len() < self.max_buffers {
+            state.free.push(buf);
+            state.stats.peak_buffers = state.stats.peak_buffers.max(state.free.len());
+        } else {
+            state.stats.discarded += 1;
+        }
+    }
+}
+
+impl Default for VecPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`SparseVec`] whose buffers return to its [`VecPool`] on drop
+///
+/// Dereferences to `SparseVec`, so it can be passed wherever `&SparseVec`
+/// is expected.
+#[derive(Debug)]
+pub struct PooledSparseVec<'a> {
+    vec: SparseVec,
+    pool: &'a VecPool,
+}
+
+impl PooledSparseVec<'_> {
+    /// Detach the vector from the pool; its buffers are not recycled
+    pub fn into_inner(mut self) -> SparseVec {
+        std::mem::replace(
+            &mut self.vec,
+            SparseVec {
+                pos: Vec::new(),
+                neg: Vec::new(),
+            },
+        )
+    }
+}
+
+impl std::ops::Deref for PooledSparseVec<'_> {
+    type Target = SparseVec;
+
+    fn deref(&self) -> &SparseVec {
+        &self.vec
+    }
+}
+
+impl std::ops::DerefMut for PooledSparseVec<'_> {
+    fn deref_mut(&mut self) -> &mut SparseVec {
+        &mut self.vec
+    }
+}
+
+impl Drop for PooledSparseVec<'_> {
+    fn drop(&mut self) {
+        self.pool.put(std::mem::take(&mut self.vec.pos));
+        self.pool.put(std::mem::take(&mut self.vec.neg));
+    }
+}
+
+/// Count intersections between two sorted slices (used for dot product)
+fn intersection_count_sorted(a: &[usize], b: &[usize]) -> usize {
+    let mut i = 0;
+    let mut j = 0;
+    let mut count = 0;
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
+
+/// Compute sparse ternary dot product: (pp + nn) - (pn + np)
+///
+/// This is a reference implementation useful for testing optimized dot product implementations.
+///
+/// # Arguments
+/// * `a` - First sparse vector
+/// * `b` - Second sparse vector
+///
+/// # Returns
+/// Dot product as i32
+pub fn sparse_dot(a: &SparseVec, b: &SparseVec) -> i32 {
+    let pp = intersection_count_sorted(&a.pos, &b.pos) as i32;
+    let nn = intersection_count_sorted(&a.neg, &b.neg) as i32;
+    let pn = intersection_count_sorted(&a.pos, &b.neg) as i32;
+    let np = intersection_count_sorted(&a.neg, &b.pos) as i32;
+    (pp + nn) - (pn + np)
+}
+
+/// Index overlap between two sparse ternary vectors
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OverlapStats {
+    /// Indices positive in both
+    pub pos_pos: usize,
+    /// Indices negative in both
+    pub neg_neg: usize,
+    /// Indices positive in `a`, negative in `b`
+    pub pos_neg: usize,
+    /// Indices negative in `a`, positive in `b`
+    pub neg_pos: usize,
+    /// Indices non-zero only in `a`
+    pub only_a: usize,
+    /// Indices non-zero only in `b`
+    pub only_b: usize,
+}
+
+impl OverlapStats {
+    /// Indices non-zero in both vectors (regardless of sign)
+    pub fn shared(&self) -> usize {
+        self.pos_pos + self.neg_neg + self.pos_neg + self.neg_pos
+    }
+
+    /// Indices non-zero in either vector
+    pub fn union(&self) -> usize {
+        self.shared() + self.only_a + self.only_b
+    }
+
+    /// Positions whose ternary value differs
+    pub fn hamming(&self) -> usize {
+        self.only_a + self.only_b + self.pos_neg + self.neg_pos
+    }
+
+    /// Shared support over union support (1.0 when both are empty)
+    pub fn jaccard(&self) -> f64 {
+        match self.union() {
+            0 => 1.0,
+            union => self.shared() as f64 / union as f64,
+        }
+    }
+
+    /// Ternary dot product of the two vectors
+    pub fn dot(&self) -> i64 {
+        (self.pos_pos + self.neg_neg) as i64 - (self.pos_neg + self.neg_pos) as i64
+    }
+
+    /// Cosine similarity of the two vectors (0.0 when either is empty)
+    pub fn cosine(&self) -> f64 {
+        let nnz_a = self.shared() + self.only_a;
+        let nnz_b = self.shared() + self.only_b;
+        if nnz_a == 0 || nnz_b == 0 {
+            0.0
+        } else {
+            self.dot() as f64 / ((nnz_a as f64).sqrt() * (nnz_b as f64).sqrt())
+        }
+    }
+}
+
+impl std::fmt::Display for OverlapStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "pp={} nn={} pn={} np={} only_a={} only_b={} hamming={} jaccard={:.4}",
+            self.pos_pos,
+            self.neg_neg,
+            self.pos_neg,
+            self.neg_pos,
+            self.only_a,
+            self.only_b,
+            self.hamming(),
+            self.jaccard()
+        )
+    }
+}
+
+/// Compute index overlap between `a` and `b` without densifying
+pub fn index_overlap(a: &SparseVec, b: &SparseVec) -> OverlapStats {
+    let pos_pos = intersection_count_sorted(&a.pos, &b.pos);
+    let neg_neg = intersection_count_sorted(&a.neg, &b.neg);
+    let pos_neg = intersection_count_sorted(&a.pos, &b.neg);
+    let neg_pos = intersection_count_sorted(&a.neg, &b.pos);
+    // Count exclusive indices over the merged supports, so an index that is
+    // both positive and negative in a corrupt vector is counted once
+    let (support_a, support_b) = (support(a), support(b));
+    let common = intersection_count_sorted(&support_a, &support_b);
+    OverlapStats {
+        pos_pos,
+        neg_neg,
+        pos_neg,
+        neg_pos,
+        only_a: support_a.len() - common,
+        only_b: support_b.len() - common,
+    }
+}
+
+/// Sorted, duplicate-free indices non-zero in `v`
+fn support(v: &SparseVec) -> Vec<usize> {
+    let mut indices: Vec<usize> = v.pos.iter().chain(&v.neg).copied().collect();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+/// Jaccard similarity of the supports of `a` and `b`
+pub fn jaccard_similarity(a: &SparseVec, b: &SparseVec) -> f64 {
+    index_overlap(a, b).jaccard()
+}
+
+/// Number of positions whose ternary value differs between `a` and `b`
+pub fn ternary_hamming(a: &SparseVec, b: &SparseVec) -> usize {
+    index_overlap(a, b).hamming()
+}
+
+/// Generate a seeded random permutation of `0..dims`
+///
+/// Uses a Fisher-Yates shuffle driven by the same LCG as
+/// [`deterministic_sparse_vec`], so a seed always yields the same permutation.
+///
+/// # Example
+/// ```rust,ignore
+/// let perm = random_permutation(DIM, 7);
+/// let shifted = permute_sparse_vec(&v, &perm);
+/// assert_eq!(permute_sparse_vec(&shifted, &inverse_permutation(&perm)), v);
+/// ```
+pub fn random_permutation(dims: usize, seed: u64) -> Vec<usize> {
+    let mut perm: Vec<usize> = (0..dims).collect();
+    let mut state = seed;
+    for i in (1..dims).rev() {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        let j = ((state >> 16) % (i as u64 + 1)) as usize;
+        perm.swap(i, j);
+    }
+    perm
+}
+
+/// Invert a permutation so that `inverse[perm[i]] == i`
+pub fn inverse_permutation(perm: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![0; perm.len()];
+    for (i, &p) in perm.iter().enumerate() {
+        inverse[p] = i;
+    }
+    inverse
+}
+
+/// Map every index of `v` through `perm`, preserving signs
+///
+/// # Panics
+/// Panics if `v` has an index `>= perm.len()`.
+pub fn permute_sparse_vec(v: &SparseVec, perm: &[usize]) -> SparseVec {
+    let map = |indices: &[usize]| {
+        let mut out: Vec<usize> = indices.iter().map(|&i| perm[i]).collect();
+        out.sort_unstable();
+        out
+    };
+    SparseVec {
+        pos: map(&v.pos),
+        neg: map(&v.neg),
+    }
+}
+
+/// Reference bundle of many vectors: the ground truth for sum-many mode
+///
+/// Sums every vector element-wise into a dense `i32` accumulator, then
+/// ternarizes by majority: positive sums become `+1`, negative sums `-1`,
+/// and ties (sum of zero) are dropped. Deliberately naive so optimized
+/// bundling paths can be checked against it.
+pub fn reference_bundle_many(vecs: &[SparseVec]) -> SparseVec {
+    let weights = vec![1; vecs.len()];
+    reference_weighted_bundle(vecs, &weights)
+}
+
+/// Reference weighted bundle: each vector contributes `weight * sign`
+///
+/// Ternarization and tie handling match [`reference_bundle_many`].
+///
+/// # Panics
+/// Panics if `vecs` and `weights` differ in length.
+pub fn reference_weighted_bundle(vecs: &[SparseVec], weights: &[i32]) -> SparseVec {
+    assert_eq!(
+        vecs.len(),
+        weights.len(),
+        "reference_weighted_bundle needs one weight per vector"
+    );
+
+    let dims = vecs
+        .iter()
+        .flat_map(|v| v.pos.iter().chain(&v.neg))
+        .max()
+        .map_or(0, |&max| max + 1);
+    let mut acc = vec![0i32; dims];
+    for (v, &w) in vecs.iter().zip(weights) {
+        for &i in &v.pos {
+            acc[i] += w;
+        }
+        for &i in &v.neg {
+            acc[i] -= w;
+        }
+    }
+
+    let mut pos = Vec::new();
+    let mut neg = Vec::new();
+    for (i, &sum) in acc.iter().enumerate() {
+        match sum.signum() {
+            1 => pos.push(i),
+            -1 => neg.push(i),
+            _ => {}
+        }
+    }
+    SparseVec { pos, neg }
+}
+
+/// Generate synthetic noise pattern using LCG
+///
+/// Useful for creating reproducible pseudo-random test data.
+pub fn generate_noise_pattern(size: usize, seed: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(size);
+    let mut state = seed;
+    for _ in 0..size {
+        // Simple LCG for reproducible pseudo-random data
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        data.push((state >> 56) as u8);
+    }
+    data
+}
+
+/// Generate synthetic gradient pattern (useful for image-like data)
+pub fn generate_gradient_pattern(width: usize, height: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            // Linear gradient from top-left to bottom-right
+            let val = ((x + y) * 255) / (width + height);
+            data.push(val as u8);
+        }
+    }
+    data
+}
+
+/// Generate synthetic binary blob (executable-like pattern)
+pub fn generate_binary_blob(size: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(size);
+
+    // ELF-like header
+    if size >= 16 {
+        data.extend_from_slice(&[0x7f, b'E', b'L', b'F']);
+        data.extend_from_slice(&[2, 1, 1, 0]); // 64-bit, little endian, v1, SYSV
+        data.extend_from_slice(&[0; 8]); // padding
+    }
+
+    // Fill with mix of patterns
+    let mut offset = data.len();
+    while offset < size {
+        let pattern_type = (offset / 256) % 4;
+        match pattern_type {
+            0 => data.push(0x90),                  // NOP slide
+            1 => data.push((offset & 0xFF) as u8), // Sequential
+            2 => data.push(0x00),                  // Zero fill
+            _ => data.push(0xCC),                  // INT3
+        }
+        offset += 1;
+    }
+
+    data.truncate(size);
+    data
+}
+
+/// Pairwise cosine similarities for a set of vectors
+///
+/// Only the strict upper triangle is stored (`n * (n - 1) / 2` values); the
+/// diagonal is 1.0 by definition and excluded from all statistics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimilarityMatrix {
+    n: usize,
+    upper: Vec<f64>,
+}
+
+impl SimilarityMatrix {
+    /// Number of vectors
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Whether the matrix covers no vectors
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Similarity between vectors `i` and `j` (order does not matter)
+    ///
+    /// # Panics
+    /// Panics if either index is out of bounds.
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        assert!(i < self.n && j < self.n, "index out of bounds");
+        match i.cmp(&j) {
+            std::cmp::Ordering::Equal => 1.0,
+            std::cmp::Ordering::Less => self.upper[upper_index(self.n, i, j)],
+            std::cmp::Ordering::Greater => self.upper[upper_index(self.n, j, i)],
+        }
+    }
+
+    /// Off-diagonal similarities in row-major upper-triangle order
+    pub fn offdiagonal(&self) -> &[f64] {
+        &self.upper
+    }
+
+    /// Largest off-diagonal similarity, or `None` for fewer than two vectors
+    pub fn max_offdiagonal(&self) -> Option<f64> {
+        self.upper.iter().copied().reduce(f64::max)
+    }
+
+    /// Mean off-diagonal similarity, or `None` for fewer than two vectors
+    pub fn mean_offdiagonal(&self) -> Option<f64> {
+        if self.upper.is_empty() {
+            None
+        } else {
+            Some(self.upper.iter().sum::<f64>() / self.upper.len() as f64)
+        }
+    }
+
+    /// Histogram of off-diagonal similarities over `[-1.0, 1.0]` in `bins` equal bins
+    pub fn histogram(&self, bins: usize) -> Vec<usize> {
+        let bins = bins.max(1);
+        let mut counts = vec![0usize; bins];
+        for &value in &self.upper {
+            let pos = ((value + 1.0) / 2.0 * bins as f64).floor();
+            let bin = (pos.max(0.0) as usize).min(bins - 1);
+            counts[bin] += 1;
+        }
+        counts
+    }
+
+    /// Full `n x n` matrix as CSV (no header)
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for i in 0..self.n {
+            let row: Vec<String> = (0..self.n)
+                .map(|j| format!("{:.6}", self.get(i, j)))
+                .collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Position of `(i, j)` with `i < j` in a row-major strict upper triangle
+fn upper_index(n: usize, i: usize, j: usize) -> usize {
+    i * n - i * (i + 1) / 2 + (j - i - 1)
+}
+
+/// Compute all pairwise cosine similarities for `vecs`
+///
+/// Large sets are computed in parallel with rayon.
+///
+/// # Example
+/// ```rust,ignore
+/// let codebook: Vec<_> = (0..100).map(|s| deterministic_sparse_vec(10000, 200, s)).collect();
+/// let sims = similarity_matrix(&codebook);
+/// assert!(sims.max_offdiagonal().unwrap() < 0.1);
+/// ```
+pub fn similarity_matrix(vecs: &[SparseVec]) -> SimilarityMatrix {
+    let n = vecs.len();
+    let row = |i: usize| (i + 1..n).map(move |j| vecs[i].cosine(&vecs[j]));
+
+    let upper = if n >= PARALLEL_SIMILARITY_THRESHOLD {
+        (0..n).into_par_iter().flat_map_iter(row).collect()
+    } else {
+        (0..n).flat_map(row).collect()
+    };
+
+    SimilarityMatrix { n, upper }
+}
+
+/// Stream every pairwise similarity `(i, j, cosine)` with `i < j` to `f`
+///
+/// Use this for sets too large to hold the full matrix in memory.
+pub fn for_each_similarity<F>(vecs: &[SparseVec], mut f: F)
+where
+    F: FnMut(usize, usize, f64),
+{
+    for i in 0..vecs.len() {
+        for j in i + 1..vecs.len() {
+            f(i, j, vecs[i].cosine(&vecs[j]));
+        }
+    }
+}
+
+/// Search candidate ranked by similarity, ordered worst-first
+///
+/// Higher scores rank better; equal scores rank the lower index better, so
+/// results never depend on iteration or thread order.
+#[derive(Clone, Copy, Debug)]
+struct Ranked {
+    score: f64,
+    index: usize,
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .score
+            .total_cmp(&self.score)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+/// Push into a heap that keeps only the `k` best entries
+fn push_bounded(heap: &mut BinaryHeap<Ranked>, k: usize, entry: Ranked) {
+    if heap.len() < k {
+        heap.push(entry);
+    } else if heap.peek().is_some_and(|worst| entry < *worst) {
+        heap.pop();
+        heap.push(entry);
+    }
+}
+
+/// Heap contents as `(index, score)`, best first
+fn ranked_results(heap: BinaryHeap<Ranked>) -> Vec<(usize, f64)> {
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|r| (r.index, r.score))
+        .collect()
+}
+
+/// The `k` candidates most cosine-similar to `probe`, best first
+///
+/// Returns `(candidate index, cosine)` pairs; ties are broken by the lower
+/// index. Fewer than `k` results are returned when there are fewer
+/// candidates. Uses a bounded heap, so memory is `O(k)`.
+///
+/// # Example
+/// ```rust,ignore
+/// let hits = top_k_similar(&query, &codebook, 5);
+/// assert_eq!(hits[0].0, expected_index);
+/// ```
+pub fn top_k_similar(probe: &SparseVec, candidates: &[SparseVec], k: usize) -> Vec<(usize, f64)> {
+    let mut heap = BinaryHeap::with_capacity(k.min(candidates.len()) + 1);
+    for (index, candidate) in candidates.iter().enumerate() {
+        let score = probe.cosine(candidate);
+        push_bounded(&mut heap, k, Ranked { score, index });
+    }
+    ranked_results(heap)
+}
+
+/// Parallel [`top_k_similar`] for large candidate sets
+///
+/// Each rayon worker keeps its own bounded heap and the heaps are merged at
+/// the end; results are identical to the serial version.
+pub fn par_top_k_similar(
+    probe: &SparseVec,
+    candidates: &[SparseVec],
+    k: usize,
+) -> Vec<(usize, f64)> {
+    let heap = candidates
+        .par_iter()
+        .enumerate()
+        .fold(BinaryHeap::new, |mut heap, (index, candidate)| {
+            let score = probe.cosine(candidate);
+            push_bounded(&mut heap, k, Ranked { score, index });
+            heap
+        })
+        .reduce(BinaryHeap::new, |mut a, b| {
+            for entry in b {
+                push_bounded(&mut a, k, entry);
+            }
+            a
+        });
+    ranked_results(heap)
+}
+
+/// Reference top-k: score every candidate, sort, truncate
+///
+/// Deliberately exhaustive so heap-based or indexed searches can be checked
+/// against it. Ordering and tie-breaking match [`top_k_similar`].
+pub fn reference_top_k_similar(
+    probe: &SparseVec,
+    candidates: &[SparseVec],
+    k: usize,
+) -> Vec<(usize, f64)> {
+    let mut all: Vec<Ranked> = candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| Ranked {
+            score: probe.cosine(candidate),
+            index,
+        })
+        .collect();
+    all.sort();
+    all.truncate(k);
+    all.into_iter().map(|r| (r.index, r.score)).collect()
+}
+
+/// FNV-1a offset basis
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// FNV-1a prime
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Fold `bytes` into an FNV-1a hash state
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Stable 64-bit hash of `data` (FNV-1a)
+///
+/// Unlike `std::hash`, the result is identical across processes, Rust
+/// versions and platforms, so it can be pinned in tests.
+pub fn bytes_fingerprint(data: &[u8]) -> u64 {
+    fnv1a(FNV_OFFSET, data)
+}
+
+/// Stable hash of `iterations` vectors from [`deterministic_sparse_vec`]
+///
+/// Vector `i` is generated with seed `seed + i`; the lengths and indices of
+/// its `pos` and `neg` lists are folded in as little-endian `u64`s. Any
+/// change to the generator's output, including ordering, changes the
+/// fingerprint.
+///
+/// # Stability
+/// Generator output is part of the testkit's stability contract:
+/// downstream golden files and seeds rely on it never changing. The
+/// following values are pinned by the testkit's own tests and only change
+/// with a deliberate, changelogged algorithm change (64-bit `usize`):
+///
+/// | Expression | Value |
+/// |---|---|
+/// | `determinism_fingerprint(42, 10_000, 200, 16)` | `0x1014_5b26_f399_6378` |
+/// | `bytes_fingerprint(&generate_noise_pattern(4096, 42))` | `0x6c72_6172_7ced_95b3` |
+/// | `bytes_fingerprint(&ChaosInjector::new(7).corrupt_copy(&generate_noise_pattern(4096, 42), 0.01))` | `0xc1b8_602f_8a56_8bcf` |
+/// | `fingerprint(&deterministic_sparse_vec(10_000, 200, 42)).hash` | `0x994f_553c_00f9_8dee` |
+/// | `bytes_fingerprint(b"a")` | `0xaf63_dc4c_8601_ec8c` |
+///
+/// # Example
+/// ```rust,ignore
+/// assert_deterministic_across_runs(0x1014_5b26_f399_6378, || {
+///     determinism_fingerprint(42, 10_000, 200, 16)
+/// });
+/// ```
+pub fn determinism_fingerprint(seed: u64, dims: usize, nnz: usize, iterations: usize) -> u64 {
+    (0..iterations).fold(FNV_OFFSET, |hash, i| {
+        let v = deterministic_sparse_vec(dims, nnz, seed.wrapping_add(i as u64));
+        fold_sparse_vec(hash, &v)
+    })
+}
+
+/// Stable hash of one vector's `pos` and `neg` lists
+///
+/// Hashes the lists as stored, so vectors with the same indices in a
+/// different order fingerprint differently.
+pub fn sparse_vec_fingerprint(v: &SparseVec) -> u64 {
+    fold_sparse_vec(FNV_OFFSET, v)
+}
+
+/// Short, stable identity of a sparse vector for logs and failure messages
+///
+/// Displays as `sv:<8 hex>/n<nnz>`, e.g. `sv:7f3a9c21/n412`.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub struct VecFingerprint {
+    /// [`sparse_vec_fingerprint`] of the canonical (sorted, deduplicated) vector
+    pub hash: u64,
+    /// Non-zero count of the canonical vector
+    pub nnz: usize,
+}
+
+impl VecFingerprint {
+    /// First 4 hex digits of the hash, for tight table columns
+    pub fn short(&self) -> String {
+        format!("{:04x}", self.hash >> 48)
+    }
+}
+
+impl std::fmt::Display for VecFingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sv:{:08x}/n{}", self.hash >> 32, self.nnz)
+    }
+}
+
+/// [`VecFingerprint`] of `v`
+///
+/// Vectors with the same index sets fingerprint the same regardless of
+/// storage order; for already-canonical vectors `hash` equals
+/// [`sparse_vec_fingerprint`]. Stable across processes and platforms.
+pub fn fingerprint(v: &SparseVec) -> VecFingerprint {
+    let canonical = |indices: &[usize]| indices.windows(2).all(|w| w[0] < w[1]);
+    if canonical(&v.pos) && canonical(&v.neg) {
+        return VecFingerprint {
+            hash: sparse_vec_fingerprint(v),
+            nnz: v.pos.len() + v.neg.len(),
+        };
+    }
+    let mut sorted = v.clone();
+    for indices in [&mut sorted.pos, &mut sorted.neg] {
+        indices.sort_unstable();
+        indices.dedup();
+    }
+    fingerprint(&sorted)
+}
+
+/// Fold the lengths and indices of `v` into an FNV-1a hash state
+fn fold_sparse_vec(mut hash: u64, v: &SparseVec) -> u64 {
+    for indices in [&v.pos, &v.neg] {
+        hash = fnv1a(hash, &(indices.len() as u64).to_le_bytes());
+        for &idx in indices {
+            hash = fnv1a(hash, &(idx as u64).to_le_bytes());
+        }
+    }
+    hash
+}
+
+/// Assert that `fingerprint` is stable within this process and matches a pinned value
+///
+/// `fingerprint` runs once on the calling thread and once on a fresh
+/// thread, which catches dependence on per-thread state such as
+/// `HashMap` seeds; the result must then equal `expected`, which catches
+/// differences between processes, releases and platforms.
+///
+/// Pin values from this crate's generators, whose outputs are stable
+/// across releases (see [`determinism_fingerprint`] for the pinned set).
+/// When the assertion fails on an upgrade, the generator changed; update
+/// `expected` only if the changelog announces it.
+///
+/// # Panics
+/// Panics with both values when either comparison fails.
+#[track_caller]
+pub fn assert_deterministic_across_runs<F>(expected: u64, fingerprint: F)
+where
+    F: Fn() -> u64 + Sync,
+{
+    let here = fingerprint();
+    let elsewhere = std::thread::scope(|s| s.spawn(&fingerprint).join().unwrap());
+    assert_eq!(
+        here, elsewhere,
+        "fingerprint differs between threads of one process: {:#018x} vs {:#018x}",
+        here, elsewhere
+    );
+    assert_eq!(
+        here, expected,
+        "fingerprint changed: expected {:#018x}, got {:#018x}; if the generator change is \
+         intentional, update the pinned constant",
+        expected, here
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    /// Pinned generator fingerprints; keep in sync with the table in the
+    /// [`determinism_fingerprint`] docs
+    const DETERMINISTIC_SPARSE_VEC_FINGERPRINT: u64 = 0x1014_5b26_f399_6378;
+    const NOISE_PATTERN_FINGERPRINT: u64 = 0x6c72_6172_7ced_95b3;
+    const CORRUPT_COPY_FINGERPRINT: u64 = 0xc1b8_602f_8a56_8bcf;
+    const VEC_FINGERPRINT: u64 = 0x994f_553c_00f9_8dee;
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_pinned_generator_fingerprints() {
+        assert_eq!(bytes_fingerprint(b"a"), 0xaf63_dc4c_8601_ec8c);
+
+        assert_deterministic_across_runs(DETERMINISTIC_SPARSE_VEC_FINGERPRINT, || {
+            determinism_fingerprint(42, 10_000, 200, 16)
+        });
+        assert_deterministic_across_runs(NOISE_PATTERN_FINGERPRINT, || {
+            bytes_fingerprint(&generate_noise_pattern(4096, 42))
+        });
+        assert_deterministic_across_runs(CORRUPT_COPY_FINGERPRINT, || {
+            let chaos = crate::chaos::ChaosInjector::new(7);
+            bytes_fingerprint(&chaos.corrupt_copy(&generate_noise_pattern(4096, 42), 0.01))
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "fingerprint changed")]
+    fn test_fingerprint_change_is_loud() {
+        assert_deterministic_across_runs(0, || determinism_fingerprint(1, 1000, 10, 2));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_vec_fingerprint_pinned_canonical_and_collision_free() {
+        let v = deterministic_sparse_vec(10_000, 200, 42);
+        assert_deterministic_across_runs(VEC_FINGERPRINT, || fingerprint(&v).hash);
+        let fp = fingerprint(&v);
+        assert_eq!(fp.nnz, 200);
+        assert_eq!(fp.hash, sparse_vec_fingerprint(&v));
+        assert_eq!(
+            fp.to_string(),
+            format!("sv:{:08x}/n200", VEC_FINGERPRINT >> 32)
+        );
+        assert_eq!(fp.short(), format!("{:04x}", VEC_FINGERPRINT >> 48));
+
+        let mut shuffled = v.clone();
+        shuffled.pos.reverse();
+        shuffled.neg.push(shuffled.neg[0]);
+        assert_eq!(fingerprint(&shuffled), fp);
+
+        let sample: HashSet<VecFingerprint> = (0..10_000)
+            .map(|seed| fingerprint(&deterministic_sparse_vec(10_000, 50, seed)))
+            .collect();
+        assert_eq!(sample.len(), 10_000);
+    }
+
+    #[test]
+    fn test_random_sparse_vec() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let vec = random_sparse_vec(&mut rng, 10000, 200);
+        let nnz = vec.pos.len() + vec.neg.len();
+        assert_eq!(nnz, 200);
+
+        // Check sorted
+        assert!(vec.pos.windows(2).all(|w| w[0] < w[1]));
+        assert!(vec.neg.windows(2).all(|w| w[0] < w[1]));
+
+        // Check no overlap
+        let pos_set: HashSet<_> = vec.pos.iter().collect();
+        let neg_set: HashSet<_> = vec.neg.iter().collect();
+        assert_eq!(pos_set.intersection(&neg_set).count(), 0);
+    }
+
+    #[test]
+    fn test_deterministic_sparse_vec() {
+        let vec1 = deterministic_sparse_vec(10000, 200, 42);
+        let vec2 = deterministic_sparse_vec(10000, 200, 42);
+        assert_eq!(vec1.pos, vec2.pos);
+        assert_eq!(vec1.neg, vec2.neg);
+
+        // Different seed should give different result
+        let vec3 = deterministic_sparse_vec(10000, 200, 43);
+        assert_ne!(vec1.pos, vec3.pos);
+    }
+
+    #[test]
+    fn test_sparse_dot() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let a = random_sparse_vec(&mut rng, 10000, 200);
+        let b = random_sparse_vec(&mut rng, 10000, 200);
+
+        let dot = sparse_dot(&a, &b);
+
+        // Dot product should be symmetric
+        let dot_rev = sparse_dot(&b, &a);
+        assert_eq!(dot, dot_rev);
+    }
+
+    #[test]
+    fn test_index_overlap_hand_computed() {
+        let a = SparseVec {
+            pos: vec![0, 2, 4],
+            neg: vec![6, 8],
+        };
+        let b = SparseVec {
+            pos: vec![0, 6, 9],
+            neg: vec![2, 8, 10],
+        };
+
+        let stats = index_overlap(&a, &b);
+        assert_eq!(
+            stats,
+            OverlapStats {
+                pos_pos: 1,
+                neg_neg: 1,
+                pos_neg: 1,
+                neg_pos: 1,
+                only_a: 1,
+                only_b: 2,
+            }
+        );
+        // Differs at 2, 4, 6, 9, 10
+        assert_eq!(ternary_hamming(&a, &b), 5);
+        assert!((jaccard_similarity(&a, &b) - 4.0 / 7.0).abs() < 1e-12);
+
+        let empty = SparseVec {
+            pos: vec![],
+            neg: vec![],
+        };
+        assert_eq!(jaccard_similarity(&empty, &empty), 1.0);
+        assert_eq!(ternary_hamming(&a, &empty), 5);
+    }
+
+    #[test]
+    fn test_index_overlap_with_pos_neg_overlap() {
+        // Index 3 is both positive and negative, as in deliberately corrupt vectors
+        let corrupt = SparseVec {
+            pos: vec![1, 3],
+            neg: vec![3],
+        };
+        let stats = index_overlap(&corrupt, &corrupt);
+        assert_eq!((stats.only_a, stats.only_b), (0, 0));
+
+        let other = SparseVec {
+            pos: vec![1],
+            neg: vec![5],
+        };
+        let stats = index_overlap(&corrupt, &other);
+        assert_eq!((stats.only_a, stats.only_b), (1, 1));
+    }
+
+    #[test]
+    fn test_ternary_hamming_consistent_with_dot() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let a = random_sparse_vec(&mut rng, 1000, 100);
+            let b = random_sparse_vec(&mut rng, 1000, 100);
+            let stats = index_overlap(&a, &b);
+
+            let agree = (stats.pos_pos + stats.neg_neg) as i32;
+            let disagree = (stats.pos_neg + stats.neg_pos) as i32;
+            assert_eq!(sparse_dot(&a, &b), agree - disagree);
+
+            // Over the union, each index either agrees or counts toward hamming
+            assert_eq!(stats.union(), agree as usize + stats.hamming());
+            assert_eq!(ternary_hamming(&a, &a), 0);
+        }
+    }
+
+    #[test]
+    fn test_similarity_matrix() {
+        let a = SparseVec {
+            pos: vec![0, 1],
+            neg: vec![],
+        };
+        let c = SparseVec {
+            pos: vec![],
+            neg: vec![0, 1],
+        };
+        let vecs = vec![a.clone(), a, c];
+
+        let sims = similarity_matrix(&vecs);
+        assert_eq!(sims.len(), 3);
+        assert_eq!(sims.offdiagonal().len(), 3);
+        for i in 0..3 {
+            assert_eq!(sims.get(i, i), 1.0);
+            for j in 0..3 {
+                assert_eq!(sims.get(i, j), sims.get(j, i));
+            }
+        }
+        assert!((sims.get(0, 1) - 1.0).abs() < 1e-9);
+        assert!((sims.get(0, 2) + 1.0).abs() < 1e-9);
+        assert!((sims.max_offdiagonal().unwrap() - 1.0).abs() < 1e-9);
+        assert!((sims.mean_offdiagonal().unwrap() + 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(sims.histogram(4), vec![2, 0, 0, 1]);
+        assert_eq!(sims.to_csv().lines().count(), 3);
+
+        let mut streamed = Vec::new();
+        for_each_similarity(&vecs, |i, j, cos| streamed.push((i, j, cos)));
+        assert_eq!(streamed.len(), 3);
+        assert!(streamed.iter().all(|&(i, j, cos)| sims.get(i, j) == cos));
+    }
+
+    #[test]
+    fn test_similarity_matrix_parallel_matches_streaming() {
+        // Seed 0 steps to state 1, so seeds 0 and 1 would share a stream
+        let vecs: Vec<_> = (1..PARALLEL_SIMILARITY_THRESHOLD as u64 + 2)
+            .map(|seed| deterministic_sparse_vec(10000, 100, seed))
+            .collect();
+        let sims = similarity_matrix(&vecs);
+        for_each_similarity(&vecs, |i, j, cos| assert_eq!(sims.get(i, j), cos));
+        assert!(sims.max_offdiagonal().unwrap() < 0.5);
+    }
+
+    #[test]
+    fn test_generate_noise_pattern() {
+        let data1 = generate_noise_pattern(1000, 42);
+        let data2 = generate_noise_pattern(1000, 42);
+        assert_eq!(data1, data2);
+
+        let data3 = generate_noise_pattern(1000, 43);
+        assert_ne!(data1, data3);
+    }
+
+    #[test]
+    fn test_identity_permutation() {
+        let v = deterministic_sparse_vec(1000, 50, 3);
+        let identity: Vec<usize> = (0..1000).collect();
+        let permuted = permute_sparse_vec(&v, &identity);
+        assert_eq!(permuted.pos, v.pos);
+        assert_eq!(permuted.neg, v.neg);
+        assert_eq!(inverse_permutation(&identity), identity);
+    }
+
+    #[test]
+    fn test_random_permutation_round_trip() {
+        let perm = random_permutation(crate::DIM, 11);
+        assert_eq!(perm, random_permutation(crate::DIM, 11));
+        let mut sorted = perm.clone();
+        sorted.sort_unstable();
+        assert!(sorted.iter().enumerate().all(|(i, &p)| i == p));
+
+        let v = deterministic_sparse_vec(crate::DIM, 200, 5);
+        let permuted = permute_sparse_vec(&v, &perm);
+        assert_ne!(permuted.pos, v.pos);
+        let restored = permute_sparse_vec(&permuted, &inverse_permutation(&perm));
+        assert_eq!(restored.pos, v.pos);
+        assert_eq!(restored.neg, v.neg);
+    }
+
+    #[test]
+    fn test_reference_bundle_majority_and_ties() {
+        let a = SparseVec {
+            pos: vec![1, 2],
+            neg: vec![5],
+        };
+        let b = SparseVec {
+            pos: vec![1, 5],
+            neg: vec![2],
+        };
+        let c = SparseVec {
+            pos: vec![1],
+            neg: vec![2, 7],
+        };
+
+        // Two vectors: index 2 and 5 tie and are dropped
+        let ab = reference_bundle_many(&[a.clone(), b.clone()]);
+        assert_eq!(ab.pos, vec![1]);
+        assert!(ab.neg.is_empty());
+
+        // Three vectors: majority decides
+        let abc = reference_bundle_many(&[a.clone(), b.clone(), c.clone()]);
+        assert_eq!(abc.pos, vec![1]);
+        assert_eq!(abc.neg, vec![2, 7]);
+
+        // Weighting b heavily flips index 2 and 5 towards b
+        let weighted = reference_weighted_bundle(&[a, b, c], &[1, 3, 1]);
+        assert_eq!(weighted.pos, vec![1, 5]);
+        assert_eq!(weighted.neg, vec![2, 7]);
+    }
+
+    #[test]
+    fn test_vec_pool_matches_unpooled_generators() {
+        let pool = VecPool::new();
+        let mut plain_rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut pooled_rng = rand::rngs::StdRng::seed_from_u64(11);
+
+        let mut hits = Vec::new();
+        for i in 0..20 {
+            {
+                let expected = random_sparse_vec(&mut plain_rng, crate::DIM, 200);
+                let v = pool.random_sparse_vec(&mut pooled_rng, crate::DIM, 200);
+                assert_eq!(v.pos, expected.pos);
+                assert_eq!(v.neg, expected.neg);
+            }
+            let expected = deterministic_sparse_vec(crate::DIM, 101, i);
+            let v = pool.deterministic_sparse_vec(crate::DIM, 101, i);
+            assert_eq!(v.pos, expected.pos);
+            assert_eq!(v.neg, expected.neg);
+            drop(v);
+            hits.push(pool.stats().hits);
+        }
+
+        // Only the first vector allocates; every later one reuses buffers
+        let stats = pool.stats();
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.hits, 78);
+        assert!(hits.windows(2).all(|w| w[1] > w[0]));
+        assert_eq!(stats.peak_buffers, 2);
+
+        // One duplicate-detection set, kept warm and empty between calls
+        {
+            let state = pool.state.lock().unwrap();
+            assert_eq!(state.scratch.len(), 1);
+            assert!(state.scratch[0].is_empty());
+            assert!(state.scratch[0].capacity() >= 200);
+        }
+
+        // Filling in place clears stale contents first
+        let mut out = deterministic_sparse_vec(crate::DIM, 500, 1);
+        deterministic_sparse_vec_into(crate::DIM, 10, 2, &mut out);
+        let expected = deterministic_sparse_vec(crate::DIM, 10, 2);
+        assert_eq!((out.pos, out.neg), (expected.pos, expected.neg));
+    }
+
+    #[test]
+    fn test_vec_pool_is_bounded() {
+        let pool = VecPool::with_max_buffers(2);
+        let held: Vec<_> = (0..3)
+            .map(|seed| pool.deterministic_sparse_vec(crate::DIM, 20, seed))
+            .collect();
+        assert_eq!(pool.stats().misses, 6);
+        drop(held);
+
+        let stats = pool.stats();
+        assert_eq!(pool.idle_buffers(), 2);
+        assert_eq!(stats.peak_buffers, 2);
+        assert_eq!(stats.discarded, 4);
+
+        // Detached vectors keep their buffers
+        let v = pool
+            .deterministic_sparse_vec(crate::DIM, 20, 0)
+            .into_inner();
+        assert_eq!(v.pos.len() + v.neg.len(), 20);
+        assert_eq!(pool.idle_buffers(), 0);
+        assert_eq!(pool.stats().hits, 2);
+    }
+
+    #[test]
+    fn test_top_k_similar_paths_agree() {
+        let candidates: Vec<_> = (0..1_000)
+            .map(|seed| deterministic_sparse_vec(crate::DIM, 200, seed))
+            .collect();
+        // Related to a handful of candidates so the top ranks are meaningful
+        let probe = reference_bundle_many(&[
+            candidates[17].clone(),
+            candidates[404].clone(),
+            candidates[999].clone(),
+        ]);
+
+        for k in [0, 1, 10, 100] {
+            let serial = top_k_similar(&probe, &candidates, k);
+            assert_eq!(serial.len(), k);
+            assert_eq!(serial, par_top_k_similar(&probe, &candidates, k));
+            assert_eq!(serial, reference_top_k_similar(&probe, &candidates, k));
+        }
+        let top: HashSet<usize> = top_k_similar(&probe, &candidates, 3)
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(top, HashSet::from([17, 404, 999]));
+    }
+
+    #[test]
+    fn test_top_k_similar_small_sets_and_ties() {
+        let a = deterministic_sparse_vec(crate::DIM, 100, 1);
+        let b = deterministic_sparse_vec(crate::DIM, 100, 2);
+        // Duplicates of the probe tie at the top and come out in index order
+        let candidates = vec![b.clone(), a.clone(), b, a.clone()];
+
+        let all = top_k_similar(&a, &candidates, 10);
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].0, 1);
+        assert_eq!(all[1].0, 3);
+        assert_eq!(all[2].0, 0);
+        assert_eq!(all[3].0, 2);
+        assert_eq!(all, par_top_k_similar(&a, &candidates, 10));
+        assert_eq!(all, reference_top_k_similar(&a, &candidates, 10));
+
+        assert!(top_k_similar(&a, &[], 5).is_empty());
+        assert!(par_top_k_similar(&a, &[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_dim_generators_validate_parameters() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let gen = for_dim(64);
+        assert_eq!(DimGenerators::standard().dims(), crate::DIM);
+
+        assert_eq!(
+            gen.random(&mut rng, 65).unwrap_err(),
+            GenerationError::SparsityExceedsDims {
+                sparsity: 65,
+                dims: 64
+            }
+        );
+        assert!(gen.deterministic(65, 1).is_err());
+        assert_eq!(
+            gen.dense(&mut rng, 1.5).unwrap_err(),
+            GenerationError::InvalidFraction {
+                name: "density",
+                value: 1.5
+            }
+        );
+
+        // sparsity == dims fills every dimension exactly once
+        let full = gen.deterministic(64, 1).unwrap();
+        let mut all: Vec<usize> = full.pos.iter().chain(&full.neg).copied().collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..64).collect::<Vec<_>>());
+        let full = gen.random(&mut rng, 64).unwrap();
+        assert_eq!(full.pos.len() + full.neg.len(), 64);
+        assert_eq!(gen.dense(&mut rng, 0.5).unwrap().pos.len(), 16);
+
+        let zero = for_dim(0);
+        assert_eq!(
+            zero.random(&mut rng, 0).unwrap_err(),
+            GenerationError::ZeroDims
+        );
+        assert_eq!(
+            zero.deterministic(0, 1).unwrap_err(),
+            GenerationError::ZeroDims
+        );
+        // The free functions still return an empty vector in zero dimensions
+        assert!(deterministic_sparse_vec(0, 0, 1).pos.is_empty());
+        let empty = random_sparse_vec(&mut rng, 0, 0);
+        assert!(empty.pos.is_empty() && empty.neg.is_empty());
+        let result = std::panic::catch_unwind(|| deterministic_sparse_vec(0, 1, 1));
+        assert!(result.is_err());
+        let result = std::panic::catch_unwind(|| deterministic_sparse_vec(10, 11, 1));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_correlated_generator() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(9);
+        let gen = DimGenerators::standard();
+        let base = gen.deterministic(200, 4).unwrap();
+
+        let same = gen.correlated(&mut rng, &base, 1.0).unwrap();
+        assert_eq!((&same.pos, &same.neg), (&base.pos, &base.neg));
+        let half = gen.correlated(&mut rng, &base, 0.5).unwrap();
+        assert_eq!(half.pos.len() + half.neg.len(), 200);
+        let stats = index_overlap(&base, &half);
+        assert_eq!(stats.pos_pos + stats.neg_neg, 100);
+
+        let out_of_range = SparseVec {
+            pos: vec![crate::DIM],
+            neg: vec![],
+        };
+        assert_eq!(
+            gen.correlated(&mut rng, &out_of_range, 0.5).unwrap_err(),
+            GenerationError::IndexOutOfRange {
+                index: crate::DIM,
+                dims: crate::DIM
+            }
+        );
+        // Replacing every entry of a half-full vector needs twice the room
+        let crowded = for_dim(10).deterministic(6, 1).unwrap();
+        assert!(for_dim(10).correlated(&mut rng, &crowded, 0.0).is_err());
+    }
+}
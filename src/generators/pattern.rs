@@ -0,0 +1,163 @@
+//! Stable pseudo-encodings of [`TestDataPattern`] content
+//!
+//! [`vec_from_pattern`] stands in for "the vector the encoder would produce
+//! for this data" in harness tests that must not depend on the real
+//! encoder. Each fixed-size window of the pattern bytes, keyed by its
+//! position, votes for a few signed indices; the strongest votes become the
+//! vector. Equal inputs give equal vectors, and inputs sharing a prefix
+//! share the votes of those windows.
+
+use super::{assert_valid_sparsity, fnv1a, FNV_OFFSET};
+use crate::fixtures::{fill_pattern, TestDataPattern};
+use embeddenator_vsa::SparseVec;
+
+/// Bytes of pattern content hashed together
+const WINDOW_BYTES: usize = 64;
+
+/// Signed index votes cast by each window
+const VOTES_PER_WINDOW: usize = 8;
+
+/// Deterministic vector for `size` bytes of `pattern`
+///
+/// Every index lies in `0..dims` and exactly `sparsity` are set, sorted and
+/// with no overlap between `pos` and `neg`. Different patterns give
+/// near-orthogonal vectors; the signs are roughly balanced but not split
+/// exactly in half.
+///
+/// # Example
+/// ```rust,ignore
+/// let a = vec_from_pattern(TestDataPattern::Text, 4096, 10_000, 200);
+/// let b = vec_from_pattern(TestDataPattern::Seeded(7), 4096, 10_000, 200);
+/// assert!(a.cosine(&b).abs() < 0.1);
+/// ```
+///
+/// # Panics
+/// Panics if `dims` is zero or `sparsity > dims`.
+#[track_caller]
+pub fn vec_from_pattern(
+    pattern: TestDataPattern,
+    size: usize,
+    dims: usize,
+    sparsity: usize,
+) -> SparseVec {
+    assert_valid_sparsity(dims, sparsity);
+    let windows = size.div_ceil(WINDOW_BYTES).max(1);
+    let per_window = sparsity.div_ceil(windows).max(VOTES_PER_WINDOW);
+
+    let mut votes = vec![0i32; dims];
+    let mut content = FNV_OFFSET;
+    let mut buf = [0u8; WINDOW_BYTES];
+    for window in 0..windows {
+        let start = window * WINDOW_BYTES;
+        let bytes = &mut buf[..WINDOW_BYTES.min(size - start.min(size))];
+        fill_pattern(bytes, pattern, start);
+        let hash = fnv1a(fnv1a(FNV_OFFSET, &(window as u64).to_le_bytes()), bytes);
+        content = fnv1a(content, &hash.to_le_bytes());
+        let mut state = hash;
+        for _ in 0..per_window {
+            let draw = splitmix64(&mut state);
+            let index = (draw >> 1) as usize % dims;
+            votes[index] += if draw & 1 == 0 { 1 } else { -1 };
+        }
+    }
+
+    // Strongest votes first; ties broken by a content-keyed hash so equal
+    // counts do not favour low indices
+    let mut ranked: Vec<usize> = (0..dims).filter(|&i| votes[i] != 0).collect();
+    ranked.sort_by_key(|&i| {
+        (
+            std::cmp::Reverse(votes[i].unsigned_abs()),
+            mix(content ^ i as u64),
+        )
+    });
+    ranked.truncate(sparsity);
+
+    // Collisions and cancelled votes can leave too few candidates
+    let mut state = content;
+    while ranked.len() < sparsity {
+        let draw = splitmix64(&mut state);
+        let index = (draw >> 1) as usize % dims;
+        if votes[index] == 0 {
+            votes[index] = if draw & 1 == 0 { 1 } else { -1 };
+            ranked.push(index);
+        }
+    }
+
+    let (mut pos, mut neg): (Vec<usize>, Vec<usize>) =
+        ranked.into_iter().partition(|&i| votes[i] > 0);
+    pos.sort_unstable();
+    neg.sort_unstable();
+    SparseVec { pos, neg }
+}
+
+/// Next value of a SplitMix64 stream
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    mix(*state)
+}
+
+/// SplitMix64 finalizer
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrity::IntegrityValidator;
+
+    const PATTERNS: [TestDataPattern; 7] = [
+        TestDataPattern::Zeros,
+        TestDataPattern::Ones,
+        TestDataPattern::Sequential,
+        TestDataPattern::Random,
+        TestDataPattern::Seeded(1),
+        TestDataPattern::Compressible,
+        TestDataPattern::Text,
+    ];
+
+    #[test]
+    fn test_pattern_vectors_are_deterministic_and_valid() {
+        let validator = IntegrityValidator::new();
+        for pattern in PATTERNS {
+            for (size, sparsity) in [(0, 20), (10, 200), (4096, 200), (100_000, 64)] {
+                let v = vec_from_pattern(pattern, size, 10_000, sparsity);
+                let again = vec_from_pattern(pattern, size, 10_000, sparsity);
+                assert_eq!((&v.pos, &v.neg), (&again.pos, &again.neg));
+                assert_eq!(v.pos.len() + v.neg.len(), sparsity, "{:?}", pattern);
+                assert!(v.pos.iter().chain(&v.neg).all(|&i| i < 10_000));
+                let report = validator.validate_sparse(&v);
+                assert!(report.is_ok(), "{:?}: {}", pattern, report.summary());
+            }
+        }
+
+        let full = vec_from_pattern(TestDataPattern::Zeros, 20, 20, 20);
+        assert_eq!(full.pos.len() + full.neg.len(), 20);
+    }
+
+    #[test]
+    fn test_different_patterns_are_near_orthogonal() {
+        let vecs: Vec<SparseVec> = PATTERNS
+            .iter()
+            .map(|&p| vec_from_pattern(p, 8192, 10_000, 200))
+            .collect();
+        for (i, a) in vecs.iter().enumerate() {
+            assert!((a.cosine(a) - 1.0).abs() < 1e-9);
+            for (j, b) in vecs.iter().enumerate().skip(i + 1) {
+                let cosine = a.cosine(b);
+                assert!(
+                    cosine.abs() < 0.1,
+                    "{:?} vs {:?}: {}",
+                    PATTERNS[i],
+                    PATTERNS[j],
+                    cosine
+                );
+            }
+        }
+
+        let other_seed = vec_from_pattern(TestDataPattern::Seeded(2), 8192, 10_000, 200);
+        assert!(vecs[4].cosine(&other_seed).abs() < 0.1);
+    }
+}
@@ -0,0 +1,152 @@
+//! Vectors whose indices are confined to shard index ranges
+
+use super::GenerationError;
+use embeddenator_vsa::SparseVec;
+use rand::Rng;
+use std::ops::Range;
+
+/// Random vector with exactly `sparsity` non-zeros, all inside `shard`
+///
+/// Indices are drawn without replacement from `shard`; the first half
+/// (rounded up) are positive and the rest negative. The shard is not checked
+/// against [`DIM`](crate::DIM), so callers can test out-of-range routing too.
+///
+/// # Example
+/// ```rust,ignore
+/// let v = shard_confined_vec(&mut rng, 2500..5000, 50)?;
+/// assert!(integrity::assert_indices_within(&v, 2500..5000).is_ok());
+/// ```
+pub fn shard_confined_vec(
+    rng: &mut impl Rng,
+    shard: Range<usize>,
+    sparsity: usize,
+) -> Result<SparseVec, GenerationError> {
+    let mut out = SparseVec {
+        pos: Vec::with_capacity(sparsity.div_ceil(2)),
+        neg: Vec::with_capacity(sparsity / 2),
+    };
+    push_confined(rng, &shard, sparsity, &mut out)?;
+    out.pos.sort_unstable();
+    out.neg.sort_unstable();
+    Ok(out)
+}
+
+/// Random vector with `per_shard_sparsity` non-zeros inside each of `shards`
+///
+/// Every shard gets its own [`shard_confined_vec`] draw, so a vector routed
+/// by index must reach all of them. Shards must be disjoint.
+pub fn shard_spanning_vec(
+    rng: &mut impl Rng,
+    shards: &[Range<usize>],
+    per_shard_sparsity: usize,
+) -> Result<SparseVec, GenerationError> {
+    for (i, a) in shards.iter().enumerate() {
+        for (j, b) in shards.iter().enumerate().skip(i + 1) {
+            if a.start < b.end && b.start < a.end {
+                return Err(GenerationError::OverlappingShards {
+                    first: i,
+                    second: j,
+                });
+            }
+        }
+    }
+
+    let total = per_shard_sparsity * shards.len();
+    let mut out = SparseVec {
+        pos: Vec::with_capacity(total.div_ceil(2)),
+        neg: Vec::with_capacity(total / 2),
+    };
+    for shard in shards {
+        push_confined(rng, shard, per_shard_sparsity, &mut out)?;
+    }
+    out.pos.sort_unstable();
+    out.neg.sort_unstable();
+    Ok(out)
+}
+
+/// Append `sparsity` distinct indices from `shard` to `out`, unsorted
+fn push_confined(
+    rng: &mut impl Rng,
+    shard: &Range<usize>,
+    sparsity: usize,
+    out: &mut SparseVec,
+) -> Result<(), GenerationError> {
+    if shard.len() < sparsity {
+        return Err(GenerationError::ShardTooSmall {
+            start: shard.start,
+            end: shard.end,
+            sparsity,
+        });
+    }
+    let positive = sparsity.div_ceil(2);
+    for (n, offset) in rand::seq::index::sample(rng, shard.len(), sparsity)
+        .into_iter()
+        .enumerate()
+    {
+        let index = shard.start + offset;
+        if n < positive {
+            out.pos.push(index);
+        } else {
+            out.neg.push(index);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrity::assert_indices_within;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const SHARDS: [Range<usize>; 4] = [0..2500, 2500..5000, 5000..7500, 7500..10000];
+
+    #[test]
+    fn test_confined_vectors_stay_in_their_shard() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for shard in SHARDS {
+            let v = shard_confined_vec(&mut rng, shard.clone(), 51).unwrap();
+            assert_eq!(v.pos.len() + v.neg.len(), 51);
+            let report = assert_indices_within(&v, shard.clone());
+            assert!(report.is_ok(), "{}", report.summary());
+            for other in SHARDS.iter().filter(|s| **s != shard) {
+                assert!(!assert_indices_within(&v, other.clone()).is_ok());
+            }
+        }
+
+        assert_eq!(
+            shard_confined_vec(&mut rng, 10..20, 11).unwrap_err(),
+            GenerationError::ShardTooSmall {
+                start: 10,
+                end: 20,
+                sparsity: 11
+            }
+        );
+        assert!(shard_confined_vec(&mut rng, 10..20, 10).is_ok());
+    }
+
+    #[test]
+    fn test_spanning_vector_touches_every_shard() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let v = shard_spanning_vec(&mut rng, &SHARDS, 3).unwrap();
+        assert_eq!(v.pos.len() + v.neg.len(), 12);
+        assert!(assert_indices_within(&v, 0..10000).is_ok());
+        for shard in SHARDS {
+            let touched = v.pos.iter().chain(&v.neg).filter(|i| shard.contains(i));
+            assert_eq!(touched.count(), 3, "shard {:?}", shard);
+        }
+
+        assert_eq!(
+            shard_spanning_vec(&mut rng, &[0..100, 50..150], 3).unwrap_err(),
+            GenerationError::OverlappingShards {
+                first: 0,
+                second: 1
+            }
+        );
+        assert!(matches!(
+            shard_spanning_vec(&mut rng, &[0..100, 100..102], 3),
+            Err(GenerationError::ShardTooSmall { start: 100, .. })
+        ));
+    }
+}
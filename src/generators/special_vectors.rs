@@ -0,0 +1,123 @@
+//! Named constructors for degenerate and edge-case vectors
+//!
+//! Every vector built here satisfies the sparse invariants checked by
+//! [`IntegrityValidator::validate_sparse`](crate::IntegrityValidator::validate_sparse):
+//! sorted, duplicate-free indices with no overlap between `pos` and `neg`.
+//! Prefer these over struct literals so edge-case tests cannot accidentally
+//! start from an invalid vector.
+
+use embeddenator_vsa::SparseVec;
+
+/// Sign of a single non-zero entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sign {
+    /// `+1`, stored in `pos`
+    Positive,
+    /// `-1`, stored in `neg`
+    Negative,
+}
+
+/// The zero vector
+pub fn empty() -> SparseVec {
+    SparseVec {
+        pos: Vec::new(),
+        neg: Vec::new(),
+    }
+}
+
+/// Vector with one non-zero at `index`
+pub fn single(index: usize, sign: Sign) -> SparseVec {
+    let mut v = empty();
+    match sign {
+        Sign::Positive => v.pos.push(index),
+        Sign::Negative => v.neg.push(index),
+    }
+    v
+}
+
+/// Every index in `0..n` positive
+pub fn all_positive_upto(n: usize) -> SparseVec {
+    SparseVec {
+        pos: (0..n).collect(),
+        neg: Vec::new(),
+    }
+}
+
+/// Every index in `0..n` set, even indices positive and odd negative
+pub fn alternating(n: usize) -> SparseVec {
+    SparseVec {
+        pos: (0..n).step_by(2).collect(),
+        neg: (1..n).step_by(2).collect(),
+    }
+}
+
+/// Densest valid vector of `dims` dimensions: every index non-zero
+///
+/// Uses the [`alternating`] sign pattern, so positive and negative counts
+/// differ by at most one.
+pub fn max_density(dims: usize) -> SparseVec {
+    alternating(dims)
+}
+
+/// Indices clustered around every multiple of `block` below `dims`
+///
+/// Sets `k*block - 1`, `k*block` and `k*block + 1` for each `k >= 1`, plus
+/// index 0 and 1, with signs alternating in index order. Packed block
+/// formats that mishandle the first or last lane of a block lose entries here.
+pub fn block_boundaries(dims: usize, block: usize) -> SparseVec {
+    let mut indices: Vec<usize> = vec![0, 1];
+    let mut edge = block.max(1);
+    while edge - 1 < dims {
+        indices.extend([edge - 1, edge, edge + 1]);
+        edge += block.max(1);
+    }
+    indices.retain(|&i| i < dims);
+    indices.sort_unstable();
+    indices.dedup();
+
+    let mut v = empty();
+    for (n, index) in indices.into_iter().enumerate() {
+        if n.is_multiple_of(2) {
+            v.pos.push(index);
+        } else {
+            v.neg.push(index);
+        }
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::fingerprint;
+    use crate::integrity::IntegrityValidator;
+
+    #[test]
+    fn test_special_vectors_validate_clean() {
+        let validator = IntegrityValidator::new().with_dims(100);
+        let vectors = [
+            empty(),
+            single(0, Sign::Positive),
+            single(99, Sign::Negative),
+            all_positive_upto(100),
+            alternating(7),
+            max_density(100),
+            block_boundaries(100, 32),
+            block_boundaries(1, 64),
+        ];
+        for v in &vectors {
+            let report = validator.validate_sparse(v);
+            assert!(report.is_ok(), "{}: {:?}", fingerprint(v), report.failures);
+        }
+
+        assert_eq!(single(5, Sign::Negative).neg, vec![5]);
+        assert_eq!(alternating(5).pos, vec![0, 2, 4]);
+        assert_eq!(alternating(5).neg, vec![1, 3]);
+        let dense = max_density(100);
+        assert_eq!(dense.pos.len() + dense.neg.len(), 100);
+        let edges = block_boundaries(200, 64);
+        let mut all: Vec<usize> = edges.pos.iter().chain(&edges.neg).copied().collect();
+        all.sort_unstable();
+        assert_eq!(all, vec![0, 1, 63, 64, 65, 127, 128, 129, 191, 192, 193]);
+    }
+}
@@ -0,0 +1,302 @@
+//! Synthetic text corpora with known token statistics
+//!
+//! Words are drawn from a fixed vocabulary with Zipf-distributed ranks, so
+//! frequent and rare tokens appear in realistic proportions, and every
+//! emitted token is counted so tests can compute expected similarities
+//! exactly instead of estimating them.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+const CONSONANTS: &[u8] = b"bcdfghjklmnprstvz";
+const VOWELS: &[u8] = b"aeiou";
+
+/// Multi-byte codepoints appended to words in Unicode mode (2, 3 and 4 bytes)
+const UNICODE_SUFFIXES: [char; 6] = ['é', 'ß', 'ж', 'λ', '中', '😀'];
+
+/// Distribution of words per sentence
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SentenceLength {
+    /// Every sentence has exactly this many words
+    Fixed(usize),
+    /// Uniform in `[min, max]` words
+    Uniform { min: usize, max: usize },
+}
+
+impl SentenceLength {
+    /// Draw a word count (at least 1)
+    fn sample(&self, rng: &mut impl Rng) -> usize {
+        let words = match *self {
+            SentenceLength::Fixed(words) => words,
+            SentenceLength::Uniform { min, max } => rng.random_range(min.min(max)..=max.max(min)),
+        };
+        words.max(1)
+    }
+}
+
+/// Configures a [`TextCorpus`]
+///
+/// # Example
+/// ```rust,ignore
+/// let mut corpus = TextCorpusBuilder::new()
+///     .with_vocab_size(5_000)
+///     .with_zipf_exponent(1.1)
+///     .with_seed(42)
+///     .build();
+/// let text = corpus.take_text(1024 * 1024);
+/// let counts = corpus.token_frequencies();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextCorpusBuilder {
+    vocab_size: usize,
+    zipf_exponent: f64,
+    sentence_length: SentenceLength,
+    seed: u64,
+    unicode: bool,
+}
+
+impl Default for TextCorpusBuilder {
+    fn default() -> Self {
+        Self {
+            vocab_size: 1000,
+            zipf_exponent: 1.0,
+            sentence_length: SentenceLength::Uniform { min: 5, max: 15 },
+            seed: 0,
+            unicode: false,
+        }
+    }
+}
+
+impl TextCorpusBuilder {
+    /// 1000 words, Zipf exponent 1.0, 5-15 words per sentence, seed 0
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw from `vocab_size` distinct words (at least 1)
+    pub fn with_vocab_size(mut self, vocab_size: usize) -> Self {
+        self.vocab_size = vocab_size.max(1);
+        self
+    }
+
+    /// Weight the word of rank `r` (from 1) by `1 / r^exponent`
+    ///
+    /// 0 gives a uniform distribution; negative values are treated as 0.
+    pub fn with_zipf_exponent(mut self, exponent: f64) -> Self {
+        self.zipf_exponent = exponent.max(0.0);
+        self
+    }
+
+    /// Use `length` for words per sentence
+    pub fn with_sentence_length(mut self, length: SentenceLength) -> Self {
+        self.sentence_length = length;
+        self
+    }
+
+    /// Seed word and sentence length sampling
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Give every third word a multi-byte codepoint suffix
+    ///
+    /// Exercises UTF-8 boundary handling in chunkers; output stays valid
+    /// UTF-8.
+    pub fn with_unicode(mut self, enabled: bool) -> Self {
+        self.unicode = enabled;
+        self
+    }
+
+    /// Start generating
+    pub fn build(&self) -> TextCorpus {
+        let vocabulary: Vec<String> = (0..self.vocab_size)
+            .map(|rank| vocabulary_word(rank, self.unicode))
+            .collect();
+        let mut total = 0.0;
+        let cumulative = (0..self.vocab_size)
+            .map(|rank| {
+                total += 1.0 / ((rank + 1) as f64).powf(self.zipf_exponent);
+                total
+            })
+            .collect();
+        TextCorpus {
+            counts: vec![0; vocabulary.len()],
+            vocabulary,
+            cumulative,
+            sentence_length: self.sentence_length,
+            rng: StdRng::seed_from_u64(self.seed),
+            tokens: 0,
+        }
+    }
+}
+
+/// Word of frequency rank `rank`, unique per rank
+///
+/// The rank is written in bijective base 85 with consonant-vowel syllables
+/// as digits.
+fn vocabulary_word(rank: usize, unicode: bool) -> String {
+    let syllables = CONSONANTS.len() * VOWELS.len();
+    let mut word = String::new();
+    let mut n = rank;
+    loop {
+        let syllable = n % syllables;
+        word.push(CONSONANTS[syllable / VOWELS.len()] as char);
+        word.push(VOWELS[syllable % VOWELS.len()] as char);
+        n /= syllables;
+        if n == 0 {
+            break;
+        }
+        n -= 1;
+    }
+    if unicode && rank % 3 == 1 {
+        word.push(UNICODE_SUFFIXES[rank / 3 % UNICODE_SUFFIXES.len()]);
+    }
+    word
+}
+
+/// Endless stream of sentences that counts the tokens it emits
+///
+/// Each sentence is lowercase words separated by single spaces and ends
+/// with a period. Created by [`TextCorpusBuilder::build`].
+#[derive(Clone, Debug)]
+pub struct TextCorpus {
+    vocabulary: Vec<String>,
+    /// Running sum of rank weights, for inverse-CDF sampling
+    cumulative: Vec<f64>,
+    sentence_length: SentenceLength,
+    rng: StdRng,
+    /// Emitted count per rank
+    counts: Vec<u64>,
+    tokens: u64,
+}
+
+impl TextCorpus {
+    /// Sentences joined by spaces, stopping once `target_bytes` is reached
+    ///
+    /// The result is at least `target_bytes` long and overshoots by less
+    /// than one sentence (plus its separator).
+    pub fn take_text(&mut self, target_bytes: usize) -> String {
+        let mut text = String::with_capacity(target_bytes);
+        while text.len() < target_bytes {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&self.next_sentence());
+        }
+        text
+    }
+
+    /// Generate one sentence
+    pub fn next_sentence(&mut self) -> String {
+        let words = self.sentence_length.sample(&mut self.rng);
+        let mut sentence = String::new();
+        for i in 0..words {
+            let total = self.cumulative[self.cumulative.len() - 1];
+            let target = self.rng.random::<f64>() * total;
+            let rank = self
+                .cumulative
+                .partition_point(|&c| c <= target)
+                .min(self.vocabulary.len() - 1);
+            self.counts[rank] += 1;
+            if i > 0 {
+                sentence.push(' ');
+            }
+            sentence.push_str(&self.vocabulary[rank]);
+        }
+        self.tokens += words as u64;
+        sentence.push('.');
+        sentence
+    }
+
+    /// Exact number of times each word has been emitted so far
+    ///
+    /// Words not yet emitted are omitted.
+    pub fn token_frequencies(&self) -> BTreeMap<String, u64> {
+        self.vocabulary
+            .iter()
+            .zip(&self.counts)
+            .filter(|(_, &count)| count > 0)
+            .map(|(word, &count)| (word.clone(), count))
+            .collect()
+    }
+
+    /// Tokens emitted so far
+    pub fn token_count(&self) -> u64 {
+        self.tokens
+    }
+
+    /// Every word that can be emitted, most frequent rank first
+    pub fn vocabulary(&self) -> &[String] {
+        &self.vocabulary
+    }
+}
+
+impl Iterator for TextCorpus {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        Some(self.next_sentence())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_text_length_and_frequencies() {
+        let target = 64 * 1024;
+        let mut corpus = TextCorpusBuilder::new().with_seed(7).build();
+        let text = corpus.take_text(target);
+        let last_sentence = text.rsplit(". ").next().unwrap();
+        assert!(text.len() >= target);
+        assert!(text.len() - target <= last_sentence.len() + 1);
+
+        let frequencies = corpus.token_frequencies();
+        assert_eq!(frequencies.values().sum::<u64>(), corpus.token_count());
+        let mut recounted = BTreeMap::new();
+        for word in text.split_whitespace() {
+            *recounted
+                .entry(word.trim_end_matches('.').to_string())
+                .or_insert(0u64) += 1;
+        }
+        assert_eq!(recounted, frequencies);
+
+        // Zipf: the top-ranked word is the most frequent
+        let top = &corpus.vocabulary()[0];
+        assert_eq!(frequencies.values().max(), frequencies.get(top));
+
+        let unique: HashSet<&String> = corpus.vocabulary().iter().collect();
+        assert_eq!(unique.len(), 1000);
+    }
+
+    #[test]
+    fn test_same_seed_reproducible() {
+        let builder = TextCorpusBuilder::new()
+            .with_vocab_size(50)
+            .with_zipf_exponent(1.3)
+            .with_sentence_length(SentenceLength::Fixed(4))
+            .with_seed(3);
+        let a: Vec<String> = builder.build().take(20).collect();
+        let b: Vec<String> = builder.build().take(20).collect();
+        assert_eq!(a, b);
+        assert!(a
+            .iter()
+            .all(|s| s.split(' ').count() == 4 && s.ends_with('.')));
+
+        let other: Vec<String> = builder.clone().with_seed(4).build().take(20).collect();
+        assert_ne!(a, other);
+    }
+
+    #[test]
+    fn test_unicode_mode_emits_multibyte_words() {
+        let mut corpus = TextCorpusBuilder::new().with_unicode(true).build();
+        let text = corpus.take_text(4096);
+        assert!(text.len() > text.chars().count());
+        assert!(corpus.vocabulary().iter().any(|w| w.ends_with('😀')));
+        assert!((0..text.len()).any(|i| !text.is_char_boundary(i)));
+    }
+}
@@ -0,0 +1,155 @@
+//! Query probe streams with a known fraction of hits
+
+use super::{assert_valid_sparsity, for_dim, random_sparse_vec, validate_fraction};
+use embeddenator_vsa::{SparseVec, DIM};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// One query from [`query_workload`]
+#[derive(Clone, Debug)]
+pub struct QueryProbe {
+    /// Vector to search with
+    pub query: SparseVec,
+    /// Index of the stored vector this probe was derived from, `None` for misses
+    pub expected: Option<usize>,
+}
+
+impl QueryProbe {
+    /// Whether a stored vector should match this probe
+    pub fn is_hit(&self) -> bool {
+        self.expected.is_some()
+    }
+}
+
+/// Mixed hit/miss probes against `stored`, in [`DIM`] dimensions
+///
+/// `round(hit_ratio * count)` probes are copies of a randomly chosen stored
+/// vector with `noise_flips` of its non-zeros moved to fresh indices (see
+/// [`DimGenerators::correlated`](super::DimGenerators::correlated)); the rest
+/// are fresh random vectors with the sparsity of a random stored vector.
+/// Probes are shuffled, and the same `rng` state gives the same workload.
+///
+/// Feed the result to [`RetrievalEval::add_probes`](crate::RetrievalEval::add_probes)
+/// to score a search function on it.
+///
+/// # Example
+/// ```rust,ignore
+/// let probes = query_workload(&stored, &mut rng, 1000, 0.8, 10);
+/// let mut eval = RetrievalEval::new(|q: &SparseVec, k| {
+///     top_k_similar(q, &stored, k).into_iter().map(|(i, _)| i).collect()
+/// });
+/// eval.add_probes(&probes);
+/// assert!(eval.evaluate(&[1]).point(1).unwrap().recall > 0.95);
+/// ```
+///
+/// # Panics
+/// Panics if `stored` is empty, `hit_ratio` is outside `[0, 1]`, or a stored
+/// vector does not fit in [`DIM`] with room for the flipped indices.
+pub fn query_workload(
+    stored: &[SparseVec],
+    rng: &mut impl Rng,
+    count: usize,
+    hit_ratio: f64,
+    noise_flips: usize,
+) -> Vec<QueryProbe> {
+    assert!(!stored.is_empty(), "query_workload needs stored vectors");
+    if let Err(e) = validate_fraction("hit_ratio", hit_ratio) {
+        panic!("invalid generator parameters: {}", e);
+    }
+    let generators = for_dim(DIM);
+    let hits = ((hit_ratio * count as f64).round() as usize).min(count);
+
+    let mut probes: Vec<QueryProbe> = (0..count)
+        .map(|i| {
+            let index = rng.random_range(0..stored.len());
+            let base = &stored[index];
+            let nnz = base.pos.len() + base.neg.len();
+            if i < hits {
+                let overlap = if nnz == 0 {
+                    1.0
+                } else {
+                    (nnz - noise_flips.min(nnz)) as f64 / nnz as f64
+                };
+                let query = generators
+                    .correlated(rng, base, overlap)
+                    .unwrap_or_else(|e| panic!("invalid generator parameters: {}", e));
+                QueryProbe {
+                    query,
+                    expected: Some(index),
+                }
+            } else {
+                assert_valid_sparsity(DIM, nnz);
+                QueryProbe {
+                    query: random_sparse_vec(rng, DIM, nnz),
+                    expected: None,
+                }
+            }
+        })
+        .collect();
+    probes.shuffle(rng);
+    probes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::top_k_similar;
+    use crate::RetrievalEval;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn stored(n: usize) -> Vec<SparseVec> {
+        let mut rng = StdRng::seed_from_u64(1);
+        (0..n)
+            .map(|_| random_sparse_vec(&mut rng, DIM, 100))
+            .collect()
+    }
+
+    #[test]
+    fn test_hit_fraction_and_expected_indices() {
+        let stored = stored(50);
+        let probes = query_workload(&stored, &mut StdRng::seed_from_u64(2), 333, 0.7, 10);
+        assert_eq!(probes.len(), 333);
+        assert_eq!(probes.iter().filter(|p| p.is_hit()).count(), 233);
+        // Shuffled: hits are not all at the front
+        assert!(probes[..233].iter().any(|p| !p.is_hit()));
+
+        for probe in &probes {
+            let nnz = probe.query.pos.len() + probe.query.neg.len();
+            assert_eq!(nnz, 100);
+            if let Some(index) = probe.expected {
+                assert!(index < stored.len());
+                let shared = crate::generators::index_overlap(&probe.query, &stored[index]);
+                assert_eq!(shared.shared(), 90);
+            }
+        }
+
+        let mut eval = RetrievalEval::new(|q: &SparseVec, k| {
+            top_k_similar(q, &stored, k)
+                .into_iter()
+                .map(|(i, _)| i)
+                .collect()
+        });
+        eval.add_probes(&probes);
+        let report = eval.evaluate(&[1]);
+        assert_eq!(report.evaluated_queries, 233);
+        assert_eq!(report.skipped_queries, 100);
+        assert_eq!(report.point(1).unwrap().recall, 1.0);
+    }
+
+    #[test]
+    fn test_same_seed_same_workload() {
+        let stored = stored(10);
+        let parts = |seed| {
+            query_workload(&stored, &mut StdRng::seed_from_u64(seed), 40, 0.5, 5)
+                .into_iter()
+                .map(|p| (p.query.pos, p.query.neg, p.expected))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(parts(9), parts(9));
+        assert_ne!(parts(9), parts(10));
+
+        let all_misses = query_workload(&stored, &mut StdRng::seed_from_u64(3), 20, 0.0, 5);
+        assert!(all_misses.iter().all(|p| !p.is_hit()));
+    }
+}
@@ -0,0 +1,235 @@
+//! File age and growth simulation for incremental-scan tests
+//!
+//! [`age_dataset`] gives every file in a tree a deterministic mtime near a
+//! base time; [`touch_subset`] and [`grow_subset`] then move a seeded
+//! fraction of the files past it, so a scanner keyed on mtime has an exact
+//! expected change set.
+
+use crate::error::{Error, IoResultExt, Result};
+use crate::fixtures::{try_walk_deterministic, WalkOrder};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Set the mtime of every file under `dir` to `base_time` plus a jitter
+///
+/// Each file's offset is a whole number of seconds in `0..=jitter`, derived
+/// from a hash of its relative path, so the same tree always ages the same
+/// way. Returns the mtime set on each file, in sorted path order.
+///
+/// # Example
+/// ```rust,ignore
+/// let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// age_dataset(&dir, base, Duration::from_secs(3600));
+/// let changed = touch_subset(&dir, 0.1, 7, base + Duration::from_secs(86_400));
+/// assert_eq!(scanner.changed_since(base + Duration::from_secs(3600)), changed);
+/// ```
+///
+/// # Panics
+/// Panics if the tree cannot be walked or a timestamp cannot be set.
+pub fn age_dataset(
+    dir: &Path,
+    base_time: SystemTime,
+    jitter: Duration,
+) -> Vec<(PathBuf, SystemTime)> {
+    try_age_dataset(dir, base_time, jitter)
+        .unwrap_or_else(|e| panic!("Failed to age dataset: {}", e))
+}
+
+/// Fallible variant of [`age_dataset`]
+pub fn try_age_dataset(
+    dir: &Path,
+    base_time: SystemTime,
+    jitter: Duration,
+) -> Result<Vec<(PathBuf, SystemTime)>> {
+    let span = jitter.as_secs() + 1;
+    try_walk_deterministic(dir, WalkOrder::Sorted)?
+        .into_iter()
+        .map(|path| {
+            let rel = path.strip_prefix(dir).unwrap_or(&path);
+            let hash = blake3::hash(rel.to_string_lossy().as_bytes());
+            let seed = u64::from_le_bytes(hash.as_bytes()[..8].try_into().expect("8 bytes"));
+            let mtime = base_time + Duration::from_secs(seed % span);
+            set_mtime(&path, mtime)?;
+            Ok((path, mtime))
+        })
+        .collect()
+}
+
+/// Set the mtime of a seeded `fraction` of the files under `dir`
+///
+/// `round(fraction * files)` files are picked by the seeded order of
+/// [`WalkOrder::SeededShuffle`]. Returns exactly the changed paths, sorted.
+///
+/// # Panics
+/// Panics if `fraction` is outside `[0, 1]` or a file cannot be touched.
+pub fn touch_subset(dir: &Path, fraction: f64, seed: u64, new_time: SystemTime) -> Vec<PathBuf> {
+    try_touch_subset(dir, fraction, seed, new_time)
+        .unwrap_or_else(|e| panic!("Failed to touch files: {}", e))
+}
+
+/// Fallible variant of [`touch_subset`]
+pub fn try_touch_subset(
+    dir: &Path,
+    fraction: f64,
+    seed: u64,
+    new_time: SystemTime,
+) -> Result<Vec<PathBuf>> {
+    change_subset(dir, fraction, seed, new_time, false)
+}
+
+/// Like [`touch_subset`], but also append one byte to each picked file
+///
+/// The same `seed` and `fraction` pick the same files as [`touch_subset`].
+pub fn grow_subset(dir: &Path, fraction: f64, seed: u64, new_time: SystemTime) -> Vec<PathBuf> {
+    try_grow_subset(dir, fraction, seed, new_time)
+        .unwrap_or_else(|e| panic!("Failed to grow files: {}", e))
+}
+
+/// Fallible variant of [`grow_subset`]
+pub fn try_grow_subset(
+    dir: &Path,
+    fraction: f64,
+    seed: u64,
+    new_time: SystemTime,
+) -> Result<Vec<PathBuf>> {
+    change_subset(dir, fraction, seed, new_time, true)
+}
+
+fn change_subset(
+    dir: &Path,
+    fraction: f64,
+    seed: u64,
+    new_time: SystemTime,
+    append: bool,
+) -> Result<Vec<PathBuf>> {
+    if !(0.0..=1.0).contains(&fraction) {
+        return Err(Error::invalid_spec(
+            dir,
+            format!("fraction must be within [0, 1], got {}", fraction),
+        ));
+    }
+    let mut paths = try_walk_deterministic(dir, WalkOrder::SeededShuffle(seed))?;
+    let count = ((fraction * paths.len() as f64).round() as usize).min(paths.len());
+    paths.truncate(count);
+    paths.sort();
+
+    for path in &paths {
+        if append {
+            fs::OpenOptions::new()
+                .append(true)
+                .open(path)
+                .and_then(|mut f| f.write_all(b"\n"))
+                .map_err(|e| Error::write(path, 1, e))?;
+        }
+        set_mtime(path, new_time)?;
+    }
+    Ok(paths)
+}
+
+/// Set the modification time of `path`
+///
+/// Uses the platform call behind [`fs::File::set_modified`] on Unix and
+/// Windows; elsewhere, or where the filesystem refuses, the error is
+/// [`Error::Unsupported`].
+pub(crate) fn set_mtime(path: &Path, mtime: SystemTime) -> Result<()> {
+    const OPERATION: &str = "setting modification times";
+
+    if cfg!(not(any(unix, windows))) {
+        return Err(Error::Unsupported {
+            path: path.to_path_buf(),
+            operation: OPERATION,
+        });
+    }
+    // futimens only needs ownership, so Unix can set times on read-only
+    // files; Windows needs a handle with write access
+    let mut options = fs::File::options();
+    if cfg!(unix) {
+        options.read(true);
+    } else {
+        options.write(true);
+    }
+    let result = options.open(path).and_then(|f| f.set_modified(mtime));
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => Err(Error::Unsupported {
+            path: path.to_path_buf(),
+            operation: OPERATION,
+        }),
+        other => other.at_path(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn mtime(path: &Path) -> SystemTime {
+        fs::metadata(path).unwrap().modified().unwrap()
+    }
+
+    #[test]
+    fn test_age_then_touch_subset_matches_mtime_scan() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..20 {
+            let sub = dir.path().join(format!("d{}", i % 3));
+            fs::create_dir_all(&sub).unwrap();
+            fs::write(sub.join(format!("f{:02}.txt", i)), vec![b'x'; i]).unwrap();
+        }
+
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let jitter = Duration::from_secs(3600);
+        let aged = age_dataset(dir.path(), base, jitter);
+        assert_eq!(aged.len(), 20);
+        for (path, expected) in &aged {
+            assert_eq!(mtime(path), *expected, "{}", path.display());
+            assert!(*expected >= base && *expected <= base + jitter);
+        }
+        assert_eq!(age_dataset(dir.path(), base, jitter), aged);
+
+        let cutoff = base + jitter;
+        let later = base + Duration::from_secs(86_400);
+        let changed = touch_subset(dir.path(), 0.1, 42, later);
+        assert_eq!(changed.len(), 2);
+        let scanned: Vec<PathBuf> = aged
+            .iter()
+            .map(|(path, _)| path.clone())
+            .filter(|path| mtime(path) > cutoff)
+            .collect();
+        assert_eq!(scanned, changed);
+        for path in &changed {
+            assert_eq!(mtime(path), later);
+        }
+
+        let sizes: Vec<u64> = changed
+            .iter()
+            .map(|p| fs::metadata(p).unwrap().len())
+            .collect();
+        assert_eq!(grow_subset(dir.path(), 0.1, 42, later), changed);
+        for (path, size) in changed.iter().zip(sizes) {
+            assert_eq!(fs::metadata(path).unwrap().len(), size + 1);
+            assert_eq!(mtime(path), later);
+        }
+
+        assert!(matches!(
+            try_touch_subset(dir.path(), 1.5, 0, later),
+            Err(Error::InvalidSpec { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_set_mtime_on_read_only_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("frozen.txt");
+        fs::write(&path, b"x").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o444)).unwrap();
+
+        let when = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        set_mtime(&path, when).unwrap();
+        assert_eq!(mtime(&path), when);
+    }
+}
@@ -0,0 +1,595 @@
+//! One tar archive with everything needed to debug a failed test
+//!
+//! [`FailureArtifacts`] gathers reports, metrics, JSON values and files,
+//! then writes them as a plain ustar archive with an `index.json` listing
+//! every entry. The archive size is capped; when the cap bites, small
+//! entries are kept whole and the largest ones lose their tails first.
+
+use super::TestHarness;
+use crate::error::{CorruptionKind, Error, IoResultExt, Result};
+use crate::integrity::IntegrityReport;
+use crate::metrics::TestMetrics;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Archive size cap used unless [`FailureArtifacts::with_max_bytes`] changes it
+pub const DEFAULT_ARTIFACT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Environment variable naming the directory for archives written when a
+/// harness with [`TestHarness::on_failure`] hooks is dropped during a panic
+pub const FAILURE_ARTIFACTS_ENV: &str = "TESTKIT_ARTIFACT_DIR";
+
+const BLOCK: usize = 512;
+
+enum Source {
+    Bytes(Vec<u8>),
+    File(PathBuf),
+}
+
+struct Entry {
+    name: String,
+    kind: &'static str,
+    source: Source,
+}
+
+/// Collects the evidence of a failed test into one archive
+///
+/// # Example
+/// ```rust,ignore
+/// let harness = TestHarness::new();
+/// let mut artifacts = FailureArtifacts::collect(&harness);
+/// artifacts
+///     .add_report("extraction", &integrity)
+///     .add_metrics(&metrics)
+///     .add_json("chaos", &corruption_manifest)
+///     .add_file(&failing_file);
+/// let tar = artifacts.write_archive(Path::new("target/failures/roundtrip.tar"))?;
+/// ```
+pub struct FailureArtifacts {
+    root: PathBuf,
+    entries: Vec<Entry>,
+    names: HashSet<String>,
+    max_bytes: u64,
+}
+
+impl FailureArtifacts {
+    /// Start an empty bundle for `harness`
+    ///
+    /// Files under the harness directory keep their relative path in the
+    /// archive; anything else is stored by file name.
+    pub fn collect(harness: &TestHarness) -> Self {
+        Self {
+            root: harness.temp_dir().to_path_buf(),
+            entries: Vec::new(),
+            names: HashSet::new(),
+            max_bytes: DEFAULT_ARTIFACT_MAX_BYTES,
+        }
+    }
+
+    /// Cap the archive size, tar headers and `index.json` included
+    ///
+    /// Entry contents get what is left after that overhead, so a cap
+    /// smaller than the overhead alone stores empty entries and is exceeded.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Add an integrity report as `reports/<name>.json`
+    pub fn add_report(&mut self, name: &str, report: &IntegrityReport) -> &mut Self {
+        let value = serde_json::json!({
+            "ok": report.is_ok(),
+            "checks_total": report.checks_total,
+            "checks_passed": report.checks_passed,
+            "bitflips_detected": report.bitflips_detected,
+            "corruption_events": report.corruption_events,
+            "invariant_violations": report.invariant_violations,
+            "failures": report.failures,
+            "file_mismatches": report
+                .file_mismatches
+                .iter()
+                .map(|m| serde_json::json!({ "path": m.path, "kind": format!("{:?}", m.kind) }))
+                .collect::<Vec<_>>(),
+            "warnings": report.warnings,
+        });
+        self.push_json(&format!("reports/{}.json", name), "report", &value)
+    }
+
+    /// Add timing statistics and counters as `metrics/<name>.json`
+    pub fn add_metrics(&mut self, metrics: &TestMetrics) -> &mut Self {
+        let stats = metrics.timing_stats();
+        let value = serde_json::json!({
+            "name": metrics.name,
+            "count": stats.count,
+            "min_ns": stats.min_ns,
+            "max_ns": stats.max_ns,
+            "mean_ns": stats.mean_ns,
+            "p50_ns": stats.p50_ns,
+            "p95_ns": stats.p95_ns,
+            "p99_ns": stats.p99_ns,
+            "op_counts": metrics.op_counts,
+            "custom_metrics": metrics.custom_metrics,
+            "error_count": metrics.error_count,
+            "warning_count": metrics.warning_count,
+        });
+        self.push_json(&format!("metrics/{}.json", metrics.name), "metrics", &value)
+    }
+
+    /// Add any serializable value as `<name>.json`, e.g. a chaos scenario
+    pub fn add_json(&mut self, name: &str, value: &impl Serialize) -> &mut Self {
+        let value = serde_json::to_value(value)
+            .unwrap_or_else(|e| serde_json::json!({ "serialize_error": e.to_string() }));
+        self.push_json(&format!("{}.json", name), "json", &value)
+    }
+
+    /// Add a file under `files/`; its content is read when the archive is
+    /// written
+    pub fn add_file(&mut self, path: &Path) -> &mut Self {
+        let rel = match path.strip_prefix(&self.root) {
+            Ok(rel) if !rel.as_os_str().is_empty() => rel.to_string_lossy().replace('\\', "/"),
+            _ => path
+                .file_name()
+                .map_or("file".to_string(), |n| n.to_string_lossy().into_owned()),
+        };
+        self.push(
+            format!("files/{}", rel),
+            "file",
+            Source::File(path.to_path_buf()),
+        )
+    }
+
+    /// Number of entries added so far (excluding `index.json`)
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing has been added
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write the archive to `dest`, creating parent directories
+    ///
+    /// `index.json` comes first and lists every entry with its original and
+    /// stored size. Files are streamed into the archive, never read whole;
+    /// files that cannot be opened are listed with an `error` instead of
+    /// being stored, and a file that shrinks while being archived is padded
+    /// with zeros to its listed size.
+    pub fn write_archive(&self, dest: &Path) -> Result<PathBuf> {
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).at_path(parent)?;
+        }
+
+        let sizes: Vec<std::result::Result<u64, String>> = self
+            .entries
+            .iter()
+            .map(|entry| match &entry.source {
+                Source::Bytes(bytes) => Ok(bytes.len() as u64),
+                Source::File(path) => file_size(path).map_err(|e| e.to_string()),
+            })
+            .collect();
+        let original: Vec<u64> = sizes.iter().map(|s| *s.as_ref().unwrap_or(&0)).collect();
+
+        let created = chrono::Utc::now().to_rfc3339();
+        let index_for = |stored: &[u64]| {
+            let items: Vec<_> = self
+                .entries
+                .iter()
+                .zip(&sizes)
+                .zip(original.iter().zip(stored))
+                .map(|((entry, size), (&original, &kept))| {
+                    let mut item = serde_json::json!({
+                        "name": entry.name,
+                        "kind": entry.kind,
+                        "original_size": original,
+                        "stored_size": kept,
+                        "truncated": kept < original,
+                    });
+                    if let Source::File(path) = &entry.source {
+                        item["source"] = path.display().to_string().into();
+                    }
+                    if let Err(e) = size {
+                        item["error"] = e.clone().into();
+                    }
+                    item
+                })
+                .collect();
+            let index = serde_json::json!({
+                "created": created,
+                "harness_dir": self.root.display().to_string(),
+                "max_bytes": self.max_bytes,
+                "entries": items,
+            });
+            serde_json::to_vec_pretty(&index).map_err(|e| Error::io(dest, io::Error::from(e)))
+        };
+
+        // Storing everything whole gives the longest index: sizes only
+        // shrink and `true` is shorter than `false`
+        let stored_entries = sizes.iter().filter(|s| s.is_ok()).count() as u64;
+        let overhead = padded(index_for(&original)?.len() as u64)
+            + (stored_entries + 1) * BLOCK as u64
+            + stored_entries * (BLOCK as u64 - 1)
+            + 2 * BLOCK as u64;
+        let stored = allocate(&original, self.max_bytes.saturating_sub(overhead));
+        let index = index_for(&stored)?;
+
+        let file = fs::File::create(dest).at_path(dest)?;
+        let mut out = BufWriter::new(file);
+        write_entry(&mut out, "index.json", &mut &index[..], index.len() as u64).at_path(dest)?;
+        for ((entry, size), &kept) in self.entries.iter().zip(&sizes).zip(&stored) {
+            if size.is_err() {
+                continue;
+            }
+            match &entry.source {
+                Source::Bytes(bytes) => {
+                    write_entry(&mut out, &entry.name, &mut &bytes[..], kept).at_path(dest)?
+                }
+                Source::File(path) => match fs::File::open(path) {
+                    Ok(file) => write_entry(&mut out, &entry.name, &mut file.take(kept), kept),
+                    Err(_) => write_entry(&mut out, &entry.name, &mut io::empty(), kept),
+                }
+                .at_path(dest)?,
+            }
+        }
+        out.write_all(&[0u8; 2 * BLOCK]).at_path(dest)?;
+        out.flush().at_path(dest)?;
+        Ok(dest.to_path_buf())
+    }
+
+    /// Read back every entry of an archive written by
+    /// [`write_archive`](Self::write_archive), in order
+    ///
+    /// # Errors
+    /// [`Error::Corrupt`] if a header is not ustar or the archive is short.
+    pub fn read_archive(path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+        let data = fs::read(path).at_path(path)?;
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while offset + BLOCK <= data.len() {
+            let header = &data[offset..offset + BLOCK];
+            if header.iter().all(|&b| b == 0) {
+                return Ok(entries);
+            }
+            if &header[257..262] != b"ustar" {
+                return Err(Error::corrupt(path, CorruptionKind::BadMagic));
+            }
+            let field = |range: std::ops::Range<usize>| {
+                let raw = &header[range];
+                let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+                String::from_utf8_lossy(&raw[..end]).into_owned()
+            };
+            let size = u64::from_str_radix(field(124..136).trim(), 8).map_err(|e| {
+                Error::corrupt(
+                    path,
+                    CorruptionKind::Malformed {
+                        record: entries.len() as u64,
+                        reason: format!("bad size field: {}", e),
+                    },
+                )
+            })? as usize;
+            let prefix = field(345..500);
+            let name = match prefix.is_empty() {
+                true => field(0..100),
+                false => format!("{}/{}", prefix, field(0..100)),
+            };
+            let start = offset + BLOCK;
+            if start + size > data.len() {
+                return Err(Error::corrupt(
+                    path,
+                    CorruptionKind::Truncated {
+                        expected: (start + size) as u64,
+                        actual: data.len() as u64,
+                    },
+                ));
+            }
+            entries.push((name, data[start..start + size].to_vec()));
+            offset = start + size.div_ceil(BLOCK) * BLOCK;
+        }
+        Err(Error::corrupt(
+            path,
+            CorruptionKind::Truncated {
+                expected: (offset + 2 * BLOCK) as u64,
+                actual: data.len() as u64,
+            },
+        ))
+    }
+
+    fn push_json(
+        &mut self,
+        name: &str,
+        kind: &'static str,
+        value: &serde_json::Value,
+    ) -> &mut Self {
+        let bytes = serde_json::to_vec_pretty(value).unwrap_or_default();
+        self.push(name.to_string(), kind, Source::Bytes(bytes))
+    }
+
+    fn push(&mut self, name: String, kind: &'static str, source: Source) -> &mut Self {
+        let name = self.unique_name(name);
+        self.names.insert(name.clone());
+        self.entries.push(Entry { name, kind, source });
+        self
+    }
+
+    /// `name`, shortened to fit a ustar header and suffixed if already used
+    fn unique_name(&self, name: String) -> String {
+        let mut candidate = fit_name(&name, None);
+        let mut n = 1;
+        while self.names.contains(&candidate) || candidate == "index.json" {
+            candidate = fit_name(&name, Some(n));
+            n += 1;
+        }
+        candidate
+    }
+}
+
+/// Directory for failure output: `explicit`, else [`FAILURE_ARTIFACTS_ENV`],
+/// else the system temp directory
+pub(super) fn artifact_dir(explicit: Option<&Path>) -> PathBuf {
+    explicit
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::var_os(FAILURE_ARTIFACTS_ENV).map(PathBuf::from))
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// `<prefix>-<pid>-<unix millis>`, unique enough for failure output names
+pub(super) fn artifact_name(prefix: &str) -> String {
+    let stamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    format!("{}-{}-{}", prefix, std::process::id(), stamp)
+}
+
+/// Size of a regular file that can be opened for reading
+fn file_size(path: &Path) -> io::Result<u64> {
+    let meta = fs::File::open(path)?.metadata()?;
+    if !meta.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "not a regular file",
+        ));
+    }
+    Ok(meta.len())
+}
+
+/// `len` rounded up to whole tar blocks
+fn padded(len: u64) -> u64 {
+    len.div_ceil(BLOCK as u64) * BLOCK as u64
+}
+
+/// Write one tar entry of exactly `size` bytes from `data`
+///
+/// `data` running short is padded with zeros, so the header stays true.
+fn write_entry(
+    out: &mut impl Write,
+    name: &str,
+    data: &mut impl Read,
+    size: u64,
+) -> io::Result<()> {
+    out.write_all(&ustar_header(name, size))?;
+    let copied = io::copy(&mut data.take(size), out)?;
+    io::copy(&mut io::repeat(0).take(padded(size) - copied), out)?;
+    Ok(())
+}
+
+/// Bytes to keep per entry so the total stays within `max_bytes`
+///
+/// Entries are filled smallest first with an equal share of what is left,
+/// so small entries survive whole and only the largest are cut.
+fn allocate(sizes: &[u64], max_bytes: u64) -> Vec<u64> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| sizes[i]);
+    let mut kept = vec![0; sizes.len()];
+    let mut remaining = max_bytes;
+    for (done, &i) in order.iter().enumerate() {
+        let share = remaining / (order.len() - done) as u64;
+        kept[i] = sizes[i].min(share);
+        remaining -= kept[i];
+    }
+    kept
+}
+
+/// Split `name` into ustar `(prefix, name)` fields, if it fits
+fn split_name(name: &str) -> Option<(&str, &str)> {
+    if name.len() <= 100 {
+        return Some(("", name));
+    }
+    name.match_indices('/')
+        .map(|(i, _)| (&name[..i], &name[i + 1..]))
+        .find(|(prefix, rest)| prefix.len() <= 155 && rest.len() <= 100 && !rest.is_empty())
+}
+
+/// `name` with an optional `~n` suffix, shortened to fit a ustar header
+fn fit_name(name: &str, suffix: Option<usize>) -> String {
+    let with_suffix = |name: &str| match suffix {
+        Some(n) => format!("{}~{}", name, n),
+        None => name.to_string(),
+    };
+    let candidate = with_suffix(name);
+    if split_name(&candidate).is_some() {
+        return candidate;
+    }
+    // Keep the top-level directory and the tail of the path
+    let (top, rest) = name.split_once('/').unwrap_or(("files", name));
+    let mut tail = rest.replace('/', "_");
+    while tail.len() > 90 {
+        tail.remove(0);
+    }
+    with_suffix(&format!("{}/{}", top, tail))
+}
+
+fn ustar_header(name: &str, size: u64) -> [u8; BLOCK] {
+    let mut header = [0u8; BLOCK];
+    // Names are fitted when added; the fallback only guards the header size
+    let (prefix, name) = split_name(name).unwrap_or_else(|| {
+        let mut start = name.len().saturating_sub(100);
+        while !name.is_char_boundary(start) {
+            start += 1;
+        }
+        ("", &name[start..])
+    });
+    let mut put = |offset: usize, value: &[u8]| {
+        header[offset..offset + value.len()].copy_from_slice(value);
+    };
+    put(0, name.as_bytes());
+    put(100, b"0000644\0");
+    put(108, b"0000000\0");
+    put(116, b"0000000\0");
+    put(124, format!("{:011o}\0", size).as_bytes());
+    let mtime = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    put(136, format!("{:011o}\0", mtime).as_bytes());
+    put(148, b"        ");
+    put(156, b"0");
+    put(257, b"ustar\0");
+    put(263, b"00");
+    put(345, prefix.as_bytes());
+    let checksum: u32 = header.iter().map(|&b| b as u32).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trip_lists_every_entry() {
+        let harness = TestHarness::new();
+        let nested = ["nested"; 30].join("/");
+        fs::create_dir_all(harness.temp_dir().join("data")).unwrap();
+        fs::create_dir_all(harness.temp_dir().join(&nested)).unwrap();
+        let big = harness.create_file("data/big.bin", &vec![7u8; 40_000]);
+        let small = harness.create_file("data/small.txt", b"hello");
+        let deep = harness.create_file(&format!("{}/leaf.bin", nested), b"deep");
+
+        let mut report = IntegrityReport::new();
+        report.fail("extraction differs");
+        let mut metrics = TestMetrics::new("roundtrip");
+        metrics.time_operation(|| ());
+
+        let mut artifacts = FailureArtifacts::collect(&harness).with_max_bytes(20_000);
+        artifacts
+            .add_report("extraction", &report)
+            .add_metrics(&metrics)
+            .add_json("chaos", &serde_json::json!({ "seed": 7 }))
+            .add_json("chaos", &serde_json::json!({ "seed": 8 }))
+            .add_file(&big)
+            .add_file(&small)
+            .add_file(&deep)
+            .add_file(&harness.temp_dir().join("missing.bin"));
+        assert_eq!(artifacts.len(), 8);
+
+        let tar = artifacts
+            .write_archive(&harness.temp_dir().join("out/failure.tar"))
+            .unwrap();
+        let entries = FailureArtifacts::read_archive(&tar).unwrap();
+        assert_eq!(entries[0].0, "index.json");
+        let index: serde_json::Value = serde_json::from_slice(&entries[0].1).unwrap();
+        let listed = index["entries"].as_array().unwrap();
+        assert_eq!(listed.len(), 8);
+
+        // Every readable entry is in the archive under its indexed name
+        let stored: Vec<&str> = entries[1..].iter().map(|(n, _)| n.as_str()).collect();
+        for item in listed {
+            let name = item["name"].as_str().unwrap();
+            assert_eq!(
+                stored.contains(&name),
+                item.get("error").is_none(),
+                "{}",
+                name
+            );
+        }
+        assert!(stored.contains(&"reports/extraction.json"));
+        assert!(stored.contains(&"chaos.json~1"));
+        assert!(stored.contains(&"files/data/small.txt"));
+
+        // Only the large file was cut to fit the cap
+        let big_item = listed
+            .iter()
+            .find(|i| i["name"] == "files/data/big.bin")
+            .unwrap();
+        assert_eq!(big_item["truncated"], true);
+        assert_eq!(big_item["original_size"], 40_000);
+        assert!(fs::metadata(&tar).unwrap().len() <= 20_000);
+        let small_data = &entries
+            .iter()
+            .find(|(n, _)| n == "files/data/small.txt")
+            .unwrap()
+            .1;
+        assert_eq!(small_data, b"hello");
+    }
+
+    #[test]
+    fn test_header_fallback_keeps_utf8_intact() {
+        // 121 bytes with no '/', so the last 100 bytes start mid-character
+        let name = format!("{}a", "é".repeat(60));
+        let header = ustar_header(&name, 0);
+        let end = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
+        let stored = std::str::from_utf8(&header[..end]).unwrap();
+        assert_eq!(stored, format!("{}a", "é".repeat(49)));
+    }
+
+    #[test]
+    fn test_streamed_file_shrinking_after_sizing_is_padded() {
+        let mut out = Vec::new();
+        write_entry(&mut out, "short.bin", &mut &b"abc"[..], 5).unwrap();
+        assert_eq!(out.len(), 2 * BLOCK);
+        assert_eq!(&out[BLOCK..BLOCK + 5], b"abc\0\0");
+    }
+
+    #[test]
+    fn test_on_failure_hooks_run_on_panic() {
+        let out = tempfile::TempDir::new().unwrap();
+        let dir = out.path().to_path_buf();
+        let result = std::panic::catch_unwind(move || {
+            let harness = TestHarness::new().with_failure_artifact_dir(&dir);
+            let file = harness.create_file("input.bin", b"payload");
+            harness.on_failure(move |artifacts| {
+                artifacts.add_file(&file);
+            });
+            panic!("roundtrip mismatch");
+        });
+        assert!(result.is_err());
+
+        let archives: Vec<_> = fs::read_dir(out.path()).unwrap().collect();
+        assert_eq!(archives.len(), 1);
+        let entries =
+            FailureArtifacts::read_archive(&archives[0].as_ref().unwrap().path()).unwrap();
+        assert_eq!(
+            entries[1],
+            ("files/input.bin".to_string(), b"payload".to_vec())
+        );
+
+        // A panicking hook is recorded and the remaining hooks still run
+        let out = tempfile::TempDir::new().unwrap();
+        let dir = out.path().to_path_buf();
+        let result = std::panic::catch_unwind(move || {
+            let harness = TestHarness::new().with_failure_artifact_dir(&dir);
+            let file = harness.create_file("input.bin", b"payload");
+            harness.on_failure(|_| panic!("hook crashed"));
+            harness.on_failure(move |artifacts| {
+                artifacts.add_file(&file);
+            });
+            panic!("roundtrip mismatch");
+        });
+        assert!(result.is_err());
+        let archive = fs::read_dir(out.path()).unwrap().next().unwrap().unwrap();
+        let entries = FailureArtifacts::read_archive(&archive.path()).unwrap();
+        let names: Vec<&str> = entries.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["index.json", "hook_panic.json", "files/input.bin"]);
+        assert!(String::from_utf8_lossy(&entries[1].1).contains("hook crashed"));
+
+        // Hooks never run when the test passes
+        let quiet = tempfile::TempDir::new().unwrap();
+        let harness = TestHarness::new().with_failure_artifact_dir(quiet.path());
+        harness.on_failure(|_| panic!("hook should not run"));
+        drop(harness);
+        assert_eq!(fs::read_dir(quiet.path()).unwrap().count(), 0);
+    }
+}
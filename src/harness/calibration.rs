@@ -0,0 +1,278 @@
+//! Empirical calibration of the "is similar" cosine threshold
+//!
+//! [`calibrate_threshold`] samples the cosine of unrelated random pairs and
+//! of a vector against a perturbed copy of itself at several noise levels,
+//! then picks, per noise level, a threshold that separates the two within
+//! target false-positive and false-negative rates.
+
+use crate::generators::{for_dim, random_sparse_vec};
+use rand::Rng;
+
+/// Fractions of non-zeros replaced in the perturbed copies
+pub const CALIBRATION_NOISE_LEVELS: [f64; 4] = [0.05, 0.1, 0.2, 0.4];
+
+/// Target false-positive rate used by [`calibrate_threshold`]
+pub const DEFAULT_TARGET_FPR: f64 = 0.01;
+
+/// Target false-negative rate used by [`calibrate_threshold`]
+pub const DEFAULT_TARGET_FNR: f64 = 0.01;
+
+/// Suggested threshold for copies perturbed at one noise level
+///
+/// A pair counts as similar when its cosine is strictly above `threshold`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseLevelThreshold {
+    /// Fraction of non-zeros replaced in the copy
+    pub noise: f64,
+    /// Mean copy-pair cosine
+    pub mean_copy_cosine: f64,
+    /// Lowest copy-pair cosine
+    pub min_copy_cosine: f64,
+    /// Suggested cut-off
+    pub threshold: f64,
+    /// Whether `threshold` meets both target rates
+    ///
+    /// When no threshold does, `threshold` keeps the false-positive target
+    /// and `false_negative_rate` shows the cost.
+    pub feasible: bool,
+    /// Unrelated pairs above `threshold`
+    pub false_positive_rate: f64,
+    /// Copy pairs at or below `threshold`
+    pub false_negative_rate: f64,
+}
+
+/// Outcome of [`calibrate_threshold`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThresholdCalibration {
+    /// Vector dimensionality
+    pub dims: usize,
+    /// Non-zeros per vector
+    pub sparsity: usize,
+    /// Pairs sampled per distribution
+    pub trials: usize,
+    /// Target false-positive rate of `levels`
+    pub target_fpr: f64,
+    /// Target false-negative rate of `levels`
+    pub target_fnr: f64,
+    /// Cosines of unrelated pairs, sorted ascending
+    pub unrelated: Vec<f64>,
+    /// Per noise level, the copy-pair cosines sorted ascending
+    pub copies: Vec<(f64, Vec<f64>)>,
+    /// Suggested thresholds, one per noise level
+    pub levels: Vec<NoiseLevelThreshold>,
+}
+
+impl ThresholdCalibration {
+    /// Mean unrelated-pair cosine
+    pub fn mean_unrelated_cosine(&self) -> f64 {
+        mean(&self.unrelated)
+    }
+
+    /// Recompute `levels` for other target rates from the raw distributions
+    pub fn suggest(&self, target_fpr: f64, target_fnr: f64) -> Vec<NoiseLevelThreshold> {
+        self.copies
+            .iter()
+            .map(|(noise, copies)| {
+                suggest_threshold(*noise, &self.unrelated, copies, target_fpr, target_fnr)
+            })
+            .collect()
+    }
+
+    /// CSV of the raw distributions: `kind,noise,cosine`, one row per pair
+    ///
+    /// `noise` is empty for unrelated pairs.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("kind,noise,cosine\n");
+        for cosine in &self.unrelated {
+            csv.push_str(&format!("unrelated,,{:.6}\n", cosine));
+        }
+        for (noise, copies) in &self.copies {
+            for cosine in copies {
+                csv.push_str(&format!("copy,{},{:.6}\n", noise, cosine));
+            }
+        }
+        csv
+    }
+
+    /// Human-readable summary
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "Threshold calibration: dims={}, sparsity={}, trials={}, target FPR={}, FNR={}\n\
+             - Unrelated: mean {:.4}, max {:.4}",
+            self.dims,
+            self.sparsity,
+            self.trials,
+            self.target_fpr,
+            self.target_fnr,
+            self.mean_unrelated_cosine(),
+            self.unrelated.last().copied().unwrap_or(0.0),
+        );
+        for level in &self.levels {
+            out.push_str(&format!(
+                "\n- Noise {:.0}%: copy mean {:.4}, threshold {:.4}{} (FPR {:.3}, FNR {:.3})",
+                level.noise * 100.0,
+                level.mean_copy_cosine,
+                level.threshold,
+                if level.feasible {
+                    ""
+                } else {
+                    " (targets unmet)"
+                },
+                level.false_positive_rate,
+                level.false_negative_rate,
+            ));
+        }
+        out
+    }
+}
+
+/// Measure unrelated and perturbed-copy cosine distributions
+///
+/// Samples `trials` unrelated pairs and, for each of
+/// [`CALIBRATION_NOISE_LEVELS`], `trials` vectors paired with a copy that
+/// keeps `1 - noise` of their non-zeros (see
+/// [`DimGenerators::correlated`](crate::generators::DimGenerators::correlated)).
+/// Suggested thresholds target [`DEFAULT_TARGET_FPR`] and
+/// [`DEFAULT_TARGET_FNR`]; use [`ThresholdCalibration::suggest`] for others.
+///
+/// # Example
+/// ```rust,ignore
+/// let mut rng = StdRng::seed_from_u64(7);
+/// let calibration = calibrate_threshold(&mut rng, 10_000, 200, 500);
+/// println!("{}", calibration.summary());
+/// std::fs::write("cosines.csv", calibration.to_csv())?;
+/// ```
+///
+/// # Panics
+/// Panics if `dims` cannot hold a vector and its perturbed copy, i.e. if
+/// `sparsity * (1 + max noise)` exceeds `dims`.
+pub fn calibrate_threshold(
+    rng: &mut impl Rng,
+    dims: usize,
+    sparsity: usize,
+    trials: usize,
+) -> ThresholdCalibration {
+    let trials = trials.max(1);
+    let generators = for_dim(dims);
+
+    let mut unrelated: Vec<f64> = (0..trials)
+        .map(|_| {
+            let a = random_sparse_vec(rng, dims, sparsity);
+            let b = random_sparse_vec(rng, dims, sparsity);
+            a.cosine(&b)
+        })
+        .collect();
+    unrelated.sort_by(f64::total_cmp);
+
+    let copies: Vec<(f64, Vec<f64>)> = CALIBRATION_NOISE_LEVELS
+        .iter()
+        .map(|&noise| {
+            let mut cosines: Vec<f64> = (0..trials)
+                .map(|_| {
+                    let base = random_sparse_vec(rng, dims, sparsity);
+                    let copy = generators
+                        .correlated(rng, &base, 1.0 - noise)
+                        .unwrap_or_else(|e| panic!("invalid calibration parameters: {}", e));
+                    base.cosine(&copy)
+                })
+                .collect();
+            cosines.sort_by(f64::total_cmp);
+            (noise, cosines)
+        })
+        .collect();
+
+    let mut calibration = ThresholdCalibration {
+        dims,
+        sparsity,
+        trials,
+        target_fpr: DEFAULT_TARGET_FPR,
+        target_fnr: DEFAULT_TARGET_FNR,
+        unrelated,
+        copies,
+        levels: Vec::new(),
+    };
+    calibration.levels = calibration.suggest(DEFAULT_TARGET_FPR, DEFAULT_TARGET_FNR);
+    calibration
+}
+
+/// Threshold between `unrelated` and `copies` (both sorted ascending)
+///
+/// The lowest cut-off meeting the false-positive target is the unrelated
+/// cosine with at most `target_fpr` of the pairs above it; the false-negative
+/// target holds below the copy cosine with at most `target_fnr` of the pairs
+/// at or under it. The suggestion is the midpoint of that gap.
+fn suggest_threshold(
+    noise: f64,
+    unrelated: &[f64],
+    copies: &[f64],
+    target_fpr: f64,
+    target_fnr: f64,
+) -> NoiseLevelThreshold {
+    let allowed_fp = (target_fpr * unrelated.len() as f64).floor() as usize;
+    let allowed_fn = (target_fnr * copies.len() as f64).floor() as usize;
+    let fp_floor = unrelated[unrelated.len() - 1 - allowed_fp.min(unrelated.len() - 1)];
+    let fn_ceiling = copies[allowed_fn.min(copies.len() - 1)];
+
+    let feasible = fp_floor < fn_ceiling;
+    let threshold = if feasible {
+        (fp_floor + fn_ceiling) / 2.0
+    } else {
+        fp_floor
+    };
+    let rate = |values: &[f64], hit: &dyn Fn(f64) -> bool| {
+        values.iter().filter(|&&c| hit(c)).count() as f64 / values.len() as f64
+    };
+    NoiseLevelThreshold {
+        noise,
+        mean_copy_cosine: mean(copies),
+        min_copy_cosine: copies[0],
+        threshold,
+        feasible,
+        false_positive_rate: rate(unrelated, &|c| c > threshold),
+        false_negative_rate: rate(copies, &|c| c <= threshold),
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::report::RunReport;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_tiny_calibration_separates_distributions() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let calibration = calibrate_threshold(&mut rng, 2_000, 40, 60);
+
+        assert_eq!(calibration.unrelated.len(), 60);
+        assert!(calibration.mean_unrelated_cosine().abs() < 0.05);
+        let (noise, copies) = &calibration.copies[0];
+        assert_eq!(*noise, 0.05);
+        assert!(mean(copies) > 0.9, "{}", mean(copies));
+
+        let max_unrelated = *calibration.unrelated.last().unwrap();
+        assert_eq!(calibration.levels.len(), CALIBRATION_NOISE_LEVELS.len());
+        for level in &calibration.levels {
+            assert!(level.feasible, "{:?}", level);
+            assert!(level.threshold >= max_unrelated, "{:?}", level);
+            assert!(level.threshold < level.min_copy_cosine, "{:?}", level);
+            assert_eq!(level.false_positive_rate, 0.0);
+            assert_eq!(level.false_negative_rate, 0.0);
+        }
+
+        let csv = calibration.to_csv();
+        assert_eq!(csv.lines().count(), 1 + 60 * 5);
+        assert!(csv.contains("\ncopy,0.4,"));
+        assert!(calibration.summary().contains("- Noise 40%"));
+
+        let md = RunReport::new("calibration")
+            .with_calibration(calibration)
+            .to_markdown();
+        assert!(md.contains("## Threshold calibration"), "{}", md);
+    }
+}
@@ -0,0 +1,383 @@
+//! Chunked encode/decode roundtrips without a filesystem pipeline
+//!
+//! [`ChunkSimulator`] splits a buffer the way hierarchical ingestion does,
+//! encodes every chunk on its own, optionally damages the encoded stream
+//! with [`ChunkFault`]s, then decodes and reassembles by offset. Reassembly
+//! reports uncovered ranges and overlapping chunks instead of concatenating
+//! whatever came back.
+
+use embeddenator_vsa::{ReversibleVSAConfig, SparseVec};
+use std::io::{self, Read};
+use std::ops::Range;
+
+/// One chunk of a source buffer, before encoding or after decoding
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// Position in the chunk sequence
+    pub index: usize,
+    /// Byte offset in the source
+    pub offset: u64,
+    /// Number of bytes
+    pub len: usize,
+    /// Chunk contents
+    pub data: Vec<u8>,
+}
+
+/// A chunk after [`ChunkSimulator::encode`]
+#[derive(Clone, Debug)]
+pub struct EncodedChunk {
+    /// Position in the chunk sequence
+    pub index: usize,
+    /// Byte offset in the source
+    pub offset: u64,
+    /// Number of bytes to decode
+    pub len: usize,
+    /// Encoded contents
+    pub vector: SparseVec,
+}
+
+/// Damage applied to the encoded stream before decoding
+#[derive(Clone, Copy, Debug)]
+pub enum ChunkFault {
+    /// Remove chunk `index` from the stream
+    Drop(usize),
+    /// Exchange the vectors of chunks `a` and `b`, keeping their offsets,
+    /// as when a pipeline delivers chunks out of order
+    Swap(usize, usize),
+    /// Modify the vector of chunk `index`
+    Corrupt {
+        /// Chunk to modify
+        index: usize,
+        /// Applied to the chunk's vector in place
+        with: fn(&mut SparseVec),
+    },
+}
+
+/// Two chunks covering the same bytes, from [`Reassembly::overlaps`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkOverlap {
+    /// Bytes covered by both chunks
+    pub range: Range<u64>,
+    /// Chunk already covering the range
+    pub first: usize,
+    /// Chunk that overlapped it
+    pub second: usize,
+    /// Whether the two chunks disagree on any byte in the range
+    pub conflicting: bool,
+}
+
+/// Outcome of [`ChunkSimulator::reassemble`]
+#[derive(Clone, Debug, Default)]
+pub struct Reassembly {
+    /// Reassembled bytes; gaps are left zeroed
+    pub data: Vec<u8>,
+    /// Byte ranges no chunk covered
+    pub gaps: Vec<Range<u64>>,
+    /// Ranges covered by more than one chunk; the first chunk's bytes are kept
+    pub overlaps: Vec<ChunkOverlap>,
+}
+
+impl Reassembly {
+    /// No gaps and no overlaps that disagree
+    pub fn is_clean(&self) -> bool {
+        self.gaps.is_empty() && !self.overlaps.iter().any(|o| o.conflicting)
+    }
+
+    /// Bytes not covered by any chunk
+    pub fn gap_bytes(&self) -> u64 {
+        self.gaps.iter().map(|g| g.end - g.start).sum()
+    }
+}
+
+/// Splits, encodes, decodes and reassembles data chunk by chunk
+///
+/// Consecutive chunks start `chunk_size - overlap` bytes apart, so with a
+/// non-zero overlap each chunk repeats the tail of the previous one.
+///
+/// # Example
+/// ```rust,ignore
+/// let sim = ChunkSimulator::new(4096)
+///     .with_overlap(64)
+///     .with_fault(ChunkFault::Drop(3));
+/// let out = sim.roundtrip(&data, &ReversibleVSAConfig::default());
+/// assert_eq!(out.gaps, vec![12160..16128]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChunkSimulator {
+    chunk_size: usize,
+    overlap: usize,
+    faults: Vec<ChunkFault>,
+}
+
+impl ChunkSimulator {
+    /// Simulator producing chunks of `chunk_size` bytes without overlap
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero.
+    pub fn new(chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk_size must be positive");
+        Self {
+            chunk_size,
+            overlap: 0,
+            faults: Vec::new(),
+        }
+    }
+
+    /// Repeat the last `overlap` bytes of each chunk at the start of the next
+    ///
+    /// # Panics
+    /// Panics if `overlap` is not smaller than the chunk size.
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        assert!(
+            overlap < self.chunk_size,
+            "overlap {} must be smaller than chunk_size {}",
+            overlap,
+            self.chunk_size
+        );
+        self.overlap = overlap;
+        self
+    }
+
+    /// Damage the encoded stream before decoding; faults apply in order
+    pub fn with_fault(mut self, fault: ChunkFault) -> Self {
+        self.faults.push(fault);
+        self
+    }
+
+    /// Bytes per chunk
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Bytes shared by consecutive chunks
+    pub fn overlap(&self) -> usize {
+        self.overlap
+    }
+
+    /// Split `data` into chunks
+    pub fn split(&self, data: &[u8]) -> Vec<ChunkInfo> {
+        self.split_reader(data)
+            .expect("reading from a slice cannot fail")
+    }
+
+    /// Split the contents of `reader` into chunks
+    ///
+    /// Only the current chunk is buffered.
+    pub fn split_reader(&self, mut reader: impl Read) -> io::Result<Vec<ChunkInfo>> {
+        let step = self.chunk_size - self.overlap;
+        let mut chunks = Vec::new();
+        let mut offset = 0u64;
+        let mut carry = Vec::new();
+
+        loop {
+            let carried = carry.len();
+            let mut data = carry;
+            (&mut reader)
+                .take((self.chunk_size - carried) as u64)
+                .read_to_end(&mut data)?;
+            if data.len() == carried {
+                break;
+            }
+            let full = data.len() == self.chunk_size;
+            carry = if full {
+                data[step..].to_vec()
+            } else {
+                Vec::new()
+            };
+            chunks.push(ChunkInfo {
+                index: chunks.len(),
+                offset,
+                len: data.len(),
+                data,
+            });
+            if !full {
+                break;
+            }
+            offset += step as u64;
+        }
+        Ok(chunks)
+    }
+
+    /// Encode each chunk with [`SparseVec::encode_data`]
+    pub fn encode(&self, chunks: &[ChunkInfo], config: &ReversibleVSAConfig) -> Vec<EncodedChunk> {
+        chunks
+            .iter()
+            .map(|chunk| EncodedChunk {
+                index: chunk.index,
+                offset: chunk.offset,
+                len: chunk.len,
+                vector: SparseVec::encode_data(&chunk.data, config, None),
+            })
+            .collect()
+    }
+
+    /// Apply the configured faults to an encoded stream
+    ///
+    /// Faults naming a chunk index that is not in the stream are ignored.
+    pub fn apply_faults(&self, encoded: &mut Vec<EncodedChunk>) {
+        let position =
+            |encoded: &[EncodedChunk], index| encoded.iter().position(|c| c.index == index);
+        for fault in &self.faults {
+            match *fault {
+                ChunkFault::Drop(index) => encoded.retain(|c| c.index != index),
+                ChunkFault::Swap(a, b) => {
+                    if let (Some(a), Some(b)) = (position(encoded, a), position(encoded, b)) {
+                        let vector = encoded[a].vector.clone();
+                        encoded[a].vector = std::mem::replace(&mut encoded[b].vector, vector);
+                    }
+                }
+                ChunkFault::Corrupt { index, with } => {
+                    if let Some(i) = position(encoded, index) {
+                        with(&mut encoded[i].vector);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Decode each chunk back to bytes
+    pub fn decode(&self, encoded: &[EncodedChunk], config: &ReversibleVSAConfig) -> Vec<ChunkInfo> {
+        encoded
+            .iter()
+            .map(|chunk| ChunkInfo {
+                index: chunk.index,
+                offset: chunk.offset,
+                len: chunk.len,
+                data: chunk.vector.decode_data(config, None, chunk.len),
+            })
+            .collect()
+    }
+
+    /// Place decoded chunks at their offsets in a `total_len` byte buffer
+    ///
+    /// Chunks are placed in offset order. Bytes outside `0..total_len` are
+    /// dropped.
+    pub fn reassemble(&self, chunks: &[ChunkInfo], total_len: u64) -> Reassembly {
+        let mut sorted: Vec<&ChunkInfo> = chunks.iter().collect();
+        sorted.sort_by_key(|c| (c.offset, c.index));
+
+        let mut out = Reassembly {
+            data: vec![0; total_len as usize],
+            ..Reassembly::default()
+        };
+        let mut covered = 0u64;
+        let mut last = None;
+
+        for chunk in sorted {
+            let start = chunk.offset.min(total_len);
+            let end = (chunk.offset + chunk.data.len() as u64).min(total_len);
+            if start > covered {
+                out.gaps.push(covered..start);
+            }
+            if let (true, Some(first)) = (start < covered, last) {
+                let shared = start..end.min(covered);
+                let ours = &chunk.data[..(shared.end - shared.start) as usize];
+                out.overlaps.push(ChunkOverlap {
+                    conflicting: out.data[shared.start as usize..shared.end as usize] != *ours,
+                    range: shared,
+                    first,
+                    second: chunk.index,
+                });
+            }
+            if end > covered {
+                let from = start.max(covered);
+                out.data[from as usize..end as usize]
+                    .copy_from_slice(&chunk.data[(from - start) as usize..(end - start) as usize]);
+                covered = end;
+                last = Some(chunk.index);
+            }
+        }
+        if covered < total_len {
+            out.gaps.push(covered..total_len);
+        }
+        out
+    }
+
+    /// Split, encode, apply faults, decode and reassemble `data`
+    pub fn roundtrip(&self, data: &[u8], config: &ReversibleVSAConfig) -> Reassembly {
+        let chunks = self.split(data);
+        let mut encoded = self.encode(&chunks, config);
+        self.apply_faults(&mut encoded);
+        let decoded = self.decode(&encoded, config);
+        self.reassemble(&decoded, data.len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{create_test_data, TestDataPattern};
+
+    #[test]
+    fn test_roundtrip_is_bit_exact() {
+        let data = create_test_data(1, TestDataPattern::Sequential);
+        let config = ReversibleVSAConfig::default();
+        for (chunk_size, overlap) in [(4096, 0), (4096, 100), (65_536, 0), (333_333, 1000)] {
+            let sim = ChunkSimulator::new(chunk_size).with_overlap(overlap);
+            let chunks = sim.split(&data);
+            assert_eq!(
+                chunks.last().unwrap().offset as usize + chunks.last().unwrap().len,
+                data.len()
+            );
+            assert_eq!(sim.split_reader(&data[..]).unwrap(), chunks);
+
+            let out = sim.roundtrip(&data, &config);
+            assert!(out.is_clean(), "{:?} {:?}", out.gaps, out.overlaps);
+            assert_eq!(
+                out.overlaps.len(),
+                if overlap > 0 { chunks.len() - 1 } else { 0 }
+            );
+            assert!(
+                out.data == data,
+                "chunk_size {} overlap {}",
+                chunk_size,
+                overlap
+            );
+        }
+    }
+
+    #[test]
+    fn test_faults_are_reported() {
+        let data = create_test_data(1, TestDataPattern::Sequential);
+        let config = ReversibleVSAConfig::default();
+
+        let dropped = ChunkSimulator::new(4096)
+            .with_fault(ChunkFault::Drop(5))
+            .roundtrip(&data, &config);
+        assert_eq!(dropped.gaps, vec![5 * 4096..6 * 4096]);
+        assert_eq!(dropped.gap_bytes(), 4096);
+        assert!(!dropped.is_clean());
+        assert!(dropped.data[5 * 4096..6 * 4096].iter().all(|&b| b == 0));
+        assert!(dropped.data[..5 * 4096] == data[..5 * 4096]);
+
+        let last = ChunkSimulator::new(4096).split(&data).len() - 1;
+        let tail = ChunkSimulator::new(4096)
+            .with_fault(ChunkFault::Drop(last))
+            .roundtrip(&data, &config);
+        assert_eq!(tail.gaps, vec![last as u64 * 4096..data.len() as u64]);
+
+        // With overlap the swapped chunks disagree with their neighbours
+        let swapped = ChunkSimulator::new(4096)
+            .with_overlap(16)
+            .with_fault(ChunkFault::Swap(1, 7))
+            .roundtrip(&data, &config);
+        assert!(swapped.gaps.is_empty());
+        let conflicts: Vec<(usize, usize)> = swapped
+            .overlaps
+            .iter()
+            .filter(|o| o.conflicting)
+            .map(|o| (o.first, o.second))
+            .collect();
+        assert_eq!(conflicts, vec![(0, 1), (1, 2), (6, 7), (7, 8)]);
+
+        let corrupted = ChunkSimulator::new(4096)
+            .with_fault(ChunkFault::Corrupt {
+                index: 2,
+                with: |v| *v = SparseVec::default(),
+            })
+            .roundtrip(&data, &config);
+        assert!(corrupted.is_clean());
+        assert!(corrupted.data[2 * 4096..3 * 4096] != data[2 * 4096..3 * 4096]);
+        assert!(corrupted.data[3 * 4096..] == data[3 * 4096..]);
+    }
+}
@@ -0,0 +1,401 @@
+//! Scripted filesystem changes for watch-based ingestion tests
+//!
+//! An [`FsEventScript`] is a list of filesystem operations, each run a
+//! fixed delay after the previous one, so a watcher sees the same bursts,
+//! gaps and rename pairs on every run. Scripts are plain data: a sequence
+//! that exposed a flaky watcher can be saved as JSON and replayed verbatim.
+
+use crate::error::{Error, IoResultExt, Result};
+use crate::fixtures::permissions_for_mode;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// One filesystem operation; paths are relative to the script's directory
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FsEvent {
+    /// Create (or truncate) a file holding `bytes`
+    CreateFile { path: PathBuf, bytes: Vec<u8> },
+    /// Append `bytes` to an existing file
+    AppendBytes { path: PathBuf, bytes: Vec<u8> },
+    /// Rename a file or directory
+    Rename { from: PathBuf, to: PathBuf },
+    /// Remove a file, or a directory and everything under it
+    Delete { path: PathBuf },
+    /// Create a directory and any missing parents
+    Mkdir { path: PathBuf },
+    /// Set permission bits (only the write bits off Unix)
+    Chmod { path: PathBuf, mode: u32 },
+}
+
+impl FsEvent {
+    /// Short name of the operation, as used in the JSON `op` tag
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FsEvent::CreateFile { .. } => "create_file",
+            FsEvent::AppendBytes { .. } => "append_bytes",
+            FsEvent::Rename { .. } => "rename",
+            FsEvent::Delete { .. } => "delete",
+            FsEvent::Mkdir { .. } => "mkdir",
+            FsEvent::Chmod { .. } => "chmod",
+        }
+    }
+
+    fn paths(&self) -> Vec<&Path> {
+        match self {
+            FsEvent::Rename { from, to } => vec![from, to],
+            FsEvent::CreateFile { path, .. }
+            | FsEvent::AppendBytes { path, .. }
+            | FsEvent::Delete { path }
+            | FsEvent::Mkdir { path }
+            | FsEvent::Chmod { path, .. } => vec![path],
+        }
+    }
+
+    fn apply(&self, dir: &Path) -> Result<()> {
+        match self {
+            FsEvent::CreateFile { path, bytes } => {
+                let path = dir.join(path);
+                fs::write(&path, bytes).map_err(|e| Error::write(&path, bytes.len() as u64, e))
+            }
+            FsEvent::AppendBytes { path, bytes } => {
+                let path = dir.join(path);
+                OpenOptions::new()
+                    .append(true)
+                    .open(&path)
+                    .and_then(|mut f| f.write_all(bytes))
+                    .map_err(|e| Error::write(&path, bytes.len() as u64, e))
+            }
+            FsEvent::Rename { from, to } => {
+                let from = dir.join(from);
+                fs::rename(&from, dir.join(to)).at_path(&from)
+            }
+            FsEvent::Delete { path } => {
+                let path = dir.join(path);
+                let metadata = fs::symlink_metadata(&path).at_path(&path)?;
+                if metadata.is_dir() {
+                    fs::remove_dir_all(&path).at_path(&path)
+                } else {
+                    fs::remove_file(&path).at_path(&path)
+                }
+            }
+            FsEvent::Mkdir { path } => {
+                let path = dir.join(path);
+                fs::create_dir_all(&path).at_path(&path)
+            }
+            FsEvent::Chmod { path, mode } => {
+                let path = dir.join(path);
+                fs::set_permissions(&path, permissions_for_mode(&path, *mode)?).at_path(&path)
+            }
+        }
+    }
+}
+
+/// An [`FsEvent`] and the pause before it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsStep {
+    /// Milliseconds to wait after the previous step (or the start)
+    pub delay_ms: u64,
+    /// Operation to perform
+    #[serde(flatten)]
+    pub event: FsEvent,
+}
+
+impl FsStep {
+    /// Pause before the step
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay_ms)
+    }
+}
+
+/// A timed sequence of filesystem changes
+///
+/// # Example
+/// ```rust,ignore
+/// let script = FsEventScript::new()
+///     .create_file(Duration::ZERO, "a.txt", b"hello")
+///     .append_bytes(Duration::from_millis(5), "a.txt", b" world")
+///     .rename(Duration::from_millis(50), "a.txt", "b.txt");
+/// let watcher = start_watcher(dir.path());
+/// let log = script.execute(dir.path());
+/// assert_eq!(watcher.drain().len(), log.entries.len());
+/// script.save(Path::new("burst.fs-script.json"))?;
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsEventScript {
+    steps: Vec<FsStep>,
+    #[serde(skip)]
+    fast: bool,
+}
+
+/// One executed step of an [`FsEventScript`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutedEvent {
+    /// Position of the step in the script
+    pub index: usize,
+    /// The operation performed
+    pub event: FsEvent,
+    /// Offset from the start the step was due at (zero in fast mode)
+    pub scheduled: Duration,
+    /// Offset from the start the step actually ran at
+    pub elapsed: Duration,
+    /// Wall-clock time just before the operation
+    pub at: SystemTime,
+}
+
+/// What [`FsEventScript::execute`] did, in execution order
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionLog {
+    /// Wall-clock time execution started
+    pub started: SystemTime,
+    /// One entry per step, in script order
+    pub entries: Vec<ExecutedEvent>,
+}
+
+impl ExecutionLog {
+    /// Largest amount by which a step ran later than scheduled
+    pub fn max_lag(&self) -> Duration {
+        self.entries
+            .iter()
+            .map(|e| e.elapsed.saturating_sub(e.scheduled))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl FsEventScript {
+    /// Create an empty script
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Steps in order
+    pub fn steps(&self) -> &[FsStep] {
+        &self.steps
+    }
+
+    /// Total of all delays
+    pub fn duration(&self) -> Duration {
+        self.steps.iter().map(FsStep::delay).sum()
+    }
+
+    /// Collapse every delay to zero when executing (for unit tests)
+    pub fn with_fast_mode(mut self, fast: bool) -> Self {
+        self.fast = fast;
+        self
+    }
+
+    /// Append `event` to run `delay` after the previous step
+    pub fn push(mut self, delay: Duration, event: FsEvent) -> Self {
+        self.steps.push(FsStep {
+            delay_ms: delay.as_millis() as u64,
+            event,
+        });
+        self
+    }
+
+    /// Append a [`FsEvent::CreateFile`]
+    pub fn create_file(self, delay: Duration, path: impl Into<PathBuf>, bytes: &[u8]) -> Self {
+        let (path, bytes) = (path.into(), bytes.to_vec());
+        self.push(delay, FsEvent::CreateFile { path, bytes })
+    }
+
+    /// Append a [`FsEvent::AppendBytes`]
+    pub fn append_bytes(self, delay: Duration, path: impl Into<PathBuf>, bytes: &[u8]) -> Self {
+        let (path, bytes) = (path.into(), bytes.to_vec());
+        self.push(delay, FsEvent::AppendBytes { path, bytes })
+    }
+
+    /// Append a [`FsEvent::Rename`]
+    pub fn rename(self, delay: Duration, from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        let (from, to) = (from.into(), to.into());
+        self.push(delay, FsEvent::Rename { from, to })
+    }
+
+    /// Append a [`FsEvent::Delete`]
+    pub fn delete(self, delay: Duration, path: impl Into<PathBuf>) -> Self {
+        self.push(delay, FsEvent::Delete { path: path.into() })
+    }
+
+    /// Append a [`FsEvent::Mkdir`]
+    pub fn mkdir(self, delay: Duration, path: impl Into<PathBuf>) -> Self {
+        self.push(delay, FsEvent::Mkdir { path: path.into() })
+    }
+
+    /// Append a [`FsEvent::Chmod`]
+    pub fn chmod(self, delay: Duration, path: impl Into<PathBuf>, mode: u32) -> Self {
+        self.push(
+            delay,
+            FsEvent::Chmod {
+                path: path.into(),
+                mode,
+            },
+        )
+    }
+
+    /// Perform the steps under `dir`, sleeping out each delay
+    ///
+    /// Delays are measured from the start of execution, so a slow operation
+    /// shortens the pause after it rather than shifting every later step.
+    ///
+    /// # Panics
+    /// Panics if a step escapes `dir` or its operation fails.
+    pub fn execute(&self, dir: &Path) -> ExecutionLog {
+        self.try_execute(dir)
+            .unwrap_or_else(|e| panic!("Failed to execute fs event script: {}", e))
+    }
+
+    /// Fallible variant of [`execute`](Self::execute)
+    ///
+    /// Nothing runs if any step has an absolute path, one with `..`, or
+    /// one naming the script directory itself (such as `""` or `.`).
+    pub fn try_execute(&self, dir: &Path) -> Result<ExecutionLog> {
+        for step in &self.steps {
+            for path in step.event.paths() {
+                let inside = path
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+                let names_entry = path.components().any(|c| matches!(c, Component::Normal(_)));
+                if !inside || !names_entry {
+                    return Err(Error::invalid_spec(
+                        path,
+                        format!(
+                            "{} must stay inside the script directory",
+                            step.event.kind()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let started = SystemTime::now();
+        let start = Instant::now();
+        let mut scheduled = Duration::ZERO;
+        let mut entries = Vec::with_capacity(self.steps.len());
+        for (index, step) in self.steps.iter().enumerate() {
+            if !self.fast {
+                scheduled += step.delay();
+                std::thread::sleep(scheduled.saturating_sub(start.elapsed()));
+            }
+            let at = SystemTime::now();
+            let elapsed = start.elapsed();
+            step.event.apply(dir)?;
+            entries.push(ExecutedEvent {
+                index,
+                event: step.event.clone(),
+                scheduled,
+                elapsed,
+                at,
+            });
+        }
+        Ok(ExecutionLog { started, entries })
+    }
+
+    /// Save script as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+    }
+
+    /// Load script from JSON
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::metadata_mode;
+    use tempfile::TempDir;
+
+    fn six_steps(step: Duration) -> FsEventScript {
+        FsEventScript::new()
+            .create_file(Duration::ZERO, "a.txt", b"hello")
+            .mkdir(step, "sub/empty")
+            .append_bytes(step, "a.txt", b" world")
+            .rename(step, "a.txt", "sub/a.txt")
+            .chmod(step, "sub/a.txt", 0o600)
+            .delete(step, "sub/empty")
+    }
+
+    #[test]
+    fn test_script_builds_expected_tree_in_order() {
+        let step = Duration::from_millis(5);
+        let script = six_steps(step);
+        assert_eq!(script.duration(), step * 5);
+
+        for fast in [true, false] {
+            let dir = TempDir::new().unwrap();
+            let log = script.clone().with_fast_mode(fast).execute(dir.path());
+
+            assert_eq!(
+                fs::read(dir.path().join("sub/a.txt")).unwrap(),
+                b"hello world"
+            );
+            assert!(!dir.path().join("a.txt").exists());
+            assert!(!dir.path().join("sub/empty").exists());
+            #[cfg(unix)]
+            {
+                let metadata = fs::metadata(dir.path().join("sub/a.txt")).unwrap();
+                assert_eq!(metadata_mode(&metadata), 0o600);
+            }
+
+            let kinds: Vec<&str> = log.entries.iter().map(|e| e.event.kind()).collect();
+            assert_eq!(
+                kinds,
+                [
+                    "create_file",
+                    "mkdir",
+                    "append_bytes",
+                    "rename",
+                    "chmod",
+                    "delete"
+                ]
+            );
+            for (i, (entry, step)) in log.entries.iter().zip(script.steps()).enumerate() {
+                assert_eq!(entry.index, i);
+                assert_eq!(entry.event, step.event);
+            }
+            // Wall-clock `at` can step backwards; only `elapsed` is monotonic
+            assert!(log.entries.windows(2).all(|w| w[0].elapsed <= w[1].elapsed));
+
+            let last = log.entries.last().unwrap();
+            if fast {
+                assert_eq!(last.scheduled, Duration::ZERO);
+            } else {
+                assert_eq!(last.scheduled, step * 5);
+                assert!(last.elapsed >= step * 5, "{:?}", last.elapsed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_script_json_roundtrip_and_path_escape() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("burst.fs-script.json");
+        let script = six_steps(Duration::from_millis(20));
+        script.save(&path).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(json.contains(r#""op": "append_bytes""#), "{}", json);
+        assert_eq!(FsEventScript::load(&path).unwrap(), script);
+
+        let escape = FsEventScript::new()
+            .mkdir(Duration::ZERO, "ok")
+            .delete(Duration::ZERO, "../outside");
+        let err = escape.try_execute(dir.path()).unwrap_err();
+        assert!(matches!(err, Error::InvalidSpec { .. }));
+        assert!(!dir.path().join("ok").exists());
+
+        for root in ["", ".", "./."] {
+            let wipe = FsEventScript::new().delete(Duration::ZERO, root);
+            let err = wipe.try_execute(dir.path()).unwrap_err();
+            assert!(matches!(err, Error::InvalidSpec { .. }), "{:?}", root);
+        }
+        assert!(path.exists());
+    }
+}
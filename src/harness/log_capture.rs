@@ -0,0 +1,337 @@
+//! Capture of `log` facade output for assertions in tests
+//!
+//! The `log` crate allows one logger per process, so the first
+//! [`LogCapture::install`] installs a forwarding logger for good and every
+//! capture after that registers with it. Records go to the most recently
+//! installed capture still alive; dropping a capture hands logging back to
+//! the one installed before it, in whatever order captures are dropped.
+//! Captures are process-wide: tests using them concurrently will see each
+//! other's records, so run such tests serially.
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A registered capture and the max level to restore once it is gone
+struct ActiveCapture {
+    state: Arc<CaptureState>,
+    previous_level: LevelFilter,
+}
+
+/// Captures currently registered, most recent last
+static ACTIVE: Mutex<Vec<ActiveCapture>> = Mutex::new(Vec::new());
+
+/// Whether [`FORWARDER`] became the process logger, decided on first install
+static FORWARDING: OnceLock<bool> = OnceLock::new();
+
+/// Process-wide logger routing records to the newest active capture
+static FORWARDER: Forwarder = Forwarder;
+
+struct Forwarder;
+
+impl Log for Forwarder {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let target = ACTIVE
+            .lock()
+            .unwrap()
+            .last()
+            .map(|active| Arc::clone(&active.state));
+        if let Some(state) = target {
+            state.push(record);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// One captured log record
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedRecord {
+    /// Severity
+    pub level: Level,
+    /// Module path or explicit `target:`
+    pub target: String,
+    /// Formatted message
+    pub message: String,
+}
+
+impl std::fmt::Display for CapturedRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:<5} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+#[derive(Debug)]
+struct CaptureState {
+    capacity: usize,
+    inner: Mutex<CaptureBuffer>,
+}
+
+#[derive(Debug, Default)]
+struct CaptureBuffer {
+    records: VecDeque<CapturedRecord>,
+    /// Records seen per level, indexed by `Level as usize - 1`
+    counts: [u64; 5],
+    dropped: u64,
+    /// Records buffered per target per second, if limited
+    rate_limit: Option<u32>,
+    /// Start of the current one-second window and records buffered in it, by target
+    windows: HashMap<String, (Instant, u32)>,
+    rate_limited: u64,
+}
+
+impl CaptureBuffer {
+    /// Whether `target` has used up its share of the current window
+    fn over_rate_limit(&mut self, target: &str, now: Instant) -> bool {
+        let Some(limit) = self.rate_limit else {
+            return false;
+        };
+        let window = self.windows.entry(target.to_string()).or_insert((now, 0));
+        if now.duration_since(window.0) >= Duration::from_secs(1) {
+            *window = (now, 0);
+        }
+        window.1 += 1;
+        window.1 > limit
+    }
+}
+
+impl CaptureState {
+    fn push(&self, record: &Record) {
+        // Format before locking: a `Display` impl that logs re-enters here
+        let captured = (self.capacity > 0).then(|| CapturedRecord {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        });
+        let mut buf = self.inner.lock().unwrap();
+        buf.counts[record.level() as usize - 1] += 1;
+        let Some(captured) = captured else {
+            buf.dropped += 1;
+            return;
+        };
+        if buf.over_rate_limit(&captured.target, Instant::now()) {
+            buf.rate_limited += 1;
+            return;
+        }
+        if buf.records.len() == self.capacity {
+            buf.records.pop_front();
+            buf.dropped += 1;
+        }
+        buf.records.push_back(captured);
+    }
+}
+
+/// Bounded in-memory sink for `log` records
+///
+/// Keeps the last `capacity` records in a ring buffer and counts every
+/// record per level, including ones evicted from the buffer, so long runs
+/// cannot exhaust memory or flood CI output. [`with_rate_limit`] also keeps
+/// a chatty target from crowding the others out of the buffer. While
+/// installed, the global max level is raised to `Trace`; dropping the
+/// capture restores the previous level and the previously installed capture.
+///
+/// [`with_rate_limit`]: LogCapture::with_rate_limit
+///
+/// # Example
+/// ```rust,ignore
+/// let capture = LogCapture::install(1024)?.with_rate_limit(50);
+/// run_scale_benchmark();
+/// capture.assert_no_errors();
+/// assert!(capture.count(log::Level::Warn) < 10);
+/// for line in capture.dump_tail(20) {
+///     eprintln!("{}", line);
+/// }
+/// ```
+pub struct LogCapture {
+    state: Arc<CaptureState>,
+}
+
+impl LogCapture {
+    /// Start capturing, keeping at most `capacity` records
+    ///
+    /// Fails if a logger other than the testkit's was already installed in
+    /// this process.
+    pub fn install(capacity: usize) -> Result<Self, SetLoggerError> {
+        if !*FORWARDING.get_or_init(|| log::set_logger(&FORWARDER).is_ok()) {
+            // Another logger owns the facade; retrying reproduces its error
+            log::set_logger(&FORWARDER)?;
+        }
+
+        let state = Arc::new(CaptureState {
+            capacity,
+            inner: Mutex::new(CaptureBuffer::default()),
+        });
+        let mut active = ACTIVE.lock().unwrap();
+        active.push(ActiveCapture {
+            state: Arc::clone(&state),
+            previous_level: log::max_level(),
+        });
+        log::set_max_level(LevelFilter::Trace);
+        Ok(Self { state })
+    }
+
+    /// Buffer at most `per_second` records per target per second
+    ///
+    /// Records over the limit are still counted per level but not
+    /// buffered; see [`rate_limited`](Self::rate_limited).
+    pub fn with_rate_limit(self, per_second: u32) -> Self {
+        self.state.inner.lock().unwrap().rate_limit = Some(per_second);
+        self
+    }
+
+    /// Buffered records, oldest first
+    pub fn records(&self) -> Vec<CapturedRecord> {
+        self.state
+            .inner
+            .lock()
+            .unwrap()
+            .records
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Last `n` buffered records formatted one per line, oldest first
+    pub fn dump_tail(&self, n: usize) -> Vec<String> {
+        let buf = self.state.inner.lock().unwrap();
+        let skip = buf.records.len().saturating_sub(n);
+        buf.records
+            .iter()
+            .skip(skip)
+            .map(|r| r.to_string())
+            .collect()
+    }
+
+    /// Records logged at exactly `level`, including evicted ones
+    pub fn count(&self, level: Level) -> u64 {
+        self.state.inner.lock().unwrap().counts[level as usize - 1]
+    }
+
+    /// Records evicted from (or never stored in) the ring buffer
+    pub fn dropped(&self) -> u64 {
+        self.state.inner.lock().unwrap().dropped
+    }
+
+    /// Records not buffered because their target exceeded the rate limit
+    pub fn rate_limited(&self) -> u64 {
+        self.state.inner.lock().unwrap().rate_limited
+    }
+
+    /// Panic if any `Error` record was logged
+    ///
+    /// The panic message lists the buffered error records.
+    #[track_caller]
+    pub fn assert_no_errors(&self) {
+        let errors = self.count(Level::Error);
+        if errors > 0 {
+            let lines: Vec<String> = self
+                .records()
+                .iter()
+                .filter(|r| r.level == Level::Error)
+                .map(|r| r.to_string())
+                .collect();
+            panic!(
+                "expected no error logs, got {}:\n{}",
+                errors,
+                lines.join("\n")
+            );
+        }
+    }
+}
+
+impl Drop for LogCapture {
+    fn drop(&mut self) {
+        let mut active = ACTIVE.lock().unwrap();
+        let Some(index) = active
+            .iter()
+            .position(|a| Arc::ptr_eq(&a.state, &self.state))
+        else {
+            return;
+        };
+        let removed = active.remove(index);
+        match active.get_mut(index) {
+            // The capture installed next now restores what this one would have
+            Some(next) => next.previous_level = removed.previous_level,
+            None => log::set_max_level(removed.previous_level),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A single test: captures are process-wide and tests run in parallel
+    #[test]
+    fn test_capture_counts_tail_and_restore() {
+        let outer = LogCapture::install(3).unwrap();
+        log::info!(target: "scale", "phase {} done", 1);
+        log::warn!("slow chunk");
+
+        {
+            let inner = LogCapture::install(10).unwrap();
+            log::error!("decode failed");
+            assert_eq!(inner.count(Level::Error), 1);
+            let result = std::panic::catch_unwind(|| inner.assert_no_errors());
+            assert!(result.is_err());
+        }
+
+        // The inner capture is gone; records flow to the outer one again
+        for i in 0..3 {
+            log::debug!("tick {}", i);
+        }
+        assert_eq!(outer.count(Level::Info), 1);
+        assert_eq!(outer.count(Level::Warn), 1);
+        assert_eq!(outer.count(Level::Debug), 3);
+        assert_eq!(outer.count(Level::Error), 0);
+        outer.assert_no_errors();
+
+        // Capacity 3 keeps only the ticks
+        assert_eq!(outer.dropped(), 2);
+        let tail = outer.dump_tail(2);
+        assert_eq!(tail.len(), 2);
+        assert!(tail[0].ends_with("tick 1"), "{}", tail[0]);
+        assert!(tail[1].starts_with("DEBUG"), "{}", tail[1]);
+        assert_eq!(outer.records()[0].message, "tick 0");
+        assert_eq!(outer.dump_tail(10).len(), 3);
+
+        // A message whose formatting logs must not deadlock
+        struct Chatty;
+        impl std::fmt::Display for Chatty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                log::trace!("formatting");
+                write!(f, "chatty")
+            }
+        }
+        log::info!("{}", Chatty);
+        assert_eq!(outer.count(Level::Trace), 1);
+        assert_eq!(outer.records()[2].message, "chatty");
+        drop(outer);
+
+        // Rate limiting is per target and still counts every record
+        let limited = LogCapture::install(10).unwrap().with_rate_limit(2);
+        for i in 0..5 {
+            log::info!(target: "spam", "spam {}", i);
+        }
+        log::info!(target: "quiet", "once");
+        assert_eq!(limited.count(Level::Info), 6);
+        assert_eq!(limited.rate_limited(), 3);
+        let messages: Vec<String> = limited.records().into_iter().map(|r| r.message).collect();
+        assert_eq!(messages, ["spam 0", "spam 1", "once"]);
+        drop(limited);
+
+        // Dropping out of install order still restores the original level
+        let original = log::max_level();
+        let first = LogCapture::install(1).unwrap();
+        let second = LogCapture::install(1).unwrap();
+        drop(first);
+        assert_eq!(log::max_level(), LevelFilter::Trace);
+        drop(second);
+        assert_eq!(log::max_level(), original);
+    }
+}
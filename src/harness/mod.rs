@@ -0,0 +1,2943 @@
+//! Test harness for managing temporary directories and test datasets
+//!
+//! Provides a unified test harness that:
+//! - Creates temporary directories automatically cleaned up after tests
+//! - Generates test datasets of various sizes and patterns
+//! - Tracks performance metrics across test runs
+//! - Provides helper methods for common test operations
+//! - Orchestrates end-to-end scale tests (`ScaleTest`, `large-scale` feature)
+//! - Scores similarity search against ground truth (`RetrievalEval`)
+//! - Records and replays VSA operation sequences (`OpTrace`)
+//! - Captures `log` output for assertions (`LogCapture`, `log` feature)
+//! - Bounds potentially hanging operations (`run_with_timeout`)
+//! - Packages the evidence of a failed test into one tar (`FailureArtifacts`)
+//! - Simulates chunked ingestion with injectable faults (`ChunkSimulator`)
+//! - Compares encoder config variations over one corpus (`ConfigSweep`)
+//! - Ages file trees and touches seeded subsets for incremental scans (`age_dataset`)
+//! - Minimizes a failing dataset to a reproducing file subset (`DatasetShrinker`)
+//! - Calibrates similarity thresholds from measured cosine distributions (`calibrate_threshold`)
+//! - Replays timed filesystem change scripts for watcher tests (`FsEventScript`)
+//! - Retries flaky operations with seeded backoff on an injectable clock (`retry`)
+
+mod aging;
+mod artifacts;
+mod calibration;
+mod chunking;
+mod fs_events;
+#[cfg(feature = "log")]
+mod log_capture;
+mod retrieval;
+mod retry;
+#[cfg(feature = "large-scale")]
+mod scale;
+mod shrink;
+mod sweep;
+mod timeout;
+mod trace;
+
+pub use aging::{
+    age_dataset, grow_subset, touch_subset, try_age_dataset, try_grow_subset, try_touch_subset,
+};
+pub use artifacts::{FailureArtifacts, DEFAULT_ARTIFACT_MAX_BYTES, FAILURE_ARTIFACTS_ENV};
+pub use calibration::{
+    calibrate_threshold, NoiseLevelThreshold, ThresholdCalibration, CALIBRATION_NOISE_LEVELS,
+    DEFAULT_TARGET_FNR, DEFAULT_TARGET_FPR,
+};
+pub use chunking::{ChunkFault, ChunkInfo, ChunkOverlap, ChunkSimulator, EncodedChunk, Reassembly};
+pub use fs_events::{ExecutedEvent, ExecutionLog, FsEvent, FsEventScript, FsStep};
+#[cfg(feature = "log")]
+pub use log_capture::{CapturedRecord, LogCapture};
+pub use retrieval::{RetrievalEval, RetrievalPoint, RetrievalReport};
+pub use retry::{
+    retry, Backoff, Clock, RetryAttempt, RetryOutcome, RetryPolicy, RetryStop, SystemClock,
+    VirtualClock,
+};
+#[cfg(feature = "large-scale")]
+pub use scale::{PhaseReport, ScaleEstimate, ScaleOutcome, ScalePhase, ScaleTest, ScaleTestReport};
+pub use shrink::{DatasetShrinker, ShrinkResult, DEFAULT_SHRINK_STEPS};
+pub use sweep::{ConfigSweep, SweepObjective, SweepReport, SweepRow};
+pub use timeout::{run_with_timeout, TimeoutResult};
+pub use trace::{record_mode, OpTrace, ReplayResult, TraceOp, TraceRecorder};
+
+use crate::cancel::CancellationToken;
+use crate::error::{Error, IoResultExt, Result};
+use crate::events;
+use crate::fixtures::{
+    write_pattern_to, DatasetManifest, DatasetOptions, FileMetaSpec, FileSizeDistribution,
+    GeneratedDataset, GenerationOutcome, GenerationStart, ManifestEntry, SeededFileKind,
+    TestDataPattern, WalkOrder,
+};
+use crate::generators::forked_rngs;
+use crate::integrity::IntegrityReport;
+use crate::metrics::{TestMetrics, TimingStats};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Barrier, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tempfile::TempDir;
+
+/// Performance metrics collector shared across tests
+#[derive(Clone, Debug, Default)]
+pub struct PerformanceMetrics {
+    pub operation_times: HashMap<String, Vec<Duration>>,
+    pub memory_usage: HashMap<String, Vec<usize>>,
+    pub throughput: HashMap<String, Vec<f64>>,
+}
+
+impl PerformanceMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a performance metric
+    pub fn record(
+        &mut self,
+        operation: &str,
+        duration: Duration,
+        memory_kb: usize,
+        throughput_mbps: f64,
+    ) {
+        self.operation_times
+            .entry(operation.to_string())
+            .or_default()
+            .push(duration);
+        self.memory_usage
+            .entry(operation.to_string())
+            .or_default()
+            .push(memory_kb);
+        self.throughput
+            .entry(operation.to_string())
+            .or_default()
+            .push(throughput_mbps);
+    }
+
+    /// Get average time for an operation
+    pub fn avg_time(&self, operation: &str) -> Option<Duration> {
+        self.operation_times.get(operation).map(|times| {
+            let sum: Duration = times.iter().sum();
+            sum / times.len() as u32
+        })
+    }
+
+    /// Get average throughput for an operation
+    pub fn avg_throughput(&self, operation: &str) -> Option<f64> {
+        self.throughput
+            .get(operation)
+            .map(|throughputs| throughputs.iter().sum::<f64>() / throughputs.len() as f64)
+    }
+}
+
+/// Test harness for comprehensive validation
+///
+/// Manages temporary directories, test datasets, and performance metrics.
+/// Automatically cleans up resources when dropped.
+///
+/// The harness is `Send + Sync`: threads may share `&TestHarness`, or take
+/// their own handle with [`clone_handle`](Self::clone_handle). Dataset
+/// directories and files are claimed atomically, so concurrent calls never
+/// write into each other's output.
+///
+/// Every method that creates a directory claims a fresh one: if the
+/// requested name is already taken, by an earlier call or another thread,
+/// `<name>_<n>` is used instead. Always use the returned path rather than
+/// rebuilding it from the name.
+pub struct TestHarness {
+    inner: Arc<HarnessInner>,
+    failure_artifact_dir: Option<PathBuf>,
+    /// Whether this is the handle from `new`, which runs the failure hooks
+    owner: bool,
+}
+
+/// State shared by every handle to one harness
+struct HarnessInner {
+    temp_dir: TempDir,
+    metrics: Mutex<PerformanceMetrics>,
+    failure_hooks: Mutex<Vec<FailureHook>>,
+    /// Suffixes for taken dataset directories and temporary file names
+    next_unique: AtomicUsize,
+}
+
+type FailureHook = Box<dyn FnOnce(&mut FailureArtifacts) + Send>;
+
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<TestHarness>();
+};
+
+impl TestHarness {
+    /// Create a new test harness
+    pub fn new() -> Self {
+        Self::try_new().unwrap_or_else(|e| panic!("Failed to create temp directory: {}", e))
+    }
+
+    /// Create a new test harness, returning an error if the temp directory
+    /// cannot be created
+    pub fn try_new() -> Result<Self> {
+        let temp_dir = TempDir::new().at_path(&std::env::temp_dir())?;
+        Ok(TestHarness {
+            inner: Arc::new(HarnessInner {
+                temp_dir,
+                metrics: Mutex::new(PerformanceMetrics::default()),
+                failure_hooks: Mutex::new(Vec::new()),
+                next_unique: AtomicUsize::new(1),
+            }),
+            failure_artifact_dir: None,
+            owner: true,
+        })
+    }
+
+    /// Another handle to this harness, e.g. to move into a spawned thread
+    ///
+    /// Handles share the temp directory, metrics and failure hooks; the
+    /// directory is removed once the last handle is dropped. Only the
+    /// original harness runs the failure hooks when dropped during a panic,
+    /// so a worker thread panicking with its handle leaves them for the
+    /// owner.
+    pub fn clone_handle(&self) -> TestHarness {
+        TestHarness {
+            inner: Arc::clone(&self.inner),
+            failure_artifact_dir: self.failure_artifact_dir.clone(),
+            owner: false,
+        }
+    }
+
+    /// Write failure archives to `dir` instead of the
+    /// [`FAILURE_ARTIFACTS_ENV`] directory
+    pub fn with_failure_artifact_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.failure_artifact_dir = Some(dir.into());
+        self
+    }
+
+    /// Register `hook` to fill a [`FailureArtifacts`] bundle if the test fails
+    ///
+    /// Hooks run only when the harness (not a
+    /// [`clone_handle`](Self::clone_handle)) is dropped while the thread is
+    /// panicking, so expensive evidence is gathered lazily. The archive goes
+    /// to the directory set by
+    /// [`with_failure_artifact_dir`](Self::with_failure_artifact_dir), else
+    /// `$TESTKIT_ARTIFACT_DIR`, else the system temp directory, and its path
+    /// is printed to stderr. A hook that panics is skipped; its message is
+    /// stored in the archive as `hook_panic.json` and the other hooks still
+    /// run.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let harness = TestHarness::new();
+    /// let input = harness.create_dataset(10);
+    /// harness.on_failure(move |artifacts| {
+    ///     artifacts.add_file(&input.join("file_0000.bin"));
+    /// });
+    /// ```
+    pub fn on_failure(&self, hook: impl FnOnce(&mut FailureArtifacts) + Send + 'static) {
+        self.inner
+            .failure_hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Box::new(hook));
+    }
+
+    /// Run the registered failure hooks now and write their archive to `dest`
+    ///
+    /// For failures detected without a panic. The hooks are consumed.
+    pub fn write_failure_artifacts(&self, dest: &Path) -> Result<PathBuf> {
+        let hooks = std::mem::take(
+            &mut *self
+                .inner
+                .failure_hooks
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()),
+        );
+        let mut artifacts = FailureArtifacts::collect(self);
+        for hook in hooks {
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| hook(&mut artifacts))) {
+                let message = panic_message(payload.as_ref());
+                artifacts.add_json("hook_panic", &serde_json::json!({ "message": message }));
+            }
+        }
+        artifacts.write_archive(dest)
+    }
+
+    /// Get the temporary directory path
+    pub fn temp_dir(&self) -> &Path {
+        self.inner.temp_dir.path()
+    }
+
+    /// Record a performance metric
+    pub fn record_metric(
+        &self,
+        operation: &str,
+        duration: Duration,
+        memory_kb: usize,
+        throughput_mbps: f64,
+    ) {
+        let mut metrics = self.inner.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        metrics.record(operation, duration, memory_kb, throughput_mbps);
+    }
+
+    /// Get a copy of current metrics
+    pub fn metrics(&self) -> PerformanceMetrics {
+        self.inner
+            .metrics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Create a test dataset of specified size in MB
+    ///
+    /// Creates a directory with various file types and patterns, named
+    /// `dataset_<size>mb`, or `dataset_<size>mb_<n>` if that is already taken
+    pub fn create_dataset(&self, size_mb: usize) -> PathBuf {
+        self.try_create_dataset(size_mb)
+            .unwrap_or_else(|e| panic!("Failed to create dataset: {}", e))
+    }
+
+    /// Fallible variant of [`create_dataset`](Self::create_dataset)
+    pub fn try_create_dataset(&self, size_mb: usize) -> Result<PathBuf> {
+        self.try_create_dataset_with(size_mb, &DatasetOptions::default())
+            .map(|dataset| dataset.path)
+    }
+
+    /// Create a test dataset using `options` (e.g. to report progress)
+    ///
+    /// Honors [`DatasetOptions::time_budget`], [`DatasetOptions::max_files`]
+    /// and [`DatasetOptions::cancellation`], stopping at a file boundary.
+    /// Every call writes into a freshly claimed directory, so the result's
+    /// `start` is always [`GenerationStart::Fresh`].
+    ///
+    /// # Errors
+    /// [`Error::InvalidSpec`] if `options` asks for anything the harness
+    /// layout cannot provide: self-describing headers, a size distribution
+    /// other than the default, a seed, a type mix or resumable generation.
+    /// Use [`try_create_test_dataset_with`](crate::fixtures::try_create_test_dataset_with)
+    /// or [`try_create_seeded_dataset_with`](Self::try_create_seeded_dataset_with)
+    /// for those.
+    pub fn try_create_dataset_with(
+        &self,
+        size_mb: usize,
+        options: &DatasetOptions,
+    ) -> Result<GeneratedDataset> {
+        let unsupported = [
+            ("self_describing", options.self_describing),
+            (
+                "size_distribution",
+                options.size_distribution != FileSizeDistribution::default(),
+            ),
+            ("seed", options.seed != 0),
+            ("type_mix", !options.type_mix.is_empty()),
+            ("mismatch_fraction", options.mismatch_fraction != 0.0),
+            ("resumable", options.resumable),
+        ];
+        if let Some((name, _)) = unsupported.iter().find(|(_, set)| *set) {
+            return Err(Error::invalid_spec(
+                self.temp_dir(),
+                format!("{} is not supported by the harness dataset layout", name),
+            ));
+        }
+
+        let dataset_dir = self.claim_dir(&format!("dataset_{}mb", size_mb))?;
+
+        let started = Instant::now();
+        let total = dataset_entries(size_mb)
+            .map(|(_, content)| content.len() as u64)
+            .sum();
+        let tracker = options.progress.tracker(Some(total), "generate");
+        let mut files = 0;
+        let mut written_bytes = 0u64;
+        let mut outcome = GenerationOutcome::Completed;
+        for (filename, content) in dataset_entries(size_mb) {
+            if let Some(stop) = options.stop_outcome(started, files, written_bytes) {
+                outcome = stop;
+                break;
+            }
+            content.write_file(&dataset_dir.join(filename))?;
+            tracker.advance(content.len() as u64);
+            written_bytes += content.len() as u64;
+            files += 1;
+        }
+        tracker.finish();
+
+        events::emit("harness", "dataset_created", || {
+            serde_json::json!({
+                "path": dataset_dir.display().to_string(),
+                "size_mb": size_mb,
+                "files": files,
+                "bytes": written_bytes,
+                "truncated": outcome.is_truncated(),
+            })
+        });
+        Ok(GeneratedDataset {
+            path: dataset_dir,
+            files,
+            outcome,
+            start: GenerationStart::Fresh,
+        })
+    }
+
+    /// Async variant of [`try_create_dataset`](Self::try_create_dataset)
+    ///
+    /// Writes the same files as the sync version with at most
+    /// `max_concurrent` writes in flight, returning only after every file has
+    /// been flushed and renamed into place.
+    #[cfg(feature = "async")]
+    pub async fn create_dataset_async(
+        &self,
+        size_mb: usize,
+        max_concurrent: usize,
+    ) -> Result<PathBuf> {
+        let dataset_dir = self.claim_dir(&format!("dataset_{}mb", size_mb))?;
+
+        let entries = dataset_entries(size_mb).map(|(filename, content)| {
+            let write = move |mut out: &mut dyn std::io::Write| content.write_to(&mut out);
+            (dataset_dir.join(filename), content.len() as u64, write)
+        });
+        crate::fixtures::write_files_async(entries, max_concurrent).await?;
+
+        Ok(dataset_dir)
+    }
+
+    /// Create a test file with specific content
+    pub fn create_file(&self, name: &str, content: &[u8]) -> PathBuf {
+        self.try_create_file(name, content)
+            .unwrap_or_else(|e| panic!("Failed to write test file: {}", e))
+    }
+
+    /// Fallible variant of [`create_file`](Self::create_file)
+    ///
+    /// The content is written to a temporary sibling and renamed into
+    /// place, so concurrent writers of one name leave exactly one of their
+    /// contents behind, never a mix.
+    pub fn try_create_file(&self, name: &str, content: &[u8]) -> Result<PathBuf> {
+        let filepath = self.temp_dir().join(name);
+        let staging = filepath.with_file_name(format!(
+            ".{}.{}.tmp",
+            filepath.file_name().unwrap_or_default().to_string_lossy(),
+            self.inner.next_unique.fetch_add(1, Ordering::Relaxed)
+        ));
+        write_file(&staging, content)?;
+        fs::rename(&staging, &filepath).at_path(&filepath)?;
+        Ok(filepath)
+    }
+
+    /// Create `base` under the temp directory, or `base_<n>` if it exists
+    ///
+    /// `fs::create_dir` fails if the directory is already there, so exactly
+    /// one caller wins each name. Missing parents of `base` are created.
+    fn claim_dir(&self, base: &str) -> Result<PathBuf> {
+        let mut dir = self.temp_dir().join(base);
+        if let Some(parent) = dir.parent() {
+            fs::create_dir_all(parent).at_path(parent)?;
+        }
+        loop {
+            match fs::create_dir(&dir) {
+                Ok(()) => return Ok(dir),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let n = self.inner.next_unique.fetch_add(1, Ordering::Relaxed);
+                    dir = self.temp_dir().join(format!("{}_{}", base, n));
+                }
+                Err(e) => return Err(Error::io(&dir, e)),
+            }
+        }
+    }
+
+    /// Create a file with content and the metadata described by `meta`
+    pub fn create_file_with_metadata(
+        &self,
+        name: &str,
+        content: &[u8],
+        meta: &FileMetaSpec,
+    ) -> PathBuf {
+        self.try_create_file_with_metadata(name, content, meta)
+            .unwrap_or_else(|e| panic!("Failed to create file with metadata: {}", e))
+    }
+
+    /// Fallible variant of [`create_file_with_metadata`](Self::create_file_with_metadata)
+    pub fn try_create_file_with_metadata(
+        &self,
+        name: &str,
+        content: &[u8],
+        meta: &FileMetaSpec,
+    ) -> Result<PathBuf> {
+        let filepath = self.try_create_file(name, content)?;
+        meta.apply(&filepath)?;
+        Ok(filepath)
+    }
+
+    /// Create a directory structure with various files
+    pub fn create_directory_structure(&self, name: &str) -> PathBuf {
+        self.try_create_directory_structure(name)
+            .unwrap_or_else(|e| panic!("Failed to create directory structure: {}", e))
+    }
+
+    /// Fallible variant of [`create_directory_structure`](Self::create_directory_structure)
+    pub fn try_create_directory_structure(&self, name: &str) -> Result<PathBuf> {
+        let base = self.claim_dir(name)?;
+
+        // Create directory structure
+        for dir in ["dir1", "dir2/nested", "empty_dir"] {
+            let path = base.join(dir);
+            fs::create_dir_all(&path).at_path(&path)?;
+        }
+
+        // Create test files
+        write_file(&base.join("file1.txt"), b"Hello, world!")?;
+        write_file(&base.join("file2.log"), b"Log entry 1\nLog entry 2\n")?;
+        write_file(
+            &base.join("dir1/file3.dat"),
+            b"Binary data: \x00\x01\x02\xFF",
+        )?;
+        write_file(
+            &base.join("dir2/file4.md"),
+            b"# Markdown\n\n## Section\n\nContent here.",
+        )?;
+        write_file(
+            &base.join("dir2/nested/file5.json"),
+            br#"{"key": "value", "number": 42}"#,
+        )?;
+
+        events::emit(
+            "harness",
+            "directory_created",
+            || serde_json::json!({ "path": base.display().to_string() }),
+        );
+        Ok(base)
+    }
+
+    /// Create a reproducible dataset of exactly `size_mb` MB derived from `seed`
+    ///
+    /// File kinds, sizes and content all follow from the seed, so equal seeds
+    /// give identical trees and different seeds give different ones. A
+    /// [`DatasetManifest`] recording the seed is written next to the
+    /// directory (see [`DatasetManifest::path_for`]).
+    pub fn create_dataset_seeded(&self, size_mb: usize, seed: u64) -> PathBuf {
+        self.try_create_dataset_seeded(size_mb, seed)
+            .unwrap_or_else(|e| panic!("Failed to create seeded dataset: {}", e))
+    }
+
+    /// Fallible variant of [`create_dataset_seeded`](Self::create_dataset_seeded)
+    pub fn try_create_dataset_seeded(&self, size_mb: usize, seed: u64) -> Result<PathBuf> {
+        self.try_create_dataset_seeded_with(size_mb, seed, &DatasetOptions::default())
+            .map(|dataset| dataset.path)
+    }
+
+    /// Create a seeded dataset using `options`
+    ///
+    /// Honors [`DatasetOptions::time_budget`] and [`DatasetOptions::max_files`]:
+    /// if either runs out, the files written so far are complete and the saved
+    /// [`DatasetManifest`] lists only those, with its `outcome` marked truncated.
+    pub fn try_create_dataset_seeded_with(
+        &self,
+        size_mb: usize,
+        seed: u64,
+        options: &DatasetOptions,
+    ) -> Result<GeneratedDataset> {
+        let dataset_dir = self.claim_dir(&format!("dataset_{}mb_seed{}", size_mb, seed))?;
+        let dataset = if options.type_mix.is_empty() {
+            let entries = crate::fixtures::seeded_dataset_entries(size_mb, seed);
+            write_seeded(&dataset_dir, seed, entries.map(untyped_entry), options)?
+        } else {
+            let entries =
+                crate::fixtures::typed_dataset_entries(&dataset_dir, size_mb, seed, options)?;
+            write_seeded(&dataset_dir, seed, entries, options)?
+        };
+        events::emit("harness", "dataset_created", || {
+            serde_json::json!({
+                "path": dataset_dir.display().to_string(),
+                "size_mb": size_mb,
+                "seed": seed,
+                "truncated": dataset.is_truncated(),
+            })
+        });
+        Ok(dataset)
+    }
+
+    /// Create a reproducible nested directory tree derived from `seed`
+    ///
+    /// Like [`create_dataset_seeded`](Self::create_dataset_seeded), a
+    /// manifest recording the seed is written next to the directory.
+    pub fn create_directory_structure_seeded(&self, name: &str, seed: u64) -> PathBuf {
+        self.try_create_directory_structure_seeded(name, seed)
+            .unwrap_or_else(|e| panic!("Failed to create seeded directory structure: {}", e))
+    }
+
+    /// Fallible variant of
+    /// [`create_directory_structure_seeded`](Self::create_directory_structure_seeded)
+    pub fn try_create_directory_structure_seeded(&self, name: &str, seed: u64) -> Result<PathBuf> {
+        let base = self.claim_dir(name)?;
+        write_seeded(
+            &base,
+            seed,
+            crate::fixtures::seeded_tree_entries(seed)
+                .into_iter()
+                .map(untyped_entry),
+            &DatasetOptions::default(),
+        )?;
+        events::emit(
+            "harness",
+            "directory_created",
+            || serde_json::json!({ "path": base.display().to_string(), "seed": seed }),
+        );
+        Ok(base)
+    }
+
+    /// Several reproducible file orders of `dataset` for order-invariance tests
+    ///
+    /// Returns sorted, reverse-sorted and three seeded shuffles, each from
+    /// [`walk_deterministic`](crate::fixtures::walk_deterministic). Ingest
+    /// the files in each order and compare the results (e.g. engram cosine
+    /// or extraction checksums); any difference is an ordering bug that
+    /// reproduces from the reported [`WalkOrder`].
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let fingerprints: Vec<u64> = harness
+    ///     .ingest_orders(&dataset)
+    ///     .into_iter()
+    ///     .map(|(order, files)| ingest_in_order(&files, order))
+    ///     .collect();
+    /// assert!(fingerprints.windows(2).all(|w| w[0] == w[1]));
+    /// ```
+    pub fn ingest_orders(&self, dataset: &Path) -> Vec<(WalkOrder, Vec<PathBuf>)> {
+        self.try_ingest_orders(dataset)
+            .unwrap_or_else(|e| panic!("Failed to walk dataset: {}", e))
+    }
+
+    /// Fallible variant of [`ingest_orders`](Self::ingest_orders)
+    pub fn try_ingest_orders(&self, dataset: &Path) -> Result<Vec<(WalkOrder, Vec<PathBuf>)>> {
+        INGEST_ORDERS
+            .iter()
+            .map(|&order| {
+                Ok((
+                    order,
+                    crate::fixtures::try_walk_deterministic(dataset, order)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Snapshot `path`, storing large files under this harness's temp dir
+    pub fn snapshot(&self, path: &Path) -> Result<DirectorySnapshot> {
+        let area = self.temp_dir().join(".snapshots");
+        fs::create_dir_all(&area).at_path(&area)?;
+        let area = tempfile::Builder::new()
+            .prefix("snapshot_")
+            .tempdir_in(&area)
+            .at_path(&area)?;
+        DirectorySnapshot::capture_with_area(path, area)
+    }
+
+    /// Create a large file with specified pattern
+    pub fn create_large_file(
+        &self,
+        name: &str,
+        size_mb: usize,
+        pattern: crate::fixtures::TestDataPattern,
+    ) -> PathBuf {
+        self.try_create_large_file(name, size_mb, pattern)
+            .unwrap_or_else(|e| panic!("Failed to write large file: {}", e))
+    }
+
+    /// Fallible variant of [`create_large_file`](Self::create_large_file)
+    pub fn try_create_large_file(
+        &self,
+        name: &str,
+        size_mb: usize,
+        pattern: crate::fixtures::TestDataPattern,
+    ) -> Result<PathBuf> {
+        let filepath = self.temp_dir().join(name);
+        crate::fixtures::write_pattern_file(&filepath, &[], size_mb * 1024 * 1024, pattern)?;
+        events::emit("harness", "file_created", || {
+            serde_json::json!({
+                "path": filepath.display().to_string(),
+                "size_mb": size_mb,
+                "pattern": format!("{:?}", pattern),
+            })
+        });
+        Ok(filepath)
+    }
+
+    /// Create chunk-boundary sized files under `name`
+    ///
+    /// See [`create_boundary_size_files`](crate::fixtures::create_boundary_size_files);
+    /// use this for chunking roundtrip tests.
+    pub fn create_boundary_files(
+        &self,
+        name: &str,
+        chunk_size: usize,
+        pattern: crate::fixtures::TestDataPattern,
+    ) -> Vec<(PathBuf, usize)> {
+        self.try_create_boundary_files(name, chunk_size, pattern)
+            .unwrap_or_else(|e| panic!("Failed to create boundary size files: {}", e))
+    }
+
+    /// Fallible variant of [`create_boundary_files`](Self::create_boundary_files)
+    pub fn try_create_boundary_files(
+        &self,
+        name: &str,
+        chunk_size: usize,
+        pattern: crate::fixtures::TestDataPattern,
+    ) -> Result<Vec<(PathBuf, usize)>> {
+        let dir = self.claim_dir(name)?;
+        let files = crate::fixtures::try_create_boundary_size_files(&dir, chunk_size, pattern)?;
+        events::emit("harness", "boundary_files_created", || {
+            serde_json::json!({
+                "path": dir.display().to_string(),
+                "chunk_size": chunk_size,
+                "files": files.len(),
+            })
+        });
+        Ok(files)
+    }
+
+    /// Create a tree of filesystem edge cases under `name`
+    ///
+    /// See [`EdgeCaseKind`] for the entries attempted. Entries the platform
+    /// or sandbox cannot express are listed in
+    /// [`EdgeCaseInventory::skipped`] instead of failing the call.
+    pub fn create_edge_case_structure(&self, name: &str) -> EdgeCaseInventory {
+        self.try_create_edge_case_structure(name)
+            .unwrap_or_else(|e| panic!("Failed to create edge case structure: {}", e))
+    }
+
+    /// Fallible variant of
+    /// [`create_edge_case_structure`](Self::create_edge_case_structure)
+    ///
+    /// Only errors creating the regular files and directories are returned;
+    /// symlinks, odd names and FIFOs are best-effort.
+    pub fn try_create_edge_case_structure(&self, name: &str) -> Result<EdgeCaseInventory> {
+        let root = self.claim_dir(name)?;
+        let mut inventory = EdgeCaseInventory {
+            root: root.clone(),
+            created: Vec::new(),
+            skipped: Vec::new(),
+        };
+
+        let nested = root.join("nested");
+        fs::create_dir_all(&nested).at_path(&nested)?;
+
+        write_file(&root.join("empty.txt"), b"")?;
+        write_file(&root.join("nested/empty.bin"), b"")?;
+        inventory.record(EdgeCaseKind::EmptyFile, "empty.txt", Ok(()));
+        inventory.record(EdgeCaseKind::EmptyFile, "nested/empty.bin", Ok(()));
+
+        let empty_dir = root.join("empty_dir");
+        fs::create_dir_all(&empty_dir).at_path(&empty_dir)?;
+        inventory.record(EdgeCaseKind::EmptyDir, "empty_dir", Ok(()));
+
+        write_file(&root.join("one_byte.bin"), b"x")?;
+        inventory.record(EdgeCaseKind::OneByteFile, "one_byte.bin", Ok(()));
+
+        // Link targets
+        write_file(&root.join("target.txt"), b"symlink target\n")?;
+
+        let links = [
+            (
+                EdgeCaseKind::SymlinkToFile,
+                "link_to_file",
+                "target.txt",
+                false,
+            ),
+            (EdgeCaseKind::SymlinkToDir, "link_to_dir", "nested", true),
+            (
+                EdgeCaseKind::DanglingSymlink,
+                "dangling_link",
+                "does_not_exist",
+                false,
+            ),
+            (
+                EdgeCaseKind::RelativeParentSymlink,
+                "nested/parent_link",
+                "../target.txt",
+                false,
+            ),
+        ];
+        for (kind, rel, target, is_dir) in links {
+            let result = make_symlink(Path::new(target), &root.join(rel), is_dir);
+            inventory.record(kind, rel, result);
+        }
+
+        let trailing = "trailing_space ";
+        let result = if cfg!(windows) {
+            Err("Windows strips trailing spaces from file names".to_string())
+        } else {
+            fs::write(root.join(trailing), b"trailing space\n").map_err(|e| e.to_string())
+        };
+        inventory.record(EdgeCaseKind::TrailingSpaceName, trailing, result);
+
+        let fifo = root.join("fifo");
+        inventory.record(EdgeCaseKind::Fifo, "fifo", make_fifo(&fifo));
+
+        events::emit("harness", "edge_cases_created", || {
+            serde_json::json!({
+                "path": root.display().to_string(),
+                "created": inventory.created.len(),
+                "skipped": inventory.skipped.len(),
+            })
+        });
+        Ok(inventory)
+    }
+}
+
+/// Kind of entry created by [`TestHarness::create_edge_case_structure`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeCaseKind {
+    /// Zero-length regular file
+    EmptyFile,
+    /// Directory with no entries
+    EmptyDir,
+    /// Symlink to a regular file in the tree
+    SymlinkToFile,
+    /// Symlink to a directory in the tree
+    SymlinkToDir,
+    /// Symlink whose target does not exist
+    DanglingSymlink,
+    /// Symlink in a subdirectory pointing through `../`
+    RelativeParentSymlink,
+    /// Regular file whose name ends in a space
+    TrailingSpaceName,
+    /// Regular file holding a single byte
+    OneByteFile,
+    /// Named pipe (Unix only)
+    Fifo,
+}
+
+/// One entry of an [`EdgeCaseInventory`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdgeCase {
+    /// What the entry exercises
+    pub kind: EdgeCaseKind,
+    /// Path relative to the inventory root, with `/` separators
+    pub path: String,
+}
+
+/// What [`TestHarness::create_edge_case_structure`] actually produced
+#[derive(Clone, Debug)]
+pub struct EdgeCaseInventory {
+    /// Root of the edge case tree
+    pub root: PathBuf,
+    /// Entries present on disk
+    pub created: Vec<EdgeCase>,
+    /// Entries not supported here, with the reason
+    pub skipped: Vec<(EdgeCase, String)>,
+}
+
+impl EdgeCaseInventory {
+    /// Whether at least one entry of `kind` was created
+    pub fn has(&self, kind: EdgeCaseKind) -> bool {
+        self.created.iter().any(|e| e.kind == kind)
+    }
+
+    /// Absolute path of a created entry
+    pub fn path(&self, entry: &EdgeCase) -> PathBuf {
+        self.root.join(&entry.path)
+    }
+
+    fn record(&mut self, kind: EdgeCaseKind, path: &str, result: std::result::Result<(), String>) {
+        let entry = EdgeCase {
+            kind,
+            path: path.to_string(),
+        };
+        match result {
+            Ok(()) => self.created.push(entry),
+            Err(reason) => self.skipped.push((entry, reason)),
+        }
+    }
+}
+
+/// Create a symlink at `link`, reporting failure as a skip reason
+fn make_symlink(target: &Path, link: &Path, is_dir: bool) -> std::result::Result<(), String> {
+    #[cfg(unix)]
+    {
+        let _ = is_dir;
+        std::os::unix::fs::symlink(target, link).map_err(|e| e.to_string())
+    }
+    #[cfg(windows)]
+    {
+        let result = if is_dir {
+            std::os::windows::fs::symlink_dir(target, link)
+        } else {
+            std::os::windows::fs::symlink_file(target, link)
+        };
+        result.map_err(|e| format!("symlink creation not permitted: {}", e))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = (target, link, is_dir);
+        Err("symlinks not supported on this platform".to_string())
+    }
+}
+
+/// Create a named pipe with the system `mkfifo`, reporting failure as a skip reason
+fn make_fifo(path: &Path) -> std::result::Result<(), String> {
+    if !cfg!(unix) {
+        return Err("FIFOs are Unix-only".to_string());
+    }
+    match std::process::Command::new("mkfifo").arg(path).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("mkfifo exited with {}", status)),
+        Err(e) => Err(format!("mkfifo unavailable: {}", e)),
+    }
+}
+
+/// How long each [`ConcurrencyStressor`] thread keeps running its workload
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StressBudget {
+    /// Run the workload this many times per thread
+    Iterations(u64),
+    /// Run the workload until this much wall-clock time has elapsed
+    Duration(Duration),
+}
+
+/// A workload panic captured on one stress thread
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadPanic {
+    /// Index of the thread that panicked
+    pub thread: usize,
+    /// Panic payload rendered as a string
+    pub message: String,
+}
+
+/// Outcome of a [`ConcurrencyStressor`] run
+#[derive(Clone, Debug)]
+pub struct StressResult {
+    /// Per-thread metrics merged into one collector
+    pub metrics: TestMetrics,
+    /// Completed workload iterations, indexed by thread
+    pub iterations: Vec<u64>,
+    /// Threads whose workload panicked
+    pub panics: Vec<ThreadPanic>,
+    /// Wall-clock time from barrier release to the last thread finishing
+    pub elapsed: Duration,
+}
+
+impl StressResult {
+    /// True if no thread panicked
+    pub fn is_ok(&self) -> bool {
+        self.panics.is_empty()
+    }
+
+    /// Indices of threads that panicked
+    pub fn panicked_threads(&self) -> Vec<usize> {
+        self.panics.iter().map(|p| p.thread).collect()
+    }
+
+    /// Iterations completed across all threads
+    pub fn total_iterations(&self) -> u64 {
+        self.iterations.iter().sum()
+    }
+
+    /// Aggregate timing statistics across all threads
+    pub fn timing_stats(&self) -> TimingStats {
+        self.metrics.timing_stats()
+    }
+}
+
+/// Runs a workload on many threads at once to shake out concurrency bugs
+///
+/// All threads wait on a barrier so they enter the workload simultaneously.
+/// Each thread gets its own deterministically seeded RNG and `TestMetrics`;
+/// panics are caught per thread and reported in the [`StressResult`].
+///
+/// # Example
+/// ```rust,ignore
+/// let result = ConcurrencyStressor::new(8)
+///     .with_budget(StressBudget::Iterations(1_000))
+///     .run("bundle", |_thread, rng| {
+///         let v = random_sparse_vec(rng, 10_000, 200);
+///         v.bundle(&shared);
+///     });
+/// assert!(result.is_ok());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConcurrencyStressor {
+    threads: usize,
+    budget: StressBudget,
+    seed: u64,
+}
+
+impl ConcurrencyStressor {
+    /// Create a stressor with `threads` threads and a 100-iteration budget
+    pub fn new(threads: usize) -> Self {
+        Self {
+            threads: threads.max(1),
+            budget: StressBudget::Iterations(100),
+            seed: 0,
+        }
+    }
+
+    /// Set the per-thread budget
+    pub fn with_budget(mut self, budget: StressBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Set the base seed used to derive per-thread RNGs
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Run `workload` on every thread until the budget is exhausted
+    ///
+    /// Each call to `workload` is timed and counted under the `"iterations"`
+    /// operation category. A thread stops at its first panic.
+    pub fn run<F>(&self, name: &str, workload: F) -> StressResult
+    where
+        F: Fn(usize, &mut StdRng) + Sync,
+    {
+        let barrier = Barrier::new(self.threads + 1);
+        let workload = &workload;
+
+        let (outcomes, elapsed) = std::thread::scope(|scope| {
+            let handles: Vec<_> = forked_rngs(self.seed, self.threads)
+                .into_iter()
+                .enumerate()
+                .map(|(thread, rng)| {
+                    let barrier = &barrier;
+                    scope.spawn(move || self.run_thread(thread, rng, name, barrier, workload))
+                })
+                .collect();
+
+            barrier.wait();
+            let start = Instant::now();
+            let outcomes: Vec<_> = handles
+                .into_iter()
+                .map(|h| h.join().expect("stress thread panicked outside workload"))
+                .collect();
+            (outcomes, start.elapsed())
+        });
+
+        let mut metrics = TestMetrics::new(name);
+        let mut iterations = Vec::with_capacity(self.threads);
+        let mut panics = Vec::new();
+        for (thread_metrics, count, panic) in outcomes {
+            metrics.merge(&thread_metrics);
+            iterations.push(count);
+            panics.extend(panic);
+        }
+
+        StressResult {
+            metrics,
+            iterations,
+            panics,
+            elapsed,
+        }
+    }
+
+    fn run_thread<F>(
+        &self,
+        thread: usize,
+        mut rng: StdRng,
+        name: &str,
+        barrier: &Barrier,
+        workload: &F,
+    ) -> (TestMetrics, u64, Option<ThreadPanic>)
+    where
+        F: Fn(usize, &mut StdRng) + Sync,
+    {
+        let mut metrics = TestMetrics::new(name);
+        let mut count = 0u64;
+
+        barrier.wait();
+        let start = Instant::now();
+
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| loop {
+            let exhausted = match self.budget {
+                StressBudget::Iterations(n) => count >= n,
+                StressBudget::Duration(d) => start.elapsed() >= d,
+            };
+            if exhausted {
+                break;
+            }
+            metrics.time_operation(|| workload(thread, &mut rng));
+            metrics.inc_op("iterations");
+            count += 1;
+        }));
+
+        let panic = outcome.err().map(|payload| ThreadPanic {
+            thread,
+            message: panic_message(payload.as_ref()),
+        });
+        if panic.is_some() {
+            metrics.record_error();
+        }
+
+        (metrics, count, panic)
+    }
+}
+
+/// Files up to this size are stored inline in a [`DirectorySnapshot`]
+const SNAPSHOT_INLINE_LIMIT: u64 = 64 * 1024;
+
+/// Where a snapshotted file's content lives
+#[derive(Debug)]
+enum SnapshotContent {
+    Inline(Vec<u8>),
+    Stored(PathBuf),
+}
+
+/// One node of a snapshotted tree
+#[derive(Debug)]
+enum SnapshotNode {
+    Dir {
+        permissions: fs::Permissions,
+    },
+    File {
+        content: SnapshotContent,
+        len: u64,
+        permissions: fs::Permissions,
+        modified: Option<SystemTime>,
+    },
+    Symlink {
+        target: PathBuf,
+    },
+}
+
+/// Kind of change reported by [`DirectorySnapshot::diff`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Present now, absent from the snapshot
+    Added,
+    /// In the snapshot, absent now
+    Removed,
+    /// Present in both with different content or type
+    Modified,
+}
+
+/// A path that changed since a [`DirectorySnapshot`] was taken
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangeRecord {
+    /// Path relative to the snapshotted root, `/`-separated
+    pub path: String,
+    /// What changed
+    pub kind: ChangeKind,
+}
+
+/// Point-in-time copy of a directory tree for mutate-and-restore tests
+///
+/// Small files are kept in memory; larger ones are copied into a private
+/// snapshot area that is deleted with the snapshot.
+///
+/// # Example
+/// ```rust,ignore
+/// let snapshot = harness.snapshot(&dataset)?;
+/// mutate(&dataset);
+/// assert_eq!(snapshot.diff(&dataset)?.len(), 3);
+/// snapshot.restore(&dataset)?;
+/// ```
+#[derive(Debug)]
+pub struct DirectorySnapshot {
+    nodes: BTreeMap<String, SnapshotNode>,
+    _area: TempDir,
+}
+
+impl DirectorySnapshot {
+    /// Snapshot `path`, storing large files in a fresh system temp directory
+    pub fn capture(path: &Path) -> Result<Self> {
+        let area = TempDir::new().at_path(&std::env::temp_dir())?;
+        Self::capture_with_area(path, area)
+    }
+
+    fn capture_with_area(path: &Path, area: TempDir) -> Result<Self> {
+        let mut nodes = BTreeMap::new();
+        let mut stored = 0usize;
+
+        for (rel, abs) in walk_tree(path)? {
+            let metadata = fs::symlink_metadata(&abs).at_path(&abs)?;
+            let node = if metadata.file_type().is_symlink() {
+                SnapshotNode::Symlink {
+                    target: fs::read_link(&abs).at_path(&abs)?,
+                }
+            } else if metadata.is_dir() {
+                SnapshotNode::Dir {
+                    permissions: metadata.permissions(),
+                }
+            } else if !metadata.is_file() {
+                // FIFOs and device nodes would block or fail on read
+                continue;
+            } else {
+                let content = if metadata.len() <= SNAPSHOT_INLINE_LIMIT {
+                    SnapshotContent::Inline(fs::read(&abs).at_path(&abs)?)
+                } else {
+                    let copy = area.path().join(format!("{:06}.bin", stored));
+                    stored += 1;
+                    fs::copy(&abs, &copy).at_path(&abs)?;
+                    SnapshotContent::Stored(copy)
+                };
+                SnapshotNode::File {
+                    content,
+                    len: metadata.len(),
+                    permissions: metadata.permissions(),
+                    modified: metadata.modified().ok(),
+                }
+            };
+            nodes.insert(rel, node);
+        }
+
+        Ok(Self { nodes, _area: area })
+    }
+
+    /// Number of files, directories and symlinks recorded (excluding the root)
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the snapshotted directory was empty
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Put `path` back exactly as it was when captured
+    ///
+    /// The current contents of `path` are removed first. Modification times
+    /// are restored on a best-effort basis.
+    pub fn restore(&self, path: &Path) -> Result<()> {
+        if path.exists() {
+            fs::remove_dir_all(path).at_path(path)?;
+        }
+        fs::create_dir_all(path).at_path(path)?;
+
+        // BTreeMap order creates parents before children
+        for (rel, node) in &self.nodes {
+            let target = path.join(rel);
+            match node {
+                SnapshotNode::Dir { .. } => fs::create_dir_all(&target).at_path(&target)?,
+                SnapshotNode::File {
+                    content,
+                    permissions,
+                    modified,
+                    ..
+                } => {
+                    match content {
+                        SnapshotContent::Inline(data) => write_file(&target, data)?,
+                        SnapshotContent::Stored(copy) => {
+                            fs::copy(copy, &target).at_path(&target)?;
+                        }
+                    }
+                    fs::set_permissions(&target, permissions.clone()).at_path(&target)?;
+                    if let Some(modified) = modified {
+                        let _ = fs::File::options()
+                            .write(true)
+                            .open(&target)
+                            .and_then(|f| f.set_modified(*modified));
+                    }
+                }
+                SnapshotNode::Symlink { target: link } => restore_symlink(link, &target)?,
+            }
+        }
+
+        // Directory permissions last, so read-only dirs don't block children
+        for (rel, node) in self.nodes.iter().rev() {
+            if let SnapshotNode::Dir { permissions } = node {
+                let target = path.join(rel);
+                fs::set_permissions(&target, permissions.clone()).at_path(&target)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// List paths added, removed or modified in `path` since the snapshot
+    pub fn diff(&self, path: &Path) -> Result<Vec<ChangeRecord>> {
+        let current: BTreeMap<String, PathBuf> = walk_tree(path)?.into_iter().collect();
+        let mut changes = Vec::new();
+
+        for (rel, node) in &self.nodes {
+            let Some(abs) = current.get(rel) else {
+                changes.push(ChangeRecord {
+                    path: rel.clone(),
+                    kind: ChangeKind::Removed,
+                });
+                continue;
+            };
+            if !node_matches(node, abs)? {
+                changes.push(ChangeRecord {
+                    path: rel.clone(),
+                    kind: ChangeKind::Modified,
+                });
+            }
+        }
+
+        for rel in current.keys() {
+            if !self.nodes.contains_key(rel) {
+                changes.push(ChangeRecord {
+                    path: rel.clone(),
+                    kind: ChangeKind::Added,
+                });
+            }
+        }
+
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(changes)
+    }
+}
+
+/// Whether the entry at `abs` still matches a snapshotted node
+fn node_matches(node: &SnapshotNode, abs: &Path) -> Result<bool> {
+    let metadata = fs::symlink_metadata(abs).at_path(abs)?;
+    Ok(match node {
+        SnapshotNode::Dir { .. } => metadata.is_dir(),
+        SnapshotNode::Symlink { target } => {
+            metadata.file_type().is_symlink() && fs::read_link(abs).at_path(abs)? == *target
+        }
+        SnapshotNode::File { content, len, .. } => {
+            metadata.is_file()
+                && metadata.len() == *len
+                && match content {
+                    SnapshotContent::Inline(data) => fs::read(abs).at_path(abs)? == *data,
+                    SnapshotContent::Stored(copy) => {
+                        fs::read(abs).at_path(abs)? == fs::read(copy).at_path(copy)?
+                    }
+                }
+        }
+    })
+}
+
+#[cfg(unix)]
+fn restore_symlink(link: &Path, target: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(link, target).at_path(target)
+}
+
+#[cfg(not(unix))]
+fn restore_symlink(link: &Path, target: &Path) -> Result<()> {
+    // Without a portable symlink API, fall back to copying the link target
+    fs::copy(target.parent().unwrap_or(target).join(link), target).at_path(target)?;
+    Ok(())
+}
+
+/// Recursively list every entry under `root` (excluding `root` itself)
+///
+/// Symlinks are listed but not followed.
+fn walk_tree(root: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut entries = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir).at_path(&dir)? {
+            let entry = entry.at_path(&dir)?;
+            let path = entry.path();
+            if entry.file_type().at_path(&path)?.is_dir() {
+                stack.push(path.clone());
+            }
+            let rel = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            entries.push((rel, path));
+        }
+    }
+
+    entries.sort();
+    Ok(entries)
+}
+
+/// Why a [`SoakRunner`] stopped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoakStop {
+    /// The full duration elapsed
+    Completed,
+    /// A checkpoint reported an integrity failure and fail-fast was enabled
+    IntegrityFailure,
+    /// The stop flag was raised or the cancellation token cancelled
+    /// (e.g. from a ctrl-c handler)
+    Requested,
+    /// A workload iteration or checkpoint exceeded the phase timeout
+    TimedOut,
+    /// A workload iteration or checkpoint panicked under a phase timeout
+    Panicked,
+}
+
+/// A soak phase that ended the run early, with its failure message
+struct PhaseAbort {
+    stop: SoakStop,
+    message: String,
+}
+
+impl PhaseAbort {
+    /// Abort for a non-completed [`TimeoutResult`] of phase `what`
+    fn from_timeout<R>(what: String, result: TimeoutResult<R>) -> Self {
+        match result {
+            TimeoutResult::TimedOut { elapsed } => Self {
+                stop: SoakStop::TimedOut,
+                message: format!("{} timed out after {:?}", what, elapsed),
+            },
+            TimeoutResult::Panicked(msg) => Self {
+                stop: SoakStop::Panicked,
+                message: format!("{} panicked: {}", what, msg),
+            },
+            TimeoutResult::Completed(_) => unreachable!("completed phases do not abort"),
+        }
+    }
+}
+
+/// Timing of a single soak checkpoint
+#[derive(Clone, Debug)]
+pub struct SoakCheckpoint {
+    /// Time since the soak started when the checkpoint ran
+    pub at: Duration,
+    /// Workload iterations completed since the previous checkpoint
+    pub iterations: u64,
+    /// Wall-clock time spent on workload since the previous checkpoint
+    pub interval: Duration,
+    /// Time taken by the verification closure
+    pub verify_duration: Duration,
+    /// Whether the checkpoint's integrity report passed
+    pub passed: bool,
+}
+
+impl SoakCheckpoint {
+    /// Workload throughput over this checkpoint's interval
+    pub fn iterations_per_sec(&self) -> f64 {
+        let secs = self.interval.as_secs_f64();
+        if secs == 0.0 {
+            0.0
+        } else {
+            self.iterations as f64 / secs
+        }
+    }
+}
+
+/// Outcome of a [`SoakRunner`] run
+#[derive(Clone, Debug)]
+pub struct SoakResult {
+    /// Total workload iterations completed
+    pub iterations: u64,
+    /// All checkpoint reports merged together
+    pub report: IntegrityReport,
+    /// Per-checkpoint time series
+    pub checkpoints: Vec<SoakCheckpoint>,
+    /// Total wall-clock time
+    pub elapsed: Duration,
+    /// Why the run ended
+    pub stop: SoakStop,
+}
+
+impl SoakResult {
+    /// True if the soak ran to completion with every checkpoint passing
+    pub fn is_ok(&self) -> bool {
+        self.stop != SoakStop::IntegrityFailure && self.report.is_ok()
+    }
+}
+
+/// Long-running stability test driver
+///
+/// Repeatedly invokes a workload for a wall-clock budget and runs a
+/// verification closure at every checkpoint. A final checkpoint always runs
+/// before returning, so the last iterations are verified too.
+///
+/// # Example
+/// ```rust,ignore
+/// let result = SoakRunner::new(Duration::from_secs(30 * 60))
+///     .checkpoint_every(Duration::from_secs(5 * 60))
+///     .fail_fast()
+///     .run(|_i| ingest_cycle(&dir), || verify_extraction(&dir));
+/// assert!(result.is_ok(), "{}", result.report.summary());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SoakRunner {
+    duration: Duration,
+    checkpoint_interval: Duration,
+    fail_fast: bool,
+    phase_timeout: Option<Duration>,
+    cancellation: CancellationToken,
+}
+
+impl SoakRunner {
+    /// Soak for `duration`, checkpointing only at the end
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            checkpoint_interval: duration,
+            fail_fast: false,
+            phase_timeout: None,
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Run the verification closure every `interval`
+    pub fn checkpoint_every(mut self, interval: Duration) -> Self {
+        self.checkpoint_interval = interval;
+        self
+    }
+
+    /// Stop at the first checkpoint whose report fails
+    pub fn fail_fast(mut self) -> Self {
+        self.fail_fast = true;
+        self
+    }
+
+    /// Limit each workload iteration and checkpoint to `limit`
+    ///
+    /// Only enforced by [`run_bounded`](Self::run_bounded); [`run`](Self::run)
+    /// calls the closures on the calling thread, which cannot be interrupted,
+    /// and ignores it.
+    pub fn phase_timeout(mut self, limit: Duration) -> Self {
+        self.phase_timeout = Some(limit);
+        self
+    }
+
+    /// Flag that stops the soak gracefully when set to `true`
+    ///
+    /// Hook this up to a ctrl-c handler to end long soaks early while still
+    /// running the final checkpoint.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.cancellation.flag()
+    }
+
+    /// Stop gracefully once `token` is cancelled
+    ///
+    /// Checked before every workload iteration, like the
+    /// [`stop_flag`](Self::stop_flag), which then shares the token's flag.
+    /// The soak ends with [`SoakStop::Requested`] after a final checkpoint,
+    /// so the result still covers every completed iteration.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Run `workload` (given the iteration index) until the budget ends
+    ///
+    /// The closures run on the calling thread and the
+    /// [`phase_timeout`](Self::phase_timeout) is ignored; use
+    /// [`run_bounded`](Self::run_bounded) to enforce it.
+    pub fn run<W, V>(&self, mut workload: W, mut verify: V) -> SoakResult
+    where
+        W: FnMut(u64),
+        V: FnMut() -> IntegrityReport,
+    {
+        self.run_phases(
+            &mut |i| {
+                workload(i);
+                Ok(())
+            },
+            &mut || Ok(verify()),
+        )
+    }
+
+    /// Like [`run`](Self::run), but enforce the [`phase_timeout`](Self::phase_timeout)
+    ///
+    /// The closures move to a single worker thread that runs every phase,
+    /// while the calling thread waits at most the phase timeout for each
+    /// one. A hung or panicking phase ends the soak with
+    /// [`SoakStop::TimedOut`] or [`SoakStop::Panicked`] and a failure in the
+    /// report instead of hanging the job. A timed-out worker is left running
+    /// detached, so no final checkpoint follows it. Without a phase timeout
+    /// this behaves like `run`.
+    pub fn run_bounded<W, V>(&self, mut workload: W, mut verify: V) -> SoakResult
+    where
+        W: FnMut(u64) + Send + 'static,
+        V: FnMut() -> IntegrityReport + Send + 'static,
+    {
+        let Some(limit) = self.phase_timeout else {
+            return self.run(workload, verify);
+        };
+
+        // `None` runs a checkpoint, `Some(i)` workload iteration `i`
+        let (phase_tx, phase_rx) = mpsc::channel::<Option<u64>>();
+        let (done_tx, done_rx) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name("testkit-soak".to_string())
+            .spawn(move || {
+                for phase in phase_rx {
+                    let outcome = panic::catch_unwind(AssertUnwindSafe(|| match phase {
+                        Some(i) => {
+                            workload(i);
+                            None
+                        }
+                        None => Some(verify()),
+                    }));
+                    let panicked = outcome.is_err();
+                    let outcome = outcome.map_err(|payload| panic_message(payload.as_ref()));
+                    if done_tx.send(outcome).is_err() || panicked {
+                        break;
+                    }
+                }
+            });
+        let spawn_error = worker.as_ref().err().map(|e| e.to_string());
+
+        let watch = |phase: Option<u64>| {
+            let start = Instant::now();
+            if phase_tx.send(phase).is_err() {
+                let reason = spawn_error.as_deref().unwrap_or("worker thread exited");
+                return TimeoutResult::Panicked(format!("soak worker unavailable: {}", reason));
+            }
+            match done_rx.recv_timeout(limit) {
+                Ok(Ok(report)) => TimeoutResult::Completed(report),
+                Ok(Err(msg)) => TimeoutResult::Panicked(msg),
+                Err(RecvTimeoutError::Timeout) => timeout::timed_out(limit, start.elapsed()),
+                Err(RecvTimeoutError::Disconnected) => {
+                    TimeoutResult::Panicked("worker thread exited without a result".to_string())
+                }
+            }
+        };
+        let result = self.run_phases(
+            &mut |i| match watch(Some(i)) {
+                TimeoutResult::Completed(_) => Ok(()),
+                other => Err(PhaseAbort::from_timeout(
+                    format!("soak workload iteration {}", i),
+                    other,
+                )),
+            },
+            &mut || match watch(None) {
+                TimeoutResult::Completed(report) => Ok(report.unwrap_or_default()),
+                other => Err(PhaseAbort::from_timeout(
+                    "soak checkpoint".to_string(),
+                    other,
+                )),
+            },
+        );
+
+        // Closing the channel ends the worker; a timed-out one stays detached
+        drop(phase_tx);
+        if result.stop != SoakStop::TimedOut {
+            if let Ok(worker) = worker {
+                let _ = worker.join();
+            }
+        }
+        result
+    }
+
+    fn run_phases(
+        &self,
+        workload: &mut dyn FnMut(u64) -> std::result::Result<(), PhaseAbort>,
+        verify: &mut dyn FnMut() -> std::result::Result<IntegrityReport, PhaseAbort>,
+    ) -> SoakResult {
+        let start = Instant::now();
+        let mut report = IntegrityReport::new();
+        let mut checkpoints = Vec::new();
+        let mut iterations = 0u64;
+        let mut since_checkpoint = 0u64;
+        let mut interval_start = Instant::now();
+
+        let mut checkpoint = |iterations: u64, interval: Duration, report: &mut IntegrityReport| {
+            let verify_start = Instant::now();
+            let checkpoint_report = verify()?;
+            let passed = checkpoint_report.is_ok();
+            report.merge(&checkpoint_report);
+            checkpoints.push(SoakCheckpoint {
+                at: start.elapsed(),
+                iterations,
+                interval,
+                verify_duration: verify_start.elapsed(),
+                passed,
+            });
+            Ok::<_, PhaseAbort>(passed)
+        };
+
+        let mut stop = loop {
+            if self.cancellation.is_cancelled() {
+                break SoakStop::Requested;
+            }
+            if start.elapsed() >= self.duration {
+                break SoakStop::Completed;
+            }
+
+            if let Err(abort) = workload(iterations) {
+                report.fail(abort.message);
+                break abort.stop;
+            }
+            iterations += 1;
+            since_checkpoint += 1;
+
+            if interval_start.elapsed() >= self.checkpoint_interval {
+                let passed =
+                    match checkpoint(since_checkpoint, interval_start.elapsed(), &mut report) {
+                        Ok(passed) => passed,
+                        Err(abort) => {
+                            report.fail(abort.message);
+                            break abort.stop;
+                        }
+                    };
+                since_checkpoint = 0;
+                interval_start = Instant::now();
+                if !passed && self.fail_fast {
+                    break SoakStop::IntegrityFailure;
+                }
+            }
+        };
+
+        // Aborted phases may still hold the closures, so skip the final checkpoint
+        let finished = matches!(stop, SoakStop::Completed | SoakStop::Requested);
+        if finished && (since_checkpoint > 0 || iterations == 0) {
+            match checkpoint(since_checkpoint, interval_start.elapsed(), &mut report) {
+                Ok(passed) if !passed && self.fail_fast => stop = SoakStop::IntegrityFailure,
+                Ok(_) => {}
+                Err(abort) => {
+                    report.fail(abort.message);
+                    stop = abort.stop;
+                }
+            }
+        }
+
+        SoakResult {
+            iterations,
+            report,
+            checkpoints,
+            elapsed: start.elapsed(),
+            stop,
+        }
+    }
+}
+
+/// A mismatch found by [`DifferentialRunner`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DifferentialFailure<I> {
+    /// Seed passed to [`DifferentialRunner::run`]
+    pub seed: u64,
+    /// Iteration at which the mismatch occurred
+    pub iteration: u64,
+    /// Generated input that produced the mismatch
+    pub input: I,
+    /// Comparator message
+    pub message: String,
+}
+
+/// Outcome of [`DifferentialRunner::run`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DifferentialResult<I> {
+    /// Iterations whose outputs matched
+    pub passed: u64,
+    /// First mismatch, if any; the run stops there
+    pub failure: Option<DifferentialFailure<I>>,
+}
+
+impl<I> DifferentialResult<I> {
+    /// True if every iteration matched
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Generate-run-compare loop for checking an implementation against a reference
+///
+/// Each iteration draws its input from an RNG seeded by `(seed, iteration)`,
+/// so a failure reported by [`run`](Self::run) can be reproduced on its own
+/// with [`replay`](Self::replay).
+///
+/// # Example
+/// ```rust,ignore
+/// let runner = DifferentialRunner::new(
+///     |rng| (random_sparse_vec(rng, 10_000, 200), random_sparse_vec(rng, 10_000, 200)),
+///     |(a, b)| sparse_dot(a, b),
+///     |(a, b)| simd_dot(a, b),
+///     |expected, actual| if expected == actual { Ok(()) } else { Err(format!("{expected} != {actual}")) },
+/// );
+/// let result = runner.run(10_000, 42);
+/// assert!(result.is_ok(), "{:?}", result.failure);
+/// ```
+#[allow(clippy::type_complexity)]
+pub struct DifferentialRunner<I, O> {
+    generate: Box<dyn Fn(&mut StdRng) -> I>,
+    reference: Box<dyn Fn(&I) -> O>,
+    candidate: Box<dyn Fn(&I) -> O>,
+    compare: Box<dyn Fn(&O, &O) -> std::result::Result<(), String>>,
+}
+
+impl<I, O> DifferentialRunner<I, O> {
+    /// Create a runner from an input generator, the reference and candidate
+    /// implementations, and a comparator returning a mismatch message
+    pub fn new(
+        generate: impl Fn(&mut StdRng) -> I + 'static,
+        reference: impl Fn(&I) -> O + 'static,
+        candidate: impl Fn(&I) -> O + 'static,
+        compare: impl Fn(&O, &O) -> std::result::Result<(), String> + 'static,
+    ) -> Self {
+        Self {
+            generate: Box::new(generate),
+            reference: Box::new(reference),
+            candidate: Box::new(candidate),
+            compare: Box::new(compare),
+        }
+    }
+
+    /// Run up to `iterations` cases, stopping at the first mismatch
+    pub fn run(&self, iterations: u64, seed: u64) -> DifferentialResult<I> {
+        let mut passed = 0;
+        for iteration in 0..iterations {
+            if let Err(failure) = self.replay(seed, iteration) {
+                return DifferentialResult {
+                    passed,
+                    failure: Some(failure),
+                };
+            }
+            passed += 1;
+        }
+        DifferentialResult {
+            passed,
+            failure: None,
+        }
+    }
+
+    /// Regenerate and check the single case `(seed, iteration)`
+    pub fn replay(
+        &self,
+        seed: u64,
+        iteration: u64,
+    ) -> std::result::Result<(), DifferentialFailure<I>> {
+        let mut rng = StdRng::seed_from_u64(
+            seed ^ iteration
+                .wrapping_add(1)
+                .wrapping_mul(0x9E37_79B9_7F4A_7C15),
+        );
+        let input = (self.generate)(&mut rng);
+        self.check(&input).map_err(|message| DifferentialFailure {
+            seed,
+            iteration,
+            input,
+            message,
+        })
+    }
+
+    /// Compare both implementations on `input`
+    pub fn check(&self, input: &I) -> std::result::Result<(), String> {
+        (self.compare)(&(self.reference)(input), &(self.candidate)(input))
+    }
+}
+
+impl<O> DifferentialRunner<Vec<u8>, O> {
+    /// Shrink a failing byte buffer by repeatedly removing chunks
+    ///
+    /// Tries dropping halves, then quarters, and so on down to single bytes,
+    /// keeping any removal that still fails. Returns `input` unchanged if it
+    /// does not fail.
+    pub fn shrink(&self, input: Vec<u8>) -> Vec<u8> {
+        let mut current = input;
+        if self.check(&current).is_ok() {
+            return current;
+        }
+
+        let mut chunk = current.len().div_ceil(2);
+        while chunk > 0 {
+            let mut start = 0;
+            let mut shrunk = false;
+            while start < current.len() {
+                let end = (start + chunk).min(current.len());
+                let mut candidate = current[..start].to_vec();
+                candidate.extend_from_slice(&current[end..]);
+                if self.check(&candidate).is_err() {
+                    current = candidate;
+                    shrunk = true;
+                } else {
+                    start = end;
+                }
+            }
+            if !shrunk {
+                chunk /= 2;
+            }
+        }
+        current
+    }
+}
+
+/// Retrieval accuracy for one bundle size in a [`CapacityReport`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapacityPoint {
+    /// Number of vectors bundled together
+    pub k: usize,
+    /// Fraction of constituents that out-scored every distractor
+    pub accuracy: f64,
+    /// Mean cosine between the bundle and its constituents
+    pub mean_member_similarity: f64,
+    /// Mean of the per-trial maximum distractor cosine
+    pub mean_max_distractor_similarity: f64,
+}
+
+/// Outcome of [`capacity_probe`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapacityReport {
+    /// Vector dimensionality
+    pub dims: usize,
+    /// Non-zeros per vector
+    pub sparsity: usize,
+    /// Trials per bundle size
+    pub trials: usize,
+    /// Accuracy per bundle size, in increasing `k`
+    pub points: Vec<CapacityPoint>,
+}
+
+impl CapacityReport {
+    /// Largest `k` whose accuracy is at least `threshold`
+    ///
+    /// Scans upward and stops at the first `k` that falls below the
+    /// threshold, so later noisy recoveries are ignored.
+    pub fn capacity(&self, threshold: f64) -> Option<usize> {
+        self.points
+            .iter()
+            .take_while(|p| p.accuracy >= threshold)
+            .last()
+            .map(|p| p.k)
+    }
+
+    /// CSV with one row per bundle size
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("k,accuracy,mean_member_similarity,mean_max_distractor_similarity\n");
+        for p in &self.points {
+            csv.push_str(&format!(
+                "{},{:.6},{:.6},{:.6}\n",
+                p.k, p.accuracy, p.mean_member_similarity, p.mean_max_distractor_similarity
+            ));
+        }
+        csv
+    }
+
+    /// Human-readable summary
+    pub fn summary(&self) -> String {
+        let fmt_capacity = |threshold: f64| {
+            self.capacity(threshold)
+                .map_or_else(|| "none".to_string(), |k| k.to_string())
+        };
+        format!(
+            "Capacity probe: dims={}, sparsity={}, trials={}, k=1..{}\n\
+             - Capacity @100%: {}\n\
+             - Capacity @95%: {}\n\
+             - Capacity @50%: {}",
+            self.dims,
+            self.sparsity,
+            self.trials,
+            self.points.len(),
+            fmt_capacity(1.0),
+            fmt_capacity(0.95),
+            fmt_capacity(0.5),
+        )
+    }
+}
+
+/// Measure how many vectors can be bundled before retrieval collapses
+///
+/// For each `k` in `1..=max_items` and each trial, bundles `k` random
+/// vectors and checks whether every constituent is more similar to the
+/// bundle than all of `max_items` fresh distractor vectors.
+///
+/// # Example
+/// ```rust,ignore
+/// let mut rng = StdRng::seed_from_u64(7);
+/// let report = capacity_probe(10_000, 200, 64, 10, &mut rng);
+/// println!("{}", report.summary());
+/// std::fs::write("capacity.csv", report.to_csv())?;
+/// ```
+pub fn capacity_probe(
+    dims: usize,
+    sparsity: usize,
+    max_items: usize,
+    trials: usize,
+    rng: &mut impl rand::Rng,
+) -> CapacityReport {
+    use crate::generators::random_sparse_vec;
+    use embeddenator_vsa::SparseVec;
+
+    let trials = trials.max(1);
+    let mut points = Vec::with_capacity(max_items);
+
+    for k in 1..=max_items {
+        let mut hits = 0usize;
+        let mut member_sim = 0.0;
+        let mut distractor_sim = 0.0;
+
+        for _ in 0..trials {
+            let members: Vec<SparseVec> = (0..k)
+                .map(|_| random_sparse_vec(rng, dims, sparsity))
+                .collect();
+            let distractors: Vec<SparseVec> = (0..max_items)
+                .map(|_| random_sparse_vec(rng, dims, sparsity))
+                .collect();
+            let bundle = SparseVec::bundle_sum_many(members.iter());
+
+            let best_distractor = distractors
+                .iter()
+                .map(|d| bundle.cosine(d))
+                .fold(f64::NEG_INFINITY, f64::max);
+            distractor_sim += best_distractor;
+
+            for member in &members {
+                let sim = bundle.cosine(member);
+                member_sim += sim;
+                if sim > best_distractor {
+                    hits += 1;
+                }
+            }
+        }
+
+        let lookups = (k * trials) as f64;
+        points.push(CapacityPoint {
+            k,
+            accuracy: hits as f64 / lookups,
+            mean_member_similarity: member_sim / lookups,
+            mean_max_distractor_similarity: distractor_sim / trials as f64,
+        });
+    }
+
+    CapacityReport {
+        dims,
+        sparsity,
+        trials,
+        points,
+    }
+}
+
+/// Render a panic payload as a string
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// Lazily generate the (file name, content) pairs of a harness dataset
+///
+/// Cycles through text, JSON and binary templates, repeating each template
+/// 1-10 times, until at least `size_mb` megabytes have been produced.
+fn dataset_entries(size_mb: usize) -> impl Iterator<Item = (String, DatasetContent)> {
+    let target = size_mb * 1024 * 1024;
+    let mut total_size = 0;
+    let mut file_count = 0;
+
+    std::iter::from_fn(move || {
+        if total_size >= target {
+            return None;
+        }
+
+        // Vary file size
+        let multiplier = (file_count % 10) + 1;
+        let (content_type, ext, content) = match file_count % 3 {
+            0 => (
+                "text",
+                "txt",
+                DatasetContent::Repeat(b"This is a text file with some content.\n", multiplier),
+            ),
+            1 => (
+                "json",
+                "json",
+                DatasetContent::Repeat(br#"{"key": "value", "number": 42}"#, multiplier),
+            ),
+            // 0..=255 repeated is exactly the sequential pattern
+            _ => (
+                "binary",
+                "bin",
+                DatasetContent::Pattern(TestDataPattern::Sequential, 256 * multiplier),
+            ),
+        };
+
+        let filename = format!("{}_{:04}.{}", content_type, file_count, ext);
+        total_size += content.len();
+        file_count += 1;
+        Some((filename, content))
+    })
+}
+
+/// Content of one file in the [`TestHarness::create_dataset`] layout
+#[derive(Clone, Copy, Debug)]
+enum DatasetContent {
+    /// `unit` written `repeats` times
+    Repeat(&'static [u8], usize),
+    /// The first `len` bytes of `pattern`
+    Pattern(TestDataPattern, usize),
+}
+
+impl DatasetContent {
+    fn len(self) -> usize {
+        match self {
+            DatasetContent::Repeat(unit, repeats) => unit.len() * repeats,
+            DatasetContent::Pattern(_, len) => len,
+        }
+    }
+
+    /// Stream the content into `writer` without building it in memory
+    fn write_to(self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        match self {
+            DatasetContent::Repeat(unit, repeats) => {
+                (0..repeats).try_for_each(|_| writer.write_all(unit))
+            }
+            DatasetContent::Pattern(pattern, len) => write_pattern_to(writer, len, pattern),
+        }
+    }
+
+    /// Create `path` holding this content
+    fn write_file(self, path: &Path) -> Result<()> {
+        let len = self.len() as u64;
+        let file = fs::File::create(path).map_err(|e| Error::write(path, len, e))?;
+        let mut out = std::io::BufWriter::new(file);
+        self.write_to(&mut out)
+            .and_then(|()| std::io::Write::flush(&mut out))
+            .map_err(|e| Error::write(path, len, e))
+    }
+}
+
+/// Orders returned by [`TestHarness::ingest_orders`]
+const INGEST_ORDERS: [WalkOrder; 5] = [
+    WalkOrder::Sorted,
+    WalkOrder::ReverseSorted,
+    WalkOrder::SeededShuffle(1),
+    WalkOrder::SeededShuffle(2),
+    WalkOrder::SeededShuffle(3),
+];
+
+/// Manifest entry for a seeded file without an exact [`FileKind`](crate::fixtures::FileKind)
+fn untyped_entry(
+    (path, kind, content): (String, SeededFileKind, Vec<u8>),
+) -> (ManifestEntry, Vec<u8>) {
+    let entry = ManifestEntry {
+        path,
+        size: content.len() as u64,
+        kind,
+        file_kind: None,
+        extension_mismatch: false,
+        pattern: None,
+    };
+    (entry, content)
+}
+
+/// Write seeded entries under `root` and save their manifest beside it
+///
+/// Stops before the next file once the budget or file cap in `options` runs
+/// out or its cancellation token is cancelled.
+fn write_seeded(
+    root: &Path,
+    seed: u64,
+    entries: impl IntoIterator<Item = (ManifestEntry, Vec<u8>)>,
+    options: &DatasetOptions,
+) -> Result<GeneratedDataset> {
+    fs::create_dir_all(root).at_path(root)?;
+
+    let started = Instant::now();
+    let mut files = Vec::new();
+    let mut written_bytes = 0u64;
+    let mut outcome = GenerationOutcome::Completed;
+    for (entry, content) in entries {
+        if let Some(stop) = options.stop_outcome(started, files.len(), written_bytes) {
+            outcome = stop;
+            break;
+        }
+        let path = root.join(&entry.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).at_path(parent)?;
+        }
+        write_file(&path, &content)?;
+        written_bytes += entry.size;
+        files.push(entry);
+    }
+
+    let dataset = GeneratedDataset {
+        path: root.to_path_buf(),
+        files: files.len(),
+        outcome,
+        start: GenerationStart::Fresh,
+    };
+    DatasetManifest {
+        seed,
+        files,
+        outcome,
+    }
+    .save(&DatasetManifest::path_for(root))?;
+    Ok(dataset)
+}
+
+/// Write `content` to `path`, mapping failures to a path-carrying [`Error`]
+fn write_file(path: &Path, content: &[u8]) -> Result<()> {
+    fs::write(path, content).map_err(|e| Error::write(path, content.len() as u64, e))
+}
+
+impl Default for TestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        let has_hooks = !self
+            .inner
+            .failure_hooks
+            .lock()
+            .map_or_else(|e| e.into_inner().is_empty(), |hooks| hooks.is_empty());
+        if !self.owner || !std::thread::panicking() || !has_hooks {
+            return;
+        }
+        let dir = artifacts::artifact_dir(self.failure_artifact_dir.as_deref());
+        let dest = dir.join(format!("{}.tar", artifacts::artifact_name("failure")));
+        match self.write_failure_artifacts(&dest) {
+            Ok(path) => eprintln!("failure artifacts written to {}", path.display()),
+            Err(e) => eprintln!("failed to write failure artifacts: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn test_harness_creation() {
+        let harness = TestHarness::new();
+        assert!(harness.temp_dir().exists());
+    }
+
+    #[test]
+    fn test_ingest_orders_cover_same_files() {
+        let harness = TestHarness::new();
+        let dataset = harness.create_directory_structure_seeded("tree", 5);
+        let orders = harness.ingest_orders(&dataset);
+        assert_eq!(orders.len(), INGEST_ORDERS.len());
+        let mut reference = orders[0].1.clone();
+        reference.sort();
+        for (order, files) in &orders {
+            let mut sorted = files.clone();
+            sorted.sort();
+            assert_eq!(sorted, reference, "{:?}", order);
+        }
+        assert_ne!(orders[0].1, orders[2].1);
+    }
+
+    #[test]
+    fn test_create_file() {
+        let harness = TestHarness::new();
+        let path = harness.create_file("test.txt", b"hello");
+        assert!(path.exists());
+        assert_eq!(fs::read(&path).unwrap(), b"hello");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_try_create_file_read_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let harness = TestHarness::new();
+        let dir = harness.temp_dir();
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o555)).unwrap();
+
+        // Privileged users (e.g. root in containers) bypass permission bits
+        let probe = dir.join(".probe");
+        if fs::write(&probe, b"").is_ok() {
+            fs::remove_file(&probe).unwrap();
+            fs::set_permissions(dir, fs::Permissions::from_mode(0o755)).unwrap();
+            eprintln!(
+                "skipping test_try_create_file_read_only: permission bits are not enforced \
+                 for this user (running as root?)"
+            );
+            return;
+        }
+
+        let err = harness
+            .try_create_file("blocked.txt", b"hello")
+            .unwrap_err();
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(matches!(err, Error::Io { .. }));
+        assert_eq!(err.path(), dir.join("blocked.txt"));
+    }
+
+    #[test]
+    fn test_metrics_recording() {
+        let harness = TestHarness::new();
+        harness.record_metric("test_op", Duration::from_millis(100), 1024, 10.0);
+
+        let metrics = harness.metrics();
+        assert_eq!(metrics.operation_times.get("test_op").unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_create_dataset_async_matches_sync() {
+        let sync_harness = TestHarness::new();
+        let async_harness = TestHarness::new();
+
+        let sync_dir = sync_harness.create_dataset(1);
+        let async_dir = async_harness.create_dataset_async(1, 8).await.unwrap();
+
+        let report = crate::integrity::compare_directories_async(&sync_dir, &async_dir, 8)
+            .await
+            .unwrap();
+        assert!(report.is_ok(), "{}", report.summary());
+    }
+
+    #[test]
+    fn test_concurrency_stressor_vsa_ops() {
+        let a = crate::generators::deterministic_sparse_vec(10000, 200, 1);
+        let b = crate::generators::deterministic_sparse_vec(10000, 200, 2);
+        let expected = a.bundle(&b).cosine(&a);
+
+        let result = ConcurrencyStressor::new(8)
+            .with_budget(StressBudget::Iterations(50))
+            .with_seed(42)
+            .run("bundle_cosine", |_thread, _rng| {
+                let bundled = a.bundle(&b);
+                assert_eq!(bundled.cosine(&a), expected);
+            });
+
+        assert!(result.is_ok(), "{:?}", result.panics);
+        assert_eq!(result.total_iterations(), 400);
+        assert_eq!(result.metrics.op_counts["iterations"], 400);
+        assert_eq!(result.timing_stats().count, 400);
+    }
+
+    #[test]
+    fn test_concurrency_stressor_captures_panics() {
+        let result = ConcurrencyStressor::new(4)
+            .with_budget(StressBudget::Iterations(10))
+            .run("panicky", |thread, _rng| {
+                if thread == 2 {
+                    panic!("thread {} failed", thread);
+                }
+            });
+
+        assert!(!result.is_ok());
+        assert_eq!(result.panicked_threads(), vec![2]);
+        assert_eq!(result.panics[0].message, "thread 2 failed");
+        assert_eq!(result.iterations, vec![10, 10, 0, 10]);
+        assert_eq!(result.metrics.error_count, 1);
+    }
+
+    #[test]
+    fn test_soak_runner_checkpoints() {
+        let result = SoakRunner::new(Duration::from_millis(200))
+            .checkpoint_every(Duration::from_millis(50))
+            .run(
+                |_i| std::thread::sleep(Duration::from_millis(1)),
+                || {
+                    let mut report = IntegrityReport::new();
+                    report.pass();
+                    report
+                },
+            );
+
+        assert!(result.is_ok());
+        assert_eq!(result.stop, SoakStop::Completed);
+        assert!(result.iterations > 0);
+        assert!(!result.checkpoints.is_empty());
+        assert_eq!(result.report.checks_total, result.checkpoints.len() as u64);
+        assert_eq!(
+            result.checkpoints.iter().map(|c| c.iterations).sum::<u64>(),
+            result.iterations
+        );
+        assert!(result.checkpoints.windows(2).all(|w| w[0].at < w[1].at));
+    }
+
+    #[test]
+    fn test_soak_runner_fail_fast() {
+        let mut checks = 0;
+        let result = SoakRunner::new(Duration::from_secs(10))
+            .checkpoint_every(Duration::from_millis(10))
+            .fail_fast()
+            .run(
+                |_i| std::thread::sleep(Duration::from_millis(1)),
+                || {
+                    checks += 1;
+                    let mut report = IntegrityReport::new();
+                    if checks == 2 {
+                        report.fail("corrupted");
+                    } else {
+                        report.pass();
+                    }
+                    report
+                },
+            );
+
+        assert_eq!(result.stop, SoakStop::IntegrityFailure);
+        assert_eq!(result.checkpoints.len(), 2);
+        assert!(!result.checkpoints[1].passed);
+    }
+
+    #[test]
+    fn test_soak_runner_stop_flag() {
+        let runner = SoakRunner::new(Duration::from_secs(10));
+        let stop = runner.stop_flag();
+        let result = runner.run(
+            |i| {
+                if i == 9 {
+                    stop.store(true, Ordering::SeqCst);
+                }
+            },
+            IntegrityReport::new,
+        );
+
+        assert_eq!(result.stop, SoakStop::Requested);
+        assert_eq!(result.iterations, 10);
+        assert_eq!(result.checkpoints.len(), 1);
+    }
+
+    #[test]
+    fn test_soak_runner_phase_timeout() {
+        let result = SoakRunner::new(Duration::from_secs(10))
+            .checkpoint_every(Duration::from_millis(10))
+            .phase_timeout(Duration::from_millis(50))
+            .run_bounded(
+                |i| {
+                    if i == 3 {
+                        std::thread::sleep(Duration::from_secs(2));
+                    }
+                },
+                || {
+                    let mut report = IntegrityReport::new();
+                    report.pass();
+                    report
+                },
+            );
+
+        assert_eq!(result.stop, SoakStop::TimedOut);
+        assert_eq!(result.iterations, 3);
+        assert!(!result.is_ok());
+        assert!(result.report.failures[0].contains("iteration 3 timed out"));
+        assert!(result.elapsed < Duration::from_secs(2));
+
+        let result = SoakRunner::new(Duration::from_secs(10))
+            .phase_timeout(Duration::from_secs(5))
+            .run_bounded(|_i| {}, || panic!("verifier crashed"));
+        assert_eq!(result.stop, SoakStop::Panicked);
+        assert!(result.report.failures[0].contains("verifier crashed"));
+    }
+
+    #[test]
+    fn test_soak_runner_bounded_uses_one_worker_thread() {
+        let threads = Arc::new(Mutex::new(std::collections::HashSet::new()));
+        let (work_threads, verify_threads) = (Arc::clone(&threads), Arc::clone(&threads));
+        let result = SoakRunner::new(Duration::from_millis(100))
+            .checkpoint_every(Duration::from_millis(20))
+            .phase_timeout(Duration::from_secs(5))
+            .run_bounded(
+                move |_i| {
+                    work_threads
+                        .lock()
+                        .unwrap()
+                        .insert(std::thread::current().id());
+                    std::thread::sleep(Duration::from_millis(1));
+                },
+                move || {
+                    verify_threads
+                        .lock()
+                        .unwrap()
+                        .insert(std::thread::current().id());
+                    let mut report = IntegrityReport::new();
+                    report.pass();
+                    report
+                },
+            );
+
+        assert_eq!(result.stop, SoakStop::Completed);
+        assert!(result.iterations > 10);
+        assert!(result.is_ok());
+        let threads = threads.lock().unwrap();
+        assert_eq!(threads.len(), 1);
+        assert!(!threads.contains(&std::thread::current().id()));
+    }
+
+    #[test]
+    fn test_capacity_probe_small() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let report = capacity_probe(2000, 40, 12, 4, &mut rng);
+
+        assert_eq!(report.points.len(), 12);
+        assert_eq!(report.points[0].accuracy, 1.0);
+        assert_eq!(report.capacity(1.0).map(|k| k >= 1), Some(true));
+        // Accuracy should not recover meaningfully as k grows
+        for w in report.points.windows(2) {
+            assert!(
+                w[1].accuracy <= w[0].accuracy + 0.15,
+                "accuracy rose from {} to {} at k={}",
+                w[0].accuracy,
+                w[1].accuracy,
+                w[1].k
+            );
+        }
+        assert_eq!(report.to_csv().lines().count(), 13);
+        assert!(report.summary().contains("dims=2000"));
+    }
+
+    #[test]
+    fn test_seeded_datasets_are_reproducible() {
+        use crate::integrity::ChecksumManifest;
+
+        let h1 = TestHarness::new();
+        let h2 = TestHarness::new();
+
+        let a = h1.create_dataset_seeded(1, 42);
+        let b = h2.create_dataset_seeded(1, 42);
+        let c = h2.create_dataset_seeded(1, 43);
+
+        let sum_a = ChecksumManifest::capture(&a).unwrap();
+        assert_eq!(sum_a, ChecksumManifest::capture(&b).unwrap());
+        assert_ne!(sum_a, ChecksumManifest::capture(&c).unwrap());
+
+        let manifest = DatasetManifest::load(&DatasetManifest::path_for(&a)).unwrap();
+        assert_eq!(manifest.seed, 42);
+        assert_eq!(manifest.files.len(), sum_a.len());
+        assert_eq!(manifest.total_bytes(), 1024 * 1024);
+
+        let t1 = h1.create_directory_structure_seeded("tree", 5);
+        let t2 = h2.create_directory_structure_seeded("tree", 5);
+        let t3 = h2.create_directory_structure_seeded("tree_other", 6);
+        let sum_t1 = ChecksumManifest::capture(&t1).unwrap();
+        assert_eq!(sum_t1, ChecksumManifest::capture(&t2).unwrap());
+        assert_ne!(sum_t1, ChecksumManifest::capture(&t3).unwrap());
+    }
+
+    #[test]
+    fn test_seeded_dataset_time_budget_manifest() {
+        let harness = TestHarness::new();
+        let options = DatasetOptions::new().time_budget(Duration::ZERO);
+        let dataset = harness
+            .try_create_dataset_seeded_with(4, 7, &options)
+            .unwrap();
+        assert_eq!(
+            dataset.outcome,
+            GenerationOutcome::Truncated { written_bytes: 0 }
+        );
+
+        let manifest = DatasetManifest::load(&DatasetManifest::path_for(&dataset.path)).unwrap();
+        assert_eq!(manifest.outcome, dataset.outcome);
+        assert!(manifest.files.is_empty());
+        assert_eq!(fs::read_dir(&dataset.path).unwrap().count(), 0);
+
+        // A full run lists exactly the files on disk, with their real sizes
+        let full = harness.create_dataset_seeded(1, 8);
+        let manifest = DatasetManifest::load(&DatasetManifest::path_for(&full)).unwrap();
+        assert_eq!(manifest.outcome, GenerationOutcome::Completed);
+        let on_disk = crate::integrity::ChecksumManifest::capture(&full).unwrap();
+        let mut listed: Vec<(&str, u64)> = manifest
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.size))
+            .collect();
+        let mut found: Vec<(&str, u64)> = on_disk
+            .entries
+            .iter()
+            .map(|(path, c)| (path.as_str(), c.size))
+            .collect();
+        listed.sort();
+        found.sort();
+        assert_eq!(listed, found);
+        assert!(manifest.verify(&full).unwrap().is_ok());
+        fs::write(full.join("stray.txt"), b"x").unwrap();
+        let report = manifest.verify(&full).unwrap();
+        assert_eq!(report.file_mismatches.len(), 1);
+        assert_eq!(
+            report.file_mismatches[0].kind,
+            crate::integrity::MismatchKind::Extra
+        );
+    }
+
+    #[test]
+    fn test_seeded_dataset_cancelled_from_another_thread() {
+        let harness = TestHarness::new();
+        let token = CancellationToken::new();
+        let dir = harness.temp_dir().join("dataset_32mb_seed5");
+        let canceller = {
+            let token = token.clone();
+            let dir = dir.clone();
+            std::thread::spawn(move || {
+                while fs::read_dir(&dir).map_or(0, |entries| entries.count()) < 3 {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                token.cancel();
+            })
+        };
+
+        let options = DatasetOptions::new().with_cancellation(token);
+        let dataset = harness
+            .try_create_dataset_seeded_with(32, 5, &options)
+            .unwrap();
+        canceller.join().unwrap();
+        assert!(dataset.outcome.is_cancelled(), "{:?}", dataset.outcome);
+        assert!(dataset.is_truncated());
+        assert!(dataset.files > 0);
+        assert!(dataset.files < crate::fixtures::seeded_dataset_entries(32, 5).count());
+
+        let manifest = DatasetManifest::load(&DatasetManifest::path_for(&dataset.path)).unwrap();
+        assert_eq!(manifest.outcome, dataset.outcome);
+        assert_eq!(manifest.files.len(), dataset.files);
+        let GenerationOutcome::Cancelled { written_bytes } = dataset.outcome else {
+            unreachable!()
+        };
+        assert_eq!(
+            written_bytes,
+            manifest.files.iter().map(|f| f.size).sum::<u64>()
+        );
+        assert!(manifest.verify(&dataset.path).unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_directory_snapshot_restore() {
+        use crate::integrity::ChecksumManifest;
+
+        let harness = TestHarness::new();
+        let dir = harness.create_directory_structure("tree");
+        harness.create_file("tree/dir1/large.bin", &vec![7u8; 200 * 1024]);
+        let original = ChecksumManifest::capture(&dir).unwrap();
+
+        let snapshot = harness.snapshot(&dir).unwrap();
+        assert!(snapshot.diff(&dir).unwrap().is_empty());
+
+        fs::remove_file(dir.join("file1.txt")).unwrap();
+        fs::write(dir.join("dir2/file4.md"), b"rewritten").unwrap();
+        fs::write(dir.join("dir1/large.bin"), vec![8u8; 200 * 1024]).unwrap();
+        fs::write(dir.join("new.txt"), b"added").unwrap();
+        fs::remove_dir(dir.join("empty_dir")).unwrap();
+
+        let changes: Vec<_> = snapshot
+            .diff(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.path, c.kind))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("dir1/large.bin".to_string(), ChangeKind::Modified),
+                ("dir2/file4.md".to_string(), ChangeKind::Modified),
+                ("empty_dir".to_string(), ChangeKind::Removed),
+                ("file1.txt".to_string(), ChangeKind::Removed),
+                ("new.txt".to_string(), ChangeKind::Added),
+            ]
+        );
+
+        snapshot.restore(&dir).unwrap();
+        assert!(snapshot.diff(&dir).unwrap().is_empty());
+        assert!(dir.join("empty_dir").is_dir());
+        let report = original.verify(&dir).unwrap();
+        assert!(report.is_ok(), "{}", report.summary());
+    }
+
+    #[test]
+    fn test_edge_case_inventory_matches_lstat() {
+        let harness = TestHarness::new();
+        let inventory = harness.create_edge_case_structure("edge");
+        assert!(inventory.has(EdgeCaseKind::EmptyFile));
+        assert!(inventory.has(EdgeCaseKind::EmptyDir));
+        assert!(inventory.has(EdgeCaseKind::OneByteFile));
+
+        for entry in &inventory.created {
+            let path = inventory.path(entry);
+            let meta = fs::symlink_metadata(&path)
+                .unwrap_or_else(|e| panic!("{:?} listed but missing: {}", entry, e));
+            let ft = meta.file_type();
+            match entry.kind {
+                EdgeCaseKind::EmptyFile => assert!(ft.is_file() && meta.len() == 0),
+                EdgeCaseKind::OneByteFile => assert!(ft.is_file() && meta.len() == 1),
+                EdgeCaseKind::TrailingSpaceName => {
+                    assert!(ft.is_file() && entry.path.ends_with(' '))
+                }
+                EdgeCaseKind::EmptyDir => {
+                    assert!(ft.is_dir() && fs::read_dir(&path).unwrap().next().is_none())
+                }
+                EdgeCaseKind::SymlinkToFile => {
+                    assert!(ft.is_symlink() && fs::metadata(&path).unwrap().is_file())
+                }
+                EdgeCaseKind::SymlinkToDir => {
+                    assert!(ft.is_symlink() && fs::metadata(&path).unwrap().is_dir())
+                }
+                EdgeCaseKind::DanglingSymlink => {
+                    assert!(ft.is_symlink() && fs::metadata(&path).is_err())
+                }
+                EdgeCaseKind::RelativeParentSymlink => {
+                    assert!(ft.is_symlink());
+                    assert!(fs::read_link(&path).unwrap().starts_with(".."));
+                    assert!(fs::metadata(&path).unwrap().is_file());
+                }
+                EdgeCaseKind::Fifo => {
+                    #[cfg(unix)]
+                    {
+                        use std::os::unix::fs::FileTypeExt;
+                        assert!(ft.is_fifo());
+                    }
+                }
+            }
+        }
+
+        for (entry, reason) in &inventory.skipped {
+            assert!(!reason.is_empty());
+            assert!(
+                fs::symlink_metadata(inventory.path(entry)).is_err(),
+                "{:?} skipped but present",
+                entry
+            );
+        }
+    }
+
+    #[test]
+    fn test_differential_runner_finds_and_replays_bug() {
+        use crate::generators::{random_sparse_vec, sparse_dot};
+        use embeddenator_vsa::SparseVec;
+        use rand::Rng;
+
+        // Drops the neg/neg term whenever both vectors are dense enough
+        fn buggy_dot(a: &SparseVec, b: &SparseVec) -> i32 {
+            let full = sparse_dot(a, b);
+            let nn = a
+                .neg
+                .iter()
+                .filter(|i| b.neg.binary_search(i).is_ok())
+                .count() as i32;
+            if a.neg.len() + b.neg.len() > 80 {
+                full - nn
+            } else {
+                full
+            }
+        }
+
+        let runner = DifferentialRunner::new(
+            |rng| {
+                let nnz = rng.random_range(10..100);
+                (
+                    random_sparse_vec(rng, 512, nnz),
+                    random_sparse_vec(rng, 512, nnz),
+                )
+            },
+            |(a, b): &(SparseVec, SparseVec)| sparse_dot(a, b),
+            |(a, b): &(SparseVec, SparseVec)| buggy_dot(a, b),
+            |expected, actual| {
+                if expected == actual {
+                    Ok(())
+                } else {
+                    Err(format!("expected {}, got {}", expected, actual))
+                }
+            },
+        );
+
+        let result = runner.run(1_000, 42);
+        let failure = result.failure.expect("bug should be found");
+        assert_eq!(result.passed, failure.iteration);
+
+        let replayed = runner.replay(failure.seed, failure.iteration).unwrap_err();
+        for (replayed, original) in [
+            (&replayed.input.0, &failure.input.0),
+            (&replayed.input.1, &failure.input.1),
+        ] {
+            assert_eq!(replayed.pos, original.pos);
+            assert_eq!(replayed.neg, original.neg);
+        }
+        assert_eq!(replayed.message, failure.message);
+        if failure.iteration > 0 {
+            assert!(runner.replay(42, 0).is_ok());
+        }
+
+        let correct = DifferentialRunner::new(
+            |rng| random_sparse_vec(rng, 256, 20),
+            |v: &SparseVec| sparse_dot(v, v),
+            |v: &SparseVec| (v.pos.len() + v.neg.len()) as i32,
+            |a, b| if a == b { Ok(()) } else { Err(String::new()) },
+        );
+        assert!(correct.run(200, 1).is_ok());
+    }
+
+    #[test]
+    fn test_differential_runner_shrinks_bytes() {
+        use rand::Rng;
+
+        let runner = DifferentialRunner::new(
+            |rng| {
+                (0..rng.random_range(64..256))
+                    .map(|_| rng.random())
+                    .collect()
+            },
+            |bytes: &Vec<u8>| bytes.iter().map(|&b| b as u32).sum::<u32>(),
+            |bytes: &Vec<u8>| {
+                bytes
+                    .iter()
+                    .map(|&b| if b == 0xFF { 0 } else { b as u32 })
+                    .sum()
+            },
+            |a, b| {
+                if a == b {
+                    Ok(())
+                } else {
+                    Err(format!("{} != {}", a, b))
+                }
+            },
+        );
+
+        let failure = runner.run(100, 3).failure.unwrap();
+        assert_eq!(runner.shrink(failure.input), vec![0xFF]);
+        assert_eq!(runner.shrink(vec![1, 2, 3]), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_create_dataset() {
+        let harness = TestHarness::new();
+        let dataset = harness.create_dataset(1); // 1MB
+        assert!(dataset.exists());
+
+        // Check that some files were created
+        let entries: Vec<_> = fs::read_dir(&dataset).unwrap().collect();
+        assert!(!entries.is_empty());
+
+        // Streamed files keep the layout's content
+        assert_eq!(
+            fs::read(dataset.join("text_0000.txt")).unwrap(),
+            b"This is a text file with some content.\n"
+        );
+        let binary: Vec<u8> = (0..=255).collect();
+        assert_eq!(
+            fs::read(dataset.join("binary_0002.bin")).unwrap(),
+            binary.repeat(3)
+        );
+    }
+
+    #[test]
+    fn test_dataset_with_rejects_options_the_layout_ignores() {
+        let harness = TestHarness::new();
+        let rejected = [
+            DatasetOptions::new().self_describing(true),
+            DatasetOptions::new().with_seed(3),
+            DatasetOptions::new().with_size_distribution(FileSizeDistribution::LogNormal {
+                mu: 9.0,
+                sigma: 1.0,
+            }),
+            DatasetOptions::new().resumable(true),
+        ];
+        for options in &rejected {
+            let err = harness.try_create_dataset_with(1, options).unwrap_err();
+            assert!(matches!(err, Error::InvalidSpec { .. }), "{}", err);
+        }
+        assert_eq!(fs::read_dir(harness.temp_dir()).unwrap().count(), 0);
+
+        let options = DatasetOptions::new().max_files(2);
+        let dataset = harness.try_create_dataset_with(1, &options).unwrap();
+        assert_eq!(dataset.files, 2);
+    }
+
+    #[test]
+    fn test_concurrent_harness_use_is_collision_free() {
+        const THREADS: usize = 16;
+        const RECORDS: usize = 50;
+        let harness = TestHarness::new();
+        let expected: Vec<(String, Vec<u8>)> = dataset_entries(1)
+            .map(|(name, content)| {
+                let mut bytes = Vec::new();
+                content.write_to(&mut bytes).unwrap();
+                (name, bytes)
+            })
+            .collect();
+
+        let handle = harness.clone_handle();
+        let outcome = run_with_timeout(Duration::from_secs(120), move || {
+            let workers: Vec<_> = (0..THREADS)
+                .map(|t| {
+                    let harness = handle.clone_handle();
+                    std::thread::spawn(move || {
+                        let dataset = harness.create_dataset(1);
+                        for i in 0..RECORDS {
+                            harness.record_metric(
+                                "shared",
+                                Duration::from_micros(i as u64),
+                                t,
+                                1.0,
+                            );
+                        }
+                        harness.record_metric(&format!("thread{}", t), Duration::ZERO, 0, 0.0);
+                        harness.create_file("contended.bin", &[t as u8; 4096]);
+                        dataset
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|w| w.join().expect("worker panicked"))
+                .collect::<Vec<PathBuf>>()
+        });
+        let datasets = outcome.completed().expect("workers deadlocked or panicked");
+
+        let unique: std::collections::HashSet<&PathBuf> = datasets.iter().collect();
+        assert_eq!(unique.len(), THREADS);
+        for dataset in &datasets {
+            for (name, content) in &expected {
+                assert_eq!(&fs::read(dataset.join(name)).unwrap(), content, "{}", name);
+            }
+            assert_eq!(fs::read_dir(dataset).unwrap().count(), expected.len());
+        }
+
+        let contended = fs::read(harness.temp_dir().join("contended.bin")).unwrap();
+        assert_eq!(contended.len(), 4096);
+        assert!(contended.iter().all(|&b| b == contended[0]));
+        assert!(fs::read_dir(harness.temp_dir()).unwrap().all(|e| !e
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .ends_with(".tmp")));
+
+        let metrics = harness.metrics();
+        assert_eq!(metrics.operation_times["shared"].len(), THREADS * RECORDS);
+        assert_eq!(metrics.operation_times.len(), THREADS + 1);
+    }
+
+    #[test]
+    fn test_every_directory_creator_claims_a_fresh_dir() {
+        let harness = TestHarness::new();
+        let pattern = crate::fixtures::TestDataPattern::Sequential;
+        let pairs = [
+            (
+                harness.create_directory_structure("tree"),
+                harness.create_directory_structure("tree"),
+            ),
+            (
+                harness.create_directory_structure_seeded("seeded", 3),
+                harness.create_directory_structure_seeded("seeded", 3),
+            ),
+            (
+                harness.create_dataset_seeded(1, 3),
+                harness.create_dataset_seeded(1, 3),
+            ),
+            (
+                harness.create_edge_case_structure("edge").root,
+                harness.create_edge_case_structure("edge").root,
+            ),
+            (
+                harness.create_boundary_files("chunks", 64, pattern)[0]
+                    .0
+                    .parent()
+                    .unwrap()
+                    .to_path_buf(),
+                harness.create_boundary_files("chunks", 64, pattern)[0]
+                    .0
+                    .parent()
+                    .unwrap()
+                    .to_path_buf(),
+            ),
+        ];
+        for (first, second) in &pairs {
+            assert_ne!(first, second);
+        }
+        let tree = crate::integrity::ChecksumManifest::capture(&pairs[0].0).unwrap();
+        assert_eq!(
+            tree,
+            crate::integrity::ChecksumManifest::capture(&pairs[0].1).unwrap()
+        );
+        assert_eq!(pairs[0].0, harness.temp_dir().join("tree"));
+        assert!(harness.create_directory_structure("a/b").ends_with("a/b"));
+    }
+
+    #[test]
+    fn test_clone_handle_does_not_run_failure_hooks() {
+        let out = tempfile::TempDir::new().unwrap();
+        let harness = TestHarness::new().with_failure_artifact_dir(out.path());
+        harness.on_failure(|artifacts| {
+            artifacts.add_json("evidence", &1);
+        });
+
+        let handle = harness.clone_handle();
+        let worker = std::thread::spawn(move || {
+            let _handle = handle;
+            panic!("worker failed");
+        });
+        assert!(worker.join().is_err());
+        assert_eq!(fs::read_dir(out.path()).unwrap().count(), 0);
+
+        // The hooks are still there for the owner
+        let tar = harness
+            .write_failure_artifacts(&out.path().join("owner.tar"))
+            .unwrap();
+        let entries = FailureArtifacts::read_archive(&tar).unwrap();
+        assert_eq!(entries[1].0, "evidence.json");
+    }
+}
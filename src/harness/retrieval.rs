@@ -0,0 +1,231 @@
+//! Recall/precision evaluation of similarity search against ground truth
+
+use crate::generators::QueryProbe;
+use embeddenator_vsa::SparseVec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Retrieval quality at one cutoff in a [`RetrievalReport`]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RetrievalPoint {
+    /// Number of results considered
+    pub k: usize,
+    /// Mean fraction of relevant items found in the top `k`
+    pub recall: f64,
+    /// Mean fraction of the top `k` slots holding a relevant item
+    pub precision: f64,
+    /// Mean reciprocal rank of the first relevant item (0 when none in the top `k`)
+    pub mrr: f64,
+}
+
+/// Outcome of [`RetrievalEval::evaluate`]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetrievalReport {
+    /// Queries included in the averages
+    pub evaluated_queries: usize,
+    /// Queries skipped because they have no relevant items
+    pub skipped_queries: usize,
+    /// One entry per requested cutoff, in input order
+    pub points: Vec<RetrievalPoint>,
+}
+
+impl RetrievalReport {
+    /// Metrics for cutoff `k`, if it was evaluated
+    pub fn point(&self, k: usize) -> Option<&RetrievalPoint> {
+        self.points.iter().find(|p| p.k == k)
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// CSV with one row per cutoff
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("k,recall,precision,mrr\n");
+        for p in &self.points {
+            csv.push_str(&format!(
+                "{},{:.6},{:.6},{:.6}\n",
+                p.k, p.recall, p.precision, p.mrr
+            ));
+        }
+        csv
+    }
+}
+
+/// Measures recall@k, precision@k and MRR of a search function
+///
+/// Register queries with the indices of the items relevant to them, then
+/// [`evaluate`](Self::evaluate) calls `search(query, k)` once per query with
+/// the largest requested `k` and scores every cutoff on prefixes of the
+/// returned ranking. Duplicate indices in a ranking count once. Short
+/// rankings are not padded: precision@k always divides by `k`.
+///
+/// Queries without relevant items have undefined recall, so they are left
+/// out of the averages and counted in
+/// [`RetrievalReport::skipped_queries`].
+///
+/// # Example
+/// ```rust,ignore
+/// let mut eval = RetrievalEval::new(|q: &SparseVec, k| {
+///     top_k_similar(q, &corpus, k).into_iter().map(|(i, _)| i).collect()
+/// });
+/// eval.add_query(noisy_copy_of_doc_3, [3]);
+/// let report = eval.evaluate(&[1, 5, 10]);
+/// assert!(report.point(5).unwrap().recall > 0.9);
+/// ```
+pub struct RetrievalEval<F>
+where
+    F: Fn(&SparseVec, usize) -> Vec<usize>,
+{
+    search: F,
+    queries: Vec<(SparseVec, HashSet<usize>)>,
+}
+
+impl<F> RetrievalEval<F>
+where
+    F: Fn(&SparseVec, usize) -> Vec<usize>,
+{
+    /// Create an evaluation of `search`
+    pub fn new(search: F) -> Self {
+        Self {
+            search,
+            queries: Vec::new(),
+        }
+    }
+
+    /// Register a query and the indices relevant to it
+    pub fn add_query(&mut self, query: SparseVec, relevant: impl IntoIterator<Item = usize>) {
+        self.queries.push((query, relevant.into_iter().collect()));
+    }
+
+    /// Register workload probes, each relevant only to its expected index
+    ///
+    /// Misses have no relevant items and are counted as skipped.
+    pub fn add_probes(&mut self, probes: &[QueryProbe]) {
+        for probe in probes {
+            self.add_query(probe.query.clone(), probe.expected);
+        }
+    }
+
+    /// Number of registered queries
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    /// Whether no queries are registered
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// Score the search function at each cutoff in `k_values`
+    pub fn evaluate(&self, k_values: &[usize]) -> RetrievalReport {
+        let max_k = k_values.iter().copied().max().unwrap_or(0);
+        let mut sums = vec![(0.0, 0.0, 0.0); k_values.len()];
+        let mut report = RetrievalReport::default();
+
+        for (query, relevant) in &self.queries {
+            if relevant.is_empty() {
+                report.skipped_queries += 1;
+                continue;
+            }
+            report.evaluated_queries += 1;
+
+            let ranking = (self.search)(query, max_k);
+            for (&k, (recall, precision, mrr)) in k_values.iter().zip(sums.iter_mut()) {
+                let mut seen = HashSet::new();
+                let mut hits = 0usize;
+                let mut first_hit = None;
+                for (rank, index) in ranking.iter().take(k).enumerate() {
+                    if relevant.contains(index) && seen.insert(*index) {
+                        hits += 1;
+                        first_hit.get_or_insert(rank + 1);
+                    }
+                }
+                *recall += hits as f64 / relevant.len() as f64;
+                if k > 0 {
+                    *precision += hits as f64 / k as f64;
+                }
+                *mrr += first_hit.map_or(0.0, |rank| 1.0 / rank as f64);
+            }
+        }
+
+        let n = report.evaluated_queries.max(1) as f64;
+        report.points = k_values
+            .iter()
+            .zip(sums)
+            .map(|(&k, (recall, precision, mrr))| RetrievalPoint {
+                k,
+                recall: recall / n,
+                precision: precision / n,
+                mrr: mrr / n,
+            })
+            .collect();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::deterministic_sparse_vec;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_hand_computed_metrics() {
+        let queries: Vec<_> = (1..=3)
+            .map(|seed| deterministic_sparse_vec(crate::DIM, 20, seed))
+            .collect();
+        let rankings: HashMap<Vec<usize>, Vec<usize>> = [
+            (queries[0].pos.clone(), vec![0, 5, 1, 7]),
+            (queries[1].pos.clone(), vec![4, 3, 9, 3]),
+            (queries[2].pos.clone(), vec![2, 6]),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut eval = RetrievalEval::new(|q: &SparseVec, k| {
+            rankings[&q.pos].iter().copied().take(k).collect()
+        });
+        eval.add_query(queries[0].clone(), [0, 1]);
+        eval.add_query(queries[1].clone(), [3]);
+        eval.add_query(queries[2].clone(), []);
+        assert_eq!(eval.len(), 3);
+
+        let report = eval.evaluate(&[1, 2, 4]);
+        assert_eq!(report.evaluated_queries, 2);
+        assert_eq!(report.skipped_queries, 1);
+
+        // (recall, precision, mrr) averaged over the two scored queries:
+        // k=1: q0 (0.5, 1.0, 1.0), q1 (0.0, 0.0, 0.0)
+        // k=2: q0 (0.5, 0.5, 1.0), q1 (1.0, 0.5, 0.5)
+        // k=4: q0 (1.0, 0.5, 1.0), q1 (1.0, 0.25, 0.5), duplicate 3 counted once
+        let expected = [
+            (1, 0.25, 0.5, 0.5),
+            (2, 0.75, 0.5, 0.75),
+            (4, 1.0, 0.375, 0.75),
+        ];
+        for (k, recall, precision, mrr) in expected {
+            let p = report.point(k).unwrap();
+            assert!((p.recall - recall).abs() < 1e-12, "recall@{}", k);
+            assert!((p.precision - precision).abs() < 1e-12, "precision@{}", k);
+            assert!((p.mrr - mrr).abs() < 1e-12, "mrr@{}", k);
+        }
+
+        let csv = report.to_csv();
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.contains("2,0.750000,0.500000,0.750000"));
+        let parsed: RetrievalReport = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn test_no_scorable_queries() {
+        let mut eval = RetrievalEval::new(|_: &SparseVec, _| vec![0]);
+        eval.add_query(deterministic_sparse_vec(crate::DIM, 20, 1), []);
+        let report = eval.evaluate(&[1]);
+        assert_eq!(report.evaluated_queries, 0);
+        assert_eq!(report.skipped_queries, 1);
+        assert_eq!(report.points[0].recall, 0.0);
+    }
+}
@@ -0,0 +1,486 @@
+//! Retries with backoff for operations that fail transiently
+//!
+//! [`retry`] replaces hand-rolled retry loops around network filesystems and
+//! busy CI runners. All waiting goes through a [`Clock`], so a test can
+//! drive the loop with a [`VirtualClock`] and assert the exact backoff
+//! schedule without sleeping.
+
+use crate::events;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of time for [`retry`]
+pub trait Clock: Send + Sync {
+    /// Current instant
+    fn now(&self) -> Instant;
+    /// Wait for `duration`
+    fn sleep(&self, duration: Duration);
+}
+
+/// The real clock: [`Instant::now`] and [`std::thread::sleep`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// A clock that only moves when told to, recording every sleep
+///
+/// Clones share the same time, so one can be handed to a [`RetryPolicy`]
+/// while the test keeps another to advance and inspect.
+#[derive(Clone, Debug)]
+pub struct VirtualClock {
+    start: Instant,
+    state: Arc<Mutex<VirtualState>>,
+}
+
+#[derive(Debug, Default)]
+struct VirtualState {
+    elapsed: Duration,
+    sleeps: Vec<Duration>,
+}
+
+impl VirtualClock {
+    /// Clock at virtual time zero
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            state: Arc::default(),
+        }
+    }
+
+    /// Move time forward without recording a sleep (e.g. simulated work)
+    pub fn advance(&self, duration: Duration) {
+        self.state.lock().unwrap().elapsed += duration;
+    }
+
+    /// Virtual time since creation
+    pub fn elapsed(&self) -> Duration {
+        self.state.lock().unwrap().elapsed
+    }
+
+    /// Every sleep requested so far, in order
+    pub fn sleeps(&self) -> Vec<Duration> {
+        self.state.lock().unwrap().sleeps.clone()
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.elapsed += duration;
+        state.sleeps.push(duration);
+    }
+}
+
+/// Delay between attempts, before jitter
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backoff {
+    /// The same delay after every failure
+    Fixed(Duration),
+    /// `initial * factor^n` after the n-th failure (from 0), capped at `max`
+    Exponential {
+        initial: Duration,
+        factor: f64,
+        max: Duration,
+    },
+}
+
+impl Backoff {
+    fn base_delay(&self, retry: u32) -> Duration {
+        match *self {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential {
+                initial,
+                factor,
+                max,
+            } => {
+                // In float seconds, so huge exponents saturate at `max`
+                let secs = initial.as_secs_f64() * factor.powf(f64::from(retry));
+                if secs < max.as_secs_f64() {
+                    Duration::from_secs_f64(secs)
+                } else {
+                    max
+                }
+            }
+        }
+    }
+}
+
+/// How often and how patiently [`retry`] calls an operation
+///
+/// # Example
+/// ```rust,ignore
+/// let policy = RetryPolicy::exponential(5, Duration::from_millis(50), Duration::from_secs(2))
+///     .with_jitter(0.5, 7)
+///     .with_retryable(|e: &io::Error| e.kind() != io::ErrorKind::NotFound);
+/// let outcome = retry(policy, || fs::read(&nfs_path));
+/// let bytes = outcome.into_result()?;
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy<E> {
+    max_attempts: u32,
+    backoff: Backoff,
+    jitter: f64,
+    seed: u64,
+    retryable: Arc<dyn Fn(&E) -> bool + Send + Sync>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<E> RetryPolicy<E> {
+    /// Up to `max_attempts` calls, `delay` apart
+    pub fn fixed(max_attempts: u32, delay: Duration) -> Self {
+        Self::with_backoff(max_attempts, Backoff::Fixed(delay))
+    }
+
+    /// Up to `max_attempts` calls, doubling the delay from `initial` up to `max`
+    pub fn exponential(max_attempts: u32, initial: Duration, max: Duration) -> Self {
+        Self::with_backoff(
+            max_attempts,
+            Backoff::Exponential {
+                initial,
+                factor: 2.0,
+                max,
+            },
+        )
+    }
+
+    /// Up to `max_attempts` calls with an arbitrary [`Backoff`]
+    ///
+    /// Every error is retryable, delays have no jitter, and waiting uses the
+    /// [`SystemClock`].
+    ///
+    /// # Panics
+    /// Panics if an exponential `factor` is NaN or below 1.0.
+    pub fn with_backoff(max_attempts: u32, backoff: Backoff) -> Self {
+        if let Backoff::Exponential { factor, .. } = backoff {
+            assert!(
+                factor >= 1.0,
+                "backoff factor must be at least 1.0, got {}",
+                factor
+            );
+        }
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff,
+            jitter: 0.0,
+            seed: 0,
+            retryable: Arc::new(|_| true),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Shorten each delay by a seeded random fraction of up to `jitter`
+    ///
+    /// `jitter` is clamped to `0.0..=1.0` (NaN counts as 0); equal seeds
+    /// give equal schedules.
+    pub fn with_jitter(mut self, jitter: f64, seed: u64) -> Self {
+        self.jitter = if jitter.is_nan() {
+            0.0
+        } else {
+            jitter.clamp(0.0, 1.0)
+        };
+        self.seed = seed;
+        self
+    }
+
+    /// Retry only errors for which `retryable` returns true
+    pub fn with_retryable(
+        mut self,
+        retryable: impl Fn(&E) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retryable = Arc::new(retryable);
+        self
+    }
+
+    /// Measure and wait with `clock` instead of the [`SystemClock`]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Maximum number of calls, the first included
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// The delays [`retry`] waits if every attempt fails, jitter included
+    ///
+    /// Holds `max_attempts - 1` entries; [`retry`] draws the same delays
+    /// lazily instead of collecting them.
+    pub fn schedule(&self) -> Vec<Duration> {
+        self.delays().collect()
+    }
+
+    /// [`schedule`](Self::schedule), one delay at a time from one seeded RNG
+    fn delays(&self) -> impl Iterator<Item = Duration> + '_ {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        (0..self.max_attempts - 1).map(move |retry| {
+            let base = self.backoff.base_delay(retry);
+            if self.jitter > 0.0 {
+                base.mul_f64(1.0 - self.jitter * rng.random::<f64>())
+            } else {
+                base
+            }
+        })
+    }
+}
+
+impl<E> fmt::Debug for RetryPolicy<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .field("jitter", &self.jitter)
+            .field("seed", &self.seed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// One call made by [`retry`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryAttempt<E> {
+    /// Time the call took, by the policy's clock
+    pub duration: Duration,
+    /// The error it returned; `None` for a successful call
+    pub error: Option<E>,
+    /// Pause before the next attempt, if there was one
+    pub backoff: Option<Duration>,
+}
+
+/// Why [`retry`] stopped calling
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryStop {
+    /// The last attempt succeeded
+    Succeeded,
+    /// The last error was not retryable
+    NotRetryable,
+    /// Every allowed attempt failed
+    Exhausted,
+}
+
+/// Outcome of [`retry`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryOutcome<T, E> {
+    /// Value of the successful attempt
+    pub value: Option<T>,
+    /// Every attempt in order
+    pub attempts: Vec<RetryAttempt<E>>,
+    /// Why retrying stopped
+    pub stop: RetryStop,
+}
+
+impl<T, E> RetryOutcome<T, E> {
+    /// True if an attempt succeeded
+    pub fn is_ok(&self) -> bool {
+        self.stop == RetryStop::Succeeded
+    }
+
+    /// Total time spent waiting between attempts
+    pub fn total_backoff(&self) -> Duration {
+        self.attempts.iter().filter_map(|a| a.backoff).sum()
+    }
+
+    /// The successful value, or the error of the last attempt
+    pub fn into_result(mut self) -> Result<T, E> {
+        match self.value {
+            Some(value) => Ok(value),
+            None => Err(self
+                .attempts
+                .pop()
+                .and_then(|a| a.error)
+                .expect("a failed retry records its last error")),
+        }
+    }
+}
+
+/// Call `f` until it succeeds, returns a non-retryable error, or `policy`
+/// runs out of attempts
+///
+/// Waits [`RetryPolicy::schedule`] between attempts and emits a
+/// `harness/retry` event before each retry.
+pub fn retry<T, E>(
+    policy: RetryPolicy<E>,
+    mut f: impl FnMut() -> Result<T, E>,
+) -> RetryOutcome<T, E> {
+    let clock = &policy.clock;
+    let mut delays = policy.delays();
+    let mut attempts = Vec::new();
+    loop {
+        let started = clock.now();
+        let result = f();
+        let duration = clock.now().saturating_duration_since(started);
+        let error = match result {
+            Ok(value) => {
+                attempts.push(RetryAttempt {
+                    duration,
+                    error: None,
+                    backoff: None,
+                });
+                return RetryOutcome {
+                    value: Some(value),
+                    attempts,
+                    stop: RetryStop::Succeeded,
+                };
+            }
+            Err(error) => error,
+        };
+
+        // The schedule has one delay fewer than attempts, so it runs out
+        // exactly on the last one
+        let retryable = (policy.retryable)(&error);
+        let backoff = if retryable { delays.next() } else { None };
+        attempts.push(RetryAttempt {
+            duration,
+            error: Some(error),
+            backoff,
+        });
+        let Some(delay) = backoff else {
+            return RetryOutcome {
+                value: None,
+                attempts,
+                stop: if retryable {
+                    RetryStop::Exhausted
+                } else {
+                    RetryStop::NotRetryable
+                },
+            };
+        };
+
+        events::emit("harness", "retry", || {
+            serde_json::json!({
+                "attempt": attempts.len(),
+                "max_attempts": policy.max_attempts,
+                "backoff_ms": delay.as_secs_f64() * 1e3,
+            })
+        });
+        clock.sleep(delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_schedule_with_seeded_jitter() {
+        let clock = VirtualClock::new();
+        let ms = Duration::from_millis;
+        let plain = RetryPolicy::<String>::exponential(6, ms(10), ms(100));
+        assert_eq!(plain.schedule(), [ms(10), ms(20), ms(40), ms(80), ms(100)]);
+
+        let policy = plain.with_jitter(0.5, 42).with_clock(clock.clone());
+        let schedule = policy.schedule();
+        assert_eq!(schedule, policy.schedule());
+        for (jittered, base) in schedule.iter().zip([10, 20, 40, 80, 100]) {
+            assert!(
+                *jittered <= ms(base) && *jittered >= ms(base) / 2,
+                "{:?}",
+                jittered
+            );
+        }
+        assert_ne!(schedule, policy.clone().with_jitter(0.5, 43).schedule());
+
+        let mut calls = 0;
+        let outcome = retry(policy, || {
+            calls += 1;
+            clock.advance(ms(3));
+            if calls < 4 {
+                Err(format!("flake {}", calls))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert!(outcome.is_ok());
+        assert_eq!(clock.sleeps(), schedule[..3]);
+        assert_eq!(
+            clock.elapsed(),
+            ms(12) + schedule[..3].iter().sum::<Duration>()
+        );
+        assert_eq!(outcome.total_backoff(), schedule[..3].iter().sum());
+        assert_eq!(outcome.attempts.len(), 4);
+        assert_eq!(outcome.attempts[1].error.as_deref(), Some("flake 2"));
+        assert!(outcome.attempts.iter().all(|a| a.duration == ms(3)));
+        assert_eq!(outcome.into_result(), Ok(4));
+    }
+
+    #[test]
+    fn test_non_retryable_error_short_circuits() {
+        let clock = VirtualClock::new();
+        let policy = RetryPolicy::fixed(5, Duration::from_secs(1))
+            .with_retryable(|e: &&str| *e != "fatal")
+            .with_clock(clock.clone());
+
+        let mut errors = ["busy", "fatal", "busy"].into_iter();
+        let outcome: RetryOutcome<(), _> = retry(policy.clone(), || Err(errors.next().unwrap()));
+        assert_eq!(outcome.stop, RetryStop::NotRetryable);
+        assert_eq!(outcome.attempts.len(), 2);
+        assert_eq!(outcome.attempts[1].backoff, None);
+        assert_eq!(clock.sleeps(), [Duration::from_secs(1)]);
+        assert_eq!(outcome.into_result(), Err("fatal"));
+
+        let exhausted: RetryOutcome<(), _> = retry(policy, || Err("busy"));
+        assert_eq!(exhausted.stop, RetryStop::Exhausted);
+        assert_eq!(exhausted.attempts.len(), 5);
+        assert_eq!(clock.sleeps().len(), 1 + 4);
+    }
+
+    #[test]
+    fn test_huge_attempt_counts_stay_lazy_and_capped() {
+        let clock = VirtualClock::new();
+        let max = Duration::from_secs(30);
+        let policy = RetryPolicy::<()>::exponential(u32::MAX, Duration::from_millis(1), max)
+            .with_jitter(f64::NAN, 1)
+            .with_clock(clock.clone());
+        assert_eq!(
+            policy.backoff.base_delay(u32::MAX - 1),
+            max,
+            "exponent must not wrap"
+        );
+
+        let mut calls = 0;
+        let outcome = retry(policy, || {
+            calls += 1;
+            if calls < 40 {
+                Err(())
+            } else {
+                Ok(())
+            }
+        });
+        assert!(outcome.is_ok());
+        assert_eq!(clock.sleeps().last(), Some(&max));
+    }
+
+    #[test]
+    #[should_panic(expected = "backoff factor must be at least 1.0")]
+    fn test_shrinking_backoff_factor_is_rejected() {
+        RetryPolicy::<()>::with_backoff(
+            3,
+            Backoff::Exponential {
+                initial: Duration::from_millis(1),
+                factor: -2.0,
+                max: Duration::from_secs(1),
+            },
+        );
+    }
+}
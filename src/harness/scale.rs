@@ -0,0 +1,581 @@
+//! End-to-end scale tests: generate, ingest, extract and verify a dataset
+//!
+//! [`ScaleTest`] replaces the dataset setup and throughput math that used to
+//! be repeated in every large-scale benchmark, so integration tests and
+//! benches drive the same pipeline.
+
+use crate::cancel::CancellationToken;
+use crate::error::{Error, IoResultExt, Result};
+use crate::fixtures::{
+    ensure_disk_space, estimate_disk_usage, try_create_test_dataset_with, DatasetOptions,
+    GenerationOutcome, TestDataPattern,
+};
+use crate::integrity::{collect_files, compare_directories, IntegrityReport};
+use crate::metrics::{format, stability_probe, MemoryProbe, StabilityReport, TestMetrics};
+use embeddenator_fs::EmbrFS;
+use embeddenator_vsa::ReversibleVSAConfig;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const MB: u64 = 1024 * 1024;
+
+/// One stage of a [`ScaleTest`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ScalePhase {
+    /// Write the dataset to disk
+    Generate,
+    /// Ingest the dataset into an `EmbrFS`
+    Ingest,
+    /// Extract the ingested dataset to a fresh directory
+    Extract,
+    /// Compare the extracted tree against the original
+    Verify,
+}
+
+impl ScalePhase {
+    /// Every phase, in execution order
+    pub const ALL: [ScalePhase; 4] = [
+        ScalePhase::Generate,
+        ScalePhase::Ingest,
+        ScalePhase::Extract,
+        ScalePhase::Verify,
+    ];
+
+    /// Lowercase phase name, also used as the [`TestMetrics`] phase key
+    pub fn name(self) -> &'static str {
+        match self {
+            ScalePhase::Generate => "generate",
+            ScalePhase::Ingest => "ingest",
+            ScalePhase::Extract => "extract",
+            ScalePhase::Verify => "verify",
+        }
+    }
+
+    /// Phase that must run first
+    fn requires(self) -> Option<ScalePhase> {
+        match self {
+            ScalePhase::Generate => None,
+            ScalePhase::Ingest => Some(ScalePhase::Generate),
+            ScalePhase::Extract => Some(ScalePhase::Ingest),
+            ScalePhase::Verify => Some(ScalePhase::Extract),
+        }
+    }
+
+    /// Rough throughput used by [`ScaleTest::dry_run`], in MB/s
+    fn assumed_mbps(self) -> f64 {
+        match self {
+            ScalePhase::Generate => 400.0,
+            ScalePhase::Ingest => 40.0,
+            ScalePhase::Extract => 80.0,
+            ScalePhase::Verify => 500.0,
+        }
+    }
+}
+
+/// Timing of one phase in a [`ScaleTestReport`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhaseReport {
+    /// Which phase ran
+    pub phase: ScalePhase,
+    /// Wall-clock time
+    pub duration: Duration,
+    /// Dataset bytes processed
+    pub bytes: u64,
+    /// `bytes` over `duration`, in MB/s
+    pub throughput_mbps: f64,
+    /// Whether the phase ran past its configured time limit
+    pub over_limit: bool,
+}
+
+/// Whether a [`ScaleTest`] ran all of its selected phases
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ScaleOutcome {
+    /// Every selected phase ran
+    #[default]
+    Completed,
+    /// The cancellation token was cancelled before or during a phase
+    Cancelled {
+        /// Phases that finished before cancellation, in order
+        completed_phases: Vec<ScalePhase>,
+    },
+}
+
+/// Outcome of [`ScaleTest::run`]
+#[derive(Clone, Debug)]
+pub struct ScaleTestReport {
+    /// Requested dataset size
+    pub target_bytes: u64,
+    /// Bytes actually generated
+    pub dataset_bytes: u64,
+    /// Files generated
+    pub files: usize,
+    /// Whether generation finished or hit the `Generate` time limit
+    pub generation: GenerationOutcome,
+    /// Phases that ran, in order
+    pub phases: Vec<PhaseReport>,
+    /// Highest RSS sampled after each phase, if the platform reports it
+    pub peak_rss: Option<usize>,
+    /// Directory comparison from the `Verify` phase
+    pub integrity: Option<IntegrityReport>,
+    /// CPU stability preflight, if one was requested
+    pub stability: Option<StabilityReport>,
+    /// Phase timings and memory samples
+    pub metrics: TestMetrics,
+    /// Whether the run was cancelled part way
+    pub outcome: ScaleOutcome,
+}
+
+impl ScaleTestReport {
+    /// Report for `phase`, if it ran
+    pub fn phase(&self, phase: ScalePhase) -> Option<&PhaseReport> {
+        self.phases.iter().find(|p| p.phase == phase)
+    }
+
+    /// Whether the run stopped early because it was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.outcome, ScaleOutcome::Cancelled { .. })
+    }
+
+    /// True if the run was not cancelled, verification (when run) passed
+    /// and no phase overran its limit
+    pub fn is_ok(&self) -> bool {
+        !self.is_cancelled()
+            && self.integrity.as_ref().is_none_or(IntegrityReport::is_ok)
+            && self.phases.iter().all(|p| !p.over_limit)
+    }
+
+    /// Human-readable summary
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "Scale test: {} files, {} of {} requested{}\n",
+            format::count(self.files as u64),
+            format::bytes(self.dataset_bytes),
+            format::bytes(self.target_bytes),
+            if self.generation.is_cancelled() {
+                " (cancelled)"
+            } else if self.generation.is_truncated() {
+                " (truncated)"
+            } else {
+                ""
+            }
+        );
+        if let ScaleOutcome::Cancelled { completed_phases } = &self.outcome {
+            let names: Vec<&str> = completed_phases.iter().map(|p| p.name()).collect();
+            out.push_str(&format!("  Cancelled after: [{}]\n", names.join(", ")));
+        }
+        if let Some(stability) = &self.stability {
+            out.push_str(&format!("  Stability: {}\n", stability.summary()));
+        }
+        for p in &self.phases {
+            out.push_str(&format!(
+                "  {:<8} {:>10} {:>12}{}\n",
+                p.phase.name(),
+                format::duration_ns(p.duration.as_nanos() as u64),
+                format::bytes_per_sec(p.throughput_mbps * MB as f64),
+                if p.over_limit { "  OVER LIMIT" } else { "" }
+            ));
+        }
+        if let Some(rss) = self.peak_rss {
+            out.push_str(&format!("  Peak RSS: {}\n", format::bytes(rss as u64)));
+        }
+        if let Some(integrity) = &self.integrity {
+            out.push_str(&format!("  Integrity: {}\n", integrity.summary()));
+        }
+        out
+    }
+}
+
+/// Disk and time estimate from [`ScaleTest::dry_run`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScaleEstimate {
+    /// Dataset size that would be generated
+    pub dataset_bytes: u64,
+    /// Files that would be generated
+    pub files: usize,
+    /// Disk needed for the dataset plus, with `Extract`, its extracted copy
+    ///
+    /// Includes block rounding and per-file overhead; see
+    /// [`estimate_disk_usage`].
+    pub disk_bytes: u64,
+    /// Estimated duration of each selected phase
+    pub phases: Vec<(ScalePhase, Duration)>,
+}
+
+impl ScaleEstimate {
+    /// Sum of the per-phase estimates
+    pub fn total_time(&self) -> Duration {
+        self.phases.iter().map(|(_, d)| *d).sum()
+    }
+}
+
+/// End-to-end scale test over a generated dataset
+///
+/// Generates a dataset of the target size, then runs the selected phases in
+/// order, timing each and sampling RSS after it. A phase whose prerequisite
+/// was not selected is skipped; `Generate` always runs.
+///
+/// # Example
+/// ```rust,ignore
+/// let test = ScaleTest::new(20 * 1024 * 1024 * 1024)
+///     .with_phase_time_limit(ScalePhase::Ingest, Duration::from_secs(30 * 60));
+/// println!("{:?}", test.dry_run());
+/// let report = test.run(&ReversibleVSAConfig::default());
+/// assert!(report.is_ok(), "{}", report.summary());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScaleTest {
+    target_bytes: u64,
+    pattern: TestDataPattern,
+    options: DatasetOptions,
+    phases: Vec<ScalePhase>,
+    limits: HashMap<ScalePhase, Duration>,
+    work_dir: Option<PathBuf>,
+    preflight: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl ScaleTest {
+    /// Scale test over a dataset of `target_bytes` (rounded up to whole MB)
+    pub fn new(target_bytes: u64) -> Self {
+        Self {
+            target_bytes,
+            pattern: TestDataPattern::Random,
+            options: DatasetOptions::default(),
+            phases: ScalePhase::ALL.to_vec(),
+            limits: HashMap::new(),
+            work_dir: None,
+            preflight: None,
+            cancellation: None,
+        }
+    }
+
+    /// Generate the dataset with `options` (size distribution, progress, ...)
+    pub fn with_dataset_options(mut self, options: DatasetOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Fill generated files with `pattern`
+    pub fn with_pattern(mut self, pattern: TestDataPattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Run only `phases` (plus `Generate`)
+    pub fn with_phases(mut self, phases: impl IntoIterator<Item = ScalePhase>) -> Self {
+        let mut phases: Vec<ScalePhase> = phases.into_iter().collect();
+        phases.push(ScalePhase::Generate);
+        phases.sort();
+        phases.dedup();
+        self.phases = phases;
+        self
+    }
+
+    /// Flag `phase` as over limit if it runs longer than `limit`
+    ///
+    /// The `Generate` limit becomes the dataset time budget, so generation
+    /// stops early instead of overrunning.
+    pub fn with_phase_time_limit(mut self, phase: ScalePhase, limit: Duration) -> Self {
+        self.limits.insert(phase, limit);
+        self
+    }
+
+    /// Create the dataset and extraction directories under `dir`
+    ///
+    /// Defaults to the system temp directory. Both are removed afterwards.
+    pub fn with_work_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.work_dir = Some(dir.into());
+        self
+    }
+
+    /// Probe CPU timing stability for `duration` before generating
+    ///
+    /// The result is recorded in [`ScaleTestReport::stability`]; the run
+    /// proceeds either way, leaving the decision to the caller.
+    pub fn with_stability_preflight(mut self, duration: Duration) -> Self {
+        self.preflight = Some(duration);
+        self
+    }
+
+    /// Stop at the next phase or file boundary once `token` is cancelled
+    ///
+    /// Generation stops at a file boundary; the other phases are checked
+    /// between phases. The report then carries
+    /// [`ScaleOutcome::Cancelled`] with the metrics and integrity results
+    /// gathered so far.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Phases that will actually run, in order
+    fn selected_phases(&self) -> Vec<ScalePhase> {
+        let mut selected: Vec<ScalePhase> = Vec::new();
+        for phase in ScalePhase::ALL {
+            let wanted = self.phases.contains(&phase);
+            let ready = phase.requires().is_none_or(|req| selected.contains(&req));
+            if wanted && ready {
+                selected.push(phase);
+            }
+        }
+        selected
+    }
+
+    fn size_mb(&self) -> usize {
+        self.target_bytes.div_ceil(MB) as usize
+    }
+
+    /// Estimate disk space and duration without touching the disk
+    ///
+    /// Times assume fixed per-phase throughputs and are only a sanity check
+    /// before committing to a long run.
+    pub fn dry_run(&self) -> ScaleEstimate {
+        let plan = self.options.file_plan(self.size_mb());
+        let dataset_bytes: u64 = plan.iter().map(|(_, size)| *size as u64).sum();
+        let selected = self.selected_phases();
+
+        let dataset_disk = estimate_disk_usage(self.size_mb(), &self.options).estimated_on_disk;
+        let mut disk_bytes = dataset_disk;
+        if selected.contains(&ScalePhase::Extract) {
+            disk_bytes += dataset_disk;
+        }
+        let phases = selected
+            .iter()
+            .map(|&phase| {
+                let secs = dataset_bytes as f64 / MB as f64 / phase.assumed_mbps();
+                (phase, Duration::from_secs_f64(secs))
+            })
+            .collect();
+
+        ScaleEstimate {
+            dataset_bytes,
+            files: plan.len(),
+            disk_bytes,
+            phases,
+        }
+    }
+
+    /// Run the scale test
+    ///
+    /// Panicking wrapper around [`try_run`](Self::try_run).
+    pub fn run(&self, config: &ReversibleVSAConfig) -> ScaleTestReport {
+        self.try_run(config)
+            .unwrap_or_else(|e| panic!("Scale test failed: {}", e))
+    }
+
+    /// Run the scale test, returning the first I/O or ingestion error
+    ///
+    /// Fails with [`Error::DiskSpace`] before generating if the work
+    /// directory has less free space than [`dry_run`](Self::dry_run)
+    /// estimates: the dataset, plus its extracted copy when `Extract` runs.
+    pub fn try_run(&self, config: &ReversibleVSAConfig) -> Result<ScaleTestReport> {
+        let work = match &self.work_dir {
+            Some(dir) => {
+                fs::create_dir_all(dir).at_path(dir)?;
+                tempfile::Builder::new()
+                    .prefix("scale_test_")
+                    .tempdir_in(dir)
+                    .at_path(dir)?
+            }
+            None => tempfile::TempDir::new().at_path(&std::env::temp_dir())?,
+        };
+        let source = work.path().join("source");
+        let extracted = work.path().join("extracted");
+
+        let mut report = ScaleTestReport {
+            target_bytes: self.target_bytes,
+            dataset_bytes: 0,
+            files: 0,
+            generation: GenerationOutcome::Completed,
+            phases: Vec::new(),
+            peak_rss: None,
+            integrity: None,
+            stability: self.preflight.map(stability_probe),
+            metrics: TestMetrics::new("scale_test"),
+            outcome: ScaleOutcome::Completed,
+        };
+        MemoryProbe::sample(&mut report.metrics);
+        let mut embrfs = EmbrFS::new();
+
+        for phase in self.selected_phases() {
+            let token_cancelled = self
+                .cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled);
+            if token_cancelled || report.generation.is_cancelled() {
+                report.outcome = cancelled_outcome(&report);
+                break;
+            }
+            let start = Instant::now();
+            match phase {
+                ScalePhase::Generate => {
+                    let mut options = self.options.clone();
+                    if let Some(&limit) = self.limits.get(&ScalePhase::Generate) {
+                        options = options.time_budget(limit);
+                    }
+                    if let Some(token) = &self.cancellation {
+                        options = options.with_cancellation(token.clone());
+                    }
+                    ensure_disk_space(work.path(), self.dry_run().disk_bytes)?;
+                    let dataset = try_create_test_dataset_with(
+                        &source,
+                        self.size_mb(),
+                        self.pattern,
+                        &options,
+                    )?;
+                    report.files = dataset.files;
+                    report.generation = dataset.outcome;
+                    report.dataset_bytes = tree_bytes(&source)?;
+                }
+                ScalePhase::Ingest => {
+                    embrfs
+                        .ingest_directory(&source, false, config)
+                        .map_err(|e| external_error(&source, e))?;
+                }
+                ScalePhase::Extract => {
+                    EmbrFS::extract(&embrfs.engram, &embrfs.manifest, &extracted, false, config)
+                        .map_err(|e| external_error(&extracted, e))?;
+                }
+                ScalePhase::Verify => {
+                    report.integrity =
+                        Some(compare_directories(&source, &extracted).at_path(&extracted)?);
+                }
+            }
+            let duration = start.elapsed();
+            report
+                .metrics
+                .phase_timings_ns
+                .entry(phase.name().to_string())
+                .or_default()
+                .push(duration.as_nanos() as u64);
+            MemoryProbe::sample(&mut report.metrics);
+
+            let secs = duration.as_secs_f64();
+            report.phases.push(PhaseReport {
+                phase,
+                duration,
+                bytes: report.dataset_bytes,
+                throughput_mbps: if secs > 0.0 {
+                    report.dataset_bytes as f64 / MB as f64 / secs
+                } else {
+                    0.0
+                },
+                over_limit: self
+                    .limits
+                    .get(&phase)
+                    .is_some_and(|&limit| duration > limit),
+            });
+        }
+
+        // Covers a generation cancelled with no phase after it. A token
+        // cancelled after the last phase finished does not count.
+        if report.outcome == ScaleOutcome::Completed && report.generation.is_cancelled() {
+            report.outcome = cancelled_outcome(&report);
+        }
+        report.peak_rss = report.metrics.memory_samples.iter().copied().max();
+        Ok(report)
+    }
+}
+
+/// [`ScaleOutcome::Cancelled`] listing the phases of `report` that completed
+fn cancelled_outcome(report: &ScaleTestReport) -> ScaleOutcome {
+    // A cancelled generation ran, but did not complete
+    let generation_cancelled = report.generation.is_cancelled();
+    ScaleOutcome::Cancelled {
+        completed_phases: report
+            .phases
+            .iter()
+            .map(|p| p.phase)
+            .filter(|&p| p != ScalePhase::Generate || !generation_cancelled)
+            .collect(),
+    }
+}
+
+/// Total size of the regular files under `dir`
+fn tree_bytes(dir: &Path) -> Result<u64> {
+    let mut total = 0;
+    for (_, path) in collect_files(dir).at_path(dir)? {
+        total += fs::metadata(&path).at_path(&path)?.len();
+    }
+    Ok(total)
+}
+
+/// Wrap an error from `embeddenator-fs` with the path being processed
+fn external_error(path: &Path, e: impl Display) -> Error {
+    Error::io(path, std::io::Error::other(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selected_phases_respect_prerequisites() {
+        let test = ScaleTest::new(MB).with_phases([ScalePhase::Ingest, ScalePhase::Verify]);
+        assert_eq!(
+            test.selected_phases(),
+            vec![ScalePhase::Generate, ScalePhase::Ingest]
+        );
+        assert_eq!(
+            ScaleTest::new(MB).selected_phases(),
+            ScalePhase::ALL.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_dry_run_estimates() {
+        let estimate = ScaleTest::new(3 * MB).dry_run();
+        assert_eq!(estimate.dataset_bytes, 3 * MB);
+        let dataset_disk = estimate_disk_usage(3, &DatasetOptions::default()).estimated_on_disk;
+        assert!(dataset_disk > 3 * MB);
+        assert_eq!(estimate.disk_bytes, 2 * dataset_disk);
+        assert_eq!(estimate.phases.len(), 4);
+        assert!(estimate.total_time() > Duration::ZERO);
+
+        let generate_only = ScaleTest::new(3 * MB).with_phases([]).dry_run();
+        assert_eq!(generate_only.disk_bytes, dataset_disk);
+        assert_eq!(generate_only.phases.len(), 1);
+    }
+
+    #[test]
+    fn test_cancelled_report_is_not_ok() {
+        let mut report = ScaleTestReport {
+            target_bytes: MB,
+            dataset_bytes: MB,
+            files: 1,
+            generation: GenerationOutcome::Completed,
+            phases: Vec::new(),
+            peak_rss: None,
+            integrity: None,
+            stability: None,
+            metrics: TestMetrics::new("scale_test"),
+            outcome: ScaleOutcome::Completed,
+        };
+        assert!(report.is_ok());
+
+        report.outcome = cancelled_outcome(&report);
+        assert!(report.is_cancelled());
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn test_token_cancelled_before_a_phase_stops_the_run() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let report = ScaleTest::new(MB)
+            .with_phases([])
+            .with_cancellation(token)
+            .run(&ReversibleVSAConfig::default());
+        assert_eq!(
+            report.outcome,
+            ScaleOutcome::Cancelled {
+                completed_phases: Vec::new()
+            }
+        );
+        assert!(report.phases.is_empty());
+        assert!(!report.is_ok());
+    }
+}
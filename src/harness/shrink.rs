@@ -0,0 +1,332 @@
+//! Delta debugging over the files of a failing dataset
+//!
+//! [`DatasetShrinker`] looks for the smallest subset of a dataset's files
+//! for which a predicate ("does the bug still reproduce?") holds. Each
+//! candidate subset is materialized in a scratch directory with hardlinks
+//! where the filesystem allows, so probing a multi-gigabyte dataset costs
+//! directory entries rather than copies. The persisted result is a real
+//! copy, independent of the original.
+
+use super::artifacts;
+use crate::error::{Error, IoResultExt, Result};
+use crate::progress::{ProgressOptions, ProgressSink};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Predicate calls allowed unless [`DatasetShrinker::with_max_steps`] changes it
+pub const DEFAULT_SHRINK_STEPS: usize = 256;
+
+/// Minimizes a failing dataset with delta debugging (ddmin)
+///
+/// # Example
+/// ```rust,ignore
+/// let result = DatasetShrinker::new()
+///     .with_max_steps(500)
+///     .shrink(&dataset, |dir| ingest(dir).is_err());
+/// println!("{} file(s) reproduce it: {:?}", result.files.len(), result.files);
+/// println!("kept at {}", result.minimized_dir.unwrap().display());
+/// ```
+#[derive(Clone, Debug)]
+pub struct DatasetShrinker {
+    max_steps: usize,
+    artifact_dir: Option<PathBuf>,
+    progress: ProgressOptions,
+}
+
+/// Outcome of [`DatasetShrinker::shrink`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShrinkResult {
+    /// Whether the predicate held for the full dataset
+    pub reproduced: bool,
+    /// Smallest reproducing subset found, as sorted relative paths
+    pub files: Vec<PathBuf>,
+    /// Number of files in the original dataset
+    pub original_files: usize,
+    /// Predicate calls made, the full dataset included
+    pub steps: usize,
+    /// Whether the step budget ran out before the subset was 1-minimal
+    pub budget_exhausted: bool,
+    /// Persistent copy of `files`, if the dataset reproduced
+    pub minimized_dir: Option<PathBuf>,
+}
+
+impl DatasetShrinker {
+    /// Shrinker with [`DEFAULT_SHRINK_STEPS`] and no progress reporting
+    pub fn new() -> Self {
+        Self {
+            max_steps: DEFAULT_SHRINK_STEPS,
+            artifact_dir: None,
+            progress: ProgressOptions::new(),
+        }
+    }
+
+    /// Cap the number of predicate calls
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    /// Persist the minimized dataset under `dir` instead of the
+    /// [`FAILURE_ARTIFACTS_ENV`](super::FAILURE_ARTIFACTS_ENV) directory
+    pub fn with_artifact_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.artifact_dir = Some(dir.into());
+        self
+    }
+
+    /// Report predicate calls (phase `shrink`) against the step budget
+    pub fn with_progress(mut self, sink: impl ProgressSink + 'static) -> Self {
+        self.progress = self.progress.with_sink(sink);
+        self
+    }
+
+    /// Set the full progress configuration
+    pub fn with_progress_options(mut self, progress: ProgressOptions) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Minimize `dataset_dir` to the smallest file set where `predicate` holds
+    ///
+    /// The predicate first runs on `dataset_dir` itself; if it does not hold
+    /// there, nothing is shrunk. Otherwise the result is kept in a
+    /// `shrunk-<pid>-<millis>` directory resolved like failure archives: the
+    /// [`with_artifact_dir`](Self::with_artifact_dir) directory, else
+    /// `$TESTKIT_ARTIFACT_DIR`, else the system temp directory.
+    ///
+    /// Candidate directories share their files with `dataset_dir` through
+    /// hardlinks, so the predicate must only read its input: writing to a
+    /// file would change the original dataset too.
+    ///
+    /// # Panics
+    /// Panics if the dataset cannot be listed or a subset cannot be built.
+    pub fn shrink(&self, dataset_dir: &Path, predicate: impl Fn(&Path) -> bool) -> ShrinkResult {
+        self.try_shrink(dataset_dir, predicate)
+            .unwrap_or_else(|e| panic!("Failed to shrink dataset: {}", e))
+    }
+
+    /// Fallible variant of [`shrink`](Self::shrink)
+    pub fn try_shrink(
+        &self,
+        dataset_dir: &Path,
+        predicate: impl Fn(&Path) -> bool,
+    ) -> Result<ShrinkResult> {
+        let mut files: Vec<PathBuf> = crate::integrity::collect_files(dataset_dir)
+            .at_path(dataset_dir)?
+            .into_iter()
+            .map(|(rel, _)| PathBuf::from(rel))
+            .collect();
+        files.sort();
+        let tracker = self.progress.tracker(Some(self.max_steps as u64), "shrink");
+        let scratch = TempDir::new().at_path(&std::env::temp_dir())?;
+
+        let mut search = Search {
+            source: dataset_dir,
+            files: &files,
+            scratch: scratch.path(),
+            predicate: &predicate,
+            steps: 1,
+            max_steps: self.max_steps,
+            tested: HashMap::new(),
+        };
+        let reproduced = predicate(dataset_dir);
+        tracker.advance(1);
+        if !reproduced {
+            tracker.finish();
+            return Ok(ShrinkResult {
+                reproduced,
+                files: Vec::new(),
+                original_files: files.len(),
+                steps: 1,
+                budget_exhausted: false,
+                minimized_dir: None,
+            });
+        }
+
+        let (minimal, budget_exhausted) = search.ddmin(|n| tracker.advance(n))?;
+        tracker.finish();
+
+        let dest = artifacts::artifact_dir(self.artifact_dir.as_deref())
+            .join(artifacts::artifact_name("shrunk"));
+        let kept: Vec<PathBuf> = minimal.iter().map(|&i| files[i].clone()).collect();
+        copy_subset(dataset_dir, &kept, &dest, false)?;
+        Ok(ShrinkResult {
+            reproduced,
+            files: kept,
+            original_files: files.len(),
+            steps: search.steps,
+            budget_exhausted,
+            minimized_dir: Some(dest),
+        })
+    }
+}
+
+impl Default for DatasetShrinker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Search<'a, P> {
+    source: &'a Path,
+    files: &'a [PathBuf],
+    scratch: &'a Path,
+    predicate: &'a P,
+    steps: usize,
+    max_steps: usize,
+    tested: HashMap<Vec<usize>, bool>,
+}
+
+impl<P: Fn(&Path) -> bool> Search<'_, P> {
+    /// Zeller's ddmin over file indices; returns the subset and whether the
+    /// budget ran out first
+    fn ddmin(&mut self, mut advance: impl FnMut(u64)) -> Result<(Vec<usize>, bool)> {
+        let mut current: Vec<usize> = (0..self.files.len()).collect();
+        let mut n = 2;
+        while current.len() >= 2 {
+            let chunks: Vec<Vec<usize>> = current
+                .chunks(current.len().div_ceil(n))
+                .map(<[usize]>::to_vec)
+                .collect();
+            let complements = chunks.iter().enumerate().map(|(skip, _)| {
+                chunks
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| i != skip)
+                    .flat_map(|(_, c)| c.iter().copied())
+                    .collect::<Vec<usize>>()
+            });
+            let candidates: Vec<(Vec<usize>, usize)> = chunks
+                .iter()
+                .cloned()
+                .map(|c| (c, 2))
+                .chain(complements.map(|c| (c, n.saturating_sub(1).max(2))))
+                .collect();
+
+            let mut reduced = false;
+            for (candidate, next_n) in candidates {
+                match self.test(&candidate, &mut advance)? {
+                    Some(true) => {
+                        current = candidate;
+                        n = next_n;
+                        reduced = true;
+                        break;
+                    }
+                    Some(false) => {}
+                    None => return Ok((current, true)),
+                }
+            }
+            if !reduced {
+                if n >= current.len() {
+                    break;
+                }
+                n = (n * 2).min(current.len());
+            }
+        }
+        Ok((current, false))
+    }
+
+    /// Run the predicate on `subset`; `None` once the budget is spent
+    fn test(&mut self, subset: &[usize], advance: &mut impl FnMut(u64)) -> Result<Option<bool>> {
+        if let Some(&known) = self.tested.get(subset) {
+            return Ok(Some(known));
+        }
+        if self.steps >= self.max_steps {
+            return Ok(None);
+        }
+        self.steps += 1;
+        advance(1);
+
+        let dir = self.scratch.join(format!("candidate-{}", self.steps));
+        let paths: Vec<PathBuf> = subset.iter().map(|&i| self.files[i].clone()).collect();
+        copy_subset(self.source, &paths, &dir, true)?;
+        let holds = (self.predicate)(&dir);
+        fs::remove_dir_all(&dir).at_path(&dir)?;
+        self.tested.insert(subset.to_vec(), holds);
+        Ok(Some(holds))
+    }
+}
+
+/// Recreate `files` (relative to `source`) under a new `dest`
+///
+/// With `link`, hardlinks each file, falling back to a copy across
+/// filesystems or where links are unsupported; otherwise always copies.
+fn copy_subset(source: &Path, files: &[PathBuf], dest: &Path, link: bool) -> Result<()> {
+    fs::create_dir_all(dest).at_path(dest)?;
+    for rel in files {
+        let from = source.join(rel);
+        let to = dest.join(rel);
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent).at_path(parent)?;
+        }
+        if !link || fs::hard_link(&from, &to).is_err() {
+            let len = fs::metadata(&from).at_path(&from)?.len();
+            fs::copy(&from, &to).map_err(|e| Error::write(&to, len, e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_shrinker_isolates_single_trigger_file() {
+        let dataset = TempDir::new().unwrap();
+        for i in 0..50 {
+            let sub = dataset.path().join(format!("d{}", i % 4));
+            fs::create_dir_all(&sub).unwrap();
+            fs::write(sub.join(format!("f{:02}.bin", i)), [i as u8; 16]).unwrap();
+        }
+        let trigger = Path::new("d1").join("f37.bin");
+        let predicate = |dir: &Path| dir.join("d1/f37.bin").is_file();
+
+        let artifacts = TempDir::new().unwrap();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let result = DatasetShrinker::new()
+            .with_max_steps(64)
+            .with_artifact_dir(artifacts.path())
+            .with_progress_options(
+                ProgressOptions::new()
+                    .with_sink(move |done: u64, _: Option<u64>, _: &str| {
+                        sink.lock().unwrap().push(done)
+                    })
+                    .with_min_interval(std::time::Duration::ZERO),
+            )
+            .shrink(dataset.path(), predicate);
+
+        assert!(result.reproduced);
+        assert_eq!(result.files, vec![trigger.clone()]);
+        assert_eq!(result.original_files, 50);
+        assert!(!result.budget_exhausted);
+        assert!(result.steps <= 64, "{} steps", result.steps);
+        assert_eq!(
+            *reports.lock().unwrap().last().unwrap(),
+            result.steps as u64
+        );
+
+        let kept = result.minimized_dir.unwrap();
+        assert!(kept.starts_with(artifacts.path()));
+        assert_eq!(fs::read(kept.join(&trigger)).unwrap(), [37u8; 16]);
+        assert_eq!(crate::integrity::collect_files(&kept).unwrap().len(), 1);
+
+        // The kept copy is independent of the original dataset
+        fs::write(kept.join(&trigger), b"edited").unwrap();
+        assert_eq!(fs::read(dataset.path().join(&trigger)).unwrap(), [37u8; 16]);
+
+        let tight = DatasetShrinker::new()
+            .with_max_steps(3)
+            .with_artifact_dir(artifacts.path())
+            .shrink(dataset.path(), predicate);
+        assert!(tight.budget_exhausted);
+        assert_eq!(tight.steps, 3);
+        assert!(tight.files.contains(&trigger) && tight.files.len() < 50);
+
+        let never = DatasetShrinker::new().shrink(dataset.path(), |_| false);
+        assert!(!never.reproduced && never.minimized_dir.is_none());
+    }
+}
@@ -0,0 +1,356 @@
+//! Side-by-side encoder runs over one corpus under several configurations
+//!
+//! [`ConfigSweep`] derives each variation from a base
+//! [`ReversibleVSAConfig`], roundtrips the whole corpus under it and
+//! collects timings and density figures. The [`SweepReport`] ranks the
+//! variations by a [`SweepObjective`] and renders as CSV or Markdown.
+
+use super::panic_message;
+use crate::metrics::{format, DensityStats, DensitySummary, TestMetrics};
+use embeddenator_vsa::{ReversibleVSAConfig, SparseVec, DIM};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+
+/// Mutation applied to a clone of the base config
+type Variation = Box<dyn Fn(&mut ReversibleVSAConfig)>;
+
+/// What a [`SweepReport`] ranks variations by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SweepObjective {
+    /// Encode plus decode MB/s, higher first
+    #[default]
+    Throughput,
+    /// Mean non-zeros per encoded vector, sparser first
+    Density,
+    /// Fraction of items that decode back to their input, higher first
+    RoundtripSuccess,
+}
+
+/// One corpus item: in-memory bytes or a file read at run time
+#[derive(Clone, Debug)]
+enum CorpusItem {
+    Bytes(Vec<u8>),
+    File(PathBuf),
+}
+
+/// Results for one variation in a [`SweepReport`]
+#[derive(Clone, Debug)]
+pub struct SweepRow {
+    /// Variation name
+    pub name: String,
+    /// Items that decoded back to their input
+    pub roundtrip_ok: usize,
+    /// Items that decoded to different bytes
+    pub roundtrip_failed: usize,
+    /// Corpus bytes processed
+    pub bytes: u64,
+    /// `"encode"` and `"decode"` phase timings, one sample per item
+    pub metrics: TestMetrics,
+    /// Encoded vector sparsity
+    pub density: DensitySummary,
+    /// Encode plus decode throughput in MB/s
+    pub throughput_mbps: f64,
+    /// Why the variation stopped early (panic or unreadable file)
+    pub error: Option<String>,
+}
+
+impl SweepRow {
+    /// Fraction of processed items that roundtripped (0 when none ran)
+    pub fn success_rate(&self) -> f64 {
+        let total = self.roundtrip_ok + self.roundtrip_failed;
+        if total == 0 {
+            0.0
+        } else {
+            self.roundtrip_ok as f64 / total as f64
+        }
+    }
+
+    /// Sort key for `objective`; larger ranks first
+    fn score(&self, objective: SweepObjective) -> f64 {
+        match objective {
+            SweepObjective::Throughput => self.throughput_mbps,
+            SweepObjective::Density => -self.density.mean_nnz,
+            SweepObjective::RoundtripSuccess => self.success_rate(),
+        }
+    }
+}
+
+/// Outcome of [`ConfigSweep::run`], best variation first
+#[derive(Clone, Debug)]
+pub struct SweepReport {
+    /// Ranking used for `rows`
+    pub objective: SweepObjective,
+    /// One row per variation; errored variations rank last
+    pub rows: Vec<SweepRow>,
+}
+
+impl SweepReport {
+    /// Row for the variation called `name`
+    pub fn row(&self, name: &str) -> Option<&SweepRow> {
+        self.rows.iter().find(|r| r.name == name)
+    }
+
+    /// Highest-ranked variation that ran without error
+    pub fn best(&self) -> Option<&SweepRow> {
+        self.rows.iter().find(|r| r.error.is_none())
+    }
+
+    /// CSV with one row per variation, in rank order
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "rank,name,roundtrip_ok,roundtrip_failed,bytes,encode_mean_us,decode_mean_us,throughput_mbps,mean_nnz,mean_density,error\n",
+        );
+        for (i, row) in self.rows.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{:.3},{:.3},{:.6},{:.3},{},{}\n",
+                i + 1,
+                row.name.replace(',', ";"),
+                row.roundtrip_ok,
+                row.roundtrip_failed,
+                row.bytes,
+                row.metrics.phase_stats("encode").mean_ns / 1000.0,
+                row.metrics.phase_stats("decode").mean_ns / 1000.0,
+                row.throughput_mbps,
+                row.density.mean_nnz,
+                row.density
+                    .mean_density
+                    .map_or_else(String::new, |d| format!("{:.6}", d)),
+                row.error
+                    .as_deref()
+                    .unwrap_or_default()
+                    .replace([',', '\n'], " ")
+            ));
+        }
+        csv
+    }
+
+    /// GitHub-flavoured Markdown table in rank order
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("Ranked by {:?}\n\n", self.objective);
+        out.push_str("| # | variation | roundtrip | throughput | mean nnz | error |\n");
+        out.push_str("| ---: | --- | ---: | ---: | ---: | --- |\n");
+        for (i, row) in self.rows.iter().enumerate() {
+            out.push_str(&format!(
+                "| {} | {} | {}/{} | {} | {:.1} | {} |\n",
+                i + 1,
+                row.name.replace('|', "\\|"),
+                row.roundtrip_ok,
+                row.roundtrip_ok + row.roundtrip_failed,
+                format::bytes_per_sec(row.throughput_mbps * 1024.0 * 1024.0),
+                row.density.mean_nnz,
+                row.error.as_deref().unwrap_or("").replace('|', "\\|")
+            ));
+        }
+        out
+    }
+}
+
+/// Roundtrips one corpus under named variations of a base config
+///
+/// Each variation clones the base config and applies its closure. A
+/// variation that panics (in the closure, encoder or decoder) or hits an
+/// unreadable corpus file keeps the results gathered so far, records the
+/// error and the sweep moves on to the next one.
+///
+/// # Example
+/// ```rust,ignore
+/// let report = ConfigSweep::new(ReversibleVSAConfig::default())
+///     .with_variation("default", |_| {})
+///     .with_variation("wide", |c| *c = ReversibleVSAConfig::large_blocks())
+///     .with_files(corpus_paths)
+///     .with_objective(SweepObjective::Density)
+///     .run();
+/// println!("{}", report.to_markdown());
+/// ```
+pub struct ConfigSweep {
+    base: ReversibleVSAConfig,
+    variations: Vec<(String, Variation)>,
+    corpus: Vec<CorpusItem>,
+    objective: SweepObjective,
+    dims: usize,
+}
+
+impl ConfigSweep {
+    /// Sweep starting from `base`, with no variations or corpus yet
+    pub fn new(base: ReversibleVSAConfig) -> Self {
+        Self {
+            base,
+            variations: Vec::new(),
+            corpus: Vec::new(),
+            objective: SweepObjective::default(),
+            dims: DIM,
+        }
+    }
+
+    /// Add a variation that mutates a clone of the base config
+    pub fn with_variation(
+        mut self,
+        name: &str,
+        variation: impl Fn(&mut ReversibleVSAConfig) + 'static,
+    ) -> Self {
+        self.variations
+            .push((name.to_string(), Box::new(variation)));
+        self
+    }
+
+    /// Add in-memory corpus items
+    pub fn with_bytes<D: Into<Vec<u8>>>(mut self, items: impl IntoIterator<Item = D>) -> Self {
+        self.corpus
+            .extend(items.into_iter().map(|d| CorpusItem::Bytes(d.into())));
+        self
+    }
+
+    /// Add corpus files, read once per variation
+    pub fn with_files<P: Into<PathBuf>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.corpus
+            .extend(paths.into_iter().map(|p| CorpusItem::File(p.into())));
+        self
+    }
+
+    /// Rank the report by `objective`
+    pub fn with_objective(mut self, objective: SweepObjective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Dimension used for density figures (defaults to [`DIM`])
+    pub fn with_dims(mut self, dims: usize) -> Self {
+        self.dims = dims;
+        self
+    }
+
+    /// Run every variation over the corpus and rank the results
+    pub fn run(&self) -> SweepReport {
+        let mut rows: Vec<SweepRow> = self
+            .variations
+            .iter()
+            .map(|(name, variation)| self.run_variation(name, variation))
+            .collect();
+        let objective = self.objective;
+        // Stable, so equal scores keep insertion order
+        rows.sort_by(|a, b| {
+            a.error
+                .is_some()
+                .cmp(&b.error.is_some())
+                .then(b.score(objective).total_cmp(&a.score(objective)))
+        });
+        SweepReport { objective, rows }
+    }
+
+    fn run_variation(&self, name: &str, variation: &Variation) -> SweepRow {
+        let mut row = SweepRow {
+            name: name.to_string(),
+            roundtrip_ok: 0,
+            roundtrip_failed: 0,
+            bytes: 0,
+            metrics: TestMetrics::new(name),
+            density: DensitySummary::default(),
+            throughput_mbps: 0.0,
+            error: None,
+        };
+        let mut density = DensityStats::new().with_dims(self.dims);
+
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut config = self.base.clone();
+            variation(&mut config);
+            for item in &self.corpus {
+                let data = match item {
+                    CorpusItem::Bytes(data) => data.clone(),
+                    CorpusItem::File(path) => fs::read(path)
+                        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?,
+                };
+                let encoded = row
+                    .metrics
+                    .time_phase("encode", || SparseVec::encode_data(&data, &config, None));
+                density.observe(&encoded);
+                let decoded = row
+                    .metrics
+                    .time_phase("decode", || encoded.decode_data(&config, None, data.len()));
+                if decoded == data {
+                    row.roundtrip_ok += 1;
+                } else {
+                    row.roundtrip_failed += 1;
+                }
+                row.bytes += data.len() as u64;
+            }
+            Ok::<_, String>(())
+        }));
+        row.error = match outcome {
+            Ok(Ok(())) => None,
+            Ok(Err(message)) => Some(message),
+            Err(payload) => Some(format!("panicked: {}", panic_message(&*payload))),
+        };
+
+        let secs = (row.metrics.phase_stats("encode").total_duration()
+            + row.metrics.phase_stats("decode").total_duration())
+        .as_secs_f64();
+        if secs > 0.0 {
+            row.throughput_mbps = row.bytes as f64 / (1024.0 * 1024.0) / secs;
+        }
+        row.density = density.summarize();
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_variations_over_tiny_corpus() {
+        let corpus: Vec<Vec<u8>> = (0..4)
+            .map(|i| format!("document {} of the sweep corpus", i).into_bytes())
+            .collect();
+        let report = ConfigSweep::new(ReversibleVSAConfig::default())
+            .with_variation("base", |_| {})
+            .with_variation("reset", |c| *c = ReversibleVSAConfig::default())
+            .with_bytes(corpus)
+            .with_objective(SweepObjective::RoundtripSuccess)
+            .run();
+
+        assert_eq!(report.rows.len(), 2);
+        for name in ["base", "reset"] {
+            let row = report.row(name).unwrap();
+            assert_eq!(row.error, None);
+            assert_eq!(row.roundtrip_ok + row.roundtrip_failed, 4);
+            assert!(row.bytes > 0);
+            assert_eq!(row.metrics.phase_stats("encode").count, 4);
+            assert_eq!(row.metrics.phase_stats("decode").count, 4);
+            assert_eq!(row.density.count, 4);
+            assert!(row.density.mean_nnz > 0.0);
+            assert!(row.throughput_mbps > 0.0);
+        }
+
+        let csv = report.to_csv();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(1).unwrap().starts_with("1,"));
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| base |") && markdown.contains("| reset |"));
+    }
+
+    #[test]
+    fn test_failing_variation_does_not_abort_sweep() {
+        let missing = std::env::temp_dir().join("config_sweep_missing_file.bin");
+        let report = ConfigSweep::new(ReversibleVSAConfig::default())
+            .with_variation("panics", |_| panic!("bad parameter"))
+            .with_variation("ok", |_| {})
+            .with_bytes([b"abc".to_vec()])
+            .run();
+        assert_eq!(report.rows[0].name, "ok");
+        assert_eq!(report.best().unwrap().name, "ok");
+        let failed = report.row("panics").unwrap();
+        assert_eq!(failed.error.as_deref(), Some("panicked: bad parameter"));
+        assert_eq!(failed.roundtrip_ok, 0);
+
+        let report = ConfigSweep::new(ReversibleVSAConfig::default())
+            .with_variation("ok", |_| {})
+            .with_bytes([b"abc".to_vec()])
+            .with_files([missing])
+            .run();
+        let row = report.row("ok").unwrap();
+        assert_eq!(row.roundtrip_ok + row.roundtrip_failed, 1);
+        assert!(row.error.as_deref().unwrap().starts_with("failed to read"));
+        assert!(report.best().is_none());
+    }
+}
@@ -0,0 +1,123 @@
+//! Time limits for operations that may hang
+
+use super::panic_message;
+use crate::events;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Outcome of [`run_with_timeout`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimeoutResult<R> {
+    /// The closure returned within the limit
+    Completed(R),
+    /// The limit passed first; the worker thread was detached
+    TimedOut {
+        /// Time waited before giving up
+        elapsed: Duration,
+    },
+    /// The closure panicked with this message
+    Panicked(String),
+}
+
+impl<R> TimeoutResult<R> {
+    /// True if the closure returned within the limit
+    pub fn is_completed(&self) -> bool {
+        matches!(self, TimeoutResult::Completed(_))
+    }
+
+    /// The closure's return value, if it completed
+    pub fn completed(self) -> Option<R> {
+        match self {
+            TimeoutResult::Completed(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Run `f` on a helper thread, giving up after `limit`
+///
+/// Rust threads cannot be killed, so a timed-out closure keeps running
+/// detached in the background until it finishes or the process exits; a
+/// `harness/timeout` event is emitted to record it. Panics inside `f` are
+/// caught and returned as [`TimeoutResult::Panicked`].
+///
+/// # Example
+/// ```rust,ignore
+/// match run_with_timeout(Duration::from_secs(5), move || decode(&corrupted)) {
+///     TimeoutResult::Completed(bytes) => assert_ne!(bytes, original),
+///     TimeoutResult::TimedOut { elapsed } => panic!("decode spun for {:?}", elapsed),
+///     TimeoutResult::Panicked(msg) => panic!("decode panicked: {}", msg),
+/// }
+/// ```
+pub fn run_with_timeout<F, R>(limit: Duration, f: F) -> TimeoutResult<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let start = Instant::now();
+    let (tx, rx) = mpsc::channel();
+    let spawned = thread::Builder::new()
+        .name("testkit-timeout".to_string())
+        .spawn(move || {
+            // The receiver may be gone after a timeout; nothing to report then
+            let _ = tx.send(panic::catch_unwind(AssertUnwindSafe(f)));
+        });
+    if let Err(e) = spawned {
+        return TimeoutResult::Panicked(format!("failed to spawn worker thread: {}", e));
+    }
+
+    match rx.recv_timeout(limit) {
+        Ok(Ok(value)) => TimeoutResult::Completed(value),
+        Ok(Err(payload)) => TimeoutResult::Panicked(panic_message(payload.as_ref())),
+        Err(RecvTimeoutError::Timeout) => timed_out(limit, start.elapsed()),
+        Err(RecvTimeoutError::Disconnected) => {
+            TimeoutResult::Panicked("worker thread exited without a result".to_string())
+        }
+    }
+}
+
+/// Record a worker left running detached after `limit` passed
+pub(super) fn timed_out<R>(limit: Duration, elapsed: Duration) -> TimeoutResult<R> {
+    events::emit("harness", "timeout", || {
+        serde_json::json!({
+            "limit_ms": limit.as_millis() as u64,
+            "elapsed_ms": elapsed.as_millis() as u64,
+            "detached": true,
+        })
+    });
+    TimeoutResult::TimedOut { elapsed }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_with_timeout_outcomes() {
+        let fast = run_with_timeout(Duration::from_secs(5), || 6 * 7);
+        assert_eq!(fast, TimeoutResult::Completed(42));
+
+        let limit = Duration::from_millis(20);
+        let slow = run_with_timeout(limit, || {
+            thread::sleep(Duration::from_secs(2));
+            1
+        });
+        match slow {
+            TimeoutResult::TimedOut { elapsed } => {
+                assert!(elapsed >= limit && elapsed < Duration::from_secs(2))
+            }
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+
+        let panicked = run_with_timeout(Duration::from_secs(5), || -> u8 {
+            panic!("decode spun out");
+        });
+        assert_eq!(
+            panicked,
+            TimeoutResult::Panicked("decode spun out".to_string())
+        );
+        assert!(!panicked.is_completed());
+    }
+}
@@ -0,0 +1,485 @@
+//! Recording and replay of VSA operation sequences
+//!
+//! An [`OpTrace`] stores operations as data rather than closures, so the
+//! exact mix that triggered a slowdown or a wrong answer can be saved as
+//! JSON, attached to a bug report and re-executed later with per-operation
+//! timings.
+//!
+//! Vectors are referred to by id: every `Encode`, `Bundle` and `Bind`
+//! produces the next id, starting at 0. `Cosine` produces no vector.
+//! Traces captured with [`record_mode`] also store the [`VecFingerprint`] of
+//! every vector produced, so a replay can point at the first op whose output
+//! changed.
+
+use crate::generators::{fingerprint, generate_noise_pattern, VecFingerprint};
+use crate::metrics::TestMetrics;
+use embeddenator_vsa::{ReversibleVSAConfig, SparseVec};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// One recorded VSA operation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TraceOp {
+    /// Encode `len` bytes of [`generate_noise_pattern`] seeded with `pattern_seed`
+    Encode { len: usize, pattern_seed: u64 },
+    /// Bundle vectors `a` and `b`
+    Bundle { a: usize, b: usize },
+    /// Bind vectors `a` and `b`
+    Bind { a: usize, b: usize },
+    /// Cosine similarity of vectors `a` and `b`
+    Cosine { a: usize, b: usize },
+}
+
+impl TraceOp {
+    /// Metrics category this operation is timed under
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TraceOp::Encode { .. } => "encode",
+            TraceOp::Bundle { .. } => "bundle",
+            TraceOp::Bind { .. } => "bind",
+            TraceOp::Cosine { .. } => "cosine",
+        }
+    }
+
+    /// Whether the operation produces a new vector id
+    pub fn produces_vector(&self) -> bool {
+        !matches!(self, TraceOp::Cosine { .. })
+    }
+
+    fn operands(&self) -> Option<(usize, usize)> {
+        match *self {
+            TraceOp::Encode { .. } => None,
+            TraceOp::Bundle { a, b } | TraceOp::Bind { a, b } | TraceOp::Cosine { a, b } => {
+                Some((a, b))
+            }
+        }
+    }
+}
+
+/// A replayable sequence of VSA operations
+///
+/// # Example
+/// ```rust,ignore
+/// let mut trace = OpTrace::new();
+/// let a = trace.encode(4096, 1);
+/// let b = trace.encode(4096, 2);
+/// let ab = trace.bundle(a, b);
+/// trace.cosine(ab, a);
+/// trace.save(Path::new("cliff.trace.json"))?;
+///
+/// let result = OpTrace::load(Path::new("cliff.trace.json"))?.replay(&config);
+/// println!("{}", result.metrics["bundle"].summary());
+/// ```
+///
+/// Deserializing rebuilds the vector count from the operations and fails if
+/// an operation references a vector id not produced earlier in the trace,
+/// or if there are more fingerprints than produced vectors.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "StoredTrace")]
+pub struct OpTrace {
+    ops: Vec<TraceOp>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fingerprints: Vec<VecFingerprint>,
+    #[serde(skip_serializing)]
+    vector_count: usize,
+}
+
+/// Serialized form of an [`OpTrace`], validated on the way in
+#[derive(Deserialize)]
+struct StoredTrace {
+    ops: Vec<TraceOp>,
+    #[serde(default)]
+    fingerprints: Vec<VecFingerprint>,
+}
+
+impl TryFrom<StoredTrace> for OpTrace {
+    type Error = String;
+
+    fn try_from(stored: StoredTrace) -> Result<Self, String> {
+        let mut trace = OpTrace::new();
+        for op in stored.ops {
+            trace.check(&op)?;
+            trace.push(op);
+        }
+        if stored.fingerprints.len() > trace.vector_count {
+            return Err(format!(
+                "{} fingerprints recorded but only {} vectors produced",
+                stored.fingerprints.len(),
+                trace.vector_count
+            ));
+        }
+        trace.fingerprints = stored.fingerprints;
+        Ok(trace)
+    }
+}
+
+impl OpTrace {
+    /// Create an empty trace
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recorded operations in order
+    pub fn ops(&self) -> &[TraceOp] {
+        &self.ops
+    }
+
+    /// Number of vectors the trace produces
+    pub fn vector_count(&self) -> usize {
+        self.vector_count
+    }
+
+    /// Fingerprints of the vectors produced while recording, by id
+    ///
+    /// Empty for traces built by hand; vectors appended after recording
+    /// have none.
+    pub fn fingerprints(&self) -> &[VecFingerprint] {
+        &self.fingerprints
+    }
+
+    /// Append an operation, returning the id of the vector it produces
+    ///
+    /// # Panics
+    /// Panics if the operation references a vector id not yet produced.
+    pub fn push(&mut self, op: TraceOp) -> Option<usize> {
+        if let Err(e) = self.check(&op) {
+            panic!("{}", e);
+        }
+        self.ops.push(op);
+        op.produces_vector().then(|| {
+            self.vector_count += 1;
+            self.vector_count - 1
+        })
+    }
+
+    /// Record an encode; returns the new vector id
+    pub fn encode(&mut self, len: usize, pattern_seed: u64) -> usize {
+        self.push(TraceOp::Encode { len, pattern_seed })
+            .expect("encode produces a vector")
+    }
+
+    /// Record a bundle; returns the new vector id
+    pub fn bundle(&mut self, a: usize, b: usize) -> usize {
+        self.push(TraceOp::Bundle { a, b })
+            .expect("bundle produces a vector")
+    }
+
+    /// Record a bind; returns the new vector id
+    pub fn bind(&mut self, a: usize, b: usize) -> usize {
+        self.push(TraceOp::Bind { a, b })
+            .expect("bind produces a vector")
+    }
+
+    /// Record a cosine similarity
+    pub fn cosine(&mut self, a: usize, b: usize) {
+        self.push(TraceOp::Cosine { a, b });
+    }
+
+    /// Re-execute the trace, timing each operation under its kind
+    pub fn replay(&self, config: &ReversibleVSAConfig) -> ReplayResult {
+        let mut executor = Executor::new(config);
+        for op in &self.ops {
+            executor.apply(*op);
+        }
+        executor.result
+    }
+
+    /// Save trace as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+    }
+
+    /// Load trace from JSON
+    ///
+    /// Fails with `InvalidData` if an operation references a vector id
+    /// that is not produced earlier in the trace.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn check(&self, op: &TraceOp) -> Result<(), String> {
+        match op.operands() {
+            Some((a, b)) if a.max(b) >= self.vector_count => Err(format!(
+                "op {} ({:?}) references vector {} but only {} exist",
+                self.ops.len(),
+                op,
+                a.max(b),
+                self.vector_count
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Outcome of [`OpTrace::replay`] or a [`record_mode`] session
+#[derive(Clone, Debug)]
+pub struct ReplayResult {
+    /// Produced vectors, indexed by id
+    pub vectors: Vec<SparseVec>,
+    /// Results of `Cosine` operations in order
+    pub cosines: Vec<f64>,
+    /// Timings per operation kind (`encode`, `bundle`, `bind`, `cosine`)
+    pub metrics: BTreeMap<String, TestMetrics>,
+}
+
+impl ReplayResult {
+    /// Last vector produced, if any
+    pub fn final_vector(&self) -> Option<&SparseVec> {
+        self.vectors.last()
+    }
+
+    /// [`fingerprint`] of the last vector produced
+    pub fn final_fingerprint(&self) -> Option<VecFingerprint> {
+        self.final_vector().map(fingerprint)
+    }
+
+    /// [`fingerprint`] of every produced vector, by id
+    pub fn fingerprints(&self) -> Vec<VecFingerprint> {
+        self.vectors.iter().map(fingerprint).collect()
+    }
+
+    /// Id of the first vector whose fingerprint differs from the one
+    /// recorded in `trace`
+    pub fn first_divergence(&self, trace: &OpTrace) -> Option<usize> {
+        trace
+            .fingerprints
+            .iter()
+            .zip(&self.vectors)
+            .position(|(recorded, v)| *recorded != fingerprint(v))
+    }
+}
+
+/// Executes operations against real vectors while collecting metrics
+struct Executor<'a> {
+    config: &'a ReversibleVSAConfig,
+    result: ReplayResult,
+}
+
+impl<'a> Executor<'a> {
+    fn new(config: &'a ReversibleVSAConfig) -> Self {
+        Self {
+            config,
+            result: ReplayResult {
+                vectors: Vec::new(),
+                cosines: Vec::new(),
+                metrics: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// Run `op`; returns the cosine for `Cosine` operations
+    fn apply(&mut self, op: TraceOp) -> Option<f64> {
+        let config = self.config;
+        let vectors = &self.result.vectors;
+        let metrics = self
+            .result
+            .metrics
+            .entry(op.kind().to_string())
+            .or_insert_with(|| TestMetrics::new(op.kind()));
+        metrics.inc_op(op.kind());
+
+        let produced = match op {
+            TraceOp::Encode { len, pattern_seed } => {
+                let data = generate_noise_pattern(len, pattern_seed);
+                metrics.time_operation(|| SparseVec::encode_data(&data, config, None))
+            }
+            TraceOp::Bundle { a, b } => metrics.time_operation(|| vectors[a].bundle(&vectors[b])),
+            TraceOp::Bind { a, b } => metrics.time_operation(|| vectors[a].bind(&vectors[b])),
+            TraceOp::Cosine { a, b } => {
+                let similarity = metrics.time_operation(|| vectors[a].cosine(&vectors[b]));
+                self.result.cosines.push(similarity);
+                return Some(similarity);
+            }
+        };
+        self.result.vectors.push(produced);
+        None
+    }
+}
+
+/// Executes operations immediately while recording them into an [`OpTrace`]
+///
+/// Obtained from [`record_mode`]. Each method runs the real VSA operation,
+/// times it, and appends it to the trace.
+pub struct TraceRecorder<'a> {
+    trace: OpTrace,
+    executor: Executor<'a>,
+}
+
+impl TraceRecorder<'_> {
+    /// Encode `len` bytes of seeded noise; returns the new vector id
+    pub fn encode(&mut self, len: usize, pattern_seed: u64) -> usize {
+        self.run(TraceOp::Encode { len, pattern_seed });
+        self.trace.vector_count - 1
+    }
+
+    /// Bundle two recorded vectors; returns the new vector id
+    pub fn bundle(&mut self, a: usize, b: usize) -> usize {
+        self.run(TraceOp::Bundle { a, b });
+        self.trace.vector_count - 1
+    }
+
+    /// Bind two recorded vectors; returns the new vector id
+    pub fn bind(&mut self, a: usize, b: usize) -> usize {
+        self.run(TraceOp::Bind { a, b });
+        self.trace.vector_count - 1
+    }
+
+    /// Cosine similarity of two recorded vectors
+    pub fn cosine(&mut self, a: usize, b: usize) -> f64 {
+        self.run(TraceOp::Cosine { a, b })
+            .expect("cosine returns a similarity")
+    }
+
+    /// Vector produced under `id`
+    ///
+    /// # Panics
+    /// Panics if `id` has not been produced yet.
+    pub fn vector(&self, id: usize) -> &SparseVec {
+        &self.executor.result.vectors[id]
+    }
+
+    fn run(&mut self, op: TraceOp) -> Option<f64> {
+        self.trace.push(op);
+        let similarity = self.executor.apply(op);
+        if op.produces_vector() {
+            let produced = self
+                .executor
+                .result
+                .final_vector()
+                .expect("vector produced");
+            self.trace.fingerprints.push(fingerprint(produced));
+        }
+        similarity
+    }
+}
+
+/// Run `f` with a [`TraceRecorder`], returning its output, the recorded
+/// trace and the live result
+///
+/// # Example
+/// ```rust,ignore
+/// let (_, trace, live) = record_mode(&config, |rec| {
+///     let doc = rec.encode(1 << 20, 7);
+///     let key = rec.encode(64, 8);
+///     let bound = rec.bind(doc, key);
+///     rec.cosine(bound, doc)
+/// });
+/// trace.save(Path::new("workload.trace.json"))?;
+/// ```
+pub fn record_mode<F, R>(config: &ReversibleVSAConfig, f: F) -> (R, OpTrace, ReplayResult)
+where
+    F: FnOnce(&mut TraceRecorder<'_>) -> R,
+{
+    let mut recorder = TraceRecorder {
+        trace: OpTrace::new(),
+        executor: Executor::new(config),
+    };
+    let output = f(&mut recorder);
+    (output, recorder.trace, recorder.executor.result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_replay_is_deterministic() {
+        let config = ReversibleVSAConfig::default();
+        let (similarity, trace, live) = record_mode(&config, |rec| {
+            let a = rec.encode(512, 1);
+            let b = rec.encode(512, 2);
+            let key = rec.encode(32, 3);
+            let ab = rec.bundle(a, b);
+            let bound = rec.bind(ab, key);
+            assert_eq!(bound, 4);
+            assert_eq!(rec.vector(bound).pos, live_check(&config));
+            rec.cosine(ab, a)
+        });
+        assert_eq!(trace.ops().len(), 6);
+        assert_eq!(trace.vector_count(), 5);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workload.trace.json");
+        trace.save(&path).unwrap();
+        let loaded = OpTrace::load(&path).unwrap();
+        assert_eq!(loaded, trace);
+
+        let first = loaded.replay(&config);
+        let second = loaded.replay(&config);
+        assert!(first.final_fingerprint().is_some());
+        assert_eq!(first.final_fingerprint(), second.final_fingerprint());
+        assert_eq!(first.fingerprints(), live.fingerprints());
+        assert_eq!(loaded.fingerprints(), live.fingerprints().as_slice());
+        assert_eq!(first.first_divergence(&loaded), None);
+        assert_eq!(first.cosines, vec![similarity]);
+        assert_eq!(first.metrics["encode"].op_counts["encode"], 3);
+        assert_eq!(first.metrics["bundle"].timings_ns.len(), 1);
+        assert_eq!(first.metrics.len(), 4);
+    }
+
+    /// The bound vector computed without the recorder
+    fn live_check(config: &ReversibleVSAConfig) -> Vec<usize> {
+        let encode =
+            |seed| SparseVec::encode_data(&generate_noise_pattern(512, seed), config, None);
+        let key = SparseVec::encode_data(&generate_noise_pattern(32, 3), config, None);
+        encode(1).bundle(&encode(2)).bind(&key).pos
+    }
+
+    #[test]
+    fn test_dangling_ids_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.trace.json");
+        fs::write(
+            &path,
+            r#"{"ops":[{"op":"encode","len":8,"pattern_seed":1},{"op":"bundle","a":0,"b":1}]}"#,
+        )
+        .unwrap();
+        let err = OpTrace::load(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("references vector 1"));
+
+        let result = std::panic::catch_unwind(|| OpTrace::new().cosine(0, 0));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_rebuilds_vector_count() {
+        let mut trace = OpTrace::new();
+        let a = trace.encode(8, 1);
+        let b = trace.encode(8, 2);
+        trace.cosine(a, b);
+        trace.bind(a, b);
+
+        let json = serde_json::to_string(&trace).unwrap();
+        assert!(!json.contains("vector_count"));
+        let mut parsed: OpTrace = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, trace);
+        assert_eq!(parsed.vector_count(), 3);
+        // Appending continues from the rebuilt count
+        assert_eq!(parsed.bundle(0, 2), 3);
+
+        let dangling = r#"{"ops":[{"op":"cosine","a":0,"b":0}]}"#;
+        assert!(serde_json::from_str::<OpTrace>(dangling).is_err());
+        let extra = r#"{"ops":[],"fingerprints":[{"hash":1,"nnz":2}]}"#;
+        assert!(serde_json::from_str::<OpTrace>(extra).is_err());
+    }
+
+    #[test]
+    fn test_replay_reports_first_divergence() {
+        let config = ReversibleVSAConfig::default();
+        let (_, mut trace, _) = record_mode(&config, |rec| {
+            let a = rec.encode(64, 1);
+            let b = rec.encode(64, 2);
+            rec.bundle(a, b);
+        });
+        assert_eq!(trace.fingerprints().len(), 3);
+        trace.fingerprints[1].hash ^= 1;
+        assert_eq!(trace.replay(&config).first_divergence(&trace), Some(1));
+    }
+}
@@ -0,0 +1,443 @@
+//! Randomized VSA operation sequences with invariant checks after each step
+//!
+//! [`AlgebraFuzzer`] keeps a small pool of vectors and repeatedly replaces
+//! one slot with the bundle, bind or permutation of others. Every result is
+//! checked with [`IntegrityValidator::validate_sparse`], an nnz bound, a
+//! cosine sanity check and one algebraic property of the operation. The
+//! operations are recorded as a [`FuzzTrace`], so a failing sequence can be
+//! saved and re-executed with [`AlgebraFuzzer::replay`].
+
+use super::{IntegrityReport, IntegrityValidator};
+use crate::generators::{fork_seed, random_permutation, random_sparse_vec};
+use embeddenator_vsa::SparseVec;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Vectors in the working set
+const POOL_SIZE: usize = 8;
+
+/// Slack allowed on cosine bounds for floating-point error
+const COSINE_EPSILON: f64 = 1e-9;
+
+/// One step of an [`AlgebraFuzzer`] run
+///
+/// Operands and outputs are pool slots; the output slot is overwritten.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FuzzOp {
+    /// `out = a ⊕ b`
+    Bundle { a: usize, b: usize, out: usize },
+    /// `out = a ⊙ b`
+    Bind { a: usize, b: usize, out: usize },
+    /// `out = a` permuted by [`random_permutation`] of `perm_seed`
+    Permute {
+        a: usize,
+        perm_seed: u64,
+        out: usize,
+    },
+    /// `out` = a fresh random vector drawn from `seed`
+    Fresh { seed: u64, out: usize },
+    /// Deliberately break `slot` by copying a positive index into `neg`
+    Corrupt { slot: usize },
+}
+
+/// Everything needed to re-execute an [`AlgebraFuzzer`] run
+///
+/// Deserializing fails if an operation names a pool slot that does not
+/// exist or `failed_step` lies past the last operation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "StoredFuzzTrace")]
+pub struct FuzzTrace {
+    /// Seed of the initial pool
+    pub seed: u64,
+    /// Vector dimension
+    pub dims: usize,
+    /// Non-zeros of the initial and fresh vectors
+    pub sparsity: usize,
+    /// Operations in execution order, up to and including a failing one
+    pub ops: Vec<FuzzOp>,
+    /// Index into `ops` of the first step whose checks failed
+    pub failed_step: Option<usize>,
+}
+
+/// Serialized form of a [`FuzzTrace`], validated on the way in
+#[derive(Deserialize)]
+struct StoredFuzzTrace {
+    seed: u64,
+    dims: usize,
+    sparsity: usize,
+    ops: Vec<FuzzOp>,
+    failed_step: Option<usize>,
+}
+
+impl TryFrom<StoredFuzzTrace> for FuzzTrace {
+    type Error = String;
+
+    fn try_from(stored: StoredFuzzTrace) -> Result<Self, String> {
+        let trace = FuzzTrace {
+            seed: stored.seed,
+            dims: stored.dims,
+            sparsity: stored.sparsity,
+            ops: stored.ops,
+            failed_step: stored.failed_step,
+        };
+        trace.validate()?;
+        Ok(trace)
+    }
+}
+
+impl FuzzOp {
+    /// Pool slots this operation reads or writes
+    fn slots(&self) -> Vec<usize> {
+        match *self {
+            FuzzOp::Bundle { a, b, out } | FuzzOp::Bind { a, b, out } => vec![a, b, out],
+            FuzzOp::Permute { a, out, .. } => vec![a, out],
+            FuzzOp::Fresh { out, .. } => vec![out],
+            FuzzOp::Corrupt { slot } => vec![slot],
+        }
+    }
+
+    /// Pool slot this operation overwrites
+    pub fn output_slot(&self) -> usize {
+        match *self {
+            FuzzOp::Bundle { out, .. }
+            | FuzzOp::Bind { out, .. }
+            | FuzzOp::Permute { out, .. }
+            | FuzzOp::Fresh { out, .. } => out,
+            FuzzOp::Corrupt { slot } => slot,
+        }
+    }
+}
+
+impl FuzzTrace {
+    /// The operation that failed, if any
+    pub fn failing_op(&self) -> Option<FuzzOp> {
+        self.failed_step
+            .and_then(|step| self.ops.get(step).copied())
+    }
+
+    /// Check that every slot exists and `failed_step` names an operation
+    pub fn validate(&self) -> Result<(), String> {
+        for (step, op) in self.ops.iter().enumerate() {
+            if let Some(slot) = op.slots().into_iter().find(|&slot| slot >= POOL_SIZE) {
+                return Err(format!(
+                    "step {}: slot {} is outside the pool of {}",
+                    step, slot, POOL_SIZE
+                ));
+            }
+        }
+        match self.failed_step {
+            Some(step) if step >= self.ops.len() => Err(format!(
+                "failed_step {} is past the last of {} ops",
+                step,
+                self.ops.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parse a trace saved with [`to_json`](Self::to_json)
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Random bundle/bind/permute sequences with per-step invariant checks
+///
+/// # Example
+/// ```rust,ignore
+/// let mut fuzzer = AlgebraFuzzer::new(42, 10_000, 200);
+/// let report = fuzzer.run(500);
+/// if !report.is_ok() {
+///     std::fs::write("fuzz_trace.json", fuzzer.trace().unwrap().to_json()?)?;
+/// }
+/// assert!(report.is_ok(), "{}", report.summary());
+/// ```
+pub struct AlgebraFuzzer {
+    seed: u64,
+    dims: usize,
+    sparsity: usize,
+    corrupt_at: Option<usize>,
+    validator: IntegrityValidator,
+    trace: Option<FuzzTrace>,
+}
+
+impl AlgebraFuzzer {
+    /// Fuzzer over `dims`-dimensional vectors with `sparsity` non-zeros
+    pub fn new(seed: u64, dims: usize, sparsity: usize) -> Self {
+        Self {
+            seed,
+            dims,
+            sparsity,
+            corrupt_at: None,
+            validator: IntegrityValidator::new().with_dims(dims),
+            trace: None,
+        }
+    }
+
+    /// Replace step `step` with a [`FuzzOp::Corrupt`] of its output slot
+    ///
+    /// Demonstrates that the checks catch a broken result and that the
+    /// trace points at the step that produced it.
+    pub fn with_corruption_at(mut self, step: usize) -> Self {
+        self.corrupt_at = Some(step);
+        self
+    }
+
+    /// Trace of the last [`run`](Self::run) or [`replay`](Self::replay)
+    pub fn trace(&self) -> Option<&FuzzTrace> {
+        self.trace.as_ref()
+    }
+
+    /// Execute `steps` random operations, stopping at the first failing step
+    pub fn run(&mut self, steps: usize) -> IntegrityReport {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let ops = (0..steps)
+            .map(|step| {
+                let op = random_op(&mut rng);
+                if self.corrupt_at == Some(step) {
+                    FuzzOp::Corrupt {
+                        slot: op.output_slot(),
+                    }
+                } else {
+                    op
+                }
+            })
+            .collect();
+        self.execute(ops)
+    }
+
+    /// Re-execute a recorded trace with this fuzzer's checks
+    ///
+    /// A trace that fails [`FuzzTrace::validate`] is not executed; the
+    /// report carries the validation error as its only failure.
+    pub fn replay(&mut self, trace: &FuzzTrace) -> IntegrityReport {
+        if let Err(e) = trace.validate() {
+            let mut report = IntegrityReport::default();
+            report.fail(format!("invalid trace: {}", e));
+            return report;
+        }
+        self.seed = trace.seed;
+        self.dims = trace.dims;
+        self.sparsity = trace.sparsity;
+        self.validator = IntegrityValidator::new().with_dims(trace.dims);
+        self.execute(trace.ops.clone())
+    }
+
+    fn execute(&mut self, ops: Vec<FuzzOp>) -> IntegrityReport {
+        let mut pool: Vec<SparseVec> = (0..POOL_SIZE)
+            .map(|slot| self.fresh(fork_seed(self.seed, slot as u64)))
+            .collect();
+        let mut report = IntegrityReport::default();
+        let mut trace = FuzzTrace {
+            seed: self.seed,
+            dims: self.dims,
+            sparsity: self.sparsity,
+            ops: Vec::with_capacity(ops.len()),
+            failed_step: None,
+        };
+
+        for (step, op) in ops.into_iter().enumerate() {
+            trace.ops.push(op);
+            let step_report = self.step(&mut pool, op);
+            report.merge(&step_report);
+            if !step_report.is_ok() {
+                report.fail(format!("step {} failed: {:?}", step, op));
+                trace.failed_step = Some(step);
+                break;
+            }
+        }
+
+        self.trace = Some(trace);
+        self.validator.finish("algebra_fuzz", report)
+    }
+
+    /// Apply `op` to the pool and check its result
+    fn step(&self, pool: &mut [SparseVec], op: FuzzOp) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+        let (out, max_nnz, operand) = match op {
+            FuzzOp::Bundle { a, b, out } => {
+                report.merge(
+                    &self
+                        .validator
+                        .validate_bundle_invariants(&pool[a], &pool[b]),
+                );
+                pool[out] = pool[a].bundle(&pool[b]);
+                (out, nnz(&pool[a]) + nnz(&pool[b]), Some(a))
+            }
+            FuzzOp::Bind { a, b, out } => {
+                report.merge(&self.validator.validate_bind_invariants(&pool[a], &pool[b]));
+                pool[out] = pool[a].bind(&pool[b]);
+                (out, self.dims, Some(a))
+            }
+            FuzzOp::Permute { a, perm_seed, out } => {
+                let perm = random_permutation(self.dims, perm_seed);
+                report.merge(
+                    &self
+                        .validator
+                        .validate_permutation_invariants(&pool[a], &perm),
+                );
+                pool[out] = crate::generators::permute_sparse_vec(&pool[a], &perm);
+                (out, nnz(&pool[a]), Some(a))
+            }
+            FuzzOp::Fresh { seed, out } => {
+                pool[out] = self.fresh(seed);
+                (out, self.sparsity, None)
+            }
+            FuzzOp::Corrupt { slot } => {
+                let v = &mut pool[slot];
+                let index = v.pos.first().copied().unwrap_or(0);
+                if v.pos.is_empty() {
+                    v.pos.push(index);
+                }
+                v.neg.insert(v.neg.partition_point(|&i| i < index), index);
+                (slot, self.dims, None)
+            }
+        };
+
+        let result = &pool[out];
+        report.merge(&self.validator.validate_sparse(result));
+
+        let started = Instant::now();
+        let result_nnz = nnz(result);
+        let bound = max_nnz.min(self.dims);
+        self.validator.check_invariant(
+            &mut report,
+            "algebra_fuzz.nnz_bound",
+            started,
+            (result_nnz > bound).then(|| format!("nnz {} exceeds bound {}", result_nnz, bound)),
+            || format!("nnz={} bound={}", result_nnz, bound),
+        );
+
+        // Self-similarity is 1 and similarity to an operand is a valid cosine.
+        // `operand` may be `out` itself, which is still a valid comparison.
+        let started = Instant::now();
+        let self_cosine = result.cosine(result);
+        let operand_cosine = operand.map_or(0.0, |a| result.cosine(&pool[a]));
+        let sane = (result_nnz == 0 || (self_cosine - 1.0).abs() <= COSINE_EPSILON)
+            && operand_cosine.is_finite()
+            && operand_cosine.abs() <= 1.0 + COSINE_EPSILON;
+        self.validator.check_invariant(
+            &mut report,
+            "algebra_fuzz.cosine",
+            started,
+            (!sane).then(|| {
+                format!(
+                    "cosine out of range: self {:.6}, operand {:.6}",
+                    self_cosine, operand_cosine
+                )
+            }),
+            || format!("self={:.6} operand={:.6}", self_cosine, operand_cosine),
+        );
+
+        report
+    }
+
+    fn fresh(&self, seed: u64) -> SparseVec {
+        random_sparse_vec(
+            &mut StdRng::seed_from_u64(seed),
+            self.dims,
+            self.sparsity.min(self.dims),
+        )
+    }
+}
+
+fn nnz(v: &SparseVec) -> usize {
+    v.pos.len() + v.neg.len()
+}
+
+/// Weighted towards bundling, with occasional fresh vectors so binds and
+/// repeated bundles do not collapse the pool
+fn random_op(rng: &mut StdRng) -> FuzzOp {
+    let slot = |rng: &mut StdRng| rng.random_range(0..POOL_SIZE);
+    let (a, b, out) = (slot(rng), slot(rng), slot(rng));
+    match rng.random_range(0..8) {
+        0..=2 => FuzzOp::Bundle { a, b, out },
+        3 | 4 => FuzzOp::Bind { a, b, out },
+        5 | 6 => FuzzOp::Permute {
+            a,
+            perm_seed: rng.random(),
+            out,
+        },
+        _ => FuzzOp::Fresh {
+            seed: rng.random(),
+            out,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_run_and_replay() {
+        let mut fuzzer = AlgebraFuzzer::new(17, 2048, 64);
+        let report = fuzzer.run(500);
+        assert!(report.is_ok(), "{}", report.failures.join("\n"));
+        let trace = fuzzer.trace().unwrap().clone();
+        assert_eq!(trace.ops.len(), 500);
+        assert_eq!(trace.failed_step, None);
+        assert!(trace
+            .ops
+            .iter()
+            .any(|op| matches!(op, FuzzOp::Permute { .. })));
+
+        let parsed = FuzzTrace::from_json(&trace.to_json().unwrap()).unwrap();
+        let replayed = AlgebraFuzzer::new(0, 1, 1).replay(&parsed);
+        assert!(replayed.is_ok());
+        assert_eq!(replayed.checks_total, report.checks_total);
+    }
+
+    #[test]
+    fn test_corruption_is_pinpointed() {
+        let mut fuzzer = AlgebraFuzzer::new(17, 2048, 64).with_corruption_at(123);
+        let report = fuzzer.run(500);
+        assert!(!report.is_ok());
+        assert!(report.corruption_events > 0);
+        assert!(report
+            .failures
+            .iter()
+            .any(|f| f.starts_with("step 123 failed: Corrupt")));
+
+        let trace = fuzzer.trace().unwrap().clone();
+        assert_eq!(trace.failed_step, Some(123));
+        assert_eq!(trace.ops.len(), 124);
+        assert!(matches!(trace.failing_op(), Some(FuzzOp::Corrupt { .. })));
+
+        // The saved trace reproduces the failure at the same step
+        let mut replayer = AlgebraFuzzer::new(0, 1, 1);
+        assert!(!replayer.replay(&trace).is_ok());
+        assert_eq!(replayer.trace().unwrap().failed_step, Some(123));
+    }
+
+    #[test]
+    fn test_out_of_range_slots_are_rejected() {
+        let mut fuzzer = AlgebraFuzzer::new(5, 512, 16);
+        fuzzer.run(3);
+        let mut trace = fuzzer.trace().unwrap().clone();
+        trace.ops.push(FuzzOp::Bind {
+            a: 0,
+            b: POOL_SIZE,
+            out: 1,
+        });
+
+        let err = FuzzTrace::from_json(&trace.to_json().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("outside the pool"), "{}", err);
+        let report = AlgebraFuzzer::new(0, 1, 1).replay(&trace);
+        assert!(!report.is_ok());
+        assert!(report.failures[0].starts_with("invalid trace"));
+
+        trace.ops.pop();
+        trace.failed_step = Some(trace.ops.len());
+        assert!(trace.validate().is_err());
+        assert_eq!(trace.failing_op(), None);
+    }
+}
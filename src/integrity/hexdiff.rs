@@ -0,0 +1,197 @@
+//! Hex dumps of the regions where two buffers differ
+//!
+//! [`hexdiff`] renders each differing region like a unified diff hunk: a
+//! header with the region's offset, then `-` rows from the expected buffer
+//! and `+` rows from the actual one, with matching rows shown once as
+//! context. Rows are 16 bytes wide with an ASCII gutter, as in `xxd`.
+
+use std::fmt::Write;
+use std::ops::Range;
+
+/// Bytes per rendered row
+const ROW: usize = 16;
+
+/// Context used when [`compare_files_mmap_with`](super::compare_files_mmap_with)
+/// embeds a hexdiff in its report
+#[cfg(feature = "mmap")]
+pub(crate) const EMBEDDED_CONTEXT: usize = 16;
+
+/// Hex dump of up to `max_regions` regions where `expected` and `actual` differ
+///
+/// Each run of differing bytes is widened by `context` bytes on both sides
+/// and rounded out to whole rows; regions whose widened ranges touch are
+/// merged into one. Bytes past the end of the shorter buffer count as
+/// differing and render as `--`. Returns an empty string for equal buffers.
+///
+/// # Example
+/// ```rust,ignore
+/// let diff = hexdiff(&expected, &actual, 16, 3);
+/// assert!(diff.is_empty(), "extraction differs:\n{}", diff);
+/// ```
+pub fn hexdiff(expected: &[u8], actual: &[u8], context: usize, max_regions: usize) -> String {
+    let runs = differing_runs(expected, actual);
+    if runs.is_empty() {
+        return String::new();
+    }
+    let len = expected.len().max(actual.len());
+    let regions = merge_regions(&runs, context, len);
+    let diff_bytes: usize = runs.iter().map(|r| r.len()).sum();
+
+    let mut out = format!(
+        "{} bytes differ in {} region{}\n",
+        diff_bytes,
+        regions.len(),
+        if regions.len() == 1 { "" } else { "s" }
+    );
+    for region in regions.iter().take(max_regions) {
+        let region_diff: usize = runs
+            .iter()
+            .filter(|r| r.start < region.end && r.end > region.start)
+            .map(|r| r.len())
+            .sum();
+        let _ = writeln!(
+            out,
+            "@@ offset {:#x} ({}), {} bytes, {} differ @@",
+            region.start,
+            region.start,
+            region.len(),
+            region_diff
+        );
+        for row in region.clone().step_by(ROW) {
+            let end = (row + ROW).min(len);
+            let a = slice(expected, row, end);
+            let b = slice(actual, row, end);
+            if a == b {
+                render_row(&mut out, ' ', row, a);
+            } else {
+                render_row(&mut out, '-', row, a);
+                render_row(&mut out, '+', row, b);
+            }
+        }
+    }
+    if regions.len() > max_regions {
+        let _ = writeln!(
+            out,
+            "... {} more region(s) not shown",
+            regions.len() - max_regions
+        );
+    }
+    out
+}
+
+/// Maximal ranges of differing bytes, including the tail of the longer buffer
+fn differing_runs(expected: &[u8], actual: &[u8]) -> Vec<Range<usize>> {
+    let common = expected.len().min(actual.len());
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    for i in (0..common).filter(|&i| expected[i] != actual[i]) {
+        match runs.last_mut() {
+            Some(run) if run.end == i => run.end += 1,
+            _ => runs.push(i..i + 1),
+        }
+    }
+    let len = expected.len().max(actual.len());
+    if len > common {
+        match runs.last_mut() {
+            Some(run) if run.end == common => run.end = len,
+            _ => runs.push(common..len),
+        }
+    }
+    runs
+}
+
+/// Widen `runs` by `context`, round out to rows and merge touching ranges
+fn merge_regions(runs: &[Range<usize>], context: usize, len: usize) -> Vec<Range<usize>> {
+    let mut regions: Vec<Range<usize>> = Vec::new();
+    for run in runs {
+        let start = run.start.saturating_sub(context) / ROW * ROW;
+        let end = run.end.saturating_add(context).min(len).div_ceil(ROW) * ROW;
+        match regions.last_mut() {
+            Some(last) if start <= last.end => last.end = last.end.max(end),
+            _ => regions.push(start..end),
+        }
+    }
+    regions
+}
+
+/// `data[start..end]`, clipped to the buffer
+fn slice(data: &[u8], start: usize, end: usize) -> &[u8] {
+    &data[start.min(data.len())..end.min(data.len())]
+}
+
+/// One `xxd`-style row; missing bytes render as `--`
+fn render_row(out: &mut String, sign: char, offset: usize, bytes: &[u8]) {
+    let _ = write!(out, "{}{:08x} ", sign, offset);
+    for i in 0..ROW {
+        if i == ROW / 2 {
+            out.push(' ');
+        }
+        match bytes.get(i) {
+            Some(b) => {
+                let _ = write!(out, " {:02x}", b);
+            }
+            None => out.push_str(" --"),
+        }
+    }
+    out.push_str("  |");
+    out.extend(bytes.iter().map(|&b| {
+        if b.is_ascii_graphic() || b == b' ' {
+            b as char
+        } else {
+            '.'
+        }
+    }));
+    out.push_str("|\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_separated_regions() {
+        let expected: Vec<u8> = (0..1024u32).map(|i| (i % 251) as u8).collect();
+        let mut actual = expected.clone();
+        actual[100] ^= 0xff;
+        actual[103] ^= 0xff;
+        actual[700] = b'X';
+
+        let diff = hexdiff(&expected, &actual, 8, 10);
+        assert!(
+            diff.starts_with("3 bytes differ in 2 regions\n"),
+            "{}",
+            diff
+        );
+        // 100 and 103 merge into one region, rounded out to rows
+        assert!(diff.contains("@@ offset 0x50 (80), 32 bytes, 2 differ @@"));
+        assert!(diff.contains("@@ offset 0x2b0 (688), 32 bytes, 1 differ @@"));
+        assert!(diff.contains("-00000060  "));
+        assert!(diff.contains("+00000060  "));
+        assert!(diff.contains("\n 00000050  "));
+        assert!(diff.contains("\n 000002c0  "));
+        assert!(diff.contains("-000002b0  "));
+        assert!(diff.contains("+000002b0  "));
+        // The ASCII gutter shows the injected byte
+        let plus_row = diff.lines().find(|l| l.starts_with("+000002b0")).unwrap();
+        assert!(plus_row.contains('X'), "{}", plus_row);
+
+        let first_only = hexdiff(&expected, &actual, 8, 1);
+        assert!(!first_only.contains("0x2b0"));
+        assert!(first_only.ends_with("... 1 more region(s) not shown\n"));
+
+        // A wide context merges both regions into one
+        assert!(hexdiff(&expected, &actual, 600, 10).contains("in 1 region\n"));
+        assert!(hexdiff(&expected, &expected, 8, 10).is_empty());
+    }
+
+    #[test]
+    fn test_length_mismatch_renders_missing_bytes() {
+        let expected = b"hello, world".to_vec();
+        let actual = b"hello, wor".to_vec();
+        let diff = hexdiff(&expected, &actual, 4, 1);
+        assert!(diff.starts_with("2 bytes differ in 1 region\n"), "{}", diff);
+        assert!(diff.contains("@@ offset 0x0 (0), 16 bytes, 2 differ @@"));
+        let plus_row = diff.lines().find(|l| l.starts_with('+')).unwrap();
+        assert!(plus_row.contains("72 -- --"), "{}", plus_row);
+        assert!(plus_row.ends_with("|hello, wor|"));
+    }
+}
@@ -0,0 +1,2494 @@
+//! Data integrity validation utilities
+//!
+//! Provides tools for validating:
+//! - Sparse vector invariants
+//! - VSA operation properties (commutativity, self-inverse, etc.)
+//! - Data corruption detection
+//! - Algebraic invariants
+//! - Directory fingerprinting via checksum manifests
+//! - Golden-file snapshots of encoded vectors ([`snapshots`])
+//! - Composable rolling checksums for chunked streams
+//! - Zeroed-region maps for packet-loss style damage ([`find_zero_runs`])
+//! - Randomized operation sequences with replayable traces ([`AlgebraFuzzer`])
+//! - Hex dumps of differing byte regions ([`hexdiff`])
+//! - Shard confinement of vector indices ([`assert_indices_within`])
+//! - Tree comparison that tolerates expected normalization ([`CompareOptions`])
+//! - Sparse/packed representation round trips ([`validate_pack_roundtrip`])
+//! - Similarity ranking across encoder configs ([`validate_similarity_preservation`])
+
+mod fuzz;
+mod hexdiff;
+mod packing;
+mod rolling;
+mod similarity;
+pub mod snapshots;
+mod tolerant;
+mod zero_runs;
+
+pub use fuzz::{AlgebraFuzzer, FuzzOp, FuzzTrace};
+pub use hexdiff::hexdiff;
+pub use packing::{sweep_pack_roundtrip, validate_pack_roundtrip};
+pub use rolling::{checksum_file_chunked, ChunkedChecksum, RollingChecksum};
+pub use similarity::validate_similarity_preservation;
+pub use tolerant::{compare_directories_tolerant, compare_files, CompareOptions};
+pub use zero_runs::{
+    find_zero_runs, find_zero_runs_in_file, summarize_runs, ZeroRun, ZeroRunSummary,
+};
+
+use crate::events;
+use crate::fixtures::{
+    metadata_mode, pattern_byte, read_xattrs, DatasetHeader, FileMetaSpec, TestDataPattern,
+    SELF_DESCRIBING_HEADER_LEN,
+};
+use crate::generators::hierarchy::{HierarchyNode, VsaHierarchy};
+use crate::generators::{
+    deterministic_sparse_vec, fingerprint, index_overlap, inverse_permutation, permute_sparse_vec,
+    random_sparse_vec, reference_bundle_many, special_vectors, OverlapStats,
+};
+use crate::metrics::format;
+use crate::progress::{ProgressOptions, ProgressSink, ProgressTracker};
+use embeddenator_vsa::SparseVec;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Read buffer size used when streaming file contents into a hasher
+const HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Validator name recorded for checks added through [`IntegrityReport::pass`]
+/// and [`IntegrityReport::fail`]
+pub const UNNAMED_VALIDATOR: &str = "unnamed";
+
+/// Results from integrity validation
+///
+/// Every check is kept as a [`CheckRecord`]; the counters and `failures`
+/// are maintained alongside the records for compatibility.
+#[derive(Clone, Debug, Default)]
+pub struct IntegrityReport {
+    /// Total checks performed
+    pub checks_total: u64,
+    /// Checks that passed
+    pub checks_passed: u64,
+    /// Detected bitflips (single bit errors)
+    pub bitflips_detected: u64,
+    /// Multi-bit corruption events
+    pub corruption_events: u64,
+    /// Algebraic invariant violations
+    pub invariant_violations: u64,
+    /// Specific failure messages
+    pub failures: Vec<String>,
+    /// Per-file mismatches found by manifest verification
+    pub file_mismatches: Vec<FileMismatch>,
+    /// Checks that could not run here (e.g. unsupported on this platform)
+    pub warnings: Vec<String>,
+    /// Differences accepted by [`CompareOptions`] rather than failed
+    pub tolerated: u64,
+    /// One record per check, in the order they ran
+    records: Vec<CheckRecord>,
+}
+
+/// Outcome of a single check
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckOutcome {
+    /// The check passed
+    Passed,
+    /// The check failed; see [`CheckRecord::detail`]
+    Failed,
+}
+
+/// A single named check and how it went
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckRecord {
+    /// Validator that ran the check, e.g. `validate_sparse`
+    pub validator: &'static str,
+    /// Check within the validator, e.g. `no_overlap`
+    pub check: String,
+    /// Whether the check passed
+    pub outcome: CheckOutcome,
+    /// Time spent on the check
+    pub duration_ns: u64,
+    /// Failure message, if any
+    pub detail: Option<String>,
+}
+
+/// Kind of mismatch detected when verifying a directory against a manifest
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MismatchKind {
+    /// Same size, different content hash
+    Content,
+    /// File size differs
+    Size,
+    /// Permission bits differ
+    Permissions,
+    /// File is in the manifest but not on disk
+    Missing,
+    /// File is on disk but not in the manifest
+    Extra,
+    /// Symlink points at a different target
+    LinkTarget,
+    /// Modification time differs beyond filesystem granularity
+    Mtime,
+}
+
+/// A single file-level mismatch found by [`ChecksumManifest::verify`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileMismatch {
+    /// Path relative to the verified directory
+    pub path: String,
+    /// What kind of mismatch was detected
+    pub kind: MismatchKind,
+}
+
+impl IntegrityReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if all validations passed
+    pub fn is_ok(&self) -> bool {
+        self.checks_passed == self.checks_total && self.failures.is_empty()
+    }
+
+    /// Pass rate as percentage
+    pub fn pass_rate(&self) -> f64 {
+        if self.checks_total == 0 {
+            100.0
+        } else {
+            (self.checks_passed as f64 / self.checks_total as f64) * 100.0
+        }
+    }
+
+    /// Record a passed check
+    pub fn pass(&mut self) {
+        self.record_check(UNNAMED_VALIDATOR, "check", Duration::ZERO, None);
+    }
+
+    /// Record a failed check with message
+    pub fn fail(&mut self, msg: impl Into<String>) {
+        self.record_check(UNNAMED_VALIDATOR, "check", Duration::ZERO, Some(msg.into()));
+    }
+
+    /// Record the outcome of a named check
+    ///
+    /// `failure` is the failure message, or `None` if the check passed.
+    /// Counters and `failures` are updated exactly as by `pass`/`fail`.
+    pub fn record_check(
+        &mut self,
+        validator: &'static str,
+        check: impl Into<String>,
+        duration: Duration,
+        failure: Option<String>,
+    ) {
+        self.checks_total += 1;
+        let outcome = match &failure {
+            None => {
+                self.checks_passed += 1;
+                CheckOutcome::Passed
+            }
+            Some(msg) => {
+                self.failures.push(msg.clone());
+                CheckOutcome::Failed
+            }
+        };
+        self.records.push(CheckRecord {
+            validator,
+            check: check.into(),
+            outcome,
+            duration_ns: duration.as_nanos() as u64,
+            detail: failure,
+        });
+    }
+
+    /// Every recorded check, in the order they ran
+    pub fn records(&self) -> &[CheckRecord] {
+        &self.records
+    }
+
+    /// The `n` slowest checks, slowest first
+    pub fn slowest_checks(&self, n: usize) -> Vec<&CheckRecord> {
+        let mut records: Vec<&CheckRecord> = self.records.iter().collect();
+        records.sort_by_key(|r| std::cmp::Reverse(r.duration_ns));
+        records.truncate(n);
+        records
+    }
+
+    /// Failed checks recorded by `validator`
+    pub fn failures_for(&self, validator: &str) -> Vec<&CheckRecord> {
+        self.records
+            .iter()
+            .filter(|r| r.validator == validator && r.outcome == CheckOutcome::Failed)
+            .collect()
+    }
+
+    /// Record a check that was skipped or downgraded, without failing
+    pub fn warn(&mut self, msg: impl Into<String>) {
+        self.warnings.push(msg.into());
+    }
+
+    /// Record detected bitflip
+    pub fn record_bitflip(&mut self) {
+        self.bitflips_detected += 1;
+    }
+
+    /// Record corruption event
+    pub fn record_corruption(&mut self) {
+        self.corruption_events += 1;
+    }
+
+    /// Record a difference that was accepted instead of failed
+    pub fn record_tolerated(&mut self) {
+        self.tolerated += 1;
+    }
+
+    /// Record invariant violation
+    ///
+    /// Adds a failure without counting a check; use
+    /// [`record_invariant_check`](Self::record_invariant_check) to record a
+    /// named, counted check instead.
+    pub fn record_invariant_violation(&mut self, msg: impl Into<String>) {
+        self.invariant_violations += 1;
+        self.failures.push(format!("INVARIANT: {}", msg.into()));
+    }
+
+    /// Record the outcome of a named invariant check
+    ///
+    /// Like [`record_check`](Self::record_check); a `violation` is also
+    /// counted in `invariant_violations` and prefixed with `INVARIANT: `.
+    pub fn record_invariant_check(
+        &mut self,
+        validator: &'static str,
+        check: impl Into<String>,
+        duration: Duration,
+        violation: Option<String>,
+    ) {
+        if violation.is_some() {
+            self.invariant_violations += 1;
+        }
+        let failure = violation.map(|msg| format!("INVARIANT: {}", msg));
+        self.record_check(validator, check, duration, failure);
+    }
+
+    /// Fold another report's checks and failures into this one
+    pub fn merge(&mut self, other: &IntegrityReport) {
+        self.checks_total += other.checks_total;
+        self.checks_passed += other.checks_passed;
+        self.bitflips_detected += other.bitflips_detected;
+        self.corruption_events += other.corruption_events;
+        self.invariant_violations += other.invariant_violations;
+        self.tolerated += other.tolerated;
+        self.failures.extend(other.failures.iter().cloned());
+        self.file_mismatches
+            .extend(other.file_mismatches.iter().cloned());
+        self.warnings.extend(other.warnings.iter().cloned());
+        self.records.extend(other.records.iter().cloned());
+    }
+
+    /// Record a file-level mismatch as a failed check
+    pub fn record_file_mismatch(&mut self, path: impl Into<String>, kind: MismatchKind) {
+        let path = path.into();
+        if matches!(kind, MismatchKind::Content | MismatchKind::Size) {
+            self.record_corruption();
+        }
+        self.fail(format!("{:?} mismatch: {}", kind, path));
+        self.file_mismatches.push(FileMismatch { path, kind });
+    }
+
+    /// Generate summary report
+    pub fn summary(&self) -> String {
+        format!(
+            "Integrity Report:\n\
+             - Total checks: {}\n\
+             - Passed: {}\n\
+             - Failed: {}\n\
+             - Pass rate: {:.1}%\n\
+             - Bitflips: {}\n\
+             - Corruption events: {}\n\
+             - Invariant violations: {}\n\
+             - Tolerated differences: {}\n\
+             - Warnings: {}",
+            format::count(self.checks_total),
+            format::count(self.checks_passed),
+            format::count(self.checks_total - self.checks_passed),
+            self.pass_rate(),
+            format::count(self.bitflips_detected),
+            format::count(self.corruption_events),
+            format::count(self.invariant_violations),
+            format::count(self.tolerated),
+            format::count(self.warnings.len() as u64)
+        )
+    }
+}
+
+/// Allowed cosine drift when checking that a permutation preserves similarity
+const PERMUTATION_COSINE_TOLERANCE: f64 = 1e-9;
+
+/// Allowed cosine shortfall between a bundling path and the reference bundle
+const BUNDLE_SIMILARITY_TOLERANCE: f64 = 0.05;
+
+/// Non-descendants compared against each parent in
+/// [`IntegrityValidator::validate_hierarchy`]
+const HIERARCHY_NON_DESCENDANT_SAMPLES: usize = 16;
+
+/// Default number of differing indices listed by [`IntegrityValidator::detect_differences`]
+const DEFAULT_MAX_REPORTED_INDICES: usize = 8;
+
+/// One verbose-mode diagnostic emitted by [`IntegrityValidator`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// Check name, e.g. `validate_sparse.sorted_pos`
+    pub check: &'static str,
+    /// Whether the check passed
+    pub passed: bool,
+    /// Input summary and measured values
+    pub details: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}: {}",
+            if self.passed { "PASS" } else { "FAIL" },
+            self.check,
+            self.details
+        )
+    }
+}
+
+/// Receiver of [`IntegrityValidator`] diagnostics in verbose mode
+pub trait DiagnosticSink: Send + Sync {
+    /// Handle one diagnostic
+    fn emit(&self, diagnostic: &Diagnostic);
+}
+
+impl<F> DiagnosticSink for F
+where
+    F: Fn(&Diagnostic) + Send + Sync,
+{
+    fn emit(&self, diagnostic: &Diagnostic) {
+        self(diagnostic)
+    }
+}
+
+/// Sink printing each diagnostic to stderr (the verbose default)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StderrDiagnostics;
+
+impl DiagnosticSink for StderrDiagnostics {
+    fn emit(&self, diagnostic: &Diagnostic) {
+        eprintln!("{}", diagnostic);
+    }
+}
+
+/// Sink collecting formatted diagnostics for later inspection
+///
+/// Clones share the same buffer, so keep one clone and hand the other to
+/// [`IntegrityValidator::with_diagnostic_sink`].
+#[derive(Clone, Debug, Default)]
+pub struct CollectingDiagnostics {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl CollectingDiagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Diagnostics collected so far, formatted as with `Display`
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().clone()
+    }
+}
+
+impl DiagnosticSink for CollectingDiagnostics {
+    fn emit(&self, diagnostic: &Diagnostic) {
+        self.lines.lock().unwrap().push(diagnostic.to_string());
+    }
+}
+
+/// Validates data integrity for VSA operations
+pub struct IntegrityValidator {
+    /// Enable verbose logging
+    pub verbose: bool,
+    /// Maximum concrete indices listed in a difference failure message
+    pub max_reported_indices: usize,
+    /// Exclusive upper bound on indices checked by `validate_sparse`
+    pub dims: Option<usize>,
+    sink: Arc<dyn DiagnosticSink>,
+}
+
+impl IntegrityValidator {
+    pub fn new() -> Self {
+        Self {
+            verbose: false,
+            max_reported_indices: DEFAULT_MAX_REPORTED_INDICES,
+            dims: None,
+            sink: Arc::new(StderrDiagnostics),
+        }
+    }
+
+    /// Emit a diagnostic for every check (to stderr unless a sink is installed)
+    pub fn verbose(mut self) -> Self {
+        self.verbose = true;
+        self
+    }
+
+    /// Route verbose diagnostics to `sink`
+    ///
+    /// Has no effect unless [`verbose`](Self::verbose) is also enabled.
+    pub fn with_diagnostic_sink(mut self, sink: impl DiagnosticSink + 'static) -> Self {
+        self.sink = Arc::new(sink);
+        self
+    }
+
+    /// Set how many differing indices are listed in failure messages
+    pub fn with_max_reported_indices(mut self, n: usize) -> Self {
+        self.max_reported_indices = n;
+        self
+    }
+
+    /// Make `validate_sparse` also reject indices `>= dims`
+    pub fn with_dims(mut self, dims: usize) -> Self {
+        self.dims = Some(dims);
+        self
+    }
+
+    /// Log the outcome of `check` to the event log and hand the report back
+    fn finish(&self, check: &'static str, report: IntegrityReport) -> IntegrityReport {
+        events::emit("integrity", "check", || {
+            serde_json::json!({
+                "check": check,
+                "checks_total": report.checks_total,
+                "checks_passed": report.checks_passed,
+                "failures": report.failures,
+            })
+        });
+        report
+    }
+
+    /// Emit a diagnostic if verbose; `details` is only formatted when needed
+    fn diagnose(&self, check: &'static str, passed: bool, details: impl FnOnce() -> String) {
+        if self.verbose {
+            self.sink.emit(&Diagnostic {
+                check,
+                passed,
+                details: details(),
+            });
+        }
+    }
+
+    /// Record one check started at `started` and emit its diagnostic
+    ///
+    /// `check` is `validator.name`, e.g. `validate_sparse.no_overlap`.
+    fn check(
+        &self,
+        report: &mut IntegrityReport,
+        check: &'static str,
+        started: Instant,
+        failure: Option<String>,
+        details: impl FnOnce() -> String,
+    ) {
+        self.diagnose(check, failure.is_none(), details);
+        let (validator, name) = check.split_once('.').unwrap_or((check, check));
+        report.record_check(validator, name, started.elapsed(), failure);
+    }
+
+    /// Like [`check`](Self::check), counting a failure as an invariant violation
+    fn check_invariant(
+        &self,
+        report: &mut IntegrityReport,
+        check: &'static str,
+        started: Instant,
+        violation: Option<String>,
+        details: impl FnOnce() -> String,
+    ) {
+        self.diagnose(check, violation.is_none(), details);
+        let (validator, name) = check.split_once('.').unwrap_or((check, check));
+        report.record_invariant_check(validator, name, started.elapsed(), violation);
+    }
+
+    /// Validate sparse vector invariants
+    ///
+    /// Checks:
+    /// - No overlap between pos and neg indices
+    /// - Indices are sorted
+    /// - No duplicate indices
+    /// - Indices are below the dimension, if set with [`with_dims`](Self::with_dims)
+    pub fn validate_sparse(&self, v: &SparseVec) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+
+        // Check no overlap between pos and neg
+        let started = Instant::now();
+        let pos_set: HashSet<_> = v.pos.iter().collect();
+        let neg_set: HashSet<_> = v.neg.iter().collect();
+        let overlap = pos_set.intersection(&neg_set).count();
+        if overlap > 0 {
+            report.record_corruption();
+        }
+        self.check(
+            &mut report,
+            "validate_sparse.no_overlap",
+            started,
+            (overlap > 0).then(|| "Overlap between pos and neg indices".to_string()),
+            || format!("nnz=+{}/-{} overlap={}", v.pos.len(), v.neg.len(), overlap),
+        );
+
+        // Check sorted
+        let started = Instant::now();
+        let pos_sorted = v.pos.windows(2).all(|w| w[0] < w[1]);
+        self.check(
+            &mut report,
+            "validate_sparse.pos_sorted",
+            started,
+            (!pos_sorted).then(|| "pos indices not sorted".to_string()),
+            || format!("pos nnz={}", v.pos.len()),
+        );
+
+        let started = Instant::now();
+        let neg_sorted = v.neg.windows(2).all(|w| w[0] < w[1]);
+        self.check(
+            &mut report,
+            "validate_sparse.neg_sorted",
+            started,
+            (!neg_sorted).then(|| "neg indices not sorted".to_string()),
+            || format!("neg nnz={}", v.neg.len()),
+        );
+
+        if let Some(dims) = self.dims {
+            let started = Instant::now();
+            let out_of_range: Vec<usize> = v
+                .pos
+                .iter()
+                .chain(&v.neg)
+                .copied()
+                .filter(|&i| i >= dims)
+                .collect();
+            if !out_of_range.is_empty() {
+                report.record_corruption();
+            }
+            self.check(
+                &mut report,
+                "validate_sparse.in_range",
+                started,
+                (!out_of_range.is_empty()).then(|| {
+                    format!(
+                        "{} indices >= dims {} in {}: {:?}",
+                        out_of_range.len(),
+                        dims,
+                        fingerprint(v),
+                        &out_of_range[..out_of_range.len().min(self.max_reported_indices)]
+                    )
+                }),
+                || format!("dims={}", dims),
+            );
+        }
+
+        self.finish("validate_sparse", report)
+    }
+
+    /// Validate algebraic invariants for bind operation
+    ///
+    /// Checks:
+    /// - Commutativity: A ⊙ B = B ⊙ A
+    pub fn validate_bind_invariants(&self, a: &SparseVec, b: &SparseVec) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+
+        // Commutativity check
+        let started = Instant::now();
+        let ab = a.bind(b);
+        let ba = b.bind(a);
+        let commutes = ab.pos == ba.pos && ab.neg == ba.neg;
+        self.check_invariant(
+            &mut report,
+            "validate_bind.commutativity",
+            started,
+            (!commutes).then(|| "Commutativity violation: A⊙B ≠ B⊙A".to_string()),
+            || {
+                format!(
+                    "nnz a={} b={} ab={} ba={}",
+                    a.pos.len() + a.neg.len(),
+                    b.pos.len() + b.neg.len(),
+                    ab.pos.len() + ab.neg.len(),
+                    ba.pos.len() + ba.neg.len()
+                )
+            },
+        );
+
+        self.finish("validate_bind", report)
+    }
+
+    /// Validate bundle operation properties
+    pub fn validate_bundle_invariants(&self, a: &SparseVec, b: &SparseVec) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+
+        // Commutativity check
+        let started = Instant::now();
+        let ab = a.bundle(b);
+        let ba = b.bundle(a);
+        let commutes = ab.pos == ba.pos && ab.neg == ba.neg;
+        self.check_invariant(
+            &mut report,
+            "validate_bundle.commutativity",
+            started,
+            (!commutes).then(|| "Bundle commutativity violation: A⊕B ≠ B⊕A".to_string()),
+            || {
+                format!(
+                    "nnz a={} b={} ab={} ba={}",
+                    a.pos.len() + a.neg.len(),
+                    b.pos.len() + b.neg.len(),
+                    ab.pos.len() + ab.neg.len(),
+                    ba.pos.len() + ba.neg.len()
+                )
+            },
+        );
+
+        self.finish("validate_bundle", report)
+    }
+
+    /// Validate a permutation operator against `v`
+    ///
+    /// Checks:
+    /// - `perm` is a bijection on `0..perm.len()`
+    /// - Permuting then inverse-permuting recovers `v`
+    /// - Permutation preserves nnz
+    /// - Cosine between `v` and a correlated partner is unchanged when both
+    ///   are permuted by `perm`
+    pub fn validate_permutation_invariants(
+        &self,
+        v: &SparseVec,
+        perm: &[usize],
+    ) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+
+        let started = Instant::now();
+        let mut seen = vec![false; perm.len()];
+        let bijective = perm
+            .iter()
+            .all(|&p| p < seen.len() && !std::mem::replace(&mut seen[p], true));
+        self.check_invariant(
+            &mut report,
+            "validate_permutation.bijective",
+            started,
+            (!bijective).then(|| "perm is not a permutation of 0..len".to_string()),
+            || format!("perm len={}", perm.len()),
+        );
+        if !bijective {
+            return self.finish("validate_permutation", report);
+        }
+
+        // Only recorded when it fails: it guards the remaining checks
+        let started = Instant::now();
+        let max_index = v.pos.iter().chain(&v.neg).max();
+        let in_range = max_index.is_none_or(|&i| i < perm.len());
+        if !in_range {
+            self.check_invariant(
+                &mut report,
+                "validate_permutation.in_range",
+                started,
+                Some(format!(
+                    "vector index exceeds permutation length {}",
+                    perm.len()
+                )),
+                || format!("max index={:?} perm len={}", max_index, perm.len()),
+            );
+            return self.finish("validate_permutation", report);
+        }
+        self.diagnose("validate_permutation.in_range", true, || {
+            format!("max index={:?} perm len={}", max_index, perm.len())
+        });
+
+        let started = Instant::now();
+        let permuted = permute_sparse_vec(v, perm);
+        let restored = permute_sparse_vec(&permuted, &inverse_permutation(perm));
+        let round_trips = restored.pos == v.pos && restored.neg == v.neg;
+        self.check_invariant(
+            &mut report,
+            "validate_permutation.round_trip",
+            started,
+            (!round_trips).then(|| "inverse permutation did not recover original".to_string()),
+            || {
+                format!(
+                    "nnz=+{}/-{} restored=+{}/-{}",
+                    v.pos.len(),
+                    v.neg.len(),
+                    restored.pos.len(),
+                    restored.neg.len()
+                )
+            },
+        );
+
+        let started = Instant::now();
+        let nnz = v.pos.len() + v.neg.len();
+        let permuted_nnz = permuted.pos.len() + permuted.neg.len();
+        let nnz_kept = permuted.pos.len() == v.pos.len() && permuted.neg.len() == v.neg.len();
+        self.check_invariant(
+            &mut report,
+            "validate_permutation.nnz",
+            started,
+            (!nnz_kept).then(|| format!("nnz changed: {} -> {}", nnz, permuted_nnz)),
+            || format!("nnz {} -> {}", nnz, permuted_nnz),
+        );
+
+        // Partner shares roughly half its support with v so the cosine is non-trivial
+        let started = Instant::now();
+        let partner = v.bundle(&deterministic_sparse_vec(perm.len(), nnz, 0x5eed));
+        let before = v.cosine(&partner);
+        let after = permuted.cosine(&permute_sparse_vec(&partner, perm));
+        let preserved = (before - after).abs() <= PERMUTATION_COSINE_TOLERANCE;
+        self.check_invariant(
+            &mut report,
+            "validate_permutation.cosine",
+            started,
+            (!preserved).then(|| format!("cosine not preserved: {:.6} -> {:.6}", before, after)),
+            || format!("cosine {:.6} -> {:.6}", before, after),
+        );
+
+        self.finish("validate_permutation", report)
+    }
+
+    /// Compare the crate's bundling paths against [`reference_bundle_many`]
+    ///
+    /// Checks that both iterated pairwise `bundle()` and
+    /// `SparseVec::bundle_sum_many` stay within a cosine tolerance of the
+    /// majority-vote reference. Exact equality is not required because
+    /// tie-breaking may legitimately differ.
+    pub fn validate_bundle_many(&self, vecs: &[SparseVec]) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+        let Some((first, rest)) = vecs.split_first() else {
+            return self.finish("validate_bundle_many", report);
+        };
+
+        let reference = reference_bundle_many(vecs);
+        let pairwise = || rest.iter().fold(first.clone(), |acc, v| acc.bundle(v));
+        let sum_many = || SparseVec::bundle_sum_many(vecs.iter());
+
+        for (check, name, bundle) in [
+            (
+                "validate_bundle_many.pairwise",
+                "pairwise",
+                &pairwise as &dyn Fn() -> SparseVec,
+            ),
+            ("validate_bundle_many.sum_many", "sum-many", &sum_many),
+        ] {
+            let started = Instant::now();
+            let bundled = bundle();
+            let similarity = bundled.cosine(&reference);
+            let close = similarity >= 1.0 - BUNDLE_SIMILARITY_TOLERANCE;
+            self.check_invariant(
+                &mut report,
+                check,
+                started,
+                (!close).then(|| {
+                    format!(
+                        "{} bundle of {} vectors diverges from reference: cosine {:.4}",
+                        name,
+                        vecs.len(),
+                        similarity
+                    )
+                }),
+                || {
+                    format!(
+                        "vectors={} reference nnz={} cosine={:.4}",
+                        vecs.len(),
+                        reference.pos.len() + reference.neg.len(),
+                        similarity
+                    )
+                },
+            );
+        }
+
+        self.finish("validate_bundle_many", report)
+    }
+
+    /// Check that every parent in `hierarchy` is the bundle of its children
+    ///
+    /// For each internal node, every child must have cosine at least
+    /// `min_parent_child_cosine` with the parent, and the least similar
+    /// child must still beat every non-descendant at the children's level
+    /// (up to 16 of them, evenly spaced).
+    pub fn validate_hierarchy(
+        &self,
+        hierarchy: &VsaHierarchy,
+        min_parent_child_cosine: f64,
+    ) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+        let nodes = hierarchy.nodes();
+
+        for (path, node) in nodes.iter().filter(|(_, node)| !node.is_leaf()) {
+            let started = Instant::now();
+            let (weakest, weakest_cosine) = node
+                .children
+                .iter()
+                .enumerate()
+                .map(|(i, child)| (i, node.vector.cosine(&child.vector)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .expect("internal node has children");
+            let others: Vec<&(Vec<usize>, &HierarchyNode)> = nodes
+                .iter()
+                .filter(|(other, _)| other.len() == path.len() + 1 && !other.starts_with(path))
+                .collect();
+            let stride = others
+                .len()
+                .div_ceil(HIERARCHY_NON_DESCENDANT_SAMPLES)
+                .max(1);
+            let closest_other = others
+                .iter()
+                .step_by(stride)
+                .map(|(other, n)| (other, node.vector.cosine(&n.vector)))
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+
+            let failure = if weakest_cosine < min_parent_child_cosine {
+                Some(format!(
+                    "node {:?}: child {} has cosine {:.4}, below {:.4}",
+                    path, weakest, weakest_cosine, min_parent_child_cosine
+                ))
+            } else {
+                closest_other
+                    .filter(|&(_, cosine)| cosine >= weakest_cosine)
+                    .map(|(other, cosine)| {
+                        format!(
+                            "node {:?}: non-descendant {:?} has cosine {:.4}, own child {} only {:.4}",
+                            path, other, cosine, weakest, weakest_cosine
+                        )
+                    })
+            };
+            self.check_invariant(
+                &mut report,
+                "validate_hierarchy.parent_child",
+                started,
+                failure,
+                || {
+                    format!(
+                        "node {:?} children={} min child cosine={:.4} max other cosine={:.4}",
+                        path,
+                        node.children.len(),
+                        weakest_cosine,
+                        closest_other.map_or(0.0, |(_, c)| c)
+                    )
+                },
+            );
+        }
+
+        self.finish("validate_hierarchy", report)
+    }
+
+    /// Detect potential corruption by comparing two vectors
+    ///
+    /// Compares the symmetric difference of the pos and neg index sets, so
+    /// vectors of equal length but different content are caught. Failure
+    /// messages list up to [`max_reported_indices`](Self::max_reported_indices)
+    /// differing indices plus the [`OverlapStats`](crate::generators::OverlapStats)
+    /// (overlap counts, ternary Hamming distance and Jaccard similarity).
+    pub fn detect_differences(&self, expected: &SparseVec, actual: &SparseVec) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+        let stats = index_overlap(expected, actual);
+
+        for (check, name, expected, actual) in [
+            ("detect_differences.pos", "pos", &expected.pos, &actual.pos),
+            ("detect_differences.neg", "neg", &expected.neg, &actual.neg),
+        ] {
+            let started = Instant::now();
+            let differing = symmetric_difference_sorted(expected, actual);
+            let failure = (!differing.is_empty()).then(|| {
+                let shown: Vec<String> = differing
+                    .iter()
+                    .take(self.max_reported_indices)
+                    .map(|i| i.to_string())
+                    .collect();
+                let more = differing.len().saturating_sub(shown.len());
+                format!(
+                    "{} indices differ at {} positions: [{}{}] ({})",
+                    name,
+                    differing.len(),
+                    shown.join(", "),
+                    if more > 0 {
+                        format!(", ... {} more", more)
+                    } else {
+                        String::new()
+                    },
+                    stats
+                )
+            });
+            if failure.is_some() {
+                report.record_corruption();
+            }
+            self.check(&mut report, check, started, failure, || {
+                format!(
+                    "expected nnz={} actual nnz={} differing={} ({})",
+                    expected.len(),
+                    actual.len(),
+                    differing.len(),
+                    stats
+                )
+            });
+        }
+
+        self.finish("detect_differences", report)
+    }
+
+    /// Check that `serialize` followed by `deserialize` reproduces `v` exactly
+    ///
+    /// The round-tripped vector must have identical `pos` and `neg` lists,
+    /// including order. Failures carry the same index-level diff summary as
+    /// [`assert_sparse_eq`].
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let report = validator.validate_serialization_roundtrip(
+    ///     &engram_root,
+    ///     |v| bincode::serialize(v).unwrap(),
+    ///     |bytes| bincode::deserialize(bytes).unwrap(),
+    /// );
+    /// ```
+    pub fn validate_serialization_roundtrip<S, D>(
+        &self,
+        v: &SparseVec,
+        serialize: S,
+        deserialize: D,
+    ) -> IntegrityReport
+    where
+        S: Fn(&SparseVec) -> Vec<u8>,
+        D: Fn(&[u8]) -> SparseVec,
+    {
+        let mut report = IntegrityReport::default();
+        self.check_roundtrip(&mut report, v, &serialize, &deserialize);
+        self.finish("validate_serialization_roundtrip", report)
+    }
+
+    /// Round-trip an empty vector, a maximally dense vector and `iterations`
+    /// seeded random vectors of varying sparsity
+    ///
+    /// Random vectors draw their nnz log-uniformly from `1..=dims / 2`; the
+    /// dense vector sets every index in `0..dims` (even indices positive,
+    /// odd negative). One check is recorded per vector.
+    pub fn fuzz_serialization_roundtrip<S, D>(
+        &self,
+        dims: usize,
+        iterations: usize,
+        seed: u64,
+        serialize: S,
+        deserialize: D,
+    ) -> IntegrityReport
+    where
+        S: Fn(&SparseVec) -> Vec<u8>,
+        D: Fn(&[u8]) -> SparseVec,
+    {
+        let mut report = IntegrityReport::default();
+        let empty = special_vectors::empty();
+        let dense = special_vectors::max_density(dims);
+        self.check_roundtrip(&mut report, &empty, &serialize, &deserialize);
+        self.check_roundtrip(&mut report, &dense, &serialize, &deserialize);
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let max_nnz = (dims / 2).max(1) as f64;
+        for _ in 0..iterations {
+            let nnz = max_nnz.powf(rng.random::<f64>()).round() as usize;
+            let v = random_sparse_vec(&mut rng, dims, nnz);
+            self.check_roundtrip(&mut report, &v, &serialize, &deserialize);
+        }
+
+        self.finish("validate_serialization_roundtrip", report)
+    }
+
+    fn check_roundtrip(
+        &self,
+        report: &mut IntegrityReport,
+        v: &SparseVec,
+        serialize: &dyn Fn(&SparseVec) -> Vec<u8>,
+        deserialize: &dyn Fn(&[u8]) -> SparseVec,
+    ) {
+        let started = Instant::now();
+        let bytes = serialize(v);
+        let restored = deserialize(&bytes);
+        let exact = restored.pos == v.pos && restored.neg == v.neg;
+        let stats = index_overlap(v, &restored);
+        if !exact {
+            report.record_corruption();
+        }
+        self.check(
+            report,
+            "validate_serialization_roundtrip.exact",
+            started,
+            (!exact).then(|| {
+                format!(
+                    "round trip through {} bytes is lossy\n{}",
+                    bytes.len(),
+                    sparse_diff_summary(v, &restored, &stats)
+                )
+            }),
+            || {
+                format!(
+                    "nnz=+{}/-{} bytes={} hamming={}",
+                    v.pos.len(),
+                    v.neg.len(),
+                    bytes.len(),
+                    stats.hamming()
+                )
+            },
+        );
+    }
+}
+
+/// Indices present in exactly one of two sorted slices, in ascending order
+fn symmetric_difference_sorted(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => {
+                out.push(a[i]);
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                out.push(b[j]);
+                j += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out.extend_from_slice(&a[i..]);
+    out.extend_from_slice(&b[j..]);
+    out
+}
+
+/// Number of differing indices per sign shown by the sparse assertions
+const ASSERT_SHOWN_INDICES: usize = 8;
+
+/// Assert that two sparse vectors are identical, with a readable diff
+///
+/// On failure the panic message reports each vector's
+/// [`VecFingerprint`](crate::generators::VecFingerprint)
+/// and nnz, how many positions differ, the first few differing indices per sign and the
+/// cosine similarity, instead of dumping both index lists.
+///
+/// # Example
+/// ```rust,ignore
+/// assert_sparse_eq(&decoded, &original);
+/// ```
+#[track_caller]
+pub fn assert_sparse_eq(left: &SparseVec, right: &SparseVec) {
+    let stats = index_overlap(left, right);
+    if stats.hamming() != 0 {
+        panic!(
+            "sparse vectors differ\n{}",
+            sparse_diff_summary(left, right, &stats)
+        );
+    }
+}
+
+/// Assert that two sparse vectors have cosine similarity of at least `min_cosine`
+///
+/// Failure messages carry the same diff summary as [`assert_sparse_eq`].
+#[track_caller]
+pub fn assert_sparse_similar(left: &SparseVec, right: &SparseVec, min_cosine: f64) {
+    let stats = index_overlap(left, right);
+    if stats.cosine() < min_cosine {
+        panic!(
+            "sparse vectors not similar enough: cosine {:.4} < {:.4}\n{}",
+            stats.cosine(),
+            min_cosine,
+            sparse_diff_summary(left, right, &stats)
+        );
+    }
+}
+
+/// Check that every non-zero index of `v` lies inside `range`
+///
+/// Records one check per sign; a failure names how many indices escaped
+/// and the first few of them, as for shard-routing tests built on
+/// [`shard_confined_vec`](crate::generators::shard_confined_vec).
+pub fn assert_indices_within(v: &SparseVec, range: Range<usize>) -> IntegrityReport {
+    const VALIDATOR: &str = "assert_indices_within";
+    const SHOWN: usize = 8;
+
+    let mut report = IntegrityReport::new();
+    for (sign, indices) in [("positive", &v.pos), ("negative", &v.neg)] {
+        let started = Instant::now();
+        let outside: Vec<usize> = indices
+            .iter()
+            .copied()
+            .filter(|i| !range.contains(i))
+            .collect();
+        let failure = (!outside.is_empty()).then(|| {
+            let shown: Vec<String> = outside.iter().take(SHOWN).map(|i| i.to_string()).collect();
+            format!(
+                "{} {} indices outside {}..{} in {}: [{}{}]",
+                outside.len(),
+                sign,
+                range.start,
+                range.end,
+                fingerprint(v),
+                shown.join(", "),
+                if outside.len() > SHOWN { ", ..." } else { "" }
+            )
+        });
+        report.record_check(VALIDATOR, sign, started.elapsed(), failure);
+    }
+    report
+}
+
+/// Multi-line description of how `left` and `right` differ
+fn sparse_diff_summary(left: &SparseVec, right: &SparseVec, stats: &OverlapStats) -> String {
+    let shown = |a: &[usize], b: &[usize]| {
+        let differing = symmetric_difference_sorted(a, b);
+        let mut shown: Vec<String> = differing
+            .iter()
+            .take(ASSERT_SHOWN_INDICES)
+            .map(|i| i.to_string())
+            .collect();
+        if differing.len() > shown.len() {
+            shown.push(format!("... {} more", differing.len() - shown.len()));
+        }
+        format!("[{}]", shown.join(", "))
+    };
+    format!(
+        "  vectors: left={} right={}\n  \
+         nnz: left={} right={}\n  \
+         differing indices: {}\n  \
+         pos differing: {}\n  \
+         neg differing: {}\n  \
+         cosine: {:.4}\n  \
+         overlap: {}",
+        fingerprint(left),
+        fingerprint(right),
+        left.pos.len() + left.neg.len(),
+        right.pos.len() + right.neg.len(),
+        stats.hamming(),
+        shown(&left.pos, &right.pos),
+        shown(&left.neg, &right.neg),
+        stats.cosine(),
+        stats
+    )
+}
+
+impl Default for IntegrityValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fingerprint of a single file inside a [`ChecksumManifest`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChecksum {
+    /// Hex-encoded BLAKE3 hash of the file content
+    pub hash: String,
+    /// File size in bytes
+    pub size: u64,
+    /// Permission bits (Unix mode, or a read-only approximation elsewhere)
+    pub mode: u32,
+}
+
+/// Content-addressed fingerprint of a directory tree
+///
+/// Captures a checksum for every regular file so a directory can be
+/// verified after extraction without keeping the original around.
+///
+/// # Example
+/// ```rust,ignore
+/// let manifest = ChecksumManifest::capture(&input_dir)?;
+/// manifest.save(&manifest_path)?;
+/// // ... ingest, delete originals, extract ...
+/// let report = ChecksumManifest::load(&manifest_path)?.verify(&output_dir)?;
+/// assert!(report.is_ok(), "{}", report.summary());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecksumManifest {
+    /// Relative path (with `/` separators) to file checksum
+    pub entries: BTreeMap<String, FileChecksum>,
+    /// Relative path to symlink target, as stored in the link
+    ///
+    /// Links are never followed, so dangling links and links out of the
+    /// tree are recorded like any other.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub symlinks: BTreeMap<String, String>,
+}
+
+impl ChecksumManifest {
+    /// Capture checksums for every file under `dir`, hashing files sequentially
+    pub fn capture(dir: &Path) -> io::Result<Self> {
+        Self::capture_with(dir, &CaptureOptions::default())
+    }
+
+    /// Capture checksums for every file under `dir`, hashing files in parallel
+    pub fn capture_parallel(dir: &Path) -> io::Result<Self> {
+        Self::capture_with(dir, &CaptureOptions::new().parallel())
+    }
+
+    /// Capture checksums for every file under `dir` using `options`
+    ///
+    /// Progress is reported in bytes hashed under the phase `"checksum"`.
+    /// Symlinks are recorded by target; FIFOs, sockets and device nodes are
+    /// skipped without being opened.
+    pub fn capture_with(dir: &Path, options: &CaptureOptions) -> io::Result<Self> {
+        let (files, symlinks) = collect_entries(dir)?;
+        let mut total = 0;
+        for (_, path) in &files {
+            total += fs::metadata(path)?.len();
+        }
+
+        let tracker = options.progress.tracker(Some(total), "checksum");
+        let hash = |(rel, path): &(String, PathBuf)| -> io::Result<(String, FileChecksum)> {
+            Ok((rel.clone(), checksum_file(path, Some(&tracker))?))
+        };
+        let entries = if options.parallel {
+            files.par_iter().map(hash).collect::<io::Result<_>>()?
+        } else {
+            files.iter().map(hash).collect::<io::Result<_>>()?
+        };
+        tracker.finish();
+
+        Ok(Self { entries, symlinks })
+    }
+
+    /// Number of files in the manifest
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the manifest contains no files
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Save manifest as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+    }
+
+    /// Load manifest from JSON
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Verify `dir` against this manifest
+    ///
+    /// Every manifest entry yields one check. Files on disk that are not in
+    /// the manifest are reported as [`MismatchKind::Extra`].
+    pub fn verify(&self, dir: &Path) -> io::Result<IntegrityReport> {
+        Ok(self.diff(&Self::capture(dir)?))
+    }
+
+    /// Compare this (expected) manifest against an `actual` manifest
+    pub fn diff(&self, actual: &ChecksumManifest) -> IntegrityReport {
+        let mut report = IntegrityReport::new();
+
+        for (rel, expected) in &self.entries {
+            let Some(found) = actual.entries.get(rel) else {
+                report.record_file_mismatch(rel.as_str(), MismatchKind::Missing);
+                continue;
+            };
+
+            let mut ok = true;
+            if found.size != expected.size {
+                report.record_file_mismatch(rel.as_str(), MismatchKind::Size);
+                ok = false;
+            } else if found.hash != expected.hash {
+                report.record_file_mismatch(rel.as_str(), MismatchKind::Content);
+                ok = false;
+            }
+            if found.mode != expected.mode {
+                report.record_file_mismatch(rel.as_str(), MismatchKind::Permissions);
+                ok = false;
+            }
+            if ok {
+                report.pass();
+            }
+        }
+
+        for rel in actual.entries.keys() {
+            if !self.entries.contains_key(rel) {
+                report.record_file_mismatch(rel.as_str(), MismatchKind::Extra);
+            }
+        }
+
+        for (rel, target) in &self.symlinks {
+            match actual.symlinks.get(rel) {
+                None => report.record_file_mismatch(rel.as_str(), MismatchKind::Missing),
+                Some(found) if found != target => {
+                    report.record_file_mismatch(rel.as_str(), MismatchKind::LinkTarget)
+                }
+                Some(_) => report.pass(),
+            }
+        }
+        for rel in actual.symlinks.keys() {
+            if !self.symlinks.contains_key(rel) {
+                report.record_file_mismatch(rel.as_str(), MismatchKind::Extra);
+            }
+        }
+
+        events::emit("integrity", "manifest_diff", || {
+            serde_json::json!({
+                "checks_total": report.checks_total,
+                "checks_passed": report.checks_passed,
+                "mismatches": report.file_mismatches.len(),
+            })
+        });
+        report
+    }
+
+    /// Async variant of [`capture`](Self::capture)
+    ///
+    /// Hashes at most `max_concurrent` files at a time using `tokio::fs`.
+    #[cfg(feature = "async")]
+    pub async fn capture_async(dir: &Path, max_concurrent: usize) -> io::Result<Self> {
+        use tokio::sync::Semaphore;
+        use tokio::task::JoinSet;
+
+        let root = dir.to_path_buf();
+        let (files, symlinks) = tokio::task::spawn_blocking(move || collect_entries(&root))
+            .await
+            .map_err(io::Error::other)??;
+
+        let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+        let mut tasks = JoinSet::new();
+        for (rel, path) in files {
+            let permit = semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            tasks.spawn(async move {
+                let _permit = permit;
+                checksum_file_async(&path).await.map(|sum| (rel, sum))
+            });
+        }
+
+        let mut entries = BTreeMap::new();
+        while let Some(joined) = tasks.join_next().await {
+            let (rel, sum) = joined.map_err(io::Error::other)??;
+            entries.insert(rel, sum);
+        }
+
+        Ok(Self { entries, symlinks })
+    }
+}
+
+/// Options for manifest capture and directory comparison
+#[derive(Clone, Debug, Default)]
+pub struct CaptureOptions {
+    /// Hash files in parallel with rayon
+    pub parallel: bool,
+    /// Progress reporting (bytes hashed)
+    pub progress: ProgressOptions,
+}
+
+impl CaptureOptions {
+    /// Sequential hashing, no progress reporting
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hash files in parallel
+    pub fn parallel(mut self) -> Self {
+        self.parallel = true;
+        self
+    }
+
+    /// Report progress to `sink`
+    pub fn with_progress(mut self, sink: impl ProgressSink + 'static) -> Self {
+        self.progress = self.progress.with_sink(sink);
+        self
+    }
+
+    /// Replace the progress configuration
+    pub fn with_progress_options(mut self, progress: ProgressOptions) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+/// Compare two directory trees file by file
+///
+/// `expected` is the reference; files only present in `actual` are
+/// reported as [`MismatchKind::Extra`]. Symlinks are compared by target
+/// without being followed, and special files such as FIFOs are ignored.
+pub fn compare_directories(expected: &Path, actual: &Path) -> io::Result<IntegrityReport> {
+    compare_directories_with(expected, actual, &CaptureOptions::default())
+}
+
+/// Compare two directory trees using `options`
+///
+/// Both trees are hashed in turn, so progress restarts from zero for the
+/// `actual` tree.
+pub fn compare_directories_with(
+    expected: &Path,
+    actual: &Path,
+    options: &CaptureOptions,
+) -> io::Result<IntegrityReport> {
+    let expected = ChecksumManifest::capture_with(expected, options)?;
+    let actual = ChecksumManifest::capture_with(actual, options)?;
+    Ok(expected.diff(&actual))
+}
+
+/// Allowed difference between expected and actual file timestamps
+///
+/// Covers coarse filesystem granularity (FAT stores mtime in 2s steps).
+const METADATA_TIME_TOLERANCE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Compare the metadata of `actual` against `expected`
+///
+/// Each attribute set in `expected` is checked separately. Attributes this
+/// platform or build cannot express (mode bits beyond read-only off Unix,
+/// xattrs without the `xattrs` feature on Linux, unreadable timestamps) are
+/// recorded as warnings instead of failures. Use
+/// [`FileMetaSpec::from_path`] to compare against another file.
+pub fn compare_metadata(expected: &FileMetaSpec, actual: &Path) -> io::Result<IntegrityReport> {
+    let mut report = IntegrityReport::new();
+    let metadata = fs::metadata(actual)?;
+
+    if let Some(mode) = expected.mode {
+        let found = metadata_mode(&metadata);
+        if !cfg!(unix) {
+            report.warn(format!(
+                "mode {:#o}: only read-only-ness is checked on this platform",
+                mode
+            ));
+        }
+        let matches = if cfg!(unix) {
+            found == mode & 0o7777
+        } else {
+            (found & 0o222 == 0) == (mode & 0o222 == 0)
+        };
+        if matches {
+            report.pass();
+        } else {
+            report.fail(format!(
+                "mode mismatch: expected {:#o}, got {:#o}",
+                mode, found
+            ));
+        }
+    }
+
+    for (name, expected_time, found) in [
+        ("mtime", expected.mtime, metadata.modified()),
+        ("atime", expected.atime, metadata.accessed()),
+    ] {
+        let Some(expected_time) = expected_time else {
+            continue;
+        };
+        match found {
+            Ok(found) => {
+                let drift = match found.duration_since(expected_time) {
+                    Ok(drift) => drift,
+                    Err(e) => e.duration(),
+                };
+                if drift <= METADATA_TIME_TOLERANCE {
+                    report.pass();
+                } else {
+                    report.fail(format!("{} mismatch: off by {:?}", name, drift));
+                }
+            }
+            Err(e) => report.warn(format!("{} unavailable: {}", name, e)),
+        }
+    }
+
+    if !expected.xattrs.is_empty() {
+        if cfg!(all(feature = "xattrs", target_os = "linux")) {
+            let found = read_xattrs(actual).map_err(io::Error::other)?;
+            for (name, value) in &expected.xattrs {
+                match found.iter().find(|(n, _)| n == name) {
+                    Some((_, v)) if v == value => report.pass(),
+                    Some(_) => report.fail(format!("xattr {} has a different value", name)),
+                    None => report.fail(format!("xattr {} missing", name)),
+                }
+            }
+        } else {
+            report.warn(format!(
+                "{} xattrs not checked: unsupported on this platform or build",
+                expected.xattrs.len()
+            ));
+        }
+    }
+
+    Ok(report)
+}
+
+/// Source of a file generated with [`DatasetOptions::self_describing`](crate::fixtures::DatasetOptions::self_describing)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileIdentity {
+    /// Seed of the dataset the file was generated in
+    pub dataset_seed: u64,
+    /// Index of the file within its dataset (matches `file_NNNN.bin`)
+    pub file_index: u64,
+    /// Pattern the payload was generated from
+    pub pattern: TestDataPattern,
+    /// Payload length declared in the header
+    pub payload_len: u64,
+    /// Whether the payload has the declared length and matches the pattern
+    pub payload_intact: bool,
+}
+
+/// Identify which generated file the content at `path` came from
+///
+/// Parses the self-describing header and checks the payload against the
+/// pattern it declares. Returns `None` if the file cannot be read or has no
+/// valid header, e.g. because extraction scrambled its first bytes.
+///
+/// # Example
+/// ```rust,ignore
+/// let id = identify_extracted_file(&out.join("file_0003.bin")).unwrap();
+/// assert_eq!(id.file_index, 3, "file 3 holds content of file {}", id.file_index);
+/// ```
+pub fn identify_extracted_file(path: &Path) -> Option<FileIdentity> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut header = [0u8; SELF_DESCRIBING_HEADER_LEN];
+    file.read_exact(&mut header).ok()?;
+    let header = DatasetHeader::decode(&header)?;
+
+    let mut payload_intact = true;
+    let mut pos = 0usize;
+    let mut buf = vec![0u8; HASH_CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        payload_intact &= buf[..n]
+            .iter()
+            .enumerate()
+            .all(|(i, &b)| b == pattern_byte(header.pattern, pos + i));
+        pos += n;
+    }
+    payload_intact &= pos as u64 == header.payload_len;
+
+    Some(FileIdentity {
+        dataset_seed: header.dataset_seed,
+        file_index: header.file_index,
+        pattern: header.pattern,
+        payload_len: header.payload_len,
+        payload_intact,
+    })
+}
+
+/// Async variant of [`compare_directories`] with at most `max_concurrent`
+/// files hashed at once per tree
+#[cfg(feature = "async")]
+pub async fn compare_directories_async(
+    expected: &Path,
+    actual: &Path,
+    max_concurrent: usize,
+) -> io::Result<IntegrityReport> {
+    let expected = ChecksumManifest::capture_async(expected, max_concurrent).await?;
+    let actual = ChecksumManifest::capture_async(actual, max_concurrent).await?;
+    Ok(expected.diff(&actual))
+}
+
+/// Compare two files byte-for-byte using 256MB memory-mapped windows
+#[cfg(feature = "mmap")]
+pub fn compare_files_mmap(expected: &Path, actual: &Path) -> io::Result<IntegrityReport> {
+    compare_files_mmap_with(expected, actual, &crate::mmap::MmapConfig::default())
+}
+
+/// Compare two files byte-for-byte using windowed mmap
+///
+/// Only one window per file is resident at a time, so files larger than RAM
+/// can be verified. The report records a size check and a content check;
+/// a content failure names the first differing offset and the number of
+/// differing bytes, plus a [`ZeroRunSummary`] when every differing byte is
+/// zero on one side, as after packet loss. With
+/// [`MmapConfig::with_hexdiff`](crate::mmap::MmapConfig::with_hexdiff), the
+/// first failure of small files also carries a [`hexdiff`] of the first
+/// differing region.
+#[cfg(feature = "mmap")]
+pub fn compare_files_mmap_with(
+    expected: &Path,
+    actual: &Path,
+    config: &crate::mmap::MmapConfig,
+) -> io::Result<IntegrityReport> {
+    use crate::mmap::{load_window, windows};
+    use zero_runs::ZeroedRegions;
+
+    let mut report = IntegrityReport::new();
+    let mut file_a = fs::File::open(expected)?;
+    let mut file_b = fs::File::open(actual)?;
+    let len_a = file_a.metadata()?.len();
+    let len_b = file_b.metadata()?.len();
+
+    // Small files are cheap to read whole for a hexdiff of the first region
+    let mut hex = match config.hexdiff_max_size {
+        Some(max) if len_a.max(len_b) <= max => {
+            let diff = hexdiff::hexdiff(
+                &fs::read(expected)?,
+                &fs::read(actual)?,
+                hexdiff::EMBEDDED_CONTEXT,
+                1,
+            );
+            (!diff.is_empty()).then(|| format!("\n{}", diff.trim_end()))
+        }
+        _ => None,
+    };
+
+    if len_a != len_b {
+        report.record_corruption();
+        report.fail(format!(
+            "size mismatch: {} is {} bytes, {} is {} bytes{}",
+            expected.display(),
+            len_a,
+            actual.display(),
+            len_b,
+            hex.take().unwrap_or_default()
+        ));
+    } else {
+        report.pass();
+    }
+
+    let common = len_a.min(len_b);
+    let mut first_diff = None;
+    let mut diff_bytes = 0u64;
+    let mut zeroed_expected = ZeroedRegions::new();
+    let mut zeroed_actual = ZeroedRegions::new();
+
+    for (offset, len) in windows(common, config.window_size) {
+        let a = load_window(&mut file_a, offset, len, config)?;
+        let b = load_window(&mut file_b, offset, len, config)?;
+        zeroed_expected.feed(offset, &a, &b);
+        zeroed_actual.feed(offset, &b, &a);
+        if *a != *b {
+            for (i, (x, y)) in a.iter().zip(b.iter()).enumerate() {
+                if x != y {
+                    first_diff.get_or_insert(offset + i as u64);
+                    diff_bytes += 1;
+                }
+            }
+        }
+        config.report(offset + len as u64, common);
+    }
+
+    match first_diff {
+        Some(offset) => {
+            let mut message = format!(
+                "content mismatch: {} bytes differ, first at offset {}",
+                diff_bytes, offset
+            );
+            for (side, zeroed) in [("actual", zeroed_actual), ("expected", zeroed_expected)] {
+                if let Some(runs) = zeroed.finish(common) {
+                    message.push_str(&format!(
+                        "; differing bytes are all zero in {}: {}",
+                        side,
+                        summarize_runs(&runs, common)
+                    ));
+                }
+            }
+            message.push_str(&hex.take().unwrap_or_default());
+            report.record_corruption();
+            report.fail(message);
+        }
+        None => report.pass(),
+    }
+
+    Ok(report)
+}
+
+/// Recursively list regular files under `root` as (relative path, absolute path)
+pub(crate) fn collect_files(root: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    Ok(collect_entries(root)?.0)
+}
+
+/// Regular files and symlink targets under `root`, sorted by relative path
+///
+/// Uses `lstat` semantics throughout: symlinks are not followed and special
+/// files (FIFOs, sockets, devices) are skipped, so a stray FIFO can never
+/// block a walk.
+#[allow(clippy::type_complexity)]
+fn collect_entries(root: &Path) -> io::Result<(Vec<(String, PathBuf)>, BTreeMap<String, String>)> {
+    let mut files = Vec::new();
+    let mut symlinks = BTreeMap::new();
+    let mut stack = vec![root.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                stack.push(path);
+            } else if file_type.is_file() {
+                files.push((relative_key(root, &path), path));
+            } else if file_type.is_symlink() {
+                let target = fs::read_link(&path)?;
+                symlinks.insert(
+                    relative_key(root, &path),
+                    target.to_string_lossy().into_owned(),
+                );
+            }
+        }
+    }
+
+    files.sort();
+    Ok((files, symlinks))
+}
+
+/// Relative path with `/` separators, stable across platforms
+fn relative_key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Stream a file through BLAKE3 in fixed-size chunks
+fn checksum_file(path: &Path, progress: Option<&ProgressTracker<'_>>) -> io::Result<FileChecksum> {
+    let mut file = fs::File::open(path)?;
+    let metadata = file.metadata()?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; HASH_CHUNK_SIZE];
+
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        if let Some(progress) = progress {
+            progress.advance(n as u64);
+        }
+    }
+
+    Ok(FileChecksum {
+        hash: hasher.finalize().to_hex().to_string(),
+        size: metadata.len(),
+        mode: metadata_mode(&metadata),
+    })
+}
+
+/// Async variant of [`checksum_file`] using `tokio::fs`
+#[cfg(feature = "async")]
+async fn checksum_file_async(path: &Path) -> io::Result<FileChecksum> {
+    use tokio::io::AsyncReadExt;
+
+    let mut file = tokio::fs::File::open(path).await?;
+    let metadata = file.metadata().await?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; HASH_CHUNK_SIZE];
+
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(FileChecksum {
+        hash: hasher.finalize().to_hex().to_string(),
+        size: metadata.len(),
+        mode: metadata_mode(&metadata),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integrity_report() {
+        let mut report = IntegrityReport::new();
+        assert!(report.is_ok());
+
+        report.pass();
+        assert_eq!(report.checks_total, 1);
+        assert_eq!(report.checks_passed, 1);
+
+        report.fail("test failure");
+        assert_eq!(report.checks_total, 2);
+        assert_eq!(report.checks_passed, 1);
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn test_integrity_report_merge() {
+        let mut a = IntegrityReport::new();
+        a.pass();
+        let mut b = IntegrityReport::new();
+        b.pass();
+        b.record_invariant_violation("broken");
+
+        a.merge(&b);
+        assert_eq!(a.checks_total, 2);
+        assert_eq!(a.checks_passed, 2);
+        assert_eq!(a.invariant_violations, 1);
+        assert!(!a.is_ok());
+    }
+
+    #[test]
+    fn test_check_records_follow_counters() {
+        let validator = IntegrityValidator::new();
+        let corrupt = SparseVec {
+            pos: vec![1, 3, 5],
+            neg: vec![3],
+        };
+        let mut report = validator.validate_sparse(&corrupt);
+        report.merge(&validator.detect_differences(&corrupt, &corrupt));
+        report.merge(&validator.validate_bundle_many(&[
+            deterministic_sparse_vec(crate::DIM, 100, 1),
+            deterministic_sparse_vec(crate::DIM, 100, 2),
+        ]));
+        report.pass();
+
+        let records = report.records();
+        assert_eq!(records.len() as u64, report.checks_total);
+        let passed = records
+            .iter()
+            .filter(|r| r.outcome == CheckOutcome::Passed)
+            .count();
+        assert_eq!(passed as u64, report.checks_passed);
+        assert_eq!(records[0].validator, "validate_sparse");
+        assert_eq!(records[0].check, "no_overlap");
+        assert_eq!(records.last().unwrap().validator, UNNAMED_VALIDATOR);
+
+        let failures = report.failures_for("validate_sparse");
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].detail.as_deref(),
+            Some("Overlap between pos and neg indices")
+        );
+        assert!(report.failures_for("detect_differences").is_empty());
+
+        let slowest = report.slowest_checks(2);
+        assert_eq!(slowest.len(), 2);
+        assert!(slowest[0].duration_ns >= slowest[1].duration_ns);
+        assert!(records
+            .iter()
+            .all(|r| r.duration_ns <= slowest[0].duration_ns));
+    }
+
+    #[test]
+    fn test_validate_sparse() {
+        let validator = IntegrityValidator::new();
+
+        // Create a valid sparse vector
+        let sparse = SparseVec {
+            pos: vec![0, 10, 20],
+            neg: vec![5, 15, 25],
+        };
+
+        let report = validator.validate_sparse(&sparse);
+        assert!(report.is_ok());
+
+        // Out-of-range indices only fail once a dimension bound is set
+        let bounded = IntegrityValidator::new().with_dims(25);
+        let report = bounded.validate_sparse(&sparse);
+        assert!(!report.is_ok());
+        assert_eq!(report.corruption_events, 1);
+        assert!(report.failures[0].contains("[25]"), "{:?}", report.failures);
+        assert!(IntegrityValidator::new()
+            .with_dims(26)
+            .validate_sparse(&sparse)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_bind_invariants() {
+        let validator = IntegrityValidator::new();
+
+        let sparse_a = SparseVec {
+            pos: vec![0, 10, 20],
+            neg: vec![5, 15, 25],
+        };
+        let sparse_b = SparseVec {
+            pos: vec![1, 11, 21],
+            neg: vec![6, 16, 26],
+        };
+
+        let report = validator.validate_bind_invariants(&sparse_a, &sparse_b);
+        // Should pass commutativity
+        assert!(report.checks_passed > 0);
+    }
+
+    #[test]
+    fn test_detect_differences_reports_overlap() {
+        let validator = IntegrityValidator::new();
+        let expected = SparseVec {
+            pos: vec![0, 2],
+            neg: vec![5],
+        };
+        let actual = SparseVec {
+            pos: vec![0],
+            neg: vec![2, 5],
+        };
+
+        let report = validator.detect_differences(&expected, &actual);
+        assert_eq!(report.corruption_events, 2);
+        assert!(
+            report.failures[0].contains("hamming=1"),
+            "{:?}",
+            report.failures
+        );
+        assert!(report.failures[0].contains("pn=1"));
+    }
+
+    #[test]
+    fn test_detect_differences_equal_length_different_content() {
+        let validator = IntegrityValidator::new().with_max_reported_indices(2);
+        let expected = SparseVec {
+            pos: vec![1, 3, 5, 7],
+            neg: vec![10],
+        };
+        let actual = SparseVec {
+            pos: vec![1, 4, 6, 8],
+            neg: vec![10],
+        };
+
+        let report = validator.detect_differences(&expected, &actual);
+        assert_eq!(report.corruption_events, 1);
+        assert_eq!(report.checks_passed, 1);
+        assert!(
+            report.failures[0].starts_with("pos indices differ at 6 positions: [3, 4, ... 4 more]"),
+            "{:?}",
+            report.failures
+        );
+        assert_eq!(
+            symmetric_difference_sorted(&expected.pos, &actual.pos),
+            vec![3, 4, 5, 6, 7, 8]
+        );
+    }
+
+    fn panic_message(f: impl FnOnce() + std::panic::UnwindSafe) -> String {
+        let payload = std::panic::catch_unwind(f).expect_err("assertion should fail");
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .expect("formatted panic message")
+    }
+
+    fn to_json(v: &SparseVec) -> Vec<u8> {
+        serde_json::to_vec(&(&v.pos, &v.neg)).unwrap()
+    }
+
+    fn from_json(bytes: &[u8]) -> SparseVec {
+        let (pos, neg) = serde_json::from_slice(bytes).unwrap();
+        SparseVec { pos, neg }
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        let validator = IntegrityValidator::new();
+        let v = deterministic_sparse_vec(crate::DIM, 200, 5);
+        let report = validator.validate_serialization_roundtrip(&v, to_json, from_json);
+        assert!(report.is_ok(), "{:?}", report.failures);
+        assert_eq!(
+            report.records()[0].validator,
+            "validate_serialization_roundtrip"
+        );
+
+        let report = validator.fuzz_serialization_roundtrip(1000, 20, 9, to_json, from_json);
+        assert!(report.is_ok(), "{:?}", report.failures);
+        assert_eq!(report.checks_total, 22);
+
+        // Drops the upper half of the positive indices
+        let lossy = |v: &SparseVec| {
+            to_json(&SparseVec {
+                pos: v.pos[..v.pos.len() / 2].to_vec(),
+                neg: v.neg.clone(),
+            })
+        };
+        let report = validator.validate_serialization_roundtrip(&v, lossy, from_json);
+        assert!(!report.is_ok());
+        assert_eq!(report.corruption_events, 1);
+        assert!(report.failures[0].contains("is lossy"));
+        assert!(report.failures[0].contains("differing indices: 50"));
+
+        let report = validator.fuzz_serialization_roundtrip(1000, 20, 9, lossy, from_json);
+        let records = report.records();
+        assert_eq!(records.len(), 22);
+        assert_eq!(records[0].outcome, CheckOutcome::Passed); // empty
+        assert_eq!(records[1].outcome, CheckOutcome::Failed); // dense
+        assert!(records[1]
+            .detail
+            .as_ref()
+            .unwrap()
+            .contains("nnz: left=1000 right=750"));
+    }
+
+    #[test]
+    fn test_sparse_assertions_report_diff() {
+        let left = SparseVec {
+            pos: (0..20).map(|i| i * 2).collect(),
+            neg: vec![1, 3, 5],
+        };
+        let mut right = left.clone();
+        *right.pos.last_mut().unwrap() = 40;
+        right.neg.pop();
+
+        assert_sparse_eq(&left, &left.clone());
+        assert_sparse_similar(&left, &right, 0.9);
+
+        let message = panic_message(|| assert_sparse_eq(&left, &right));
+        let vectors = format!(
+            "vectors: left={} right={}",
+            fingerprint(&left),
+            fingerprint(&right)
+        );
+        for field in [
+            vectors.as_str(),
+            "nnz: left=23 right=22",
+            "differing indices: 3",
+            "pos differing: [38, 40]",
+            "neg differing: [5]",
+            "cosine: 0.9336",
+        ] {
+            assert!(
+                message.contains(field),
+                "missing {:?} in {}",
+                field,
+                message
+            );
+        }
+
+        let message = panic_message(|| assert_sparse_similar(&left, &right, 0.95));
+        assert!(message.contains("cosine 0.9336 < 0.9500"), "{}", message);
+        assert!(message.contains("differing indices: 3"));
+    }
+
+    #[test]
+    fn test_verbose_emits_diagnostics() {
+        let diagnostics = CollectingDiagnostics::new();
+        let validator = IntegrityValidator::new()
+            .verbose()
+            .with_diagnostic_sink(diagnostics.clone());
+
+        let corrupt = SparseVec {
+            pos: vec![1, 3, 5],
+            neg: vec![3],
+        };
+        let report = validator.validate_sparse(&corrupt);
+        assert!(!report.is_ok());
+
+        let lines = diagnostics.lines();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "[FAIL] validate_sparse.no_overlap: nnz=+3/-1 overlap=1"
+        );
+        assert!(lines[1].starts_with("[PASS] validate_sparse.pos_sorted"));
+
+        // Not verbose: nothing is emitted
+        let quiet = IntegrityValidator::new().with_diagnostic_sink(diagnostics.clone());
+        quiet.validate_sparse(&corrupt);
+        assert_eq!(diagnostics.lines().len(), 3);
+    }
+
+    #[test]
+    fn test_permutation_invariants() {
+        use crate::generators::random_permutation;
+
+        let validator = IntegrityValidator::new();
+        let v = deterministic_sparse_vec(crate::DIM, 200, 21);
+
+        let identity: Vec<usize> = (0..crate::DIM).collect();
+        let report = validator.validate_permutation_invariants(&v, &identity);
+        assert!(report.is_ok(), "{}", report.summary());
+
+        let perm = random_permutation(crate::DIM, 99);
+        let report = validator.validate_permutation_invariants(&v, &perm);
+        assert!(report.is_ok(), "{}", report.summary());
+        assert_eq!(report.checks_passed, 4);
+
+        let mut broken = perm.clone();
+        broken[0] = broken[1];
+        let report = validator.validate_permutation_invariants(&v, &broken);
+        assert_eq!(report.invariant_violations, 1);
+    }
+
+    #[test]
+    fn test_validate_hierarchy() {
+        use crate::generators::hierarchy;
+
+        let validator = IntegrityValidator::new();
+        let mut rng = StdRng::seed_from_u64(12);
+        let mut tree = hierarchy::build(&mut rng, &[3, 3, 3], 100);
+        let report = validator.validate_hierarchy(&tree, 0.2);
+        assert!(report.is_ok(), "{}", report.failures.join("\n"));
+        // One check per internal node: root, 3 and 9
+        assert_eq!(report.checks_total, 13);
+
+        let deep = hierarchy::build(&mut rng, &[2, 1, 2, 1, 2], 100);
+        assert!(validator.validate_hierarchy(&deep, 0.2).is_ok());
+
+        // Move a leaf under the wrong parent
+        assert!(tree.swap_vectors(&[0, 0, 0], &[1, 2, 0]));
+        let report = validator.validate_hierarchy(&tree, 0.2);
+        assert!(!report.is_ok());
+        assert_eq!(report.invariant_violations, 2);
+        assert!(
+            report.failures[0].contains("[0, 0]"),
+            "{:?}",
+            report.failures
+        );
+    }
+
+    #[test]
+    fn test_validate_bundle_many() {
+        let validator = IntegrityValidator::new();
+        let base = deterministic_sparse_vec(crate::DIM, 400, 8);
+
+        // Each member keeps a different ~90% of the base support, so all
+        // bundling strategies should converge on the base vector
+        let member = |k: usize| SparseVec {
+            pos: base
+                .pos
+                .iter()
+                .copied()
+                .filter(|i| !(i + k).is_multiple_of(10))
+                .collect(),
+            neg: base
+                .neg
+                .iter()
+                .copied()
+                .filter(|i| !(i + k).is_multiple_of(10))
+                .collect(),
+        };
+
+        for count in [2, 3, 8, 33] {
+            let mut vecs: Vec<SparseVec> = (0..count).map(member).collect();
+            // Deliberate tie: one vector votes + and another - on a fresh index
+            let free = (0..crate::DIM)
+                .find(|i| !base.pos.contains(i) && !base.neg.contains(i))
+                .unwrap();
+            vecs[0].pos.push(free);
+            vecs[0].pos.sort_unstable();
+            vecs[1].neg.push(free);
+            vecs[1].neg.sort_unstable();
+
+            let reference = reference_bundle_many(&vecs);
+            assert!(!reference.pos.contains(&free) && !reference.neg.contains(&free));
+
+            let report = validator.validate_bundle_many(&vecs);
+            assert!(report.is_ok(), "{} vectors: {}", count, report.summary());
+        }
+    }
+
+    #[test]
+    fn test_identify_extracted_file_reports_swap() {
+        use crate::fixtures::{try_create_test_dataset_with, DatasetOptions};
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join("data");
+        let options = DatasetOptions::new().with_seed(42).self_describing(true);
+        try_create_test_dataset_with(&dir, 1, TestDataPattern::Seeded(9), &options).unwrap();
+
+        let (a, b) = (dir.join("file_0000.bin"), dir.join("file_0001.bin"));
+        let id = identify_extracted_file(&a).unwrap();
+        assert_eq!(id.dataset_seed, 42);
+        assert_eq!(id.file_index, 0);
+        assert_eq!(id.pattern, TestDataPattern::Seeded(9));
+        assert_eq!(id.payload_len, 1024 - SELF_DESCRIBING_HEADER_LEN as u64);
+        assert!(id.payload_intact);
+
+        // Simulate extraction putting each file's bytes under the other's name
+        let (content_a, content_b) = (fs::read(&a).unwrap(), fs::read(&b).unwrap());
+        fs::write(&a, &content_b).unwrap();
+        fs::write(&b, &content_a).unwrap();
+        assert_eq!(identify_extracted_file(&a).unwrap().file_index, 1);
+        assert_eq!(identify_extracted_file(&b).unwrap().file_index, 0);
+
+        // Truncated payload is flagged; a clobbered header is unidentifiable
+        fs::write(&a, &content_a[..500]).unwrap();
+        assert!(!identify_extracted_file(&a).unwrap().payload_intact);
+        let mut clobbered = content_b.clone();
+        clobbered[20] ^= 0xFF;
+        fs::write(&b, &clobbered).unwrap();
+        assert!(identify_extracted_file(&b).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_compare_metadata_catches_mismatch() {
+        use std::time::{Duration, SystemTime};
+
+        let harness = crate::TestHarness::new();
+        let past = SystemTime::now() - Duration::from_secs(30 * 24 * 3600);
+        let spec = FileMetaSpec::new().with_mode(0o640).with_mtime(past);
+        let path = harness.create_file_with_metadata("meta.txt", b"metadata", &spec);
+
+        let report = compare_metadata(&spec, &path).unwrap();
+        assert!(report.is_ok(), "{:?}", report.failures);
+        assert_eq!(report.checks_passed, 2);
+        assert_eq!(FileMetaSpec::from_path(&path).unwrap().mode, Some(0o640));
+
+        let wrong = FileMetaSpec::new()
+            .with_mode(0o600)
+            .with_mtime(past + Duration::from_secs(3600));
+        let report = compare_metadata(&wrong, &path).unwrap();
+        assert_eq!(report.failures.len(), 2);
+        assert!(report.failures[0].contains("expected 0o600, got 0o640"));
+        assert!(report.failures[1].starts_with("mtime mismatch"));
+
+        // Without the xattrs feature, xattr expectations become warnings
+        if !cfg!(feature = "xattrs") {
+            let report =
+                compare_metadata(&FileMetaSpec::new().with_xattr("user.k", "v"), &path).unwrap();
+            assert!(report.is_ok());
+            assert_eq!(report.warnings.len(), 1);
+        }
+    }
+
+    fn copy_tree(src: &Path, dst: &Path) {
+        fs::create_dir_all(dst).unwrap();
+        for entry in fs::read_dir(src).unwrap() {
+            let entry = entry.unwrap();
+            let target = dst.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_tree(&entry.path(), &target);
+            } else {
+                fs::copy(entry.path(), &target).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_checksum_manifest_roundtrip() {
+        let harness = crate::TestHarness::new();
+        let original = harness.create_directory_structure("original");
+        let manifest_path = harness.temp_dir().join("manifest.json");
+
+        ChecksumManifest::capture(&original)
+            .unwrap()
+            .save(&manifest_path)
+            .unwrap();
+
+        // "Extract" a copy, then drop the originals
+        let extracted = harness.temp_dir().join("extracted");
+        copy_tree(&original, &extracted);
+        fs::remove_dir_all(&original).unwrap();
+
+        let manifest = ChecksumManifest::load(&manifest_path).unwrap();
+        assert_eq!(manifest.len(), 5);
+        let report = manifest.verify(&extracted).unwrap();
+        assert!(report.is_ok(), "{}", report.summary());
+        assert_eq!(report.checks_passed, 5);
+
+        // Flip a single byte in a nested file
+        let target = extracted.join("dir2/nested/file5.json");
+        let mut data = fs::read(&target).unwrap();
+        data[0] ^= 0x01;
+        fs::write(&target, data).unwrap();
+
+        let report = manifest.verify(&extracted).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.file_mismatches,
+            vec![FileMismatch {
+                path: "dir2/nested/file5.json".to_string(),
+                kind: MismatchKind::Content,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_compare_directories() {
+        let harness = crate::TestHarness::new();
+        let a = harness.create_directory_structure("a");
+        let b = harness.create_directory_structure("b");
+        assert!(compare_directories(&a, &b).unwrap().is_ok());
+
+        fs::write(b.join("file1.txt"), b"Hello, World!").unwrap();
+        let report = compare_directories(&a, &b).unwrap();
+        assert_eq!(report.file_mismatches.len(), 1);
+        assert_eq!(report.file_mismatches[0].kind, MismatchKind::Content);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_compare_directories_symlinks_and_fifos() {
+        let harness = crate::TestHarness::new();
+        let inventory = harness.create_edge_case_structure("edge");
+        let root = &inventory.root;
+
+        // Completes even with a FIFO in the tree
+        let manifest = ChecksumManifest::capture(root).unwrap();
+        assert!(compare_directories(root, root).unwrap().is_ok());
+        assert_eq!(manifest.symlinks["nested/parent_link"], "../target.txt");
+        assert!(manifest.symlinks.contains_key("dangling_link"));
+        assert!(!manifest.entries.contains_key("fifo"));
+
+        fs::remove_file(root.join("link_to_file")).unwrap();
+        std::os::unix::fs::symlink("one_byte.bin", root.join("link_to_file")).unwrap();
+        fs::remove_file(root.join("dangling_link")).unwrap();
+        let report = manifest.verify(root).unwrap();
+        let mut found: Vec<_> = report
+            .file_mismatches
+            .iter()
+            .map(|m| (m.path.as_str(), m.kind))
+            .collect();
+        found.sort_by_key(|(path, _)| *path);
+        assert_eq!(
+            found,
+            vec![
+                ("dangling_link", MismatchKind::Missing),
+                ("link_to_file", MismatchKind::LinkTarget),
+            ]
+        );
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_compare_files_mmap_window_boundaries() {
+        use crate::fixtures::{write_file_of_size, TestDataPattern};
+        use crate::mmap::MmapConfig;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let harness = crate::TestHarness::new();
+        let a = harness.temp_dir().join("a.bin");
+        let b = harness.temp_dir().join("b.bin");
+        let size = 3 * 1024 * 1024 + 123;
+        write_file_of_size(&a, size, TestDataPattern::Random).unwrap();
+        write_file_of_size(&b, size, TestDataPattern::Random).unwrap();
+
+        let last_done = Arc::new(AtomicU64::new(0));
+        let seen = last_done.clone();
+        let config = MmapConfig::new()
+            .with_window_size(64 * 1024)
+            .with_progress(move |done, _total| seen.store(done, Ordering::SeqCst));
+        assert!(compare_files_mmap_with(&a, &b, &config).unwrap().is_ok());
+        assert_eq!(last_done.load(Ordering::SeqCst), size as u64);
+
+        // Flip the last byte of one window and the first byte of the next
+        let mut data = fs::read(&b).unwrap();
+        data[64 * 1024 - 1] ^= 0xFF;
+        data[64 * 1024] ^= 0xFF;
+        fs::write(&b, data).unwrap();
+
+        for config in [
+            MmapConfig::new().with_window_size(64 * 1024),
+            MmapConfig::new().with_window_size(64 * 1024).buffered(),
+        ] {
+            let report = compare_files_mmap_with(&a, &b, &config).unwrap();
+            assert!(!report.is_ok());
+            assert!(report.failures[0].contains("2 bytes differ, first at offset 65535"));
+            assert!(!report.failures[0].contains("all zero"));
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_compare_files_mmap_reports_zeroed_packets() {
+        use crate::mmap::MmapConfig;
+
+        let harness = crate::TestHarness::new();
+        let a = harness.temp_dir().join("a.bin");
+        let b = harness.temp_dir().join("b.bin");
+        let data: Vec<u8> = (0..300_000).map(|i| (i % 251) as u8 + 1).collect();
+        let mut damaged = data.clone();
+        crate::ChaosInjector::new(3).simulate_packet_loss(&mut damaged, 0.05, 4096);
+        fs::write(&a, &data).unwrap();
+        fs::write(&b, &damaged).unwrap();
+
+        let config = MmapConfig::new().with_window_size(10_000);
+        let report = compare_files_mmap_with(&a, &b, &config).unwrap();
+        let runs = find_zero_runs(&damaged, 1);
+        let expected = summarize_runs(&runs, damaged.len() as u64);
+        assert!(expected.is_aligned_to(4096));
+        assert!(
+            report.failures[0].contains(&format!("all zero in actual: {}", expected)),
+            "{}",
+            report.failures[0]
+        );
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_compare_files_mmap_embeds_hexdiff_for_small_files() {
+        use crate::mmap::MmapConfig;
+
+        let harness = crate::TestHarness::new();
+        let a = harness.temp_dir().join("a.bin");
+        let b = harness.temp_dir().join("b.bin");
+        let data: Vec<u8> = (0..8192).map(|i| (i % 251) as u8).collect();
+        let mut damaged = data.clone();
+        damaged[4096] = 0xee;
+        damaged[6000] = 0xee;
+        fs::write(&a, &data).unwrap();
+        fs::write(&b, &damaged).unwrap();
+
+        let plain = compare_files_mmap(&a, &b).unwrap();
+        assert!(!plain.failures[0].contains("@@"));
+
+        let config = MmapConfig::new().with_hexdiff(8192);
+        let report = compare_files_mmap_with(&a, &b, &config).unwrap();
+        let failure = &report.failures[0];
+        assert!(failure.contains("first at offset 4096"), "{}", failure);
+        assert!(failure.contains("@@ offset 0xff0 (4080)"), "{}", failure);
+        assert!(failure.contains("+00001000  ee"), "{}", failure);
+        assert!(!failure.contains("@@ offset 0x1760"));
+        assert!(failure.contains("1 more region(s) not shown"));
+
+        let too_big = MmapConfig::new().with_hexdiff(8191);
+        let report = compare_files_mmap_with(&a, &b, &too_big).unwrap();
+        assert!(!report.failures[0].contains("@@"));
+    }
+
+    #[test]
+    fn test_capture_progress_is_monotonic() {
+        use crate::progress::ProgressOptions;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        let harness = crate::TestHarness::new();
+        let dir = harness.create_dataset(2);
+        let total: u64 = collect_files(&dir)
+            .unwrap()
+            .iter()
+            .map(|(_, p)| fs::metadata(p).unwrap().len())
+            .sum();
+
+        for parallel in [false, true] {
+            let reports = Arc::new(Mutex::new(Vec::new()));
+            let sink_reports = reports.clone();
+            let mut options = CaptureOptions::new().with_progress_options(
+                ProgressOptions::new()
+                    .with_sink(move |done, total: Option<u64>, phase: &str| {
+                        assert_eq!(phase, "checksum");
+                        sink_reports.lock().unwrap().push((done, total));
+                    })
+                    .with_min_interval(Duration::ZERO),
+            );
+            options.parallel = parallel;
+
+            ChecksumManifest::capture_with(&dir, &options).unwrap();
+
+            let reports = reports.lock().unwrap();
+            assert!(!reports.is_empty());
+            assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+            assert_eq!(*reports.last().unwrap(), (total, Some(total)));
+        }
+    }
+
+    #[test]
+    fn test_checksum_manifest_missing_and_extra() {
+        let harness = crate::TestHarness::new();
+        let dir = harness.create_directory_structure("tree");
+        let manifest = ChecksumManifest::capture_parallel(&dir).unwrap();
+        assert_eq!(manifest, ChecksumManifest::capture(&dir).unwrap());
+
+        fs::remove_file(dir.join("file1.txt")).unwrap();
+        fs::write(dir.join("dir1/new.bin"), b"extra").unwrap();
+        fs::write(dir.join("file2.log"), b"short").unwrap();
+
+        let report = manifest.verify(&dir).unwrap();
+        let kinds: Vec<_> = report
+            .file_mismatches
+            .iter()
+            .map(|m| (m.path.as_str(), m.kind))
+            .collect();
+        assert!(kinds.contains(&("file1.txt", MismatchKind::Missing)));
+        assert!(kinds.contains(&("dir1/new.bin", MismatchKind::Extra)));
+        assert!(kinds.contains(&("file2.log", MismatchKind::Size)));
+        assert_eq!(kinds.len(), 3);
+    }
+}
@@ -0,0 +1,208 @@
+//! Round trips between sparse index lists and packed representations
+//!
+//! The packed type stays generic: callers pass `pack`/`unpack` closures, so
+//! the testkit can check the VSA crate's packed ternary blocks without
+//! naming them in its API.
+
+use super::{index_overlap, sparse_diff_summary, IntegrityReport, IntegrityValidator};
+use crate::generators::{fingerprint, random_sparse_vec, special_vectors};
+use embeddenator_vsa::SparseVec;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::Instant;
+
+/// Block widths whose boundaries [`IntegrityValidator::sweep_pack_roundtrip`] probes
+const PACK_BLOCK_WIDTHS: [usize; 2] = [64, 128];
+
+impl IntegrityValidator {
+    /// Check that `unpack(pack(v))` reproduces `v` exactly
+    ///
+    /// Failures carry the same index-level diff summary as
+    /// [`assert_sparse_eq`](super::assert_sparse_eq).
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let report = validator.validate_pack_roundtrip(
+    ///     &v,
+    ///     |v| PackedTritVec::from_sparse(v, DIM),
+    ///     |p| p.to_sparse(),
+    /// );
+    /// ```
+    pub fn validate_pack_roundtrip<P>(
+        &self,
+        v: &SparseVec,
+        pack: impl Fn(&SparseVec) -> P,
+        unpack: impl Fn(&P) -> SparseVec,
+    ) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+        self.check_pack(&mut report, "vector", v, &pack, &unpack);
+        self.finish("validate_pack", report)
+    }
+
+    /// Pack round trip across sparsity levels and block-boundary clusters
+    ///
+    /// Covers the empty vector, seeded random vectors from 1 non-zero up to
+    /// half of `dims`, a maximally dense vector, and
+    /// [`block_boundaries`](special_vectors::block_boundaries) for 64- and
+    /// 128-wide blocks. One check is recorded per vector; failures name it.
+    pub fn sweep_pack_roundtrip<P>(
+        &self,
+        dims: usize,
+        seed: u64,
+        pack: impl Fn(&SparseVec) -> P,
+        unpack: impl Fn(&P) -> SparseVec,
+    ) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+        self.check_pack(
+            &mut report,
+            "empty",
+            &special_vectors::empty(),
+            &pack,
+            &unpack,
+        );
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut nnz = 1;
+        while nnz <= dims / 2 {
+            let v = random_sparse_vec(&mut rng, dims, nnz);
+            let label = format!("random nnz={}", nnz);
+            self.check_pack(&mut report, &label, &v, &pack, &unpack);
+            nnz *= 4;
+        }
+
+        let dense = special_vectors::max_density(dims);
+        self.check_pack(&mut report, "max_density", &dense, &pack, &unpack);
+        for block in PACK_BLOCK_WIDTHS {
+            let v = special_vectors::block_boundaries(dims, block);
+            let label = format!("block{} boundaries", block);
+            self.check_pack(&mut report, &label, &v, &pack, &unpack);
+        }
+
+        self.finish("validate_pack", report)
+    }
+
+    fn check_pack<P>(
+        &self,
+        report: &mut IntegrityReport,
+        label: &str,
+        v: &SparseVec,
+        pack: &dyn Fn(&SparseVec) -> P,
+        unpack: &dyn Fn(&P) -> SparseVec,
+    ) {
+        let started = Instant::now();
+        let restored = unpack(&pack(v));
+        let exact = restored.pos == v.pos && restored.neg == v.neg;
+        let stats = index_overlap(v, &restored);
+        if !exact {
+            report.record_corruption();
+        }
+        self.check(
+            report,
+            "validate_pack.roundtrip",
+            started,
+            (!exact).then(|| {
+                format!(
+                    "{}: packed round trip is lossy\n{}",
+                    label,
+                    sparse_diff_summary(v, &restored, &stats)
+                )
+            }),
+            || format!("{}: {} hamming={}", label, fingerprint(v), stats.hamming()),
+        );
+    }
+}
+
+/// [`IntegrityValidator::validate_pack_roundtrip`] with a default validator
+pub fn validate_pack_roundtrip<P>(
+    v: &SparseVec,
+    pack: impl Fn(&SparseVec) -> P,
+    unpack: impl Fn(&P) -> SparseVec,
+) -> IntegrityReport {
+    IntegrityValidator::new().validate_pack_roundtrip(v, pack, unpack)
+}
+
+/// [`IntegrityValidator::sweep_pack_roundtrip`] with a default validator
+pub fn sweep_pack_roundtrip<P>(
+    dims: usize,
+    seed: u64,
+    pack: impl Fn(&SparseVec) -> P,
+    unpack: impl Fn(&P) -> SparseVec,
+) -> IntegrityReport {
+    IntegrityValidator::new().sweep_pack_roundtrip(dims, seed, pack, unpack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIMS: usize = 1000;
+
+    /// Two bitmaps of 64-lane words: positive and negative
+    type Packed = (Vec<u64>, Vec<u64>);
+
+    fn pack(v: &SparseVec) -> Packed {
+        let words = DIMS.div_ceil(64);
+        let (mut pos, mut neg) = (vec![0u64; words], vec![0u64; words]);
+        for &i in &v.pos {
+            pos[i / 64] |= 1 << (i % 64);
+        }
+        for &i in &v.neg {
+            neg[i / 64] |= 1 << (i % 64);
+        }
+        (pos, neg)
+    }
+
+    fn unpack_lanes(packed: &Packed, lanes: usize) -> SparseVec {
+        let indices = |words: &[u64]| -> Vec<usize> {
+            (0..words.len())
+                .flat_map(|w| (0..lanes).map(move |lane| (w, lane)))
+                .filter(|&(w, lane)| (words[w] >> lane) & 1 == 1)
+                .map(|(w, lane)| w * 64 + lane)
+                .collect()
+        };
+        SparseVec {
+            pos: indices(&packed.0),
+            neg: indices(&packed.1),
+        }
+    }
+
+    fn unpack(packed: &Packed) -> SparseVec {
+        unpack_lanes(packed, 64)
+    }
+
+    // Off by one: never reads the last lane of a word
+    fn unpack_off_by_one(packed: &Packed) -> SparseVec {
+        unpack_lanes(packed, 63)
+    }
+
+    #[test]
+    fn test_correct_pack_pair_passes_sweep() {
+        let report = sweep_pack_roundtrip(DIMS, 3, pack, unpack);
+        assert!(report.is_ok(), "{:?}", report.failures);
+        // empty, nnz 1/4/16/64/256, max density, two boundary clusters
+        assert_eq!(report.checks_total, 9);
+    }
+
+    #[test]
+    fn test_off_by_one_unpack_is_caught_at_block_boundaries() {
+        let interior = special_vectors::single(10, special_vectors::Sign::Positive);
+        assert!(validate_pack_roundtrip(&interior, pack, unpack_off_by_one).is_ok());
+
+        let edge = special_vectors::single(63, special_vectors::Sign::Negative);
+        let report = validate_pack_roundtrip(&edge, pack, unpack_off_by_one);
+        assert!(!report.is_ok());
+        assert!(report.failures[0].contains("neg differing: [63]"));
+
+        let report = sweep_pack_roundtrip(DIMS, 3, pack, unpack_off_by_one);
+        assert!(!report.is_ok());
+        assert_eq!(report.corruption_events as usize, report.failures.len());
+        for label in ["max_density", "block64 boundaries", "block128 boundaries"] {
+            assert!(
+                report.failures.iter().any(|f| f.starts_with(label)),
+                "{} not reported",
+                label
+            );
+        }
+        assert!(report.failures.iter().all(|f| !f.starts_with("empty")));
+    }
+}
@@ -0,0 +1,237 @@
+//! Composable checksums for chunk-level verification
+//!
+//! A polynomial hash over the Mersenne prime `2^61 - 1`: the digest of
+//! bytes `b_0 .. b_{n-1}` is `sum (b_i + 1) * B^(n-1-i) mod P`. Appending
+//! `right` to `left` multiplies the left digest by `B^len(right)`, so
+//! digests of adjacent chunks computed independently (e.g. in parallel)
+//! combine into the digest of the whole stream. Bytes are offset by one so
+//! leading zero bytes still change the digest.
+//!
+//! This is for coverage and ordering checks in tests, not for security.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Modulus: the Mersenne prime `2^61 - 1`
+const MODULUS: u64 = (1 << 61) - 1;
+
+/// Polynomial base
+const BASE: u64 = 0x0100_0000_01b3;
+
+/// `a * b mod 2^61 - 1`
+fn mul_mod(a: u64, b: u64) -> u64 {
+    let product = a as u128 * b as u128;
+    let folded = (product & MODULUS as u128) + (product >> 61);
+    let folded = folded as u64;
+    if folded >= MODULUS {
+        folded - MODULUS
+    } else {
+        folded
+    }
+}
+
+/// `a + b mod 2^61 - 1` for reduced inputs
+fn add_mod(a: u64, b: u64) -> u64 {
+    let sum = a + b;
+    if sum >= MODULUS {
+        sum - MODULUS
+    } else {
+        sum
+    }
+}
+
+/// `BASE^exp mod 2^61 - 1`
+fn base_pow(mut exp: u64) -> u64 {
+    let mut result = 1;
+    let mut base = BASE;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base);
+        }
+        base = mul_mod(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Incremental checksum whose digests can be concatenated
+///
+/// # Example
+/// ```rust,ignore
+/// let (left, right) = data.split_at(4096);
+/// let combined = RollingChecksum::combine(
+///     RollingChecksum::of(left),
+///     RollingChecksum::of(right),
+///     right.len() as u64,
+/// );
+/// assert_eq!(combined, RollingChecksum::of(&data));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RollingChecksum {
+    state: u64,
+    len: u64,
+}
+
+impl RollingChecksum {
+    /// Checksum of the empty stream
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Digest of `data` in one call
+    pub fn of(data: &[u8]) -> u64 {
+        let mut checksum = Self::new();
+        checksum.update(data);
+        checksum.digest()
+    }
+
+    /// Append `data` to the stream
+    pub fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.state = add_mod(mul_mod(self.state, BASE), b as u64 + 1);
+        }
+        self.len += data.len() as u64;
+    }
+
+    /// Digest of everything appended so far
+    pub fn digest(&self) -> u64 {
+        self.state
+    }
+
+    /// Bytes appended so far
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether nothing has been appended
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Digest of the concatenation of two streams
+    ///
+    /// `right_len` is the length in bytes of the stream `right_digest`
+    /// was computed over. Digests are reduced modulo the prime first, so
+    /// arbitrary `u64` inputs do not overflow.
+    pub fn combine(left_digest: u64, right_digest: u64, right_len: u64) -> u64 {
+        add_mod(
+            mul_mod(left_digest % MODULUS, base_pow(right_len)),
+            right_digest % MODULUS,
+        )
+    }
+}
+
+/// Per-chunk and whole-file digests from [`checksum_file_chunked`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkedChecksum {
+    /// Chunk size used; every chunk but the last is exactly this long
+    pub chunk_size: usize,
+    /// `(length, digest)` of each chunk in file order
+    pub chunks: Vec<(u64, u64)>,
+    /// Digest of the whole file, combined from the chunk digests
+    pub digest: u64,
+    /// File length in bytes
+    pub len: u64,
+}
+
+/// Checksum `path` in `chunk_size` chunks
+///
+/// The whole-file digest is built only by combining chunk digests, so it
+/// matching [`RollingChecksum::of`] over the full content verifies that
+/// the chunks cover every byte exactly once, in order.
+///
+/// # Panics
+/// Panics if `chunk_size` is zero.
+pub fn checksum_file_chunked(path: &Path, chunk_size: usize) -> io::Result<ChunkedChecksum> {
+    assert!(chunk_size > 0, "chunk_size must be positive");
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; chunk_size];
+    let mut chunks = Vec::new();
+    let mut digest = RollingChecksum::new().digest();
+    let mut len = 0u64;
+
+    loop {
+        // Fill the whole chunk unless the file ends first
+        let mut filled = 0;
+        while filled < chunk_size {
+            match file.read(&mut buf[filled..])? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        if filled == 0 {
+            break;
+        }
+        let chunk_digest = RollingChecksum::of(&buf[..filled]);
+        digest = RollingChecksum::combine(digest, chunk_digest, filled as u64);
+        chunks.push((filled as u64, chunk_digest));
+        len += filled as u64;
+        if filled < chunk_size {
+            break;
+        }
+    }
+
+    Ok(ChunkedChecksum {
+        chunk_size,
+        chunks,
+        digest,
+        len,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::generate_noise_pattern;
+
+    #[test]
+    fn test_combine_matches_whole_stream() {
+        let data = generate_noise_pattern(10_000, 3);
+        let whole = RollingChecksum::of(&data);
+        assert_ne!(RollingChecksum::of(&[0, 1]), RollingChecksum::of(&[1]));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, &data).unwrap();
+
+        for chunk_size in [1, 7, 1000, 4096, 10_000, 65_536] {
+            let chunked = checksum_file_chunked(&path, chunk_size).unwrap();
+            assert_eq!(chunked.digest, whole, "chunk_size={}", chunk_size);
+            assert_eq!(chunked.len, 10_000);
+            assert_eq!(
+                chunked.chunks.len(),
+                10_000usize.div_ceil(chunk_size),
+                "chunk_size={}",
+                chunk_size
+            );
+
+            // Combining in a different grouping gives the same result
+            let (last_len, last) = *chunked.chunks.last().unwrap();
+            let prefix = chunked.chunks[..chunked.chunks.len() - 1]
+                .iter()
+                .fold(0, |acc, &(len, d)| RollingChecksum::combine(acc, d, len));
+            assert_eq!(RollingChecksum::combine(prefix, last, last_len), whole);
+        }
+
+        // Unreduced digests are taken modulo the prime
+        assert_eq!(
+            RollingChecksum::combine(u64::MAX, u64::MAX, 3),
+            RollingChecksum::combine(u64::MAX % MODULUS, u64::MAX % MODULUS, 3)
+        );
+
+        // Swapping two chunks is detected
+        let chunked = checksum_file_chunked(&path, 1000).unwrap();
+        let (a, b) = (chunked.chunks[0], chunked.chunks[1]);
+        let swapped = RollingChecksum::combine(RollingChecksum::combine(0, b.1, b.0), a.1, a.0);
+        let in_order = RollingChecksum::combine(RollingChecksum::combine(0, a.1, a.0), b.1, b.0);
+        assert_ne!(swapped, in_order);
+
+        let mut incremental = RollingChecksum::new();
+        for piece in data.chunks(333) {
+            incremental.update(piece);
+        }
+        assert_eq!(incremental.digest(), whole);
+        assert_eq!(incremental.len(), 10_000);
+    }
+}
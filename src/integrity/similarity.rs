@@ -0,0 +1,225 @@
+//! Rank preservation of pairwise similarities across encoder configs
+//!
+//! Changing [`ReversibleVSAConfig`] is allowed to move absolute cosines, but
+//! inputs that were close should stay closer than inputs that were far.
+//! [`validate_similarity_preservation`] measures that with the Spearman
+//! rank correlation of the two pairwise cosine matrices.
+
+use super::{IntegrityReport, IntegrityValidator};
+use embeddenator_vsa::{ReversibleVSAConfig, SparseVec};
+use rayon::prelude::*;
+use std::time::Instant;
+
+/// Fewest inputs for which pairwise ranks mean anything
+const MIN_PRESERVATION_INPUTS: usize = 3;
+
+impl IntegrityValidator {
+    /// Check that `config_b` keeps the similarity ranking of `config_a`
+    ///
+    /// Encodes every input under both configs, takes the upper triangle of
+    /// each pairwise cosine matrix and fails if their Spearman correlation
+    /// is below `min_spearman`. Fewer than 3 inputs fail outright.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let report = validator.validate_similarity_preservation(
+    ///     &corpus,
+    ///     &ReversibleVSAConfig::default(),
+    ///     &ReversibleVSAConfig::large_blocks(),
+    ///     0.9,
+    /// );
+    /// assert!(report.is_ok(), "{:?}", report.failures);
+    /// ```
+    pub fn validate_similarity_preservation(
+        &self,
+        inputs: &[Vec<u8>],
+        config_a: &ReversibleVSAConfig,
+        config_b: &ReversibleVSAConfig,
+        min_spearman: f64,
+    ) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+        if inputs.len() < MIN_PRESERVATION_INPUTS {
+            self.check(
+                &mut report,
+                "validate_similarity_preservation.corpus",
+                Instant::now(),
+                Some(format!(
+                    "similarity preservation needs at least {} inputs, got {}",
+                    MIN_PRESERVATION_INPUTS,
+                    inputs.len()
+                )),
+                || format!("inputs={}", inputs.len()),
+            );
+            return self.finish("validate_similarity_preservation", report);
+        }
+
+        let upper_a = upper_triangle(inputs, config_a);
+        let upper_b = upper_triangle(inputs, config_b);
+        self.check_rank_correlation(&mut report, &upper_a, &upper_b, min_spearman);
+        self.finish("validate_similarity_preservation", report)
+    }
+
+    fn check_rank_correlation(
+        &self,
+        report: &mut IntegrityReport,
+        upper_a: &[f64],
+        upper_b: &[f64],
+        min_spearman: f64,
+    ) {
+        let started = Instant::now();
+        let rho = spearman(upper_a, upper_b);
+        let shift = upper_a
+            .iter()
+            .zip(upper_b)
+            .map(|(a, b)| (a - b).abs())
+            .sum::<f64>()
+            / upper_a.len() as f64;
+        self.check(
+            report,
+            "validate_similarity_preservation.spearman",
+            started,
+            (rho < min_spearman).then(|| {
+                format!(
+                    "similarity ranking not preserved: spearman {:.4} < {:.4} over {} pairs \
+                     (mean |cosine shift| {:.4})",
+                    rho,
+                    min_spearman,
+                    upper_a.len(),
+                    shift
+                )
+            }),
+            || {
+                format!(
+                    "pairs={} spearman={:.4} mean_shift={:.4}",
+                    upper_a.len(),
+                    rho,
+                    shift
+                )
+            },
+        );
+    }
+}
+
+/// [`IntegrityValidator::validate_similarity_preservation`] with a default validator
+pub fn validate_similarity_preservation(
+    inputs: &[Vec<u8>],
+    config_a: &ReversibleVSAConfig,
+    config_b: &ReversibleVSAConfig,
+    min_spearman: f64,
+) -> IntegrityReport {
+    IntegrityValidator::new().validate_similarity_preservation(
+        inputs,
+        config_a,
+        config_b,
+        min_spearman,
+    )
+}
+
+/// Pairwise cosines `(i, j)` for `i < j`, row by row
+fn upper_triangle(inputs: &[Vec<u8>], config: &ReversibleVSAConfig) -> Vec<f64> {
+    let vectors: Vec<SparseVec> = inputs
+        .par_iter()
+        .map(|data| SparseVec::encode_data(data, config, None))
+        .collect();
+    (0..vectors.len())
+        .flat_map(|i| (i + 1..vectors.len()).map(move |j| (i, j)))
+        .map(|(i, j)| vectors[i].cosine(&vectors[j]))
+        .collect()
+}
+
+/// Spearman rank correlation, with tied values sharing their mean rank
+///
+/// Two constant sequences count as perfectly correlated; one constant
+/// sequence against a varying one as uncorrelated.
+fn spearman(a: &[f64], b: &[f64]) -> f64 {
+    let (ra, rb) = (ranks(a), ranks(b));
+    let mean = (a.len() as f64 + 1.0) / 2.0;
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in ra.iter().zip(&rb) {
+        cov += (x - mean) * (y - mean);
+        var_a += (x - mean) * (x - mean);
+        var_b += (y - mean) * (y - mean);
+    }
+    match (var_a == 0.0, var_b == 0.0) {
+        (true, true) => 1.0,
+        (true, false) | (false, true) => 0.0,
+        (false, false) => cov / (var_a * var_b).sqrt(),
+    }
+}
+
+/// 1-based ranks of `values`; ties get the mean of the ranks they span
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&i, &j| values[i].total_cmp(&values[j]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::generate_noise_pattern;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    /// Inputs sharing prefixes of varying length, so similarities spread out
+    fn corpus() -> Vec<Vec<u8>> {
+        let base = generate_noise_pattern(512, 1);
+        (0..10u64)
+            .map(|i| {
+                let mut data = base.clone();
+                let keep = 512 - 48 * i as usize;
+                data[keep..].copy_from_slice(&generate_noise_pattern(512 - keep, 100 + i));
+                data
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_config_preserves_ranking_and_shuffle_fails() {
+        let config = ReversibleVSAConfig::default();
+        let inputs = corpus();
+        let report = validate_similarity_preservation(&inputs, &config, &config, 0.999);
+        assert!(report.is_ok(), "{:?}", report.failures);
+        assert_eq!(report.checks_total, 1);
+
+        let upper = upper_triangle(&inputs, &config);
+        assert_eq!(upper.len(), 45);
+        assert_eq!(spearman(&upper, &upper), 1.0);
+
+        let mut shuffled = upper.clone();
+        shuffled.shuffle(&mut StdRng::seed_from_u64(5));
+        let mut report = IntegrityReport::default();
+        IntegrityValidator::new().check_rank_correlation(&mut report, &upper, &shuffled, 0.8);
+        assert!(!report.is_ok());
+        assert!(
+            report.failures[0].contains("spearman") && report.failures[0].contains("45 pairs"),
+            "{}",
+            report.failures[0]
+        );
+    }
+
+    #[test]
+    fn test_degenerate_corpus_and_ties() {
+        let config = ReversibleVSAConfig::default();
+        let report = validate_similarity_preservation(&corpus()[..2], &config, &config, 0.5);
+        assert!(!report.is_ok());
+        assert!(report.failures[0].contains("at least 3 inputs, got 2"));
+
+        assert_eq!(ranks(&[0.3, 0.1, 0.3, 0.2]), vec![3.5, 1.0, 3.5, 2.0]);
+        assert!((spearman(&[1.0, 2.0, 3.0], &[3.0, 2.0, 1.0]) + 1.0).abs() < 1e-12);
+    }
+}
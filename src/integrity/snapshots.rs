@@ -0,0 +1,188 @@
+//! Golden-file snapshots of encoded vectors
+//!
+//! Snapshots are stored as `<dir>/<name>.snap.json`, one compact JSON object
+//! with sorted `pos` and `neg` index lists, so diffs stay reviewable in
+//! version control. Set `UPDATE_SNAPSHOTS` to rewrite snapshots instead of
+//! failing when they differ or are missing.
+//!
+//! # Example
+//! ```rust,ignore
+//! let report = assert_matches_snapshot("hello_world", &encode(b"hello world"), &snapshot_dir);
+//! assert!(report.is_ok(), "{}", report.failures.join("\n"));
+//! ```
+
+use super::{sparse_diff_summary, IntegrityReport};
+use crate::generators::index_overlap;
+use embeddenator_vsa::SparseVec;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Environment variable that switches comparisons to rewriting snapshots
+pub const UPDATE_SNAPSHOTS_ENV: &str = "UPDATE_SNAPSHOTS";
+
+/// Validator name recorded for snapshot checks
+const VALIDATOR: &str = "snapshot";
+
+/// Snapshot format version written to every file
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// On-disk snapshot representation
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    pos: Vec<usize>,
+    neg: Vec<usize>,
+}
+
+impl Snapshot {
+    fn of(vec: &SparseVec) -> Self {
+        let canonical = |indices: &[usize]| {
+            let mut out = indices.to_vec();
+            out.sort_unstable();
+            out
+        };
+        Self {
+            version: SNAPSHOT_VERSION,
+            pos: canonical(&vec.pos),
+            neg: canonical(&vec.neg),
+        }
+    }
+}
+
+/// Path of the snapshot called `name` in `dir`
+pub fn snapshot_path(name: &str, dir: &Path) -> PathBuf {
+    dir.join(format!("{}.snap.json", name))
+}
+
+/// Write `vec` as the snapshot called `name`, creating `dir` if needed
+pub fn save_snapshot(name: &str, vec: &SparseVec, dir: &Path) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = snapshot_path(name, dir);
+    let mut content = serde_json::to_string(&Snapshot::of(vec))?;
+    content.push('\n');
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Load the snapshot called `name` from `dir`
+pub fn load_snapshot(name: &str, dir: &Path) -> io::Result<SparseVec> {
+    let content = fs::read_to_string(snapshot_path(name, dir))?;
+    let snapshot: Snapshot = serde_json::from_str(&content)?;
+    Ok(SparseVec {
+        pos: snapshot.pos,
+        neg: snapshot.neg,
+    })
+}
+
+/// Compare `vec` against the snapshot called `name` in `dir`
+///
+/// A mismatch fails with the index-level diff (nnz, differing indices per
+/// sign, cosine and overlap counts). A missing snapshot also fails. When
+/// `UPDATE_SNAPSHOTS` is set, the snapshot is rewritten instead and the
+/// check passes with a warning.
+pub fn assert_matches_snapshot(name: &str, vec: &SparseVec, dir: &Path) -> IntegrityReport {
+    match_snapshot(
+        name,
+        vec,
+        dir,
+        std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some(),
+    )
+}
+
+fn match_snapshot(name: &str, vec: &SparseVec, dir: &Path, update: bool) -> IntegrityReport {
+    let mut report = IntegrityReport::new();
+    let started = Instant::now();
+    let path = snapshot_path(name, dir);
+
+    let failure = match load_snapshot(name, dir) {
+        Ok(expected) => {
+            let stats = index_overlap(&expected, vec);
+            (stats.hamming() != 0).then(|| {
+                report.record_corruption();
+                format!(
+                    "snapshot {} mismatch ({})\n{}",
+                    name,
+                    path.display(),
+                    sparse_diff_summary(&expected, vec, &stats)
+                )
+            })
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Some(format!(
+            "snapshot {} missing at {}; set {} to create it",
+            name,
+            path.display(),
+            UPDATE_SNAPSHOTS_ENV
+        )),
+        Err(e) => Some(format!(
+            "snapshot {} unreadable at {}: {}",
+            name,
+            path.display(),
+            e
+        )),
+    };
+
+    let failure = match failure {
+        Some(msg) if update => match save_snapshot(name, vec, dir) {
+            Ok(_) => {
+                report.warn(format!("snapshot {} updated: {}", name, msg));
+                None
+            }
+            Err(e) => Some(format!(
+                "snapshot {} could not be updated at {}: {}",
+                name,
+                path.display(),
+                e
+            )),
+        },
+        failure => failure,
+    };
+    report.record_check(VALIDATOR, name, started.elapsed(), failure);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::deterministic_sparse_vec;
+
+    #[test]
+    fn test_snapshot_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let v = deterministic_sparse_vec(crate::DIM, 200, 3);
+
+        // Missing snapshots fail unless updating
+        let report = match_snapshot("encoded", &v, dir.path(), false);
+        assert!(!report.is_ok());
+        assert!(report.failures[0].contains("missing"));
+
+        save_snapshot("encoded", &v, dir.path()).unwrap();
+        let loaded = load_snapshot("encoded", dir.path()).unwrap();
+        assert_eq!((loaded.pos, loaded.neg), (v.pos.clone(), v.neg.clone()));
+        let report = match_snapshot("encoded", &v, dir.path(), false);
+        assert!(report.is_ok(), "{:?}", report.failures);
+        assert_eq!(report.records()[0].validator, "snapshot");
+        assert_eq!(report.records()[0].check, "encoded");
+
+        let mut mutated = v.clone();
+        let moved = mutated.pos.remove(0);
+        mutated.neg.push(moved);
+        mutated.neg.sort_unstable();
+        let report = match_snapshot("encoded", &mutated, dir.path(), false);
+        assert!(!report.is_ok());
+        assert_eq!(report.corruption_events, 1);
+        let message = &report.failures[0];
+        assert!(message.contains("differing indices: 1"), "{}", message);
+        assert!(message.contains(&format!("pos differing: [{}]", moved)));
+        assert!(message.contains("pn=1"));
+
+        // Updating rewrites the snapshot and passes with a warning
+        let report = match_snapshot("encoded", &mutated, dir.path(), true);
+        assert!(report.is_ok());
+        assert_eq!(report.warnings.len(), 1);
+        assert!(match_snapshot("encoded", &mutated, dir.path(), false).is_ok());
+        assert!(!match_snapshot("encoded", &v, dir.path(), false).is_ok());
+    }
+}
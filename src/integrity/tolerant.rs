@@ -0,0 +1,417 @@
+//! Directory and file comparison that accepts expected transformations
+//!
+//! Extraction may legitimately convert line endings, drop a trailing
+//! newline, reset permission bits or move a subtree. [`CompareOptions`]
+//! names the transformations to accept; each accepted difference is counted
+//! in [`IntegrityReport::tolerated`] so drift stays visible without failing.
+
+use super::{checksum_file, collect_entries, IntegrityReport, MismatchKind};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Allowed mtime drift, covering coarse filesystem granularity
+const MTIME_TOLERANCE: Duration = Duration::from_secs(2);
+
+/// Differences [`compare_directories_tolerant`] and [`compare_files`] accept
+///
+/// The default is strict: every difference fails, including mtimes, which
+/// [`compare_directories`](super::compare_directories) does not check.
+///
+/// # Example
+/// ```rust,ignore
+/// let options = CompareOptions::new()
+///     .normalize_line_endings()
+///     .ignore_permissions()
+///     .with_path_rewrite("input/", "restored/")
+///     .with_ignore("**/*.tmp");
+/// let report = compare_directories_tolerant(&input, &output, &options)?;
+/// assert!(report.is_ok(), "{}", report.summary());
+/// assert_eq!(report.tolerated, 0, "unexpected normalization");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompareOptions {
+    /// Treat `\r\n` and `\n` as equal
+    pub normalize_line_endings: bool,
+    /// Accept a missing or extra final newline
+    pub ignore_trailing_newline: bool,
+    /// Accept differing permission bits
+    pub ignore_permissions: bool,
+    /// Accept differing modification times
+    pub ignore_mtime: bool,
+    /// Expected paths starting with `.0` are looked up with `.1` instead
+    pub path_rewrites: Vec<(String, String)>,
+    /// Globs of relative paths left out on both sides (`*`, `?`, `**`)
+    pub ignore_globs: Vec<String>,
+}
+
+impl CompareOptions {
+    /// Strict comparison
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept every difference this type knows how to tolerate
+    pub fn permissive() -> Self {
+        Self {
+            normalize_line_endings: true,
+            ignore_trailing_newline: true,
+            ignore_permissions: true,
+            ignore_mtime: true,
+            ..Self::default()
+        }
+    }
+
+    /// Treat `\r\n` and `\n` as equal
+    pub fn normalize_line_endings(mut self) -> Self {
+        self.normalize_line_endings = true;
+        self
+    }
+
+    /// Accept a missing or extra final newline
+    pub fn ignore_trailing_newline(mut self) -> Self {
+        self.ignore_trailing_newline = true;
+        self
+    }
+
+    /// Accept differing permission bits
+    pub fn ignore_permissions(mut self) -> Self {
+        self.ignore_permissions = true;
+        self
+    }
+
+    /// Accept differing modification times
+    pub fn ignore_mtime(mut self) -> Self {
+        self.ignore_mtime = true;
+        self
+    }
+
+    /// Look up expected paths under `from` at `to` in the actual tree
+    pub fn with_path_rewrite(mut self, from: &str, to: &str) -> Self {
+        self.path_rewrites.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Skip relative paths matching `glob` on both sides
+    pub fn with_ignore(mut self, glob: &str) -> Self {
+        self.ignore_globs.push(glob.to_string());
+        self
+    }
+
+    fn is_ignored(&self, rel: &str) -> bool {
+        self.ignore_globs.iter().any(|g| glob_match(g, rel))
+    }
+
+    /// `rel` after the first matching rewrite, if any
+    fn rewrite(&self, rel: &str) -> Option<String> {
+        self.path_rewrites.iter().find_map(|(from, to)| {
+            rel.strip_prefix(from.as_str())
+                .map(|rest| format!("{}{}", to, rest))
+        })
+    }
+
+    fn normalizes_content(&self) -> bool {
+        self.normalize_line_endings || self.ignore_trailing_newline
+    }
+
+    fn normalize<'a>(&self, mut data: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.ignore_trailing_newline {
+            if let Some(rest) = data.strip_suffix(b"\n") {
+                data = rest.strip_suffix(b"\r").unwrap_or(rest);
+            }
+        }
+        if self.normalize_line_endings && data.windows(2).any(|w| w == b"\r\n") {
+            let mut out = Vec::with_capacity(data.len());
+            let mut iter = data.iter().peekable();
+            while let Some(&b) = iter.next() {
+                if b != b'\r' || iter.peek() != Some(&&b'\n') {
+                    out.push(b);
+                }
+            }
+            return out.into();
+        }
+        data.into()
+    }
+}
+
+/// Compare two directory trees, accepting the differences `options` allows
+///
+/// Like [`compare_directories`](super::compare_directories): one check per
+/// expected file and symlink, with files only in `actual` reported as
+/// [`MismatchKind::Extra`]. Ignored paths are not compared at all; a path
+/// found through a rewrite counts as one tolerated difference.
+pub fn compare_directories_tolerant(
+    expected: &Path,
+    actual: &Path,
+    options: &CompareOptions,
+) -> io::Result<IntegrityReport> {
+    let (expected_files, expected_links) = collect_entries(expected)?;
+    let (actual_files, actual_links) = collect_entries(actual)?;
+    let actual_paths: BTreeMap<&str, &Path> = actual_files
+        .iter()
+        .map(|(rel, path)| (rel.as_str(), path.as_path()))
+        .collect();
+    let mut matched: HashSet<String> = HashSet::new();
+    let mut report = IntegrityReport::new();
+
+    for (rel, path) in &expected_files {
+        if options.is_ignored(rel) {
+            continue;
+        }
+        let target = resolve(rel, options, &mut report);
+        let Some(found) = actual_paths.get(target.as_str()) else {
+            report.record_file_mismatch(rel.as_str(), MismatchKind::Missing);
+            continue;
+        };
+        matched.insert(target);
+        compare_pair(&mut report, rel, path, found, options)?;
+    }
+    for (rel, _) in &actual_files {
+        if !matched.contains(rel) && !options.is_ignored(rel) {
+            report.record_file_mismatch(rel.as_str(), MismatchKind::Extra);
+        }
+    }
+
+    let mut matched_links: HashSet<String> = HashSet::new();
+    for (rel, link) in &expected_links {
+        if options.is_ignored(rel) {
+            continue;
+        }
+        let target = resolve(rel, options, &mut report);
+        match actual_links.get(&target) {
+            None => report.record_file_mismatch(rel.as_str(), MismatchKind::Missing),
+            Some(found) if found != link => {
+                report.record_file_mismatch(rel.as_str(), MismatchKind::LinkTarget)
+            }
+            Some(_) => report.pass(),
+        }
+        matched_links.insert(target);
+    }
+    for rel in actual_links.keys() {
+        if !matched_links.contains(rel) && !options.is_ignored(rel) {
+            report.record_file_mismatch(rel.as_str(), MismatchKind::Extra);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Compare one file against another, accepting the differences `options` allows
+///
+/// Path rewrites and ignore globs do not apply to a single pair.
+pub fn compare_files(
+    expected: &Path,
+    actual: &Path,
+    options: &CompareOptions,
+) -> io::Result<IntegrityReport> {
+    let mut report = IntegrityReport::new();
+    let name = actual.display().to_string();
+    compare_pair(&mut report, &name, expected, actual, options)?;
+    Ok(report)
+}
+
+/// Where `rel` is expected in the actual tree, counting a rewrite as tolerated
+fn resolve(rel: &str, options: &CompareOptions, report: &mut IntegrityReport) -> String {
+    match options.rewrite(rel) {
+        Some(target) if target != rel => {
+            report.record_tolerated();
+            target
+        }
+        _ => rel.to_string(),
+    }
+}
+
+/// Content, mode and mtime of one file pair as a single check
+fn compare_pair(
+    report: &mut IntegrityReport,
+    rel: &str,
+    expected: &Path,
+    actual: &Path,
+    options: &CompareOptions,
+) -> io::Result<()> {
+    let want = checksum_file(expected, None)?;
+    let found = checksum_file(actual, None)?;
+    let mut ok = true;
+
+    if want.hash != found.hash {
+        let tolerated = options.normalizes_content() && {
+            let (a, b) = (fs::read(expected)?, fs::read(actual)?);
+            options.normalize(&a) == options.normalize(&b)
+        };
+        if tolerated {
+            report.record_tolerated();
+        } else {
+            let kind = if want.size != found.size {
+                MismatchKind::Size
+            } else {
+                MismatchKind::Content
+            };
+            report.record_file_mismatch(rel, kind);
+            ok = false;
+        }
+    }
+
+    if want.mode != found.mode {
+        if options.ignore_permissions {
+            report.record_tolerated();
+        } else {
+            report.record_file_mismatch(rel, MismatchKind::Permissions);
+            ok = false;
+        }
+    }
+
+    let mtimes = (
+        fs::metadata(expected)?.modified(),
+        fs::metadata(actual)?.modified(),
+    );
+    if let (Ok(a), Ok(b)) = mtimes {
+        let drift = a.duration_since(b).unwrap_or_else(|e| e.duration());
+        if drift > MTIME_TOLERANCE {
+            if options.ignore_mtime {
+                report.record_tolerated();
+            } else {
+                report.record_file_mismatch(rel, MismatchKind::Mtime);
+                ok = false;
+            }
+        }
+    }
+
+    if ok {
+        report.pass();
+    }
+    Ok(())
+}
+
+/// Match `/`-separated `path` against `pattern`
+///
+/// `*` and `?` stay within one component; `**` spans any number of them.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    fn matches(p: &[u8], s: &[u8]) -> bool {
+        match p.split_first() {
+            None => s.is_empty(),
+            Some((b'*', rest)) if rest.first() == Some(&b'*') => {
+                let rest = rest[1..].strip_prefix(b"/").unwrap_or(&rest[1..]);
+                if rest.is_empty() {
+                    return true;
+                }
+                (0..=s.len()).any(|i| (i == 0 || s[i - 1] == b'/') && matches(rest, &s[i..]))
+            }
+            Some((b'*', rest)) => {
+                for i in 0..=s.len() {
+                    if matches(rest, &s[i..]) {
+                        return true;
+                    }
+                    if i < s.len() && s[i] == b'/' {
+                        break;
+                    }
+                }
+                false
+            }
+            Some((b'?', rest)) => s.first().is_some_and(|&c| c != b'/') && matches(rest, &s[1..]),
+            Some((&c, rest)) => s.first() == Some(&c) && matches(rest, &s[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), path.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, data: &[u8]) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn test_tolerates_crlf_and_mode_changes_only_when_asked() {
+        let dir = TempDir::new().unwrap();
+        let (expected, actual) = (dir.path().join("expected"), dir.path().join("actual"));
+        write(&expected, "a.txt", b"one\ntwo\n");
+        write(&expected, "docs/b.txt", b"three\n");
+        write(&expected, "c.bin", &[0, 1, 2, 3]);
+        write(&expected, "skip.log", b"volatile");
+        write(&actual, "a.txt", b"one\r\ntwo\r\n");
+        write(&actual, "docs/b.txt", b"three\r\n");
+        write(&actual, "c.bin", &[0, 1, 2, 3]);
+        write(&actual, "skip.log", b"changed");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for rel in ["a.txt", "c.bin"] {
+                let path = actual.join(rel);
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+            }
+            fs::set_permissions(expected.join("a.txt"), fs::Permissions::from_mode(0o644)).unwrap();
+            fs::set_permissions(expected.join("c.bin"), fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        let mode_changes = if cfg!(unix) { 2 } else { 0 };
+
+        let permissive = CompareOptions::permissive().with_ignore("*.log");
+        let report = compare_directories_tolerant(&expected, &actual, &permissive).unwrap();
+        assert!(report.is_ok(), "{}", report.summary());
+        assert_eq!(report.checks_total, 3);
+        assert_eq!(report.tolerated, 2 + mode_changes);
+
+        let strict = CompareOptions::new();
+        let report = compare_directories_tolerant(&expected, &actual, &strict).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.tolerated, 0);
+        let kinds: Vec<_> = report
+            .file_mismatches
+            .iter()
+            .map(|m| (m.path.as_str(), m.kind))
+            .collect();
+        assert!(kinds.contains(&("a.txt", MismatchKind::Size)));
+        assert!(kinds.contains(&("docs/b.txt", MismatchKind::Size)));
+        assert!(kinds.contains(&("skip.log", MismatchKind::Size)));
+        if cfg!(unix) {
+            assert!(kinds.contains(&("c.bin", MismatchKind::Permissions)));
+        }
+
+        let report =
+            compare_files(&expected.join("a.txt"), &actual.join("a.txt"), &permissive).unwrap();
+        assert!(report.is_ok());
+    }
+
+    #[test]
+    fn test_trailing_newline_and_path_rewrites() {
+        let dir = TempDir::new().unwrap();
+        let (expected, actual) = (dir.path().join("expected"), dir.path().join("actual"));
+        write(&expected, "in/notes.txt", b"last line");
+        write(&actual, "out/notes.txt", b"last line\n");
+
+        let strict = CompareOptions::new().with_path_rewrite("in/", "out/");
+        let report = compare_directories_tolerant(&expected, &actual, &strict).unwrap();
+        assert_eq!(report.tolerated, 1);
+        assert_eq!(report.file_mismatches.len(), 1);
+        assert_eq!(report.file_mismatches[0].kind, MismatchKind::Size);
+
+        let options = strict.ignore_trailing_newline();
+        let report = compare_directories_tolerant(&expected, &actual, &options).unwrap();
+        assert!(report.is_ok(), "{}", report.summary());
+        assert_eq!(report.tolerated, 2);
+
+        let report =
+            compare_directories_tolerant(&expected, &actual, &CompareOptions::new()).unwrap();
+        let kinds: Vec<_> = report.file_mismatches.iter().map(|m| m.kind).collect();
+        assert_eq!(kinds, vec![MismatchKind::Missing, MismatchKind::Extra]);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.log", "run.log"));
+        assert!(!glob_match("*.log", "logs/run.log"));
+        assert!(glob_match("**/*.log", "logs/run.log"));
+        assert!(glob_match("**/*.log", "run.log"));
+        assert!(glob_match("logs/**", "logs/a/b.txt"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file/.txt"));
+    }
+}
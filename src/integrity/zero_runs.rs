@@ -0,0 +1,332 @@
+//! Maps of zeroed byte ranges, for diagnosing packet-loss style damage
+//!
+//! [`ChaosInjector::simulate_packet_loss`](crate::ChaosInjector::simulate_packet_loss)
+//! and many real transport failures leave whole packets zeroed. Runs whose
+//! offsets and lengths share the packet size as a common factor point at
+//! that kind of loss rather than at bit rot.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Read buffer size for [`find_zero_runs_in_file`]
+const SCAN_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A maximal range of zero bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZeroRun {
+    /// Offset of the first zero byte
+    pub offset: u64,
+    /// Number of zero bytes
+    pub len: u64,
+}
+
+impl ZeroRun {
+    /// Offset one past the last zero byte
+    pub fn end(&self) -> u64 {
+        self.offset + self.len
+    }
+}
+
+/// Aggregates from [`summarize_runs`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZeroRunSummary {
+    /// Number of runs
+    pub runs: usize,
+    /// Zero bytes across all runs
+    pub zero_bytes: u64,
+    /// Length of the data the runs were found in
+    pub total_len: u64,
+    /// Longest run
+    pub largest: u64,
+    /// Greatest common divisor of run offsets and lengths
+    ///
+    /// The length of a run reaching `total_len` is left out, since a final
+    /// partial packet is cut short by the end of the data. 0 without runs,
+    /// or when the only run covers all the data.
+    pub granularity: u64,
+}
+
+impl ZeroRunSummary {
+    /// Fraction of the data covered by runs
+    pub fn zero_fraction(&self) -> f64 {
+        if self.total_len == 0 {
+            0.0
+        } else {
+            self.zero_bytes as f64 / self.total_len as f64
+        }
+    }
+
+    /// Whether every run starts and ends on a multiple of `packet_size`
+    pub fn is_aligned_to(&self, packet_size: u64) -> bool {
+        packet_size > 0 && self.granularity > 0 && self.granularity.is_multiple_of(packet_size)
+    }
+}
+
+impl fmt::Display for ZeroRunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} zero runs, {} bytes ({:.2}%), largest {}",
+            self.runs,
+            self.zero_bytes,
+            self.zero_fraction() * 100.0,
+            self.largest
+        )?;
+        if self.granularity > 1 {
+            write!(f, ", aligned to {} bytes", self.granularity)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs of at least `min_run_len` zero bytes in `data`
+///
+/// # Example
+/// ```rust,ignore
+/// injector.simulate_packet_loss(&mut data, 0.05, 1500);
+/// let runs = find_zero_runs(&data, 64);
+/// assert!(summarize_runs(&runs, data.len() as u64).is_aligned_to(1500));
+/// ```
+pub fn find_zero_runs(data: &[u8], min_run_len: u64) -> Vec<ZeroRun> {
+    let mut scanner = ZeroRunScanner::new(min_run_len);
+    scanner.feed(data);
+    scanner.finish()
+}
+
+/// [`find_zero_runs`] over a file, read in fixed-size chunks
+pub fn find_zero_runs_in_file(path: &Path, min_run_len: u64) -> io::Result<Vec<ZeroRun>> {
+    let mut file = File::open(path)?;
+    let mut scanner = ZeroRunScanner::new(min_run_len);
+    let mut buf = vec![0u8; SCAN_CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        scanner.feed(&buf[..n]);
+    }
+    Ok(scanner.finish())
+}
+
+/// Count, size and alignment of `runs` within `total_len` bytes
+pub fn summarize_runs(runs: &[ZeroRun], total_len: u64) -> ZeroRunSummary {
+    let granularity = runs.iter().fold(0, |g, run| {
+        let g = gcd(g, run.offset);
+        if run.end() >= total_len {
+            g
+        } else {
+            gcd(g, run.len)
+        }
+    });
+    ZeroRunSummary {
+        runs: runs.len(),
+        zero_bytes: runs.iter().map(|r| r.len).sum(),
+        total_len,
+        largest: runs.iter().map(|r| r.len).max().unwrap_or(0),
+        granularity,
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Incremental zero-run finder over consecutive chunks
+struct ZeroRunScanner {
+    min_run_len: u64,
+    position: u64,
+    open: Option<u64>,
+    runs: Vec<ZeroRun>,
+}
+
+impl ZeroRunScanner {
+    fn new(min_run_len: u64) -> Self {
+        Self {
+            min_run_len: min_run_len.max(1),
+            position: 0,
+            open: None,
+            runs: Vec::new(),
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) {
+        for (i, &byte) in chunk.iter().enumerate() {
+            let offset = self.position + i as u64;
+            match (byte == 0, self.open) {
+                (true, None) => self.open = Some(offset),
+                (false, Some(start)) => self.close(start, offset),
+                _ => {}
+            }
+        }
+        self.position += chunk.len() as u64;
+    }
+
+    fn close(&mut self, start: u64, end: u64) {
+        self.open = None;
+        if end - start >= self.min_run_len {
+            self.runs.push(ZeroRun {
+                offset: start,
+                len: end - start,
+            });
+        }
+    }
+
+    fn finish(mut self) -> Vec<ZeroRun> {
+        if let Some(start) = self.open {
+            self.close(start, self.position);
+        }
+        self.runs
+    }
+}
+
+/// Zero runs on one side of a comparison that cover differing bytes
+///
+/// Fed window by window; gives up as soon as a differing byte is non-zero
+/// on this side, since the damage is then not (only) zeroing.
+#[cfg(feature = "mmap")]
+pub(crate) struct ZeroedRegions {
+    runs: Vec<ZeroRun>,
+    /// Start of the open zero run and whether it covers a differing byte
+    open: Option<(u64, bool)>,
+    only_zeroed: bool,
+}
+
+#[cfg(feature = "mmap")]
+impl ZeroedRegions {
+    pub(crate) fn new() -> Self {
+        Self {
+            runs: Vec::new(),
+            open: None,
+            only_zeroed: true,
+        }
+    }
+
+    /// Scan `side` against `other`, both starting at `offset`
+    pub(crate) fn feed(&mut self, offset: u64, side: &[u8], other: &[u8]) {
+        if !self.only_zeroed {
+            return;
+        }
+        if side == other {
+            // Only an open run can change; it ends at the first non-zero
+            if self.open.is_some() {
+                if let Some(i) = side.iter().position(|&b| b != 0) {
+                    self.close(offset + i as u64);
+                }
+            }
+            return;
+        }
+        for (i, (&x, &y)) in side.iter().zip(other).enumerate() {
+            let at = offset + i as u64;
+            if x == 0 {
+                let (_, covers_diff) = self.open.get_or_insert((at, false));
+                *covers_diff |= x != y;
+            } else if x != y {
+                self.only_zeroed = false;
+                self.runs.clear();
+                return;
+            } else if self.open.is_some() {
+                self.close(at);
+            }
+        }
+    }
+
+    fn close(&mut self, end: u64) {
+        if let Some((start, true)) = self.open.take() {
+            self.runs.push(ZeroRun {
+                offset: start,
+                len: end - start,
+            });
+        }
+    }
+
+    /// The runs, if every differing byte was zero on this side
+    pub(crate) fn finish(mut self, end: u64) -> Option<Vec<ZeroRun>> {
+        if !self.only_zeroed {
+            return None;
+        }
+        self.close(end);
+        (!self.runs.is_empty()).then_some(self.runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChaosInjector;
+
+    /// Bytes 1..=255 repeating, so every zero comes from the damage
+    fn nonzero_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 255) as u8 + 1).collect()
+    }
+
+    #[test]
+    fn test_packet_loss_runs_align_to_packet_size() {
+        let packet = 1500;
+        let len = packet * 200 + 700;
+        let mut data = nonzero_data(len);
+        ChaosInjector::new(11).simulate_packet_loss(&mut data, 0.1, packet);
+
+        let runs = find_zero_runs(&data, 1);
+        assert!(!runs.is_empty());
+        for run in &runs {
+            assert!(run.offset.is_multiple_of(packet as u64), "{:?}", run);
+            assert!(
+                run.len.is_multiple_of(packet as u64) || run.end() == len as u64,
+                "{:?}",
+                run
+            );
+        }
+
+        let summary = summarize_runs(&runs, len as u64);
+        assert_eq!(summary.runs, runs.len());
+        assert_eq!(
+            summary.zero_bytes,
+            data.iter().filter(|&&b| b == 0).count() as u64
+        );
+        assert!(summary.is_aligned_to(packet as u64), "{}", summary);
+        assert!(!summary.is_aligned_to(1024));
+
+        let path = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(path.path(), &data).unwrap();
+        assert_eq!(find_zero_runs_in_file(path.path(), 1).unwrap(), runs);
+    }
+
+    #[test]
+    fn test_min_run_len_and_unaligned_runs() {
+        let mut data = nonzero_data(1000);
+        data[10] = 0;
+        data[100..133].fill(0);
+        data[990..].fill(0);
+        assert_eq!(find_zero_runs(&data, 1).len(), 3);
+        let runs = find_zero_runs(&data, 8);
+        assert_eq!(
+            runs,
+            vec![
+                ZeroRun {
+                    offset: 100,
+                    len: 33
+                },
+                ZeroRun {
+                    offset: 990,
+                    len: 10
+                },
+            ]
+        );
+        let summary = summarize_runs(&runs, 1000);
+        assert_eq!(summary.granularity, 1);
+        assert_eq!(summary.largest, 33);
+        assert!(!summary.is_aligned_to(10));
+        assert_eq!(
+            summarize_runs(&[], 1000),
+            ZeroRunSummary {
+                total_len: 1000,
+                ..ZeroRunSummary::default()
+            }
+        );
+    }
+}
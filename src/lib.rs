@@ -65,22 +65,78 @@
 //! println!("{}", metrics.summary());
 //! ```
 
+pub mod cancel;
 pub mod chaos;
+pub mod error;
+pub mod events;
 pub mod fixtures;
 pub mod generators;
 pub mod harness;
 pub mod integrity;
 pub mod metrics;
+pub mod progress;
+
+#[cfg(feature = "mmap")]
+pub mod mmap;
 
 // Re-export commonly used items
-pub use chaos::ChaosInjector;
-pub use fixtures::{create_test_data, create_test_dataset, TestDataPattern};
+pub use cancel::CancellationToken;
+pub use chaos::{
+    noise_tolerance_sweep, BitFlip, ChaosInjector, CorruptedFile, CorruptionManifest,
+    DelayDistribution, FailurePattern, Flaky, InjectedFault, IoNoise, LatencyInjector,
+    LoadGenerator, LossMode, MemoryPressure, NoiseSweepReport, SpliceSegment, SpliceSource,
+    TruncateAmount,
+};
+pub use error::{CorruptionKind, Error};
+pub use fixtures::{
+    check_disk_space, create_boundary_size_files, create_test_data, create_test_dataset,
+    detect_pattern, estimate_disk_usage, explain_mismatch, robustness_suite, run_robustness_suite,
+    try_create_test_dataset, verify_dataset_against_manifest, walk_deterministic, write_pattern_to,
+    DatasetMutator, ExpectedOutcome, FileKind, FileMetaSpec, MutationKind, MutationLog,
+    PatternDetection, PatternMismatch, RobustnessCase, TestDataPattern, VectorCorpusFile,
+    WalkOrder,
+};
 pub use generators::{
-    deterministic_sparse_vec, mk_random_sparsevec, random_sparse_vec, sparse_dot,
+    assert_deterministic_across_runs, bytes_fingerprint, determinism_fingerprint,
+    deterministic_sparse_vec, deterministic_sparse_vec_into, fingerprint, for_dim, forked_rngs,
+    index_overlap, inverse_permutation, jaccard_similarity, mk_random_sparsevec, par_map_seeded,
+    par_top_k_similar, permute_sparse_vec, query_workload, random_permutation, random_sparse_vec,
+    random_sparse_vec_into, reference_bundle_many, reference_top_k_similar,
+    reference_weighted_bundle, shard_confined_vec, shard_spanning_vec, similarity_matrix,
+    sparse_dot, sparse_vec_fingerprint, ternary_hamming, top_k_similar, vec_from_pattern,
+    DimGenerators, GenerationError, OverlapStats, PoolStats, PooledSparseVec, QueryProbe,
+    SimilarityMatrix, VecFingerprint, VecPool,
+};
+#[cfg(feature = "log")]
+pub use harness::LogCapture;
+pub use harness::{
+    age_dataset, calibrate_threshold, capacity_probe, grow_subset, record_mode, retry,
+    run_with_timeout, touch_subset, CapacityReport, ChangeKind, ChangeRecord, ChunkFault,
+    ChunkSimulator, ConcurrencyStressor, ConfigSweep, DatasetShrinker, DifferentialFailure,
+    DifferentialResult, DifferentialRunner, DirectorySnapshot, EdgeCase, EdgeCaseInventory,
+    EdgeCaseKind, ExecutionLog, FailureArtifacts, FsEvent, FsEventScript, OpTrace, RetrievalEval,
+    RetrievalPoint, RetrievalReport, RetryOutcome, RetryPolicy, RetryStop, ShrinkResult,
+    SoakRunner, SoakStop, StressBudget, StressResult, SweepObjective, SweepReport, TestHarness,
+    ThresholdCalibration, TimeoutResult, VirtualClock,
+};
+#[cfg(feature = "large-scale")]
+pub use harness::{ScaleOutcome, ScaleTest, ScaleTestReport};
+pub use integrity::{
+    assert_indices_within, assert_sparse_eq, assert_sparse_similar, checksum_file_chunked,
+    compare_directories, compare_directories_tolerant, compare_files, compare_metadata,
+    find_zero_runs, hexdiff, identify_extracted_file, summarize_runs, sweep_pack_roundtrip,
+    validate_pack_roundtrip, validate_similarity_preservation, AlgebraFuzzer, CheckOutcome,
+    CheckRecord, ChecksumManifest, ChunkedChecksum, CollectingDiagnostics, CompareOptions,
+    Diagnostic, DiagnosticSink, FileIdentity, FileMismatch, FuzzOp, FuzzTrace, IntegrityReport,
+    IntegrityValidator, MismatchKind, RollingChecksum, StderrDiagnostics, ZeroRun, ZeroRunSummary,
+};
+pub use metrics::{
+    compare_samples, AccuracyMetrics, BudgetViolation, ComparisonResult, DensityStats,
+    EnvironmentInfo, Histogram, HistogramBucket, HistogramSpec, IndexUsage, LatencyBudget,
+    MemoryProbe, MetricsCheckpoint, MetricsDelta, SharedMetrics, StabilityReport, TestMetrics,
+    TimingGuard, TimingStats, VsaEvaluationMetrics,
 };
-pub use harness::TestHarness;
-pub use integrity::{IntegrityReport, IntegrityValidator};
-pub use metrics::{AccuracyMetrics, TestMetrics, TimingStats, VsaEvaluationMetrics};
+pub use progress::{NoopSink, ProgressOptions, ProgressSink};
 
 // Re-export VSA types for integration tests
 pub use embeddenator_vsa::{SparseVec, SparsityScaling, VsaConfig, VsaConfigSchema, DIM};
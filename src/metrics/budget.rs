@@ -0,0 +1,206 @@
+//! Latency budgets checked against [`TestMetrics`](super::TestMetrics) timings
+//!
+//! A [`LatencyBudget`] bounds the mean, p95, p99 and max of a metric's
+//! samples. [`TestMetrics::assert_budget`](super::TestMetrics::assert_budget)
+//! reports every breached bound at once, so a failing test names what was
+//! slow and by how much. Budgets can be stored in a
+//! [`Baseline`](super::report::Baseline) file next to the values they guard.
+
+use super::{format, TimingStats};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Upper bounds on timing statistics; unset bounds are not checked
+///
+/// Bounds are stored in nanoseconds, matching [`TimingStats`]. When loaded
+/// from JSON a bound may also be a string such as `"200µs"` or `"5ms"`
+/// (see [`format::parse_duration_ns`]).
+///
+/// # Example
+/// ```rust,ignore
+/// let budget = LatencyBudget::new()
+///     .with_p95(Duration::from_micros(200))
+///     .with_max(Duration::from_millis(5));
+/// metrics.assert_budget(&budget).unwrap_or_else(|v| panic!("{}", v));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyBudget {
+    /// Bound on the mean
+    #[serde(
+        default,
+        deserialize_with = "deserialize_bound",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mean_ns: Option<u64>,
+    /// Bound on the 95th percentile
+    #[serde(
+        default,
+        deserialize_with = "deserialize_bound",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub p95_ns: Option<u64>,
+    /// Bound on the 99th percentile
+    #[serde(
+        default,
+        deserialize_with = "deserialize_bound",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub p99_ns: Option<u64>,
+    /// Bound on the slowest sample
+    #[serde(
+        default,
+        deserialize_with = "deserialize_bound",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_ns: Option<u64>,
+}
+
+impl LatencyBudget {
+    /// Budget without bounds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bound the mean
+    pub fn with_mean(mut self, limit: Duration) -> Self {
+        self.mean_ns = Some(limit.as_nanos() as u64);
+        self
+    }
+
+    /// Bound the 95th percentile
+    pub fn with_p95(mut self, limit: Duration) -> Self {
+        self.p95_ns = Some(limit.as_nanos() as u64);
+        self
+    }
+
+    /// Bound the 99th percentile
+    pub fn with_p99(mut self, limit: Duration) -> Self {
+        self.p99_ns = Some(limit.as_nanos() as u64);
+        self
+    }
+
+    /// Bound the slowest sample
+    pub fn with_max(mut self, limit: Duration) -> Self {
+        self.max_ns = Some(limit.as_nanos() as u64);
+        self
+    }
+
+    /// Every bound `stats` exceeds, in mean, p95, p99, max order
+    pub fn breaches(&self, stats: &TimingStats) -> Vec<BudgetBreach> {
+        [
+            (BudgetBound::Mean, self.mean_ns, stats.mean_ns),
+            (BudgetBound::P95, self.p95_ns, stats.p95_ns as f64),
+            (BudgetBound::P99, self.p99_ns, stats.p99_ns as f64),
+            (BudgetBound::Max, self.max_ns, stats.max_ns as f64),
+        ]
+        .into_iter()
+        .filter_map(|(bound, allowed, measured)| {
+            let allowed_ns = allowed?;
+            (measured > allowed_ns as f64).then_some(BudgetBreach {
+                bound,
+                measured_ns: measured,
+                allowed_ns,
+            })
+        })
+        .collect()
+    }
+}
+
+/// A bound given as nanoseconds or as a duration string
+fn deserialize_bound<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bound {
+        Nanos(u64),
+        Text(String),
+    }
+    match Option::<Bound>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Bound::Nanos(ns)) => Ok(Some(ns)),
+        Some(Bound::Text(text)) => format::parse_duration_ns(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// Statistic bounded by a [`LatencyBudget`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BudgetBound {
+    /// Mean of all samples
+    Mean,
+    /// 95th percentile
+    P95,
+    /// 99th percentile
+    P99,
+    /// Slowest sample
+    Max,
+    /// A single call checked by
+    /// [`TestMetrics::record_and_check`](super::TestMetrics::record_and_check)
+    PerCall,
+}
+
+impl BudgetBound {
+    /// Short lowercase name
+    pub fn name(self) -> &'static str {
+        match self {
+            BudgetBound::Mean => "mean",
+            BudgetBound::P95 => "p95",
+            BudgetBound::P99 => "p99",
+            BudgetBound::Max => "max",
+            BudgetBound::PerCall => "per-call",
+        }
+    }
+}
+
+/// One bound exceeded by the measured value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BudgetBreach {
+    /// Which statistic was over budget
+    pub bound: BudgetBound,
+    /// Measured value in nanoseconds
+    pub measured_ns: f64,
+    /// Allowed value in nanoseconds
+    pub allowed_ns: u64,
+}
+
+/// Every bound a metric exceeded, with the number of samples measured
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetViolation {
+    /// Name of the [`TestMetrics`](super::TestMetrics) that was checked
+    pub name: String,
+    /// Samples the statistics were computed from
+    pub samples: usize,
+    /// Breached bounds, in mean, p95, p99, max order
+    pub breaches: Vec<BudgetBreach>,
+}
+
+impl BudgetViolation {
+    /// The breach of `bound`, if it was exceeded
+    pub fn breach(&self, bound: BudgetBound) -> Option<&BudgetBreach> {
+        self.breaches.iter().find(|b| b.bound == bound)
+    }
+}
+
+impl fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} over latency budget ({} samples):",
+            self.name, self.samples
+        )?;
+        for (i, breach) in self.breaches.iter().enumerate() {
+            write!(
+                f,
+                "{} {} {} > {} allowed",
+                if i == 0 { "" } else { "," },
+                breach.bound.name(),
+                format::duration_ns(breach.measured_ns.round() as u64),
+                format::duration_ns(breach.allowed_ns)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BudgetViolation {}
@@ -0,0 +1,119 @@
+//! Per-phase views of a [`TestMetrics`](super::TestMetrics) without resetting it
+//!
+//! [`TestMetrics::checkpoint`](super::TestMetrics::checkpoint) remembers how
+//! far every counter and sample list had grown;
+//! [`TestMetrics::delta_since`](super::TestMetrics::delta_since) then reports
+//! only what was added after that point.
+
+use super::{format, TimingStats};
+use std::collections::HashMap;
+
+/// Counter values and sample counts captured at one point in a run
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetricsCheckpoint {
+    pub(super) op_counts: HashMap<String, u64>,
+    pub(super) timing_samples: usize,
+    pub(super) phase_samples: HashMap<String, usize>,
+    pub(super) memory_samples: usize,
+    pub(super) error_count: u64,
+    pub(super) warning_count: u64,
+}
+
+/// Work recorded since a [`MetricsCheckpoint`]
+#[derive(Clone, Debug, Default)]
+pub struct MetricsDelta {
+    /// Name of the metrics the delta was taken from
+    pub name: String,
+    /// Operation count increase per category; unchanged categories are left out
+    pub op_counts: HashMap<String, u64>,
+    /// Statistics over timing samples added since the checkpoint
+    pub timing: TimingStats,
+    /// Statistics over phase samples added since the checkpoint, per phase
+    pub phases: HashMap<String, TimingStats>,
+    /// Largest memory sample added since the checkpoint
+    pub memory_peak: Option<usize>,
+    /// Errors recorded since the checkpoint
+    pub errors: u64,
+    /// Warnings recorded since the checkpoint
+    pub warnings: u64,
+}
+
+impl MetricsDelta {
+    /// Whether nothing was recorded since the checkpoint
+    pub fn is_empty(&self) -> bool {
+        self.op_counts.is_empty()
+            && self.timing.count == 0
+            && self.phases.is_empty()
+            && self.memory_peak.is_none()
+            && self.errors == 0
+            && self.warnings == 0
+    }
+
+    /// Operation count increase for `category` (0 if unchanged)
+    pub fn ops(&self, category: &str) -> u64 {
+        self.op_counts.get(category).copied().unwrap_or(0)
+    }
+
+    /// Human-readable report in the style of `TestMetrics::summary`
+    pub fn summary(&self) -> String {
+        let mut report = format!("=== {} Metrics (since checkpoint) ===\n", self.name);
+        if self.is_empty() {
+            report.push_str("No activity\n");
+            return report;
+        }
+
+        if self.timing.count > 0 {
+            report.push_str(&format!(
+                "Timing: {} ops, mean={}, p95={}, max={}\n",
+                format::count(self.timing.count as u64),
+                format::duration_ns(self.timing.mean_ns.round() as u64),
+                format::duration_ns(self.timing.p95_ns),
+                format::duration_ns(self.timing.max_ns),
+            ));
+        }
+
+        if !self.phases.is_empty() {
+            let mut phases: Vec<_> = self.phases.iter().collect();
+            phases.sort_by(|a, b| a.0.cmp(b.0));
+            report.push_str("Phases: ");
+            let phases: Vec<_> = phases
+                .into_iter()
+                .map(|(phase, stats)| {
+                    format!(
+                        "{}={}x{}",
+                        phase,
+                        stats.count,
+                        format::duration_ns(stats.mean_ns.round() as u64)
+                    )
+                })
+                .collect();
+            report.push_str(&phases.join(", "));
+            report.push('\n');
+        }
+
+        if !self.op_counts.is_empty() {
+            let mut ops: Vec<_> = self.op_counts.iter().collect();
+            ops.sort();
+            report.push_str("Operations: ");
+            let ops: Vec<_> = ops
+                .into_iter()
+                .map(|(k, v)| format!("{}=+{}", k, v))
+                .collect();
+            report.push_str(&ops.join(", "));
+            report.push('\n');
+        }
+
+        if let Some(peak) = self.memory_peak {
+            report.push_str(&format!("Memory: peak={}\n", format::bytes(peak as u64)));
+        }
+
+        if self.errors > 0 || self.warnings > 0 {
+            report.push_str(&format!(
+                "Issues: errors={}, warnings={}\n",
+                self.errors, self.warnings
+            ));
+        }
+
+        report
+    }
+}
@@ -0,0 +1,248 @@
+//! Significance testing between two sets of timing samples
+//!
+//! Uses the two-sided Mann-Whitney U test, which compares ranks rather than
+//! means, so a few outliers cannot manufacture or hide a difference. The
+//! p-value comes from the normal approximation with tie and continuity
+//! corrections; it is reliable from roughly 8 samples per side.
+
+use std::fmt;
+
+/// Significance level used unless [`ComparisonResult::with_alpha`] changes it
+pub const DEFAULT_ALPHA: f64 = 0.05;
+
+/// Outcome of a comparison, from the first sample set's point of view
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The first set is faster (smaller timings) at the chosen alpha
+    SignificantlyFaster,
+    /// The first set is slower (larger timings) at the chosen alpha
+    SignificantlySlower,
+    /// No difference detectable at the chosen alpha
+    NoSignificantDifference,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Verdict::SignificantlyFaster => "faster",
+            Verdict::SignificantlySlower => "SLOWER",
+            Verdict::NoSignificantDifference => "no significant difference",
+        })
+    }
+}
+
+/// Result of [`compare_samples`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComparisonResult {
+    /// Samples in the first set
+    pub a_count: usize,
+    /// Samples in the second set
+    pub b_count: usize,
+    /// Mann-Whitney U statistic of the first set
+    pub u_statistic: f64,
+    /// Two-sided p-value
+    pub p_value: f64,
+    /// Median of the first set over the median of the second (effect size)
+    ///
+    /// Below 1.0 the first set is faster.
+    pub median_ratio: f64,
+    /// Significance level the verdict was decided at
+    pub alpha: f64,
+    /// Direction of the difference, if significant
+    pub verdict: Verdict,
+}
+
+impl ComparisonResult {
+    /// Re-decide the verdict at significance level `alpha`
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        let mean_u = self.a_count as f64 * self.b_count as f64 / 2.0;
+        self.verdict = verdict(self.p_value, alpha, self.u_statistic, mean_u);
+        self
+    }
+
+    /// Whether the difference is significant at `alpha`
+    pub fn is_significant(&self) -> bool {
+        self.verdict != Verdict::NoSignificantDifference
+    }
+
+    /// One-line description
+    pub fn summary(&self) -> String {
+        format!(
+            "{} (median ratio {:.3}, p = {:.4}, alpha = {}, n = {}/{})",
+            self.verdict, self.median_ratio, self.p_value, self.alpha, self.a_count, self.b_count
+        )
+    }
+}
+
+/// Compare timing samples `a` against `b` with a Mann-Whitney U test
+///
+/// The verdict describes `a`: [`Verdict::SignificantlyFaster`] means `a`'s
+/// timings are stochastically smaller. An empty side yields a p-value of
+/// 1.0 and no significant difference.
+///
+/// # Example
+/// ```rust,ignore
+/// let result = compare_samples(&candidate.timings_ns, &baseline.timings_ns).with_alpha(0.01);
+/// assert_ne!(result.verdict, Verdict::SignificantlySlower, "{}", result.summary());
+/// ```
+pub fn compare_samples(a: &[u64], b: &[u64]) -> ComparisonResult {
+    let (n1, n2) = (a.len(), b.len());
+    let median_ratio = match (median(a), median(b)) {
+        (Some(ma), Some(mb)) if mb > 0.0 => ma / mb,
+        _ => 1.0,
+    };
+    if n1 == 0 || n2 == 0 {
+        return ComparisonResult {
+            a_count: n1,
+            b_count: n2,
+            u_statistic: 0.0,
+            p_value: 1.0,
+            median_ratio,
+            alpha: DEFAULT_ALPHA,
+            verdict: Verdict::NoSignificantDifference,
+        };
+    }
+
+    // Rank the pooled samples, giving ties their average rank
+    let mut pooled: Vec<(u64, bool)> = a
+        .iter()
+        .map(|&v| (v, true))
+        .chain(b.iter().map(|&v| (v, false)))
+        .collect();
+    pooled.sort_unstable_by_key(|&(v, _)| v);
+    let mut rank_sum_a = 0.0;
+    let mut tie_term = 0.0;
+    let mut i = 0;
+    while i < pooled.len() {
+        let j = i + pooled[i..]
+            .iter()
+            .take_while(|p| p.0 == pooled[i].0)
+            .count();
+        let average_rank = (i + j + 1) as f64 / 2.0;
+        let in_a = pooled[i..j].iter().filter(|p| p.1).count();
+        rank_sum_a += average_rank * in_a as f64;
+        let t = (j - i) as f64;
+        tie_term += t * t * t - t;
+        i = j;
+    }
+
+    let (n1f, n2f) = (n1 as f64, n2 as f64);
+    let n = n1f + n2f;
+    let u = rank_sum_a - n1f * (n1f + 1.0) / 2.0;
+    let mean_u = n1f * n2f / 2.0;
+    let variance = n1f * n2f / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    let p_value = if variance > 0.0 {
+        let z = ((u - mean_u).abs() - 0.5).max(0.0) / variance.sqrt();
+        (2.0 * (1.0 - standard_normal_cdf(z))).clamp(0.0, 1.0)
+    } else {
+        // Every sample identical
+        1.0
+    };
+
+    ComparisonResult {
+        a_count: n1,
+        b_count: n2,
+        u_statistic: u,
+        p_value,
+        median_ratio,
+        alpha: DEFAULT_ALPHA,
+        verdict: verdict(p_value, DEFAULT_ALPHA, u, mean_u),
+    }
+}
+
+/// Verdict at `alpha`; a U below its null mean means `a` ranks low (faster)
+fn verdict(p_value: f64, alpha: f64, u: f64, mean_u: f64) -> Verdict {
+    if p_value >= alpha {
+        Verdict::NoSignificantDifference
+    } else if u < mean_u {
+        Verdict::SignificantlyFaster
+    } else {
+        Verdict::SignificantlySlower
+    }
+}
+
+fn median(samples: &[u64]) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] as f64 + sorted[mid] as f64) / 2.0
+    } else {
+        sorted[mid] as f64
+    })
+}
+
+/// Standard normal CDF via the Abramowitz-Stegun 7.1.26 erf approximation
+///
+/// Absolute error below 1.5e-7.
+fn standard_normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn noisy(rng: &mut StdRng, center: u64, spread: u64, n: usize) -> Vec<u64> {
+        (0..n)
+            .map(|_| center - spread + rng.random_range(0..=2 * spread))
+            .collect()
+    }
+
+    #[test]
+    fn test_separated_distributions_are_significant() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let fast = noisy(&mut rng, 1_000, 100, 50);
+        let slow = noisy(&mut rng, 1_500, 100, 50);
+
+        let result = compare_samples(&fast, &slow);
+        assert_eq!(result.verdict, Verdict::SignificantlyFaster);
+        assert!(result.p_value < 1e-6, "{}", result.summary());
+        assert!((result.median_ratio - 1000.0 / 1500.0).abs() < 0.1);
+        assert_eq!(
+            compare_samples(&slow, &fast).verdict,
+            Verdict::SignificantlySlower
+        );
+    }
+
+    #[test]
+    fn test_overlapping_distributions_are_not() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let a = noisy(&mut rng, 1_000, 300, 40);
+        let b = noisy(&mut rng, 1_010, 300, 40);
+        let result = compare_samples(&a, &b);
+        assert_eq!(result.verdict, Verdict::NoSignificantDifference);
+        assert!(result.p_value > 0.05, "{}", result.summary());
+
+        // A lenient enough alpha turns any p-value below 1 into a verdict
+        assert!(result.clone().with_alpha(1.0).is_significant());
+
+        let identical = compare_samples(&[5; 10], &[5; 10]);
+        assert_eq!(identical.p_value, 1.0);
+        assert!(!compare_samples(&[], &a).is_significant());
+    }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!((standard_normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((standard_normal_cdf(1.959_964) - 0.975).abs() < 1e-6);
+        assert!((standard_normal_cdf(-1.959_964) - 0.025).abs() < 1e-6);
+    }
+}
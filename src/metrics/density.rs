@@ -0,0 +1,252 @@
+//! Non-zero count and density statistics for encoded vectors
+
+use super::{Histogram, HistogramSpec};
+use embeddenator_vsa::{ReversibleVSAConfig, SparseVec};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Non-zero counts of one observed vector
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DensitySample {
+    /// Positive entries
+    pub pos: usize,
+    /// Negative entries
+    pub neg: usize,
+}
+
+impl DensitySample {
+    /// Total non-zero entries
+    pub fn nnz(&self) -> usize {
+        self.pos + self.neg
+    }
+}
+
+/// Aggregates from [`DensityStats::summarize`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DensitySummary {
+    /// Vectors observed
+    pub count: usize,
+    /// Fewest non-zeros in one vector
+    pub min_nnz: usize,
+    /// Most non-zeros in one vector
+    pub max_nnz: usize,
+    /// Mean non-zeros per vector
+    pub mean_nnz: f64,
+    /// Standard deviation of non-zeros per vector
+    pub std_dev_nnz: f64,
+    /// Positive entries over all non-zeros (0.5 is balanced)
+    pub pos_fraction: f64,
+    /// Lowest density (`nnz / dims`), when dims are known
+    pub min_density: Option<f64>,
+    /// Mean density, when dims are known
+    pub mean_density: Option<f64>,
+    /// Highest density, when dims are known
+    pub max_density: Option<f64>,
+}
+
+/// Collects the sparsity the encoder actually produces
+///
+/// # Example
+/// ```rust,ignore
+/// let mut density = DensityStats::new().with_dims(DIM);
+/// density.observe_corpus(documents.iter(), &config);
+/// println!("{}", density.summary());
+/// let report = RunReport::new("encoder tuning").with_density("docs", &density);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DensityStats {
+    dims: Option<usize>,
+    samples: Vec<DensitySample>,
+}
+
+impl DensityStats {
+    /// Empty collector without a dimension, so densities are not computed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compute densities against `dims` dimensions
+    pub fn with_dims(mut self, dims: usize) -> Self {
+        self.dims = Some(dims);
+        self
+    }
+
+    /// Record one vector
+    pub fn observe(&mut self, v: &SparseVec) {
+        self.samples.push(DensitySample {
+            pos: v.pos.len(),
+            neg: v.neg.len(),
+        });
+    }
+
+    /// Encode each item with `config` and record the result
+    pub fn observe_corpus<I, D>(&mut self, items: I, config: &ReversibleVSAConfig)
+    where
+        I: IntoIterator<Item = D>,
+        D: AsRef<[u8]>,
+    {
+        for item in items {
+            self.observe(&SparseVec::encode_data(item.as_ref(), config, None));
+        }
+    }
+
+    /// Read, encode and record each file in `paths`
+    pub fn observe_files<P: AsRef<Path>>(
+        &mut self,
+        paths: impl IntoIterator<Item = P>,
+        config: &ReversibleVSAConfig,
+    ) -> io::Result<()> {
+        for path in paths {
+            let data = fs::read(path.as_ref())?;
+            self.observe(&SparseVec::encode_data(&data, config, None));
+        }
+        Ok(())
+    }
+
+    /// Per-vector counts in observation order
+    pub fn samples(&self) -> &[DensitySample] {
+        &self.samples
+    }
+
+    /// Min, max, mean and spread of the observed counts
+    pub fn summarize(&self) -> DensitySummary {
+        if self.samples.is_empty() {
+            return DensitySummary::default();
+        }
+        let nnz: Vec<usize> = self.samples.iter().map(DensitySample::nnz).collect();
+        let count = nnz.len() as f64;
+        let mean_nnz = nnz.iter().sum::<usize>() as f64 / count;
+        let variance = nnz
+            .iter()
+            .map(|&n| (n as f64 - mean_nnz).powi(2))
+            .sum::<f64>()
+            / count;
+        let min_nnz = *nnz.iter().min().unwrap();
+        let max_nnz = *nnz.iter().max().unwrap();
+        let pos: usize = self.samples.iter().map(|s| s.pos).sum();
+        let total: usize = nnz.iter().sum();
+        let density = |n: f64| self.dims.filter(|&d| d > 0).map(|d| n / d as f64);
+
+        DensitySummary {
+            count: nnz.len(),
+            min_nnz,
+            max_nnz,
+            mean_nnz,
+            std_dev_nnz: variance.sqrt(),
+            pos_fraction: if total == 0 {
+                0.0
+            } else {
+                pos as f64 / total as f64
+            },
+            min_density: density(min_nnz as f64),
+            mean_density: density(mean_nnz),
+            max_density: density(max_nnz as f64),
+        }
+    }
+
+    /// Histogram of non-zero counts
+    pub fn histogram(&self, spec: HistogramSpec) -> Histogram {
+        let nnz: Vec<u64> = self.samples.iter().map(|s| s.nnz() as u64).collect();
+        Histogram::from_samples(&nnz, spec)
+    }
+
+    /// Human-readable summary
+    pub fn summary(&self) -> String {
+        let s = self.summarize();
+        let mut out = format!(
+            "{} vectors, nnz min {} / mean {:.1} / max {} (std dev {:.1}), {:.1}% positive",
+            s.count,
+            s.min_nnz,
+            s.mean_nnz,
+            s.max_nnz,
+            s.std_dev_nnz,
+            s.pos_fraction * 100.0
+        );
+        if let (Some(min), Some(mean), Some(max)) = (s.min_density, s.mean_density, s.max_density) {
+            out.push_str(&format!(
+                ", density min {:.4} / mean {:.4} / max {:.4}",
+                min, mean, max
+            ));
+        }
+        out
+    }
+
+    /// One row per observed vector: `index,nnz,pos,neg,density`
+    ///
+    /// The density column is empty when no dimension was set.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("index,nnz,pos,neg,density\n");
+        for (i, s) in self.samples.iter().enumerate() {
+            let density = self
+                .dims
+                .filter(|&d| d > 0)
+                .map(|d| format!("{}", s.nnz() as f64 / d as f64))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                i,
+                s.nnz(),
+                s.pos,
+                s.neg,
+                density
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::special_vectors::{all_positive_upto, alternating, empty};
+
+    #[test]
+    fn test_known_nnz_vectors() {
+        let mut stats = DensityStats::new().with_dims(100);
+        stats.observe(&all_positive_upto(10));
+        stats.observe(&alternating(50));
+        stats.observe(&all_positive_upto(30));
+
+        let s = stats.summarize();
+        assert_eq!(s.count, 3);
+        assert_eq!((s.min_nnz, s.max_nnz), (10, 50));
+        assert_eq!(s.mean_nnz, 30.0);
+        assert_eq!(s.min_density, Some(0.1));
+        assert_eq!(s.mean_density, Some(0.3));
+        assert_eq!(s.max_density, Some(0.5));
+        // 10 + 25 + 30 positive out of 90
+        assert_eq!(s.pos_fraction, 65.0 / 90.0);
+        assert_eq!(
+            stats
+                .histogram(HistogramSpec::Linear { buckets: 4 })
+                .total(),
+            3
+        );
+
+        let csv = stats.to_csv();
+        assert_eq!(csv.lines().count(), 4);
+        assert_eq!(csv.lines().nth(2), Some("1,50,25,25,0.5"));
+
+        let mut undimensioned = DensityStats::new();
+        undimensioned.observe(&empty());
+        assert_eq!(undimensioned.summarize().mean_density, None);
+        assert!(undimensioned.to_csv().ends_with("0,0,0,0,\n"));
+        assert_eq!(DensityStats::new().summarize(), DensitySummary::default());
+    }
+
+    #[test]
+    fn test_observe_corpus_encodes_items() {
+        let config = ReversibleVSAConfig::default();
+        let mut stats = DensityStats::new();
+        let items = [b"alpha".to_vec(), b"beta gamma".to_vec()];
+        stats.observe_corpus(items.iter(), &config);
+        assert_eq!(stats.samples().len(), 2);
+
+        let expected = SparseVec::encode_data(b"alpha", &config, None);
+        assert_eq!(
+            stats.samples()[0].nnz(),
+            expected.pos.len() + expected.neg.len()
+        );
+    }
+}
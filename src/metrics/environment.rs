@@ -0,0 +1,244 @@
+//! Machine and build description for reproducible performance numbers
+
+use super::MemoryProbe;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// Environment variables that identify a CI provider, checked in order
+const CI_MARKERS: [(&str, &str); 8] = [
+    ("GITHUB_ACTIONS", "github-actions"),
+    ("GITLAB_CI", "gitlab"),
+    ("BUILDKITE", "buildkite"),
+    ("CIRCLECI", "circleci"),
+    ("TRAVIS", "travis"),
+    ("JENKINS_URL", "jenkins"),
+    ("TF_BUILD", "azure-pipelines"),
+    ("CI", "unknown"),
+];
+
+/// Where and how a test run was executed
+///
+/// Every field that depends on the platform is optional; [`capture`]
+/// leaves it `None` rather than failing when the information is missing.
+///
+/// [`capture`]: EnvironmentInfo::capture
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentInfo {
+    /// CPU model name
+    pub cpu_model: Option<String>,
+    /// Logical CPUs available to this process
+    pub cpu_cores: Option<usize>,
+    /// Total physical memory in bytes
+    pub total_memory_bytes: Option<u64>,
+    /// Operating system family, e.g. `linux`
+    pub os: String,
+    /// Distribution or OS release name
+    pub os_version: Option<String>,
+    /// Kernel release
+    pub kernel_version: Option<String>,
+    /// Compiler that built the testkit
+    pub rustc_version: Option<String>,
+    /// Testkit crate version
+    pub crate_version: String,
+    /// Target triple the testkit was built for
+    pub target_triple: String,
+    /// Whether the process appears to run inside a container
+    pub in_container: bool,
+    /// Detected CI provider, if any
+    pub ci: Option<String>,
+}
+
+impl EnvironmentInfo {
+    /// Describe the current machine and build
+    pub fn capture() -> Self {
+        Self {
+            cpu_model: cpu_model(),
+            cpu_cores: std::thread::available_parallelism().ok().map(|n| n.get()),
+            total_memory_bytes: MemoryProbe::system_memory()
+                .map(|m| m.total_bytes)
+                .or_else(|| sysctl("hw.memsize").and_then(|v| v.parse().ok())),
+            os: std::env::consts::OS.to_string(),
+            os_version: os_version(),
+            kernel_version: kernel_version(),
+            rustc_version: option_env!("TESTKIT_RUSTC_VERSION").map(str::to_string),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            target_triple: env!("TESTKIT_TARGET").to_string(),
+            in_container: in_container(),
+            ci: CI_MARKERS
+                .iter()
+                .find(|(var, _)| std::env::var_os(var).is_some())
+                .map(|(_, name)| name.to_string()),
+        }
+    }
+
+    /// Hardware differences between `self` and `other`, one line each
+    ///
+    /// Compares CPU model and core count, total memory, target triple and
+    /// containerization. Fields unknown on either side are not compared.
+    pub fn hardware_differences(&self, other: &EnvironmentInfo) -> Vec<String> {
+        fn differs<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+            matches!((a, b), (Some(a), Some(b)) if a != b)
+        }
+
+        let mut out = Vec::new();
+        if differs(&self.cpu_model, &other.cpu_model) {
+            out.push(format!(
+                "CPU model: {} vs {}",
+                self.cpu_model.as_deref().unwrap_or_default(),
+                other.cpu_model.as_deref().unwrap_or_default()
+            ));
+        }
+        if differs(&self.cpu_cores, &other.cpu_cores) {
+            out.push(format!(
+                "CPU cores: {} vs {}",
+                self.cpu_cores.unwrap_or_default(),
+                other.cpu_cores.unwrap_or_default()
+            ));
+        }
+        if differs(&self.total_memory_bytes, &other.total_memory_bytes) {
+            out.push(format!(
+                "Total memory: {:.1} GB vs {:.1} GB",
+                self.total_memory_bytes.unwrap_or_default() as f64 / 1e9,
+                other.total_memory_bytes.unwrap_or_default() as f64 / 1e9
+            ));
+        }
+        if self.target_triple != other.target_triple {
+            out.push(format!(
+                "Target: {} vs {}",
+                self.target_triple, other.target_triple
+            ));
+        }
+        if self.in_container != other.in_container {
+            out.push(format!(
+                "Container: {} vs {}",
+                self.in_container, other.in_container
+            ));
+        }
+        out
+    }
+
+    /// One-line description, e.g. for report headers
+    pub fn summary(&self) -> String {
+        let mut parts = vec![self.target_triple.clone()];
+        if let Some(cpu) = &self.cpu_model {
+            parts.push(cpu.clone());
+        }
+        if let Some(cores) = self.cpu_cores {
+            parts.push(format!("{} cores", cores));
+        }
+        if let Some(mem) = self.total_memory_bytes {
+            parts.push(format!("{:.1} GB RAM", mem as f64 / 1e9));
+        }
+        if let Some(os) = self.os_version.as_ref().or(self.kernel_version.as_ref()) {
+            parts.push(os.clone());
+        }
+        if let Some(rustc) = &self.rustc_version {
+            parts.push(rustc.clone());
+        }
+        parts.push(format!("testkit {}", self.crate_version));
+        if self.in_container {
+            parts.push("container".to_string());
+        }
+        if let Some(ci) = &self.ci {
+            parts.push(format!("CI: {}", ci));
+        }
+        parts.join(", ")
+    }
+}
+
+fn cpu_model() -> Option<String> {
+    if let Ok(cpuinfo) = std::fs::read_to_string("/proc/cpuinfo") {
+        // x86 reports "model name"; many ARM kernels only report "Hardware" or "Model"
+        for key in ["model name", "Hardware", "Model"] {
+            let model = cpuinfo.lines().find_map(|line| {
+                let (k, v) = line.split_once(':')?;
+                (k.trim() == key).then(|| v.trim().to_string())
+            });
+            if model.is_some() {
+                return model;
+            }
+        }
+    }
+    sysctl("machdep.cpu.brand_string")
+}
+
+fn os_version() -> Option<String> {
+    if let Ok(release) = std::fs::read_to_string("/etc/os-release") {
+        return release.lines().find_map(|line| {
+            line.strip_prefix("PRETTY_NAME=")
+                .map(|v| v.trim_matches('"').to_string())
+        });
+    }
+    command_output("sw_vers", &["-productVersion"]).map(|v| format!("macOS {}", v))
+}
+
+fn kernel_version() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/osrelease")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .or_else(|| command_output("uname", &["-r"]))
+}
+
+fn in_container() -> bool {
+    if std::path::Path::new("/.dockerenv").exists()
+        || std::path::Path::new("/run/.containerenv").exists()
+        || std::env::var_os("KUBERNETES_SERVICE_HOST").is_some()
+    {
+        return true;
+    }
+    std::fs::read_to_string("/proc/1/cgroup").is_ok_and(|cgroup| {
+        ["docker", "kubepods", "containerd", "lxc", "podman"]
+            .iter()
+            .any(|marker| cgroup.contains(marker))
+    })
+}
+
+fn sysctl(name: &str) -> Option<String> {
+    if cfg!(any(target_os = "macos", target_os = "freebsd")) {
+        command_output("sysctl", &["-n", name])
+    } else {
+        None
+    }
+}
+
+/// Trimmed stdout of a successful command
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_populates_mandatory_fields() {
+        let env = EnvironmentInfo::capture();
+        assert!(!env.target_triple.is_empty());
+        assert_eq!(env.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(env.os, std::env::consts::OS);
+        assert!(env.cpu_cores.is_some_and(|n| n > 0));
+
+        let json = serde_json::to_string(&env).unwrap();
+        assert_eq!(serde_json::from_str::<EnvironmentInfo>(&json).unwrap(), env);
+        assert!(env.summary().contains(&env.target_triple));
+    }
+
+    #[test]
+    fn test_hardware_differences() {
+        let a = EnvironmentInfo::capture();
+        assert!(a.hardware_differences(&a).is_empty());
+
+        let mut b = a.clone();
+        b.cpu_cores = Some(a.cpu_cores.unwrap_or(1) + 4);
+        b.cpu_model = None; // unknown fields are not compared
+        b.rustc_version = Some("rustc 0.0.0".to_string()); // not hardware
+        let diffs = a.hardware_differences(&b);
+        assert_eq!(diffs.len(), 1);
+        assert!(diffs[0].starts_with("CPU cores"));
+    }
+}
@@ -0,0 +1,220 @@
+//! Human-readable sizes, rates, durations and counts
+//!
+//! Every report in the crate formats through these helpers so units read
+//! the same everywhere: binary byte units (`KiB`, `MiB`, ...), durations
+//! from `ns` up to `s`, three significant figures above the base unit and
+//! a space before the unit. [`parse_bytes`] and [`parse_duration_ns`] read
+//! the same notation back, e.g. for hand-written budget and baseline files.
+//!
+//! ```rust,ignore
+//! assert_eq!(format::bytes(1536), "1.50 KiB");
+//! assert_eq!(format::duration_ns(1_250_000), "1.25 ms");
+//! assert_eq!(format::count(1_234_567), "1,234,567");
+//! assert_eq!(format::parse_bytes("1.5GiB"), Ok(1_610_612_736));
+//! ```
+
+/// Binary byte units, each 1024 times the previous
+const BYTE_UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Duration units and their length in nanoseconds
+const DURATION_UNITS: [(&str, f64); 4] = [("ns", 1.0), ("µs", 1e3), ("ms", 1e6), ("s", 1e9)];
+
+/// Byte count in the largest binary unit below 1024, e.g. `1.50 KiB`
+///
+/// Values under 1 KiB are printed exactly (`999 B`).
+pub fn bytes(n: u64) -> String {
+    scaled_bytes(n as f64)
+}
+
+/// Transfer rate in binary units per second, e.g. `39.5 MiB/s`
+pub fn bytes_per_sec(rate: f64) -> String {
+    format!("{}/s", scaled_bytes(rate))
+}
+
+/// Duration in the largest unit from `ns` to `s` that keeps it at least 1
+///
+/// Nanoseconds are printed exactly (`999 ns`); larger units with three
+/// significant figures (`1.00 µs`, `15.4 ms`, `250 s`).
+pub fn duration_ns(ns: u64) -> String {
+    let mut unit = 0;
+    while unit + 1 < DURATION_UNITS.len() && ns as f64 >= DURATION_UNITS[unit + 1].1 {
+        unit += 1;
+    }
+    if unit == 0 {
+        return format!("{} ns", ns);
+    }
+    let mut value = ns as f64 / DURATION_UNITS[unit].1;
+    // 999.6 µs would print as "1000 µs"; move it up a unit instead
+    if value.round() >= 1000.0 && unit + 1 < DURATION_UNITS.len() {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{} {}", three_significant(value), DURATION_UNITS[unit].0)
+}
+
+/// Integer with comma thousands separators, e.g. `1,234,567`
+pub fn count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Parse a byte size such as `1536`, `512 B`, `1.5GiB` or `2 MB`
+///
+/// Binary units (`KiB`..`EiB`) are powers of 1024, SI units (`KB`..`EB`)
+/// powers of 1000; a bare number is bytes. Units are case-insensitive.
+pub fn parse_bytes(text: &str) -> Result<u64, String> {
+    let (value, unit) = split_number(text)?;
+    let multiplier = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "kib" => 1024f64,
+        "mib" => 1024f64.powi(2),
+        "gib" => 1024f64.powi(3),
+        "tib" => 1024f64.powi(4),
+        "pib" => 1024f64.powi(5),
+        "eib" => 1024f64.powi(6),
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        "pb" => 1e15,
+        "eb" => 1e18,
+        _ => return Err(format!("unknown byte unit {:?} in {:?}", unit, text)),
+    };
+    to_u64(value * multiplier, text)
+}
+
+/// Parse a duration such as `250ns`, `1.5 µs`, `1.5us`, `20ms` or `2 s`
+/// into nanoseconds
+///
+/// A bare number is nanoseconds.
+pub fn parse_duration_ns(text: &str) -> Result<u64, String> {
+    let (value, unit) = split_number(text)?;
+    let nanos = match unit {
+        "" | "ns" => 1.0,
+        "µs" | "us" => 1e3,
+        "ms" => 1e6,
+        "s" => 1e9,
+        _ => return Err(format!("unknown duration unit {:?} in {:?}", unit, text)),
+    };
+    to_u64(value * nanos, text)
+}
+
+fn scaled_bytes(n: f64) -> String {
+    let mut value = n;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < BYTE_UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        return format!("{:.0} B", value);
+    }
+    // 1023.7 KiB would print as "1024 KiB"; move it up a unit instead
+    if value.round() >= 1024.0 && unit + 1 < BYTE_UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{} {}", three_significant(value), BYTE_UNITS[unit])
+}
+
+/// `value` (at least 1) with three significant figures, or all its integer
+/// digits when it has more than three
+fn three_significant(value: f64) -> String {
+    let decimals = if value < 9.995 {
+        2
+    } else if value < 99.95 {
+        1
+    } else {
+        0
+    };
+    format!("{:.*}", decimals, value)
+}
+
+/// Split `text` into its leading number and the trimmed unit after it
+fn split_number(text: &str) -> Result<(f64, &str), String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("expected a number in {:?}", text))?;
+    Ok((value, unit.trim()))
+}
+
+fn to_u64(value: f64, text: &str) -> Result<u64, String> {
+    if value > u64::MAX as f64 {
+        return Err(format!("{:?} does not fit in 64 bits", text));
+    }
+    Ok(value.round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundary_values_format_exactly() {
+        let sizes: Vec<String> = [0, 999, 1000, 1023, 1024, 1536, 10 * 1024 + 512]
+            .into_iter()
+            .map(bytes)
+            .collect();
+        assert_eq!(
+            sizes,
+            ["0 B", "999 B", "1000 B", "1023 B", "1.00 KiB", "1.50 KiB", "10.5 KiB"]
+        );
+        assert_eq!(bytes(1024 * 1024 - 1), "1.00 MiB");
+        assert_eq!(bytes(3 << 30), "3.00 GiB");
+        assert_eq!(bytes_per_sec(1536.0), "1.50 KiB/s");
+        assert_eq!(bytes_per_sec(40.0 * 1024.0 * 1024.0), "40.0 MiB/s");
+
+        let durations: Vec<String> = [999, 1000, 1024, 1536, 999_999, 150_000_000]
+            .into_iter()
+            .map(duration_ns)
+            .collect();
+        assert_eq!(
+            durations,
+            ["999 ns", "1.00 µs", "1.02 µs", "1.54 µs", "1.00 ms", "150 ms"]
+        );
+        assert_eq!(duration_ns(2_500_000_000_000), "2500 s");
+
+        let counts: Vec<String> = [0, 999, 1000, 1024, 1536, 1_234_567]
+            .into_iter()
+            .map(count)
+            .collect();
+        assert_eq!(counts, ["0", "999", "1,000", "1,024", "1,536", "1,234,567"]);
+    }
+
+    #[test]
+    fn test_parsers_round_trip_formatting() {
+        assert_eq!(parse_bytes("1.5GiB"), Ok(1_610_612_736));
+        assert_eq!(parse_bytes(" 2 MB "), Ok(2_000_000));
+        assert_eq!(parse_bytes("1536"), Ok(1536));
+        assert_eq!(parse_bytes("4kib"), Ok(4096));
+        for n in [0, 999, 1000, 1024, 1536, 3 << 30] {
+            assert_eq!(parse_bytes(&bytes(n)), Ok(n), "{}", bytes(n));
+        }
+
+        assert_eq!(parse_duration_ns("1.5us"), Ok(1_500));
+        assert_eq!(parse_duration_ns("20ms"), Ok(20_000_000));
+        assert_eq!(parse_duration_ns("250"), Ok(250));
+        for ns in [999, 1000, 1500, 2_000_000, 150_000_000] {
+            assert_eq!(parse_duration_ns(&duration_ns(ns)), Ok(ns), "{}", ns);
+        }
+
+        assert!(parse_bytes("12 parsecs")
+            .unwrap_err()
+            .contains("unknown byte unit"));
+        assert!(parse_duration_ns("ms")
+            .unwrap_err()
+            .contains("expected a number"));
+    }
+}
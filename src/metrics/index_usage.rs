@@ -0,0 +1,351 @@
+//! Per-index hit counts across a vector corpus
+//!
+//! An unbiased encoder spreads non-zeros evenly over all dimensions; heavy
+//! reuse of a few indices shows up here as a high imbalance coefficient.
+
+use embeddenator_vsa::SparseVec;
+
+/// Hit counts over a contiguous index range, from [`IndexUsage::buckets`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UsageBucket {
+    /// First index in the bucket
+    pub start: usize,
+    /// One past the last index
+    pub end: usize,
+    /// Positive hits in the range
+    pub pos: u64,
+    /// Negative hits in the range
+    pub neg: u64,
+}
+
+impl UsageBucket {
+    /// Hits of either sign
+    pub fn total(&self) -> u64 {
+        self.pos + self.neg
+    }
+}
+
+/// Aggregates from [`IndexUsage::summarize`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexUsageSummary {
+    /// Dimensions tracked
+    pub dims: usize,
+    /// Vectors observed
+    pub vectors: u64,
+    /// Non-zeros observed within `dims`
+    pub total_hits: u64,
+    /// Indices hit at least once
+    pub used_indices: usize,
+    /// Most used index and its hits
+    pub max: Option<(usize, u64)>,
+    /// Least used index and its hits
+    pub min: Option<(usize, u64)>,
+    /// Gini coefficient of per-index hits (0 even, near 1 concentrated)
+    pub gini: f64,
+    /// Non-zeros at indices `>= dims`, which are not counted
+    pub out_of_range: u64,
+}
+
+/// Accumulates how often each index is used, per sign
+///
+/// Counters are dense `u32` arrays of length `dims`, allocated on the first
+/// observation, so an unused collector costs nothing. Collectors built on
+/// different threads can be combined with [`merge`](Self::merge).
+///
+/// # Example
+/// ```rust,ignore
+/// let mut usage = IndexUsage::new(DIM);
+/// usage.observe_many(&encoded);
+/// println!("{}", usage.summary());
+/// std::fs::write("usage.csv", usage.to_csv(100))?;
+/// let report = RunReport::new("encoder bias").with_index_usage("docs", &usage);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexUsage {
+    dims: usize,
+    pos: Vec<u32>,
+    neg: Vec<u32>,
+    vectors: u64,
+    out_of_range: u64,
+}
+
+impl IndexUsage {
+    /// Collector for indices `0..dims`
+    pub fn new(dims: usize) -> Self {
+        Self {
+            dims,
+            ..Self::default()
+        }
+    }
+
+    /// Dimensions tracked
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// Vectors observed
+    pub fn vectors(&self) -> u64 {
+        self.vectors
+    }
+
+    /// Count the non-zeros of `v`
+    ///
+    /// Indices outside `0..dims` are tallied separately in
+    /// [`IndexUsageSummary::out_of_range`].
+    pub fn observe(&mut self, v: &SparseVec) {
+        self.allocate();
+        self.vectors += 1;
+        for (indices, counts) in [(&v.pos, &mut self.pos), (&v.neg, &mut self.neg)] {
+            for &i in indices {
+                match counts.get_mut(i) {
+                    Some(count) => *count = count.saturating_add(1),
+                    None => self.out_of_range += 1,
+                }
+            }
+        }
+    }
+
+    /// Count every vector in `vecs`
+    pub fn observe_many<'a>(&mut self, vecs: impl IntoIterator<Item = &'a SparseVec>) {
+        for v in vecs {
+            self.observe(v);
+        }
+    }
+
+    /// Add the counts of `other`, growing to its dimension if larger
+    pub fn merge(&mut self, other: &IndexUsage) {
+        self.dims = self.dims.max(other.dims);
+        self.vectors += other.vectors;
+        self.out_of_range += other.out_of_range;
+        if other.pos.is_empty() {
+            return;
+        }
+        self.pos.resize(self.dims, 0);
+        self.neg.resize(self.dims, 0);
+        for (mine, theirs) in [(&mut self.pos, &other.pos), (&mut self.neg, &other.neg)] {
+            for (a, &b) in mine.iter_mut().zip(theirs) {
+                *a = a.saturating_add(b);
+            }
+        }
+    }
+
+    /// Positive and negative hits at `index`
+    pub fn hits(&self, index: usize) -> (u32, u32) {
+        (
+            self.pos.get(index).copied().unwrap_or(0),
+            self.neg.get(index).copied().unwrap_or(0),
+        )
+    }
+
+    /// The `n` most used indices with their hits, most used first
+    ///
+    /// Ties are broken by lower index.
+    pub fn most_used(&self, n: usize) -> Vec<(usize, u64)> {
+        let mut totals = self.totals();
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        totals.truncate(n);
+        totals
+    }
+
+    /// The `n` least used indices with their hits, least used first
+    ///
+    /// Ties are broken by lower index.
+    pub fn least_used(&self, n: usize) -> Vec<(usize, u64)> {
+        let mut totals = self.totals();
+        totals.sort_by_key(|&(i, hits)| (hits, i));
+        totals.truncate(n);
+        totals
+    }
+
+    /// Gini coefficient of per-index hits
+    ///
+    /// 0.0 when every index is hit equally often (or nothing was observed),
+    /// approaching 1.0 as hits concentrate on a single index.
+    pub fn gini(&self) -> f64 {
+        let mut totals: Vec<u64> = self.totals().into_iter().map(|(_, hits)| hits).collect();
+        let sum: u64 = totals.iter().sum();
+        if sum == 0 {
+            return 0.0;
+        }
+        totals.sort_unstable();
+        let n = totals.len() as f64;
+        let weighted: f64 = totals
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| (i + 1) as f64 * x as f64)
+            .sum();
+        2.0 * weighted / (n * sum as f64) - (n + 1.0) / n
+    }
+
+    /// Hits summed over `count` contiguous index ranges of near-equal width
+    ///
+    /// Each row of a heatmap; fewer buckets are returned if `dims` is
+    /// smaller than `count`.
+    pub fn buckets(&self, count: usize) -> Vec<UsageBucket> {
+        let count = count.clamp(1, self.dims.max(1));
+        (0..count)
+            .map(|b| {
+                let start = b * self.dims / count;
+                let end = (b + 1) * self.dims / count;
+                let sum = |counts: &[u32]| {
+                    counts
+                        .get(start..end)
+                        .map_or(0, |range| range.iter().map(|&c| c as u64).sum())
+                };
+                UsageBucket {
+                    start,
+                    end,
+                    pos: sum(&self.pos),
+                    neg: sum(&self.neg),
+                }
+            })
+            .collect()
+    }
+
+    /// [`buckets`](Self::buckets) as CSV: `start,end,pos,neg,total`
+    pub fn to_csv(&self, buckets: usize) -> String {
+        let mut out = String::from("start,end,pos,neg,total\n");
+        for b in self.buckets(buckets) {
+            out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                b.start,
+                b.end,
+                b.pos,
+                b.neg,
+                b.total()
+            ));
+        }
+        out
+    }
+
+    /// Totals, extremes and imbalance
+    pub fn summarize(&self) -> IndexUsageSummary {
+        let totals = self.totals();
+        IndexUsageSummary {
+            dims: self.dims,
+            vectors: self.vectors,
+            total_hits: totals.iter().map(|&(_, hits)| hits).sum(),
+            used_indices: totals.iter().filter(|&&(_, hits)| hits > 0).count(),
+            max: self.most_used(1).first().copied(),
+            min: self.least_used(1).first().copied(),
+            gini: self.gini(),
+            out_of_range: self.out_of_range,
+        }
+    }
+
+    /// Human-readable summary
+    pub fn summary(&self) -> String {
+        let s = self.summarize();
+        let mut out = format!(
+            "{} vectors, {} hits over {}/{} indices, gini {:.3}",
+            s.vectors, s.total_hits, s.used_indices, s.dims, s.gini
+        );
+        if let (Some((max_index, max)), Some((min_index, min))) = (s.max, s.min) {
+            out.push_str(&format!(
+                ", max {} at {}, min {} at {}",
+                max, max_index, min, min_index
+            ));
+        }
+        if s.out_of_range > 0 {
+            out.push_str(&format!(", {} out of range", s.out_of_range));
+        }
+        out
+    }
+
+    /// Hits of either sign per index (all zeros before any observation)
+    fn totals(&self) -> Vec<(usize, u64)> {
+        (0..self.dims)
+            .map(|i| {
+                let (pos, neg) = self.hits(i);
+                (i, pos as u64 + neg as u64)
+            })
+            .collect()
+    }
+
+    fn allocate(&mut self) {
+        if self.pos.len() < self.dims {
+            self.pos.resize(self.dims, 0);
+            self.neg.resize(self.dims, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::random_sparse_vec;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_skewed_indices_raise_imbalance() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let uniform: Vec<SparseVec> = (0..500)
+            .map(|_| random_sparse_vec(&mut rng, 1000, 50))
+            .collect();
+        // Same sparsity, but half the non-zeros always land in 0..10
+        let skewed: Vec<SparseVec> = uniform
+            .iter()
+            .map(|v| SparseVec {
+                pos: (0..10).chain(v.pos.iter().copied().skip(10)).collect(),
+                neg: v.neg.clone(),
+            })
+            .collect();
+
+        let mut even = IndexUsage::new(1000);
+        even.observe_many(&uniform);
+        let mut biased = IndexUsage::new(1000);
+        biased.observe_many(&skewed);
+
+        assert!(even.gini() < 0.15, "{}", even.summary());
+        assert!(biased.gini() > even.gini() + 0.1, "{}", biased.summary());
+        assert_eq!(biased.hits(3).0, 500);
+        assert!(biased.most_used(10).iter().all(|&(i, _)| i < 10));
+        let (max_index, max_hits) = biased.summarize().max.unwrap();
+        assert!(max_index < 10 && max_hits >= 500);
+
+        let buckets = biased.buckets(10);
+        assert_eq!(buckets.len(), 10);
+        assert_eq!(
+            buckets[0],
+            UsageBucket {
+                start: 0,
+                end: 100,
+                ..buckets[0]
+            }
+        );
+        assert!(buckets[0].total() > buckets[5].total() * 2);
+        let total: u64 = buckets.iter().map(UsageBucket::total).sum();
+        assert_eq!(total, biased.summarize().total_hits);
+        assert_eq!(biased.to_csv(10).lines().count(), 11);
+    }
+
+    #[test]
+    fn test_lazy_allocation_and_merge() {
+        let mut a = IndexUsage::new(1 << 20);
+        assert!(a.pos.is_empty());
+        assert_eq!(a.gini(), 0.0);
+        assert_eq!(a.summarize().total_hits, 0);
+
+        let mut rng = StdRng::seed_from_u64(8);
+        let vecs: Vec<SparseVec> = (0..40)
+            .map(|_| random_sparse_vec(&mut rng, 1 << 20, 100))
+            .collect();
+        let mut whole = IndexUsage::new(1 << 20);
+        whole.observe_many(&vecs);
+
+        let mut b = IndexUsage::new(1 << 20);
+        a.observe_many(&vecs[..15]);
+        b.observe_many(&vecs[15..]);
+        a.merge(&b);
+        assert_eq!(a, whole);
+
+        let mut small = IndexUsage::new(4);
+        small.observe(&SparseVec {
+            pos: vec![1, 9],
+            neg: vec![2],
+        });
+        assert_eq!(small.summarize().out_of_range, 1);
+        assert_eq!(small.least_used(2), vec![(0, 0), (3, 0)]);
+    }
+}
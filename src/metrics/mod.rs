@@ -0,0 +1,1348 @@
+//! Performance metrics and timing utilities for testing
+//!
+//! Provides granular performance measurement tools including:
+//! - Operation timing with statistics (mean, median, percentiles)
+//! - Memory usage tracking
+//! - Preallocated sample buffers, checked for reallocation with `strict-metrics`
+//! - Throughput calculations
+//! - Custom metric recording
+//! - Linear and log2 latency histograms
+//! - Prometheus text exposition ([`prometheus`])
+//! - Markdown and HTML run reports ([`report`])
+//! - Machine and build description ([`EnvironmentInfo`])
+//! - Thread-safe collection from concurrent tests ([`SharedMetrics`])
+//! - CPU timing stability preflight ([`stability_probe`])
+//! - Mann-Whitney significance tests between runs ([`compare_samples`])
+//! - Non-zero count and density distributions of encoded vectors ([`DensityStats`])
+//! - Per-index usage and imbalance across a vector corpus ([`IndexUsage`])
+//! - Latency budgets over timing statistics ([`LatencyBudget`])
+//! - Deltas between checkpoints of one collector ([`MetricsCheckpoint`])
+//! - Nested profiling scopes rendered as trees or folded stacks ([`profile`])
+//! - Uniform size, rate, duration and count formatting ([`format`])
+
+mod budget;
+mod checkpoint;
+mod compare;
+mod density;
+mod environment;
+pub mod format;
+mod index_usage;
+pub mod profile;
+pub mod prometheus;
+pub mod report;
+mod shared;
+mod stability;
+
+pub use budget::{BudgetBound, BudgetBreach, BudgetViolation, LatencyBudget};
+pub use checkpoint::{MetricsCheckpoint, MetricsDelta};
+pub use compare::{compare_samples, ComparisonResult, Verdict, DEFAULT_ALPHA};
+pub use density::{DensitySample, DensityStats, DensitySummary};
+pub use environment::EnvironmentInfo;
+pub use index_usage::{IndexUsage, IndexUsageSummary, UsageBucket};
+pub use shared::SharedMetrics;
+pub use stability::{stability_probe, StabilityReport};
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Granular performance metrics for test operations
+///
+/// Clones keep the sample capacity of the original, so a clone of
+/// preallocated metrics is preallocated too.
+#[derive(Debug)]
+pub struct TestMetrics {
+    /// Operation name for reporting
+    pub name: String,
+    /// Individual timing samples (nanoseconds)
+    pub timings_ns: Vec<u64>,
+    /// Start time for current measurement
+    start: Option<Instant>,
+    /// Operation counts by category
+    pub op_counts: HashMap<String, u64>,
+    /// Custom numeric metrics
+    pub custom_metrics: HashMap<String, f64>,
+    /// Memory snapshots (bytes)
+    pub memory_samples: Vec<usize>,
+    /// Error/warning counts
+    pub error_count: u64,
+    pub warning_count: u64,
+    /// Timing samples per named phase (nanoseconds)
+    pub phase_timings_ns: HashMap<String, Vec<u64>>,
+    /// Histogram included in `summary()`, if enabled
+    histogram: Option<HistogramSpec>,
+    /// Whether samples were preallocated, arming the reallocation check
+    #[cfg(feature = "strict-metrics")]
+    preallocated: bool,
+    /// Sample capacities when the current measurement started
+    #[cfg(feature = "strict-metrics")]
+    window: Option<(usize, usize)>,
+}
+
+/// Copy of `samples` with the same capacity
+fn clone_with_capacity<T: Copy>(samples: &[T], capacity: usize) -> Vec<T> {
+    let mut copy = Vec::with_capacity(capacity);
+    copy.extend_from_slice(samples);
+    copy
+}
+
+impl Clone for TestMetrics {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            timings_ns: clone_with_capacity(&self.timings_ns, self.timings_ns.capacity()),
+            start: self.start,
+            op_counts: self.op_counts.clone(),
+            custom_metrics: self.custom_metrics.clone(),
+            memory_samples: clone_with_capacity(
+                &self.memory_samples,
+                self.memory_samples.capacity(),
+            ),
+            error_count: self.error_count,
+            warning_count: self.warning_count,
+            phase_timings_ns: self.phase_timings_ns.clone(),
+            histogram: self.histogram,
+            #[cfg(feature = "strict-metrics")]
+            preallocated: self.preallocated,
+            #[cfg(feature = "strict-metrics")]
+            window: self.window,
+        }
+    }
+}
+
+impl TestMetrics {
+    /// Create new metrics collector for named operation
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            timings_ns: Vec::new(),
+            start: None,
+            op_counts: HashMap::new(),
+            custom_metrics: HashMap::new(),
+            memory_samples: Vec::new(),
+            error_count: 0,
+            warning_count: 0,
+            phase_timings_ns: HashMap::new(),
+            histogram: None,
+            #[cfg(feature = "strict-metrics")]
+            preallocated: false,
+            #[cfg(feature = "strict-metrics")]
+            window: None,
+        }
+    }
+
+    /// Include a histogram of timing samples in `summary()`
+    pub fn with_histogram(mut self, spec: HistogramSpec) -> Self {
+        self.histogram = Some(spec);
+        self
+    }
+
+    /// Preallocate room for `samples` timing and memory samples
+    ///
+    /// Recording then never reallocates mid-benchmark until `samples` is
+    /// exceeded; see [`samples_capacity`](Self::samples_capacity). With the
+    /// `strict-metrics` feature, debug builds panic when a measurement on
+    /// preallocated metrics reallocates either sample vector.
+    pub fn with_capacity(mut self, samples: usize) -> Self {
+        self.reserve_samples(samples);
+        self
+    }
+
+    /// Make room for `n` more timing and memory samples
+    pub fn reserve_samples(&mut self, n: usize) {
+        self.timings_ns.reserve(n);
+        self.memory_samples.reserve(n);
+        #[cfg(feature = "strict-metrics")]
+        {
+            self.preallocated = true;
+        }
+    }
+
+    /// Samples that fit without reallocating either sample vector
+    pub fn samples_capacity(&self) -> usize {
+        self.timings_ns
+            .capacity()
+            .min(self.memory_samples.capacity())
+    }
+
+    /// Start timing measurement
+    #[inline]
+    pub fn start_timing(&mut self) {
+        self.open_window();
+        self.start = Some(Instant::now());
+    }
+
+    /// Stop timing and record sample
+    #[inline]
+    pub fn stop_timing(&mut self) {
+        if let Some(start) = self.start.take() {
+            self.push_timing(start.elapsed().as_nanos() as u64);
+        }
+    }
+
+    /// Remember sample capacities at the start of a measurement
+    #[inline]
+    fn open_window(&mut self) {
+        #[cfg(feature = "strict-metrics")]
+        if self.preallocated {
+            self.window = Some((self.timings_ns.capacity(), self.memory_samples.capacity()));
+        }
+    }
+
+    /// Record a timing sample, closing the measurement window
+    ///
+    /// With `strict-metrics`, debug builds panic if samples were preallocated
+    /// and either sample vector reallocated since
+    /// [`open_window`](Self::open_window).
+    #[inline]
+    fn push_timing(&mut self, ns: u64) {
+        self.timings_ns.push(ns);
+        #[cfg(feature = "strict-metrics")]
+        if let Some((timings, memory)) = self.window.take().filter(|_| !std::thread::panicking()) {
+            let now = (self.timings_ns.capacity(), self.memory_samples.capacity());
+            debug_assert!(
+                now == (timings, memory),
+                "{}: sample vectors reallocated during a measurement \
+                 (timing capacity {} -> {}, memory capacity {} -> {}); \
+                 preallocate with TestMetrics::with_capacity",
+                self.name,
+                timings,
+                now.0,
+                memory,
+                now.1
+            );
+        }
+    }
+
+    /// Record a timed operation with closure
+    #[inline]
+    pub fn time_operation<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.start_timing();
+        let result = f();
+        self.stop_timing();
+        result
+    }
+
+    /// Start a timing scope that records its sample when dropped
+    ///
+    /// The sample is recorded even if the scope ends by early return, `?` or
+    /// panic unwinding. The guard holds `&mut self`, so nested guards on the
+    /// same metrics are rejected at compile time.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// {
+    ///     let _guard = metrics.scoped();
+    ///     encode(&data)?;
+    /// } // sample recorded here
+    /// ```
+    #[inline]
+    pub fn scoped(&mut self) -> TimingGuard<'_> {
+        self.open_window();
+        TimingGuard {
+            metrics: self,
+            start: Instant::now(),
+        }
+    }
+
+    /// Time a fallible closure, counting `Err` results in `error_count`
+    #[inline]
+    pub fn time_result<F, T, E>(&mut self, f: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let result = {
+            let _guard = self.scoped();
+            f()
+        };
+        if result.is_err() {
+            self.record_error();
+        }
+        result
+    }
+
+    /// Time `f` as one sample of `phase`
+    ///
+    /// Samples are kept per phase (see [`phase_stats`](Self::phase_stats))
+    /// and each call also increments the `phase` operation counter.
+    pub fn time_phase<F, R>(&mut self, phase: &str, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed().as_nanos() as u64;
+        self.phase_timings_ns
+            .entry(phase.to_string())
+            .or_default()
+            .push(elapsed);
+        self.inc_op(phase);
+        result
+    }
+
+    /// Timing statistics for one phase (empty stats if never timed)
+    pub fn phase_stats(&self, phase: &str) -> TimingStats {
+        TimingStats::from_samples(self.phase_timings_ns.get(phase).map_or(&[], Vec::as_slice))
+    }
+
+    /// Increment operation counter
+    #[inline]
+    pub fn inc_op(&mut self, category: &str) {
+        *self.op_counts.entry(category.to_string()).or_insert(0) += 1;
+    }
+
+    /// Record custom metric
+    #[inline]
+    pub fn record_metric(&mut self, name: &str, value: f64) {
+        self.custom_metrics.insert(name.to_string(), value);
+    }
+
+    /// Record memory usage
+    #[inline]
+    pub fn record_memory(&mut self, bytes: usize) {
+        self.memory_samples.push(bytes);
+    }
+
+    /// Record operation count
+    #[inline]
+    pub fn record_operation(&mut self, count: usize) {
+        self.inc_op("operations");
+        self.record_metric("last_count", count as f64);
+    }
+
+    /// Record an error
+    #[inline]
+    pub fn record_error(&mut self) {
+        self.error_count += 1;
+    }
+
+    /// Record a warning
+    #[inline]
+    pub fn record_warning(&mut self) {
+        self.warning_count += 1;
+    }
+
+    /// Merge samples and counters from `other` into this collector
+    ///
+    /// Timings and memory samples are appended, operation counts and
+    /// error/warning counts are summed, and custom metrics from `other`
+    /// overwrite same-named entries.
+    pub fn merge(&mut self, other: &TestMetrics) {
+        self.timings_ns.extend_from_slice(&other.timings_ns);
+        for (category, count) in &other.op_counts {
+            *self.op_counts.entry(category.clone()).or_insert(0) += count;
+        }
+        for (name, value) in &other.custom_metrics {
+            self.custom_metrics.insert(name.clone(), *value);
+        }
+        self.memory_samples.extend_from_slice(&other.memory_samples);
+        self.error_count += other.error_count;
+        self.warning_count += other.warning_count;
+        for (phase, samples) in &other.phase_timings_ns {
+            self.phase_timings_ns
+                .entry(phase.clone())
+                .or_default()
+                .extend_from_slice(samples);
+        }
+    }
+
+    /// Capture the current counters and sample counts
+    ///
+    /// Pass the checkpoint to [`delta_since`](Self::delta_since) later to see
+    /// only the work recorded in between.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let cp = metrics.checkpoint();
+    /// run_compaction(&mut metrics);
+    /// println!("{}", metrics.summary_since(&cp));
+    /// ```
+    pub fn checkpoint(&self) -> MetricsCheckpoint {
+        MetricsCheckpoint {
+            op_counts: self.op_counts.clone(),
+            timing_samples: self.timings_ns.len(),
+            phase_samples: self
+                .phase_timings_ns
+                .iter()
+                .map(|(phase, samples)| (phase.clone(), samples.len()))
+                .collect(),
+            memory_samples: self.memory_samples.len(),
+            error_count: self.error_count,
+            warning_count: self.warning_count,
+        }
+    }
+
+    /// Work recorded since `cp` was taken
+    ///
+    /// Timing statistics cover only the samples appended after the
+    /// checkpoint. Counters that went backwards (e.g. a reset in between)
+    /// count as unchanged.
+    pub fn delta_since(&self, cp: &MetricsCheckpoint) -> MetricsDelta {
+        let new_samples = |samples: &[u64], seen: usize| {
+            TimingStats::from_samples(samples.get(seen..).unwrap_or(&[]))
+        };
+        MetricsDelta {
+            name: self.name.clone(),
+            op_counts: self
+                .op_counts
+                .iter()
+                .filter_map(|(category, &count)| {
+                    let before = cp.op_counts.get(category).copied().unwrap_or(0);
+                    let added = count.saturating_sub(before);
+                    (added > 0).then(|| (category.clone(), added))
+                })
+                .collect(),
+            timing: new_samples(&self.timings_ns, cp.timing_samples),
+            phases: self
+                .phase_timings_ns
+                .iter()
+                .filter_map(|(phase, samples)| {
+                    let seen = cp.phase_samples.get(phase).copied().unwrap_or(0);
+                    let stats = new_samples(samples, seen);
+                    (stats.count > 0).then(|| (phase.clone(), stats))
+                })
+                .collect(),
+            memory_peak: self
+                .memory_samples
+                .get(cp.memory_samples..)
+                .and_then(|samples| samples.iter().max().copied()),
+            errors: self.error_count.saturating_sub(cp.error_count),
+            warnings: self.warning_count.saturating_sub(cp.warning_count),
+        }
+    }
+
+    /// [`MetricsDelta::summary`] of the work recorded since `cp`
+    pub fn summary_since(&self, cp: &MetricsCheckpoint) -> String {
+        self.delta_since(cp).summary()
+    }
+
+    /// Get timing statistics
+    pub fn timing_stats(&self) -> TimingStats {
+        TimingStats::from_samples(&self.timings_ns)
+    }
+
+    /// Check the timing samples against `budget`
+    ///
+    /// Fails with every breached bound, not just the first. Without samples
+    /// every statistic is zero, so any budget passes.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let budget = baseline.budgets["encode"];
+    /// metrics.assert_budget(&budget).unwrap_or_else(|v| panic!("{}", v));
+    /// ```
+    pub fn assert_budget(&self, budget: &LatencyBudget) -> Result<(), BudgetViolation> {
+        let stats = self.timing_stats();
+        let breaches = budget.breaches(&stats);
+        if breaches.is_empty() {
+            Ok(())
+        } else {
+            Err(BudgetViolation {
+                name: self.name.clone(),
+                samples: stats.count,
+                breaches,
+            })
+        }
+    }
+
+    /// Time `f` as one sample and fail if that call took longer than `limit`
+    ///
+    /// The sample is recorded either way, so a later
+    /// [`assert_budget`](Self::assert_budget) still sees it.
+    pub fn record_and_check<F, R>(&mut self, limit: Duration, f: F) -> Result<R, BudgetViolation>
+    where
+        F: FnOnce() -> R,
+    {
+        self.open_window();
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed().as_nanos() as u64;
+        self.push_timing(elapsed);
+        let allowed_ns = limit.as_nanos() as u64;
+        if elapsed > allowed_ns {
+            return Err(BudgetViolation {
+                name: self.name.clone(),
+                samples: 1,
+                breaches: vec![BudgetBreach {
+                    bound: BudgetBound::PerCall,
+                    measured_ns: elapsed as f64,
+                    allowed_ns,
+                }],
+            });
+        }
+        Ok(result)
+    }
+
+    /// Test whether this run's timings differ significantly from `other`'s
+    ///
+    /// Runs [`compare_samples`] on the raw samples; the verdict describes
+    /// `self`, so `SignificantlySlower` means this run regressed.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let result = candidate.compare_with(&baseline);
+    /// let report = RunReport::new("PR check").with_comparison("encode", result);
+    /// ```
+    pub fn compare_with(&self, other: &TestMetrics) -> ComparisonResult {
+        compare_samples(&self.timings_ns, &other.timings_ns)
+    }
+
+    /// Histogram of the stored timing samples
+    pub fn histogram(&self, buckets: HistogramSpec) -> Histogram {
+        Histogram::from_samples(&self.timings_ns, buckets)
+    }
+
+    /// Generate summary report
+    pub fn summary(&self) -> String {
+        let stats = self.timing_stats();
+        crate::events::emit("metrics", "summary", || {
+            serde_json::json!({
+                "name": self.name,
+                "count": stats.count,
+                "mean_ns": stats.mean_ns,
+                "p99_ns": stats.p99_ns,
+                "errors": self.error_count,
+                "warnings": self.warning_count,
+            })
+        });
+        let mut report = format!("=== {} Metrics ===\n", self.name);
+
+        if stats.count > 0 {
+            report.push_str(&format!(
+                "Timing: {} ops, mean={}, p50={}, p95={}, p99={}\n",
+                format::count(stats.count as u64),
+                format::duration_ns(stats.mean_ns.round() as u64),
+                format::duration_ns(stats.p50_ns),
+                format::duration_ns(stats.p95_ns),
+                format::duration_ns(stats.p99_ns),
+            ));
+            report.push_str(&format!(
+                "        min={}, max={}, stddev={}\n",
+                format::duration_ns(stats.min_ns),
+                format::duration_ns(stats.max_ns),
+                format::duration_ns(stats.std_dev_ns.round() as u64),
+            ));
+        }
+
+        if let Some(spec) = self.histogram {
+            if stats.count > 0 {
+                report.push_str("Histogram:\n");
+                report.push_str(&self.histogram(spec).render_ascii(HISTOGRAM_SUMMARY_WIDTH));
+            }
+        }
+
+        if !self.phase_timings_ns.is_empty() {
+            let mut phases: Vec<_> = self.phase_timings_ns.keys().collect();
+            phases.sort();
+            report.push_str(&format!(
+                "Phases:\n  {:<16} {:>6} {:>12} {:>12} {:>12}\n",
+                "phase", "count", "mean", "p95", "total"
+            ));
+            let mut combined_count = 0;
+            let mut combined_ns = 0;
+            for phase in phases {
+                let stats = self.phase_stats(phase);
+                combined_count += stats.count;
+                combined_ns += stats.total_ns;
+                report.push_str(&format!(
+                    "  {:<16} {:>6} {:>12} {:>12} {:>12}\n",
+                    phase,
+                    stats.count,
+                    format::duration_ns(stats.mean_ns.round() as u64),
+                    format::duration_ns(stats.p95_ns),
+                    format::duration_ns(stats.total_ns),
+                ));
+            }
+            report.push_str(&format!(
+                "  {:<16} {:>6} {:>12} {:>12} {:>12}\n",
+                "total",
+                combined_count,
+                "",
+                "",
+                format::duration_ns(combined_ns)
+            ));
+        }
+
+        if !self.op_counts.is_empty() {
+            report.push_str("Operations: ");
+            let ops: Vec<_> = self
+                .op_counts
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect();
+            report.push_str(&ops.join(", "));
+            report.push('\n');
+        }
+
+        if !self.custom_metrics.is_empty() {
+            report.push_str("Metrics: ");
+            let metrics: Vec<_> = self
+                .custom_metrics
+                .iter()
+                .map(|(k, v)| format!("{}={:.4}", k, v))
+                .collect();
+            report.push_str(&metrics.join(", "));
+            report.push('\n');
+        }
+
+        if !self.memory_samples.is_empty() {
+            let max_mem = self.memory_samples.iter().max().unwrap_or(&0);
+            let avg_mem = self.memory_samples.iter().sum::<usize>() / self.memory_samples.len();
+            report.push_str(&format!(
+                "Memory: peak={}, avg={}\n",
+                format::bytes(*max_mem as u64),
+                format::bytes(avg_mem as u64),
+            ));
+        }
+
+        if self.error_count > 0 || self.warning_count > 0 {
+            report.push_str(&format!(
+                "Issues: errors={}, warnings={}\n",
+                self.error_count, self.warning_count
+            ));
+        }
+
+        report
+    }
+}
+
+/// System-wide memory figures
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SystemMemory {
+    /// Total physical memory in bytes
+    pub total_bytes: u64,
+    /// Memory available for new allocations without swapping, in bytes
+    pub available_bytes: u64,
+}
+
+impl SystemMemory {
+    /// Fraction of total memory currently available (0.0 - 1.0)
+    pub fn available_fraction(&self) -> f64 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            self.available_bytes as f64 / self.total_bytes as f64
+        }
+    }
+}
+
+/// Reads process and system memory usage
+///
+/// Backed by `/proc` on Linux; every query returns `None` on platforms where
+/// the information is unavailable.
+pub struct MemoryProbe;
+
+impl MemoryProbe {
+    /// Resident set size of the current process in bytes
+    pub fn current_rss() -> Option<usize> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        proc_kb_field(&status, "VmRSS:").map(|kb| kb as usize * 1024)
+    }
+
+    /// Total and available system memory
+    pub fn system_memory() -> Option<SystemMemory> {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        Some(SystemMemory {
+            total_bytes: proc_kb_field(&meminfo, "MemTotal:")? * 1024,
+            available_bytes: proc_kb_field(&meminfo, "MemAvailable:")? * 1024,
+        })
+    }
+
+    /// Record the current RSS into `metrics`, if available
+    pub fn sample(metrics: &mut TestMetrics) -> Option<usize> {
+        let rss = Self::current_rss()?;
+        metrics.record_memory(rss);
+        Some(rss)
+    }
+}
+
+/// Parse a `Key:   1234 kB` line from a /proc file
+fn proc_kb_field(content: &str, key: &str) -> Option<u64> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(key))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|value| value.parse().ok())
+}
+
+/// Bar width used for the histogram in [`TestMetrics::summary`]
+const HISTOGRAM_SUMMARY_WIDTH: usize = 40;
+
+/// Bucket scheme for [`Histogram`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistogramSpec {
+    /// `buckets` equal-width buckets spanning the observed range
+    Linear { buckets: usize },
+    /// Power-of-two buckets `[2^k, 2^(k+1))` spanning the observed range
+    Log2,
+}
+
+/// One histogram bucket covering `[lower, upper)` nanoseconds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HistogramBucket {
+    pub lower: u64,
+    pub upper: u64,
+    pub count: usize,
+}
+
+/// Distribution of timing samples, for spotting multi-modal latency
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Histogram {
+    pub buckets: Vec<HistogramBucket>,
+}
+
+impl Histogram {
+    /// Bucket raw nanosecond samples according to `spec`
+    ///
+    /// Works on any sample slice, e.g. `timings_ns` or a single phase's
+    /// samples. The last bucket is inclusive of the maximum sample.
+    pub fn from_samples(samples: &[u64], spec: HistogramSpec) -> Self {
+        let (Some(&min), Some(&max)) = (samples.iter().min(), samples.iter().max()) else {
+            return Self::default();
+        };
+
+        let first_log2 = log2_floor(min);
+        let (mut buckets, index): (Vec<HistogramBucket>, Box<dyn Fn(u64) -> usize>) = match spec {
+            HistogramSpec::Linear { buckets } => {
+                let n = buckets.max(1) as u64;
+                let width = (max - min) / n + 1;
+                let buckets = (0..n)
+                    .map(|i| HistogramBucket {
+                        lower: min + i * width,
+                        upper: min + (i + 1) * width,
+                        count: 0,
+                    })
+                    .collect();
+                (buckets, Box::new(move |v| ((v - min) / width) as usize))
+            }
+            HistogramSpec::Log2 => {
+                let buckets = (first_log2..=log2_floor(max))
+                    .map(|k| HistogramBucket {
+                        lower: if k == 0 { 0 } else { 1u64 << k },
+                        upper: 1u64.checked_shl(k + 1).unwrap_or(u64::MAX),
+                        count: 0,
+                    })
+                    .collect();
+                (
+                    buckets,
+                    Box::new(move |v| (log2_floor(v) - first_log2) as usize),
+                )
+            }
+        };
+
+        let last = buckets.len() - 1;
+        for &sample in samples {
+            buckets[index(sample).min(last)].count += 1;
+        }
+
+        Self { buckets }
+    }
+
+    /// Total number of samples
+    pub fn total(&self) -> usize {
+        self.buckets.iter().map(|b| b.count).sum()
+    }
+
+    /// Indices of local maxima (non-empty buckets larger than both neighbours)
+    pub fn modes(&self) -> Vec<usize> {
+        let count = |i: Option<usize>| i.and_then(|i| self.buckets.get(i)).map_or(0, |b| b.count);
+        (0..self.buckets.len())
+            .filter(|&i| {
+                let c = self.buckets[i].count;
+                c > 0 && c > count(i.checked_sub(1)) && c >= count(Some(i + 1))
+            })
+            .collect()
+    }
+
+    /// Render as a horizontal ASCII bar chart, bars scaled to `width` chars
+    pub fn render_ascii(&self, width: usize) -> String {
+        let peak = self
+            .buckets
+            .iter()
+            .map(|b| b.count)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let mut out = String::new();
+        for bucket in &self.buckets {
+            let bar = (bucket.count * width).div_ceil(peak);
+            out.push_str(&format!(
+                "  [{:>12.2}µs, {:>12.2}µs) {:>6} |{}\n",
+                bucket.lower as f64 / 1000.0,
+                bucket.upper as f64 / 1000.0,
+                bucket.count,
+                "#".repeat(bar)
+            ));
+        }
+        out
+    }
+}
+
+/// `floor(log2(v))`, treating 0 as 1
+fn log2_floor(v: u64) -> u32 {
+    v.max(1).ilog2()
+}
+
+/// Records one timing sample into [`TestMetrics`] when dropped
+///
+/// Created by [`TestMetrics::scoped`].
+#[must_use = "the sample is recorded when the guard is dropped"]
+pub struct TimingGuard<'a> {
+    metrics: &'a mut TestMetrics,
+    start: Instant,
+}
+
+impl Drop for TimingGuard<'_> {
+    fn drop(&mut self) {
+        self.metrics
+            .push_timing(self.start.elapsed().as_nanos() as u64);
+    }
+}
+
+/// Timing statistics
+#[derive(Clone, Debug, Default)]
+pub struct TimingStats {
+    pub count: usize,
+    pub min_ns: u64,
+    pub max_ns: u64,
+    pub mean_ns: f64,
+    pub std_dev_ns: f64,
+    pub p50_ns: u64,
+    pub p95_ns: u64,
+    pub p99_ns: u64,
+    pub total_ns: u64,
+}
+
+impl TimingStats {
+    /// Compute statistics over raw nanosecond samples
+    pub fn from_samples(samples: &[u64]) -> Self {
+        if samples.is_empty() {
+            return TimingStats::default();
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+
+        let sum: u64 = sorted.iter().sum();
+        let count = sorted.len() as f64;
+        let mean = sum as f64 / count;
+
+        let variance = sorted
+            .iter()
+            .map(|&t| {
+                let diff = t as f64 - mean;
+                diff * diff
+            })
+            .sum::<f64>()
+            / count;
+
+        TimingStats {
+            count: sorted.len(),
+            min_ns: sorted[0],
+            max_ns: sorted[sorted.len() - 1],
+            mean_ns: mean,
+            std_dev_ns: variance.sqrt(),
+            p50_ns: sorted[sorted.len() / 2],
+            p95_ns: sorted[(sorted.len() as f64 * 0.95) as usize],
+            p99_ns: sorted[(sorted.len() as f64 * 0.99).min(sorted.len() as f64 - 1.0) as usize],
+            total_ns: sum,
+        }
+    }
+
+    /// Total time as Duration
+    pub fn total_duration(&self) -> Duration {
+        Duration::from_nanos(self.total_ns)
+    }
+
+    /// Throughput in operations per second
+    pub fn ops_per_sec(&self) -> f64 {
+        if self.total_ns == 0 {
+            0.0
+        } else {
+            (self.count as f64) / (self.total_ns as f64 / 1_000_000_000.0)
+        }
+    }
+
+    /// Mean time as Duration
+    pub fn mean_duration(&self) -> Duration {
+        Duration::from_nanos(self.mean_ns as u64)
+    }
+
+    /// Median time as Duration
+    pub fn median_duration(&self) -> Duration {
+        Duration::from_nanos(self.p50_ns)
+    }
+}
+
+/// Accuracy metrics for VSA encoding/decoding fidelity
+#[derive(Clone, Debug, Default)]
+pub struct AccuracyMetrics {
+    /// Total bytes processed
+    pub total_bytes: usize,
+    /// Bytes requiring correction
+    pub correction_bytes: usize,
+    /// Number of correction entries
+    pub correction_count: usize,
+    /// Raw reconstruction accuracy (before corrections)
+    pub raw_accuracy: f64,
+    /// Final accuracy (after corrections)
+    pub final_accuracy: f64,
+    /// Signal-to-noise ratio in dB
+    pub snr_db: f64,
+    /// Compression ratio (original / encoded size)
+    pub compression_ratio: f64,
+}
+
+impl AccuracyMetrics {
+    /// Create new accuracy metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record encoding fidelity from original and reconstructed data
+    pub fn record_fidelity(&mut self, original: &[u8], reconstructed: &[u8]) {
+        self.total_bytes += original.len();
+
+        let matching = original
+            .iter()
+            .zip(reconstructed.iter())
+            .filter(|(a, b)| a == b)
+            .count();
+
+        let errors = original.len().saturating_sub(matching);
+        self.correction_bytes += errors;
+
+        self.raw_accuracy = if self.total_bytes > 0 {
+            1.0 - (self.correction_bytes as f64 / self.total_bytes as f64)
+        } else {
+            1.0
+        };
+
+        // Calculate SNR: 10 * log10(signal_power / noise_power)
+        // For byte data, signal_power ≈ variance of original, noise_power ≈ MSE
+        if !original.is_empty() && !reconstructed.is_empty() {
+            let signal_power: f64 =
+                original.iter().map(|&b| (b as f64).powi(2)).sum::<f64>() / original.len() as f64;
+            let noise_power: f64 = original
+                .iter()
+                .zip(reconstructed.iter())
+                .map(|(&a, &b)| ((a as f64) - (b as f64)).powi(2))
+                .sum::<f64>()
+                / original.len() as f64;
+
+            self.snr_db = if noise_power > 0.0 {
+                10.0 * (signal_power / noise_power).log10()
+            } else {
+                f64::INFINITY // Perfect reconstruction
+            };
+        }
+    }
+
+    /// Record correction store statistics
+    pub fn record_corrections(&mut self, correction_count: usize, _correction_size_bytes: usize) {
+        self.correction_count += correction_count;
+        // Final accuracy assumes corrections fix all remaining errors
+        self.final_accuracy = 1.0;
+    }
+
+    /// Record compression statistics
+    pub fn record_compression(&mut self, original_size: usize, encoded_size: usize) {
+        if encoded_size > 0 {
+            self.compression_ratio = original_size as f64 / encoded_size as f64;
+        }
+    }
+
+    /// Get correction ratio (corrections / total bytes)
+    pub fn correction_ratio(&self) -> f64 {
+        if self.total_bytes > 0 {
+            self.correction_bytes as f64 / self.total_bytes as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Generate human-readable summary
+    pub fn summary(&self) -> String {
+        format!(
+            "Accuracy: raw={:.2}%, final={:.2}%, SNR={:.1}dB, corrections={} ({:.2}%)",
+            self.raw_accuracy * 100.0,
+            self.final_accuracy * 100.0,
+            self.snr_db,
+            self.correction_count,
+            self.correction_ratio() * 100.0
+        )
+    }
+}
+
+/// Combined performance and accuracy metrics for VSA operations
+#[derive(Clone, Debug)]
+pub struct VsaEvaluationMetrics {
+    /// Performance timing metrics
+    pub performance: TestMetrics,
+    /// Accuracy and fidelity metrics
+    pub accuracy: AccuracyMetrics,
+    /// Configuration used for evaluation
+    pub config_dimension: usize,
+    pub config_density: f64,
+    pub config_scaling: String,
+}
+
+impl VsaEvaluationMetrics {
+    /// Create new evaluation metrics
+    pub fn new(name: &str, dimension: usize, density: f64, scaling: &str) -> Self {
+        Self {
+            performance: TestMetrics::new(name),
+            accuracy: AccuracyMetrics::new(),
+            config_dimension: dimension,
+            config_density: density,
+            config_scaling: scaling.to_string(),
+        }
+    }
+
+    /// Generate comprehensive summary report
+    pub fn full_summary(&self) -> String {
+        let mut report = format!("=== VSA Evaluation: {} ===\n", self.performance.name);
+        report.push_str(&format!(
+            "Config: dim={}, density={:.3}, scaling={}\n",
+            self.config_dimension, self.config_density, self.config_scaling
+        ));
+        report.push_str(&self.performance.summary());
+        report.push_str(&self.accuracy.summary());
+        report.push('\n');
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_metrics_timing() {
+        let mut metrics = TestMetrics::new("test_operation");
+
+        metrics.start_timing();
+        thread::sleep(Duration::from_millis(10));
+        metrics.stop_timing();
+
+        let stats = metrics.timing_stats();
+        assert_eq!(stats.count, 1);
+        assert!(stats.mean_ns > 10_000_000.0); // At least 10ms
+    }
+
+    #[test]
+    fn test_delta_since_checkpoint_covers_only_new_work() {
+        let mut metrics = TestMetrics::new("ingest");
+        metrics.timings_ns = vec![1_000_000; 10];
+        metrics.inc_op("chunk");
+        metrics.inc_op("chunk");
+        metrics.time_phase("encode", || ());
+        metrics.record_memory(1 << 30);
+        metrics.record_error();
+
+        let cp = metrics.checkpoint();
+        assert!(metrics.delta_since(&cp).is_empty());
+        assert!(metrics.summary_since(&cp).contains("No activity"));
+
+        for _ in 0..3 {
+            metrics.time_operation(|| thread::sleep(Duration::from_micros(50)));
+            metrics.inc_op("chunk");
+        }
+        metrics.inc_op("flush");
+        metrics.time_phase("encode", || ());
+        metrics.time_phase("decode", || ());
+        metrics.record_memory(4096);
+        metrics.record_warning();
+
+        let delta = metrics.delta_since(&cp);
+        assert_eq!(delta.timing.count, 3);
+        assert!(delta.timing.max_ns < 1_000_000_000);
+        assert!(delta.timing.min_ns >= 50_000);
+        assert_eq!(delta.ops("chunk"), 3);
+        assert_eq!(delta.ops("flush"), 1);
+        assert_eq!(delta.ops("encode"), 1);
+        assert_eq!(delta.phases["encode"].count, 1);
+        assert_eq!(delta.phases["decode"].count, 1);
+        assert_eq!(delta.memory_peak, Some(4096));
+        assert_eq!((delta.errors, delta.warnings), (0, 1));
+
+        let summary = metrics.summary_since(&cp);
+        assert!(summary.contains("Timing: 3 ops"));
+        assert!(summary.contains("chunk=+3"));
+        assert!(summary.contains("peak=4.00 KiB"));
+        assert_eq!(metrics.timing_stats().count, 13);
+    }
+
+    #[test]
+    fn test_budget_names_only_the_breached_bound() {
+        let mut metrics = TestMetrics::new("encode");
+        metrics.timings_ns = [vec![100; 980], vec![10_000; 20]].concat();
+
+        let budget = LatencyBudget::new()
+            .with_mean(Duration::from_nanos(500))
+            .with_p95(Duration::from_nanos(200))
+            .with_p99(Duration::from_nanos(1_000))
+            .with_max(Duration::from_nanos(20_000));
+        let violation = metrics.assert_budget(&budget).unwrap_err();
+        assert_eq!(violation.samples, 1000);
+        assert_eq!(
+            violation.breaches,
+            vec![BudgetBreach {
+                bound: BudgetBound::P99,
+                measured_ns: 10_000.0,
+                allowed_ns: 1_000,
+            }]
+        );
+        assert_eq!(
+            violation.to_string(),
+            "encode over latency budget (1000 samples): p99 10.0 µs > 1.00 µs allowed"
+        );
+        assert!(metrics
+            .assert_budget(&budget.with_p99(Duration::from_micros(10)))
+            .is_ok());
+
+        // Budgets load from baseline JSON, with or without the field
+        let baseline = report::Baseline::capture().with_budget("encode", budget);
+        let json = serde_json::to_string(&baseline).unwrap();
+        let loaded: report::Baseline = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.budget("encode"), Some(&budget));
+        let mut legacy = serde_json::to_value(report::Baseline::capture()).unwrap();
+        legacy.as_object_mut().unwrap().remove("budgets");
+        let legacy: report::Baseline = serde_json::from_value(legacy).unwrap();
+        assert!(legacy.budgets.is_empty());
+
+        let written: LatencyBudget =
+            serde_json::from_str(r#"{"mean_ns": 500, "p95_ns": "200ns", "max_ns": "20 µs"}"#)
+                .unwrap();
+        assert_eq!(
+            written,
+            LatencyBudget {
+                p99_ns: None,
+                ..budget
+            }
+        );
+    }
+
+    #[test]
+    fn test_record_and_check_per_call_bound() {
+        let mut metrics = TestMetrics::new("call");
+        assert_eq!(
+            metrics.record_and_check(Duration::from_secs(60), || 7),
+            Ok(7)
+        );
+        let violation = metrics
+            .record_and_check(Duration::from_millis(1), || {
+                thread::sleep(Duration::from_millis(5))
+            })
+            .unwrap_err();
+        assert_eq!(violation.breaches.len(), 1);
+        assert_eq!(violation.breaches[0].bound, BudgetBound::PerCall);
+        assert!(violation.breaches[0].measured_ns >= 5_000_000.0);
+        assert_eq!(metrics.timings_ns.len(), 2);
+    }
+
+    #[test]
+    fn test_scoped_records_on_panic() {
+        let mut metrics = TestMetrics::new("scoped");
+
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = metrics.scoped();
+            panic!("measured code failed");
+        }));
+        assert!(outcome.is_err());
+        assert_eq!(metrics.timings_ns.len(), 1);
+
+        {
+            let _guard = metrics.scoped();
+        }
+        assert_eq!(metrics.timings_ns.len(), 2);
+    }
+
+    #[test]
+    fn test_preallocated_samples_never_reallocate() {
+        let mut metrics = TestMetrics::new("hot").with_capacity(256);
+        assert!(metrics.samples_capacity() >= 256);
+        let capacity = metrics.timings_ns.capacity();
+        let ptr = metrics.timings_ns.as_ptr();
+
+        for _ in 0..capacity {
+            metrics.time_operation(|| std::hint::black_box(1 + 1));
+        }
+        assert_eq!(metrics.timings_ns.len(), capacity);
+        assert_eq!(metrics.timings_ns.capacity(), capacity);
+        assert_eq!(metrics.timings_ns.as_ptr(), ptr);
+
+        metrics.reserve_samples(10);
+        assert!(metrics.timings_ns.capacity() >= capacity + 10);
+        assert!(metrics.samples_capacity() >= capacity);
+    }
+
+    #[test]
+    fn test_clone_keeps_preallocated_capacity() {
+        let mut metrics = TestMetrics::new("hot").with_capacity(64);
+        metrics.time_operation(|| ());
+        let mut copy = metrics.clone();
+        assert_eq!(copy.samples_capacity(), metrics.samples_capacity());
+        assert_eq!(copy.timings_ns, metrics.timings_ns);
+
+        // Would trip strict mode on the first push if the clone were tight
+        while copy.timings_ns.len() < copy.samples_capacity() {
+            copy.time_operation(|| ());
+        }
+        assert_eq!(copy.samples_capacity(), metrics.samples_capacity());
+    }
+
+    #[cfg(all(feature = "strict-metrics", debug_assertions))]
+    #[test]
+    fn test_strict_mode_flags_reallocation_past_capacity() {
+        let mut metrics = TestMetrics::new("hot").with_capacity(16);
+        for _ in 0..metrics.samples_capacity() {
+            metrics.time_operation(|| ());
+        }
+
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            metrics.time_operation(|| ());
+        }));
+        let message = outcome.unwrap_err().downcast::<String>().unwrap();
+        assert!(
+            message.contains("hot: sample vectors reallocated during a measurement"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_time_result_counts_errors() {
+        let mut metrics = TestMetrics::new("fallible");
+
+        let ok: Result<u32, String> = metrics.time_result(|| Ok(7));
+        assert_eq!(ok, Ok(7));
+        let err: Result<u32, String> = metrics.time_result(|| Err("boom".to_string()));
+        assert!(err.is_err());
+
+        assert_eq!(metrics.timings_ns.len(), 2);
+        assert_eq!(metrics.error_count, 1);
+    }
+
+    #[test]
+    fn test_time_phase_breakdown() {
+        let mut metrics = TestMetrics::new("workflow");
+        let start = Instant::now();
+        for _ in 0..2 {
+            metrics.time_phase("read", || thread::sleep(Duration::from_millis(2)));
+            metrics.time_phase("encode", || thread::sleep(Duration::from_millis(8)));
+            metrics.time_phase("write", || thread::sleep(Duration::from_millis(16)));
+        }
+        let wall = start.elapsed().as_nanos() as u64;
+
+        let read = metrics.phase_stats("read");
+        let encode = metrics.phase_stats("encode");
+        let write = metrics.phase_stats("write");
+        assert_eq!(read.count, 2);
+        assert!(read.mean_ns < encode.mean_ns && encode.mean_ns < write.mean_ns);
+        assert_eq!(metrics.op_counts["encode"], 2);
+        assert_eq!(metrics.phase_stats("missing").count, 0);
+
+        let combined = read.total_ns + encode.total_ns + write.total_ns;
+        assert!(combined <= wall);
+        let summary = metrics.summary();
+        assert!(summary.contains("encode"));
+        assert!(summary.contains(&format::duration_ns(combined)));
+    }
+
+    #[test]
+    fn test_histogram_bimodal() {
+        let mut metrics = TestMetrics::new("bimodal").with_histogram(HistogramSpec::Log2);
+        // Sparse fast path around 1µs, packed slow path around 1ms
+        metrics.timings_ns.extend((0..100).map(|i| 1_000 + i % 10));
+        metrics
+            .timings_ns
+            .extend((0..60).map(|i| 1_000_000 + i % 10));
+
+        let log = metrics.histogram(HistogramSpec::Log2);
+        assert_eq!(log.total(), 160);
+        let modes: Vec<_> = log.modes().iter().map(|&i| log.buckets[i]).collect();
+        assert_eq!(modes.len(), 2);
+        assert_eq!((modes[0].lower, modes[0].count), (512, 100));
+        assert_eq!((modes[1].lower, modes[1].count), (524_288, 60));
+
+        let linear = metrics.histogram(HistogramSpec::Linear { buckets: 10 });
+        assert_eq!(linear.buckets.len(), 10);
+        assert_eq!(linear.buckets[0].count, 100);
+        assert_eq!(linear.buckets[9].count, 60);
+        assert_eq!(linear.modes(), vec![0, 9]);
+
+        let chart = log.render_ascii(20);
+        assert!(chart.lines().any(|l| l.ends_with(&"#".repeat(20))));
+        assert!(metrics.summary().contains("Histogram:"));
+    }
+
+    #[test]
+    fn test_time_operation() {
+        let mut metrics = TestMetrics::new("test");
+
+        let result = metrics.time_operation(|| {
+            thread::sleep(Duration::from_millis(5));
+            42
+        });
+
+        assert_eq!(result, 42);
+        assert_eq!(metrics.timings_ns.len(), 1);
+    }
+
+    #[test]
+    fn test_custom_metrics() {
+        let mut metrics = TestMetrics::new("test");
+        metrics.record_metric("accuracy", 0.95);
+        metrics.record_metric("loss", 0.05);
+
+        assert_eq!(metrics.custom_metrics.get("accuracy"), Some(&0.95));
+        assert_eq!(metrics.custom_metrics.get("loss"), Some(&0.05));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = TestMetrics::new("a");
+        a.timings_ns.push(10);
+        a.inc_op("bind");
+        a.record_error();
+
+        let mut b = TestMetrics::new("b");
+        b.timings_ns.extend([20, 30]);
+        b.inc_op("bind");
+        b.inc_op("bundle");
+        b.record_metric("accuracy", 0.9);
+
+        a.merge(&b);
+        assert_eq!(a.name, "a");
+        assert_eq!(a.timings_ns, vec![10, 20, 30]);
+        assert_eq!(a.op_counts["bind"], 2);
+        assert_eq!(a.op_counts["bundle"], 1);
+        assert_eq!(a.custom_metrics["accuracy"], 0.9);
+        assert_eq!(a.error_count, 1);
+    }
+
+    #[test]
+    fn test_proc_kb_field() {
+        let meminfo =
+            "MemTotal:       16318480 kB\nMemFree:         1234 kB\nMemAvailable:    8000000 kB\n";
+        assert_eq!(proc_kb_field(meminfo, "MemTotal:"), Some(16318480));
+        assert_eq!(proc_kb_field(meminfo, "MemAvailable:"), Some(8000000));
+        assert_eq!(proc_kb_field(meminfo, "SwapTotal:"), None);
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut metrics = TestMetrics::new("test_op");
+        metrics.start_timing();
+        thread::sleep(Duration::from_millis(1));
+        metrics.stop_timing();
+
+        let summary = metrics.summary();
+        assert!(summary.contains("test_op"));
+        assert!(summary.contains("Timing:"));
+    }
+}
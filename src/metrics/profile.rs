@@ -0,0 +1,381 @@
+//! Nested profiling scopes collected into a per-thread call tree
+//!
+//! [`scope`] returns a guard that times the enclosing block. Guards opened
+//! while another is alive on the same thread become its children, so a big
+//! workflow breaks down into inclusive and exclusive time per call path.
+//! [`take`] hands back the thread's [`ProfileTree`], which renders as an
+//! indented tree or as folded stacks for inferno/flamegraph tooling.
+//!
+//! Profiling is off until [`enable`] is called; a disabled [`scope`] is a
+//! single relaxed atomic load.
+//!
+//! ```rust,ignore
+//! profile::enable();
+//! {
+//!     let _ingest = profile::scope("ingest");
+//!     for chunk in chunks {
+//!         let _encode = profile::scope("encode");
+//!         encode(chunk);
+//!     }
+//! }
+//! let tree = profile::take();
+//! println!("{}", tree.render());
+//! std::fs::write("ingest.folded", tree.to_folded())?;
+//! ```
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = RefCell::new(Profiler::default());
+}
+
+/// Turn profiling on for every thread
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Turn profiling off; scopes already open still record when they close
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Whether new scopes are recorded
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Time the current block as `name`, nested under any open scope
+///
+/// Recursion nests too: a scope opened inside a scope of the same name
+/// becomes its child, as in a folded stack.
+#[must_use = "the scope ends when the guard is dropped"]
+pub fn scope(name: &'static str) -> ScopeGuard {
+    if !is_enabled() {
+        return ScopeGuard {
+            entered: None,
+            _not_send: PhantomData,
+        };
+    }
+    let entered = PROFILER.with(|p| p.borrow_mut().enter(name));
+    ScopeGuard {
+        entered: Some(entered),
+        _not_send: PhantomData,
+    }
+}
+
+/// Return this thread's tree and start a fresh one
+///
+/// Scopes still open are left out, and ignored when they close.
+pub fn take() -> ProfileTree {
+    PROFILER.with(|p| {
+        let mut p = p.borrow_mut();
+        let tree = p.tree();
+        let generation = p.generation.wrapping_add(1);
+        *p = Profiler {
+            generation,
+            ..Profiler::default()
+        };
+        tree
+    })
+}
+
+/// Closes its scope when dropped; see [`scope`]
+pub struct ScopeGuard {
+    entered: Option<Entered>,
+    // The open scope lives on this thread's stack
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        if let Some(entered) = self.entered.take() {
+            let elapsed = entered.start.elapsed().as_nanos() as u64;
+            PROFILER.with(|p| p.borrow_mut().exit(entered, elapsed));
+        }
+    }
+}
+
+struct Entered {
+    node: usize,
+    generation: u64,
+    start: Instant,
+}
+
+/// Arena node; index 0 is the synthetic root
+#[derive(Default)]
+struct RawNode {
+    name: &'static str,
+    parent: usize,
+    children: Vec<usize>,
+    inclusive_ns: u64,
+    child_ns: u64,
+    count: u64,
+}
+
+struct Profiler {
+    nodes: Vec<RawNode>,
+    open: Vec<usize>,
+    generation: u64,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self {
+            nodes: vec![RawNode::default()],
+            open: Vec::new(),
+            generation: 0,
+        }
+    }
+}
+
+impl Profiler {
+    fn enter(&mut self, name: &'static str) -> Entered {
+        let parent = self.open.last().copied().unwrap_or(0);
+        let existing = self.nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .find(|&c| self.nodes[c].name == name);
+        let node = existing.unwrap_or_else(|| {
+            self.nodes.push(RawNode {
+                name,
+                parent,
+                ..RawNode::default()
+            });
+            let node = self.nodes.len() - 1;
+            self.nodes[parent].children.push(node);
+            node
+        });
+        self.open.push(node);
+        Entered {
+            node,
+            generation: self.generation,
+            start: Instant::now(),
+        }
+    }
+
+    fn exit(&mut self, entered: Entered, elapsed: u64) {
+        if entered.generation != self.generation {
+            return;
+        }
+        if let Some(pos) = self.open.iter().rposition(|&n| n == entered.node) {
+            self.open.truncate(pos);
+        }
+        let node = &mut self.nodes[entered.node];
+        node.inclusive_ns += elapsed;
+        node.count += 1;
+        let parent = node.parent;
+        self.nodes[parent].child_ns += elapsed;
+    }
+
+    fn tree(&self) -> ProfileTree {
+        ProfileTree {
+            roots: self.build_children(0),
+        }
+    }
+
+    fn build_children(&self, index: usize) -> Vec<ProfileNode> {
+        self.nodes[index]
+            .children
+            .iter()
+            .filter(|&&c| self.nodes[c].count > 0)
+            .map(|&c| {
+                let raw = &self.nodes[c];
+                ProfileNode {
+                    name: raw.name.to_string(),
+                    inclusive_ns: raw.inclusive_ns,
+                    exclusive_ns: raw.inclusive_ns.saturating_sub(raw.child_ns),
+                    count: raw.count,
+                    children: self.build_children(c),
+                }
+            })
+            .collect()
+    }
+}
+
+/// One call path in a [`ProfileTree`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileNode {
+    /// Scope name
+    pub name: String,
+    /// Time inside the scope, children included
+    pub inclusive_ns: u64,
+    /// Time inside the scope minus time in child scopes
+    pub exclusive_ns: u64,
+    /// Times the scope closed on this path
+    pub count: u64,
+    /// Scopes opened inside this one, in first-entry order
+    pub children: Vec<ProfileNode>,
+}
+
+/// Call tree of the scopes closed on one thread
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProfileTree {
+    /// Outermost scopes, in first-entry order
+    pub roots: Vec<ProfileNode>,
+}
+
+impl ProfileTree {
+    /// Whether no scope was recorded
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Node at `path` of scope names from the root, e.g. `["ingest", "encode"]`
+    pub fn find(&self, path: &[&str]) -> Option<&ProfileNode> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.roots.iter().find(|n| n.name == *first)?;
+        for name in rest {
+            node = node.children.iter().find(|n| n.name == *name)?;
+        }
+        Some(node)
+    }
+
+    /// Indented tree, siblings sorted by inclusive time (largest first)
+    pub fn render(&self) -> String {
+        fn walk(out: &mut String, nodes: &[ProfileNode], depth: usize) {
+            let mut sorted: Vec<&ProfileNode> = nodes.iter().collect();
+            sorted.sort_by_key(|n| std::cmp::Reverse(n.inclusive_ns));
+            for node in sorted {
+                out.push_str(&format!(
+                    "{:indent$}{} {:.3}ms incl, {:.3}ms excl, {}x\n",
+                    "",
+                    node.name,
+                    node.inclusive_ns as f64 / 1e6,
+                    node.exclusive_ns as f64 / 1e6,
+                    node.count,
+                    indent = depth * 2
+                ));
+                walk(out, &node.children, depth + 1);
+            }
+        }
+        let mut out = String::new();
+        walk(&mut out, &self.roots, 0);
+        out
+    }
+
+    /// Folded stacks: one `a;b;c <exclusive_ns>` line per call path
+    ///
+    /// `;` inside scope names is replaced with `:` so it cannot split frames.
+    pub fn to_folded(&self) -> String {
+        fn walk(out: &mut String, nodes: &[ProfileNode], prefix: &str) {
+            for node in nodes {
+                let name = node.name.replace(';', ":");
+                let stack = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{};{}", prefix, name)
+                };
+                out.push_str(&format!("{} {}\n", stack, node.exclusive_ns));
+                walk(out, &node.children, &stack);
+            }
+        }
+        let mut out = String::new();
+        walk(&mut out, &self.roots, "");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    fn recurse(depth: usize) {
+        let _scope = scope("recurse");
+        if depth > 0 {
+            recurse(depth - 1);
+        }
+    }
+
+    /// Disables profiling when dropped, even if an assertion fails
+    struct Enabled;
+
+    impl Drop for Enabled {
+        fn drop(&mut self) {
+            disable();
+        }
+    }
+
+    // One test, since enable/disable are process-wide
+    #[test]
+    fn test_nested_scopes_build_call_tree() {
+        disable();
+        drop(scope("ignored"));
+        assert!(take().is_empty());
+
+        enable();
+        let _enabled = Enabled;
+        {
+            let _outer = scope("outer");
+            thread::sleep(Duration::from_millis(4));
+            for _ in 0..2 {
+                let _middle = scope("middle");
+                thread::sleep(Duration::from_millis(2));
+                let _inner = scope("inner");
+                thread::sleep(Duration::from_millis(3));
+            }
+        }
+        let tree = take();
+
+        assert_eq!(tree.roots.len(), 1);
+        let outer = tree.find(&["outer"]).unwrap();
+        let middle = tree.find(&["outer", "middle"]).unwrap();
+        let inner = tree.find(&["outer", "middle", "inner"]).unwrap();
+        assert_eq!((outer.count, middle.count, inner.count), (1, 2, 2));
+        assert!(inner.children.is_empty());
+
+        assert_eq!(outer.exclusive_ns, outer.inclusive_ns - middle.inclusive_ns);
+        assert_eq!(
+            middle.exclusive_ns,
+            middle.inclusive_ns - inner.inclusive_ns
+        );
+        assert_eq!(inner.exclusive_ns, inner.inclusive_ns);
+        assert!(outer.exclusive_ns >= 4_000_000);
+        assert!(middle.exclusive_ns >= 4_000_000);
+        assert!(inner.inclusive_ns >= 6_000_000);
+
+        let folded = tree.to_folded();
+        let lines: Vec<&str> = folded.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], format!("outer {}", outer.exclusive_ns));
+        assert_eq!(lines[1], format!("outer;middle {}", middle.exclusive_ns));
+        assert_eq!(
+            lines[2],
+            format!("outer;middle;inner {}", inner.exclusive_ns)
+        );
+
+        let rendered = tree.render();
+        assert!(rendered.starts_with("outer "));
+        assert!(rendered.contains("\n  middle ") && rendered.contains("\n    inner "));
+        assert!(rendered.contains(", 2x\n"));
+
+        recurse(2);
+        let tree = take();
+        let deepest = tree.find(&["recurse", "recurse", "recurse"]).unwrap();
+        assert_eq!(deepest.count, 1);
+        assert!(deepest.children.is_empty());
+        assert_eq!(
+            tree.to_folded()
+                .lines()
+                .last()
+                .unwrap()
+                .rsplit_once(' ')
+                .unwrap()
+                .0,
+            "recurse;recurse;recurse"
+        );
+
+        // A scope open across take() does not leak into the next tree
+        let open = scope("open");
+        assert!(take().is_empty());
+        drop(open);
+        assert!(take().is_empty());
+    }
+}
@@ -0,0 +1,392 @@
+//! Prometheus text exposition for [`TestMetrics`]
+//!
+//! Renders one or more metrics collectors in the Prometheus text format
+//! (version 0.0.4) so soak tests running in scraped containers show up
+//! without custom glue:
+//! - Timing samples as the `embeddenator_test_duration_seconds` summary
+//! - `op_counts` as the `embeddenator_test_ops_total` counter
+//! - Error and warning counts as counters
+//! - `custom_metrics` as `embeddenator_test_custom_<name>` gauges
+//! - Peak memory as the `embeddenator_test_memory_peak_bytes` gauge
+//!
+//! Every sample carries the collector's name as the `operation` label.
+
+use super::TestMetrics;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Prefix for every exported metric family
+const PREFIX: &str = "embeddenator_test";
+
+/// Reads one quantile out of [`TimingStats`](super::TimingStats)
+type QuantileFn = fn(&super::TimingStats) -> u64;
+
+/// Quantiles exported for timing summaries
+const QUANTILES: [(&str, QuantileFn); 3] = [
+    ("0.5", |s| s.p50_ns),
+    ("0.95", |s| s.p95_ns),
+    ("0.99", |s| s.p99_ns),
+];
+
+/// Samples of one metric family, collected before rendering
+struct Family {
+    kind: &'static str,
+    help: String,
+    samples: Vec<String>,
+}
+
+/// Encode `metrics` in Prometheus text exposition format
+///
+/// Each metric family gets exactly one `# HELP`/`# TYPE` header, even when
+/// several collectors contribute samples to it.
+///
+/// # Example
+/// ```rust,ignore
+/// let body = prometheus::encode(&[&ingest, &query]);
+/// std::fs::write("/var/lib/node-exporter/testkit.prom", body)?;
+/// ```
+pub fn encode(metrics: &[&TestMetrics]) -> String {
+    let mut families: BTreeMap<String, Family> = BTreeMap::new();
+
+    for m in metrics {
+        let operation = format!("operation=\"{}\"", escape_label_value(&m.name));
+
+        let stats = m.timing_stats();
+        if stats.count > 0 {
+            let name = format!("{PREFIX}_duration_seconds");
+            let summary = family(
+                &mut families,
+                name.clone(),
+                "summary",
+                "Timed operation duration",
+            );
+            for (quantile, value) in QUANTILES {
+                summary.push(format!(
+                    "{name}{{{operation},quantile=\"{quantile}\"}} {}",
+                    format_value(value(&stats) as f64 / 1e9)
+                ));
+            }
+            summary.push(format!(
+                "{name}_sum{{{operation}}} {}",
+                format_value(stats.total_ns as f64 / 1e9)
+            ));
+            summary.push(format!("{name}_count{{{operation}}} {}", stats.count));
+        }
+
+        let mut categories: Vec<_> = m.op_counts.iter().collect();
+        categories.sort();
+        for (category, count) in categories {
+            let name = format!("{PREFIX}_ops_total");
+            family(
+                &mut families,
+                name.clone(),
+                "counter",
+                "Operations performed by category",
+            )
+            .push(format!(
+                "{name}{{{operation},category=\"{}\"}} {count}",
+                escape_label_value(category)
+            ));
+        }
+
+        for (suffix, help, value) in [
+            ("errors_total", "Errors recorded", m.error_count),
+            ("warnings_total", "Warnings recorded", m.warning_count),
+        ] {
+            let name = format!("{PREFIX}_{suffix}");
+            family(&mut families, name.clone(), "counter", help)
+                .push(format!("{name}{{{operation}}} {value}"));
+        }
+
+        let mut custom: Vec<_> = m.custom_metrics.iter().collect();
+        custom.sort_by(|a, b| a.0.cmp(b.0));
+        for (metric, value) in custom {
+            let name = format!("{PREFIX}_custom_{}", sanitize_metric_name(metric));
+            family(&mut families, name.clone(), "gauge", "Custom test metric")
+                .push(format!("{name}{{{operation}}} {}", format_value(*value)));
+        }
+
+        if let Some(peak) = m.memory_samples.iter().max() {
+            let name = format!("{PREFIX}_memory_peak_bytes");
+            family(
+                &mut families,
+                name.clone(),
+                "gauge",
+                "Peak sampled memory usage",
+            )
+            .push(format!("{name}{{{operation}}} {peak}"));
+        }
+    }
+
+    let mut out = String::new();
+    for (name, family) in &families {
+        let _ = writeln!(out, "# HELP {} {}", name, family.help);
+        let _ = writeln!(out, "# TYPE {} {}", name, family.kind);
+        for sample in &family.samples {
+            out.push_str(sample);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Sample list of the `name` family, declaring it on first use
+fn family<'a>(
+    families: &'a mut BTreeMap<String, Family>,
+    name: String,
+    kind: &'static str,
+    help: &str,
+) -> &'a mut Vec<String> {
+    &mut families
+        .entry(name)
+        .or_insert_with(|| Family {
+            kind,
+            help: help.to_string(),
+            samples: Vec::new(),
+        })
+        .samples
+}
+
+/// Map `name` onto the metric name charset `[a-zA-Z_:][a-zA-Z0-9_:]*`
+pub fn sanitize_metric_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Escape backslash, double quote and newline in a label value
+fn escape_label_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Format a sample value, spelling infinities and NaN as Prometheus expects
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// How long the server waits on a client to send its request or accept the
+/// response before dropping the connection
+#[cfg(feature = "prometheus-server")]
+const CLIENT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Minimal HTTP endpoint serving `GET /metrics` from a background thread
+#[cfg(feature = "prometheus-server")]
+pub struct MetricsServer {
+    addr: std::net::SocketAddr,
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+/// Serve `render()` at `GET /metrics` on `addr` until the server is dropped
+///
+/// Bind to port 0 and read [`MetricsServer::local_addr`] to pick a free port.
+/// Requests are served one at a time; a client that stalls for more than a
+/// second is disconnected, so it cannot block other scrapes or the drop.
+///
+/// # Example
+/// ```rust,ignore
+/// let shared = Arc::new(Mutex::new(TestMetrics::new("soak")));
+/// let source = shared.clone();
+/// let _server = prometheus::serve("0.0.0.0:9464", move || {
+///     prometheus::encode(&[&source.lock().unwrap()])
+/// })?;
+/// ```
+#[cfg(feature = "prometheus-server")]
+pub fn serve<F>(addr: impl std::net::ToSocketAddrs, render: F) -> std::io::Result<MetricsServer>
+where
+    F: Fn() -> String + Send + 'static,
+{
+    use std::io::{BufRead, BufReader, Write as _};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let listener = std::net::TcpListener::bind(addr)?;
+    let addr = listener.local_addr()?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop.clone();
+
+    let thread = std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            if thread_stop.load(Ordering::Relaxed) {
+                break;
+            }
+            let Ok(mut stream) = stream else { continue };
+            if stream.set_read_timeout(Some(CLIENT_TIMEOUT)).is_err()
+                || stream.set_write_timeout(Some(CLIENT_TIMEOUT)).is_err()
+            {
+                continue;
+            }
+            let mut request_line = String::new();
+            if BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .is_err()
+            {
+                continue;
+            }
+
+            let response = if request_line.starts_with("GET /metrics ") {
+                let body = render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string()
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    Ok(MetricsServer {
+        addr,
+        stop,
+        thread: Some(thread),
+    })
+}
+
+#[cfg(feature = "prometheus-server")]
+impl MetricsServer {
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> std::net::SocketAddr {
+        self.addr
+    }
+}
+
+#[cfg(feature = "prometheus-server")]
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        // Wake the blocking accept so the thread sees the stop flag
+        let _ = std::net::TcpStream::connect(self.addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn sample_metrics(name: &str) -> TestMetrics {
+        let mut metrics = TestMetrics::new(name);
+        metrics.timings_ns.extend([1_000_000, 2_000_000, 3_000_000]);
+        metrics.inc_op("encode");
+        metrics.inc_op("encode");
+        metrics.record_metric("recall@10", 0.97);
+        metrics.record_memory(4096);
+        metrics
+    }
+
+    #[test]
+    fn test_encode_exposition_basics() {
+        let a = sample_metrics("ingest");
+        let b = sample_metrics("query \"hot\"\npath");
+        let text = encode(&[&a, &b]);
+
+        // One HELP/TYPE header per family, and every sample belongs to a declared family
+        let mut declared = HashSet::new();
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let name = rest.split(' ').next().unwrap();
+                assert!(
+                    declared.insert(name.to_string()),
+                    "duplicate TYPE for {name}"
+                );
+            } else if !line.starts_with('#') {
+                let name = line.split(['{', ' ']).next().unwrap();
+                assert!(
+                    declared.iter().any(|d| name.starts_with(d.as_str())),
+                    "undeclared sample {line}"
+                );
+                assert!(name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':'));
+            }
+        }
+
+        assert!(text.contains(
+            "embeddenator_test_duration_seconds{operation=\"ingest\",quantile=\"0.5\"} 0.002"
+        ));
+        assert!(text.contains("embeddenator_test_duration_seconds_count{operation=\"ingest\"} 3"));
+        assert!(text
+            .contains("embeddenator_test_ops_total{operation=\"ingest\",category=\"encode\"} 2"));
+        assert!(text.contains("embeddenator_test_custom_recall_10{operation=\"ingest\"} 0.97"));
+        assert!(text.contains("embeddenator_test_memory_peak_bytes{operation=\"ingest\"} 4096"));
+        assert!(text.contains("operation=\"query \\\"hot\\\"\\npath\""));
+    }
+
+    #[test]
+    fn test_sanitize_metric_name() {
+        assert_eq!(sanitize_metric_name("recall@10"), "recall_10");
+        assert_eq!(sanitize_metric_name("9lives"), "_9lives");
+        assert_eq!(sanitize_metric_name("ns:ok_name"), "ns:ok_name");
+        assert_eq!(format_value(f64::INFINITY), "+Inf");
+    }
+
+    #[cfg(feature = "prometheus-server")]
+    #[test]
+    fn test_serve_metrics_endpoint() {
+        use std::io::{Read, Write};
+
+        let metrics = sample_metrics("served");
+        let expected = encode(&[&metrics]);
+        let server = serve("127.0.0.1:0", move || encode(&[&metrics])).unwrap();
+
+        let mut stream = std::net::TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(&expected));
+    }
+
+    #[cfg(feature = "prometheus-server")]
+    #[test]
+    fn test_silent_client_does_not_block_server() {
+        use std::io::{Read, Write};
+
+        let server = serve("127.0.0.1:0", || "up 1\n".to_string()).unwrap();
+        // Connects and never sends a request
+        let _silent = std::net::TcpStream::connect(server.local_addr()).unwrap();
+
+        let mut stream = std::net::TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.ends_with("up 1\n"));
+
+        // Would hang forever if the silent client kept the thread in read_line
+        drop(server);
+    }
+}
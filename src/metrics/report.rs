@@ -0,0 +1,986 @@
+//! Markdown and HTML summaries of a test run
+//!
+//! [`RunReport`] collects timing metrics, integrity results, capacity probes,
+//! threshold calibrations and baseline comparisons into one artifact that can be pasted into a PR
+//! (`to_markdown`) or archived as a standalone page (`to_html`). Sections
+//! without data are left out. Every report carries the [`EnvironmentInfo`]
+//! of the machine it was produced on.
+//!
+//! [`Baseline`] stores metric values, latency budgets and their environment
+//! as JSON between runs; [`compare_baselines`] diffs two of them and warns
+//! when the hardware changed.
+
+use super::{
+    format, ComparisonResult, DensityStats, DensitySummary, EnvironmentInfo, IndexUsage,
+    IndexUsageSummary, LatencyBudget, TestMetrics, Verdict,
+};
+use crate::harness::{CapacityReport, ThresholdCalibration};
+use crate::integrity::IntegrityReport;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A section of a [`RunReport`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportSection {
+    /// Per-operation timing table
+    Timings,
+    /// Integrity pass rates
+    Integrity,
+    /// Bundle capacity curves
+    Capacity,
+    /// Suggested similarity thresholds per noise level
+    Calibration,
+    /// Comparison against baseline values
+    Baselines,
+    /// Significance tests between runs
+    Comparisons,
+    /// Encoded vector density
+    Density,
+    /// Per-index usage imbalance
+    IndexUsage,
+}
+
+impl ReportSection {
+    /// Default section order
+    pub const ALL: [ReportSection; 8] = [
+        ReportSection::Timings,
+        ReportSection::Integrity,
+        ReportSection::Capacity,
+        ReportSection::Calibration,
+        ReportSection::Baselines,
+        ReportSection::Comparisons,
+        ReportSection::Density,
+        ReportSection::IndexUsage,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            ReportSection::Timings => "Timings",
+            ReportSection::Integrity => "Integrity",
+            ReportSection::Capacity => "Capacity",
+            ReportSection::Calibration => "Threshold calibration",
+            ReportSection::Baselines => "Baselines",
+            ReportSection::Comparisons => "Comparisons",
+            ReportSection::Density => "Density",
+            ReportSection::IndexUsage => "Index usage",
+        }
+    }
+}
+
+/// One metric compared against its baseline value
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaselineDiff {
+    /// Metric name
+    pub metric: String,
+    /// Baseline value
+    pub baseline: f64,
+    /// Value measured in this run
+    pub current: f64,
+    /// Whether larger values are improvements (throughput) or not (latency)
+    pub higher_is_better: bool,
+}
+
+impl BaselineDiff {
+    /// Compare a metric where smaller is better, such as latency
+    pub fn lower_is_better(metric: &str, baseline: f64, current: f64) -> Self {
+        Self {
+            metric: metric.to_string(),
+            baseline,
+            current,
+            higher_is_better: false,
+        }
+    }
+
+    /// Compare a metric where larger is better, such as throughput
+    pub fn higher_is_better(metric: &str, baseline: f64, current: f64) -> Self {
+        Self {
+            metric: metric.to_string(),
+            baseline,
+            current,
+            higher_is_better: true,
+        }
+    }
+
+    /// Relative change from baseline, in percent
+    pub fn change_pct(&self) -> f64 {
+        if self.baseline == 0.0 {
+            0.0
+        } else {
+            (self.current - self.baseline) / self.baseline.abs() * 100.0
+        }
+    }
+
+    /// Whether the metric got worse by more than `tolerance_pct`
+    pub fn is_regression(&self, tolerance_pct: f64) -> bool {
+        let change = self.change_pct();
+        if self.higher_is_better {
+            change < -tolerance_pct
+        } else {
+            change > tolerance_pct
+        }
+    }
+}
+
+/// A recorded metric value and its direction
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BaselineValue {
+    /// Measured value, given in JSON as a number or a byte size such as `"1.5GiB"`
+    #[serde(deserialize_with = "deserialize_value")]
+    pub value: f64,
+    /// Whether larger values are improvements
+    pub higher_is_better: bool,
+}
+
+/// A value given as a number or as a byte size string
+fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Number(f64),
+        Text(String),
+    }
+    match Value::deserialize(deserializer)? {
+        Value::Number(value) => Ok(value),
+        Value::Text(text) => format::parse_bytes(&text)
+            .map(|bytes| bytes as f64)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// Metric values recorded on one machine, saved between runs
+///
+/// # Example
+/// ```rust,ignore
+/// let current = Baseline::capture()
+///     .with_higher_is_better("ingest MB/s", 39.5)
+///     .with_lower_is_better("p95 us", 102.0);
+/// let previous = Baseline::load(Path::new("baseline.json"))?;
+/// let report = RunReport::new("Nightly")
+///     .with_baseline_comparison(compare_baselines(&previous, &current));
+/// current.save(Path::new("baseline.json"))?;
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Machine and build the values were measured on
+    pub environment: EnvironmentInfo,
+    /// Values by metric name
+    pub values: BTreeMap<String, BaselineValue>,
+    /// Latency budgets by metric name, for [`TestMetrics::assert_budget`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub budgets: BTreeMap<String, LatencyBudget>,
+}
+
+impl Baseline {
+    /// Empty baseline for the current environment
+    pub fn capture() -> Self {
+        Self {
+            environment: EnvironmentInfo::capture(),
+            values: BTreeMap::new(),
+            budgets: BTreeMap::new(),
+        }
+    }
+
+    /// Store a latency budget for `metric`
+    pub fn with_budget(mut self, metric: &str, budget: LatencyBudget) -> Self {
+        self.budgets.insert(metric.to_string(), budget);
+        self
+    }
+
+    /// Budget stored for `metric`, if any
+    pub fn budget(&self, metric: &str) -> Option<&LatencyBudget> {
+        self.budgets.get(metric)
+    }
+
+    /// Record a metric where smaller is better, such as latency
+    pub fn with_lower_is_better(mut self, metric: &str, value: f64) -> Self {
+        self.values.insert(
+            metric.to_string(),
+            BaselineValue {
+                value,
+                higher_is_better: false,
+            },
+        );
+        self
+    }
+
+    /// Record a metric where larger is better, such as throughput
+    pub fn with_higher_is_better(mut self, metric: &str, value: f64) -> Self {
+        self.values.insert(
+            metric.to_string(),
+            BaselineValue {
+                value,
+                higher_is_better: true,
+            },
+        );
+        self
+    }
+
+    /// Save baseline as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+    }
+
+    /// Load baseline from JSON
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+/// Result of [`compare_baselines`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaselineComparison {
+    /// One diff per metric present in both baselines
+    pub diffs: Vec<BaselineDiff>,
+    /// Hardware differences between the two environments
+    pub hardware_warnings: Vec<String>,
+}
+
+/// Compare `current` against `baseline`
+///
+/// Metrics missing from either side are skipped; the direction of each
+/// metric is taken from `baseline`. Hardware differences are returned as
+/// warnings because they make the numbers hard to compare.
+pub fn compare_baselines(baseline: &Baseline, current: &Baseline) -> BaselineComparison {
+    let diffs = baseline
+        .values
+        .iter()
+        .filter_map(|(metric, old)| {
+            let new = current.values.get(metric)?;
+            Some(BaselineDiff {
+                metric: metric.clone(),
+                baseline: old.value,
+                current: new.value,
+                higher_is_better: old.higher_is_better,
+            })
+        })
+        .collect();
+    BaselineComparison {
+        diffs,
+        hardware_warnings: baseline
+            .environment
+            .hardware_differences(&current.environment),
+    }
+}
+
+/// Aggregated summary of a test run, renderable as Markdown or HTML
+///
+/// # Example
+/// ```rust,ignore
+/// let report = RunReport::new("Nightly scale test")
+///     .with_metrics(ingest_metrics)
+///     .with_integrity("extraction", integrity)
+///     .with_baseline(BaselineDiff::higher_is_better("ingest MB/s", 42.0, 39.5))
+///     .with_sections([ReportSection::Baselines, ReportSection::Timings]);
+/// std::fs::write("report.md", report.to_markdown())?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct RunReport {
+    title: String,
+    metrics: Vec<TestMetrics>,
+    integrity: Vec<(String, IntegrityReport)>,
+    capacity: Vec<CapacityReport>,
+    calibration: Vec<ThresholdCalibration>,
+    baselines: Vec<BaselineDiff>,
+    comparisons: Vec<(String, ComparisonResult)>,
+    density: Vec<(String, DensitySummary)>,
+    index_usage: Vec<(String, IndexUsageSummary)>,
+    hardware_warnings: Vec<String>,
+    environment: EnvironmentInfo,
+    sections: Vec<ReportSection>,
+    regression_tolerance_pct: f64,
+}
+
+impl RunReport {
+    /// Empty report with all sections in default order and a 5% tolerance
+    ///
+    /// The current environment is captured here.
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            metrics: Vec::new(),
+            integrity: Vec::new(),
+            capacity: Vec::new(),
+            calibration: Vec::new(),
+            baselines: Vec::new(),
+            comparisons: Vec::new(),
+            density: Vec::new(),
+            index_usage: Vec::new(),
+            hardware_warnings: Vec::new(),
+            environment: EnvironmentInfo::capture(),
+            sections: ReportSection::ALL.to_vec(),
+            regression_tolerance_pct: 5.0,
+        }
+    }
+
+    /// Add a row to the timing table
+    pub fn with_metrics(mut self, metrics: TestMetrics) -> Self {
+        self.metrics.push(metrics);
+        self
+    }
+
+    /// Add a named integrity result
+    pub fn with_integrity(mut self, name: &str, report: IntegrityReport) -> Self {
+        self.integrity.push((name.to_string(), report));
+        self
+    }
+
+    /// Add a capacity probe result
+    pub fn with_capacity(mut self, report: CapacityReport) -> Self {
+        self.capacity.push(report);
+        self
+    }
+
+    /// Add a similarity threshold calibration
+    pub fn with_calibration(mut self, calibration: ThresholdCalibration) -> Self {
+        self.calibration.push(calibration);
+        self
+    }
+
+    /// Add a baseline comparison
+    pub fn with_baseline(mut self, diff: BaselineDiff) -> Self {
+        self.baselines.push(diff);
+        self
+    }
+
+    /// Add the diffs and hardware warnings of a [`compare_baselines`] result
+    pub fn with_baseline_comparison(mut self, comparison: BaselineComparison) -> Self {
+        self.baselines.extend(comparison.diffs);
+        self.hardware_warnings.extend(comparison.hardware_warnings);
+        self
+    }
+
+    /// Add a named significance test, e.g. from [`TestMetrics::compare_with`]
+    pub fn with_comparison(mut self, name: &str, result: ComparisonResult) -> Self {
+        self.comparisons.push((name.to_string(), result));
+        self
+    }
+
+    /// Add a named density summary
+    pub fn with_density(mut self, name: &str, stats: &DensityStats) -> Self {
+        self.density.push((name.to_string(), stats.summarize()));
+        self
+    }
+
+    /// Add a named index usage summary
+    pub fn with_index_usage(mut self, name: &str, usage: &IndexUsage) -> Self {
+        self.index_usage.push((name.to_string(), usage.summarize()));
+        self
+    }
+
+    /// Replace the captured environment, e.g. with one loaded from disk
+    pub fn with_environment(mut self, environment: EnvironmentInfo) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Environment the report describes
+    pub fn environment(&self) -> &EnvironmentInfo {
+        &self.environment
+    }
+
+    /// Render only `sections`, in the given order
+    pub fn with_sections(mut self, sections: impl IntoIterator<Item = ReportSection>) -> Self {
+        self.sections = sections.into_iter().collect();
+        self
+    }
+
+    /// Flag baseline changes worse than `pct` percent as regressions
+    pub fn with_regression_tolerance(mut self, pct: f64) -> Self {
+        self.regression_tolerance_pct = pct;
+        self
+    }
+
+    /// Baseline comparisons that count as regressions
+    pub fn regressions(&self) -> Vec<&BaselineDiff> {
+        self.baselines
+            .iter()
+            .filter(|d| d.is_regression(self.regression_tolerance_pct))
+            .collect()
+    }
+
+    /// Render as GitHub-flavoured Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# {}\n\nEnvironment: {}\n",
+            self.title,
+            self.environment.summary()
+        );
+        for table in self.tables() {
+            out.push_str(&format!("\n## {}\n\n", table.section.title()));
+            if let Some(caption) = &table.caption {
+                out.push_str(caption);
+                out.push_str("\n\n");
+            }
+            out.push_str(&format!("| {} |\n", table.headers.join(" | ")));
+            let align: Vec<&str> = (0..table.headers.len())
+                .map(|i| if i == 0 { "---" } else { "---:" })
+                .collect();
+            out.push_str(&format!("| {} |\n", align.join(" | ")));
+            for row in &table.rows {
+                let cells: Vec<String> = row
+                    .cells
+                    .iter()
+                    .map(|c| {
+                        let c = c.replace('|', "\\|");
+                        if row.highlight {
+                            format!("**{}**", c)
+                        } else {
+                            c
+                        }
+                    })
+                    .collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+        }
+        out
+    }
+
+    /// Render as a standalone HTML page with inline styling
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title);
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>Environment: {}</p>\n",
+            escape_html(&self.environment.summary())
+        );
+        for table in self.tables() {
+            out.push_str(&format!("<h2>{}</h2>\n", table.section.title()));
+            if let Some(caption) = &table.caption {
+                out.push_str(&format!("<p>{}</p>\n", escape_html(caption)));
+            }
+            out.push_str("<table>\n<thead><tr>");
+            for header in &table.headers {
+                out.push_str(&format!("<th>{}</th>", escape_html(header)));
+            }
+            out.push_str("</tr></thead>\n<tbody>\n");
+            for row in &table.rows {
+                out.push_str(if row.highlight {
+                    "<tr class=\"bad\">"
+                } else {
+                    "<tr>"
+                });
+                for cell in &row.cells {
+                    out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</tbody>\n</table>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Tables for the configured sections that have data
+    fn tables(&self) -> Vec<Table> {
+        let mut tables = Vec::new();
+        for &section in &self.sections {
+            match section {
+                ReportSection::Timings if !self.metrics.is_empty() => {
+                    tables.push(self.timings_table())
+                }
+                ReportSection::Integrity if !self.integrity.is_empty() => {
+                    tables.push(self.integrity_table())
+                }
+                ReportSection::Capacity => {
+                    tables.extend(self.capacity.iter().map(capacity_table));
+                }
+                ReportSection::Calibration => {
+                    tables.extend(self.calibration.iter().map(calibration_table));
+                }
+                ReportSection::Baselines if !self.baselines.is_empty() => {
+                    tables.push(self.baselines_table())
+                }
+                ReportSection::Comparisons if !self.comparisons.is_empty() => {
+                    tables.push(self.comparisons_table())
+                }
+                ReportSection::Density if !self.density.is_empty() => {
+                    tables.push(self.density_table())
+                }
+                ReportSection::IndexUsage if !self.index_usage.is_empty() => {
+                    tables.push(self.index_usage_table())
+                }
+                _ => {}
+            }
+        }
+        tables
+    }
+
+    fn timings_table(&self) -> Table {
+        let rows = self
+            .metrics
+            .iter()
+            .map(|m| {
+                let stats = m.timing_stats();
+                let throughput = if stats.total_ns > 0 {
+                    stats.count as f64 / (stats.total_ns as f64 / 1e9)
+                } else {
+                    0.0
+                };
+                Row {
+                    cells: vec![
+                        m.name.clone(),
+                        format::count(stats.count as u64),
+                        format::duration_ns(stats.mean_ns.round() as u64),
+                        format::duration_ns(stats.p95_ns),
+                        format!("{:.1}", throughput),
+                    ],
+                    highlight: m.error_count > 0,
+                }
+            })
+            .collect();
+        Table {
+            section: ReportSection::Timings,
+            caption: None,
+            headers: vec!["Operation", "Count", "Mean", "p95", "Throughput (ops/s)"],
+            rows,
+        }
+    }
+
+    fn integrity_table(&self) -> Table {
+        let rows = self
+            .integrity
+            .iter()
+            .map(|(name, report)| Row {
+                cells: vec![
+                    name.clone(),
+                    report.checks_passed.to_string(),
+                    report.checks_total.to_string(),
+                    format!("{:.1}%", report.pass_rate()),
+                    if report.is_ok() { "pass" } else { "FAIL" }.to_string(),
+                ],
+                highlight: !report.is_ok(),
+            })
+            .collect();
+        Table {
+            section: ReportSection::Integrity,
+            caption: None,
+            headers: vec!["Check", "Passed", "Total", "Pass rate", "Status"],
+            rows,
+        }
+    }
+
+    fn baselines_table(&self) -> Table {
+        let rows = self
+            .baselines
+            .iter()
+            .map(|d| {
+                let regression = d.is_regression(self.regression_tolerance_pct);
+                Row {
+                    cells: vec![
+                        d.metric.clone(),
+                        format!("{:.3}", d.baseline),
+                        format!("{:.3}", d.current),
+                        format!("{:+.1}%", d.change_pct()),
+                        if regression { "REGRESSION" } else { "ok" }.to_string(),
+                    ],
+                    highlight: regression,
+                }
+            })
+            .collect();
+        let mut caption = format!(
+            "Regression tolerance: {:.1}%",
+            self.regression_tolerance_pct
+        );
+        for warning in &self.hardware_warnings {
+            caption.push_str(&format!(
+                "\nWarning: hardware differs from baseline ({})",
+                warning
+            ));
+        }
+        Table {
+            section: ReportSection::Baselines,
+            caption: Some(caption),
+            headers: vec!["Metric", "Baseline", "Current", "Change", "Status"],
+            rows,
+        }
+    }
+
+    fn comparisons_table(&self) -> Table {
+        let rows = self
+            .comparisons
+            .iter()
+            .map(|(name, c)| Row {
+                cells: vec![
+                    name.clone(),
+                    format!("{}/{}", c.a_count, c.b_count),
+                    format!("{:.3}", c.median_ratio),
+                    format!("{:.4}", c.p_value),
+                    format!("{}", c.alpha),
+                    c.verdict.to_string(),
+                ],
+                highlight: c.verdict == Verdict::SignificantlySlower,
+            })
+            .collect();
+        Table {
+            section: ReportSection::Comparisons,
+            caption: Some(
+                "Two-sided Mann-Whitney U test; median ratio below 1 is faster".to_string(),
+            ),
+            headers: vec![
+                "Operation",
+                "Samples",
+                "Median ratio",
+                "p-value",
+                "Alpha",
+                "Verdict",
+            ],
+            rows,
+        }
+    }
+
+    fn density_table(&self) -> Table {
+        let density = |d: Option<f64>| d.map_or("-".to_string(), |d| format!("{:.4}", d));
+        let rows = self
+            .density
+            .iter()
+            .map(|(name, s)| Row {
+                cells: vec![
+                    name.clone(),
+                    s.count.to_string(),
+                    s.min_nnz.to_string(),
+                    format!("{:.1}", s.mean_nnz),
+                    s.max_nnz.to_string(),
+                    density(s.mean_density),
+                    format!("{:.1}%", s.pos_fraction * 100.0),
+                ],
+                highlight: false,
+            })
+            .collect();
+        Table {
+            section: ReportSection::Density,
+            caption: None,
+            headers: vec![
+                "Corpus",
+                "Vectors",
+                "Min nnz",
+                "Mean nnz",
+                "Max nnz",
+                "Mean density",
+                "Positive",
+            ],
+            rows,
+        }
+    }
+
+    fn index_usage_table(&self) -> Table {
+        let extreme = |e: Option<(usize, u64)>| {
+            e.map_or("-".to_string(), |(index, hits)| {
+                format!("{} (#{})", hits, index)
+            })
+        };
+        let rows = self
+            .index_usage
+            .iter()
+            .map(|(name, s)| Row {
+                cells: vec![
+                    name.clone(),
+                    s.vectors.to_string(),
+                    format!("{}/{}", s.used_indices, s.dims),
+                    extreme(s.max),
+                    extreme(s.min),
+                    format!("{:.3}", s.gini),
+                ],
+                highlight: s.out_of_range > 0,
+            })
+            .collect();
+        Table {
+            section: ReportSection::IndexUsage,
+            caption: Some(
+                "Gini 0 is perfectly even; highlighted rows saw indices beyond dims".to_string(),
+            ),
+            headers: vec![
+                "Corpus",
+                "Vectors",
+                "Used indices",
+                "Max hits",
+                "Min hits",
+                "Gini",
+            ],
+            rows,
+        }
+    }
+}
+
+/// Table for one capacity probe
+fn capacity_table(report: &CapacityReport) -> Table {
+    Table {
+        section: ReportSection::Capacity,
+        caption: Some(format!(
+            "dims={}, sparsity={}, trials={}",
+            report.dims, report.sparsity, report.trials
+        )),
+        headers: vec!["k", "Accuracy", "Member sim", "Max distractor sim"],
+        rows: report
+            .points
+            .iter()
+            .map(|p| Row {
+                cells: vec![
+                    p.k.to_string(),
+                    format!("{:.3}", p.accuracy),
+                    format!("{:.4}", p.mean_member_similarity),
+                    format!("{:.4}", p.mean_max_distractor_similarity),
+                ],
+                highlight: false,
+            })
+            .collect(),
+    }
+}
+
+/// Table for one threshold calibration; rows missing the targets are highlighted
+fn calibration_table(calibration: &ThresholdCalibration) -> Table {
+    Table {
+        section: ReportSection::Calibration,
+        caption: Some(format!(
+            "dims={}, sparsity={}, trials={}, unrelated mean cosine {:.4}, target FPR {} / FNR {}",
+            calibration.dims,
+            calibration.sparsity,
+            calibration.trials,
+            calibration.mean_unrelated_cosine(),
+            calibration.target_fpr,
+            calibration.target_fnr
+        )),
+        headers: vec!["Noise", "Copy mean", "Copy min", "Threshold", "FPR", "FNR"],
+        rows: calibration
+            .levels
+            .iter()
+            .map(|level| Row {
+                cells: vec![
+                    format!("{:.0}%", level.noise * 100.0),
+                    format!("{:.4}", level.mean_copy_cosine),
+                    format!("{:.4}", level.min_copy_cosine),
+                    format!("{:.4}", level.threshold),
+                    format!("{:.3}", level.false_positive_rate),
+                    format!("{:.3}", level.false_negative_rate),
+                ],
+                highlight: !level.feasible,
+            })
+            .collect(),
+    }
+}
+
+/// Format-independent table, rendered by `to_markdown` and `to_html`
+struct Table {
+    section: ReportSection,
+    caption: Option<String>,
+    headers: Vec<&'static str>,
+    rows: Vec<Row>,
+}
+
+struct Row {
+    cells: Vec<String>,
+    /// Failing or regressed row, rendered bold / red
+    highlight: bool,
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+th,td{border:1px solid #ccc;padding:4px 10px;text-align:right}\
+th:first-child,td:first-child{text-align:left}\
+th{background:#f0f0f0}\
+tr.bad td{background:#fde8e8;color:#a00;font-weight:bold}";
+
+/// Escape text for HTML element content and attribute values
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::CapacityPoint;
+
+    fn sample_report() -> RunReport {
+        let mut ingest = TestMetrics::new("ingest");
+        ingest.timings_ns.extend([1_000_000, 2_000_000, 3_000_000]);
+        let mut integrity = IntegrityReport::new();
+        integrity.pass();
+        integrity.fail("bad <chunk>".to_string());
+        let capacity = CapacityReport {
+            dims: 10_000,
+            sparsity: 200,
+            trials: 4,
+            points: vec![CapacityPoint {
+                k: 1,
+                accuracy: 1.0,
+                mean_member_similarity: 1.0,
+                mean_max_distractor_similarity: 0.05,
+            }],
+        };
+
+        RunReport::new("Nightly <scale> run")
+            .with_metrics(ingest)
+            .with_integrity("extraction", integrity)
+            .with_capacity(capacity)
+            .with_baseline(BaselineDiff::higher_is_better("ingest MB/s", 40.0, 30.0))
+            .with_baseline(BaselineDiff::lower_is_better("p95 us", 100.0, 102.0))
+    }
+
+    #[test]
+    fn test_markdown_tables() {
+        let md = sample_report().to_markdown();
+        assert!(md.starts_with("# Nightly <scale> run\n\nEnvironment: "));
+        assert!(md.contains("| ingest | 3 | 2.00 ms | 3.00 ms | 500.0 |"));
+        assert!(md.contains("| **extraction** | **1** | **2** | **50.0%** | **FAIL** |"));
+        assert!(md.contains("| 1 | 1.000 | 1.0000 | 0.0500 |"));
+        assert!(md.contains(
+            "| **ingest MB/s** | **40.000** | **30.000** | **-25.0%** | **REGRESSION** |"
+        ));
+        assert!(md.contains("| p95 us | 100.000 | 102.000 | +2.0% | ok |"));
+
+        let positions: Vec<usize> = ["## Timings", "## Integrity", "## Capacity", "## Baselines"]
+            .iter()
+            .map(|h| md.find(h).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_section_selection_and_order() {
+        let md = sample_report()
+            .with_sections([ReportSection::Baselines, ReportSection::Timings])
+            .to_markdown();
+        assert!(md.find("## Baselines").unwrap() < md.find("## Timings").unwrap());
+        assert!(!md.contains("## Integrity"));
+        assert!(!md.contains("## Capacity"));
+
+        let empty = RunReport::new("empty").to_markdown();
+        assert!(empty.starts_with("# empty\n\nEnvironment: "));
+        assert!(!empty.contains("##"));
+        assert_eq!(sample_report().regressions().len(), 1);
+    }
+
+    #[test]
+    fn test_compare_baselines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        let previous = Baseline::capture()
+            .with_higher_is_better("ingest MB/s", 40.0)
+            .with_lower_is_better("p95 us", 100.0)
+            .with_lower_is_better("dropped", 1.0);
+        previous.save(&path).unwrap();
+        let previous = Baseline::load(&path).unwrap();
+
+        let mut current = Baseline::capture()
+            .with_higher_is_better("ingest MB/s", 30.0)
+            .with_lower_is_better("p95 us", 90.0);
+        let same_machine = compare_baselines(&previous, &current);
+        assert_eq!(same_machine.diffs.len(), 2);
+        assert!(same_machine.hardware_warnings.is_empty());
+
+        current.environment.target_triple = "riscv64gc-unknown-linux-gnu".to_string();
+        let comparison = compare_baselines(&previous, &current);
+        assert_eq!(comparison.hardware_warnings.len(), 1);
+
+        let report = RunReport::new("cmp").with_baseline_comparison(comparison);
+        assert_eq!(report.regressions().len(), 1);
+        let md = report.to_markdown();
+        assert!(md.contains("Warning: hardware differs from baseline (Target: "));
+        assert!(md.contains(&format!("Environment: {}", report.environment().summary())));
+    }
+
+    #[test]
+    fn test_baseline_value_accepts_byte_sizes() {
+        let value: BaselineValue =
+            serde_json::from_str(r#"{"value": "1.5GiB", "higher_is_better": false}"#).unwrap();
+        assert_eq!(value.value, 1_610_612_736.0);
+        let value: BaselineValue =
+            serde_json::from_str(r#"{"value": 39.5, "higher_is_better": true}"#).unwrap();
+        assert_eq!(value.value, 39.5);
+        assert!(serde_json::from_str::<BaselineValue>(
+            r#"{"value": "fast", "higher_is_better": true}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_comparison_section() {
+        let mut baseline = TestMetrics::new("encode");
+        baseline.timings_ns.extend((0..30).map(|i| 1_000 + i));
+        let mut candidate = TestMetrics::new("encode");
+        candidate.timings_ns.extend((0..30).map(|i| 2_000 + i));
+
+        let result = candidate.compare_with(&baseline);
+        assert_eq!(result.verdict, Verdict::SignificantlySlower);
+        let md = RunReport::new("cmp")
+            .with_comparison("encode", result)
+            .with_comparison("decode", baseline.compare_with(&baseline))
+            .to_markdown();
+        assert!(md.contains("## Comparisons"));
+        assert!(md.contains("| **encode** | **30/30** |"), "{}", md);
+        assert!(md.contains("| no significant difference |"));
+    }
+
+    #[test]
+    fn test_density_section() {
+        let mut stats = DensityStats::new().with_dims(100);
+        stats.observe(&crate::generators::special_vectors::alternating(20));
+        let md = RunReport::new("density")
+            .with_density("docs", &stats)
+            .to_markdown();
+        assert!(md.contains("## Density"));
+        assert!(
+            md.contains("| docs | 1 | 20 | 20.0 | 20 | 0.2000 | 50.0% |"),
+            "{}",
+            md
+        );
+    }
+
+    #[test]
+    fn test_index_usage_section() {
+        let mut usage = IndexUsage::new(4);
+        usage.observe(&crate::generators::special_vectors::alternating(3));
+        usage.observe(&crate::generators::special_vectors::alternating(1));
+        let md = RunReport::new("usage")
+            .with_index_usage("docs", &usage)
+            .to_markdown();
+        assert!(md.contains("## Index usage"));
+        assert!(
+            md.contains("| docs | 2 | 3/4 | 2 (#0) | 0 (#3) | 0.375 |"),
+            "{}",
+            md
+        );
+    }
+
+    #[test]
+    fn test_html_is_balanced() {
+        const VOID: [&str; 1] = ["meta"];
+        let html = sample_report().to_html();
+        assert!(html.contains("Nightly &lt;scale&gt; run"));
+        assert!(html.contains("<tr class=\"bad\">"));
+        assert!(!html.contains("<scale>"));
+
+        let mut stack: Vec<String> = Vec::new();
+        let mut rest = html.as_str();
+        while let Some(start) = rest.find('<') {
+            let end = rest[start..].find('>').unwrap() + start;
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            if tag.starts_with('!') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(stack.pop().as_deref(), Some(name), "unbalanced </{}>", name);
+            } else {
+                let name = tag.split_whitespace().next().unwrap();
+                if !VOID.contains(&name) {
+                    stack.push(name.to_string());
+                }
+            }
+        }
+        assert!(stack.is_empty(), "unclosed tags: {:?}", stack);
+    }
+}
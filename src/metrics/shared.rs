@@ -0,0 +1,244 @@
+//! Thread-safe metrics handle for concurrent tests
+
+use super::TestMetrics;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+/// Source of per-handle ids used to key thread-local timing state
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Pending `start_timing` instants of this thread, by handle id
+    static STARTS: RefCell<HashMap<u64, Instant>> = RefCell::new(HashMap::new());
+}
+
+/// Cheap-to-clone, internally synchronized counterpart of [`TestMetrics`]
+///
+/// All methods take `&self`, so clones can be moved into worker threads
+/// without wrapping in `Arc<Mutex<_>>`. Operation and error counts are
+/// atomics; only sample vectors and custom metrics sit behind a mutex.
+///
+/// `start_timing`/`stop_timing` keep their start instant per thread: a stop
+/// pairs with the last start made *on the same thread* through any clone of
+/// this handle, and a stop without a matching start records nothing. Other
+/// threads timing concurrently on the same handle do not interfere.
+///
+/// # Example
+/// ```rust,ignore
+/// let metrics = SharedMetrics::new("bundle");
+/// (0..8).into_par_iter().for_each(|_| {
+///     metrics.time_operation(|| a.bundle(&b));
+///     metrics.inc_op("bundle");
+/// });
+/// println!("{}", metrics.snapshot().summary());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SharedMetrics {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    id: u64,
+    name: String,
+    timings_ns: Mutex<Vec<u64>>,
+    op_counts: RwLock<HashMap<String, AtomicU64>>,
+    custom_metrics: Mutex<HashMap<String, f64>>,
+    memory_samples: Mutex<Vec<usize>>,
+    error_count: AtomicU64,
+    warning_count: AtomicU64,
+}
+
+impl SharedMetrics {
+    /// Create a shared collector for named operation
+    pub fn new(name: &str) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+                name: name.to_string(),
+                timings_ns: Mutex::new(Vec::new()),
+                op_counts: RwLock::new(HashMap::new()),
+                custom_metrics: Mutex::new(HashMap::new()),
+                memory_samples: Mutex::new(Vec::new()),
+                error_count: AtomicU64::new(0),
+                warning_count: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Start timing measurement on the calling thread
+    #[inline]
+    pub fn start_timing(&self) {
+        STARTS.with(|starts| {
+            starts.borrow_mut().insert(self.inner.id, Instant::now());
+        });
+    }
+
+    /// Stop the calling thread's timing and record the sample
+    #[inline]
+    pub fn stop_timing(&self) {
+        let start = STARTS.with(|starts| starts.borrow_mut().remove(&self.inner.id));
+        if let Some(start) = start {
+            self.record_sample(start.elapsed().as_nanos() as u64);
+        }
+    }
+
+    /// Record a timed operation with closure
+    ///
+    /// Independent of `start_timing`/`stop_timing`, so it may be nested
+    /// inside a pending measurement.
+    #[inline]
+    pub fn time_operation<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let start = Instant::now();
+        let result = f();
+        self.record_sample(start.elapsed().as_nanos() as u64);
+        result
+    }
+
+    /// Increment operation counter
+    #[inline]
+    pub fn inc_op(&self, category: &str) {
+        {
+            let counts = self.inner.op_counts.read().unwrap();
+            if let Some(count) = counts.get(category) {
+                count.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+        // First use of this category: another thread may have inserted it
+        // between dropping the read lock and taking the write lock
+        self.inner
+            .op_counts
+            .write()
+            .unwrap()
+            .entry(category.to_string())
+            .or_insert_with(|| AtomicU64::new(0))
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record custom metric
+    #[inline]
+    pub fn record_metric(&self, name: &str, value: f64) {
+        self.inner
+            .custom_metrics
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), value);
+    }
+
+    /// Record memory usage
+    #[inline]
+    pub fn record_memory(&self, bytes: usize) {
+        self.inner.memory_samples.lock().unwrap().push(bytes);
+    }
+
+    /// Record an error
+    #[inline]
+    pub fn record_error(&self) {
+        self.inner.error_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a warning
+    #[inline]
+    pub fn record_warning(&self) {
+        self.inner.warning_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Copy the collected data into a plain [`TestMetrics`] for reporting
+    ///
+    /// Each field is copied atomically, but fields are read one after
+    /// another: work completing during the snapshot may show up in some
+    /// fields and not others. Snapshots taken after all workers have
+    /// finished (e.g. after joining their threads) are exact.
+    pub fn snapshot(&self) -> TestMetrics {
+        let mut metrics = TestMetrics::new(&self.inner.name);
+        metrics.timings_ns = self.inner.timings_ns.lock().unwrap().clone();
+        metrics.op_counts = self
+            .inner
+            .op_counts
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(category, count)| (category.clone(), count.load(Ordering::Relaxed)))
+            .collect();
+        metrics.custom_metrics = self.inner.custom_metrics.lock().unwrap().clone();
+        metrics.memory_samples = self.inner.memory_samples.lock().unwrap().clone();
+        metrics.error_count = self.inner.error_count.load(Ordering::Relaxed);
+        metrics.warning_count = self.inner.warning_count.load(Ordering::Relaxed);
+        metrics
+    }
+
+    fn record_sample(&self, ns: u64) {
+        self.inner.timings_ns.lock().unwrap().push(ns);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_totals() {
+        const THREADS: usize = 16;
+        const ITERS: usize = 500;
+        let metrics = SharedMetrics::new("shared");
+
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let metrics = metrics.clone();
+                thread::spawn(move || {
+                    for i in 0..ITERS {
+                        metrics.start_timing();
+                        metrics.time_operation(|| metrics.inc_op("inner"));
+                        metrics.stop_timing();
+                        metrics.inc_op(if i % 2 == 0 { "even" } else { "odd" });
+                        if i % 10 == 0 {
+                            metrics.record_error();
+                        }
+                        metrics.record_metric(&format!("thread_{}", t), i as f64);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.name, "shared");
+        assert_eq!(snapshot.timings_ns.len(), THREADS * ITERS * 2);
+        assert_eq!(snapshot.op_counts["inner"], (THREADS * ITERS) as u64);
+        assert_eq!(snapshot.op_counts["even"], (THREADS * ITERS / 2) as u64);
+        assert_eq!(snapshot.op_counts["odd"], (THREADS * ITERS / 2) as u64);
+        assert_eq!(snapshot.error_count, (THREADS * ITERS / 10) as u64);
+        assert_eq!(snapshot.custom_metrics.len(), THREADS);
+        assert_eq!(snapshot.custom_metrics["thread_0"], (ITERS - 1) as f64);
+    }
+
+    #[test]
+    fn test_timing_state_is_per_thread() {
+        let metrics = SharedMetrics::new("per-thread");
+        metrics.start_timing();
+
+        // A stop on another thread has no start to pair with
+        let other = metrics.clone();
+        thread::spawn(move || other.stop_timing()).join().unwrap();
+        assert!(metrics.snapshot().timings_ns.is_empty());
+
+        // Separate handles do not share timing state either
+        let unrelated = SharedMetrics::new("unrelated");
+        unrelated.stop_timing();
+        assert!(unrelated.snapshot().timings_ns.is_empty());
+
+        metrics.stop_timing();
+        metrics.stop_timing();
+        assert_eq!(metrics.snapshot().timings_ns.len(), 1);
+    }
+}
@@ -0,0 +1,185 @@
+//! Preflight check for CPU timing stability before benchmarking
+//!
+//! Frequency scaling, turbo and thermal throttling make the same work take
+//! different amounts of time, which inflates tail latencies. A short probe
+//! running a fixed workload shows whether the machine is quiet enough for
+//! the numbers to mean anything.
+
+use serde::{Deserialize, Serialize};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Arithmetic steps per probe iteration
+const WORKLOAD_STEPS: u64 = 20_000;
+
+/// Windows the iteration times are split into for trend detection
+const TREND_WINDOWS: usize = 4;
+
+/// Last-window slowdown over the first that counts as throttling
+const THROTTLE_SLOWDOWN: f64 = 1.05;
+
+/// Outcome of [`stability_probe`]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StabilityReport {
+    /// Wall-clock time spent probing
+    pub duration: Duration,
+    /// Workload iterations timed
+    pub iterations: usize,
+    /// Mean iteration time in nanoseconds
+    pub mean_ns: f64,
+    /// Standard deviation of iteration times in nanoseconds
+    pub stddev_ns: f64,
+    /// Coefficient of variation (`stddev_ns / mean_ns`)
+    pub cov: f64,
+    /// Mean of the last quarter of iterations over the first quarter
+    pub trend_ratio: f64,
+    /// Whether iteration times rose steadily across the probe
+    pub throttling_suspected: bool,
+    /// CPU frequency scaling governor (Linux), e.g. `performance`
+    pub governor: Option<String>,
+}
+
+impl StabilityReport {
+    /// True if `cov` is at most `threshold` and no throttling was seen
+    pub fn is_stable(&self, threshold: f64) -> bool {
+        self.cov <= threshold && !self.throttling_suspected
+    }
+
+    /// One-line description, e.g. for report headers
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "CoV {:.2}% over {} iterations, trend x{:.3}",
+            self.cov * 100.0,
+            self.iterations,
+            self.trend_ratio
+        );
+        if let Some(governor) = &self.governor {
+            out.push_str(&format!(", governor {}", governor));
+        }
+        if self.throttling_suspected {
+            out.push_str(", throttling suspected");
+        }
+        out
+    }
+}
+
+/// Time a fixed workload repeatedly for `duration`
+///
+/// Runs at least one iteration. Throttling is suspected when the mean
+/// iteration time rises in every quarter of the probe and the last quarter
+/// is more than 5% slower than the first.
+///
+/// # Example
+/// ```rust,ignore
+/// let stability = stability_probe(Duration::from_millis(500));
+/// if !stability.is_stable(0.05) {
+///     eprintln!("skipping benchmark: {}", stability.summary());
+///     return;
+/// }
+/// ```
+pub fn stability_probe(duration: Duration) -> StabilityReport {
+    let start = Instant::now();
+    let mut samples = Vec::new();
+    while samples.is_empty() || start.elapsed() < duration {
+        let iteration = Instant::now();
+        black_box(workload(black_box(samples.len() as u64)));
+        samples.push(iteration.elapsed().as_nanos() as f64);
+    }
+    let elapsed = start.elapsed();
+
+    let n = samples.len() as f64;
+    let mean_ns = samples.iter().sum::<f64>() / n;
+    let stddev_ns = (samples.iter().map(|s| (s - mean_ns).powi(2)).sum::<f64>() / n).sqrt();
+    let (trend_ratio, throttling_suspected) = trend(&samples);
+
+    StabilityReport {
+        duration: elapsed,
+        iterations: samples.len(),
+        mean_ns,
+        stddev_ns,
+        cov: if mean_ns > 0.0 {
+            stddev_ns / mean_ns
+        } else {
+            0.0
+        },
+        trend_ratio,
+        throttling_suspected,
+        governor: scaling_governor(),
+    }
+}
+
+/// Fixed integer work the compiler cannot fold away
+fn workload(seed: u64) -> u64 {
+    let mut state = seed;
+    for _ in 0..WORKLOAD_STEPS {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1)
+            .rotate_left(7);
+    }
+    state
+}
+
+/// Last-over-first window ratio and whether the windows rise steadily
+fn trend(samples: &[f64]) -> (f64, bool) {
+    if samples.len() < 2 * TREND_WINDOWS {
+        return (1.0, false);
+    }
+    let window = samples.len() / TREND_WINDOWS;
+    let means: Vec<f64> = samples
+        .chunks(window)
+        .take(TREND_WINDOWS)
+        .map(|w| w.iter().sum::<f64>() / w.len() as f64)
+        .collect();
+    let ratio = if means[0] > 0.0 {
+        means[TREND_WINDOWS - 1] / means[0]
+    } else {
+        1.0
+    };
+    let rising = means.windows(2).all(|pair| pair[1] > pair[0]);
+    (ratio, rising && ratio > THROTTLE_SLOWDOWN)
+}
+
+fn scaling_governor() -> Option<String> {
+    std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor")
+        .ok()
+        .map(|g| g.trim().to_string())
+        .filter(|g| !g.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_probe_populates_report() {
+        let report = stability_probe(Duration::from_millis(30));
+        assert!(report.iterations > 0);
+        assert!(report.duration >= Duration::from_millis(30));
+        assert!(report.mean_ns > 0.0);
+        assert!(report.cov.is_finite() && report.cov >= 0.0);
+        assert!(report.trend_ratio.is_finite());
+        assert!(report.summary().starts_with("CoV"));
+        assert_eq!(
+            report.is_stable(f64::INFINITY),
+            !report.throttling_suspected
+        );
+        assert!(!report.is_stable(-1.0));
+
+        let single = stability_probe(Duration::ZERO);
+        assert_eq!(single.iterations, 1);
+        assert_eq!(single.cov, 0.0);
+    }
+
+    #[test]
+    fn test_trend_detects_steady_slowdown() {
+        let rising: Vec<f64> = (0..40).map(|i| 100.0 + i as f64 * 2.0).collect();
+        let (ratio, throttled) = trend(&rising);
+        assert!(ratio > 1.5);
+        assert!(throttled);
+
+        let flat: Vec<f64> = (0..40).map(|i| 100.0 + (i % 3) as f64).collect();
+        assert!(!trend(&flat).1);
+        assert_eq!(trend(&[100.0, 200.0]), (1.0, false));
+    }
+}
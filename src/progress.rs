@@ -0,0 +1,224 @@
+//! Progress reporting hooks for long-running testkit operations
+//!
+//! Dataset generation and directory verification accept a [`ProgressSink`]
+//! through their options builders. Reports are throttled internally, so a
+//! sink sees at most one call per `min_interval` plus a final report once
+//! the operation completes.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default minimum time between two progress reports
+pub const DEFAULT_REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Receiver of progress updates
+pub trait ProgressSink: Send + Sync {
+    /// Report `done` units (bytes) out of `total` for the current `phase`
+    fn report(&self, done: u64, total: Option<u64>, phase: &str);
+}
+
+impl<F> ProgressSink for F
+where
+    F: Fn(u64, Option<u64>, &str) + Send + Sync,
+{
+    fn report(&self, done: u64, total: Option<u64>, phase: &str) {
+        self(done, total, phase)
+    }
+}
+
+/// Sink that discards all reports (the default)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopSink;
+
+impl ProgressSink for NoopSink {
+    fn report(&self, _done: u64, _total: Option<u64>, _phase: &str) {}
+}
+
+/// Progress sink backed by an `indicatif` progress bar
+#[cfg(feature = "progress-bars")]
+pub struct IndicatifSink {
+    bar: indicatif::ProgressBar,
+}
+
+#[cfg(feature = "progress-bars")]
+impl IndicatifSink {
+    /// Create a sink with a byte-oriented progress bar
+    pub fn new() -> Self {
+        let bar = indicatif::ProgressBar::new(0);
+        bar.set_style(
+            indicatif::ProgressStyle::default_bar()
+                .template("{msg} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        Self { bar }
+    }
+
+    /// Wrap an existing progress bar
+    pub fn with_bar(bar: indicatif::ProgressBar) -> Self {
+        Self { bar }
+    }
+
+    /// Underlying progress bar
+    pub fn bar(&self) -> &indicatif::ProgressBar {
+        &self.bar
+    }
+}
+
+#[cfg(feature = "progress-bars")]
+impl Default for IndicatifSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "progress-bars")]
+impl ProgressSink for IndicatifSink {
+    fn report(&self, done: u64, total: Option<u64>, phase: &str) {
+        if let Some(total) = total {
+            self.bar.set_length(total);
+        }
+        self.bar.set_message(phase.to_string());
+        self.bar.set_position(done);
+    }
+}
+
+/// Progress configuration shared by options builders
+#[derive(Clone)]
+pub struct ProgressOptions {
+    sink: Arc<dyn ProgressSink>,
+    min_interval: Duration,
+}
+
+impl ProgressOptions {
+    /// No-op progress with the default throttle interval
+    pub fn new() -> Self {
+        Self {
+            sink: Arc::new(NoopSink),
+            min_interval: DEFAULT_REPORT_INTERVAL,
+        }
+    }
+
+    /// Install a progress sink
+    pub fn with_sink(mut self, sink: impl ProgressSink + 'static) -> Self {
+        self.sink = Arc::new(sink);
+        self
+    }
+
+    /// Install a shared progress sink
+    pub fn with_shared_sink(mut self, sink: Arc<dyn ProgressSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    /// Set the minimum time between reports (`Duration::ZERO` reports every update)
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Start tracking a new phase
+    pub(crate) fn tracker(&self, total: Option<u64>, phase: &'static str) -> ProgressTracker<'_> {
+        ProgressTracker {
+            sink: self.sink.as_ref(),
+            total,
+            phase,
+            min_interval: self.min_interval,
+            done: AtomicU64::new(0),
+            state: Mutex::new(ReportState {
+                last_at: None,
+                last_done: None,
+            }),
+        }
+    }
+}
+
+impl Default for ProgressOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ProgressOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressOptions")
+            .field("min_interval", &self.min_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+struct ReportState {
+    last_at: Option<Instant>,
+    last_done: Option<u64>,
+}
+
+/// Throttled, thread-safe progress counter for a single phase
+///
+/// Reported `done` values are strictly increasing even when `advance` is
+/// called from several threads.
+pub(crate) struct ProgressTracker<'a> {
+    sink: &'a dyn ProgressSink,
+    total: Option<u64>,
+    phase: &'static str,
+    min_interval: Duration,
+    done: AtomicU64,
+    state: Mutex<ReportState>,
+}
+
+impl ProgressTracker<'_> {
+    /// Record `n` more units of work
+    pub(crate) fn advance(&self, n: u64) {
+        self.done.fetch_add(n, Ordering::Relaxed);
+        // Skip rather than block if another thread is reporting
+        if let Ok(mut state) = self.state.try_lock() {
+            let due = state
+                .last_at
+                .is_none_or(|at| at.elapsed() >= self.min_interval);
+            if due {
+                self.emit(&mut state);
+            }
+        }
+    }
+
+    /// Emit the final report
+    pub(crate) fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        self.emit(&mut state);
+    }
+
+    fn emit(&self, state: &mut ReportState) {
+        let done = self.done.load(Ordering::Relaxed);
+        if state.last_done.is_some_and(|last| done <= last) {
+            return;
+        }
+        self.sink.report(done, self.total, self.phase);
+        state.last_at = Some(Instant::now());
+        state.last_done = Some(done);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_throttles_and_finishes() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let sink_calls = calls.clone();
+        let options = ProgressOptions::new()
+            .with_sink(move |done, _total: Option<u64>, _phase: &str| {
+                sink_calls.lock().unwrap().push(done)
+            })
+            .with_min_interval(Duration::from_secs(3600));
+
+        let tracker = options.tracker(Some(1000), "test");
+        for _ in 0..1000 {
+            tracker.advance(1);
+        }
+        tracker.finish();
+
+        // First update plus the final one; everything in between is throttled
+        assert_eq!(*calls.lock().unwrap(), vec![1, 1000]);
+    }
+}
@@ -0,0 +1,39 @@
+//! End-to-end scale test integration
+//!
+//! Runs a small `ScaleTest` through every phase and checks the report.
+
+#![cfg(feature = "large-scale")]
+
+use embeddenator_testkit::harness::{ScalePhase, ScaleTest};
+use embeddenator_vsa::ReversibleVSAConfig;
+
+const MB: u64 = 1024 * 1024;
+
+/// Test: 20MB dataset through generate → ingest → extract → verify
+#[test]
+fn test_scale_test_20mb_end_to_end() {
+    let test = ScaleTest::new(20 * MB);
+    let estimate = test.dry_run();
+    assert_eq!(estimate.dataset_bytes, 20 * MB);
+    assert!(estimate.disk_bytes > 40 * MB);
+
+    let report = test.run(&ReversibleVSAConfig::default());
+    assert!(report.is_ok(), "{}", report.summary());
+    assert_eq!(report.dataset_bytes, 20 * MB);
+    assert_eq!(report.files, estimate.files);
+    assert!(!report.generation.is_truncated());
+
+    let phases: Vec<_> = report.phases.iter().map(|p| p.phase).collect();
+    assert_eq!(phases, ScalePhase::ALL.to_vec());
+    for phase in &report.phases {
+        assert_eq!(phase.bytes, 20 * MB);
+        assert!(phase.throughput_mbps > 0.0);
+        assert!(!phase.over_limit);
+        assert_eq!(report.metrics.phase_stats(phase.phase.name()).count, 1);
+    }
+
+    let integrity = report.integrity.as_ref().unwrap();
+    assert_eq!(integrity.checks_total, report.files as u64);
+    #[cfg(target_os = "linux")]
+    assert!(report.peak_rss.is_some());
+}